@@ -0,0 +1,35 @@
+//! Compare Ipopt configurations on the Hock-Schittkowski test problems.
+//!
+//! Run with `cargo run --release --example bench --features hs`. Linear solvers that are not
+//! available in the linked Ipopt build show up as configurations that solve no problems.
+
+use ipopt::hs::*;
+use ipopt::*;
+
+fn main() {
+    let configs = OptionGrid::new()
+        .axis(
+            "linear_solver",
+            vec!["mumps".into(), "ma27".into(), "ma57".into()],
+        )
+        .axis(
+            "nlp_scaling_method",
+            vec!["none".into(), "gradient-based".into()],
+        )
+        .axis("tol", vec![1e-6.into(), 1e-8.into()])
+        .configurations();
+
+    let mut base = OptionSet::new();
+    base.set("print_level", 0).set("sb", "yes");
+
+    let mut bench = Benchmark::new();
+    bench.base_options(base).configurations(configs);
+    for problem in HsProblem::all() {
+        bench.problem(problem.name(), problem);
+    }
+
+    match bench.run() {
+        Ok(report) => print!("{}", report),
+        Err(err) => eprintln!("{}", err),
+    }
+}
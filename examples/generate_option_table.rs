@@ -0,0 +1,48 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Generate `src/option_table.rs`, which backs the `option!` macro, from the options registered
+//! by the linked Ipopt library.
+//!
+//! Run with `cargo run --example generate_option_table > src/option_table.rs` after changing the
+//! Ipopt version the crate is built against.
+
+use ipopt::{OptionRegistry, OptionType};
+
+fn main() {
+    let registry = OptionRegistry::linked();
+
+    println!("// This file is generated by `cargo run --example generate_option_table`.");
+    println!("// Do not edit it by hand.");
+    println!();
+    println!("/// Build a typed option from the name of an option registered with Ipopt.");
+    println!("#[doc(hidden)]");
+    println!("#[macro_export]");
+    println!("macro_rules! __ipopt_option {{");
+    for info in registry.iter() {
+        let constructor = match info.option_type {
+            OptionType::Number => "__num_option",
+            OptionType::Integer => "__int_option",
+            OptionType::String => "__str_option",
+        };
+        // Write each arm as rustfmt would, so that formatting the crate leaves the table as is.
+        println!("    ({:?}, $value:expr) => {{", info.name);
+        println!("        $crate::{}({:?}, $value)", constructor, info.name);
+        println!("    }};");
+    }
+    println!("    ($name:tt, $value:expr) => {{");
+    println!("        compile_error!(concat!(\"unknown Ipopt option \", stringify!($name)))");
+    println!("    }};");
+    println!("}}");
+}
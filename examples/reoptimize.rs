@@ -0,0 +1,180 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! In this example we measure the speedup of re-solving a problem with the same structure.
+//!
+//! A large chain problem is solved for a sequence of target curves, once through `solve` and once
+//! through `reoptimize`, which reuses the structures and symbolic factorization set up by the
+//! previous solve. Run with `cargo run --release --example reoptimize`.
+
+use ipopt::*;
+use std::time::{Duration, Instant};
+
+/// Number of links in the chain.
+const N: usize = 20_000;
+/// Number of solves in each sequence.
+const SOLVES: usize = 10;
+
+/// Fit a chain of points to a target curve while keeping consecutive points in a disk:
+///
+/// min sum (x_i - t_i)^2 + sum (x_{i+1} - x_i)^4 s.t. x_i^2 + x_{i+1}^2 <= 2
+struct Chain {
+    /// Phase of the target curve, changed between solves.
+    phase: Number,
+}
+
+impl Chain {
+    fn target(&self, i: usize) -> Number {
+        1.5 * (i as Number * 1e-3 + self.phase).sin()
+    }
+}
+
+impl BasicProblem for Chain {
+    fn num_variables(&self) -> usize {
+        N
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.iter_mut().for_each(|x| *x = -1e20);
+        x_u.iter_mut().for_each(|x| *x = 1e20);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.iter_mut().for_each(|x| *x = 0.0);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = x
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| (x - self.target(i)).powi(2))
+            .sum::<Number>()
+            + x.windows(2).map(|w| (w[1] - w[0]).powi(4)).sum::<Number>();
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        for (i, g) in grad_f.iter_mut().enumerate() {
+            *g = 2.0 * (x[i] - self.target(i));
+        }
+        for i in 0..N - 1 {
+            let d = 4.0 * (x[i + 1] - x[i]).powi(3);
+            grad_f[i] -= d;
+            grad_f[i + 1] += d;
+        }
+        true
+    }
+}
+
+impl ConstrainedProblem for Chain {
+    fn num_constraints(&self) -> usize {
+        N - 1
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        2 * (N - 1)
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        for (i, g) in g.iter_mut().enumerate() {
+            *g = x[i] * x[i] + x[i + 1] * x[i + 1];
+        }
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l.iter_mut().for_each(|g| *g = -1e20);
+        g_u.iter_mut().for_each(|g| *g = 2.0);
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        for i in 0..N - 1 {
+            rows[2 * i] = i as Index;
+            cols[2 * i] = i as Index;
+            rows[2 * i + 1] = i as Index;
+            cols[2 * i + 1] = (i + 1) as Index;
+        }
+        true
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        for i in 0..N - 1 {
+            vals[2 * i] = 2.0 * x[i];
+            vals[2 * i + 1] = 2.0 * x[i + 1];
+        }
+        true
+    }
+
+    // The diagonal comes first, followed by the subdiagonal.
+    fn num_hessian_non_zeros(&self) -> usize {
+        2 * N - 1
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        for i in 0..N {
+            rows[i] = i as Index;
+            cols[i] = i as Index;
+        }
+        for i in 0..N - 1 {
+            rows[N + i] = (i + 1) as Index;
+            cols[N + i] = i as Index;
+        }
+        true
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[..N].iter_mut().for_each(|v| *v = 2.0 * obj_factor);
+        vals[N..].iter_mut().for_each(|v| *v = 0.0);
+        for i in 0..N - 1 {
+            let h = obj_factor * 12.0 * (x[i + 1] - x[i]).powi(2);
+            vals[i] += h + 2.0 * lambda[i];
+            vals[i + 1] += h + 2.0 * lambda[i];
+            vals[N + i] -= h;
+        }
+        true
+    }
+}
+
+/// Solve the sequence of problems and return the total time spent in all but the first solve,
+/// which is the same for both methods.
+fn run(reoptimize: bool) -> Duration {
+    let mut ipopt = Ipopt::new(Chain { phase: 0.0 }).unwrap();
+    ipopt.set_option("print_level", 0);
+    ipopt.set_option("sb", "yes");
+
+    let mut total = Duration::default();
+    for k in 0..SOLVES {
+        ipopt.solver_data_mut().problem.phase = k as Number * 0.1;
+        let start = Instant::now();
+        let status = if reoptimize {
+            ipopt.reoptimize().status
+        } else {
+            ipopt.solve().status
+        };
+        if k > 0 {
+            total += start.elapsed();
+        }
+        assert_eq!(status, SolveStatus::SolveSucceeded);
+    }
+    total
+}
+
+fn main() {
+    let solve = run(false);
+    let reoptimize = run(true);
+    println!("solve:      {:?}", solve);
+    println!("reoptimize: {:?}", reoptimize);
+    println!(
+        "speedup:    {:.2}x",
+        solve.as_secs_f64() / reoptimize.as_secs_f64()
+    );
+}
@@ -0,0 +1,195 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! In this example we count the Rust-side allocations of repeated solves.
+//!
+//! A small model predictive control problem is re-solved for a moving initial state, as an MPC
+//! loop would, and a counting global allocator records the allocations made by each solve. Once
+//! the first few solves are done, all buffers are reused and solves don't allocate, which
+//! `tests/solve_allocations.rs` checks. Allocations made by Ipopt itself go through the C++
+//! runtime and are not counted. Run with `cargo run --release --example solve_allocations`.
+
+use ipopt::*;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Length of the prediction horizon.
+const N: usize = 20;
+/// Number of warm-up solves excluded from the count.
+const WARM_UP: usize = 3;
+/// Number of counted solves.
+const SOLVES: usize = 100;
+
+/// Drive the scalar system `x_{k+1} = x_k + u_k` to the set point `p_0` from the initial state
+/// `p_1`:
+///
+/// min sum (x_k - p_0)^2 + 0.1 u_k^2 s.t. x_1 = p_1 + u_0, x_{k+1} = x_k + u_k, |u_k| <= 0.5
+///
+/// The variables are `x_1, ..., x_N` followed by `u_0, ..., u_{N-1}`.
+struct Mpc;
+
+impl ParametricProblem for Mpc {
+    fn num_parameters(&self) -> usize {
+        2
+    }
+    fn num_variables(&self) -> usize {
+        2 * N
+    }
+    fn bounds(&self, _p: &[Number], x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l[..N].iter_mut().for_each(|x| *x = -1e20);
+        x_u[..N].iter_mut().for_each(|x| *x = 1e20);
+        x_l[N..].iter_mut().for_each(|u| *u = -0.5);
+        x_u[N..].iter_mut().for_each(|u| *u = 0.5);
+        true
+    }
+    fn initial_point(&self, _p: &[Number], x: &mut [Number]) -> bool {
+        x.iter_mut().for_each(|x| *x = 0.0);
+        true
+    }
+    fn objective(&self, p: &[Number], x: &[Number], obj: &mut Number) -> bool {
+        let (x, u) = x.split_at(N);
+        *obj = x.iter().map(|&x| (x - p[0]) * (x - p[0])).sum::<Number>()
+            + u.iter().map(|&u| 0.1 * u * u).sum::<Number>();
+        true
+    }
+    fn objective_grad(&self, p: &[Number], x: &[Number], grad_f: &mut [Number]) -> bool {
+        for (g, &x) in grad_f[..N].iter_mut().zip(x[..N].iter()) {
+            *g = 2.0 * (x - p[0]);
+        }
+        for (g, &u) in grad_f[N..].iter_mut().zip(x[N..].iter()) {
+            *g = 0.2 * u;
+        }
+        true
+    }
+    fn num_constraints(&self) -> usize {
+        N
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        2 + 3 * (N - 1)
+    }
+    fn constraint(&self, p: &[Number], x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = x[0] - p[1] - x[N];
+        for k in 1..N {
+            g[k] = x[k] - x[k - 1] - x[N + k];
+        }
+        true
+    }
+    fn constraint_bounds(&self, _p: &[Number], g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l.iter_mut().for_each(|g| *g = 0.0);
+        g_u.iter_mut().for_each(|g| *g = 0.0);
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows[..2].copy_from_slice(&[0, 0]);
+        cols[..2].copy_from_slice(&[0, N as Index]);
+        for k in 1..N {
+            let i = 2 + 3 * (k - 1);
+            rows[i..i + 3].iter_mut().for_each(|r| *r = k as Index);
+            cols[i] = k as Index;
+            cols[i + 1] = (k - 1) as Index;
+            cols[i + 2] = (N + k) as Index;
+        }
+        true
+    }
+    fn constraint_jacobian_values(
+        &self,
+        _p: &[Number],
+        _x: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[..2].copy_from_slice(&[1.0, -1.0]);
+        for k in 1..N {
+            let i = 2 + 3 * (k - 1);
+            vals[i..i + 3].copy_from_slice(&[1.0, -1.0, -1.0]);
+        }
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        2 * N
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        for i in 0..2 * N {
+            rows[i] = i as Index;
+            cols[i] = i as Index;
+        }
+        true
+    }
+    fn hessian_values(
+        &self,
+        _p: &[Number],
+        _x: &[Number],
+        obj_factor: Number,
+        _lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[..N].iter_mut().for_each(|v| *v = 2.0 * obj_factor);
+        vals[N..].iter_mut().for_each(|v| *v = 0.2 * obj_factor);
+        true
+    }
+}
+
+fn main() {
+    let mut solver = ParametricSolver::new(Mpc, &[1.0, 0.0]).unwrap();
+    solver.ipopt().set_option("print_level", 0);
+    solver.ipopt().set_option("sb", "yes");
+
+    let mut params = [1.0, 0.0];
+    let mut allocations = 0;
+    let start = Instant::now();
+    for k in 0..WARM_UP + SOLVES {
+        solver.set_params(&params);
+        let before = ALLOCATIONS.load(Ordering::SeqCst);
+        let result = solver.resolve();
+        let after = ALLOCATIONS.load(Ordering::SeqCst);
+        assert_eq!(result.status, SolveStatus::SolveSucceeded);
+        // Apply the first control to move the initial state, as an MPC loop would.
+        params[1] += result.solver_data.solution.primal_variables[N];
+
+        if k >= WARM_UP {
+            allocations += after - before;
+        }
+    }
+
+    println!("solves:                {}", WARM_UP + SOLVES);
+    println!(
+        "time per solve:        {:?}",
+        start.elapsed() / (WARM_UP + SOLVES) as u32
+    );
+    println!(
+        "allocations per solve: {}",
+        allocations as f64 / SOLVES as f64
+    );
+}
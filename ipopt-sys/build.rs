@@ -61,6 +61,8 @@ const METIS_URL: &str = "https://github.com/coin-or-tools/ThirdParty-Metis/archi
 const METIS_MD5: &str = "1811597f87787dcf996c0ae41f4416c9";
 const METIS_SHA1: &str = "a2cc549be601bc78543e5cf5f21ee1438a66fd24";
 
+const COINBREW_URL: &str = "https://raw.githubusercontent.com/coin-or/coinbrew/master/coinbrew";
+
 #[cfg(target_os = "macos")]
 mod platform {
     // For some reason I couldn't build and link to Ipopt as a static lib on macos, so this is here.
@@ -124,6 +126,19 @@ fn init_logger() {
 
 fn main() {
     init_logger();
+    println!("cargo:rerun-if-env-changed=PARDISO_LIB");
+    println!("cargo:rerun-if-env-changed=IPOPT_COINBREW");
+    println!("cargo:rerun-if-env-changed=COINBREW");
+    println!("cargo:rerun-if-env-changed=COINHSL_DIR");
+
+    // Building with coinbrew is opt-in and replaces all other methods, since the point is to get
+    // Ipopt and its third party libraries from a single tree.
+    if coinbrew_enabled() {
+        let link_info = build_with_coinbrew().expect("Failed to build Ipopt with coinbrew.");
+        link(build_cnlp(&link_info.include_paths), link_info)
+            .expect("Failed to create bindings for Ipopt library.");
+        return;
+    }
 
     let mut msg = String::from("\n\n");
 
@@ -470,6 +485,31 @@ fn check_tarball_hashes(tarball_path: &Path, md5: &str, sha1: &str) -> Result<()
     Ok(())
 }
 
+// Find the AMPL Solver Library (ASL), which is needed to read .nl files when the `asl` feature is
+// enabled.
+fn find_asl() -> Result<LinkInfo, Error> {
+    let mut link_info = find_linux_lib("coinasl", "coin/ThirdParty/asl_pfgh.h")?;
+    // ASL headers are installed in a subdirectory of the include path.
+    link_info.include_paths = link_info
+        .include_paths
+        .iter()
+        .flat_map(|path| {
+            vec![
+                path.clone(),
+                path.join("coin").join("ThirdParty"),
+                path.join("coin-or").join("asl"),
+            ]
+        })
+        .collect();
+    Ok(link_info)
+}
+
+// Find sIpopt, which is needed to compute parametric sensitivities when the `sipopt` feature is
+// enabled.
+fn find_sipopt() -> Result<LinkInfo, Error> {
+    find_linux_lib("sipopt", "coin/SensApplication.hpp")
+}
+
 /// Build the CNLP interface.
 fn build_cnlp(ipopt_include_paths: &[PathBuf]) -> PathBuf {
     let mut ipopt_include_dirs = String::new();
@@ -477,9 +517,36 @@ fn build_cnlp(ipopt_include_paths: &[PathBuf]) -> PathBuf {
         ipopt_include_dirs.push_str(path.to_str().unwrap());
         ipopt_include_dirs.push(' ');
     }
-    cmake::Config::new("cnlp")
-        .define("Ipopt_INCLUDE_DIRS:STRING", ipopt_include_dirs)
-        .build()
+    let mut config = cmake::Config::new("cnlp");
+    config.define("Ipopt_INCLUDE_DIRS:STRING", ipopt_include_dirs);
+
+    if cfg!(feature = "asl") {
+        let asl = find_asl()
+            .expect("Failed to find the AMPL Solver Library required by the `asl` feature.");
+        let mut asl_include_dirs = String::new();
+        for path in asl.include_paths.iter() {
+            asl_include_dirs.push_str(path.to_str().unwrap());
+            asl_include_dirs.push(' ');
+        }
+        config
+            .define("CNLP_WITH_ASL", "ON")
+            .define("Asl_INCLUDE_DIRS:STRING", asl_include_dirs);
+    }
+
+    if cfg!(feature = "sipopt") {
+        let sipopt =
+            find_sipopt().expect("Failed to find sIpopt required by the `sipopt` feature.");
+        let mut sipopt_include_dirs = String::new();
+        for path in sipopt.include_paths.iter() {
+            sipopt_include_dirs.push_str(path.to_str().unwrap());
+            sipopt_include_dirs.push(' ');
+        }
+        config
+            .define("CNLP_WITH_SIPOPT", "ON")
+            .define("Sipopt_INCLUDE_DIRS:STRING", sipopt_include_dirs);
+    }
+
+    config.build()
 }
 
 /// Link ipopt-sys to our cnlp api. If ipopt is provided as a dynamic lib, we need to link it here.
@@ -492,6 +559,28 @@ fn link(cnlp_install_path: PathBuf, link_info: LinkInfo) -> Result<(), Error> {
     );
     println!("cargo:rustc-link-lib=static=ipopt_cnlp");
 
+    // Link to ASL, which is used by cnlp to read .nl files.
+    if cfg!(feature = "asl") {
+        let asl = find_asl()?;
+        for path in asl.search_paths {
+            println!("cargo:rustc-link-search=native={}", path.display());
+        }
+        for (_, lib) in asl.libs {
+            println!("cargo:rustc-link-lib={}", lib);
+        }
+    }
+
+    // Link to sIpopt, which is used by cnlp to compute sensitivities.
+    if cfg!(feature = "sipopt") {
+        let sipopt = find_sipopt()?;
+        for path in sipopt.search_paths {
+            println!("cargo:rustc-link-search=native={}", path.display());
+        }
+        for (_, lib) in sipopt.libs {
+            println!("cargo:rustc-link-lib={}", lib);
+        }
+    }
+
     // Order is important here. The most core libs should appear last.
     for path in link_info.search_paths {
         println!("cargo:rustc-link-search=native={}", path.display());
@@ -515,10 +604,19 @@ fn link(cnlp_install_path: PathBuf, link_info: LinkInfo) -> Result<(), Error> {
     // Generate raw bindings to CNLP interface
     let c_api_header = cnlp_install_path.join("include").join("c_api.h");
 
-    let bindings = bindgen::builder()
-        .header(c_api_header.to_str().unwrap())
-        .generate()
-        .expect("Unable to generate bindings!");
+    let mut builder = bindgen::builder().header(c_api_header.to_str().unwrap());
+
+    if cfg!(feature = "asl") {
+        let asl_api_header = cnlp_install_path.join("include").join("asl_api.h");
+        builder = builder.header(asl_api_header.to_str().unwrap());
+    }
+
+    if cfg!(feature = "sipopt") {
+        let sens_api_header = cnlp_install_path.join("include").join("sens_api.h");
+        builder = builder.header(sens_api_header.to_str().unwrap());
+    }
+
+    let bindings = builder.generate().expect("Unable to generate bindings!");
 
     let output = PathBuf::from(&env::var("OUT_DIR").unwrap());
     bindings
@@ -528,6 +626,29 @@ fn link(cnlp_install_path: PathBuf, link_info: LinkInfo) -> Result<(), Error> {
     Ok(())
 }
 
+/// Download a file from the given URL.
+fn download_file(path: &Path, url: &str) -> Result<(), Error> {
+    let f = File::create(path).unwrap();
+    let mut writer = BufWriter::new(f);
+    let mut easy = Easy::new();
+    easy.follow_location(true)?;
+    easy.url(url).unwrap();
+    easy.write_function(move |data| Ok(writer.write(data).unwrap()))
+        .unwrap();
+    easy.perform().unwrap();
+
+    let response_code = easy.response_code().unwrap();
+    if response_code != 200 {
+        return Err(Error::DownloadFailure {
+            response_code,
+            url: url.to_string(),
+        });
+    } else {
+        info!("Download successful!");
+    }
+    Ok(())
+}
+
 /// Download a tarball if it doesn't already exist.
 fn download_tarball(
     tarball_path: &Path,
@@ -537,24 +658,7 @@ fn download_tarball(
 ) -> Result<(), Error> {
     if !tarball_path.exists() {
         info!("Tarball doesn't exist, downloading...");
-        let f = File::create(tarball_path).unwrap();
-        let mut writer = BufWriter::new(f);
-        let mut easy = Easy::new();
-        easy.follow_location(true)?;
-        easy.url(binary_url).unwrap();
-        easy.write_function(move |data| Ok(writer.write(data).unwrap()))
-            .unwrap();
-        easy.perform().unwrap();
-
-        let response_code = easy.response_code().unwrap();
-        if response_code != 200 {
-            return Err(Error::DownloadFailure {
-                response_code,
-                url: binary_url.to_string(),
-            });
-        } else {
-            info!("Download successful!");
-        }
+        download_file(tarball_path, binary_url)?;
     }
 
     check_tarball_hashes(&tarball_path, md5, sha1)?;
@@ -644,6 +748,169 @@ fn build_and_install_ipopt() -> Result<LinkInfo, Error> {
     Ok(libs_info)
 }
 
+/// Whether Ipopt should be built with coinbrew, which is requested by setting `IPOPT_COINBREW`.
+fn coinbrew_enabled() -> bool {
+    match env::var("IPOPT_COINBREW") {
+        Ok(value) => !value.is_empty() && value != "0",
+        Err(_) => false,
+    }
+}
+
+/// The coinbrew script given by `COINBREW`, or a copy downloaded into the given directory.
+fn coinbrew_script(work_dir: &Path) -> Result<PathBuf, Error> {
+    if let Some(path) = env::var_os("COINBREW") {
+        let path = PathBuf::from(path);
+        if !path.is_file() {
+            warn!("COINBREW does not point to a file: {:?}", path);
+            return Err(Error::IOError);
+        }
+        return Ok(path);
+    }
+
+    let path = work_dir.join("coinbrew");
+    if !path.exists() {
+        info!("Downloading coinbrew...");
+        if let Err(err) = download_file(&path, COINBREW_URL) {
+            // Don't leave a partial script behind for the next build to pick up.
+            fs::remove_file(&path).ok();
+            return Err(err);
+        }
+    }
+    Ok(path)
+}
+
+/// Build Ipopt along with its third party libraries using COIN-OR's coinbrew script.
+///
+/// coinbrew fetches Ipopt and the third party projects it depends on (ASL, Blas, Lapack, Metis,
+/// Mumps and HSL) into one source tree and builds them together with the same configuration.
+/// HSL sources can't be downloaded, but are built if `COINHSL_DIR` points to them.
+fn build_with_coinbrew() -> Result<LinkInfo, Error> {
+    let crate_dir = PathBuf::from(&env::var("CARGO_MANIFEST_DIR").unwrap());
+    let work_dir = crate_dir.join("target").join("coinbrew");
+    debug!("work_dir = {:?}", &work_dir);
+    if !work_dir.exists() {
+        fs::create_dir_all(&work_dir).unwrap();
+    }
+
+    let install_dir = PathBuf::from(&env::var("OUT_DIR").unwrap());
+    let lib_dir = install_dir.join("lib");
+
+    // Let pkg-config find the libraries installed with Ipopt. This also makes the `asl` feature
+    // pick up the ASL built in the same tree.
+    let mut pkg_config_path = vec![lib_dir.join("pkgconfig")];
+    if let Some(path) = env::var_os("PKG_CONFIG_PATH") {
+        pkg_config_path.extend(env::split_paths(&path));
+    }
+    env::set_var("PKG_CONFIG_PATH", env::join_paths(pkg_config_path).unwrap());
+
+    let library_path = lib_dir.join(format!("lib{}.{}", LIBRARY, LIB_EXT));
+    if library_path.exists() {
+        // Library is already installed, retrieve link info and return.
+        return Ok(load_link_info()?);
+    }
+
+    let coinbrew = coinbrew_script(&work_dir)?;
+    debug!("coinbrew = {:?}", &coinbrew);
+    let coinbrew = coinbrew.to_str().unwrap();
+
+    // Fetching needs network access, so only fetch once to allow offline rebuilds.
+    if !work_dir.join("Ipopt").exists() {
+        run("bash", |cmd| {
+            cmd.current_dir(&work_dir)
+                .arg(coinbrew)
+                .arg("fetch")
+                .arg(format!("Ipopt@{}", VERSION))
+                .arg("--no-prompt")
+        });
+    }
+
+    if let Some(hsl_dir) = env::var_os("COINHSL_DIR") {
+        let coinhsl = work_dir.join("ThirdParty").join("HSL").join("coinhsl");
+        if !coinhsl.exists() {
+            link_dir(Path::new(&hsl_dir), &coinhsl)?;
+        }
+    }
+
+    let debug: bool = env::var("DEBUG").unwrap().parse().unwrap();
+    debug!("debug build? {}", debug);
+
+    let num_cpus = env::var("NUM_JOBS").unwrap_or(1.to_string());
+    run("bash", |cmd| {
+        let cmd = cmd
+            .current_dir(&work_dir)
+            .arg(coinbrew)
+            .arg("build")
+            .arg("Ipopt")
+            .arg(format!("--prefix={}", install_dir.display()))
+            .arg(format!("--parallel-jobs={}", num_cpus))
+            .arg("--tests=none")
+            .arg("--no-prompt");
+        if !cfg!(feature = "asl") {
+            cmd.arg("--skip=ThirdParty/ASL");
+        }
+        if debug {
+            cmd.arg("--enable-debug");
+        }
+        // Remaining arguments are forwarded to configure.
+        cmd.args(&BUILD_FLAGS).args(pardiso_configure_arg())
+    });
+
+    // Link against everything Ipopt was built with, preferring the static libraries installed
+    // alongside it.
+    let lib = pkg_config::Config::new()
+        .statik(true)
+        .cargo_metadata(false)
+        .probe(LIBRARY)
+        .map_err(|_| Error::PkgConfigNotFound)?;
+    debug!("lib = {:?}", &lib);
+
+    let mut link_libs: Vec<_> = lib
+        .libs
+        .iter()
+        .map(|name| {
+            let kind = if lib_dir.join(format!("lib{}.a", name)).exists() {
+                LibKind::Static
+            } else {
+                LibKind::Dynamic
+            };
+            (kind, name.clone())
+        })
+        .collect();
+    let mut search_paths = lib.link_paths.clone();
+    if let Some((dir, name)) = pardiso_lib() {
+        link_libs.push((LibKind::Dynamic, name));
+        search_paths.push(dir);
+    }
+    for framework in lib.frameworks.iter() {
+        link_libs.push((LibKind::Framework, framework.clone()));
+    }
+
+    // The headers are installed under `include/coin`, which is where cnlp looks for them.
+    let libs_info = LinkInfo {
+        libs: link_libs,
+        search_paths,
+        include_paths: vec![install_dir.join("include")],
+    };
+
+    save_link_info(&libs_info)?;
+
+    Ok(libs_info)
+}
+
+/// Make the directory `src` available at `dst`.
+#[cfg(target_family = "unix")]
+fn link_dir(src: &Path, dst: &Path) -> Result<(), Error> {
+    std::os::unix::fs::symlink(src, dst)?;
+    Ok(())
+}
+
+/// Make the directory `src` available at `dst`.
+#[cfg(target_os = "windows")]
+fn link_dir(src: &Path, dst: &Path) -> Result<(), Error> {
+    std::os::windows::fs::symlink_dir(src, dst)?;
+    Ok(())
+}
+
 /// The kind of library being linked by rustc.
 #[derive(Copy, Clone, Serialize, Deserialize, Debug)]
 enum LibKind {
@@ -662,6 +929,29 @@ struct LinkInfo {
     include_paths: Vec<PathBuf>,
 }
 
+/// The Panua Pardiso library given by `PARDISO_LIB`, linked into Ipopt when it is built from
+/// source.
+///
+/// Returns the directory of the library along with the name to link against. Without it,
+/// Pardiso can still be loaded at runtime.
+fn pardiso_lib() -> Option<(PathBuf, String)> {
+    let path = PathBuf::from(env::var_os("PARDISO_LIB")?);
+    if !path.is_file() {
+        warn!("PARDISO_LIB does not point to a file: {:?}", path);
+        return None;
+    }
+    let dir = path.parent()?.to_path_buf();
+    let stem = path.file_name()?.to_str()?.split('.').next()?;
+    let name = stem.strip_prefix("lib").unwrap_or(stem).to_string();
+    Some((dir, name))
+}
+
+/// Configure argument linking Pardiso into Ipopt, if `PARDISO_LIB` is set.
+fn pardiso_configure_arg() -> Option<String> {
+    let (dir, name) = pardiso_lib()?;
+    Some(format!("--with-pardiso=-L{} -l{}", dir.display(), name))
+}
+
 // Build Ipopt static lib with MKL in the current directory.
 fn build_with_mkl(install_dir: &Path, debug: bool) -> Result<LinkInfo, Error> {
     let mkl_libs = ["mkl_intel_lp64", "mkl_tbb_thread", "mkl_core"];
@@ -749,7 +1039,8 @@ fn build_with_mkl(install_dir: &Path, debug: bool) -> Result<LinkInfo, Error> {
             let cmd = cmd
                 .arg(format!("--prefix={}", install_dir.display()))
                 .args(&BUILD_FLAGS)
-                .arg(blas.clone());
+                .arg(blas.clone())
+                .args(pardiso_configure_arg());
 
             if debug {
                 cmd.arg(format!("--enable-debug-ipopt"))
@@ -789,9 +1080,15 @@ fn build_with_mkl(install_dir: &Path, debug: bool) -> Result<LinkInfo, Error> {
     }
     link_libs.push((LibKind::Dynamic, "tbb".to_string()));
 
+    let mut search_paths = vec![mkl_libs_path, install_dir.join("lib")];
+    if let Some((dir, name)) = pardiso_lib() {
+        link_libs.push((LibKind::Dynamic, name));
+        search_paths.push(dir);
+    }
+
     Ok(LinkInfo {
         libs: link_libs,
-        search_paths: vec![mkl_libs_path, install_dir.join("lib")],
+        search_paths,
         include_paths: vec![install_dir.join("include")],
     })
 }
@@ -1001,7 +1298,8 @@ fn build_with_default_blas(install_dir: &Path, debug: bool) -> Result<LinkInfo,
     run(root_dir.join("configure").to_str().unwrap(), |cmd| {
         let cmd = cmd
             .arg(format!("--prefix={}", install_dir.display()))
-            .args(&BUILD_FLAGS);
+            .args(&BUILD_FLAGS)
+            .args(pardiso_configure_arg());
 
         if debug {
             cmd.arg(format!("--enable-debug-ipopt"))
@@ -1015,6 +1313,11 @@ fn build_with_default_blas(install_dir: &Path, debug: bool) -> Result<LinkInfo,
     //run("make", |cmd| cmd.arg("test")); // Ensure everything is working
     run("make", |cmd| cmd.arg("install")); // Install to install_dir
 
+    if let Some((dir, name)) = pardiso_lib() {
+        link_libs.push((LibKind::Dynamic, name));
+        search_paths.push(dir);
+    }
+
     Ok(LinkInfo {
         libs: link_libs,
         search_paths,
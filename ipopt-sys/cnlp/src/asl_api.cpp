@@ -0,0 +1,239 @@
+#include "asl_api.h"
+
+#include <cstring>
+#include <vector>
+
+#include "asl_pfgh.h"
+#include "getstub.h"
+
+/**
+ * An AMPL model read using the ASL reader with partially separable structure. This structure
+ * mirrors the setup performed by Ipopt's own AmplTNLP.
+ */
+struct CNLP_AslProblem
+{
+    ASL_pfgh* asl;
+    CNLP_Number obj_sign; // -1 for maximization problems, 1 otherwise.
+    CNLP_Index nnz_h_lag;
+    std::vector<CNLP_Number> obj_weights; // Objective weights passed to sphes.
+};
+
+enum CNLP_AslReadStatus cnlp_asl_read(CNLP_AslProblemPtr * const out_problem,
+                                      const char* file_name)
+{
+    *out_problem = nullptr;
+
+    ASL_pfgh* asl = (ASL_pfgh*)ASL_alloc(ASL_read_pfgh);
+
+    // Let jac0dim return a null pointer instead of exiting the process.
+    return_nofile = 1;
+
+    std::vector<char> stub(file_name, file_name + std::strlen(file_name) + 1);
+    FILE* nl = jac0dim(stub.data(), (fint)std::strlen(file_name));
+    if (!nl) {
+        ASL_free((ASL**)&asl);
+        return CNLP_ASL_FILE_NOT_FOUND;
+    }
+
+    // Request the initial primal and dual values stored in the model.
+    X0 = (real*)M1alloc(n_var*sizeof(real));
+    havex0 = (char*)M1alloc(n_var*sizeof(char));
+    pi0 = (real*)M1alloc(n_con*sizeof(real));
+    havepi0 = (char*)M1alloc(n_con*sizeof(char));
+    want_xpi0 = 1 | 2;
+
+    if (pfgh_read(nl, ASL_return_read_err | ASL_findgroups) != 0) {
+        ASL_free((ASL**)&asl);
+        return CNLP_ASL_READ_ERROR;
+    }
+
+    CNLP_AslProblemPtr problem = new CNLP_AslProblem;
+    problem->asl = asl;
+    problem->obj_sign = (n_obj > 0 && objtype[0] != 0) ? -1.0 : 1.0;
+    problem->obj_weights.resize(n_obj > 0 ? n_obj : 1, 0.0);
+
+    // Setup the Hessian structure of the Lagrangian including the objective and all constraints,
+    // storing the upper triangular part by columns.
+    problem->nnz_h_lag = (CNLP_Index)sphsetup(n_obj > 0 ? 0 : -1, n_obj > 0, n_con > 0, 1);
+
+    *out_problem = problem;
+    return CNLP_ASL_READ_SUCCESS;
+}
+
+void cnlp_asl_free(CNLP_AslProblemPtr problem)
+{
+    if (problem) {
+        ASL_free((ASL**)&problem->asl);
+        delete problem;
+    }
+}
+
+void cnlp_asl_sizes(CNLP_AslProblemPtr problem,
+                    CNLP_Index *n, CNLP_Index *m,
+                    CNLP_Index *nnz_jac_g, CNLP_Index *nnz_h_lag)
+{
+    ASL_pfgh* asl = problem->asl;
+    *n = n_var;
+    *m = n_con;
+    *nnz_jac_g = nzc;
+    *nnz_h_lag = problem->nnz_h_lag;
+}
+
+void cnlp_asl_bounds(CNLP_AslProblemPtr problem,
+                     CNLP_Number* x_l, CNLP_Number* x_u,
+                     CNLP_Number* g_l, CNLP_Number* g_u)
+{
+    ASL_pfgh* asl = problem->asl;
+    for (int i = 0; i < n_var; ++i) {
+        x_l[i] = Uvx ? LUv[i] : LUv[2*i];
+        x_u[i] = Uvx ? Uvx[i] : LUv[2*i + 1];
+    }
+    for (int i = 0; i < n_con; ++i) {
+        g_l[i] = Urhsx ? LUrhs[i] : LUrhs[2*i];
+        g_u[i] = Urhsx ? Urhsx[i] : LUrhs[2*i + 1];
+    }
+}
+
+void cnlp_asl_initial_point(CNLP_AslProblemPtr problem, CNLP_Number* x)
+{
+    ASL_pfgh* asl = problem->asl;
+    for (int i = 0; i < n_var; ++i) {
+        x[i] = havex0[i] ? X0[i] : 0.0;
+    }
+}
+
+void cnlp_asl_initial_multipliers(CNLP_AslProblemPtr problem, CNLP_Number* lambda)
+{
+    ASL_pfgh* asl = problem->asl;
+    for (int i = 0; i < n_con; ++i) {
+        // AMPL and Ipopt use opposite sign conventions for constraint multipliers.
+        lambda[i] = havepi0[i] ? -pi0[i] : 0.0;
+    }
+}
+
+CNLP_Bool cnlp_asl_eval_f(CNLP_AslProblemPtr problem, const CNLP_Number* x,
+                          CNLP_Number* obj_value)
+{
+    ASL_pfgh* asl = problem->asl;
+    if (n_obj == 0) {
+        *obj_value = 0.0;
+        return 1;
+    }
+    fint nerror = 0;
+    real f = objval(0, const_cast<real*>(x), &nerror);
+    if (nerror != 0) {
+        return 0;
+    }
+    *obj_value = problem->obj_sign*f;
+    return 1;
+}
+
+CNLP_Bool cnlp_asl_eval_grad_f(CNLP_AslProblemPtr problem, const CNLP_Number* x,
+                               CNLP_Number* grad_f)
+{
+    ASL_pfgh* asl = problem->asl;
+    if (n_obj == 0) {
+        for (int i = 0; i < n_var; ++i) {
+            grad_f[i] = 0.0;
+        }
+        return 1;
+    }
+    fint nerror = 0;
+    objgrd(0, const_cast<real*>(x), grad_f, &nerror);
+    if (nerror != 0) {
+        return 0;
+    }
+    if (problem->obj_sign < 0.0) {
+        for (int i = 0; i < n_var; ++i) {
+            grad_f[i] = -grad_f[i];
+        }
+    }
+    return 1;
+}
+
+CNLP_Bool cnlp_asl_eval_g(CNLP_AslProblemPtr problem, const CNLP_Number* x,
+                          CNLP_Number* g)
+{
+    ASL_pfgh* asl = problem->asl;
+    fint nerror = 0;
+    conval(const_cast<real*>(x), g, &nerror);
+    return nerror == 0;
+}
+
+void cnlp_asl_jac_g_structure(CNLP_AslProblemPtr problem,
+                              CNLP_Index* iRow, CNLP_Index* jCol)
+{
+    ASL_pfgh* asl = problem->asl;
+    for (int i = 0; i < n_con; ++i) {
+        for (cgrad* cg = Cgrad[i]; cg; cg = cg->next) {
+            iRow[cg->goff] = i;
+            jCol[cg->goff] = cg->varno;
+        }
+    }
+}
+
+CNLP_Bool cnlp_asl_eval_jac_g(CNLP_AslProblemPtr problem, const CNLP_Number* x,
+                              CNLP_Number* values)
+{
+    ASL_pfgh* asl = problem->asl;
+    fint nerror = 0;
+    jacval(const_cast<real*>(x), values, &nerror);
+    return nerror == 0;
+}
+
+void cnlp_asl_h_structure(CNLP_AslProblemPtr problem,
+                          CNLP_Index* iRow, CNLP_Index* jCol)
+{
+    ASL_pfgh* asl = problem->asl;
+    // ASL stores the upper triangular part by columns, which is the lower triangular part by rows.
+    CNLP_Index k = 0;
+    for (int i = 0; i < n_var; ++i) {
+        for (int j = sputinfo->hcolstarts[i]; j < sputinfo->hcolstarts[i + 1]; ++j) {
+            iRow[k] = i;
+            jCol[k] = sputinfo->hrownos[j];
+            ++k;
+        }
+    }
+}
+
+CNLP_Bool cnlp_asl_eval_h(CNLP_AslProblemPtr problem, const CNLP_Number* x,
+                          CNLP_Number obj_factor, const CNLP_Number* lambda,
+                          CNLP_Number* values)
+{
+    ASL_pfgh* asl = problem->asl;
+
+    // The Hessian is evaluated at the point of the last function evaluation, so make sure the
+    // nonlinear expressions are up to date.
+    fint nerror = 0;
+    if (n_obj > 0) {
+        objval(0, const_cast<real*>(x), &nerror);
+    }
+    if (n_con > 0) {
+        std::vector<real> g(n_con);
+        conval(const_cast<real*>(x), g.data(), &nerror);
+    }
+    if (nerror != 0) {
+        return 0;
+    }
+
+    real* ow = nullptr;
+    if (n_obj > 0) {
+        problem->obj_weights[0] = problem->obj_sign*obj_factor;
+        ow = problem->obj_weights.data();
+    }
+    sphes(values, n_obj > 0 ? 0 : -1, ow, const_cast<real*>(lambda));
+    return 1;
+}
+
+void cnlp_asl_write_solution(CNLP_AslProblemPtr problem, const char* message,
+                             const CNLP_Number* x, const CNLP_Number* lambda)
+{
+    ASL_pfgh* asl = problem->asl;
+    std::vector<char> msg(message, message + std::strlen(message) + 1);
+    std::vector<real> y(lambda, lambda + n_con);
+    // Convert the multipliers back to the AMPL sign convention.
+    for (auto& yi : y) {
+        yi = -yi;
+    }
+    write_sol(msg.data(), const_cast<real*>(x), y.data(), nullptr);
+}
@@ -0,0 +1,117 @@
+#ifndef __IPOPT_SYS_ASL_API_H__
+#define __IPOPT_SYS_ASL_API_H__
+
+#include "c_api.h"
+
+#ifndef CNLP_API
+#ifdef _MSC_VER
+#define CNLP_API(type) __declspec(dllexport) type __cdecl
+#else
+#define CNLP_API(type) type
+#endif
+#endif
+
+#ifdef __cplusplus
+extern "C"
+{
+#endif
+
+    /**
+     * Structure holding an AMPL model read from an .nl file through the AMPL Solver Library
+     * (ASL). This is defined in the source file.
+     */
+    struct CNLP_AslProblem;
+
+    /** Pointer to a CNLP_AslProblem */
+    typedef struct CNLP_AslProblem* CNLP_AslProblemPtr;
+
+    /** Enum reporting the status of reading an .nl file */
+    enum CNLP_AslReadStatus {
+        CNLP_ASL_READ_SUCCESS,
+        CNLP_ASL_FILE_NOT_FOUND,
+        CNLP_ASL_READ_ERROR,
+    };
+
+    /**
+     * Read the model stored in the given .nl file. On success the output problem must be freed
+     * with cnlp_asl_free.
+     */
+    CNLP_API(enum CNLP_AslReadStatus) cnlp_asl_read(CNLP_AslProblemPtr * const p // Output problem
+                                                   , const char* file_name);
+
+    /** Free a model previously read with cnlp_asl_read. */
+    CNLP_API(void) cnlp_asl_free(CNLP_AslProblemPtr problem);
+
+    /**
+     * Retrieve the number of variables, constraints as well as the number of non-zeros in the
+     * constraint Jacobian and the Hessian of the Lagrangian (lower triangular part).
+     */
+    CNLP_API(void) cnlp_asl_sizes(CNLP_AslProblemPtr problem,
+                                  CNLP_Index *n, CNLP_Index *m,
+                                  CNLP_Index *nnz_jac_g, CNLP_Index *nnz_h_lag);
+
+    /** Retrieve variable and constraint bounds. */
+    CNLP_API(void) cnlp_asl_bounds(CNLP_AslProblemPtr problem,
+                                   CNLP_Number* x_l, CNLP_Number* x_u,
+                                   CNLP_Number* g_l, CNLP_Number* g_u);
+
+    /**
+     * Retrieve the initial point stored in the model. Variables without an initial value are set
+     * to zero.
+     */
+    CNLP_API(void) cnlp_asl_initial_point(CNLP_AslProblemPtr problem, CNLP_Number* x);
+
+    /**
+     * Retrieve the initial constraint multipliers stored in the model. Multipliers without an
+     * initial value are set to zero.
+     */
+    CNLP_API(void) cnlp_asl_initial_multipliers(CNLP_AslProblemPtr problem, CNLP_Number* lambda);
+
+    /**
+     * Evaluate the objective. The objective is negated for maximization problems, such that the
+     * problem passed to Ipopt is always a minimization.
+     */
+    CNLP_API(CNLP_Bool) cnlp_asl_eval_f(CNLP_AslProblemPtr problem, const CNLP_Number* x,
+                                        CNLP_Number* obj_value);
+
+    /** Evaluate the gradient of the objective (negated for maximization problems). */
+    CNLP_API(CNLP_Bool) cnlp_asl_eval_grad_f(CNLP_AslProblemPtr problem, const CNLP_Number* x,
+                                             CNLP_Number* grad_f);
+
+    /** Evaluate the constraint functions. */
+    CNLP_API(CNLP_Bool) cnlp_asl_eval_g(CNLP_AslProblemPtr problem, const CNLP_Number* x,
+                                        CNLP_Number* g);
+
+    /** Retrieve the zero-based sparsity structure of the constraint Jacobian. */
+    CNLP_API(void) cnlp_asl_jac_g_structure(CNLP_AslProblemPtr problem,
+                                            CNLP_Index* iRow, CNLP_Index* jCol);
+
+    /** Evaluate the constraint Jacobian values. */
+    CNLP_API(CNLP_Bool) cnlp_asl_eval_jac_g(CNLP_AslProblemPtr problem, const CNLP_Number* x,
+                                            CNLP_Number* values);
+
+    /**
+     * Retrieve the zero-based sparsity structure of the lower triangular part of the Hessian of
+     * the Lagrangian.
+     */
+    CNLP_API(void) cnlp_asl_h_structure(CNLP_AslProblemPtr problem,
+                                        CNLP_Index* iRow, CNLP_Index* jCol);
+
+    /** Evaluate the values of the Hessian of the Lagrangian. */
+    CNLP_API(CNLP_Bool) cnlp_asl_eval_h(CNLP_AslProblemPtr problem, const CNLP_Number* x,
+                                        CNLP_Number obj_factor, const CNLP_Number* lambda,
+                                        CNLP_Number* values);
+
+    /**
+     * Write an AMPL .sol file next to the .nl file with the given message, primal variables and
+     * constraint multipliers.
+     */
+    CNLP_API(void) cnlp_asl_write_solution(CNLP_AslProblemPtr problem, const char* message,
+                                           const CNLP_Number* x, const CNLP_Number* lambda);
+
+#ifdef __cplusplus
+} /* extern "C" { */
+#endif
+
+#undef CNLP_API
+#endif
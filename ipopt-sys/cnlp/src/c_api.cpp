@@ -2,10 +2,40 @@
 #include "nlp.hpp"
 
 #include <coin/IpIpoptApplication.hpp>
+#include <coin/IpIpoptData.hpp>
+#include <coin/IpTimingStatistics.hpp>
+#include <coin/IpoptConfig.h>
+#if IPOPT_VERSION_MAJOR == 3 && IPOPT_VERSION_MINOR < 14
+#include <coin/IpOrigIpoptNLP.hpp>
+#endif
+#include <algorithm>
 #include <memory>
 #include <vector>
 #include <iterator>
 
+/*
+ * Thread control functions of the threading runtimes that may be linked with Ipopt. These are
+ * declared weak so that they resolve to null when the corresponding runtime is absent. Weak
+ * references are only reliable on ELF platforms, so elsewhere no runtime is detected.
+ */
+#if defined(__ELF__)
+extern "C" {
+void omp_set_num_threads(int) __attribute__((weak));
+void openblas_set_num_threads(int) __attribute__((weak));
+void mkl_set_num_threads(int) __attribute__((weak));
+}
+#endif
+
+/*
+ * Entry point of the linear solver loader compiled into Ipopt when solvers are loaded at
+ * runtime, declared weak for the same reason.
+ */
+#if defined(__ELF__)
+extern "C" {
+int LSL_loadPardisoLib(const char*, char*, int) __attribute__((weak));
+}
+#endif
+
 using namespace std;
 
 enum CNLP_CreateProblemStatus cnlp_create_problem(
@@ -79,21 +109,120 @@ CNLP_Bool cnlp_add_str_option(CNLP_ProblemPtr problem, const char* keyword, cons
 {
     std::string tag(keyword);
     std::string value(val);
-    return (CNLP_Bool) problem->get_app()->Options()->SetStringValue(tag, value);
+    if (!problem->get_app()->Options()->SetStringValue(tag, value)) {
+        return 0;
+    }
+    problem->record_user_option(tag);
+    return 1;
 }
 
 CNLP_Bool cnlp_add_num_option(CNLP_ProblemPtr problem, const char* keyword, CNLP_Number val)
 {
     std::string tag(keyword);
     Ipopt::Number value = val;
-    return (CNLP_Bool) problem->get_app()->Options()->SetNumericValue(tag, value);
+    if (!problem->get_app()->Options()->SetNumericValue(tag, value)) {
+        return 0;
+    }
+    problem->record_user_option(tag);
+    return 1;
 }
 
 CNLP_Bool cnlp_add_int_option(CNLP_ProblemPtr problem, const char* keyword, CNLP_Int val)
 {
     std::string tag(keyword);
     Ipopt::Index value = val;
-    return (CNLP_Bool) problem->get_app()->Options()->SetIntegerValue(tag, value);
+    if (!problem->get_app()->Options()->SetIntegerValue(tag, value)) {
+        return 0;
+    }
+    problem->record_user_option(tag);
+    return 1;
+}
+
+CNLP_Bool cnlp_get_str_option(CNLP_ProblemPtr problem, const char* keyword, char* val,
+                              CNLP_Int val_len)
+{
+    std::string tag(keyword);
+    // Looking up an option that is not registered or has another type throws.
+    Ipopt::SmartPtr<const Ipopt::RegisteredOption> option =
+        problem->get_app()->RegOptions()->GetOption(tag);
+    if (Ipopt::IsNull(option) || option->Type() != Ipopt::OT_String) {
+        return 0;
+    }
+    std::string value;
+    if (!problem->get_app()->Options()->GetStringValue(tag, value, "")
+        || value.size() >= static_cast<std::size_t>(std::max(val_len, 0))) {
+        return 0;
+    }
+    std::copy(value.begin(), value.end(), val);
+    val[value.size()] = '\0';
+    return 1;
+}
+
+CNLP_Bool cnlp_visit_options(CNLP_ProblemPtr problem, CNLP_Option_CB visit,
+                             CNLP_UserDataPtr user_data)
+{
+    return (CNLP_Bool) problem->visit_options(visit, user_data);
+}
+
+void cnlp_visit_registered_options(CNLP_OptionInfo_CB visit, CNLP_UserDataPtr user_data)
+{
+    // A fresh application registers all options of the linked Ipopt.
+    Ipopt::SmartPtr<Ipopt::IpoptApplication> app = new Ipopt::IpoptApplication(false);
+    const Ipopt::RegisteredOptions::RegOptionsList& options =
+        app->RegOptions()->RegisteredOptionsList();
+    for (const auto& entry : options) {
+        const Ipopt::RegisteredOption& option = *entry.second;
+        CNLP_OptionInfo info = {};
+        switch (option.Type()) {
+            case Ipopt::OT_Number:  info.type = CNLP_NUMBER_OPTION; break;
+            case Ipopt::OT_Integer: info.type = CNLP_INTEGER_OPTION; break;
+            case Ipopt::OT_String:  info.type = CNLP_STRING_OPTION; break;
+            default:                continue;
+        }
+
+#if IPOPT_VERSION_MAJOR > 3 || IPOPT_VERSION_MINOR >= 14
+        std::string category =
+            Ipopt::IsValid(option.RegisteringCategory()) ? option.RegisteringCategory()->Name() : "";
+#else
+        std::string category = option.RegisteringCategory();
+#endif
+        std::string default_str;
+        std::vector<const char*> valid_values;
+        std::vector<const char*> valid_value_descriptions;
+        if (info.type == CNLP_NUMBER_OPTION) {
+            info.has_lower = option.HasLower();
+            info.lower_strict = option.LowerStrict();
+            info.lower = option.LowerNumber();
+            info.has_upper = option.HasUpper();
+            info.upper_strict = option.UpperStrict();
+            info.upper = option.UpperNumber();
+            info.default_num = option.DefaultNumber();
+        } else if (info.type == CNLP_INTEGER_OPTION) {
+            info.has_lower = option.HasLower();
+            info.lower = option.LowerInteger();
+            info.has_upper = option.HasUpper();
+            info.upper = option.UpperInteger();
+            info.default_int = option.DefaultInteger();
+        } else {
+            default_str = option.DefaultString();
+            for (const auto& valid : option.GetValidStrings()) {
+                // A "*" entry accepts any string.
+                if (valid.value_ != "*") {
+                    valid_values.push_back(valid.value_.c_str());
+                    valid_value_descriptions.push_back(valid.description_.c_str());
+                }
+            }
+        }
+
+        info.name = entry.first.c_str();
+        info.category = category.c_str();
+        info.short_description = option.ShortDescription().c_str();
+        info.default_str = default_str.c_str();
+        info.num_valid_values = static_cast<CNLP_Index>(valid_values.size());
+        info.valid_values = valid_values.data();
+        info.valid_value_descriptions = valid_value_descriptions.data();
+        visit(&info, user_data);
+    }
 }
 
 CNLP_Bool cnlp_open_output_file(CNLP_ProblemPtr problem, const char* file_name,
@@ -104,6 +233,72 @@ CNLP_Bool cnlp_open_output_file(CNLP_ProblemPtr problem, const char* file_name,
     return (CNLP_Bool) problem->get_app()->OpenOutputFile(name, level);
 }
 
+CNLP_Bool cnlp_set_num_threads(CNLP_Int num_threads)
+{
+    bool found = false;
+#if defined(__ELF__)
+    if (omp_set_num_threads) {
+        omp_set_num_threads(num_threads);
+        found = true;
+    }
+    if (openblas_set_num_threads) {
+        openblas_set_num_threads(num_threads);
+        found = true;
+    }
+    if (mkl_set_num_threads) {
+        mkl_set_num_threads(num_threads);
+        found = true;
+    }
+#else
+    (void) num_threads;
+#endif
+    return (CNLP_Bool) found;
+}
+
+CNLP_Bool cnlp_load_pardiso_library(const char* path, char* msg, CNLP_Int msg_len)
+{
+    if (msg_len > 0) {
+        msg[0] = '\0';
+    }
+#if defined(__ELF__)
+    if (LSL_loadPardisoLib) {
+        // The loader returns zero on success.
+        return (CNLP_Bool) (LSL_loadPardisoLib(path, msg, msg_len) == 0);
+    }
+#else
+    (void) path;
+#endif
+    return 0;
+}
+
+CNLP_Bool cnlp_open_log_file(CNLP_ProblemPtr problem, const char* file_name,
+                             CNLP_Int print_level, CNLP_Bool append)
+{
+    if (print_level < 0 || print_level >= Ipopt::J_LAST_LEVEL) {
+        return 0;
+    }
+    Ipopt::EJournalLevel level = Ipopt::EJournalLevel(print_level);
+    return (CNLP_Bool) problem->open_log_file(file_name, level, append != 0);
+}
+
+void cnlp_close_log_file(CNLP_ProblemPtr problem)
+{
+    problem->close_log_file();
+}
+
+CNLP_Bool cnlp_set_category_print_level(CNLP_ProblemPtr problem,
+                                        enum CNLP_JournalCategory category,
+                                        CNLP_Int print_level)
+{
+    if (category > CNLP_J_USER_APPLICATION || print_level < 0 || print_level >= Ipopt::J_LAST_LEVEL) {
+        return 0;
+    }
+    Ipopt::EJournalCategory cat = Ipopt::EJournalCategory(category);
+    Ipopt::EJournalLevel level = Ipopt::EJournalLevel(print_level);
+    problem->set_category_print_level(cat, level);
+    return 1;
+}
+
 void cnlp_set_intermediate_callback(CNLP_ProblemPtr problem,
         CNLP_Intermediate_CB intermediate_cb)
 {
@@ -115,6 +310,92 @@ CNLP_SolveResult cnlp_solve(CNLP_ProblemPtr problem, CNLP_UserDataPtr user_data)
     return problem->solve(user_data);
 }
 
+CNLP_SolveResult cnlp_reoptimize(CNLP_ProblemPtr problem, CNLP_UserDataPtr user_data)
+{
+    return problem->reoptimize(user_data);
+}
+
+static CNLP_TaskTime task_time(const Ipopt::TimedTask& task)
+{
+    CNLP_TaskTime time;
+    time.cpu_time = task.TotalCpuTime();
+    time.wallclock_time = task.TotalWallclockTime();
+    return time;
+}
+
+/*
+ * Function evaluation times, which are kept by the TimingStatistics since Ipopt 3.14 and by the
+ * OrigIpoptNLP before that.
+ */
+template <typename Timers>
+static void function_evaluation_times(Timers& t, CNLP_TimingStats* stats)
+{
+    stats->function_evaluations.cpu_time = t.TotalFunctionEvaluationCpuTime();
+    stats->function_evaluations.wallclock_time = t.TotalFunctionEvaluationWallclockTime();
+    stats->objective = task_time(t.f_eval_time());
+    stats->objective_gradient = task_time(t.grad_f_eval_time());
+    stats->equality_constraints = task_time(t.c_eval_time());
+    stats->inequality_constraints = task_time(t.d_eval_time());
+    stats->equality_jacobian = task_time(t.jac_c_eval_time());
+    stats->inequality_jacobian = task_time(t.jac_d_eval_time());
+    stats->hessian = task_time(t.h_eval_time());
+}
+
+CNLP_Bool cnlp_get_timing_stats(CNLP_ProblemPtr problem, CNLP_TimingStats* stats)
+{
+    Ipopt::IpoptApplication* app = problem->get_app();
+    if (!Ipopt::IsValid(app->Statistics())) {
+        return 0;
+    }
+    Ipopt::SmartPtr<Ipopt::IpoptData> data = app->IpoptDataObject();
+    if (!Ipopt::IsValid(data)) {
+        return 0;
+    }
+#if IPOPT_VERSION_MAJOR > 3 || IPOPT_VERSION_MINOR >= 14
+    bool timing = false;
+    bool print_timing = false;
+    app->Options()->GetBoolValue("timing_statistics", timing, "");
+    app->Options()->GetBoolValue("print_timing_statistics", print_timing, "");
+    if (!timing && !print_timing) {
+        return 0;
+    }
+#endif
+
+    Ipopt::TimingStatistics& t = data->TimingStats();
+    stats->overall_algorithm = task_time(t.OverallAlgorithm());
+#if IPOPT_VERSION_MAJOR > 3 || IPOPT_VERSION_MINOR >= 14
+    function_evaluation_times(t, stats);
+#else
+    Ipopt::SmartPtr<Ipopt::IpoptNLP> nlp = app->IpoptNLPObject();
+    Ipopt::OrigIpoptNLP* orig_nlp =
+            dynamic_cast<Ipopt::OrigIpoptNLP*>(Ipopt::GetRawPtr(nlp));
+    if (orig_nlp != NULL) {
+        function_evaluation_times(*orig_nlp, stats);
+    }
+#endif
+    stats->initialize_iterates = task_time(t.InitializeIterates());
+    stats->update_hessian = task_time(t.UpdateHessian());
+    stats->output_iteration = task_time(t.OutputIteration());
+    stats->update_barrier_parameter = task_time(t.UpdateBarrierParameter());
+    stats->compute_search_direction = task_time(t.ComputeSearchDirection());
+    stats->compute_acceptable_trial_point = task_time(t.ComputeAcceptableTrialPoint());
+    stats->accept_trial_point = task_time(t.AcceptTrialPoint());
+    stats->check_convergence = task_time(t.CheckConvergence());
+    stats->pd_system_solve = task_time(t.PDSystemSolverTotal());
+    stats->compute_residuals = task_time(t.ComputeResiduals());
+    stats->linear_system_scaling = task_time(t.LinearSystemScaling());
+    stats->linear_system_symbolic_factorization =
+            task_time(t.LinearSystemSymbolicFactorization());
+    stats->linear_system_factorization = task_time(t.LinearSystemFactorization());
+    stats->linear_system_back_solve = task_time(t.LinearSystemBackSolve());
+    return 1;
+}
+
+CNLP_Bool cnlp_get_restoration_start(CNLP_ProblemPtr problem, CNLP_Number* x, CNLP_Index* iter)
+{
+    return (CNLP_Bool) problem->get_restoration_start(x, iter);
+}
+
 CNLP_Bool cnlp_init_solution(CNLP_ProblemPtr problem, CNLP_UserDataPtr user_data)
 {
     problem->set_user_data(user_data);
@@ -126,3 +407,27 @@ CNLP_SolverData cnlp_get_solver_data(CNLP_ProblemPtr problem)
     return problem->get_solution_arguments();
 }
 
+
+CNLP_Bool cnlp_get_solve_stats(CNLP_ProblemPtr problem, CNLP_SolveStats* stats)
+{
+    Ipopt::SmartPtr<Ipopt::SolveStatistics> s = problem->get_app()->Statistics();
+    if (!Ipopt::IsValid(s)) {
+        return 0;
+    }
+
+    stats->iteration_count = s->IterationCount();
+    stats->total_cpu_time = s->TotalCpuTime();
+    stats->total_sys_time = s->TotalSysTime();
+    stats->total_wallclock_time = s->TotalWallclockTime();
+    s->NumberOfEvaluations(stats->num_obj_evals, stats->num_constr_evals,
+                           stats->num_obj_grad_evals, stats->num_constr_jac_evals,
+                           stats->num_hess_evals);
+    stats->obj_val = s->FinalObjective();
+    stats->scaled_obj_val = s->FinalScaledObjective();
+    s->Infeasibilities(stats->dual_inf, stats->constr_viol, stats->complementarity,
+                       stats->kkt_error);
+
+    stats->num_regularized_iterations = problem->get_num_regularized_iterations();
+    stats->final_regularization = problem->get_last_regularization();
+    return 1;
+}
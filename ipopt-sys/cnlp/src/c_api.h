@@ -47,6 +47,42 @@ extern "C"
         CNLP_RESTORATION_PHASE_MODE = 1
     };
 
+    /** Categories of messages printed by the Ipopt journalist. */
+    enum CNLP_JournalCategory
+    {
+        CNLP_J_DBG = 0,
+        CNLP_J_STATISTICS,
+        CNLP_J_MAIN,
+        CNLP_J_INITIALIZATION,
+        CNLP_J_BARRIER_UPDATE,
+        CNLP_J_SOLVE_PD_SYSTEM,
+        CNLP_J_FRAC_TO_BOUND,
+        CNLP_J_LINEAR_ALGEBRA,
+        CNLP_J_LINE_SEARCH,
+        CNLP_J_HESSIAN_APPROXIMATION,
+        CNLP_J_SOLUTION,
+        CNLP_J_DOCUMENTATION,
+        CNLP_J_NLP,
+        CNLP_J_TIMING_STATISTICS,
+        CNLP_J_USER_APPLICATION
+    };
+
+    /** Types of Ipopt options. */
+    enum CNLP_OptionType
+    {
+        CNLP_NUMBER_OPTION = 0,
+        CNLP_INTEGER_OPTION = 1,
+        CNLP_STRING_OPTION = 2
+    };
+
+    /** Where the effective value of an Ipopt option comes from. */
+    enum CNLP_OptionSource
+    {
+        CNLP_DEFAULT_VALUE = 0,   // The option was not set
+        CNLP_OPTION_FILE = 1,     // The option was set in the option file
+        CNLP_USER_VALUE = 2       // The option was set with a cnlp_add_*_option function
+    };
+
     /** The following typedefs must match the typedefs in Ipopt */
     //@{
     typedef double CNLP_Number;
@@ -171,6 +207,45 @@ extern "C"
             CNLP_Number alpha_du, CNLP_Number alpha_pr,
             CNLP_Index ls_trials, CNLP_UserDataPtr user_data);
 
+    /**
+     * Type defining the callback function receiving the effective value of an option. Only the
+     * value matching the type of the option is meaningful. The name and string value are only
+     * valid for the duration of the call.
+     */
+    typedef void (*CNLP_Option_CB)(
+            const char* name, enum CNLP_OptionType type, enum CNLP_OptionSource source,
+            CNLP_Number num_value, CNLP_Int int_value, const char* str_value,
+            CNLP_UserDataPtr user_data);
+
+    /**
+     * Metadata of an option registered with Ipopt. Bounds are given for numeric and integer
+     * options, valid values for string options; an empty list of valid values means any string
+     * is accepted. Only the default matching the type of the option is meaningful. All strings
+     * are only valid for the duration of the callback receiving this struct.
+     */
+    struct CNLP_OptionInfo {
+        const char* name;                   // Option name
+        enum CNLP_OptionType type;          // Option type
+        const char* category;               // Category the option is listed under
+        const char* short_description;      // One line description
+        CNLP_Bool has_lower;                // Whether the option has a lower bound
+        CNLP_Bool lower_strict;             // Whether the lower bound is excluded
+        CNLP_Number lower;                  // Lower bound
+        CNLP_Bool has_upper;                // Whether the option has an upper bound
+        CNLP_Bool upper_strict;             // Whether the upper bound is excluded
+        CNLP_Number upper;                  // Upper bound
+        CNLP_Number default_num;            // Default of a numeric option
+        CNLP_Int default_int;               // Default of an integer option
+        const char* default_str;            // Default of a string option
+        CNLP_Index num_valid_values;        // Number of valid values of a string option
+        const char* const* valid_values;    // Valid values of a string option
+        const char* const* valid_value_descriptions; // Description of each valid value
+    };
+
+    /** Type defining the callback function receiving the metadata of a registered option. */
+    typedef void (*CNLP_OptionInfo_CB)(const struct CNLP_OptionInfo* info,
+                                       CNLP_UserDataPtr user_data);
+
     /** Enum reporting the status of problem creation */
     enum CNLP_CreateProblemStatus {
         CNLP_SUCCESS,
@@ -238,6 +313,30 @@ extern "C"
     CNLP_API(CNLP_Bool) cnlp_add_int_option(CNLP_ProblemPtr problem, const char* keyword,
                                             CNLP_Int val);
 
+    /**
+     * Copy the value of a string option set for this problem into val, including the
+     * terminating null. Returns 0 if the keyword is unknown, the option is not set, or its value
+     * does not fit into val_len bytes.
+     */
+    CNLP_API(CNLP_Bool) cnlp_get_str_option(CNLP_ProblemPtr problem, const char* keyword,
+                                            char* val, CNLP_Int val_len);
+
+    /**
+     * Call the given function with the effective value and source of every registered option,
+     * in order of option names. The option file is read when the problem is solved for the first
+     * time, so this returns 0 without calling the function if the problem has not been solved
+     * yet.
+     */
+    CNLP_API(CNLP_Bool) cnlp_visit_options(CNLP_ProblemPtr problem, CNLP_Option_CB visit,
+                                           CNLP_UserDataPtr user_data);
+
+    /**
+     * Call the given function with the metadata of every option registered with the linked
+     * version of Ipopt, in order of option names.
+     */
+    CNLP_API(void) cnlp_visit_registered_options(CNLP_OptionInfo_CB visit,
+                                                 CNLP_UserDataPtr user_data);
+
     /**
      * Function for opening an output file for a given name with given printlevel.  Returns 0
      * if there was a problem opening the file.
@@ -245,6 +344,42 @@ extern "C"
     CNLP_API(CNLP_Bool) cnlp_open_output_file(CNLP_ProblemPtr problem, const char* file_name,
                                               CNLP_Int print_level);
 
+    /**
+     * Set the number of threads used by the OpenMP, OpenBLAS and MKL runtimes linked into the
+     * process, which in turn are used by BLAS, LAPACK and the multithreaded linear solvers. This
+     * affects the whole process. Returns 0 if none of these runtimes could be found.
+     */
+    CNLP_API(CNLP_Bool) cnlp_set_num_threads(CNLP_Int num_threads);
+
+    /**
+     * Load the Pardiso library at the given path into the linear solver loader of Ipopt, after
+     * which "pardiso" can be selected as the linear solver. This is for Ipopt builds before 3.14,
+     * which have no "pardisolib" option. Returns 0 and writes an error message of at most msg_len
+     * bytes into msg if the library could not be loaded. The message is empty if Ipopt was built
+     * without a linear solver loader, which can only be detected on ELF platforms.
+     */
+    CNLP_API(CNLP_Bool) cnlp_load_pardiso_library(const char *path, char *msg, CNLP_Int msg_len);
+
+    /**
+     * Write output to the given file at the given print level. Unlike cnlp_open_output_file, the
+     * file can be replaced by calling this function again, and it can be appended to instead of
+     * truncated. Returns 0 if the file could not be opened.
+     */
+    CNLP_API(CNLP_Bool) cnlp_open_log_file(CNLP_ProblemPtr problem, const char* file_name,
+                                           CNLP_Int print_level, CNLP_Bool append);
+
+    /** Stop writing output to the file opened with cnlp_open_log_file. */
+    CNLP_API(void) cnlp_close_log_file(CNLP_ProblemPtr problem);
+
+    /**
+     * Set the print level of console output for a single journal category, overriding the
+     * "print_level" option for that category. The level is applied at the start of every
+     * subsequent solve. Returns 0 if the category or print level is invalid.
+     */
+    CNLP_API(CNLP_Bool) cnlp_set_category_print_level(CNLP_ProblemPtr problem,
+                                                      enum CNLP_JournalCategory category,
+                                                      CNLP_Int print_level);
+
     /**
      * Setting a callback function for the "intermediate callback" method in the TNLP.  This gives
      * control back to the user once per iteration.  If set, it provides the user with some
@@ -285,6 +420,94 @@ extern "C"
                                        // passed unmodified to the callback functions.
             );
 
+    /**
+     * Solve a problem previously solved with cnlp_solve again, reusing the internal data
+     * structures and symbolic factorization of the previous solve. Only numeric data such as
+     * bounds, function values and derivatives may change between the two solves; the sizes and
+     * sparsity structure must stay the same. If the problem has not been solved yet, this is
+     * equivalent to cnlp_solve.
+     */
+    CNLP_API(struct CNLP_SolveResult) cnlp_reoptimize(CNLP_ProblemPtr problem,
+                                                     CNLP_UserDataPtr user_data);
+
+    /** Statistics collected during the last solve. */
+    struct CNLP_SolveStats {
+        CNLP_Index iteration_count;       // Number of iterations
+        CNLP_Number total_cpu_time;       // Total CPU time in seconds
+        CNLP_Number total_sys_time;       // Total system time in seconds
+        CNLP_Number total_wallclock_time; // Total wallclock time in seconds
+        CNLP_Index num_obj_evals;         // Number of objective function evaluations
+        CNLP_Index num_constr_evals;      // Number of constraint function evaluations
+        CNLP_Index num_obj_grad_evals;    // Number of objective gradient evaluations
+        CNLP_Index num_constr_jac_evals;  // Number of constraint Jacobian evaluations
+        CNLP_Index num_hess_evals;        // Number of Lagrangian Hessian evaluations
+        CNLP_Number obj_val;              // Final (unscaled) objective value
+        CNLP_Number scaled_obj_val;       // Final scaled objective value
+        CNLP_Number dual_inf;             // Final (unscaled) dual infeasibility
+        CNLP_Number constr_viol;          // Final (unscaled) constraint violation
+        CNLP_Number complementarity;      // Final (unscaled) complementarity
+        CNLP_Number kkt_error;            // Final (unscaled) overall NLP error
+        CNLP_Index num_regularized_iterations; // Iterations with a regularized Hessian
+        CNLP_Number final_regularization; // Hessian regularization in the last iteration
+    };
+
+    /**
+     * Retrieve statistics of the last solve. Returns 0 if no statistics are available, for
+     * instance if the problem has not been solved yet or if the solve failed before the
+     * algorithm started.
+     */
+    CNLP_API(CNLP_Bool) cnlp_get_solve_stats(CNLP_ProblemPtr problem,
+                                             struct CNLP_SolveStats* stats);
+
+    /** Time spent in a task of the algorithm. */
+    struct CNLP_TaskTime {
+        CNLP_Number cpu_time;       // Total CPU time in seconds
+        CNLP_Number wallclock_time; // Total wallclock time in seconds
+    };
+
+    /** Breakdown of the time spent in the last solve, as printed by print_timing_statistics. */
+    struct CNLP_TimingStats {
+        struct CNLP_TaskTime overall_algorithm;            // The whole algorithm
+        struct CNLP_TaskTime function_evaluations;         // All function evaluations
+        struct CNLP_TaskTime objective;                    // Objective function
+        struct CNLP_TaskTime objective_gradient;           // Objective gradient
+        struct CNLP_TaskTime equality_constraints;         // Equality constraints
+        struct CNLP_TaskTime inequality_constraints;       // Inequality constraints
+        struct CNLP_TaskTime equality_jacobian;            // Equality constraint Jacobian
+        struct CNLP_TaskTime inequality_jacobian;          // Inequality constraint Jacobian
+        struct CNLP_TaskTime hessian;                      // Lagrangian Hessian
+        struct CNLP_TaskTime initialize_iterates;          // Computing the initial point
+        struct CNLP_TaskTime update_hessian;               // Hessian (or approximation) update
+        struct CNLP_TaskTime output_iteration;             // Iteration output
+        struct CNLP_TaskTime update_barrier_parameter;     // Barrier parameter update
+        struct CNLP_TaskTime compute_search_direction;     // Search direction
+        struct CNLP_TaskTime compute_acceptable_trial_point; // Line search
+        struct CNLP_TaskTime accept_trial_point;           // Accepting the trial point
+        struct CNLP_TaskTime check_convergence;            // Convergence check
+        struct CNLP_TaskTime pd_system_solve;              // Primal-dual system solves
+        struct CNLP_TaskTime compute_residuals;            // Residuals of the primal-dual system
+        struct CNLP_TaskTime linear_system_scaling;        // Linear system scaling
+        struct CNLP_TaskTime linear_system_symbolic_factorization; // Symbolic factorization
+        struct CNLP_TaskTime linear_system_factorization;  // Numeric factorization
+        struct CNLP_TaskTime linear_system_back_solve;     // Back solves
+    };
+
+    /**
+     * Retrieve the timing breakdown of the last solve. Returns 0 if the problem has not been
+     * solved yet, or if timing statistics were not collected. Ipopt 3.14 and newer only collect
+     * them if the timing_statistics or print_timing_statistics option is set to "yes".
+     */
+    CNLP_API(CNLP_Bool) cnlp_get_timing_stats(CNLP_ProblemPtr problem,
+                                              struct CNLP_TimingStats* stats);
+
+    /**
+     * Retrieve the primal iterate at which the last solve switched to the restoration phase
+     * along with the corresponding iteration number. The array x must have room for all
+     * variables. Returns 0 if the last solve did not enter the restoration phase.
+     */
+    CNLP_API(CNLP_Bool) cnlp_get_restoration_start(CNLP_ProblemPtr problem, CNLP_Number* x,
+                                                   CNLP_Index* iter);
+
     /**
      * Initialize the solution vectors in the nlp. Calling this is required before calling
      * cnlp_get_solver_data. This function will call the necessary initialization callbacks provided
@@ -1,8 +1,14 @@
 #include "nlp.hpp"
 #include <coin/IpIpoptApplication.hpp>
 #include <coin/IpBlas.hpp>
+#include <coin/IpIpoptCalculatedQuantities.hpp>
+#include <coin/IpIpoptData.hpp>
+#include <coin/IpNLPScaling.hpp>
+#include <coin/IpOrigIpoptNLP.hpp>
+#include <coin/IpTNLPAdapter.hpp>
 
 #include <algorithm>
+#include <string>
 
 /**
  * The following two functions provide safe conversion for return codes and modes in Ipopt.
@@ -48,6 +54,48 @@ CNLP_AlgorithmMode convert_algorithm_mode(Ipopt::AlgorithmMode mode)
     };
 }
 
+CNLP_FileJournal::CNLP_FileJournal(const std::string& name, Ipopt::EJournalLevel default_level)
+    : Ipopt::Journal(name, default_level)
+    , m_file(nullptr)
+{
+}
+
+CNLP_FileJournal::~CNLP_FileJournal() {
+    close();
+}
+
+bool CNLP_FileJournal::open(const char* file_name, bool append) {
+    close();
+    m_file = std::fopen(file_name, append ? "a" : "w");
+    return m_file != nullptr;
+}
+
+void CNLP_FileJournal::close() {
+    if (m_file) {
+        std::fclose(m_file);
+        m_file = nullptr;
+    }
+}
+
+void CNLP_FileJournal::PrintImpl(Ipopt::EJournalCategory, Ipopt::EJournalLevel, const char* str) {
+    if (m_file) {
+        std::fputs(str, m_file);
+    }
+}
+
+void CNLP_FileJournal::PrintfImpl(Ipopt::EJournalCategory, Ipopt::EJournalLevel,
+                                  const char* pformat, va_list ap) {
+    if (m_file) {
+        std::vfprintf(m_file, pformat, ap);
+    }
+}
+
+void CNLP_FileJournal::FlushBufferImpl() {
+    if (m_file) {
+        std::fflush(m_file);
+    }
+}
+
 CNLP_Problem::CNLP_Problem(
         Ipopt::SmartPtr<Ipopt::IpoptApplication> app,
         CNLP_Index index_style,
@@ -63,6 +111,12 @@ CNLP_Problem::CNLP_Problem(
     : TNLP()
     , m_app(app)
     , m_num_solves(0)
+    , m_last_status(Ipopt::Solve_Succeeded)
+    , m_prev_mode(Ipopt::RegularMode)
+    , m_resto_candidate_cq(nullptr)
+    , m_resto_candidate_iter(0)
+    , m_resto_start_iter(0)
+    , m_has_resto_start(false)
     , m_index_style(index_style)
     , m_sizes(sizes)
     , m_init(init)
@@ -76,6 +130,9 @@ CNLP_Problem::CNLP_Problem(
     , m_intermediate_cb(nullptr)
     , m_user_data(nullptr)
       , m_obj_sol(0.0)
+    , m_has_sens_sol(false)
+    , m_num_regularized_iterations(0)
+    , m_last_regularization(0.0)
 {
     ASSERT_EXCEPTION(m_index_style == 0 || m_index_style == 1, INVALID_NLP,
             "Valid index styles are 0 (C style) or 1 (Fortran style)");
@@ -135,6 +192,7 @@ CNLP_Number* CNLP_Problem::get_constraint_function_values() {
 }
 
 CNLP_Problem::~CNLP_Problem() {
+    close_log_file();
     m_app = nullptr;
 }
 
@@ -165,6 +223,11 @@ CNLP_SolveResult CNLP_Problem::build_solver_result(Ipopt::ApplicationReturnStatu
 
 CNLP_SolveResult CNLP_Problem::solve(CNLP_UserDataPtr user_data) {
     set_user_data(user_data);
+    m_has_sens_sol = false;
+    m_num_regularized_iterations = 0;
+    m_last_regularization = 0.0;
+    m_has_resto_start = false;
+    m_prev_mode = Ipopt::RegularMode;
     Ipopt::SmartPtr<TNLP> tnlp(this);
     this->AddRef(&tnlp); // Add an extra ref, since we don't want this deleted.
     Ipopt::ApplicationReturnStatus status;
@@ -176,9 +239,11 @@ CNLP_SolveResult CNLP_Problem::solve(CNLP_UserDataPtr user_data) {
             if (status != Ipopt::Solve_Succeeded) {
                 return build_solver_result(status);
             }
+            apply_category_print_levels();
             // Solve
             status = m_app->OptimizeTNLP(tnlp);
         } else {
+            apply_category_print_levels();
             // Re-solve
             status = m_app->ReOptimizeTNLP(tnlp);
         }
@@ -192,11 +257,190 @@ CNLP_SolveResult CNLP_Problem::solve(CNLP_UserDataPtr user_data) {
         status = Ipopt::Unrecoverable_Exception;
     }
 
+    // Don't hold on to the last regular iterate past the solve that produced it.
+    m_resto_candidate = nullptr;
+    m_resto_candidate_cq = nullptr;
+
     m_num_solves += 1;
+    m_last_status = status;
 
     return build_solver_result(status);
 }
 
+void CNLP_Problem::set_category_print_level(Ipopt::EJournalCategory category,
+                                            Ipopt::EJournalLevel level) {
+    m_category_print_levels[category] = level;
+}
+
+void CNLP_Problem::apply_category_print_levels() {
+    // The console journal is reset to "print_level" for all categories during Initialize.
+    Ipopt::SmartPtr<Ipopt::Journal> console = m_app->Jnlst()->GetJournal("console");
+    if (Ipopt::IsNull(console)) {
+        return;
+    }
+    for (const auto& entry : m_category_print_levels) {
+        console->SetPrintLevel(entry.first, entry.second);
+    }
+}
+
+bool CNLP_Problem::open_log_file(const char* file_name, Ipopt::EJournalLevel level, bool append) {
+    if (Ipopt::IsNull(m_log_journal)) {
+        m_log_journal = new CNLP_FileJournal("cnlp_log_file", Ipopt::J_NONE);
+        m_app->Jnlst()->AddJournal(Ipopt::GetRawPtr(m_log_journal));
+    }
+    m_log_journal->SetAllPrintLevels(Ipopt::J_NONE);
+    if (!m_log_journal->open(file_name, append)) {
+        return false;
+    }
+    m_log_journal->SetAllPrintLevels(level);
+    m_log_journal->SetPrintLevel(Ipopt::J_DBG, Ipopt::J_NONE);
+    return true;
+}
+
+void CNLP_Problem::close_log_file() {
+    if (Ipopt::IsValid(m_log_journal)) {
+        m_log_journal->SetAllPrintLevels(Ipopt::J_NONE);
+        m_log_journal->close();
+    }
+}
+
+CNLP_SolveResult CNLP_Problem::reoptimize(CNLP_UserDataPtr user_data) {
+    if (m_num_solves == 0) {
+        return solve(user_data);
+    }
+
+    // Only enable same structure warm starts for this solve, restoring the user's setting after.
+    const std::string name("warm_start_same_structure");
+    Ipopt::SmartPtr<Ipopt::OptionsList> options = m_app->Options();
+    std::string prev_value;
+    bool was_set = m_internal_options.count(name) == 0 && options->GetStringValue(name, prev_value, "");
+    options->SetStringValue(name, "yes");
+    CNLP_SolveResult res = solve(user_data);
+    if (was_set) {
+        options->SetStringValue(name, prev_value);
+    } else {
+        // Ipopt cannot unset an option, so restore the default and keep reporting it as such.
+        options->SetStringValue(name, m_app->RegOptions()->GetOption(name)->DefaultString());
+        m_internal_options.insert(name);
+    }
+    return res;
+}
+
+Ipopt::ApplicationReturnStatus CNLP_Problem::get_last_status() {
+    return m_last_status;
+}
+
+bool CNLP_Problem::set_sens_parameters(CNLP_Index num_params, const CNLP_Index* vars, const CNLP_Index* cons) {
+    if (m_num_solves > 0) {
+        return false;
+    }
+    m_sens_vars.assign(vars, vars + num_params);
+    m_sens_cons.assign(cons, cons + num_params);
+    m_has_sens_sol = false;
+    return true;
+}
+
+void CNLP_Problem::record_user_option(const std::string& name) {
+    m_internal_options.erase(name);
+    Ipopt::SmartPtr<const Ipopt::RegisteredOption> option = m_app->RegOptions()->GetOption(name);
+    if (Ipopt::IsNull(option)) {
+        return;
+    }
+    // Store the value as Ipopt reports it, so that it compares equal to the effective value.
+    get_option_value(*option, m_user_options[name]);
+}
+
+bool CNLP_Problem::get_option_value(const Ipopt::RegisteredOption& option,
+                                    CNLP_OptionValue& value) {
+    const std::string& name = option.Name();
+    switch (option.Type()) {
+        case Ipopt::OT_Number:
+            return m_app->Options()->GetNumericValue(name, value.num_value, "");
+        case Ipopt::OT_Integer:
+            return m_app->Options()->GetIntegerValue(name, value.int_value, "");
+        default:
+            return m_app->Options()->GetStringValue(name, value.str_value, "");
+    }
+}
+
+bool CNLP_Problem::visit_options(CNLP_Option_CB visit, CNLP_UserDataPtr user_data) {
+    if (m_num_solves == 0) {
+        return false;
+    }
+    const Ipopt::RegisteredOptions::RegOptionsList& options =
+        m_app->RegOptions()->RegisteredOptionsList();
+    for (const auto& entry : options) {
+        const Ipopt::RegisteredOption& option = *entry.second;
+        CNLP_OptionType type;
+        switch (option.Type()) {
+            case Ipopt::OT_Number:  type = CNLP_NUMBER_OPTION; break;
+            case Ipopt::OT_Integer: type = CNLP_INTEGER_OPTION; break;
+            case Ipopt::OT_String:  type = CNLP_STRING_OPTION; break;
+            default:                continue;
+        }
+
+        CNLP_OptionValue value = { 0.0, 0, std::string() };
+        CNLP_OptionSource source = CNLP_DEFAULT_VALUE;
+        if (get_option_value(option, value) && m_internal_options.count(entry.first) == 0) {
+            // An option set through the C API may have been overridden by the option file.
+            auto user = m_user_options.find(entry.first);
+            bool same = user != m_user_options.end();
+            if (same) {
+                switch (type) {
+                    case CNLP_NUMBER_OPTION:  same = user->second.num_value == value.num_value; break;
+                    case CNLP_INTEGER_OPTION: same = user->second.int_value == value.int_value; break;
+                    case CNLP_STRING_OPTION:  same = user->second.str_value == value.str_value; break;
+                }
+            }
+            source = same ? CNLP_USER_VALUE : CNLP_OPTION_FILE;
+        }
+        visit(entry.first.c_str(), type, source, value.num_value, value.int_value,
+              value.str_value.c_str(), user_data);
+    }
+    return true;
+}
+
+CNLP_Index CNLP_Problem::get_num_regularized_iterations() const {
+    return m_num_regularized_iterations;
+}
+
+CNLP_Number CNLP_Problem::get_last_regularization() const {
+    return m_last_regularization;
+}
+
+bool CNLP_Problem::get_restoration_start(CNLP_Number* x, CNLP_Index* iter) {
+    if (!m_has_resto_start) {
+        return false;
+    }
+    std::copy(m_resto_start.begin(), m_resto_start.end(), x);
+    *iter = m_resto_start_iter;
+    return true;
+}
+
+CNLP_Index CNLP_Problem::get_num_sens_parameters() {
+    return static_cast<CNLP_Index>(m_sens_vars.size());
+}
+
+bool CNLP_Problem::get_sens_derivatives(CNLP_Number* dx_dp, CNLP_Number* dlambda_dp) {
+    if (!m_has_sens_sol) {
+        return false;
+    }
+
+    // Step zero is unperturbed, step j+1 perturbs parameter j by one. Since the sensitivity
+    // update is linear in the perturbation, differences between steps are exact derivatives.
+    std::size_t n = m_x_sol.size();
+    std::size_t m = m_lambda_sol.size();
+    for (std::size_t j = 0; j < m_sens_vars.size(); ++j) {
+        for (std::size_t i = 0; i < n; ++i) {
+            dx_dp[j*n + i] = m_sens_x[(j+1)*n + i] - m_sens_x[i];
+        }
+        for (std::size_t i = 0; i < m; ++i) {
+            dlambda_dp[j*m + i] = m_sens_lambda[(j+1)*m + i] - m_sens_lambda[i];
+        }
+    }
+    return true;
+}
+
 bool CNLP_Problem::get_nlp_info(
         Ipopt::Index& n, Ipopt::Index& m, Ipopt::Index& nnz_jac_g,
         Ipopt::Index& nnz_h_lag, IndexStyleEnum& index_style)
@@ -326,6 +570,28 @@ bool CNLP_Problem::intermediate_callback(
         const Ipopt::IpoptData* ip_data,
         Ipopt::IpoptCalculatedQuantities* ip_cq)
 {
+    if (regularization_size > 0.0) {
+        m_num_regularized_iterations += 1;
+    }
+    m_last_regularization = regularization_size;
+
+    if (mode == Ipopt::RegularMode) {
+        // Keep a reference to the current iterate in case the next iteration falls back to
+        // restoration. Iterates are never modified, so this doesn't copy anything; the iterate
+        // is only mapped to the original variables once restoration starts.
+        if (ip_data != nullptr && Ipopt::IsValid(ip_data->curr())) {
+            m_resto_candidate = ip_data->curr()->x();
+        } else {
+            m_resto_candidate = nullptr;
+        }
+        m_resto_candidate_cq = ip_cq;
+        m_resto_candidate_iter = iter;
+    } else if (m_prev_mode == Ipopt::RegularMode && Ipopt::IsValid(m_resto_candidate)) {
+        m_has_resto_start = get_original_x(m_resto_candidate_cq, m_resto_candidate, m_resto_start);
+        m_resto_start_iter = m_resto_candidate_iter;
+    }
+    m_prev_mode = mode;
+
     CNLP_Bool retval = 1;
     if (m_intermediate_cb && *m_intermediate_cb) {
         retval = (**m_intermediate_cb)(convert_algorithm_mode(mode), iter, obj_value, inf_pr, inf_du,
@@ -335,6 +601,30 @@ bool CNLP_Problem::intermediate_callback(
     return (retval!=0);
 }
 
+bool CNLP_Problem::get_original_x(Ipopt::IpoptCalculatedQuantities* ip_cq,
+                                  const Ipopt::SmartPtr<const Ipopt::Vector>& x_internal,
+                                  std::vector<CNLP_Number>& x) {
+    if (ip_cq == nullptr) {
+        return false;
+    }
+    Ipopt::OrigIpoptNLP* orig_nlp =
+        dynamic_cast<Ipopt::OrigIpoptNLP*>(Ipopt::GetRawPtr(ip_cq->GetIpoptNLP()));
+    if (orig_nlp == nullptr) {
+        return false;
+    }
+    Ipopt::TNLPAdapter* adapter =
+        dynamic_cast<Ipopt::TNLPAdapter*>(Ipopt::GetRawPtr(orig_nlp->nlp()));
+    if (adapter == nullptr) {
+        return false;
+    }
+    // Internal iterates are scaled and exclude fixed variables, so map them back.
+    Ipopt::SmartPtr<const Ipopt::Vector> x_unscaled =
+        orig_nlp->NLP_scaling()->unapply_vector_scaling_x(x_internal);
+    x.resize(m_x_sol.size());
+    adapter->ResortX(*x_unscaled, x.data());
+    return true;
+}
+
 void CNLP_Problem::finalize_solution(
         Ipopt::SolverReturn status,
         Ipopt::Index n, const Ipopt::Number* x, const Ipopt::Number* z_L, const Ipopt::Number* z_U,
@@ -358,3 +648,75 @@ void CNLP_Problem::finalize_solution(
     // don't need to store the status, we get the status from the OptimizeTNLP method
 }
 
+bool CNLP_Problem::get_var_con_metadata(
+        Ipopt::Index n,
+        StringMetaDataMapType& var_string_md,
+        IntegerMetaDataMapType& var_integer_md,
+        NumericMetaDataMapType& var_numeric_md,
+        Ipopt::Index m,
+        StringMetaDataMapType& con_string_md,
+        IntegerMetaDataMapType& con_integer_md,
+        NumericMetaDataMapType& con_numeric_md)
+{
+    if (m_sens_vars.empty()) {
+        return false;
+    }
+
+    // Parameters are perturbed relative to the initial point.
+    std::vector<Ipopt::Number> x0(n, 0.0);
+    if (!get_starting_point(n, true, x0.data(), false, nullptr, nullptr, m, false, nullptr)) {
+        return false;
+    }
+
+    std::vector<Ipopt::Index> init_constr(m, 0);
+    std::vector<Ipopt::Index> state(n, 0);
+    for (std::size_t j = 0; j < m_sens_vars.size(); ++j) {
+        init_constr[m_sens_cons[j]] = j + 1;
+        state[m_sens_vars[j]] = j + 1;
+    }
+    con_integer_md["sens_init_constr"] = init_constr;
+
+    for (std::size_t k = 0; k <= m_sens_vars.size(); ++k) {
+        std::vector<Ipopt::Number> value(n, 0.0);
+        for (std::size_t j = 0; j < m_sens_vars.size(); ++j) {
+            value[m_sens_vars[j]] = x0[m_sens_vars[j]] + (k == j + 1 ? 1.0 : 0.0);
+        }
+        std::string step = std::to_string(k + 1);
+        var_integer_md["sens_state_" + step] = state;
+        var_numeric_md["sens_state_value_" + step] = value;
+    }
+    return true;
+}
+
+void CNLP_Problem::finalize_metadata(
+        Ipopt::Index n,
+        const StringMetaDataMapType& var_string_md,
+        const IntegerMetaDataMapType& var_integer_md,
+        const NumericMetaDataMapType& var_numeric_md,
+        Ipopt::Index m,
+        const StringMetaDataMapType& con_string_md,
+        const IntegerMetaDataMapType& con_integer_md,
+        const NumericMetaDataMapType& con_numeric_md)
+{
+    if (m_sens_vars.empty()) {
+        return;
+    }
+
+    std::size_t num_steps = m_sens_vars.size() + 1;
+    std::vector<CNLP_Number> sens_x(num_steps*n, 0.0);
+    std::vector<CNLP_Number> sens_lambda(num_steps*m, 0.0);
+    for (std::size_t k = 0; k < num_steps; ++k) {
+        std::string name = "sens_sol_state_" + std::to_string(k + 1);
+        NumericMetaDataMapType::const_iterator x_it = var_numeric_md.find(name);
+        NumericMetaDataMapType::const_iterator lambda_it = con_numeric_md.find(name);
+        if (x_it == var_numeric_md.end() || lambda_it == con_numeric_md.end()) {
+            // This is the regular finalization before sIpopt has run.
+            return;
+        }
+        std::copy(x_it->second.begin(), x_it->second.end(), sens_x.begin() + k*n);
+        std::copy(lambda_it->second.begin(), lambda_it->second.end(), sens_lambda.begin() + k*m);
+    }
+    m_sens_x.swap(sens_x);
+    m_sens_lambda.swap(sens_lambda);
+    m_has_sens_sol = true;
+}
@@ -7,13 +7,58 @@
 #include <coin/IpTNLP.hpp>
 #include <coin/IpException.hpp>
 #include <coin/IpSmartPtr.hpp>
+#include <coin/IpVector.hpp>
 #include <coin/IpIpoptApplication.hpp>
+#include <coin/IpJournalist.hpp>
+#include <cstdarg>
+#include <cstdio>
+#include <map>
+#include <set>
+#include <string>
 #include <vector>
 
 /** Declare excpetion that is thrown when invalid NLP data
 *  is provided */
 DECLARE_STD_EXCEPTION(INVALID_NLP);
 
+/**
+ * A journal writing to a file that can be reopened between solves, either truncating or
+ * appending to the target file.
+ */
+class CNLP_FileJournal : public Ipopt::Journal
+{
+public:
+    CNLP_FileJournal(const std::string& name, Ipopt::EJournalLevel default_level);
+    virtual ~CNLP_FileJournal();
+
+    /** Redirect output to the given file. Returns false if the file could not be opened. */
+    bool open(const char* file_name, bool append);
+
+    /** Stop writing output. */
+    void close();
+
+protected:
+    /** Journal overloads */
+    //@{
+    virtual void PrintImpl(Ipopt::EJournalCategory category, Ipopt::EJournalLevel level,
+                           const char* str);
+    virtual void PrintfImpl(Ipopt::EJournalCategory category, Ipopt::EJournalLevel level,
+                            const char* pformat, va_list ap);
+    virtual void FlushBufferImpl();
+    //@}
+
+private:
+    FILE* m_file;
+};
+
+/** A value of an Ipopt option. Only the member matching the type of the option is used. */
+struct CNLP_OptionValue
+{
+    CNLP_Number num_value;
+    CNLP_Int int_value;
+    std::string str_value;
+};
+
 struct CNLP_Problem : public Ipopt::TNLP
 {
 public:
@@ -62,6 +107,74 @@ public:
 
     CNLP_SolveResult solve(CNLP_UserDataPtr user_data);
 
+    /**
+     * Solve a problem with the same structure as in the previous call to solve. This reuses
+     * the internal data structures and symbolic factorization set up by the previous solve. If
+     * the problem has not been solved yet, this is equivalent to solve.
+     */
+    CNLP_SolveResult reoptimize(CNLP_UserDataPtr user_data);
+
+    /**
+     * Override the console print level of a journal category. The overrides are applied after
+     * the "print_level" option at the start of every solve.
+     */
+    void set_category_print_level(Ipopt::EJournalCategory category, Ipopt::EJournalLevel level);
+
+    /**
+     * Write output to the given file at the given print level in addition to the console,
+     * replacing any file previously opened this way. Returns false if the file could not be
+     * opened.
+     */
+    bool open_log_file(const char* file_name, Ipopt::EJournalLevel level, bool append);
+
+    /** Stop writing output to the file opened with open_log_file. */
+    void close_log_file();
+
+    /** Number of iterations of the last solve in which the Hessian was regularized. */
+    CNLP_Index get_num_regularized_iterations() const;
+
+    /** Hessian regularization used in the last iteration of the last solve. */
+    CNLP_Number get_last_regularization() const;
+
+    /**
+     * Get the iterate at which the last solve switched to the restoration phase, along with the
+     * iteration number. Returns false if the last solve never entered restoration.
+     */
+    bool get_restoration_start(CNLP_Number* x, CNLP_Index* iter);
+
+    /**
+     * Remember the value of an option that was just set through the C API, so that it can be
+     * told apart from a value set in the option file.
+     */
+    void record_user_option(const std::string& name);
+
+    /**
+     * Call visit with the effective value and source of every registered option. Returns false
+     * if the problem has not been solved yet, since the option file is read by the first solve.
+     */
+    bool visit_options(CNLP_Option_CB visit, CNLP_UserDataPtr user_data);
+
+    /** Status returned by the last call to solve. */
+    Ipopt::ApplicationReturnStatus get_last_status();
+
+    /**
+     * Declare parameters for sIpopt. Each parameter is a variable fixed by an equality
+     * constraint; both are given by zero-based indices. This must be called before the first
+     * solve, since metadata is only queried when the problem is first set up, so false is
+     * returned otherwise.
+     */
+    bool set_sens_parameters(CNLP_Index num_params, const CNLP_Index* vars, const CNLP_Index* cons);
+
+    /**
+     * Retrieve derivatives of the primal variables and constraint multipliers with respect to
+     * each declared parameter, stored parameter by parameter. Returns false if sIpopt did not
+     * report perturbed solutions.
+     */
+    bool get_sens_derivatives(CNLP_Number* dx_dp, CNLP_Number* dlambda_dp);
+
+    /** Number of parameters declared for sIpopt. */
+    CNLP_Index get_num_sens_parameters();
+
     /**@name methods to gather information about the NLP. These methods are
      * overloaded from TNLP. See TNLP for their more detailed documentation. */
     //@{
@@ -123,6 +236,16 @@ public:
                                        Ipopt::Index ls_trials,
                                        const Ipopt::IpoptData* ip_data,
                                        Ipopt::IpoptCalculatedQuantities* ip_cq);
+
+    /** Provides sIpopt metadata for declared parameters. Overloaded from TNLP */
+    virtual bool get_var_con_metadata(Ipopt::Index n,
+                                      StringMetaDataMapType& var_string_md,
+                                      IntegerMetaDataMapType& var_integer_md,
+                                      NumericMetaDataMapType& var_numeric_md,
+                                      Ipopt::Index m,
+                                      StringMetaDataMapType& con_string_md,
+                                      IntegerMetaDataMapType& con_integer_md,
+                                      NumericMetaDataMapType& con_numeric_md);
     //@}
 
     /** @name Solution Methods */
@@ -133,6 +256,16 @@ public:
                                    Ipopt::Number obj_value,
                                    const Ipopt::IpoptData* ip_data,
                                    Ipopt::IpoptCalculatedQuantities* ip_cq);
+
+    /** Collects perturbed solutions computed by sIpopt. Overloaded from TNLP */
+    virtual void finalize_metadata(Ipopt::Index n,
+                                   const StringMetaDataMapType& var_string_md,
+                                   const IntegerMetaDataMapType& var_integer_md,
+                                   const NumericMetaDataMapType& var_numeric_md,
+                                   Ipopt::Index m,
+                                   const StringMetaDataMapType& con_string_md,
+                                   const IntegerMetaDataMapType& con_integer_md,
+                                   const NumericMetaDataMapType& con_numeric_md);
     //@}
 private:
     /** 
@@ -141,9 +274,29 @@ private:
      */
     CNLP_SolveResult build_solver_result(Ipopt::ApplicationReturnStatus status);
 
+    /**
+     * Map an iterate of the solver's internal, scaled variables to the original problem
+     * variables. Returns false if the quantities are not those of the original problem, e.g.
+     * during restoration.
+     */
+    bool get_original_x(Ipopt::IpoptCalculatedQuantities* ip_cq,
+                        const Ipopt::SmartPtr<const Ipopt::Vector>& x_internal,
+                        std::vector<CNLP_Number>& x);
+
+    /** Apply the print level overrides to the console journal. */
+    void apply_category_print_levels();
+
+    /** Get the current value of an option. Returns false if the option has not been set. */
+    bool get_option_value(const Ipopt::RegisteredOption& option, CNLP_OptionValue& value);
+
 private:
     Ipopt::SmartPtr<Ipopt::IpoptApplication> m_app; // The application sets solver options
     std::size_t m_num_solves; // CNLP_Number of times the Solver ran for this instance
+    Ipopt::ApplicationReturnStatus m_last_status; // Status of the last solve
+    std::map<Ipopt::EJournalCategory, Ipopt::EJournalLevel> m_category_print_levels; // Console overrides
+    Ipopt::SmartPtr<CNLP_FileJournal> m_log_journal; // Journal writing to the log file
+    std::map<std::string, CNLP_OptionValue> m_user_options; // Options set through the C API
+    std::set<std::string> m_internal_options; // Options only ever set by cnlp itself
 
     const CNLP_Index m_index_style; // Starting value of the iRow and jCol parameters for matrices
 
@@ -170,6 +323,29 @@ private:
     CNLP_Number m_obj_sol;
     //@}
 
+    /** sIpopt data */
+    //@{
+    std::vector<CNLP_Index> m_sens_vars; // Variables representing parameters
+    std::vector<CNLP_Index> m_sens_cons; // Constraints fixing the parameters
+    std::vector<CNLP_Number> m_sens_x; // Perturbed primal solutions, one per sensitivity step
+    std::vector<CNLP_Number> m_sens_lambda; // Perturbed multipliers, one per sensitivity step
+    bool m_has_sens_sol; // Whether perturbed solutions were reported
+    //@}
+
+    CNLP_Index m_num_regularized_iterations; // Iterations with a regularized Hessian
+    CNLP_Number m_last_regularization; // Hessian regularization in the last iteration
+
+    /** Restoration phase data */
+    //@{
+    Ipopt::AlgorithmMode m_prev_mode; // Algorithm mode in the previous iteration
+    Ipopt::SmartPtr<const Ipopt::Vector> m_resto_candidate; // Latest regular iterate (internal)
+    Ipopt::IpoptCalculatedQuantities* m_resto_candidate_cq; // Quantities of the latest iterate
+    CNLP_Index m_resto_candidate_iter; // Iteration of the latest regular iterate
+    std::vector<CNLP_Number> m_resto_start; // Iterate at which restoration started
+    CNLP_Index m_resto_start_iter; // Iteration at which restoration started
+    bool m_has_resto_start; // Whether restoration started during the last solve
+    //@}
+
     /** Overloaded Equals Operator */
     void operator=(const CNLP_Problem&);
 
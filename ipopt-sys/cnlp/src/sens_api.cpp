@@ -0,0 +1,60 @@
+#include "sens_api.h"
+#include "nlp.hpp"
+
+#include <coin/SensApplication.hpp>
+#include <coin/SensRegOp.hpp>
+
+CNLP_Bool cnlp_set_sens_parameters(CNLP_ProblemPtr problem,
+                                   CNLP_Index num_params,
+                                   const CNLP_Index* param_vars,
+                                   const CNLP_Index* param_cons)
+{
+    Ipopt::IpoptApplication* app = problem->get_app();
+
+    // sIpopt options are not registered by a plain IpoptApplication.
+    if (!Ipopt::IsValid(app->RegOptions()->GetOption("run_sens"))) {
+        Ipopt::RegisterOptions_sIpopt(app->RegOptions());
+    }
+
+    if (!problem->set_sens_parameters(num_params, param_vars, param_cons)) {
+        return 0;
+    }
+
+    // One unperturbed step followed by one step per parameter.
+    bool ok = app->Options()->SetStringValue("run_sens", "yes");
+    ok = ok && app->Options()->SetIntegerValue("n_sens_steps", num_params + 1);
+    return (CNLP_Bool) ok;
+}
+
+CNLP_SolveResult cnlp_sens_solve(CNLP_ProblemPtr problem, CNLP_UserDataPtr user_data)
+{
+    CNLP_SolveResult result = problem->solve(user_data);
+
+    Ipopt::SmartPtr<Ipopt::IpoptApplication> app = problem->get_app();
+    Ipopt::SmartPtr<Ipopt::SensApplication> sens_app =
+        new Ipopt::SensApplication(app->Jnlst(), app->Options(), app->RegOptions());
+
+    try {
+        sens_app->Initialize();
+        sens_app->SetIpoptAlgorithmObjects(app, problem->get_last_status());
+        // The perturbed solutions are passed back to the problem through finalize_metadata.
+        sens_app->Run();
+    }
+    catch (Ipopt::IpoptException& e) {
+        e.ReportException(*app->Jnlst(), Ipopt::J_ERROR);
+    }
+
+    return result;
+}
+
+CNLP_Index cnlp_get_num_sens_parameters(CNLP_ProblemPtr problem)
+{
+    return problem->get_num_sens_parameters();
+}
+
+CNLP_Bool cnlp_get_sens_derivatives(CNLP_ProblemPtr problem,
+                                    CNLP_Number* dx_dp,
+                                    CNLP_Number* dlambda_dp)
+{
+    return (CNLP_Bool) problem->get_sens_derivatives(dx_dp, dlambda_dp);
+}
@@ -0,0 +1,58 @@
+#ifndef __IPOPT_SYS_SENS_API_H__
+#define __IPOPT_SYS_SENS_API_H__
+
+#include "c_api.h"
+
+#ifndef CNLP_API
+#ifdef _MSC_VER
+#define CNLP_API(type) __declspec(dllexport) type __cdecl
+#else
+#define CNLP_API(type) type
+#endif
+#endif
+
+#ifdef __cplusplus
+extern "C"
+{
+#endif
+
+    /**
+     * Declare parameters for parametric sensitivity analysis with sIpopt. Following the sIpopt
+     * convention, each parameter is a variable fixed by an equality constraint, given by the
+     * zero-based indices in param_vars and param_cons respectively.
+     *
+     * This registers and enables the sIpopt options, and must be called before the problem is
+     * first solved. Returns false if the problem has already been solved or the sIpopt options
+     * could not be set.
+     */
+    CNLP_API(CNLP_Bool) cnlp_set_sens_parameters(CNLP_ProblemPtr problem
+                                                , CNLP_Index num_params
+                                                , const CNLP_Index* param_vars
+                                                , const CNLP_Index* param_cons);
+
+    /**
+     * Solve the problem like cnlp_solve and subsequently run the sIpopt sensitivity analysis
+     * for the parameters declared with cnlp_set_sens_parameters.
+     */
+    CNLP_API(struct CNLP_SolveResult) cnlp_sens_solve(CNLP_ProblemPtr problem
+                                                     , CNLP_UserDataPtr user_data);
+
+    /** Number of parameters declared with cnlp_set_sens_parameters. */
+    CNLP_API(CNLP_Index) cnlp_get_num_sens_parameters(CNLP_ProblemPtr problem);
+
+    /**
+     * Retrieve the derivatives of the optimal primal variables and constraint multipliers with
+     * respect to each parameter computed during the last call to cnlp_sens_solve. The outputs
+     * must have room for num_params*n and num_params*m values respectively, and are stored
+     * parameter by parameter. Returns false if no sensitivities are available.
+     */
+    CNLP_API(CNLP_Bool) cnlp_get_sens_derivatives(CNLP_ProblemPtr problem
+                                                 , CNLP_Number* dx_dp
+                                                 , CNLP_Number* dlambda_dp);
+
+#ifdef __cplusplus
+} /* extern "C" { */
+#endif
+
+#undef CNLP_API
+#endif
@@ -0,0 +1,339 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Classification of variable bounds and constraints as active or inactive at a solution.
+//!
+//! A bound or constraint is considered active if the corresponding value lies within a tolerance
+//! of the bound. Active entries are further split into strongly active entries, which have a
+//! non-zero multiplier, and weakly active entries, whose multiplier vanishes (within a tolerance).
+//! Weakly active entries indicate degeneracy, which is relevant for sensitivity analysis and for
+//! deciding how to warm start subsequent solves.
+
+use crate::{BasicProblem, ConstrainedProblem, GroupActivity, Number, Solution};
+
+/// Any bound with magnitude at least this large is treated as infinite.
+///
+/// This mirrors the default values of the `nlp_lower_bound_inf` and `nlp_upper_bound_inf` Ipopt
+/// options.
+pub(crate) const BOUND_INF: Number = 1e19;
+
+/// Tolerances used to classify bounds and constraints at a solution.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ActiveSetTolerance {
+    /// A bound is active if the value is within this distance from the bound.
+    ///
+    /// The distance is relative for bounds with magnitude larger than one.
+    pub activity: Number,
+    /// An active bound is strongly active if the magnitude of its multiplier is larger than this
+    /// value, and weakly active otherwise.
+    pub multiplier: Number,
+}
+
+impl Default for ActiveSetTolerance {
+    fn default() -> Self {
+        ActiveSetTolerance {
+            activity: 1e-6,
+            multiplier: 1e-6,
+        }
+    }
+}
+
+/// Which side of the bounds is active.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ActiveSide {
+    /// The value is at its lower bound.
+    Lower,
+    /// The value is at its upper bound.
+    Upper,
+    /// The lower and upper bounds coincide (equality constraint or fixed variable).
+    Equality,
+}
+
+/// The activity of a single bound or constraint.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Activity {
+    /// The value lies strictly between its bounds.
+    Inactive,
+    /// The value is at a bound, but the corresponding multiplier is zero.
+    WeaklyActive,
+    /// The value is at a bound and the corresponding multiplier is non-zero.
+    StronglyActive,
+}
+
+/// An active variable bound or constraint.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ActiveEntry {
+    /// Index of the variable or constraint.
+    pub index: usize,
+    /// The active side.
+    pub side: ActiveSide,
+    /// Whether this entry is strongly or weakly active.
+    pub activity: Activity,
+    /// Value of the variable or constraint function at the solution.
+    pub value: Number,
+    /// The multiplier associated with the active bound.
+    ///
+    /// For variables this is the lower or upper bound multiplier for the active side, and the
+    /// difference `z_l - z_u` for fixed variables. For constraints this is the constraint
+    /// multiplier as returned by Ipopt.
+    pub multiplier: Number,
+}
+
+/// The active set report at a solution.
+///
+/// Only active entries are recorded; any variable or constraint not listed here is inactive.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActiveSet {
+    /// Active variable bounds.
+    pub variables: Vec<ActiveEntry>,
+    /// Active constraints.
+    pub constraints: Vec<ActiveEntry>,
+    /// Number of active constraints in each constraint group.
+    ///
+    /// This is empty if the problem doesn't define
+    /// [`constraint_groups`](trait.ConstrainedProblem.html#method.constraint_groups).
+    pub groups: Vec<GroupActivity>,
+}
+
+impl ActiveSet {
+    /// Strongly active variable bounds followed by strongly active constraints.
+    pub fn strongly_active(&self) -> impl Iterator<Item = &ActiveEntry> {
+        self.variables
+            .iter()
+            .chain(self.constraints.iter())
+            .filter(|e| e.activity == Activity::StronglyActive)
+    }
+
+    /// Weakly active variable bounds followed by weakly active constraints.
+    pub fn weakly_active(&self) -> impl Iterator<Item = &ActiveEntry> {
+        self.variables
+            .iter()
+            .chain(self.constraints.iter())
+            .filter(|e| e.activity == Activity::WeaklyActive)
+    }
+
+    /// Returns `true` if any bound or constraint is weakly active.
+    ///
+    /// This means that strict complementarity does not hold at the solution.
+    pub fn is_degenerate(&self) -> bool {
+        self.weakly_active().next().is_some()
+    }
+
+    /// The activity of the bounds for the variable at the given index.
+    pub fn variable_activity(&self, index: usize) -> Activity {
+        Self::activity_of(&self.variables, index)
+    }
+
+    /// The activity of the constraint at the given index.
+    pub fn constraint_activity(&self, index: usize) -> Activity {
+        Self::activity_of(&self.constraints, index)
+    }
+
+    fn activity_of(entries: &[ActiveEntry], index: usize) -> Activity {
+        entries
+            .binary_search_by_key(&index, |e| e.index)
+            .map(|i| entries[i].activity)
+            .unwrap_or(Activity::Inactive)
+    }
+
+    /// Classify variables given their bounds and bound multipliers.
+    fn classify_variables(
+        x: &[Number],
+        x_l: &[Number],
+        x_u: &[Number],
+        z_l: &[Number],
+        z_u: &[Number],
+        tol: ActiveSetTolerance,
+    ) -> Vec<ActiveEntry> {
+        (0..x.len())
+            .filter_map(|i| {
+                classify(x[i], x_l[i], x_u[i], tol).map(|side| {
+                    let multiplier = match side {
+                        ActiveSide::Lower => z_l[i],
+                        ActiveSide::Upper => z_u[i],
+                        ActiveSide::Equality => z_l[i] - z_u[i],
+                    };
+                    ActiveEntry::new(i, side, x[i], multiplier, tol)
+                })
+            })
+            .collect()
+    }
+
+    /// Classify constraints given their bounds and multipliers.
+    fn classify_constraints(
+        g: &[Number],
+        g_l: &[Number],
+        g_u: &[Number],
+        lambda: &[Number],
+        tol: ActiveSetTolerance,
+    ) -> Vec<ActiveEntry> {
+        (0..g.len())
+            .filter_map(|i| {
+                classify(g[i], g_l[i], g_u[i], tol)
+                    .map(|side| ActiveEntry::new(i, side, g[i], lambda[i], tol))
+            })
+            .collect()
+    }
+}
+
+impl ActiveEntry {
+    fn new(
+        index: usize,
+        side: ActiveSide,
+        value: Number,
+        multiplier: Number,
+        tol: ActiveSetTolerance,
+    ) -> Self {
+        ActiveEntry {
+            index,
+            side,
+            activity: if multiplier.abs() > tol.multiplier {
+                Activity::StronglyActive
+            } else {
+                Activity::WeaklyActive
+            },
+            value,
+            multiplier,
+        }
+    }
+}
+
+/// Determine which side of the bounds, if any, is active for the given value.
+fn classify(
+    value: Number,
+    lower: Number,
+    upper: Number,
+    tol: ActiveSetTolerance,
+) -> Option<ActiveSide> {
+    let near = |bound: Number| (value - bound).abs() <= tol.activity * bound.abs().max(1.0);
+    let has_lower = lower > -BOUND_INF;
+    let has_upper = upper < BOUND_INF;
+    if has_lower && has_upper && lower == upper {
+        Some(ActiveSide::Equality)
+    } else if has_lower && near(lower) {
+        Some(ActiveSide::Lower)
+    } else if has_upper && near(upper) {
+        Some(ActiveSide::Upper)
+    } else {
+        None
+    }
+}
+
+impl<'a> Solution<'a> {
+    /// Compute the active set of the variable bounds at this solution.
+    ///
+    /// Constraints are ignored, which makes this function suitable for problems that don't
+    /// implement [`ConstrainedProblem`](trait.ConstrainedProblem.html).
+    ///
+    /// Returns `None` if the bounds could not be retrieved from the problem.
+    pub fn bound_active_set<P: BasicProblem>(
+        &self,
+        problem: &P,
+        tol: ActiveSetTolerance,
+    ) -> Option<ActiveSet> {
+        let n = self.primal_variables.len();
+        let mut x_l = vec![0.0; n];
+        let mut x_u = vec![0.0; n];
+        if !problem.bounds(&mut x_l, &mut x_u) {
+            return None;
+        }
+
+        Some(ActiveSet {
+            variables: ActiveSet::classify_variables(
+                self.primal_variables,
+                &x_l,
+                &x_u,
+                self.lower_bound_multipliers,
+                self.upper_bound_multipliers,
+                tol,
+            ),
+            constraints: Vec::new(),
+            groups: Vec::new(),
+        })
+    }
+
+    /// Compute the active set of variable bounds and constraints at this solution.
+    ///
+    /// The constraint function is evaluated at the primal variables of this solution to determine
+    /// which constraints are active. Active constraints are also counted per group of
+    /// [`constraint_groups`](trait.ConstrainedProblem.html#method.constraint_groups).
+    ///
+    /// Returns `None` if the bounds or the constraint values could not be retrieved from the
+    /// problem.
+    pub fn active_set<P: ConstrainedProblem>(
+        &self,
+        problem: &P,
+        tol: ActiveSetTolerance,
+    ) -> Option<ActiveSet> {
+        let mut active_set = self.bound_active_set(problem, tol)?;
+
+        let m = self.constraint_multipliers.len();
+        let mut g = vec![0.0; m];
+        let mut g_l = vec![0.0; m];
+        let mut g_u = vec![0.0; m];
+        if !problem.constraint_bounds(&mut g_l, &mut g_u)
+            || !problem.constraint(self.primal_variables, &mut g)
+        {
+            return None;
+        }
+
+        active_set.constraints =
+            ActiveSet::classify_constraints(&g, &g_l, &g_u, self.constraint_multipliers, tol);
+        active_set.groups = problem.constraint_groups().summarize_activity(&active_set);
+        Some(active_set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classification_test() {
+        let tol = ActiveSetTolerance::default();
+        let x = [1.0, 2.5, 5.0, 3.0, 0.0];
+        let x_l = [1.0, 1.0, 1.0, 3.0, -2e19];
+        let x_u = [5.0, 5.0, 5.0, 3.0, 2e19];
+        let z_l = [1.5, 0.0, 0.0, 2.0, 0.0];
+        let z_u = [0.0, 0.0, 1e-9, 0.5, 0.0];
+
+        let variables = ActiveSet::classify_variables(&x, &x_l, &x_u, &z_l, &z_u, tol);
+        let active_set = ActiveSet {
+            variables,
+            constraints: ActiveSet::classify_constraints(
+                &[25.0, 10.0],
+                &[25.0, -1e20],
+                &[2e19, 40.0],
+                &[-0.5, 0.0],
+                tol,
+            ),
+            groups: Vec::new(),
+        };
+
+        assert_eq!(active_set.variable_activity(0), Activity::StronglyActive);
+        assert_eq!(active_set.variable_activity(1), Activity::Inactive);
+        assert_eq!(active_set.variable_activity(2), Activity::WeaklyActive);
+        assert_eq!(active_set.variable_activity(3), Activity::StronglyActive);
+        assert_eq!(active_set.variables[2].side, ActiveSide::Equality);
+        assert_eq!(active_set.variables[2].multiplier, 1.5);
+        assert_eq!(active_set.variable_activity(4), Activity::Inactive);
+
+        assert_eq!(active_set.constraint_activity(0), Activity::StronglyActive);
+        assert_eq!(active_set.constraints[0].side, ActiveSide::Lower);
+        assert_eq!(active_set.constraint_activity(1), Activity::Inactive);
+
+        assert!(active_set.is_degenerate());
+        assert_eq!(active_set.strongly_active().count(), 3);
+    }
+}
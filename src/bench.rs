@@ -0,0 +1,436 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! A benchmark harness for comparing Ipopt configurations.
+//!
+//! A [`Benchmark`](struct.Benchmark.html) solves a set of problems with every configuration in a
+//! list of option sets, typically generated from an [`OptionGrid`](struct.OptionGrid.html) over
+//! linear solvers, scaling methods and tolerances. The resulting
+//! [`BenchReport`](struct.BenchReport.html) records iterations, time and status of each solve
+//! and prints as a comparison table of all configurations.
+//!
+//! ```no_run
+//! use ipopt::*;
+//! # struct NLP;
+//! # impl BasicProblem for NLP {
+//! #     fn num_variables(&self) -> usize { 1 }
+//! #     fn bounds(&self, _: &mut [Number], _: &mut [Number]) -> bool { true }
+//! #     fn initial_point(&self, _: &mut [Number]) -> bool { true }
+//! #     fn objective(&self, _: &[Number], _: &mut Number) -> bool { true }
+//! #     fn objective_grad(&self, _: &[Number], _: &mut [Number]) -> bool { true }
+//! # }
+//! # impl ConstrainedProblem for NLP {
+//! #     fn num_constraints(&self) -> usize { 0 }
+//! #     fn num_constraint_jacobian_non_zeros(&self) -> usize { 0 }
+//! #     fn constraint(&self, _: &[Number], _: &mut [Number]) -> bool { true }
+//! #     fn constraint_bounds(&self, _: &mut [Number], _: &mut [Number]) -> bool { true }
+//! #     fn constraint_jacobian_indices(&self, _: &mut [Index], _: &mut [Index]) -> bool { true }
+//! #     fn constraint_jacobian_values(&self, _: &[Number], _: &mut [Number]) -> bool { true }
+//! #     fn num_hessian_non_zeros(&self) -> usize { 0 }
+//! #     fn hessian_indices(&self, _: &mut [Index], _: &mut [Index]) -> bool { true }
+//! #     fn hessian_values(&self, _: &[Number], _: Number, _: &[Number], _: &mut [Number]) -> bool { true }
+//! # }
+//! # impl Clone for NLP { fn clone(&self) -> Self { NLP } }
+//! let configs = OptionGrid::new()
+//!     .axis("linear_solver", vec!["mumps".into(), "ma27".into()])
+//!     .axis("tol", vec![1e-6.into(), 1e-8.into()])
+//!     .configurations();
+//!
+//! let mut base = OptionSet::new();
+//! base.set("print_level", 0).set("sb", "yes");
+//!
+//! let report = Benchmark::new()
+//!     .base_options(base)
+//!     .problem("my_problem", NLP)
+//!     .configurations(configs)
+//!     .run()
+//!     .unwrap();
+//! println!("{}", report);
+//! ```
+
+use crate::{
+    ConstrainedProblem, CreateError, Index, InvalidOptionError, Ipopt, Number, OptionSet,
+    OptionValue, SolveStatus,
+};
+
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
+
+/// A cartesian product of option values.
+///
+/// Each axis is an option name with a list of values to try.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OptionGrid {
+    axes: Vec<(String, Vec<OptionValue>)>,
+}
+
+impl OptionGrid {
+    /// Construct an empty grid, which has a single empty configuration.
+    pub fn new() -> Self {
+        OptionGrid::default()
+    }
+
+    /// Add an option with the given values to try.
+    pub fn axis(&mut self, name: &str, values: Vec<OptionValue>) -> &mut Self {
+        self.axes.push((name.to_string(), values));
+        self
+    }
+
+    /// Number of configurations in the grid.
+    pub fn len(&self) -> usize {
+        self.axes.iter().map(|(_, values)| values.len()).product()
+    }
+
+    /// Returns `true` if some axis has no values, in which case the grid has no configurations.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Generate all configurations in the grid.
+    ///
+    /// The last axis varies fastest.
+    pub fn configurations(&self) -> Vec<OptionSet> {
+        let mut configs = vec![OptionSet::new()];
+        for (name, values) in self.axes.iter() {
+            configs = configs
+                .iter()
+                .flat_map(|config| {
+                    values.iter().map(move |value| {
+                        let mut config = config.clone();
+                        config.set(name, value.clone());
+                        config
+                    })
+                })
+                .collect();
+        }
+        configs
+    }
+}
+
+/// Error produced when a benchmark could not be run.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BenchError {
+    /// Failed to create an Ipopt instance for the named problem.
+    Create {
+        /// Name of the problem.
+        problem: String,
+        /// The underlying error.
+        error: CreateError,
+    },
+    /// Ipopt rejected an option in a configuration.
+    InvalidOption(InvalidOptionError),
+}
+
+impl Display for BenchError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            BenchError::Create { problem, error } => {
+                write!(f, "Failed to create problem \"{}\": {}", problem, error)
+            }
+            BenchError::InvalidOption(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for BenchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+impl From<InvalidOptionError> for BenchError {
+    fn from(error: InvalidOptionError) -> Self {
+        BenchError::InvalidOption(error)
+    }
+}
+
+/// The outcome of solving a single problem with a single configuration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchRun {
+    /// Name of the problem.
+    pub problem: String,
+    /// Index of the configuration in the benchmark.
+    pub config: usize,
+    /// Status returned by Ipopt.
+    pub status: SolveStatus,
+    /// Final objective value.
+    pub objective_value: Number,
+    /// Number of iterations if reported by Ipopt.
+    pub iterations: Option<Index>,
+    /// Wallclock time spent in the solve.
+    pub time: Duration,
+}
+
+impl BenchRun {
+    /// Returns `true` if the problem was solved to the desired or acceptable tolerance.
+    pub fn succeeded(&self) -> bool {
+        matches!(
+            self.status,
+            SolveStatus::SolveSucceeded | SolveStatus::SolvedToAcceptableLevel
+        )
+    }
+}
+
+/// Solve a single problem with the given options and record the outcome.
+///
+/// The returned run has configuration index zero.
+pub fn bench_run<P: ConstrainedProblem>(
+    name: &str,
+    problem: P,
+    options: &OptionSet,
+) -> Result<BenchRun, BenchError> {
+    let mut ipopt = Ipopt::new(problem).map_err(|error| BenchError::Create {
+        problem: name.to_string(),
+        error,
+    })?;
+    ipopt.set_options(options)?;
+
+    let start = Instant::now();
+    let result = ipopt.solve();
+    let time = start.elapsed();
+    let status = result.status;
+    let objective_value = result.objective_value;
+
+    Ok(BenchRun {
+        problem: name.to_string(),
+        config: 0,
+        status,
+        objective_value,
+        iterations: ipopt.solve_stats().map(|stats| stats.iteration_count),
+        time,
+    })
+}
+
+/// A set of problems solved with a set of configurations.
+#[derive(Clone, Debug)]
+pub struct Benchmark<P> {
+    problems: Vec<(String, P)>,
+    configs: Vec<OptionSet>,
+    base: OptionSet,
+}
+
+impl<P> Default for Benchmark<P> {
+    fn default() -> Self {
+        Benchmark {
+            problems: Vec::new(),
+            configs: Vec::new(),
+            base: OptionSet::new(),
+        }
+    }
+}
+
+impl<P: ConstrainedProblem + Clone> Benchmark<P> {
+    /// Construct an empty benchmark.
+    pub fn new() -> Self {
+        Benchmark::default()
+    }
+
+    /// Set options applied before each configuration, for instance to suppress output.
+    pub fn base_options(&mut self, options: OptionSet) -> &mut Self {
+        self.base = options;
+        self
+    }
+
+    /// Add a named problem to the benchmark.
+    pub fn problem(&mut self, name: &str, problem: P) -> &mut Self {
+        self.problems.push((name.to_string(), problem));
+        self
+    }
+
+    /// Add a configuration to the benchmark.
+    pub fn configuration(&mut self, options: OptionSet) -> &mut Self {
+        self.configs.push(options);
+        self
+    }
+
+    /// Add a list of configurations to the benchmark.
+    pub fn configurations(&mut self, options: Vec<OptionSet>) -> &mut Self {
+        self.configs.extend(options);
+        self
+    }
+
+    /// Solve every problem with every configuration.
+    ///
+    /// If no configurations were added, each problem is solved once with the base options.
+    pub fn run(&self) -> Result<BenchReport, BenchError> {
+        let configs = if self.configs.is_empty() {
+            vec![OptionSet::new()]
+        } else {
+            self.configs.clone()
+        };
+
+        let mut runs = Vec::with_capacity(configs.len() * self.problems.len());
+        for (i, config) in configs.iter().enumerate() {
+            let mut options = self.base.clone();
+            options.extend(config.iter().map(|(name, opt)| (name, opt.clone())));
+            for (name, problem) in self.problems.iter() {
+                let run = bench_run(name, problem.clone(), &options)?;
+                runs.push(BenchRun { config: i, ..run });
+            }
+        }
+
+        Ok(BenchReport { configs, runs })
+    }
+}
+
+/// Aggregate results of a single configuration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigSummary {
+    /// Index of the configuration in the benchmark.
+    pub config: usize,
+    /// Number of problems solved to the desired or acceptable tolerance.
+    pub solved: usize,
+    /// Number of problems attempted.
+    pub attempted: usize,
+    /// Total number of iterations over the solved problems.
+    pub iterations: Index,
+    /// Total time spent on the solved problems.
+    pub time: Duration,
+}
+
+impl ConfigSummary {
+    /// Fraction of problems solved.
+    pub fn robustness(&self) -> Number {
+        if self.attempted == 0 {
+            0.0
+        } else {
+            self.solved as Number / self.attempted as Number
+        }
+    }
+}
+
+/// Results of a benchmark.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchReport {
+    /// Configurations in the order they were run.
+    pub configs: Vec<OptionSet>,
+    /// Outcomes of all solves.
+    pub runs: Vec<BenchRun>,
+}
+
+impl BenchReport {
+    /// Summarize the results of each configuration.
+    ///
+    /// Summaries are sorted from best to worst: by the number of solved problems, then by total
+    /// time.
+    pub fn summary(&self) -> Vec<ConfigSummary> {
+        let mut summary: Vec<_> = (0..self.configs.len())
+            .map(|config| ConfigSummary {
+                config,
+                solved: 0,
+                attempted: 0,
+                iterations: 0,
+                time: Duration::default(),
+            })
+            .collect();
+        for run in self.runs.iter() {
+            let s = &mut summary[run.config];
+            s.attempted += 1;
+            if run.succeeded() {
+                s.solved += 1;
+                s.iterations += run.iterations.unwrap_or(0);
+                s.time += run.time;
+            }
+        }
+        summary.sort_by(|a, b| b.solved.cmp(&a.solved).then(a.time.cmp(&b.time)));
+        summary
+    }
+}
+
+/// Produce a short description of a configuration like `linear_solver=ma27 tol=1e-8`.
+pub(crate) fn config_label(options: &OptionSet) -> String {
+    let label: Vec<_> = options
+        .iter()
+        .map(|(name, opt)| match opt {
+            OptionValue::Int(v) => format!("{}={}", name, v),
+            OptionValue::Num(v) => format!("{}={:e}", name, v),
+            OptionValue::Str(v) => format!("{}={}", name, v),
+        })
+        .collect();
+    if label.is_empty() {
+        "(default)".to_string()
+    } else {
+        label.join(" ")
+    }
+}
+
+/// Print the summary of each configuration as a table, best configuration first.
+impl Display for BenchReport {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:>8} {:>10} {:>12}  configuration",
+            "solved", "iterations", "time (s)"
+        )?;
+        for s in self.summary() {
+            writeln!(
+                f,
+                "{:>8} {:>10} {:>12.4}  {}",
+                format!("{}/{}", s.solved, s.attempted),
+                s.iterations,
+                s.time.as_secs_f64(),
+                config_label(&self.configs[s.config])
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn option_grid_test() {
+        let configs = OptionGrid::new()
+            .axis("linear_solver", vec!["mumps".into(), "ma27".into()])
+            .axis("tol", vec![1e-6.into(), 1e-8.into(), 1e-10.into()])
+            .configurations();
+        assert_eq!(configs.len(), 6);
+        assert_eq!(config_label(&configs[0]), "linear_solver=mumps tol=1e-6");
+        assert_eq!(config_label(&configs[5]), "linear_solver=ma27 tol=1e-10");
+        assert_eq!(
+            config_label(&OptionGrid::new().configurations()[0]),
+            "(default)"
+        );
+    }
+
+    #[test]
+    fn summary_test() {
+        let run = |config, status, iterations, millis| BenchRun {
+            problem: String::from("p"),
+            config,
+            status,
+            objective_value: 0.0,
+            iterations: Some(iterations),
+            time: Duration::from_millis(millis),
+        };
+        let report = BenchReport {
+            configs: vec![OptionSet::new(); 2],
+            runs: vec![
+                run(0, SolveStatus::SolveSucceeded, 10, 20),
+                run(0, SolveStatus::MaximumIterationsExceeded, 3000, 500),
+                run(1, SolveStatus::SolveSucceeded, 12, 30),
+                run(1, SolveStatus::SolvedToAcceptableLevel, 40, 60),
+            ],
+        };
+        let summary = report.summary();
+        assert_eq!(summary[0].config, 1);
+        assert_eq!(summary[0].solved, 2);
+        assert_eq!(summary[0].iterations, 52);
+        assert_eq!(summary[1].config, 0);
+        assert_eq!(summary[1].time, Duration::from_millis(20));
+        assert_eq!(summary[1].robustness(), 0.5);
+    }
+}
@@ -0,0 +1,670 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Composition of block-structured problems.
+//!
+//! Multistage and decomposition-style models consist of a number of subproblems, each with its
+//! own variables and constraints, tied together by a few coupling constraints.
+//! [`BlockProblem`](struct.BlockProblem.html) stacks the variables and constraints of its blocks,
+//! offsets their Jacobian and Hessian indices, and appends the coupling constraints, which are
+//! defined over the stacked variables. The result is a single `ConstrainedProblem` whose
+//! solution can be split back into the blocks.
+//!
+//! The sizes of each block are queried once, when the block is added. Constraint groups of the
+//! blocks are preserved, and coupling constraints are grouped under `"coupling"` when any block
+//! defines groups.
+
+use crate::{ConstrainedProblem, ConstraintGroups, Index, IndexingStyle, Number};
+
+/// Constraints coupling the variables of different blocks of a
+/// [`BlockProblem`](struct.BlockProblem.html).
+///
+/// All variable indices refer to the stacked variables of the block problem and are zero-based;
+/// the offset of each block is given by
+/// [`BlockProblem::variable_offset`](struct.BlockProblem.html#method.variable_offset). Row
+/// indices of the Jacobian are local to these constraints.
+pub trait CouplingConstraints {
+    /// Number of coupling constraints.
+    fn num_constraints(&self) -> usize;
+    /// Number of non-zeros in the Jacobian of the coupling constraints.
+    fn num_constraint_jacobian_non_zeros(&self) -> usize;
+    /// Coupling constraint function.
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool;
+    /// Lower and upper bounds on the coupling constraints.
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool;
+    /// Jacobian indices of the coupling constraints.
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool;
+    /// Jacobian values of the coupling constraints.
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool;
+    /// Number of non-zeros in the Hessian of the coupling constraints.
+    ///
+    /// For convenience, this is zero by default, which is correct for linear coupling.
+    fn num_hessian_non_zeros(&self) -> usize {
+        0
+    }
+    /// Hessian indices in the lower triangular part.
+    fn hessian_indices(&self, _rows: &mut [Index], _cols: &mut [Index]) -> bool {
+        true
+    }
+    /// Hessian values of the coupling constraints multiplied by the corresponding values in
+    /// `lambda`.
+    fn hessian_values(&self, _x: &[Number], _lambda: &[Number], _vals: &mut [Number]) -> bool {
+        true
+    }
+}
+
+/// Offsets of a part of the block problem into the stacked arrays.
+#[derive(Copy, Clone, Debug, Default)]
+struct Offsets {
+    variables: usize,
+    constraints: usize,
+    jacobian: usize,
+    hessian: usize,
+}
+
+/// Sizes of a part of the block problem.
+#[derive(Copy, Clone, Debug, Default)]
+struct Sizes {
+    variables: usize,
+    constraints: usize,
+    jacobian: usize,
+    hessian: usize,
+}
+
+struct Block {
+    problem: Box<dyn ConstrainedProblem>,
+    sizes: Sizes,
+    offsets: Offsets,
+}
+
+struct Coupling {
+    constraints: Box<dyn CouplingConstraints>,
+    sizes: Sizes,
+    offsets: Offsets,
+}
+
+/// A problem composed of independent blocks and coupling constraints between them.
+///
+/// Variables and constraints of the blocks are stacked in the order the blocks are added,
+/// followed by the coupling constraints.
+///
+/// The objective is the sum of the block objectives. Blocks providing variable or constraint
+/// scaling get it applied to their ranges, with unit scaling elsewhere; objective scaling of
+/// individual blocks is ignored.
+#[derive(Default)]
+pub struct BlockProblem {
+    blocks: Vec<Block>,
+    couplings: Vec<Coupling>,
+}
+
+impl BlockProblem {
+    /// Create a problem without any blocks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a block to the problem and return its index.
+    pub fn add_block<P: ConstrainedProblem + 'static>(&mut self, problem: P) -> usize {
+        let sizes = Sizes {
+            variables: problem.num_variables(),
+            constraints: problem.num_constraints(),
+            jacobian: problem.num_constraint_jacobian_non_zeros(),
+            hessian: problem.num_hessian_non_zeros(),
+        };
+        self.blocks.push(Block {
+            problem: Box::new(problem),
+            sizes,
+            offsets: Offsets::default(),
+        });
+        self.update_offsets();
+        self.blocks.len() - 1
+    }
+
+    /// Add coupling constraints between the blocks added so far.
+    pub fn add_coupling<C: CouplingConstraints + 'static>(&mut self, constraints: C) -> &mut Self {
+        let sizes = Sizes {
+            variables: 0,
+            constraints: constraints.num_constraints(),
+            jacobian: constraints.num_constraint_jacobian_non_zeros(),
+            hessian: constraints.num_hessian_non_zeros(),
+        };
+        self.couplings.push(Coupling {
+            constraints: Box::new(constraints),
+            sizes,
+            offsets: Offsets::default(),
+        });
+        self.update_offsets();
+        self
+    }
+
+    /// Recompute offsets, keeping coupling constraints after all block constraints.
+    fn update_offsets(&mut self) {
+        let mut offsets = Offsets::default();
+        let advance = |offsets: &mut Offsets, sizes: &Sizes| {
+            let current = *offsets;
+            offsets.variables += sizes.variables;
+            offsets.constraints += sizes.constraints;
+            offsets.jacobian += sizes.jacobian;
+            offsets.hessian += sizes.hessian;
+            current
+        };
+        for block in self.blocks.iter_mut() {
+            block.offsets = advance(&mut offsets, &block.sizes);
+        }
+        for coupling in self.couplings.iter_mut() {
+            coupling.offsets = advance(&mut offsets, &coupling.sizes);
+        }
+    }
+
+    /// Totals over all blocks and coupling constraints.
+    fn totals(&self) -> Offsets {
+        let last_block = self.blocks.last().map(|b| (b.offsets, b.sizes));
+        let last = self
+            .couplings
+            .last()
+            .map(|c| (c.offsets, c.sizes))
+            .or(last_block);
+        last.map_or(Offsets::default(), |(o, s)| Offsets {
+            variables: o.variables + s.variables,
+            constraints: o.constraints + s.constraints,
+            jacobian: o.jacobian + s.jacobian,
+            hessian: o.hessian + s.hessian,
+        })
+    }
+
+    /// Number of blocks.
+    pub fn num_blocks(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Offset of the variables of the given block in the stacked variables.
+    pub fn variable_offset(&self, block: usize) -> usize {
+        self.blocks[block].offsets.variables
+    }
+
+    /// Offset of the constraints of the given block in the stacked constraints.
+    pub fn constraint_offset(&self, block: usize) -> usize {
+        self.blocks[block].offsets.constraints
+    }
+
+    /// Offset of the first coupling constraint in the stacked constraints.
+    pub fn coupling_offset(&self) -> usize {
+        self.blocks
+            .last()
+            .map_or(0, |b| b.offsets.constraints + b.sizes.constraints)
+    }
+
+    /// Split a vector over the stacked variables, such as a solution or bound multipliers, into
+    /// the portions belonging to each block.
+    pub fn split_variables<'a>(&self, x: &'a [Number]) -> Vec<&'a [Number]> {
+        self.blocks
+            .iter()
+            .map(|b| &x[b.offsets.variables..b.offsets.variables + b.sizes.variables])
+            .collect()
+    }
+
+    /// Split a vector over the stacked constraints, such as constraint values or multipliers,
+    /// into the portions belonging to each block, followed by the portion belonging to the
+    /// coupling constraints.
+    pub fn split_constraints<'a>(&self, g: &'a [Number]) -> (Vec<&'a [Number]>, &'a [Number]) {
+        let blocks = self
+            .blocks
+            .iter()
+            .map(|b| &g[b.offsets.constraints..b.offsets.constraints + b.sizes.constraints])
+            .collect();
+        (blocks, &g[self.coupling_offset()..])
+    }
+
+    /// Apply `f` to every block and its portions of two vectors over the variables.
+    fn for_each_block_variables(
+        &self,
+        a: &mut [Number],
+        b: &mut [Number],
+        mut f: impl FnMut(&dyn ConstrainedProblem, &mut [Number], &mut [Number]) -> bool,
+    ) -> bool {
+        self.blocks.iter().all(|block| {
+            let range = block.offsets.variables..block.offsets.variables + block.sizes.variables;
+            f(block.problem.as_ref(), &mut a[range.clone()], &mut b[range])
+        })
+    }
+}
+
+/// Portion of the stacked variables belonging to a block.
+fn block_x<'a>(block: &Block, x: &'a [Number]) -> &'a [Number] {
+    &x[block.offsets.variables..block.offsets.variables + block.sizes.variables]
+}
+
+/// Shift indices produced by a block into the stacked arrays.
+fn shift_indices(indices: &mut [Index], style: IndexingStyle, offset: usize) {
+    let offset = offset as Index - style.offset();
+    for i in indices.iter_mut() {
+        *i += offset;
+    }
+}
+
+impl crate::BasicProblem for BlockProblem {
+    fn num_variables(&self) -> usize {
+        self.totals().variables
+    }
+
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        self.for_each_block_variables(x_l, x_u, |p, l, u| p.bounds(l, u))
+    }
+
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        for block in self.blocks.iter() {
+            let x =
+                &mut x[block.offsets.variables..block.offsets.variables + block.sizes.variables];
+            if !block.problem.initial_point(x) {
+                for v in x.iter_mut() {
+                    *v = 0.0;
+                }
+            }
+        }
+        true
+    }
+
+    fn initial_bounds_multipliers(&self, z_l: &mut [Number], z_u: &mut [Number]) -> bool {
+        self.for_each_block_variables(z_l, z_u, |p, l, u| {
+            if !p.initial_bounds_multipliers(l, u) {
+                for (l, u) in l.iter_mut().zip(u.iter_mut()) {
+                    *l = 0.0;
+                    *u = 0.0;
+                }
+            }
+            true
+        })
+    }
+
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = 0.0;
+        for block in self.blocks.iter() {
+            let mut block_obj = 0.0;
+            if !block.problem.objective(block_x(block, x), &mut block_obj) {
+                return false;
+            }
+            *obj += block_obj;
+        }
+        true
+    }
+
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        self.blocks.iter().all(|block| {
+            let range = block.offsets.variables..block.offsets.variables + block.sizes.variables;
+            block
+                .problem
+                .objective_grad(&x[range.clone()], &mut grad_f[range])
+        })
+    }
+
+    fn variable_scaling(&self, x_scaling: &mut [Number]) -> bool {
+        let mut scaled = false;
+        for block in self.blocks.iter() {
+            let s = &mut x_scaling
+                [block.offsets.variables..block.offsets.variables + block.sizes.variables];
+            if block.problem.variable_scaling(s) {
+                scaled = true;
+            } else {
+                for v in s.iter_mut() {
+                    *v = 1.0;
+                }
+            }
+        }
+        scaled
+    }
+}
+
+impl ConstrainedProblem for BlockProblem {
+    fn num_constraints(&self) -> usize {
+        self.totals().constraints
+    }
+
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        self.totals().jacobian
+    }
+
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        let blocks = self.blocks.iter().all(|block| {
+            let o = block.offsets.constraints;
+            let g = &mut g[o..o + block.sizes.constraints];
+            block.problem.constraint(block_x(block, x), g)
+        });
+        blocks
+            && self.couplings.iter().all(|c| {
+                let o = c.offsets.constraints;
+                c.constraints
+                    .constraint(x, &mut g[o..o + c.sizes.constraints])
+            })
+    }
+
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        let blocks = self.blocks.iter().all(|block| {
+            let range =
+                block.offsets.constraints..block.offsets.constraints + block.sizes.constraints;
+            block
+                .problem
+                .constraint_bounds(&mut g_l[range.clone()], &mut g_u[range])
+        });
+        blocks
+            && self.couplings.iter().all(|c| {
+                let range = c.offsets.constraints..c.offsets.constraints + c.sizes.constraints;
+                c.constraints
+                    .constraint_bounds(&mut g_l[range.clone()], &mut g_u[range])
+            })
+    }
+
+    fn initial_constraint_multipliers(&self, lambda: &mut [Number]) -> bool {
+        for v in lambda.iter_mut() {
+            *v = 0.0;
+        }
+        for block in self.blocks.iter() {
+            let o = block.offsets.constraints;
+            let lambda = &mut lambda[o..o + block.sizes.constraints];
+            if !block.problem.initial_constraint_multipliers(lambda) {
+                for v in lambda.iter_mut() {
+                    *v = 0.0;
+                }
+            }
+        }
+        true
+    }
+
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        for block in self.blocks.iter() {
+            let range = block.offsets.jacobian..block.offsets.jacobian + block.sizes.jacobian;
+            let (rows, cols) = (&mut rows[range.clone()], &mut cols[range]);
+            if !block.problem.constraint_jacobian_indices(rows, cols) {
+                return false;
+            }
+            let style = block.problem.indexing_style();
+            shift_indices(rows, style, block.offsets.constraints);
+            shift_indices(cols, style, block.offsets.variables);
+        }
+        for c in self.couplings.iter() {
+            let range = c.offsets.jacobian..c.offsets.jacobian + c.sizes.jacobian;
+            let (rows, cols) = (&mut rows[range.clone()], &mut cols[range]);
+            if !c.constraints.constraint_jacobian_indices(rows, cols) {
+                return false;
+            }
+            shift_indices(rows, IndexingStyle::CStyle, c.offsets.constraints);
+        }
+        true
+    }
+
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        let blocks = self.blocks.iter().all(|block| {
+            let o = block.offsets.jacobian;
+            let vals = &mut vals[o..o + block.sizes.jacobian];
+            block
+                .problem
+                .constraint_jacobian_values(block_x(block, x), vals)
+        });
+        blocks
+            && self.couplings.iter().all(|c| {
+                let o = c.offsets.jacobian;
+                c.constraints
+                    .constraint_jacobian_values(x, &mut vals[o..o + c.sizes.jacobian])
+            })
+    }
+
+    fn num_hessian_non_zeros(&self) -> usize {
+        self.totals().hessian
+    }
+
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        // Ipopt sums duplicate entries, so overlapping patterns are simply concatenated.
+        for block in self.blocks.iter() {
+            let range = block.offsets.hessian..block.offsets.hessian + block.sizes.hessian;
+            let (rows, cols) = (&mut rows[range.clone()], &mut cols[range]);
+            if !block.problem.hessian_indices(rows, cols) {
+                return false;
+            }
+            let style = block.problem.indexing_style();
+            shift_indices(rows, style, block.offsets.variables);
+            shift_indices(cols, style, block.offsets.variables);
+        }
+        self.couplings.iter().all(|c| {
+            let range = c.offsets.hessian..c.offsets.hessian + c.sizes.hessian;
+            c.constraints
+                .hessian_indices(&mut rows[range.clone()], &mut cols[range])
+        })
+    }
+
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        let blocks = self.blocks.iter().all(|block| {
+            let o = block.offsets.constraints;
+            let lambda = &lambda[o..o + block.sizes.constraints];
+            let o = block.offsets.hessian;
+            let vals = &mut vals[o..o + block.sizes.hessian];
+            block
+                .problem
+                .hessian_values(block_x(block, x), obj_factor, lambda, vals)
+        });
+        blocks
+            && self.couplings.iter().all(|c| {
+                let lambda =
+                    &lambda[c.offsets.constraints..c.offsets.constraints + c.sizes.constraints];
+                let vals = &mut vals[c.offsets.hessian..c.offsets.hessian + c.sizes.hessian];
+                c.constraints.hessian_values(x, lambda, vals)
+            })
+    }
+
+    fn constraint_scaling(&self, g_scaling: &mut [Number]) -> bool {
+        for v in g_scaling.iter_mut() {
+            *v = 1.0;
+        }
+        let mut scaled = false;
+        for block in self.blocks.iter() {
+            let o = block.offsets.constraints;
+            let s = &mut g_scaling[o..o + block.sizes.constraints];
+            if block.problem.constraint_scaling(s) {
+                scaled = true;
+            } else {
+                for v in s.iter_mut() {
+                    *v = 1.0;
+                }
+            }
+        }
+        scaled
+    }
+
+    fn constraint_groups(&self) -> ConstraintGroups {
+        let mut groups = ConstraintGroups::new();
+        for block in self.blocks.iter() {
+            groups.extend_shifted(block.problem.constraint_groups(), block.offsets.constraints);
+        }
+        let coupling = self.coupling_offset();
+        let num_coupling = self.num_constraints() - coupling;
+        if !groups.is_empty() && num_coupling > 0 {
+            groups.add_group("coupling", coupling..coupling + num_coupling);
+        }
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BasicProblem;
+
+    /// min (x0 - c)^2 + (x1 - c)^2 s.t. x0 * x1 >= 1.
+    struct Stage {
+        c: Number,
+        style: IndexingStyle,
+    }
+
+    impl BasicProblem for Stage {
+        fn indexing_style(&self) -> IndexingStyle {
+            self.style
+        }
+        fn num_variables(&self) -> usize {
+            2
+        }
+        fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+            x_l.copy_from_slice(&[-1e20; 2]);
+            x_u.copy_from_slice(&[1e20; 2]);
+            true
+        }
+        fn initial_point(&self, x: &mut [Number]) -> bool {
+            x.copy_from_slice(&[self.c; 2]);
+            true
+        }
+        fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+            *obj = (x[0] - self.c).powi(2) + (x[1] - self.c).powi(2);
+            true
+        }
+        fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+            grad_f[0] = 2.0 * (x[0] - self.c);
+            grad_f[1] = 2.0 * (x[1] - self.c);
+            true
+        }
+    }
+
+    impl ConstrainedProblem for Stage {
+        fn num_constraints(&self) -> usize {
+            1
+        }
+        fn num_constraint_jacobian_non_zeros(&self) -> usize {
+            2
+        }
+        fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+            g[0] = x[0] * x[1];
+            true
+        }
+        fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+            g_l[0] = 1.0;
+            g_u[0] = 1e20;
+            true
+        }
+        fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+            let base = self.style.offset();
+            rows.copy_from_slice(&[base, base]);
+            cols.copy_from_slice(&[base, base + 1]);
+            true
+        }
+        fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+            vals.copy_from_slice(&[x[1], x[0]]);
+            true
+        }
+        fn num_hessian_non_zeros(&self) -> usize {
+            3
+        }
+        fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+            let base = self.style.offset();
+            rows.copy_from_slice(&[base, base + 1, base + 1]);
+            cols.copy_from_slice(&[base, base, base + 1]);
+            true
+        }
+        fn hessian_values(
+            &self,
+            _: &[Number],
+            obj_factor: Number,
+            lambda: &[Number],
+            vals: &mut [Number],
+        ) -> bool {
+            vals.copy_from_slice(&[2.0 * obj_factor, lambda[0], 2.0 * obj_factor]);
+            true
+        }
+    }
+
+    /// Continuity between the last variable of one stage and the first of the next.
+    struct Link {
+        from: usize,
+        to: usize,
+    }
+
+    impl CouplingConstraints for Link {
+        fn num_constraints(&self) -> usize {
+            1
+        }
+        fn num_constraint_jacobian_non_zeros(&self) -> usize {
+            2
+        }
+        fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+            g[0] = x[self.to] - x[self.from];
+            true
+        }
+        fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+            g_l[0] = 0.0;
+            g_u[0] = 0.0;
+            true
+        }
+        fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+            rows.copy_from_slice(&[0, 0]);
+            cols.copy_from_slice(&[self.from as Index, self.to as Index]);
+            true
+        }
+        fn constraint_jacobian_values(&self, _: &[Number], vals: &mut [Number]) -> bool {
+            vals.copy_from_slice(&[-1.0, 1.0]);
+            true
+        }
+    }
+
+    #[test]
+    fn block_layout_test() {
+        let mut problem = BlockProblem::new();
+        let first = problem.add_block(Stage {
+            c: 1.0,
+            style: IndexingStyle::CStyle,
+        });
+        let second = problem.add_block(Stage {
+            c: 2.0,
+            style: IndexingStyle::FortranStyle,
+        });
+        let link = Link {
+            from: problem.variable_offset(first) + 1,
+            to: problem.variable_offset(second),
+        };
+        problem.add_coupling(link);
+
+        assert_eq!(problem.num_variables(), 4);
+        assert_eq!(problem.num_constraints(), 3);
+        assert_eq!(problem.coupling_offset(), 2);
+        assert_eq!(problem.num_constraint_jacobian_non_zeros(), 6);
+        assert_eq!(problem.num_hessian_non_zeros(), 6);
+
+        let mut rows = [0; 6];
+        let mut cols = [0; 6];
+        assert!(problem.constraint_jacobian_indices(&mut rows, &mut cols));
+        assert_eq!(rows, [0, 0, 1, 1, 2, 2]);
+        assert_eq!(cols, [0, 1, 2, 3, 1, 2]);
+        assert!(problem.hessian_indices(&mut rows, &mut cols));
+        assert_eq!(rows, [0, 1, 1, 2, 3, 3]);
+        assert_eq!(cols, [0, 0, 1, 2, 2, 3]);
+
+        let x = [1.0, 2.0, 3.0, 4.0];
+        let mut obj = 0.0;
+        assert!(problem.objective(&x, &mut obj));
+        assert_eq!(obj, 0.0 + 1.0 + 1.0 + 4.0);
+        let mut g = [0.0; 3];
+        assert!(problem.constraint(&x, &mut g));
+        assert_eq!(g, [2.0, 12.0, 1.0]);
+
+        let mut vals = [0.0; 6];
+        assert!(problem.hessian_values(&x, 1.0, &[0.5, 0.25, 7.0], &mut vals));
+        assert_eq!(vals, [2.0, 0.5, 2.0, 2.0, 0.25, 2.0]);
+
+        let split = problem.split_variables(&x);
+        assert_eq!(split, vec![&x[..2], &x[2..]]);
+        let (blocks, coupling) = problem.split_constraints(&g);
+        assert_eq!(blocks, vec![&g[..1], &g[1..2]]);
+        assert_eq!(coupling, &g[2..]);
+    }
+}
@@ -0,0 +1,224 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Minimization subject to variable bounds only.
+//!
+//! Many problems are smooth minimizations over a box. A
+//! [`BoundConstrainedProblem`](trait.BoundConstrainedProblem.html) asks only for the objective,
+//! its gradient, the bounds and a starting point, and
+//! [`solve_bound_constrained`](fn.solve_bound_constrained.html) sets up Ipopt without any
+//! constraint or Jacobian callbacks, using a limited-memory approximation of the Hessian. The
+//! result is returned as an owned
+//! [`BoundConstrainedSolution`](struct.BoundConstrainedSolution.html), so no solver has to be
+//! kept around.
+//!
+//! To configure the solver further, wrap the problem in
+//! [`BoundConstrained`](struct.BoundConstrained.html) and pass it to
+//! [`Ipopt::new_unconstrained`](struct.Ipopt.html#method.new_unconstrained).
+
+use crate::{
+    BasicProblem, CreateError, InvalidOptionError, Ipopt, Number, OptionSet, SolveStats,
+    SolveStatus,
+};
+
+use std::fmt::{Display, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A problem whose only constraints are bounds on the variables.
+///
+/// Unlike [`BasicProblem`](trait.BasicProblem.html), bounds default to none and the starting
+/// point must always be given. The evaluation callbacks may fail by returning `false`, in which
+/// case Ipopt tries a smaller step.
+pub trait BoundConstrainedProblem {
+    /// Total number of variables.
+    fn num_variables(&self) -> usize;
+
+    /// Specify lower and upper variable bounds given by `x_l` and `x_u` respectively.
+    ///
+    /// For convenience, the variables are unbounded by default.
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) {
+        for (l, u) in x_l.iter_mut().zip(x_u.iter_mut()) {
+            *l = Number::NEG_INFINITY;
+            *u = Number::INFINITY;
+        }
+    }
+
+    /// Write the starting point into `x`.
+    fn initial_point(&self, x: &mut [Number]);
+
+    /// Objective function. This is the function being minimized.
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool;
+
+    /// The gradient of the objective function.
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool;
+}
+
+/// Adapter presenting a [`BoundConstrainedProblem`](trait.BoundConstrainedProblem.html) as a
+/// [`BasicProblem`](trait.BasicProblem.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoundConstrained<P>(pub P);
+
+impl<P: BoundConstrainedProblem> BasicProblem for BoundConstrained<P> {
+    fn num_variables(&self) -> usize {
+        self.0.num_variables()
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        self.0.bounds(x_l, x_u);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        self.0.initial_point(x);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        self.0.objective(x, obj)
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        self.0.objective_grad(x, grad_f)
+    }
+}
+
+/// The outcome of a bound-constrained solve.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BoundConstrainedSolution {
+    /// Solve status.
+    pub status: SolveStatus,
+    /// Objective value at the solution.
+    pub objective_value: Number,
+    /// The minimizer, or the last iterate if the solve failed.
+    pub primal_variables: Vec<Number>,
+    /// Lower bound multipliers.
+    pub lower_bound_multipliers: Vec<Number>,
+    /// Upper bound multipliers.
+    pub upper_bound_multipliers: Vec<Number>,
+    /// Statistics of the solve, if Ipopt started iterating.
+    pub stats: Option<SolveStats>,
+}
+
+impl BoundConstrainedSolution {
+    /// Returns `true` if the problem was solved to the desired or acceptable tolerance.
+    pub fn succeeded(&self) -> bool {
+        matches!(
+            self.status,
+            SolveStatus::SolveSucceeded | SolveStatus::SolvedToAcceptableLevel
+        )
+    }
+}
+
+/// Error produced when a bound-constrained solve could not be started.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BoundConstrainedError {
+    /// Failed to create the Ipopt instance.
+    Create(CreateError),
+    /// Ipopt rejected one of the given options.
+    InvalidOption(InvalidOptionError),
+}
+
+impl Display for BoundConstrainedError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            BoundConstrainedError::Create(error) => write!(f, "{}", error),
+            BoundConstrainedError::InvalidOption(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for BoundConstrainedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+impl From<CreateError> for BoundConstrainedError {
+    fn from(error: CreateError) -> Self {
+        BoundConstrainedError::Create(error)
+    }
+}
+
+impl From<InvalidOptionError> for BoundConstrainedError {
+    fn from(error: InvalidOptionError) -> Self {
+        BoundConstrainedError::InvalidOption(error)
+    }
+}
+
+/// Minimize a bound-constrained problem with the given options.
+///
+/// The Hessian is approximated with L-BFGS; the `limited_memory_*` options tune the
+/// approximation.
+pub fn solve_bound_constrained<P: BoundConstrainedProblem>(
+    problem: P,
+    options: &OptionSet,
+) -> Result<BoundConstrainedSolution, BoundConstrainedError> {
+    let mut ipopt = Ipopt::new_unconstrained(BoundConstrained(problem))?;
+    ipopt.set_options(options)?;
+
+    let solution = {
+        let result = ipopt.solve();
+        let solution = result.solver_data.solution;
+        BoundConstrainedSolution {
+            status: result.status,
+            objective_value: result.objective_value,
+            primal_variables: solution.primal_variables.to_vec(),
+            lower_bound_multipliers: solution.lower_bound_multipliers.to_vec(),
+            upper_bound_multipliers: solution.upper_bound_multipliers.to_vec(),
+            stats: None,
+        }
+    };
+    Ok(BoundConstrainedSolution {
+        stats: ipopt.solve_stats(),
+        ..solution
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Box2;
+
+    impl BoundConstrainedProblem for Box2 {
+        fn num_variables(&self) -> usize {
+            2
+        }
+        fn initial_point(&self, x: &mut [Number]) {
+            x.copy_from_slice(&[1.0, 2.0]);
+        }
+        fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+            *obj = x[0] * x[0] + x[1] * x[1];
+            true
+        }
+        fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+            grad_f.copy_from_slice(&[2.0 * x[0], 2.0 * x[1]]);
+            true
+        }
+    }
+
+    #[test]
+    fn adapter_test() {
+        let p = BoundConstrained(Box2);
+        let (mut x_l, mut x_u) = ([0.0; 2], [0.0; 2]);
+        assert!(p.bounds(&mut x_l, &mut x_u));
+        assert_eq!(x_l, [Number::NEG_INFINITY; 2]);
+        assert_eq!(x_u, [Number::INFINITY; 2]);
+
+        let mut x = [0.0; 2];
+        assert!(p.initial_point(&mut x));
+        let mut grad = [0.0; 2];
+        assert!(p.objective_grad(&x, &mut grad));
+        assert_eq!(grad, [2.0, 4.0]);
+    }
+}
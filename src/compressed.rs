@@ -0,0 +1,264 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Conversion between compressed sparse rows or columns and triplets.
+//!
+//! Matrices assembled by other libraries are usually stored in compressed sparse row (CSR) or
+//! column (CSC) format: an array of offsets with one entry per row (or column) plus one, and an
+//! array with the column (or row) index of each stored value. These are the `indptr` and
+//! `indices` of `sprs` matrices, and the major offsets and minor indices of `nalgebra-sparse`
+//! patterns, so they can be passed here directly.
+//!
+//! [`Triplets::from_compressed`](struct.Triplets.html#method.from_compressed) converts such a
+//! structure into a [`Triplets`](struct.Triplets.html) structure for Ipopt along with a
+//! [`CompressedMap`](struct.CompressedMap.html), which copies values from the native layout
+//! into the triplet value array in every evaluation without looking up any indices.
+//! [`Triplets::to_compressed`](struct.Triplets.html#method.to_compressed) goes the other way.
+
+use crate::sparsity::{SparsityBuilder, SparsityError, Triplets};
+use crate::Number;
+
+/// Storage order of a compressed sparse matrix.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Compression {
+    /// Compressed sparse rows: the offsets delimit rows and the indices are columns.
+    Row,
+    /// Compressed sparse columns: the offsets delimit columns and the indices are rows.
+    Column,
+}
+
+/// The structure of a compressed sparse matrix.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompressedStructure {
+    /// Storage order.
+    pub compression: Compression,
+    /// Position of the first entry of each row or column, followed by the number of entries.
+    pub offsets: Vec<usize>,
+    /// Column or row index of each entry.
+    pub indices: Vec<usize>,
+}
+
+/// Maps each value of a compressed matrix to a slot of a triplet structure.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompressedMap {
+    slots: Vec<usize>,
+    /// Whether each value is accumulated into its slot. Upper triangular values of symmetric
+    /// matrices whose transpose is also stored are skipped.
+    accumulate: Vec<bool>,
+}
+
+impl CompressedMap {
+    /// The triplet slot of each compressed value.
+    pub fn slots(&self) -> &[usize] {
+        &self.slots
+    }
+
+    /// Write the triplet values `vals` from the compressed values `native`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `native` does not have one value per compressed entry.
+    pub fn to_triplet_values(&self, native: &[Number], vals: &mut [Number]) {
+        assert_eq!(native.len(), self.slots.len());
+        for v in vals.iter_mut() {
+            *v = 0.0;
+        }
+        for ((&slot, &accumulate), &value) in self.slots.iter().zip(&self.accumulate).zip(native) {
+            if accumulate {
+                vals[slot] += value;
+            }
+        }
+    }
+
+    /// Write the compressed values `native` from the triplet values `vals`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `native` does not have one value per compressed entry.
+    pub fn to_compressed_values(&self, vals: &[Number], native: &mut [Number]) {
+        assert_eq!(native.len(), self.slots.len());
+        for (n, &slot) in native.iter_mut().zip(&self.slots) {
+            *n = vals[slot];
+        }
+    }
+}
+
+/// Iterate over the `(row, col)` entries of a compressed structure, checking its offsets.
+fn compressed_entries<'a>(
+    compression: Compression,
+    num_outer: usize,
+    offsets: &'a [usize],
+    indices: &'a [usize],
+) -> Result<impl Iterator<Item = (usize, usize)> + 'a, SparsityError> {
+    let valid = offsets.len() == num_outer + 1
+        && offsets[0] == 0
+        && offsets[num_outer] == indices.len()
+        && offsets.windows(2).all(|w| w[0] <= w[1]);
+    if !valid {
+        return Err(SparsityError::InvalidOffsets);
+    }
+    Ok(offsets.windows(2).enumerate().flat_map(move |(outer, w)| {
+        indices[w[0]..w[1]]
+            .iter()
+            .map(move |&inner| match compression {
+                Compression::Row => (outer, inner),
+                Compression::Column => (inner, outer),
+            })
+    }))
+}
+
+impl Triplets {
+    /// Convert the structure of a general compressed matrix.
+    ///
+    /// Entries repeated in the compressed structure share a slot.
+    pub fn from_compressed(
+        compression: Compression,
+        num_rows: usize,
+        num_cols: usize,
+        offsets: &[usize],
+        indices: &[usize],
+    ) -> Result<(Triplets, CompressedMap), SparsityError> {
+        let num_outer = match compression {
+            Compression::Row => num_rows,
+            Compression::Column => num_cols,
+        };
+        let mut builder = SparsityBuilder::new(num_rows, num_cols);
+        for (r, c) in compressed_entries(compression, num_outer, offsets, indices)? {
+            builder.add(r, c);
+        }
+        let triplets = builder.build()?;
+        let map = CompressedMap {
+            slots: triplets.entry_slots().to_vec(),
+            accumulate: vec![true; indices.len()],
+        };
+        Ok((triplets, map))
+    }
+
+    /// Convert the structure of a symmetric compressed `n` by `n` matrix into the lower
+    /// triangular triplets expected for Hessians.
+    ///
+    /// The compressed matrix may store the lower triangular part, the upper triangular part or
+    /// both. Upper triangular entries are mapped to their transpose, and skipped when the
+    /// transpose is also stored.
+    pub fn from_compressed_symmetric(
+        compression: Compression,
+        n: usize,
+        offsets: &[usize],
+        indices: &[usize],
+    ) -> Result<(Triplets, CompressedMap), SparsityError> {
+        let entries: Vec<_> = compressed_entries(compression, n, offsets, indices)?.collect();
+        let mut lower: Vec<_> = entries.iter().filter(|&&(r, c)| r >= c).cloned().collect();
+        lower.sort_unstable();
+
+        let mut builder = SparsityBuilder::symmetric(n);
+        let mut accumulate = Vec::with_capacity(entries.len());
+        for &(r, c) in entries.iter() {
+            if r >= c {
+                builder.add(r, c);
+                accumulate.push(true);
+            } else {
+                builder.add(c, r);
+                accumulate.push(lower.binary_search(&(c, r)).is_err());
+            }
+        }
+        let triplets = builder.build()?;
+        let map = CompressedMap {
+            slots: triplets.entry_slots().to_vec(),
+            accumulate,
+        };
+        Ok((triplets, map))
+    }
+
+    /// Convert this structure into a compressed structure.
+    ///
+    /// Symmetric structures are converted as they are stored, with the lower triangular part
+    /// only.
+    pub fn to_compressed(&self, compression: Compression) -> (CompressedStructure, CompressedMap) {
+        let num_outer = match compression {
+            Compression::Row => self.num_rows(),
+            Compression::Column => self.num_cols(),
+        };
+        let key = |&(r, c): &(usize, usize)| match compression {
+            Compression::Row => (r, c),
+            Compression::Column => (c, r),
+        };
+
+        // Entries are sorted by row, so only column compression needs a permutation.
+        let mut order: Vec<_> = (0..self.len()).collect();
+        order.sort_by_key(|&slot| key(&self.entries()[slot]));
+
+        let mut offsets = vec![0; num_outer + 1];
+        let mut indices = Vec::with_capacity(self.len());
+        for &slot in order.iter() {
+            let (outer, inner) = key(&self.entries()[slot]);
+            offsets[outer + 1] += 1;
+            indices.push(inner);
+        }
+        for i in 0..num_outer {
+            offsets[i + 1] += offsets[i];
+        }
+
+        let structure = CompressedStructure {
+            compression,
+            offsets,
+            indices,
+        };
+        let map = CompressedMap {
+            accumulate: vec![true; order.len()],
+            slots: order,
+        };
+        (structure, map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compressed_round_trip_test() {
+        // The full symmetric matrix
+        // [1 2 0]
+        // [2 3 4]
+        // [0 4 5]
+        // in compressed sparse columns.
+        let offsets = [0, 2, 5, 7];
+        let indices = [0, 1, 0, 1, 2, 1, 2];
+        let values = [1.0, 2.0, 2.0, 3.0, 4.0, 4.0, 5.0];
+        let (triplets, map) =
+            Triplets::from_compressed_symmetric(Compression::Column, 3, &offsets, &indices)
+                .unwrap();
+        assert_eq!(
+            triplets.entries(),
+            &[(0, 0), (1, 0), (1, 1), (2, 1), (2, 2)]
+        );
+
+        let mut vals = [0.0; 5];
+        map.to_triplet_values(&values, &mut vals);
+        assert_eq!(vals, [1.0, 2.0, 3.0, 4.0, 5.0]);
+
+        // The lower triangular part in compressed sparse columns.
+        let (structure, map) = triplets.to_compressed(Compression::Column);
+        assert_eq!(structure.offsets, vec![0, 2, 4, 5]);
+        assert_eq!(structure.indices, vec![0, 1, 1, 2, 2]);
+        let mut native = [0.0; 5];
+        map.to_compressed_values(&vals, &mut native);
+        assert_eq!(native, [1.0, 2.0, 3.0, 4.0, 5.0]);
+
+        assert_eq!(
+            Triplets::from_compressed(Compression::Row, 2, 2, &[0, 2, 1], &[0, 1]),
+            Err(SparsityError::InvalidOffsets)
+        );
+    }
+}
@@ -0,0 +1,819 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Loading problems from the [CUTEst](https://github.com/ralna/CUTEst) test collection.
+//!
+//! CUTEst problems are described in SIF files, which are decoded by `sifdecoder` into Fortran
+//! sources and an `OUTSDIF.d` data file. The sources must then be compiled together with the
+//! CUTEst library into a shared library for each problem, for instance
+//!
+//! ```verbatim
+//!     sifdecoder HS71.SIF
+//!     gfortran -shared -fPIC ELFUN.f EXTER.f GROUP.f RANGE.f -lcutest -o libHS71.so
+//! ```
+//!
+//! The library is loaded at run time by
+//! [`CutestProblem::load`](struct.CutestProblem.html#method.load), which produces a problem
+//! that can be passed directly to [`Ipopt::new`](struct.Ipopt.html#method.new):
+//!
+//! ```no_run
+//! use ipopt::*;
+//!
+//! let nlp = CutestProblem::load("libHS71.so", "OUTSDIF.d").unwrap();
+//! let mut ipopt = Ipopt::new(nlp).unwrap();
+//! let result = ipopt.solve();
+//! println!("{:?}: {}", result.status, result.objective_value);
+//! ```
+//!
+//! Both constrained and unconstrained CUTEst problems are supported.
+
+use crate::{BasicProblem, ConstrainedProblem, Index, IndexingStyle, Number};
+
+use libloading::Library;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fmt::{Display, Formatter};
+use std::os::raw::c_char;
+use std::path::Path;
+
+type Integer = i32;
+type Logical = bool;
+
+/// Fortran unit used to read the `OUTSDIF.d` file.
+const FUNIT: Integer = 42;
+/// Fortran unit for CUTEst output (standard output).
+const IOUT: Integer = 6;
+/// Fortran unit used internally by CUTEst for buffering.
+const IO_BUFFER: Integer = 11;
+
+type OpenFn = unsafe extern "C" fn(*const Integer, *const c_char, *mut Integer);
+type CloseFn = unsafe extern "C" fn(*const Integer, *mut Integer);
+type DimenFn = unsafe extern "C" fn(*mut Integer, *const Integer, *mut Integer, *mut Integer);
+type CsetupFn = unsafe extern "C" fn(
+    *mut Integer,
+    *const Integer,
+    *const Integer,
+    *const Integer,
+    *mut Integer,
+    *mut Integer,
+    *mut Number,
+    *mut Number,
+    *mut Number,
+    *mut Number,
+    *mut Number,
+    *mut Number,
+    *mut Logical,
+    *mut Logical,
+    *const Integer,
+    *const Integer,
+    *const Integer,
+);
+type UsetupFn = unsafe extern "C" fn(
+    *mut Integer,
+    *const Integer,
+    *const Integer,
+    *const Integer,
+    *mut Integer,
+    *mut Number,
+    *mut Number,
+    *mut Number,
+);
+type CfnFn = unsafe extern "C" fn(
+    *mut Integer,
+    *const Integer,
+    *const Integer,
+    *const Number,
+    *mut Number,
+    *mut Number,
+);
+type CofgFn = unsafe extern "C" fn(
+    *mut Integer,
+    *const Integer,
+    *const Number,
+    *mut Number,
+    *mut Number,
+    *const Logical,
+);
+type UfnFn = unsafe extern "C" fn(*mut Integer, *const Integer, *const Number, *mut Number);
+type UgrFn = unsafe extern "C" fn(*mut Integer, *const Integer, *const Number, *mut Number);
+type DimsFn = unsafe extern "C" fn(*mut Integer, *mut Integer);
+type CsgrpFn = unsafe extern "C" fn(
+    *mut Integer,
+    *const Integer,
+    *mut Integer,
+    *const Integer,
+    *mut Integer,
+    *mut Integer,
+);
+type CsgrFn = unsafe extern "C" fn(
+    *mut Integer,
+    *const Integer,
+    *const Integer,
+    *const Number,
+    *const Number,
+    *const Logical,
+    *mut Integer,
+    *const Integer,
+    *mut Number,
+    *mut Integer,
+    *mut Integer,
+);
+type ShpFn = unsafe extern "C" fn(
+    *mut Integer,
+    *const Integer,
+    *mut Integer,
+    *const Integer,
+    *mut Integer,
+    *mut Integer,
+);
+type CshjFn = unsafe extern "C" fn(
+    *mut Integer,
+    *const Integer,
+    *const Integer,
+    *const Number,
+    *const Number,
+    *const Number,
+    *mut Integer,
+    *const Integer,
+    *mut Number,
+    *mut Integer,
+    *mut Integer,
+);
+type UshFn = unsafe extern "C" fn(
+    *mut Integer,
+    *const Integer,
+    *const Number,
+    *mut Integer,
+    *const Integer,
+    *mut Number,
+    *mut Integer,
+    *mut Integer,
+);
+type TerminateFn = unsafe extern "C" fn(*mut Integer);
+
+/// Error produced when loading a CUTEst problem.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CutestError {
+    /// The given path is not valid UTF-8 or contains a nul byte.
+    InvalidPath,
+    /// The problem library could not be loaded.
+    LibraryLoad(String),
+    /// A required CUTEst routine is missing from the problem library.
+    MissingSymbol(&'static str),
+    /// The `OUTSDIF.d` file could not be opened.
+    FileNotFound,
+    /// A CUTEst routine reported an error status.
+    Status {
+        /// Name of the routine.
+        routine: &'static str,
+        /// The non-zero status code.
+        status: i32,
+    },
+}
+
+impl Display for CutestError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            CutestError::InvalidPath => write!(f, "Invalid path."),
+            CutestError::LibraryLoad(err) => {
+                write!(f, "Failed to load the CUTEst problem library: {}", err)
+            }
+            CutestError::MissingSymbol(name) => {
+                write!(f, "The problem library does not export \"{}\".", name)
+            }
+            CutestError::FileNotFound => write!(f, "Failed to open the OUTSDIF.d file."),
+            CutestError::Status { routine, status } => {
+                write!(
+                    f,
+                    "CUTEst routine {} failed with status {}.",
+                    routine, status
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for CutestError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+/// Routines used by unconstrained problems.
+struct UnconstrainedFns {
+    ufn: UfnFn,
+    ugr: UgrFn,
+    ush: UshFn,
+}
+
+/// Routines used by constrained problems.
+struct ConstrainedFns {
+    cfn: CfnFn,
+    cofg: CofgFn,
+    csgr: CsgrFn,
+    cshj: CshjFn,
+}
+
+enum Routines {
+    Unconstrained(UnconstrainedFns),
+    Constrained(ConstrainedFns),
+}
+
+/// A sparse matrix pattern with a lookup from one-based `(row, col)` pairs to entry positions.
+struct Pattern {
+    rows: Vec<Index>,
+    cols: Vec<Index>,
+    position: HashMap<(Index, Index), usize>,
+}
+
+impl Pattern {
+    fn new(entries: impl Iterator<Item = (Index, Index)>) -> Self {
+        let mut rows = Vec::new();
+        let mut cols = Vec::new();
+        let mut position = HashMap::new();
+        for entry in entries {
+            position.entry(entry).or_insert_with(|| {
+                rows.push(entry.0);
+                cols.push(entry.1);
+                rows.len() - 1
+            });
+        }
+        Pattern {
+            rows,
+            cols,
+            position,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Accumulate values given in arbitrary order into `vals` ordered by this pattern.
+    fn scatter(
+        &self,
+        entries: impl Iterator<Item = ((Index, Index), Number)>,
+        vals: &mut [Number],
+    ) -> bool {
+        for v in vals.iter_mut() {
+            *v = 0.0;
+        }
+        for (entry, val) in entries {
+            match self.position.get(&entry) {
+                Some(&k) => vals[k] += val,
+                None => return false,
+            }
+        }
+        true
+    }
+}
+
+/// Orient an entry of a symmetric matrix into the lower triangle.
+fn lower(row: Index, col: Index) -> (Index, Index) {
+    if row >= col {
+        (row, col)
+    } else {
+        (col, row)
+    }
+}
+
+/// A problem from the CUTEst test collection.
+///
+/// CUTEst keeps the problem data in global state of the loaded library, so only one problem
+/// from a given library may be loaded at a time, and the problem is neither `Send` nor `Sync`.
+/// The problem is terminated and the library unloaded when this value is dropped.
+pub struct CutestProblem {
+    routines: Routines,
+    terminate: TerminateFn,
+    num_variables: usize,
+    num_constraints: usize,
+    x0: Vec<Number>,
+    x_l: Vec<Number>,
+    x_u: Vec<Number>,
+    lambda0: Vec<Number>,
+    g_l: Vec<Number>,
+    g_u: Vec<Number>,
+    equality: Vec<bool>,
+    linear: Vec<bool>,
+    jacobian: Pattern,
+    hessian: Pattern,
+    // Keep the library loaded for as long as the routines above are in use.
+    _lib: Library,
+}
+
+impl std::fmt::Debug for CutestProblem {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.debug_struct("CutestProblem")
+            .field("num_variables", &self.num_variables)
+            .field("num_constraints", &self.num_constraints)
+            .finish()
+    }
+}
+
+/// Check the status returned by a CUTEst routine.
+fn check(routine: &'static str, status: Integer) -> Result<(), CutestError> {
+    if status == 0 {
+        Ok(())
+    } else {
+        Err(CutestError::Status { routine, status })
+    }
+}
+
+/// Look up a routine in the problem library.
+///
+/// # Safety
+///
+/// `T` must match the signature of the named routine.
+unsafe fn symbol<T: Copy>(lib: &Library, name: &'static str) -> Result<T, CutestError> {
+    let bytes = format!("{}\0", name);
+    lib.get::<T>(bytes.as_bytes())
+        .map(|sym| *sym)
+        .map_err(|_| CutestError::MissingSymbol(name))
+}
+
+impl CutestProblem {
+    /// Load a problem from its compiled shared library and the `OUTSDIF.d` file produced by
+    /// `sifdecoder`.
+    pub fn load<L: AsRef<Path>, D: AsRef<Path>>(
+        library: L,
+        outsdif: D,
+    ) -> Result<Self, CutestError> {
+        let outsdif = outsdif
+            .as_ref()
+            .to_str()
+            .and_then(|p| CString::new(p).ok())
+            .ok_or(CutestError::InvalidPath)?;
+        let lib = unsafe { Library::new(library.as_ref()) }
+            .map_err(|err| CutestError::LibraryLoad(err.to_string()))?;
+
+        unsafe {
+            let open: OpenFn = symbol(&lib, "fortran_open_")?;
+            let close: CloseFn = symbol(&lib, "fortran_close_")?;
+            let cdimen: DimenFn = symbol(&lib, "cutest_cdimen_")?;
+
+            let mut status = 0;
+            open(&FUNIT, outsdif.as_ptr(), &mut status);
+            if status != 0 {
+                return Err(CutestError::FileNotFound);
+            }
+
+            let mut n = 0;
+            let mut m = 0;
+            cdimen(&mut status, &FUNIT, &mut n, &mut m);
+            if let Err(err) = check("cdimen", status) {
+                close(&FUNIT, &mut status);
+                return Err(err);
+            }
+
+            let result = if m == 0 {
+                Self::setup_unconstrained(lib, n)
+            } else {
+                Self::setup_constrained(lib, n, m)
+            };
+            close(&FUNIT, &mut status);
+            result
+        }
+    }
+
+    /// Set up an unconstrained problem with `n` variables from the open `OUTSDIF.d` file.
+    unsafe fn setup_unconstrained(lib: Library, n: Integer) -> Result<Self, CutestError> {
+        let usetup: UsetupFn = symbol(&lib, "cutest_usetup_")?;
+        let udimsh: DimsFn = symbol(&lib, "cutest_udimsh_")?;
+        let ushp: ShpFn = symbol(&lib, "cutest_ushp_")?;
+        let routines = UnconstrainedFns {
+            ufn: symbol(&lib, "cutest_ufn_")?,
+            ugr: symbol(&lib, "cutest_ugr_")?,
+            ush: symbol(&lib, "cutest_ush_")?,
+        };
+        let terminate: TerminateFn = symbol(&lib, "cutest_uterminate_")?;
+
+        let num_variables = n as usize;
+        let mut x0 = vec![0.0; num_variables];
+        let mut x_l = vec![0.0; num_variables];
+        let mut x_u = vec![0.0; num_variables];
+        let mut status = 0;
+        let mut n = n;
+        usetup(
+            &mut status,
+            &FUNIT,
+            &IOUT,
+            &IO_BUFFER,
+            &mut n,
+            x0.as_mut_ptr(),
+            x_l.as_mut_ptr(),
+            x_u.as_mut_ptr(),
+        );
+        check("usetup", status)?;
+
+        let hessian = {
+            let mut nnzh = 0;
+            udimsh(&mut status, &mut nnzh);
+            check("udimsh", status)?;
+            let mut rows = vec![0; nnzh as usize];
+            let mut cols = vec![0; nnzh as usize];
+            ushp(
+                &mut status,
+                &n,
+                &mut nnzh,
+                &nnzh,
+                rows.as_mut_ptr(),
+                cols.as_mut_ptr(),
+            );
+            check("ushp", status)?;
+            Pattern::new(
+                rows.into_iter()
+                    .zip(cols)
+                    .take(nnzh as usize)
+                    .map(|(r, c)| lower(r, c)),
+            )
+        };
+
+        Ok(CutestProblem {
+            routines: Routines::Unconstrained(routines),
+            terminate,
+            num_variables,
+            num_constraints: 0,
+            x0,
+            x_l,
+            x_u,
+            lambda0: Vec::new(),
+            g_l: Vec::new(),
+            g_u: Vec::new(),
+            equality: Vec::new(),
+            linear: Vec::new(),
+            jacobian: Pattern::new(std::iter::empty()),
+            hessian,
+            _lib: lib,
+        })
+    }
+
+    /// Set up a constrained problem with `n` variables and `m` constraints from the open
+    /// `OUTSDIF.d` file.
+    unsafe fn setup_constrained(lib: Library, n: Integer, m: Integer) -> Result<Self, CutestError> {
+        let csetup: CsetupFn = symbol(&lib, "cutest_csetup_")?;
+        let cdimsj: DimsFn = symbol(&lib, "cutest_cdimsj_")?;
+        let csgrp: CsgrpFn = symbol(&lib, "cutest_csgrp_")?;
+        let cdimsh: DimsFn = symbol(&lib, "cutest_cdimsh_")?;
+        let cshp: ShpFn = symbol(&lib, "cutest_cshp_")?;
+        let routines = ConstrainedFns {
+            cfn: symbol(&lib, "cutest_cfn_")?,
+            cofg: symbol(&lib, "cutest_cofg_")?,
+            csgr: symbol(&lib, "cutest_csgr_")?,
+            cshj: symbol(&lib, "cutest_cshj_")?,
+        };
+        let terminate: TerminateFn = symbol(&lib, "cutest_cterminate_")?;
+
+        let num_variables = n as usize;
+        let num_constraints = m as usize;
+        let mut x0 = vec![0.0; num_variables];
+        let mut x_l = vec![0.0; num_variables];
+        let mut x_u = vec![0.0; num_variables];
+        let mut lambda0 = vec![0.0; num_constraints];
+        let mut g_l = vec![0.0; num_constraints];
+        let mut g_u = vec![0.0; num_constraints];
+        let mut equality = vec![false; num_constraints];
+        let mut linear = vec![false; num_constraints];
+        let order = 0;
+        let mut status = 0;
+        let (mut n, mut m) = (n, m);
+        csetup(
+            &mut status,
+            &FUNIT,
+            &IOUT,
+            &IO_BUFFER,
+            &mut n,
+            &mut m,
+            x0.as_mut_ptr(),
+            x_l.as_mut_ptr(),
+            x_u.as_mut_ptr(),
+            lambda0.as_mut_ptr(),
+            g_l.as_mut_ptr(),
+            g_u.as_mut_ptr(),
+            equality.as_mut_ptr(),
+            linear.as_mut_ptr(),
+            &order,
+            &order,
+            &order,
+        );
+        check("csetup", status)?;
+
+        // The pattern includes the objective gradient as row zero, which is dropped here.
+        let jacobian = {
+            let mut nnzj = 0;
+            cdimsj(&mut status, &mut nnzj);
+            check("cdimsj", status)?;
+            let mut vars = vec![0; nnzj as usize];
+            let mut funs = vec![0; nnzj as usize];
+            csgrp(
+                &mut status,
+                &n,
+                &mut nnzj,
+                &nnzj,
+                vars.as_mut_ptr(),
+                funs.as_mut_ptr(),
+            );
+            check("csgrp", status)?;
+            Pattern::new(
+                funs.into_iter()
+                    .zip(vars)
+                    .take(nnzj as usize)
+                    .filter(|&(fun, _)| fun > 0),
+            )
+        };
+
+        let hessian = {
+            let mut nnzh = 0;
+            cdimsh(&mut status, &mut nnzh);
+            check("cdimsh", status)?;
+            let mut rows = vec![0; nnzh as usize];
+            let mut cols = vec![0; nnzh as usize];
+            cshp(
+                &mut status,
+                &n,
+                &mut nnzh,
+                &nnzh,
+                rows.as_mut_ptr(),
+                cols.as_mut_ptr(),
+            );
+            check("cshp", status)?;
+            Pattern::new(
+                rows.into_iter()
+                    .zip(cols)
+                    .take(nnzh as usize)
+                    .map(|(r, c)| lower(r, c)),
+            )
+        };
+
+        Ok(CutestProblem {
+            routines: Routines::Constrained(routines),
+            terminate,
+            num_variables,
+            num_constraints,
+            x0,
+            x_l,
+            x_u,
+            lambda0,
+            g_l,
+            g_u,
+            equality,
+            linear,
+            jacobian,
+            hessian,
+            _lib: lib,
+        })
+    }
+
+    /// For each constraint, whether it is an equality constraint.
+    pub fn equality_constraints(&self) -> &[bool] {
+        &self.equality
+    }
+
+    /// For each constraint, whether it is linear.
+    pub fn linear_constraints(&self) -> &[bool] {
+        &self.linear
+    }
+}
+
+impl Drop for CutestProblem {
+    fn drop(&mut self) {
+        let mut status = 0;
+        unsafe { (self.terminate)(&mut status) };
+    }
+}
+
+impl BasicProblem for CutestProblem {
+    fn indexing_style(&self) -> IndexingStyle {
+        IndexingStyle::FortranStyle
+    }
+    fn num_variables(&self) -> usize {
+        self.num_variables
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&self.x_l);
+        x_u.copy_from_slice(&self.x_u);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&self.x0);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        let n = self.num_variables as Integer;
+        let mut status = 0;
+        match self.routines {
+            Routines::Unconstrained(ref r) => unsafe { (r.ufn)(&mut status, &n, x.as_ptr(), obj) },
+            Routines::Constrained(ref r) => {
+                let grad = false;
+                unsafe {
+                    (r.cofg)(
+                        &mut status,
+                        &n,
+                        x.as_ptr(),
+                        obj,
+                        std::ptr::null_mut(),
+                        &grad,
+                    )
+                }
+            }
+        }
+        status == 0
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        let n = self.num_variables as Integer;
+        let mut status = 0;
+        match self.routines {
+            Routines::Unconstrained(ref r) => unsafe {
+                (r.ugr)(&mut status, &n, x.as_ptr(), grad_f.as_mut_ptr())
+            },
+            Routines::Constrained(ref r) => {
+                let grad = true;
+                let mut obj = 0.0;
+                unsafe {
+                    (r.cofg)(
+                        &mut status,
+                        &n,
+                        x.as_ptr(),
+                        &mut obj,
+                        grad_f.as_mut_ptr(),
+                        &grad,
+                    )
+                }
+            }
+        }
+        status == 0
+    }
+}
+
+impl ConstrainedProblem for CutestProblem {
+    fn num_constraints(&self) -> usize {
+        self.num_constraints
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        self.jacobian.len()
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        if let Routines::Constrained(ref r) = self.routines {
+            let n = self.num_variables as Integer;
+            let m = self.num_constraints as Integer;
+            let mut status = 0;
+            let mut obj = 0.0;
+            unsafe { (r.cfn)(&mut status, &n, &m, x.as_ptr(), &mut obj, g.as_mut_ptr()) };
+            status == 0
+        } else {
+            true
+        }
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l.copy_from_slice(&self.g_l);
+        g_u.copy_from_slice(&self.g_u);
+        true
+    }
+    fn initial_constraint_multipliers(&self, lambda: &mut [Number]) -> bool {
+        lambda.copy_from_slice(&self.lambda0);
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&self.jacobian.rows);
+        cols.copy_from_slice(&self.jacobian.cols);
+        true
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        let r = match self.routines {
+            Routines::Constrained(ref r) => r,
+            Routines::Unconstrained(_) => return true,
+        };
+        let n = self.num_variables as Integer;
+        let m = self.num_constraints as Integer;
+        // Leave room for the objective gradient, which is always included by csgr.
+        let lj = (self.jacobian.len() + self.num_variables) as Integer;
+        let mut nnzj = 0;
+        let mut jac = vec![0.0; lj as usize];
+        let mut vars = vec![0; lj as usize];
+        let mut funs = vec![0; lj as usize];
+        let y = vec![0.0; self.num_constraints];
+        let grlagf = false;
+        let mut status = 0;
+        unsafe {
+            (r.csgr)(
+                &mut status,
+                &n,
+                &m,
+                x.as_ptr(),
+                y.as_ptr(),
+                &grlagf,
+                &mut nnzj,
+                &lj,
+                jac.as_mut_ptr(),
+                vars.as_mut_ptr(),
+                funs.as_mut_ptr(),
+            )
+        };
+        if status != 0 {
+            return false;
+        }
+        let entries = funs
+            .into_iter()
+            .zip(vars)
+            .zip(jac)
+            .take(nnzj as usize)
+            .filter(|&((fun, _), _)| fun > 0);
+        self.jacobian.scatter(entries, vals)
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        self.hessian.len()
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&self.hessian.rows);
+        cols.copy_from_slice(&self.hessian.cols);
+        true
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        let n = self.num_variables as Integer;
+        let m = self.num_constraints as Integer;
+        let lh = self.hessian.len() as Integer;
+        let mut nnzh = 0;
+        let mut hess = vec![0.0; lh as usize];
+        let mut rows = vec![0; lh as usize];
+        let mut cols = vec![0; lh as usize];
+        let mut status = 0;
+        match self.routines {
+            Routines::Unconstrained(ref r) => unsafe {
+                (r.ush)(
+                    &mut status,
+                    &n,
+                    x.as_ptr(),
+                    &mut nnzh,
+                    &lh,
+                    hess.as_mut_ptr(),
+                    rows.as_mut_ptr(),
+                    cols.as_mut_ptr(),
+                );
+                for h in hess.iter_mut() {
+                    *h *= obj_factor;
+                }
+            },
+            Routines::Constrained(ref r) => unsafe {
+                (r.cshj)(
+                    &mut status,
+                    &n,
+                    &m,
+                    x.as_ptr(),
+                    &obj_factor,
+                    lambda.as_ptr(),
+                    &mut nnzh,
+                    &lh,
+                    hess.as_mut_ptr(),
+                    rows.as_mut_ptr(),
+                    cols.as_mut_ptr(),
+                )
+            },
+        }
+        if status != 0 {
+            return false;
+        }
+        let entries = rows
+            .into_iter()
+            .zip(cols)
+            .map(|(r, c)| lower(r, c))
+            .zip(hess)
+            .take(nnzh as usize);
+        self.hessian.scatter(entries, vals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pattern_test() {
+        let pattern = Pattern::new(vec![(2, 1), (1, 1), (2, 2), (2, 1)].into_iter());
+        assert_eq!(pattern.len(), 3);
+        assert_eq!(pattern.rows, vec![2, 1, 2]);
+        assert_eq!(pattern.cols, vec![1, 1, 2]);
+
+        let mut vals = [0.0; 3];
+        let entries = vec![((2, 2), 1.0), ((2, 1), 2.0), ((2, 1), 3.0)];
+        assert!(pattern.scatter(entries.into_iter(), &mut vals));
+        assert_eq!(vals, [5.0, 0.0, 1.0]);
+        assert!(!pattern.scatter(vec![((3, 1), 1.0)].into_iter(), &mut vals));
+        assert_eq!(lower(1, 3), (3, 1));
+    }
+}
@@ -0,0 +1,332 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Problems with dense derivatives.
+//!
+//! For small problems, spelling out the sparsity structure of the Jacobian and the Hessian is
+//! more trouble than it is worth. Problems implementing
+//! [`DenseNewtonProblem`](trait.DenseNewtonProblem.html) or
+//! [`DenseConstrainedProblem`](trait.DenseConstrainedProblem.html) write dense matrices into
+//! row-major slices instead, and wrapping them in [`Dense`](struct.Dense.html) generates the
+//! triplet structure and packs the values for Ipopt.
+//!
+//! The constraint Jacobian is written directly into the Ipopt buffer. The Hessian is written
+//! into a full square scratch matrix, whose lower triangular part is passed to Ipopt. With the
+//! `ndarray` feature, the same is available for problems written with `ndarray` types through
+//! `Ndarray`.
+//!
+//! The generated indices are zero-based regardless of the indexing style of the wrapped problem.
+
+use crate::{AlgorithmMode, BasicProblem, ConstrainedProblem, Index, NewtonProblem, Number};
+
+use std::cell::RefCell;
+
+/// An extension to [`BasicProblem`](trait.BasicProblem.html) providing a dense objective
+/// Hessian for full Newton iterations.
+pub trait DenseNewtonProblem: BasicProblem {
+    /// The dense objective Hessian.
+    ///
+    /// The output is a zeroed `num_variables` by `num_variables` matrix in row-major order. Only
+    /// the lower triangular part is passed to Ipopt, so the upper triangular part need not be
+    /// filled.
+    fn hessian(&self, x: &[Number], hess: &mut [Number]) -> bool;
+}
+
+/// An extension to [`BasicProblem`](trait.BasicProblem.html) with constraints and dense
+/// derivatives.
+pub trait DenseConstrainedProblem: BasicProblem {
+    /// Number of equality and inequality constraints.
+    fn num_constraints(&self) -> usize;
+
+    /// Constraint function.
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool;
+
+    /// Specify lower and upper bounds, `g_l` and `g_u` respectively, on the constraint function.
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool;
+
+    /// Construct the initial guess of the constraint multipliers for Ipopt to start with.
+    ///
+    /// For convenience, the default implementation initializes constraint multipliers to zero.
+    fn initial_constraint_multipliers(&self, lambda: &mut [Number]) -> bool {
+        for l in lambda.iter_mut() {
+            *l = 0.0;
+        }
+        true
+    }
+
+    /// The dense constraint Jacobian with `num_constraints` rows and `num_variables` columns in
+    /// row-major order.
+    fn constraint_jacobian(&self, x: &[Number], jac: &mut [Number]) -> bool;
+
+    /// The dense Hessian of the Lagrangian.
+    ///
+    /// The output is a zeroed `num_variables` by `num_variables` matrix in row-major order. Only
+    /// the lower triangular part is passed to Ipopt, so the upper triangular part need not be
+    /// filled. Write the objective Hessian multiplied by `obj_factor` and constraint Hessians
+    /// multiplied by the corresponding values in `lambda`.
+    fn hessian(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        hess: &mut [Number],
+    ) -> bool;
+}
+
+/// An adapter implementing the Ipopt problem traits for problems with dense derivatives.
+#[derive(Debug)]
+pub struct Dense<P> {
+    problem: P,
+    /// Scratch buffer for the full Hessian.
+    hessian: RefCell<Vec<Number>>,
+}
+
+impl<P: BasicProblem> Dense<P> {
+    /// Wrap a problem with dense derivatives.
+    pub fn new(problem: P) -> Self {
+        let n = problem.num_variables();
+        Dense {
+            problem,
+            hessian: RefCell::new(vec![0.0; n * n]),
+        }
+    }
+
+    /// The wrapped problem.
+    pub fn problem(&self) -> &P {
+        &self.problem
+    }
+
+    /// The wrapped problem.
+    pub fn problem_mut(&mut self) -> &mut P {
+        &mut self.problem
+    }
+
+    /// Unwrap the original problem.
+    pub fn into_inner(self) -> P {
+        self.problem
+    }
+
+    /// Number of entries in the lower triangular part of the Hessian.
+    fn lower_triangle_size(&self) -> usize {
+        let n = self.problem.num_variables();
+        n * (n + 1) / 2
+    }
+
+    /// Write the lower triangular Hessian indices in row-major order.
+    fn lower_triangle_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        let mut k = 0;
+        for i in 0..self.problem.num_variables() {
+            for j in 0..=i {
+                rows[k] = i as Index;
+                cols[k] = j as Index;
+                k += 1;
+            }
+        }
+        true
+    }
+
+    /// Evaluate the full Hessian with `f` and pack its lower triangular part into `vals`.
+    fn packed_hessian(&self, vals: &mut [Number], f: impl FnOnce(&mut [Number]) -> bool) -> bool {
+        let n = self.problem.num_variables();
+        let mut hess = self.hessian.borrow_mut();
+        hess.clear();
+        hess.resize(n * n, 0.0);
+        if !f(&mut hess) {
+            return false;
+        }
+        let mut k = 0;
+        for i in 0..n {
+            vals[k..k + i + 1].copy_from_slice(&hess[i * n..i * n + i + 1]);
+            k += i + 1;
+        }
+        true
+    }
+}
+
+impl<P: BasicProblem> BasicProblem for Dense<P> {
+    fn num_variables(&self) -> usize {
+        self.problem.num_variables()
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        self.problem.bounds(x_l, x_u)
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        self.problem.initial_point(x)
+    }
+    fn initial_bounds_multipliers(&self, z_l: &mut [Number], z_u: &mut [Number]) -> bool {
+        self.problem.initial_bounds_multipliers(z_l, z_u)
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        self.problem.objective(x, obj)
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        self.problem.objective_grad(x, grad_f)
+    }
+    fn variable_scaling(&self, x_scaling: &mut [Number]) -> bool {
+        self.problem.variable_scaling(x_scaling)
+    }
+    fn objective_scaling(&self) -> f64 {
+        self.problem.objective_scaling()
+    }
+    fn algorithm_mode_changed(&mut self, mode: AlgorithmMode) {
+        self.problem.algorithm_mode_changed(mode)
+    }
+}
+
+impl<P: DenseNewtonProblem> NewtonProblem for Dense<P> {
+    fn num_hessian_non_zeros(&self) -> usize {
+        self.lower_triangle_size()
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        self.lower_triangle_indices(rows, cols)
+    }
+    fn hessian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        self.packed_hessian(vals, |hess| self.problem.hessian(x, hess))
+    }
+}
+
+impl<P: DenseConstrainedProblem> ConstrainedProblem for Dense<P> {
+    fn num_constraints(&self) -> usize {
+        self.problem.num_constraints()
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        self.problem.num_constraints() * self.problem.num_variables()
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        self.problem.constraint(x, g)
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        self.problem.constraint_bounds(g_l, g_u)
+    }
+    fn initial_constraint_multipliers(&self, lambda: &mut [Number]) -> bool {
+        self.problem.initial_constraint_multipliers(lambda)
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        let n = self.problem.num_variables();
+        for (k, (r, c)) in rows.iter_mut().zip(cols.iter_mut()).enumerate() {
+            *r = (k / n) as Index;
+            *c = (k % n) as Index;
+        }
+        true
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        self.problem.constraint_jacobian(x, vals)
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        self.lower_triangle_size()
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        self.lower_triangle_indices(rows, cols)
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        self.packed_hessian(vals, |hess| {
+            self.problem.hessian(x, obj_factor, lambda, hess)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimize `x0^2 x1` subject to `x0 + x1^2 = 1`.
+    struct Model;
+
+    impl BasicProblem for Model {
+        fn num_variables(&self) -> usize {
+            2
+        }
+        fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+            x_l.copy_from_slice(&[-1e20; 2]);
+            x_u.copy_from_slice(&[1e20; 2]);
+            true
+        }
+        fn initial_point(&self, x: &mut [Number]) -> bool {
+            x.copy_from_slice(&[0.5, 0.5]);
+            true
+        }
+        fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+            *obj = x[0] * x[0] * x[1];
+            true
+        }
+        fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+            grad_f.copy_from_slice(&[2.0 * x[0] * x[1], x[0] * x[0]]);
+            true
+        }
+    }
+
+    impl DenseConstrainedProblem for Model {
+        fn num_constraints(&self) -> usize {
+            1
+        }
+        fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+            g[0] = x[0] + x[1] * x[1];
+            true
+        }
+        fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+            g_l[0] = 1.0;
+            g_u[0] = 1.0;
+            true
+        }
+        fn constraint_jacobian(&self, x: &[Number], jac: &mut [Number]) -> bool {
+            jac.copy_from_slice(&[1.0, 2.0 * x[1]]);
+            true
+        }
+        fn hessian(
+            &self,
+            x: &[Number],
+            obj_factor: Number,
+            lambda: &[Number],
+            hess: &mut [Number],
+        ) -> bool {
+            hess[0] = obj_factor * 2.0 * x[1];
+            hess[2] = obj_factor * 2.0 * x[0];
+            hess[3] = lambda[0] * 2.0;
+            true
+        }
+    }
+
+    #[test]
+    fn dense_packing_test() {
+        let p = Dense::new(Model);
+        assert_eq!(p.num_constraint_jacobian_non_zeros(), 2);
+        let (mut rows, mut cols) = ([0; 2], [0; 2]);
+        assert!(p.constraint_jacobian_indices(&mut rows, &mut cols));
+        assert_eq!((rows, cols), ([0, 0], [0, 1]));
+
+        assert_eq!(ConstrainedProblem::num_hessian_non_zeros(&p), 3);
+        let (mut rows, mut cols) = ([0; 3], [0; 3]);
+        assert!(ConstrainedProblem::hessian_indices(
+            &p, &mut rows, &mut cols
+        ));
+        assert_eq!((rows, cols), ([0, 1, 1], [0, 0, 1]));
+
+        let mut vals = [0.0; 3];
+        let x = [3.0, 2.0];
+        assert!(ConstrainedProblem::hessian_values(
+            &p,
+            &x,
+            2.0,
+            &[5.0],
+            &mut vals
+        ));
+        assert_eq!(vals, [8.0, 12.0, 10.0]);
+    }
+}
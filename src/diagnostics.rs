@@ -0,0 +1,483 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Diagnostics for common mistakes in problem implementations.
+//!
+//! Mistakes in the callbacks of a problem rarely produce a helpful error inside Ipopt. A panic
+//! in a callback aborts the process, since it cannot unwind through Ipopt, a non-zero count
+//! that disagrees with the index callback leaves garbage indices that crash the linear solver,
+//! and a Hessian given in full double counts the off-diagonal entries and silently produces
+//! wrong steps.
+//!
+//! [`diagnose`](fn.diagnose.html) and [`diagnose_newton`](fn.diagnose_newton.html) call every
+//! callback of a problem once at its initial point, outside of Ipopt, and report each mistake
+//! found as a [`Diagnostic`](enum.Diagnostic.html) with the offending callback and indices:
+//!
+//! ```no_run
+//! # let problem = ipopt::RandomProblem::new(10, 5, 0);
+//! for diagnostic in ipopt::diagnose(&problem) {
+//!     eprintln!("{}", diagnostic);
+//! }
+//! ```
+//!
+//! Panics are caught, but still printed by the panic hook.
+
+use crate::output_check::{first_unwritten, poison};
+use crate::structure::check_entries;
+use crate::{
+    BasicProblem, ConstrainedProblem, Evaluation, Index, IndexingStyle, NewtonProblem, Number,
+    SparsityError, UnwrittenOutput,
+};
+
+use std::any::Any;
+use std::fmt::{Display, Formatter};
+use std::panic::{self, AssertUnwindSafe};
+
+/// Marks index entries not written by an index callback. No indexing style makes it valid.
+const INDEX_SENTINEL: Index = -1;
+
+/// A mistake found in a problem implementation.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Diagnostic {
+    /// A callback panicked, typically because its output has a different length than the
+    /// problem assumes.
+    Panicked {
+        /// Name of the callback.
+        callback: &'static str,
+        /// Length of the output slices given to the callback.
+        len: usize,
+        /// The panic message.
+        message: String,
+    },
+    /// A callback returned `false` at the initial point.
+    Failed {
+        /// Name of the callback.
+        callback: &'static str,
+    },
+    /// An index callback wrote fewer entries than the number of non-zeros reported by the
+    /// problem.
+    NonZeroMismatch {
+        /// The matrix of the index callback.
+        matrix: Evaluation,
+        /// The number of non-zeros reported by the problem.
+        expected: usize,
+        /// The number of leading entries written by the index callback.
+        written: usize,
+    },
+    /// A values callback left an entry of its output unwritten.
+    Unwritten(UnwrittenOutput),
+    /// An off-diagonal Hessian entry is given along with its transpose, which suggests that the
+    /// full Hessian is given instead of its lower triangular part.
+    SymmetricPair {
+        /// Position of the entry in the structure.
+        entry: usize,
+        /// Position of the transposed entry in the structure.
+        transpose: usize,
+        /// Row index of the entry as returned by the problem.
+        row: Index,
+        /// Column index of the entry as returned by the problem.
+        col: Index,
+    },
+    /// An entry of a sparsity structure is invalid.
+    Structure(SparsityError),
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Diagnostic::Panicked {
+                callback,
+                len,
+                message,
+            } => write!(
+                f,
+                "The {} callback panicked with outputs of length {}: {}",
+                callback, len, message
+            ),
+            Diagnostic::Failed { callback } => write!(
+                f,
+                "The {} callback returned false at the initial point.",
+                callback
+            ),
+            Diagnostic::NonZeroMismatch {
+                matrix,
+                expected,
+                written,
+            } => write!(
+                f,
+                "The {} has {} non-zeros, but the index callback only wrote the first {}.",
+                matrix, expected, written
+            ),
+            Diagnostic::Unwritten(unwritten) => write!(f, "The {}.", unwritten),
+            Diagnostic::SymmetricPair {
+                entry,
+                transpose,
+                row,
+                col,
+            } => write!(
+                f,
+                "The Hessian entries {} at ({}, {}) and {} at ({}, {}) are transposes of each \
+                 other. Only the lower triangular part of the Hessian must be given.",
+                entry, row, col, transpose, col, row
+            ),
+            Diagnostic::Structure(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Diagnostic {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+/// Extract the message of a panic payload.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Collects diagnostics while calling the callbacks of a problem.
+struct Diagnoser {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Diagnoser {
+    /// Call a callback with outputs of length `len`, catching panics.
+    ///
+    /// Returns `true` if the callback succeeded.
+    fn call<F: FnOnce() -> bool>(&mut self, callback: &'static str, len: usize, f: F) -> bool {
+        match panic::catch_unwind(AssertUnwindSafe(f)) {
+            Ok(true) => true,
+            Ok(false) => {
+                self.diagnostics.push(Diagnostic::Failed { callback });
+                false
+            }
+            Err(payload) => {
+                self.diagnostics.push(Diagnostic::Panicked {
+                    callback,
+                    len,
+                    message: panic_message(payload.as_ref()),
+                });
+                false
+            }
+        }
+    }
+
+    /// Call a values callback and check that it writes its whole output.
+    fn values<F>(&mut self, callback: &'static str, evaluation: Evaluation, len: usize, f: F)
+    where
+        F: FnOnce(&mut [Number]) -> bool,
+    {
+        let mut out = vec![0.0; len];
+        poison(&mut out);
+        if self.call(callback, len, || f(&mut out)) {
+            if let Some(index) = first_unwritten(&out) {
+                self.diagnostics
+                    .push(Diagnostic::Unwritten(UnwrittenOutput { evaluation, index }));
+            }
+        }
+    }
+
+    /// Call an index callback and check the structure it writes.
+    fn indices<F>(
+        &mut self,
+        callback: &'static str,
+        matrix: Evaluation,
+        style: IndexingStyle,
+        size: (usize, usize),
+        nnz: usize,
+        f: F,
+    ) where
+        F: FnOnce(&mut [Index], &mut [Index]) -> bool,
+    {
+        let (mut rows, mut cols) = (vec![INDEX_SENTINEL; nnz], vec![INDEX_SENTINEL; nnz]);
+        if !self.call(callback, nnz, || f(&mut rows, &mut cols)) {
+            return;
+        }
+
+        let written = rows
+            .iter()
+            .zip(cols.iter())
+            .position(|(&r, &c)| r == INDEX_SENTINEL || c == INDEX_SENTINEL)
+            .unwrap_or(nnz);
+        if written < nnz {
+            self.diagnostics.push(Diagnostic::NonZeroMismatch {
+                matrix,
+                expected: nnz,
+                written,
+            });
+            return;
+        }
+
+        if matrix == Evaluation::Hessian {
+            if let Some(pair) = symmetric_pair(&rows, &cols) {
+                self.diagnostics.push(pair);
+                return;
+            }
+        }
+        if let Err(err) = check_entries(matrix, style, size, &rows, &cols) {
+            self.diagnostics.push(Diagnostic::Structure(err));
+        }
+    }
+
+    /// Diagnose the callbacks common to all problems, returning the initial point if it is
+    /// available.
+    fn basic<P: BasicProblem + ?Sized>(&mut self, problem: &P) -> Option<Vec<Number>> {
+        let n = problem.num_variables();
+        let (mut x_l, mut x_u) = (vec![0.0; n], vec![0.0; n]);
+        self.call("bounds", n, || problem.bounds(&mut x_l, &mut x_u));
+
+        let mut x = vec![0.0; n];
+        if !self.call("initial_point", n, || problem.initial_point(&mut x)) {
+            return None;
+        }
+
+        let mut obj = 0.0;
+        self.call("objective", 1, || problem.objective(&x, &mut obj));
+        self.values("objective_grad", Evaluation::ObjectiveGradient, n, |out| {
+            problem.objective_grad(&x, out)
+        });
+        Some(x)
+    }
+}
+
+/// Find an off-diagonal entry whose transpose is also in the structure.
+fn symmetric_pair(rows: &[Index], cols: &[Index]) -> Option<Diagnostic> {
+    let mut entries: Vec<_> = rows
+        .iter()
+        .zip(cols)
+        .enumerate()
+        .filter(|&(_, (r, c))| r != c)
+        .map(|(i, (&r, &c))| ((r, c), i))
+        .collect();
+    entries.sort_unstable();
+    for &((row, col), entry) in entries.iter() {
+        if row > col {
+            if let Ok(j) = entries.binary_search_by_key(&(col, row), |&(e, _)| e) {
+                return Some(Diagnostic::SymmetricPair {
+                    entry,
+                    transpose: entries[j].1,
+                    row,
+                    col,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Call every callback of an unconstrained problem at its initial point and report the
+/// mistakes found.
+pub fn diagnose_newton<P: NewtonProblem>(problem: &P) -> Vec<Diagnostic> {
+    let mut diagnoser = Diagnoser {
+        diagnostics: Vec::new(),
+    };
+    let n = problem.num_variables();
+    let x = diagnoser.basic(problem);
+
+    let nnz = problem.num_hessian_non_zeros();
+    let style = problem.indexing_style();
+    diagnoser.indices(
+        "hessian_indices",
+        Evaluation::Hessian,
+        style,
+        (n, n),
+        nnz,
+        |rows, cols| problem.hessian_indices(rows, cols),
+    );
+    if let Some(x) = x {
+        diagnoser.values("hessian_values", Evaluation::Hessian, nnz, |out| {
+            problem.hessian_values(&x, out)
+        });
+    }
+    diagnoser.diagnostics
+}
+
+/// Call every callback of a constrained problem at its initial point and report the mistakes
+/// found.
+///
+/// The Hessian is evaluated with an objective factor and constraint multipliers of one.
+pub fn diagnose<P: ConstrainedProblem>(problem: &P) -> Vec<Diagnostic> {
+    let mut diagnoser = Diagnoser {
+        diagnostics: Vec::new(),
+    };
+    let n = problem.num_variables();
+    let m = problem.num_constraints();
+    let style = problem.indexing_style();
+    let x = diagnoser.basic(problem);
+
+    let (mut g_l, mut g_u) = (vec![0.0; m], vec![0.0; m]);
+    diagnoser.call("constraint_bounds", m, || {
+        problem.constraint_bounds(&mut g_l, &mut g_u)
+    });
+    if let Some(x) = x.as_ref() {
+        diagnoser.values("constraint", Evaluation::Constraint, m, |out| {
+            problem.constraint(x, out)
+        });
+    }
+
+    let jac_nnz = problem.num_constraint_jacobian_non_zeros();
+    diagnoser.indices(
+        "constraint_jacobian_indices",
+        Evaluation::ConstraintJacobian,
+        style,
+        (m, n),
+        jac_nnz,
+        |rows, cols| problem.constraint_jacobian_indices(rows, cols),
+    );
+    if let Some(x) = x.as_ref() {
+        diagnoser.values(
+            "constraint_jacobian_values",
+            Evaluation::ConstraintJacobian,
+            jac_nnz,
+            |out| problem.constraint_jacobian_values(x, out),
+        );
+    }
+
+    let hess_nnz = problem.num_hessian_non_zeros();
+    diagnoser.indices(
+        "hessian_indices",
+        Evaluation::Hessian,
+        style,
+        (n, n),
+        hess_nnz,
+        |rows, cols| problem.hessian_indices(rows, cols),
+    );
+    if let Some(x) = x.as_ref() {
+        let lambda = vec![1.0; m];
+        diagnoser.values("hessian_values", Evaluation::Hessian, hess_nnz, |out| {
+            problem.hessian_values(x, 1.0, &lambda, out)
+        });
+    }
+    diagnoser.diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A problem with two variables and one constraint that makes several mistakes.
+    struct Sloppy;
+
+    impl BasicProblem for Sloppy {
+        fn num_variables(&self) -> usize {
+            2
+        }
+        fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+            // Bounds for three variables.
+            x_l.copy_from_slice(&[0.0; 3]);
+            x_u.copy_from_slice(&[1.0; 3]);
+            true
+        }
+        fn initial_point(&self, x: &mut [Number]) -> bool {
+            x.copy_from_slice(&[0.5, 0.5]);
+            true
+        }
+        fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+            *obj = x[0] * x[1];
+            true
+        }
+        fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+            grad_f[0] = x[1];
+            grad_f[1] = x[0];
+            true
+        }
+    }
+
+    impl ConstrainedProblem for Sloppy {
+        fn num_constraints(&self) -> usize {
+            1
+        }
+        fn num_constraint_jacobian_non_zeros(&self) -> usize {
+            2
+        }
+        fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+            g[0] = x[0] + x[1];
+            true
+        }
+        fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+            g_l[0] = 1.0;
+            g_u[0] = 1.0;
+            true
+        }
+        fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+            // Only the first entry is written.
+            rows[0] = 0;
+            cols[0] = 0;
+            true
+        }
+        fn constraint_jacobian_values(&self, _x: &[Number], vals: &mut [Number]) -> bool {
+            vals[0] = 1.0;
+            true
+        }
+        fn num_hessian_non_zeros(&self) -> usize {
+            2
+        }
+        fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+            // The full Hessian.
+            rows.copy_from_slice(&[1, 0]);
+            cols.copy_from_slice(&[0, 1]);
+            true
+        }
+        fn hessian_values(
+            &self,
+            _x: &[Number],
+            obj_factor: Number,
+            _lambda: &[Number],
+            vals: &mut [Number],
+        ) -> bool {
+            vals.copy_from_slice(&[obj_factor, obj_factor]);
+            true
+        }
+    }
+
+    #[test]
+    fn diagnose_test() {
+        let diagnostics = diagnose(&Sloppy);
+
+        assert_eq!(diagnostics.len(), 4);
+        match &diagnostics[0] {
+            Diagnostic::Panicked { callback, len, .. } => {
+                assert_eq!((*callback, *len), ("bounds", 2));
+            }
+            other => panic!("unexpected diagnostic: {}", other),
+        }
+        assert_eq!(
+            diagnostics[1],
+            Diagnostic::NonZeroMismatch {
+                matrix: Evaluation::ConstraintJacobian,
+                expected: 2,
+                written: 1
+            }
+        );
+        assert_eq!(
+            diagnostics[2],
+            Diagnostic::Unwritten(UnwrittenOutput {
+                evaluation: Evaluation::ConstraintJacobian,
+                index: 1
+            })
+        );
+        assert_eq!(
+            diagnostics[3].to_string(),
+            "The Hessian entries 0 at (1, 0) and 1 at (0, 1) are transposes of each other. \
+             Only the lower triangular part of the Hessian must be given."
+        );
+    }
+}
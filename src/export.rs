@@ -0,0 +1,547 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Export of problems to file formats understood by other solvers and tools.
+//!
+//! This is intended for cross-validating a model defined in Rust with other solvers. Since
+//! problems are given only through callbacks, nonlinear functions are approximated by sampling
+//! derivatives at a given point: the objective is replaced by its second order Taylor expansion
+//! and the constraints by their first order Taylor expansion. For linear and quadratic programs
+//! the exported model is therefore exact.
+//!
+//! For debugging, the constraint Jacobian and the Hessian of the Lagrangian at a given point can
+//! also be dumped in MatrixMarket format.
+
+use crate::{ConstrainedProblem, Index, IndexingStyle, Number, BOUND_INF};
+
+use std::fmt::{Display, Formatter};
+use std::io::{self, Write};
+
+/// Export error type.
+#[derive(Debug)]
+pub enum ExportError {
+    /// The given point does not have the same size as the number of variables in the problem.
+    InvalidPoint,
+    /// One of the problem callbacks returned `false`.
+    EvaluationFailed,
+    /// The sparsity structure of the Jacobian or Hessian has indices out of bounds.
+    InvalidStructure,
+    /// Writing the output failed.
+    Io(io::Error),
+}
+
+impl Display for ExportError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ExportError::InvalidPoint => write!(
+                f,
+                "The size of the given point does not match the number of variables."
+            ),
+            ExportError::EvaluationFailed => write!(f, "Failed to evaluate the problem."),
+            ExportError::InvalidStructure => {
+                write!(f, "The sparsity structure has indices out of bounds.")
+            }
+            ExportError::Io(err) => write!(f, "Failed to write the problem: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ExportError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ExportError {
+    fn from(err: io::Error) -> Self {
+        ExportError::Io(err)
+    }
+}
+
+/// A sparse matrix in triplet form with zero-based indices.
+pub(crate) struct TripletMatrix {
+    pub(crate) rows: Vec<usize>,
+    pub(crate) cols: Vec<usize>,
+    pub(crate) vals: Vec<Number>,
+}
+
+impl TripletMatrix {
+    /// Convert the given sparsity pattern into zero-based indices, checking that all indices are
+    /// within the given matrix dimensions.
+    fn new(
+        rows: &[Index],
+        cols: &[Index],
+        vals: Vec<Number>,
+        (nrows, ncols): (usize, usize),
+        style: IndexingStyle,
+    ) -> Result<Self, ExportError> {
+        let offset = match style {
+            IndexingStyle::CStyle => 0,
+            IndexingStyle::FortranStyle => 1,
+        };
+        let to_zero_based = |indices: &[Index], size: usize| {
+            indices
+                .iter()
+                .map(|&i| {
+                    let i = i - offset;
+                    if i >= 0 && (i as usize) < size {
+                        Ok(i as usize)
+                    } else {
+                        Err(ExportError::InvalidStructure)
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(TripletMatrix {
+            rows: to_zero_based(rows, nrows)?,
+            cols: to_zero_based(cols, ncols)?,
+            vals,
+        })
+    }
+
+    /// Evaluate the constraint Jacobian at `x`.
+    pub(crate) fn constraint_jacobian<P: ConstrainedProblem>(
+        problem: &P,
+        x: &[Number],
+    ) -> Result<Self, ExportError> {
+        let nnz = problem.num_constraint_jacobian_non_zeros();
+        let mut rows = vec![0; nnz];
+        let mut cols = vec![0; nnz];
+        let mut vals = vec![0.0; nnz];
+        if !problem.constraint_jacobian_indices(&mut rows, &mut cols)
+            || !problem.constraint_jacobian_values(x, &mut vals)
+        {
+            return Err(ExportError::EvaluationFailed);
+        }
+        let size = (problem.num_constraints(), problem.num_variables());
+        TripletMatrix::new(&rows, &cols, vals, size, problem.indexing_style())
+    }
+
+    /// Evaluate the lower triangular part of the Hessian of the Lagrangian at `x`.
+    pub(crate) fn hessian<P: ConstrainedProblem>(
+        problem: &P,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+    ) -> Result<Self, ExportError> {
+        let nnz = problem.num_hessian_non_zeros();
+        let mut rows = vec![0; nnz];
+        let mut cols = vec![0; nnz];
+        let mut vals = vec![0.0; nnz];
+        if !problem.hessian_indices(&mut rows, &mut cols)
+            || !problem.hessian_values(x, obj_factor, lambda, &mut vals)
+        {
+            return Err(ExportError::EvaluationFailed);
+        }
+        let n = problem.num_variables();
+        let mut hess = TripletMatrix::new(&rows, &cols, vals, (n, n), problem.indexing_style())?;
+        // Ipopt accepts either triangle, so normalize to the lower triangular part.
+        for (r, c) in hess.rows.iter_mut().zip(hess.cols.iter_mut()) {
+            if *r < *c {
+                std::mem::swap(r, c);
+            }
+        }
+        Ok(hess)
+    }
+
+    /// Collect entries by column, summing duplicates. Entries within each column are sorted by
+    /// row.
+    fn by_column(&self, num_cols: usize) -> Vec<Vec<(usize, Number)>> {
+        let mut columns = vec![Vec::new(); num_cols];
+        for ((&r, &c), &v) in self.rows.iter().zip(self.cols.iter()).zip(self.vals.iter()) {
+            columns[c].push((r, v));
+        }
+        for col in columns.iter_mut() {
+            col.sort_by_key(|&(r, _)| r);
+            col.dedup_by(|(r, v), (prev_r, prev_v)| {
+                if r == prev_r {
+                    *prev_v += *v;
+                    true
+                } else {
+                    false
+                }
+            });
+        }
+        columns
+    }
+}
+
+/// Write the problem to `out` in free MPS format, with the quadratic objective specified in a
+/// `QUADOBJ` section as understood by most QP solvers.
+///
+/// Nonlinear functions are approximated around the point `x`: the objective by a quadratic and
+/// constraints by a linear function. Variables are named `x0, x1, ...` and constraints
+/// `c0, c1, ...` following zero-based indexing regardless of the indexing style of the
+/// problem. Bounds with magnitude larger than `1e19` are treated as infinite.
+///
+/// Constraints without bounds are written as free (`N`) rows.
+pub fn write_mps<P, W>(problem: &P, x: &[Number], name: &str, out: W) -> Result<(), ExportError>
+where
+    P: ConstrainedProblem,
+    W: Write,
+{
+    let n = problem.num_variables();
+    let m = problem.num_constraints();
+    if x.len() != n {
+        return Err(ExportError::InvalidPoint);
+    }
+
+    // Sample the problem at the given point.
+    let mut x_l = vec![0.0; n];
+    let mut x_u = vec![0.0; n];
+    let mut g_l = vec![0.0; m];
+    let mut g_u = vec![0.0; m];
+    let mut f = 0.0;
+    let mut grad_f = vec![0.0; n];
+    let mut g = vec![0.0; m];
+    if !problem.bounds(&mut x_l, &mut x_u)
+        || !problem.constraint_bounds(&mut g_l, &mut g_u)
+        || !problem.objective(x, &mut f)
+        || !problem.objective_grad(x, &mut grad_f)
+        || !problem.constraint(x, &mut g)
+    {
+        return Err(ExportError::EvaluationFailed);
+    }
+    let jac = TripletMatrix::constraint_jacobian(problem, x)?.by_column(n);
+    let hess = TripletMatrix::hessian(problem, x, 1.0, &vec![0.0; m])?.by_column(n);
+
+    // Expand f(x0) + grad_f.(x - x0) + (x - x0)'H(x - x0)/2 into c + b.x + x'Hx/2.
+    let mut hx = vec![0.0; n];
+    for (j, col) in hess.iter().enumerate() {
+        for &(i, v) in col.iter() {
+            hx[i] += v * x[j];
+            if i != j {
+                hx[j] += v * x[i];
+            }
+        }
+    }
+    let mut obj_constant = f;
+    for i in 0..n {
+        obj_constant += x[i] * (0.5 * hx[i] - grad_f[i]);
+        grad_f[i] -= hx[i];
+    }
+
+    // Similarly g(x0) + J(x - x0) = J x + (g(x0) - J x0), so shift the constraint bounds.
+    for (j, col) in jac.iter().enumerate() {
+        for &(i, v) in col.iter() {
+            g[i] -= v * x[j];
+        }
+    }
+
+    let mut out = io::BufWriter::new(out);
+    writeln!(out, "NAME {}", name)?;
+
+    writeln!(out, "ROWS")?;
+    writeln!(out, " N obj")?;
+    for i in 0..m {
+        let row_type = match (g_l[i] > -BOUND_INF, g_u[i] < BOUND_INF) {
+            (true, true) if g_l[i] == g_u[i] => "E",
+            (true, _) => "G",
+            (false, true) => "L",
+            (false, false) => "N",
+        };
+        writeln!(out, " {} c{}", row_type, i)?;
+    }
+
+    writeln!(out, "COLUMNS")?;
+    for j in 0..n {
+        if grad_f[j] != 0.0 {
+            writeln!(out, "    x{} obj {:e}", j, grad_f[j])?;
+        }
+        for &(i, v) in jac[j].iter() {
+            if v != 0.0 {
+                writeln!(out, "    x{} c{} {:e}", j, i, v)?;
+            }
+        }
+    }
+
+    writeln!(out, "RHS")?;
+    if obj_constant != 0.0 {
+        // The objective RHS is the negated objective constant.
+        writeln!(out, "    rhs obj {:e}", -obj_constant)?;
+    }
+    for i in 0..m {
+        let rhs = if g_l[i] > -BOUND_INF {
+            g_l[i]
+        } else if g_u[i] < BOUND_INF {
+            g_u[i]
+        } else {
+            continue;
+        };
+        if rhs - g[i] != 0.0 {
+            writeln!(out, "    rhs c{} {:e}", i, rhs - g[i])?;
+        }
+    }
+
+    let ranged: Vec<_> = (0..m)
+        .filter(|&i| g_l[i] > -BOUND_INF && g_u[i] < BOUND_INF && g_l[i] != g_u[i])
+        .collect();
+    if !ranged.is_empty() {
+        writeln!(out, "RANGES")?;
+        for i in ranged {
+            writeln!(out, "    rng c{} {:e}", i, g_u[i] - g_l[i])?;
+        }
+    }
+
+    writeln!(out, "BOUNDS")?;
+    for j in 0..n {
+        match (x_l[j] > -BOUND_INF, x_u[j] < BOUND_INF) {
+            (true, true) if x_l[j] == x_u[j] => writeln!(out, " FX bnd x{} {:e}", j, x_l[j])?,
+            (true, has_upper) => {
+                writeln!(out, " LO bnd x{} {:e}", j, x_l[j])?;
+                if has_upper {
+                    writeln!(out, " UP bnd x{} {:e}", j, x_u[j])?;
+                }
+            }
+            (false, true) => {
+                writeln!(out, " MI bnd x{}", j)?;
+                writeln!(out, " UP bnd x{} {:e}", j, x_u[j])?;
+            }
+            (false, false) => writeln!(out, " FR bnd x{}", j)?,
+        }
+    }
+
+    if hess.iter().any(|col| col.iter().any(|&(_, v)| v != 0.0)) {
+        writeln!(out, "QUADOBJ")?;
+        for (j, col) in hess.iter().enumerate() {
+            for &(i, v) in col.iter() {
+                if v != 0.0 {
+                    writeln!(out, "    x{} x{} {:e}", j, i, v)?;
+                }
+            }
+        }
+    }
+
+    writeln!(out, "ENDATA")?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Write the constraint Jacobian evaluated at `x` to `out` in MatrixMarket coordinate format.
+///
+/// This is useful for inspecting the sparsity and conditioning of the Jacobian in external tools.
+/// Duplicate entries in the sparsity pattern are summed and entries are written in column-major
+/// order with one-based indices as required by the format.
+pub fn write_jacobian_matrix_market<P, W>(
+    problem: &P,
+    x: &[Number],
+    out: W,
+) -> Result<(), ExportError>
+where
+    P: ConstrainedProblem,
+    W: Write,
+{
+    let n = problem.num_variables();
+    if x.len() != n {
+        return Err(ExportError::InvalidPoint);
+    }
+    let jac = TripletMatrix::constraint_jacobian(problem, x)?.by_column(n);
+    write_matrix_market(&jac, problem.num_constraints(), "general", out)
+}
+
+/// Write the Hessian of the Lagrangian evaluated at `x` to `out` in MatrixMarket coordinate
+/// format.
+///
+/// The arguments `obj_factor` and `lambda` are forwarded to
+/// [`ConstrainedProblem::hessian_values`](trait.ConstrainedProblem.html#tymethod.hessian_values).
+/// The matrix is written as symmetric, meaning only the lower triangular part is stored. Entries
+/// given in the upper triangular part by the problem are mirrored to the lower triangular part.
+pub fn write_hessian_matrix_market<P, W>(
+    problem: &P,
+    x: &[Number],
+    obj_factor: Number,
+    lambda: &[Number],
+    out: W,
+) -> Result<(), ExportError>
+where
+    P: ConstrainedProblem,
+    W: Write,
+{
+    let n = problem.num_variables();
+    if x.len() != n || lambda.len() != problem.num_constraints() {
+        return Err(ExportError::InvalidPoint);
+    }
+    let hess = TripletMatrix::hessian(problem, x, obj_factor, lambda)?.by_column(n);
+    write_matrix_market(&hess, n, "symmetric", out)
+}
+
+/// Write a matrix given by columns in MatrixMarket coordinate format with the given symmetry
+/// qualifier.
+fn write_matrix_market<W: Write>(
+    columns: &[Vec<(usize, Number)>],
+    num_rows: usize,
+    symmetry: &str,
+    out: W,
+) -> Result<(), ExportError> {
+    let mut out = io::BufWriter::new(out);
+    let nnz: usize = columns.iter().map(|col| col.len()).sum();
+    writeln!(out, "%%MatrixMarket matrix coordinate real {}", symmetry)?;
+    writeln!(out, "{} {} {}", num_rows, columns.len(), nnz)?;
+    for (j, col) in columns.iter().enumerate() {
+        for &(i, v) in col.iter() {
+            writeln!(out, "{} {} {:e}", i + 1, j + 1, v)?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BasicProblem;
+
+    /// min (x0 - 1)^2 + x0 x1 + 3 x1
+    /// s.t. 1 <= x0 + x1 <= 4
+    ///      x0^2 = 2
+    ///      0 <= x0, x1 <= 5
+    struct Qp;
+
+    impl BasicProblem for Qp {
+        fn num_variables(&self) -> usize {
+            2
+        }
+        fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+            x_l.copy_from_slice(&[0.0, -2e19]);
+            x_u.copy_from_slice(&[2e19, 5.0]);
+            true
+        }
+        fn initial_point(&self, _: &mut [Number]) -> bool {
+            false
+        }
+        fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+            *obj = (x[0] - 1.0) * (x[0] - 1.0) + x[0] * x[1] + 3.0 * x[1];
+            true
+        }
+        fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+            grad_f[0] = 2.0 * (x[0] - 1.0) + x[1];
+            grad_f[1] = x[0] + 3.0;
+            true
+        }
+    }
+
+    impl ConstrainedProblem for Qp {
+        fn num_constraints(&self) -> usize {
+            2
+        }
+        fn num_constraint_jacobian_non_zeros(&self) -> usize {
+            3
+        }
+        fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+            g[0] = x[0] + x[1];
+            g[1] = x[0] * x[0];
+            true
+        }
+        fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+            g_l.copy_from_slice(&[1.0, 2.0]);
+            g_u.copy_from_slice(&[4.0, 2.0]);
+            true
+        }
+        fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+            rows.copy_from_slice(&[0, 0, 1]);
+            cols.copy_from_slice(&[0, 1, 0]);
+            true
+        }
+        fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+            vals.copy_from_slice(&[1.0, 1.0, 2.0 * x[0]]);
+            true
+        }
+        fn num_hessian_non_zeros(&self) -> usize {
+            2
+        }
+        fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+            // Upper triangular entry to check normalization.
+            rows.copy_from_slice(&[0, 0]);
+            cols.copy_from_slice(&[0, 1]);
+            true
+        }
+        fn hessian_values(
+            &self,
+            _: &[Number],
+            obj_factor: Number,
+            lambda: &[Number],
+            vals: &mut [Number],
+        ) -> bool {
+            vals[0] = 2.0 * obj_factor + 2.0 * lambda[1];
+            vals[1] = obj_factor;
+            true
+        }
+    }
+
+    #[test]
+    fn mps_test() {
+        let mut out = Vec::new();
+        write_mps(&Qp, &[1.0, 2.0], "qp", &mut out).unwrap();
+        let expected = "\
+NAME qp
+ROWS
+ N obj
+ G c0
+ E c1
+COLUMNS
+    x0 obj -2e0
+    x0 c0 1e0
+    x0 c1 2e0
+    x1 obj 3e0
+    x1 c0 1e0
+RHS
+    rhs obj -1e0
+    rhs c0 1e0
+    rhs c1 3e0
+RANGES
+    rng c0 3e0
+BOUNDS
+ LO bnd x0 0e0
+ MI bnd x1
+ UP bnd x1 5e0
+QUADOBJ
+    x0 x0 2e0
+    x0 x1 1e0
+ENDATA
+";
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+
+        assert!(matches!(
+            write_mps(&Qp, &[1.0], "qp", Vec::new()),
+            Err(ExportError::InvalidPoint)
+        ));
+    }
+
+    #[test]
+    fn matrix_market_test() {
+        let mut out = Vec::new();
+        write_jacobian_matrix_market(&Qp, &[3.0, 2.0], &mut out).unwrap();
+        let expected = "\
+%%MatrixMarket matrix coordinate real general
+2 2 3
+1 1 1e0
+2 1 6e0
+1 2 1e0
+";
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+
+        let mut out = Vec::new();
+        write_hessian_matrix_market(&Qp, &[3.0, 2.0], 0.5, &[0.0, 1.0], &mut out).unwrap();
+        let expected = "\
+%%MatrixMarket matrix coordinate real symmetric
+2 2 2
+1 1 3e0
+2 1 5e-1
+";
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+}
@@ -0,0 +1,217 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Interoperability with sparse matrices from [`faer`](https://docs.rs/faer).
+//!
+//! This module converts between the triplet representation used by the problem callbacks and
+//! compressed `faer` matrices. It also provides [`kkt_diagnostics`](fn.kkt_diagnostics.html),
+//! which factorizes the matrices Ipopt works with at a given point independently of the linear
+//! solver used by Ipopt. This is useful for detecting rank deficient Jacobians or an indefinite
+//! Hessian when Ipopt struggles to converge.
+
+use crate::export::TripletMatrix;
+use crate::{ConstrainedProblem, ExportError, Index, IndexingStyle, Number};
+
+use faer::prelude::SpSolver;
+use faer::sparse::{CreationError, SparseColMat};
+use faer::{Mat, Side};
+
+/// Build a compressed sparse column matrix from triplets as given by the problem callbacks.
+///
+/// Duplicate entries are summed.
+pub fn triplets_to_faer(
+    nrows: usize,
+    ncols: usize,
+    rows: &[Index],
+    cols: &[Index],
+    vals: &[Number],
+    style: IndexingStyle,
+) -> Result<SparseColMat<usize, Number>, CreationError> {
+    let offset = match style {
+        IndexingStyle::CStyle => 0,
+        IndexingStyle::FortranStyle => 1,
+    };
+    let triplets: Vec<_> = rows
+        .iter()
+        .zip(cols.iter())
+        .zip(vals.iter())
+        .map(|((&r, &c), &v)| ((r - offset) as usize, (c - offset) as usize, v))
+        .collect();
+    SparseColMat::try_new_from_triplets(nrows, ncols, &triplets)
+}
+
+/// Convert a compressed sparse column matrix into triplets using the given indexing style.
+///
+/// Entries are produced in column-major order.
+pub fn faer_to_triplets(
+    mtx: &SparseColMat<usize, Number>,
+    style: IndexingStyle,
+) -> (Vec<Index>, Vec<Index>, Vec<Number>) {
+    let offset = match style {
+        IndexingStyle::CStyle => 0,
+        IndexingStyle::FortranStyle => 1,
+    };
+    let mut rows = Vec::new();
+    let mut cols = Vec::new();
+    let mut vals = Vec::new();
+    for j in 0..mtx.ncols() {
+        for (i, &v) in mtx.row_indices_of_col(j).zip(mtx.values_of_col(j).iter()) {
+            rows.push(i as Index + offset);
+            cols.push(j as Index + offset);
+            vals.push(v);
+        }
+    }
+    (rows, cols, vals)
+}
+
+fn to_faer(
+    mtx: &TripletMatrix,
+    nrows: usize,
+    ncols: usize,
+) -> Result<SparseColMat<usize, Number>, ExportError> {
+    let triplets: Vec<_> = mtx
+        .rows
+        .iter()
+        .zip(mtx.cols.iter())
+        .zip(mtx.vals.iter())
+        .map(|((&r, &c), &v)| (r, c, v))
+        .collect();
+    SparseColMat::try_new_from_triplets(nrows, ncols, &triplets)
+        .map_err(|_| ExportError::InvalidStructure)
+}
+
+/// Evaluate the constraint Jacobian at `x` as a `faer` sparse matrix.
+pub fn constraint_jacobian_faer<P: ConstrainedProblem>(
+    problem: &P,
+    x: &[Number],
+) -> Result<SparseColMat<usize, Number>, ExportError> {
+    if x.len() != problem.num_variables() {
+        return Err(ExportError::InvalidPoint);
+    }
+    let jac = TripletMatrix::constraint_jacobian(problem, x)?;
+    to_faer(&jac, problem.num_constraints(), problem.num_variables())
+}
+
+/// Evaluate the lower triangular part of the Hessian of the Lagrangian at `x` as a `faer` sparse
+/// matrix.
+pub fn hessian_faer<P: ConstrainedProblem>(
+    problem: &P,
+    x: &[Number],
+    obj_factor: Number,
+    lambda: &[Number],
+) -> Result<SparseColMat<usize, Number>, ExportError> {
+    let n = problem.num_variables();
+    if x.len() != n || lambda.len() != problem.num_constraints() {
+        return Err(ExportError::InvalidPoint);
+    }
+    let hess = TripletMatrix::hessian(problem, x, obj_factor, lambda)?;
+    to_faer(&hess, n, n)
+}
+
+/// Results of factorizing the KKT-relevant matrices at a given point.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct KktDiagnostics {
+    /// Whether a Cholesky factorization of the Hessian of the Lagrangian succeeded, meaning that
+    /// the Hessian is positive definite.
+    pub hessian_positive_definite: bool,
+    /// Whether the sparse LU factorization of the KKT matrix succeeded.
+    pub kkt_factorized: bool,
+    /// Relative residual `|K y - b| / |b|` after solving the KKT system `K y = b` with a vector
+    /// of ones for `b`.
+    ///
+    /// A large or non-finite residual indicates that the KKT matrix is singular or badly
+    /// conditioned, for instance because the constraint Jacobian is rank deficient. This is
+    /// `None` if the factorization failed.
+    pub kkt_residual: Option<Number>,
+}
+
+/// Factorize the Hessian of the Lagrangian and the KKT matrix
+///
+/// ```verbatim
+///     K = [ H  J^T ]
+///         [ J   0  ]
+/// ```
+///
+/// at the given primal point `x` and constraint multipliers `lambda` using `faer`.
+pub fn kkt_diagnostics<P: ConstrainedProblem>(
+    problem: &P,
+    x: &[Number],
+    obj_factor: Number,
+    lambda: &[Number],
+) -> Result<KktDiagnostics, ExportError> {
+    let n = problem.num_variables();
+    let m = problem.num_constraints();
+    if x.len() != n || lambda.len() != m {
+        return Err(ExportError::InvalidPoint);
+    }
+
+    let hess = TripletMatrix::hessian(problem, x, obj_factor, lambda)?;
+    let jac = TripletMatrix::constraint_jacobian(problem, x)?;
+
+    let hessian_positive_definite = to_faer(&hess, n, n)?
+        .as_ref()
+        .sp_cholesky(Side::Lower)
+        .is_ok();
+
+    // Assemble the full symmetric KKT matrix.
+    let mut kkt = Vec::with_capacity(2 * (hess.vals.len() + jac.vals.len()));
+    for ((&r, &c), &v) in hess.rows.iter().zip(hess.cols.iter()).zip(hess.vals.iter()) {
+        kkt.push((r, c, v));
+        if r != c {
+            kkt.push((c, r, v));
+        }
+    }
+    for ((&r, &c), &v) in jac.rows.iter().zip(jac.cols.iter()).zip(jac.vals.iter()) {
+        kkt.push((n + r, c, v));
+        kkt.push((c, n + r, v));
+    }
+    let size = n + m;
+    let kkt_mtx = SparseColMat::try_new_from_triplets(size, size, &kkt)
+        .map_err(|_| ExportError::InvalidStructure)?;
+
+    let kkt_residual = kkt_mtx.as_ref().sp_lu().ok().map(|lu| {
+        let b = Mat::<Number>::from_fn(size, 1, |_, _| 1.0);
+        let y = lu.solve(&b);
+        let mut res = vec![-1.0; size];
+        for &(r, c, v) in kkt.iter() {
+            res[r] += v * y.read(c, 0);
+        }
+        let norm = res.iter().map(|r| r * r).sum::<Number>().sqrt();
+        norm / (size as Number).sqrt()
+    });
+
+    Ok(KktDiagnostics {
+        hessian_positive_definite,
+        kkt_factorized: kkt_residual.is_some(),
+        kkt_residual,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn triplet_conversion_test() {
+        let rows = [1, 2, 1, 3];
+        let cols = [1, 1, 1, 2];
+        let vals = [1.0, 2.0, 3.0, 4.0];
+        let mtx = triplets_to_faer(3, 2, &rows, &cols, &vals, IndexingStyle::FortranStyle).unwrap();
+
+        let (rows, cols, vals) = faer_to_triplets(&mtx, IndexingStyle::CStyle);
+        assert_eq!(rows, vec![0, 1, 2]);
+        assert_eq!(cols, vec![0, 0, 1]);
+        assert_eq!(vals, vec![4.0, 2.0, 4.0]);
+    }
+}
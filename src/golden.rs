@@ -0,0 +1,446 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Regression testing against stored solve results.
+//!
+//! [`assert_golden`](fn.assert_golden.html) solves a problem and compares the status, the
+//! objective value and the solution against a golden file, which guards against regressions
+//! when upgrading Ipopt or changing the model:
+//!
+//! ```no_run
+//! # use ipopt::{assert_golden, GoldenTolerance, Ipopt, RandomProblem};
+//! #[test]
+//! fn random_problem_golden() {
+//!     let mut ipopt = Ipopt::new(RandomProblem::new(40, 25, 7)).unwrap();
+//!     ipopt.set_option("print_level", 0);
+//!     assert_golden(&mut ipopt, "tests/golden/random.golden", GoldenTolerance::default());
+//! }
+//! ```
+//!
+//! Running the tests with the `IPOPT_UPDATE_GOLDEN` environment variable set to `1` writes the
+//! current results to the golden files instead of comparing against them. Golden files are
+//! plain text with one `key = value` entry per line, so changes are easy to review.
+
+use crate::{BasicProblem, Ipopt, Number, SolveResult};
+
+use std::fmt::{Display, Formatter};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// Environment variable enabling the update mode.
+const UPDATE_VAR: &str = "IPOPT_UPDATE_GOLDEN";
+
+/// Tolerances for comparing numbers against golden results.
+///
+/// A value `a` matches the golden value `b` if `|a - b| <= absolute + relative * |b|`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GoldenTolerance {
+    /// Absolute tolerance.
+    pub absolute: Number,
+    /// Tolerance relative to the golden value.
+    pub relative: Number,
+}
+
+impl Default for GoldenTolerance {
+    fn default() -> Self {
+        GoldenTolerance {
+            absolute: 1e-6,
+            relative: 1e-6,
+        }
+    }
+}
+
+impl GoldenTolerance {
+    fn matches(&self, actual: Number, expected: Number) -> bool {
+        if actual.is_nan() || expected.is_nan() {
+            return actual.is_nan() && expected.is_nan();
+        }
+        actual == expected
+            || (actual - expected).abs() <= self.absolute + self.relative * expected.abs()
+    }
+}
+
+/// The compared parts of a solve result.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GoldenResult {
+    /// Name of the solve status, such as `SolveSucceeded`.
+    pub status: String,
+    /// Final objective value.
+    pub objective_value: Number,
+    /// Final primal variables.
+    pub primal_variables: Vec<Number>,
+    /// Final constraint multipliers.
+    pub constraint_multipliers: Vec<Number>,
+}
+
+impl GoldenResult {
+    /// The compared parts of the given solve result.
+    pub fn from_solve_result<P>(result: &SolveResult<P>) -> Self {
+        let solution = &result.solver_data.solution;
+        GoldenResult {
+            status: format!("{:?}", result.status),
+            objective_value: result.objective_value,
+            primal_variables: solution.primal_variables.to_vec(),
+            constraint_multipliers: solution.constraint_multipliers.to_vec(),
+        }
+    }
+
+    /// Write the result in the golden file format.
+    ///
+    /// Numbers are written with enough digits to be read back exactly.
+    pub fn write_to<W: Write>(&self, mut out: W) -> io::Result<()> {
+        let join = |values: &[Number]| {
+            values
+                .iter()
+                .map(|v| format!("{:e}", v))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        writeln!(out, "status = {}", self.status)?;
+        writeln!(out, "objective = {:e}", self.objective_value)?;
+        writeln!(out, "primal_variables = {}", join(&self.primal_variables))?;
+        writeln!(
+            out,
+            "constraint_multipliers = {}",
+            join(&self.constraint_multipliers)
+        )?;
+        out.flush()
+    }
+
+    /// Read a result written by [`write_to`](#method.write_to).
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn read_from<R: BufRead>(input: R) -> Result<Self, GoldenError> {
+        let mut result = GoldenResult {
+            status: String::new(),
+            objective_value: 0.0,
+            primal_variables: Vec::new(),
+            constraint_multipliers: Vec::new(),
+        };
+        for (i, line) in input.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || GoldenError::InvalidLine(i + 1);
+            let mut parts = line.splitn(2, '=');
+            let key = parts.next().unwrap_or("").trim();
+            let value = parts.next().ok_or_else(invalid)?.trim();
+            let numbers = || -> Result<Vec<Number>, GoldenError> {
+                value
+                    .split_whitespace()
+                    .map(|v| v.parse().map_err(|_| invalid()))
+                    .collect()
+            };
+            match key {
+                "status" => result.status = value.to_string(),
+                "objective" => result.objective_value = value.parse().map_err(|_| invalid())?,
+                "primal_variables" => result.primal_variables = numbers()?,
+                "constraint_multipliers" => result.constraint_multipliers = numbers()?,
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(result)
+    }
+
+    /// Compare this result against the golden result `expected`.
+    pub fn compare(
+        &self,
+        expected: &GoldenResult,
+        tolerance: GoldenTolerance,
+    ) -> Vec<GoldenMismatch> {
+        let mut mismatches = Vec::new();
+        if self.status != expected.status {
+            mismatches.push(GoldenMismatch::Status {
+                expected: expected.status.clone(),
+                actual: self.status.clone(),
+            });
+        }
+        if !tolerance.matches(self.objective_value, expected.objective_value) {
+            mismatches.push(GoldenMismatch::Objective {
+                expected: expected.objective_value,
+                actual: self.objective_value,
+            });
+        }
+        let fields = [
+            (
+                "primal_variables",
+                &self.primal_variables,
+                &expected.primal_variables,
+            ),
+            (
+                "constraint_multipliers",
+                &self.constraint_multipliers,
+                &expected.constraint_multipliers,
+            ),
+        ];
+        for &(field, actual, expected) in fields.iter() {
+            if actual.len() != expected.len() {
+                mismatches.push(GoldenMismatch::Size {
+                    field,
+                    expected: expected.len(),
+                    actual: actual.len(),
+                });
+                continue;
+            }
+            let first = actual
+                .iter()
+                .zip(expected.iter())
+                .position(|(&a, &e)| !tolerance.matches(a, e));
+            if let Some(index) = first {
+                mismatches.push(GoldenMismatch::Value {
+                    field,
+                    index,
+                    expected: expected[index],
+                    actual: actual[index],
+                });
+            }
+        }
+        mismatches
+    }
+}
+
+/// A difference between a solve result and its golden result.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GoldenMismatch {
+    /// The solve status differs.
+    Status {
+        /// Golden status.
+        expected: String,
+        /// Status of the solve.
+        actual: String,
+    },
+    /// The objective value differs.
+    Objective {
+        /// Golden objective value.
+        expected: Number,
+        /// Objective value of the solve.
+        actual: Number,
+    },
+    /// A vector has a different size.
+    Size {
+        /// Name of the vector.
+        field: &'static str,
+        /// Golden size.
+        expected: usize,
+        /// Size in the solve.
+        actual: usize,
+    },
+    /// A vector entry differs. Only the first differing entry of each vector is reported.
+    Value {
+        /// Name of the vector.
+        field: &'static str,
+        /// Position of the entry.
+        index: usize,
+        /// Golden value.
+        expected: Number,
+        /// Value in the solve.
+        actual: Number,
+    },
+}
+
+impl Display for GoldenMismatch {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            GoldenMismatch::Status { expected, actual } => {
+                write!(f, "status is {}, expected {}", actual, expected)
+            }
+            GoldenMismatch::Objective { expected, actual } => {
+                write!(f, "objective is {:e}, expected {:e}", actual, expected)
+            }
+            GoldenMismatch::Size {
+                field,
+                expected,
+                actual,
+            } => write!(f, "{} has {} entries, expected {}", field, actual, expected),
+            GoldenMismatch::Value {
+                field,
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{}[{}] is {:e}, expected {:e}",
+                field, index, actual, expected
+            ),
+        }
+    }
+}
+
+/// Error produced when a golden file check fails.
+#[derive(Debug)]
+pub enum GoldenError {
+    /// The golden file does not exist.
+    Missing(PathBuf),
+    /// A line of the golden file could not be parsed.
+    InvalidLine(usize),
+    /// The solve result differs from the golden result.
+    Mismatch {
+        /// Path of the golden file.
+        path: PathBuf,
+        /// The differences found.
+        mismatches: Vec<GoldenMismatch>,
+    },
+    /// Reading or writing the golden file failed.
+    Io(io::Error),
+}
+
+impl Display for GoldenError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            GoldenError::Missing(path) => write!(
+                f,
+                "The golden file {} does not exist. Run with {}=1 to create it.",
+                path.display(),
+                UPDATE_VAR
+            ),
+            GoldenError::InvalidLine(line) => {
+                write!(f, "Line {} of the golden file is invalid.", line)
+            }
+            GoldenError::Mismatch { path, mismatches } => {
+                write!(f, "The solve result differs from {}:", path.display())?;
+                for mismatch in mismatches.iter() {
+                    write!(f, "\n  {}", mismatch)?;
+                }
+                write!(f, "\nRun with {}=1 to update the golden file.", UPDATE_VAR)
+            }
+            GoldenError::Io(err) => write!(f, "Failed to access the golden file: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for GoldenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GoldenError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for GoldenError {
+    fn from(err: io::Error) -> Self {
+        GoldenError::Io(err)
+    }
+}
+
+/// Whether golden files are updated instead of checked.
+fn update_mode() -> bool {
+    match std::env::var(UPDATE_VAR) {
+        Ok(value) => !value.is_empty() && value != "0",
+        Err(_) => false,
+    }
+}
+
+/// Compare a result against the golden file at `path`, or write it in update mode.
+fn check_result(
+    result: &GoldenResult,
+    path: &Path,
+    tolerance: GoldenTolerance,
+) -> Result<(), GoldenError> {
+    if update_mode() {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        return Ok(result.write_to(std::fs::File::create(path)?)?);
+    }
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            return Err(GoldenError::Missing(path.to_path_buf()))
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let expected = GoldenResult::read_from(io::BufReader::new(file))?;
+    let mismatches = result.compare(&expected, tolerance);
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(GoldenError::Mismatch {
+            path: path.to_path_buf(),
+            mismatches,
+        })
+    }
+}
+
+/// Solve with the given solver and compare the result against the golden file at `path`.
+///
+/// In update mode, the result is written to the golden file instead.
+pub fn check_golden<P: BasicProblem, Q: AsRef<Path>>(
+    ipopt: &mut Ipopt<P>,
+    path: Q,
+    tolerance: GoldenTolerance,
+) -> Result<(), GoldenError> {
+    let result = GoldenResult::from_solve_result(&ipopt.solve());
+    check_result(&result, path.as_ref(), tolerance)
+}
+
+/// Solve with the given solver and assert that the result matches the golden file at `path`.
+///
+/// # Panics
+///
+/// This function panics with a description of the differences if the check fails.
+pub fn assert_golden<P: BasicProblem, Q: AsRef<Path>>(
+    ipopt: &mut Ipopt<P>,
+    path: Q,
+    tolerance: GoldenTolerance,
+) {
+    if let Err(err) = check_golden(ipopt, path, tolerance) {
+        panic!("{}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_test() {
+        let golden = GoldenResult {
+            status: "SolveSucceeded".to_string(),
+            objective_value: 17.0140173,
+            primal_variables: vec![1.0, 4.743, 3.82115, 1.37941],
+            constraint_multipliers: vec![-0.552294, 0.161469],
+        };
+        let mut bytes = Vec::new();
+        golden.write_to(&mut bytes).unwrap();
+        let read = GoldenResult::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(read, golden);
+
+        let tolerance = GoldenTolerance::default();
+        let mut actual = golden.clone();
+        actual.primal_variables[1] += 1e-9;
+        assert!(actual.compare(&golden, tolerance).is_empty());
+
+        actual.primal_variables[2] += 1e-3;
+        actual.constraint_multipliers.pop();
+        assert_eq!(
+            actual.compare(&golden, tolerance),
+            vec![
+                GoldenMismatch::Value {
+                    field: "primal_variables",
+                    index: 2,
+                    expected: 3.82115,
+                    actual: 3.82115 + 1e-3,
+                },
+                GoldenMismatch::Size {
+                    field: "constraint_multipliers",
+                    expected: 2,
+                    actual: 1,
+                }
+            ]
+        );
+    }
+}
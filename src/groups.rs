@@ -0,0 +1,291 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Named groups of constraints.
+//!
+//! Large models typically consist of a few families of constraints, such as dynamics, path
+//! constraints and boundary conditions. Reports that list individual constraint indices are hard
+//! to interpret for such models, so constraints can be tagged into named groups by implementing
+//! [`ConstrainedProblem::constraint_groups`](trait.ConstrainedProblem.html#method.constraint_groups).
+//! Violation reports, such as
+//! [`RestorationDiagnostics`](struct.RestorationDiagnostics.html), then name the group of each
+//! violated constraint and aggregate the violations per group, and
+//! [`ActiveSet`](struct.ActiveSet.html) counts the active constraints of each group.
+
+use crate::{ActiveSet, Activity, ConstraintViolation, Number};
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// Name under which constraints that don't belong to any group are reported.
+pub const UNGROUPED: &str = "ungrouped";
+
+/// An assignment of constraints to named groups.
+///
+/// Each constraint belongs to at most one group. If a constraint is added to multiple groups,
+/// the first group it was added to takes precedence.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConstraintGroups {
+    groups: Vec<(String, Vec<usize>)>,
+}
+
+impl ConstraintGroups {
+    /// Create an empty group assignment.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a group with the given name containing the constraints at the given indices.
+    ///
+    /// Indices are zero-based regardless of the indexing style of the problem.
+    pub fn add_group(
+        &mut self,
+        name: impl Into<String>,
+        indices: impl IntoIterator<Item = usize>,
+    ) -> &mut Self {
+        self.groups
+            .push((name.into(), indices.into_iter().collect()));
+        self
+    }
+
+    /// Returns `true` if no groups were added.
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Names of all groups in the order they were added.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.groups.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Name of the group containing the constraint at the given index, if any.
+    pub fn group_of(&self, index: usize) -> Option<&str> {
+        self.groups
+            .iter()
+            .find(|(_, indices)| indices.contains(&index))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Add the groups of `other` with constraint indices shifted by `offset`.
+    pub(crate) fn extend_shifted(&mut self, other: ConstraintGroups, offset: usize) {
+        self.groups.extend(
+            other
+                .groups
+                .into_iter()
+                .map(|(name, indices)| (name, indices.into_iter().map(|i| i + offset).collect())),
+        );
+    }
+
+    /// Map from constraint index to the position of its group.
+    fn assignment(&self) -> HashMap<usize, usize> {
+        let mut assignment = HashMap::new();
+        for (group, (_, indices)) in self.groups.iter().enumerate() {
+            for &i in indices.iter() {
+                assignment.entry(i).or_insert(group);
+            }
+        }
+        assignment
+    }
+
+    /// Name of the group at the given position, where the position one past the last group
+    /// stands for ungrouped constraints.
+    fn name(&self, group: usize) -> &str {
+        self.groups
+            .get(group)
+            .map_or(UNGROUPED, |(name, _)| name.as_str())
+    }
+
+    /// Aggregate constraint violations by group.
+    ///
+    /// Only groups with violated constraints are reported, sorted from the largest to the
+    /// smallest maximum violation. Violated constraints outside of any group are reported under
+    /// [`UNGROUPED`](constant.UNGROUPED.html). Returns an empty vector if there are no groups.
+    pub fn summarize_violations(&self, violations: &[ConstraintViolation]) -> Vec<GroupViolation> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        let assignment = self.assignment();
+        let ungrouped = self.groups.len();
+        let mut summary: Vec<Option<GroupViolation>> = vec![None; ungrouped + 1];
+        for v in violations.iter() {
+            let group = *assignment.get(&v.index).unwrap_or(&ungrouped);
+            let entry = summary[group].get_or_insert_with(|| GroupViolation {
+                name: self.name(group).to_string(),
+                num_violated: 0,
+                max_violation: 0.0,
+                total_violation: 0.0,
+            });
+            entry.num_violated += 1;
+            entry.max_violation = entry.max_violation.max(v.violation);
+            entry.total_violation += v.violation;
+        }
+        let mut summary: Vec<_> = summary.into_iter().flatten().collect();
+        summary.sort_by(|a, b| {
+            b.max_violation
+                .partial_cmp(&a.max_violation)
+                .unwrap_or(Ordering::Equal)
+        });
+        summary
+    }
+
+    /// Count the active constraints of each group.
+    ///
+    /// Every group is reported in the order the groups were added, followed by
+    /// [`UNGROUPED`](constant.UNGROUPED.html) if any active constraint is outside of all groups.
+    /// Returns an empty vector if there are no groups.
+    pub fn summarize_activity(&self, active_set: &ActiveSet) -> Vec<GroupActivity> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        let assignment = self.assignment();
+        let ungrouped = self.groups.len();
+        let mut summary: Vec<_> = (0..=ungrouped)
+            .map(|group| GroupActivity {
+                name: self.name(group).to_string(),
+                num_strongly_active: 0,
+                num_weakly_active: 0,
+            })
+            .collect();
+        for entry in active_set.constraints.iter() {
+            let group = &mut summary[*assignment.get(&entry.index).unwrap_or(&ungrouped)];
+            match entry.activity {
+                Activity::StronglyActive => group.num_strongly_active += 1,
+                Activity::WeaklyActive => group.num_weakly_active += 1,
+                Activity::Inactive => {}
+            }
+        }
+        if summary[ungrouped].num_strongly_active + summary[ungrouped].num_weakly_active == 0 {
+            summary.pop();
+        }
+        summary
+    }
+}
+
+/// Violation of a group of constraints.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroupViolation {
+    /// Name of the group.
+    pub name: String,
+    /// Number of violated constraints in the group.
+    pub num_violated: usize,
+    /// Largest violation of a constraint in the group.
+    pub max_violation: Number,
+    /// Sum of the violations of all constraints in the group.
+    pub total_violation: Number,
+}
+
+impl Display for GroupViolation {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} violated, max {:e}, total {:e}",
+            self.name, self.num_violated, self.max_violation, self.total_violation
+        )
+    }
+}
+
+/// Active constraints of a group.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroupActivity {
+    /// Name of the group.
+    pub name: String,
+    /// Number of strongly active constraints in the group.
+    pub num_strongly_active: usize,
+    /// Number of weakly active constraints in the group.
+    pub num_weakly_active: usize,
+}
+
+impl Display for GroupActivity {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} strongly active, {} weakly active",
+            self.name, self.num_strongly_active, self.num_weakly_active
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ActiveEntry, ActiveSide};
+
+    fn violation(index: usize, violation: Number) -> ConstraintViolation {
+        ConstraintViolation {
+            index,
+            group: None,
+            value: violation,
+            lower: 0.0,
+            upper: 0.0,
+            violation,
+        }
+    }
+
+    fn active(index: usize, activity: Activity) -> ActiveEntry {
+        ActiveEntry {
+            index,
+            side: ActiveSide::Lower,
+            activity,
+            value: 0.0,
+            multiplier: 0.0,
+        }
+    }
+
+    #[test]
+    fn summary_test() {
+        let mut groups = ConstraintGroups::new();
+        groups
+            .add_group("dynamics", 0..3)
+            .add_group("path", vec![3, 4]);
+        assert_eq!(groups.group_of(4), Some("path"));
+        assert_eq!(groups.group_of(5), None);
+
+        let violations = [
+            violation(0, 1.0),
+            violation(2, 0.5),
+            violation(4, 2.0),
+            violation(6, 0.1),
+        ];
+        let summary = groups.summarize_violations(&violations);
+        let names: Vec<_> = summary.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, vec!["path", "dynamics", UNGROUPED]);
+        assert_eq!(summary[1].num_violated, 2);
+        assert_eq!(summary[1].max_violation, 1.0);
+        assert_eq!(summary[1].total_violation, 1.5);
+        assert_eq!(
+            summary[1].to_string(),
+            "dynamics: 2 violated, max 1e0, total 1.5e0"
+        );
+
+        let active_set = ActiveSet {
+            variables: Vec::new(),
+            constraints: vec![
+                active(1, Activity::StronglyActive),
+                active(2, Activity::WeaklyActive),
+                active(3, Activity::StronglyActive),
+            ],
+            groups: Vec::new(),
+        };
+        let activity = groups.summarize_activity(&active_set);
+        assert_eq!(activity.len(), 2);
+        assert_eq!(activity[0].num_strongly_active, 1);
+        assert_eq!(activity[0].num_weakly_active, 1);
+        assert_eq!(activity[1].num_strongly_active, 1);
+
+        assert!(ConstraintGroups::new()
+            .summarize_violations(&violations)
+            .is_empty());
+    }
+}
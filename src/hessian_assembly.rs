@@ -0,0 +1,336 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Assembling the Hessian of the Lagrangian from separate Hessians.
+//!
+//! Ipopt asks for the Hessian of the Lagrangian `σ ∇²f + Σ λ_i ∇²g_i` as a single set of
+//! triplets. Problems implementing [`SeparateHessians`](trait.SeparateHessians.html) give the
+//! objective Hessian and the Hessian of each constraint instead, each with its own sparsity
+//! structure, and a [`HessianAssembly`](struct.HessianAssembly.html) merges the structures and
+//! accumulates the weighted values:
+//!
+//! ```no_run
+//! # use ipopt::*;
+//! # struct Model {
+//! #     assembly: HessianAssembly,
+//! # }
+//! # impl SeparateHessians for Model {
+//! #     fn num_objective_hessian_non_zeros(&self) -> usize { 0 }
+//! #     fn objective_hessian_indices(&self, _: &mut [Index], _: &mut [Index]) -> bool { true }
+//! #     fn objective_hessian_values(&self, _: &[Number], _: &mut [Number]) -> bool { true }
+//! # }
+//! # impl BasicProblem for Model {
+//! #     fn num_variables(&self) -> usize { 1 }
+//! #     fn bounds(&self, _: &mut [Number], _: &mut [Number]) -> bool { true }
+//! #     fn initial_point(&self, _: &mut [Number]) -> bool { true }
+//! #     fn objective(&self, _: &[Number], _: &mut Number) -> bool { true }
+//! #     fn objective_grad(&self, _: &[Number], _: &mut [Number]) -> bool { true }
+//! # }
+//! # impl ConstrainedProblem for Model {
+//! #     fn num_constraints(&self) -> usize { 0 }
+//! #     fn num_constraint_jacobian_non_zeros(&self) -> usize { 0 }
+//! #     fn constraint(&self, _: &[Number], _: &mut [Number]) -> bool { true }
+//! #     fn constraint_bounds(&self, _: &mut [Number], _: &mut [Number]) -> bool { true }
+//! #     fn constraint_jacobian_indices(&self, _: &mut [Index], _: &mut [Index]) -> bool { true }
+//! #     fn constraint_jacobian_values(&self, _: &[Number], _: &mut [Number]) -> bool { true }
+//! fn num_hessian_non_zeros(&self) -> usize {
+//!     self.assembly.num_non_zeros()
+//! }
+//! fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+//!     self.assembly.indices(rows, cols)
+//! }
+//! fn hessian_values(
+//!     &self,
+//!     x: &[Number],
+//!     obj_factor: Number,
+//!     lambda: &[Number],
+//!     vals: &mut [Number],
+//! ) -> bool {
+//!     self.assembly.values(self, x, obj_factor, lambda, vals)
+//! }
+//! # }
+//! ```
+//!
+//! Separate Hessians use zero-based indices, and may give each entry in either triangle. The
+//! assembled indices are zero-based, so problems using the assembly should keep the default
+//! `IndexingStyle::CStyle`.
+
+use crate::sparsity::{SparsityBuilder, SparsityError, Triplets};
+use crate::{Index, IndexingStyle, Number};
+
+use std::cell::RefCell;
+use std::fmt::{Display, Formatter};
+
+/// Objective and constraint Hessians given separately.
+pub trait SeparateHessians {
+    /// Number of non-zeros in the objective Hessian.
+    fn num_objective_hessian_non_zeros(&self) -> usize;
+    /// Row and column indices of the objective Hessian entries.
+    fn objective_hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool;
+    /// Values of the objective Hessian entries.
+    fn objective_hessian_values(&self, x: &[Number], vals: &mut [Number]) -> bool;
+
+    /// Number of non-zeros in the Hessian of the given constraint.
+    ///
+    /// For convenience, constraints are linear by default.
+    fn num_constraint_hessian_non_zeros(&self, _constraint: usize) -> usize {
+        0
+    }
+    /// Row and column indices of the Hessian entries of the given constraint.
+    fn constraint_hessian_indices(
+        &self,
+        _constraint: usize,
+        _rows: &mut [Index],
+        _cols: &mut [Index],
+    ) -> bool {
+        true
+    }
+    /// Values of the Hessian entries of the given constraint.
+    fn constraint_hessian_values(
+        &self,
+        _constraint: usize,
+        _x: &[Number],
+        _vals: &mut [Number],
+    ) -> bool {
+        true
+    }
+}
+
+/// The merged Hessian structure of a problem with separate Hessians.
+#[derive(Debug)]
+pub struct HessianAssembly {
+    triplets: Triplets,
+    /// Slot in the merged structure of each objective Hessian entry.
+    objective_slots: Vec<usize>,
+    /// Slot in the merged structure of each constraint Hessian entry.
+    constraint_slots: Vec<Vec<usize>>,
+    /// Scratch buffer for the values of a single Hessian.
+    scratch: RefCell<Vec<Number>>,
+}
+
+impl HessianAssembly {
+    /// Merge the Hessian structures of a problem with the given numbers of variables and
+    /// constraints.
+    ///
+    /// Fails if the problem does not give the indices of one of the Hessians, or if an index
+    /// is out of bounds.
+    pub fn new<P: SeparateHessians + ?Sized>(
+        problem: &P,
+        num_variables: usize,
+        num_constraints: usize,
+    ) -> Result<Self, HessianAssemblyError> {
+        let mut builder = SparsityBuilder::symmetric(num_variables);
+        let mut sizes = Vec::with_capacity(num_constraints + 1);
+
+        let nnz = problem.num_objective_hessian_non_zeros();
+        let (mut rows, mut cols) = (vec![0; nnz], vec![0; nnz]);
+        if !problem.objective_hessian_indices(&mut rows, &mut cols) {
+            return Err(HessianAssemblyError::IndicesFailed { constraint: None });
+        }
+        add_lower(&mut builder, &rows, &cols);
+        sizes.push(nnz);
+
+        for i in 0..num_constraints {
+            let nnz = problem.num_constraint_hessian_non_zeros(i);
+            rows.resize(nnz, 0);
+            cols.resize(nnz, 0);
+            if !problem.constraint_hessian_indices(i, &mut rows, &mut cols) {
+                return Err(HessianAssemblyError::IndicesFailed {
+                    constraint: Some(i),
+                });
+            }
+            add_lower(&mut builder, &rows, &cols);
+            sizes.push(nnz);
+        }
+
+        let triplets = builder.build().map_err(HessianAssemblyError::Sparsity)?;
+        let mut slots = triplets.entry_slots().iter().cloned();
+        let mut take = |n: usize| slots.by_ref().take(n).collect::<Vec<_>>();
+        let objective_slots = take(sizes[0]);
+        let constraint_slots = sizes[1..].iter().map(|&n| take(n)).collect();
+
+        Ok(HessianAssembly {
+            triplets,
+            objective_slots,
+            constraint_slots,
+            scratch: RefCell::new(Vec::new()),
+        })
+    }
+
+    /// The merged structure.
+    pub fn triplets(&self) -> &Triplets {
+        &self.triplets
+    }
+
+    /// Number of non-zeros of the Hessian of the Lagrangian.
+    pub fn num_non_zeros(&self) -> usize {
+        self.triplets.len()
+    }
+
+    /// Write the zero-based row and column indices of the Hessian of the Lagrangian.
+    pub fn indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        self.triplets
+            .write_indices(IndexingStyle::CStyle, rows, cols)
+    }
+
+    /// Assemble the values of `obj_factor ∇²f + Σ lambda_i ∇²g_i` at `x`.
+    ///
+    /// Hessians with a zero factor are not evaluated.
+    pub fn values<P: SeparateHessians + ?Sized>(
+        &self,
+        problem: &P,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        for v in vals.iter_mut() {
+            *v = 0.0;
+        }
+        let mut scratch = self.scratch.borrow_mut();
+
+        if obj_factor != 0.0 {
+            scratch.resize(self.objective_slots.len(), 0.0);
+            if !problem.objective_hessian_values(x, &mut scratch) {
+                return false;
+            }
+            accumulate(vals, &self.objective_slots, obj_factor, &scratch);
+        }
+
+        for (i, (slots, &l)) in self.constraint_slots.iter().zip(lambda).enumerate() {
+            if l == 0.0 || slots.is_empty() {
+                continue;
+            }
+            scratch.resize(slots.len(), 0.0);
+            if !problem.constraint_hessian_values(i, x, &mut scratch) {
+                return false;
+            }
+            accumulate(vals, slots, l, &scratch);
+        }
+        true
+    }
+}
+
+/// Add entries to a symmetric structure, reoriented into the lower triangle.
+fn add_lower(builder: &mut SparsityBuilder, rows: &[Index], cols: &[Index]) {
+    for (&r, &c) in rows.iter().zip(cols) {
+        let (r, c) = (r as usize, c as usize);
+        if r >= c {
+            builder.add(r, c);
+        } else {
+            builder.add(c, r);
+        }
+    }
+}
+
+fn accumulate(vals: &mut [Number], slots: &[usize], factor: Number, values: &[Number]) {
+    for (&slot, &v) in slots.iter().zip(values) {
+        vals[slot] += factor * v;
+    }
+}
+
+/// Error produced when the Hessian structures of a problem cannot be merged.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum HessianAssemblyError {
+    /// The problem failed to give the indices of the objective Hessian, or of the Hessian of
+    /// the given constraint.
+    IndicesFailed {
+        /// The constraint whose Hessian indices are missing, or `None` for the objective.
+        constraint: Option<usize>,
+    },
+    /// A Hessian has an entry out of bounds.
+    Sparsity(SparsityError),
+}
+
+impl Display for HessianAssemblyError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            HessianAssemblyError::IndicesFailed { constraint: None } => {
+                write!(f, "Failed to get the objective Hessian indices.")
+            }
+            HessianAssemblyError::IndicesFailed {
+                constraint: Some(i),
+            } => write!(f, "Failed to get the Hessian indices of constraint {}.", i),
+            HessianAssemblyError::Sparsity(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for HessianAssemblyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Objective `x0^2 x1`, constraints `x0 + x1` and `x1^2 + x1 x2`.
+    struct Model;
+
+    impl SeparateHessians for Model {
+        fn num_objective_hessian_non_zeros(&self) -> usize {
+            2
+        }
+        fn objective_hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+            rows.copy_from_slice(&[0, 0]);
+            cols.copy_from_slice(&[0, 1]);
+            true
+        }
+        fn objective_hessian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+            vals.copy_from_slice(&[2.0 * x[1], 2.0 * x[0]]);
+            true
+        }
+        // The first constraint is linear.
+        fn num_constraint_hessian_non_zeros(&self, constraint: usize) -> usize {
+            2 * constraint
+        }
+        fn constraint_hessian_indices(
+            &self,
+            constraint: usize,
+            rows: &mut [Index],
+            cols: &mut [Index],
+        ) -> bool {
+            if constraint == 1 {
+                rows.copy_from_slice(&[1, 2]);
+                cols.copy_from_slice(&[1, 1]);
+            }
+            true
+        }
+        fn constraint_hessian_values(
+            &self,
+            _constraint: usize,
+            _x: &[Number],
+            vals: &mut [Number],
+        ) -> bool {
+            vals.copy_from_slice(&[2.0, 1.0]);
+            true
+        }
+    }
+
+    #[test]
+    fn assembly_test() {
+        let assembly = HessianAssembly::new(&Model, 3, 2).unwrap();
+        assert_eq!(assembly.num_non_zeros(), 4);
+        assert_eq!(
+            assembly.triplets().entries(),
+            &[(0, 0), (1, 0), (1, 1), (2, 1)]
+        );
+
+        let mut vals = [0.0; 4];
+        assert!(assembly.values(&Model, &[3.0, 2.0, 1.0], 2.0, &[7.0, 0.5], &mut vals));
+        assert_eq!(vals, [8.0, 12.0, 1.0, 0.5]);
+    }
+}
@@ -0,0 +1,239 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Convergence history of a solve.
+//!
+//! When enabled with
+//! [`Ipopt::set_history_recording`](struct.Ipopt.html#method.set_history_recording), the data
+//! Ipopt reports in every iteration is recorded in a [`History`](struct.History.html). The
+//! history can be written to CSV, or to Parquet with the `parquet` feature, to plot convergence
+//! with pandas or polars without parsing Ipopt's console output.
+
+use crate::{AlgorithmMode, BasicProblem, IntermediateCallbackData, Ipopt};
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Column names of the exported history, in order.
+const COLUMNS: [&str; 11] = [
+    "iter",
+    "mode",
+    "objective",
+    "inf_pr",
+    "inf_du",
+    "mu",
+    "d_norm",
+    "regularization_size",
+    "alpha_du",
+    "alpha_pr",
+    "ls_trials",
+];
+
+/// Name of the algorithm mode in the exported history.
+fn mode_name(mode: AlgorithmMode) -> &'static str {
+    match mode {
+        AlgorithmMode::Regular => "regular",
+        AlgorithmMode::RestorationPhase => "restoration",
+    }
+}
+
+/// The data of all iterations of a solve.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct History {
+    iterations: Vec<IntermediateCallbackData>,
+}
+
+impl History {
+    /// The data of each iteration in order.
+    pub fn iterations(&self) -> &[IntermediateCallbackData] {
+        &self.iterations
+    }
+
+    /// Number of recorded iterations.
+    pub fn len(&self) -> usize {
+        self.iterations.len()
+    }
+
+    /// Returns `true` if no iterations were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.iterations.is_empty()
+    }
+
+    /// Write the history as CSV with a header line to the file at the given path.
+    ///
+    /// The columns are `iter`, `mode` (`regular` or `restoration`), `objective`, `inf_pr`,
+    /// `inf_du`, `mu`, `d_norm`, `regularization_size`, `alpha_du`, `alpha_pr` and `ls_trials`.
+    pub fn write_csv<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.to_csv(&mut out)?;
+        out.flush()
+    }
+
+    /// Write the history as CSV with a header line.
+    pub fn to_csv<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "{}", COLUMNS.join(","))?;
+        for data in self.iterations.iter() {
+            writeln!(
+                out,
+                "{},{},{:e},{:e},{:e},{:e},{:e},{:e},{:e},{:e},{}",
+                data.iter_count,
+                mode_name(data.alg_mod),
+                data.obj_value,
+                data.inf_pr,
+                data.inf_du,
+                data.mu,
+                data.d_norm,
+                data.regularization_size,
+                data.alpha_du,
+                data.alpha_pr,
+                data.ls_trials
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write the history to a Parquet file at the given path.
+    ///
+    /// The columns are the same as in [`write_csv`](struct.History.html#method.write_csv).
+    #[cfg(feature = "parquet")]
+    pub fn write_parquet<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<(), parquet::errors::ParquetError> {
+        use arrow::array::{ArrayRef, Float64Array, Int32Array, StringArray};
+        use arrow::datatypes::{Field, Schema};
+        use arrow::record_batch::RecordBatch;
+        use std::sync::Arc;
+
+        let ints = |f: fn(&IntermediateCallbackData) -> i32| -> ArrayRef {
+            Arc::new(Int32Array::from(
+                self.iterations.iter().map(f).collect::<Vec<_>>(),
+            ))
+        };
+        let floats = |f: fn(&IntermediateCallbackData) -> f64| -> ArrayRef {
+            Arc::new(Float64Array::from(
+                self.iterations.iter().map(f).collect::<Vec<_>>(),
+            ))
+        };
+        let modes: ArrayRef = Arc::new(StringArray::from(
+            self.iterations
+                .iter()
+                .map(|d| mode_name(d.alg_mod))
+                .collect::<Vec<_>>(),
+        ));
+        let columns = vec![
+            ints(|d| d.iter_count),
+            modes,
+            floats(|d| d.obj_value),
+            floats(|d| d.inf_pr),
+            floats(|d| d.inf_du),
+            floats(|d| d.mu),
+            floats(|d| d.d_norm),
+            floats(|d| d.regularization_size),
+            floats(|d| d.alpha_du),
+            floats(|d| d.alpha_pr),
+            ints(|d| d.ls_trials),
+        ];
+        let fields: Vec<_> = COLUMNS
+            .iter()
+            .zip(columns.iter())
+            .map(|(name, column)| Field::new(*name, column.data_type().clone(), false))
+            .collect();
+        let schema = Arc::new(Schema::new(fields));
+        let batch = RecordBatch::try_new(schema.clone(), columns)?;
+
+        let mut writer = parquet::arrow::ArrowWriter::try_new(File::create(path)?, schema, None)?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+}
+
+impl<P: BasicProblem> Ipopt<P> {
+    /// Record the data of every iteration during solves.
+    ///
+    /// The history of the last solve is available through
+    /// [`history`](struct.Ipopt.html#method.history). Recording is disabled by default.
+    pub fn set_history_recording(&mut self, enable: bool) -> &mut Self {
+        self.history = if enable {
+            Some(History::default())
+        } else {
+            None
+        };
+        self.update_intermediate_callback();
+        self
+    }
+
+    /// The convergence history of the last solve, if history recording is enabled.
+    pub fn history(&self) -> Option<&History> {
+        self.history.as_ref()
+    }
+
+    /// Record the data of an iteration, if history recording is enabled.
+    pub(crate) fn record_history(&mut self, data: &IntermediateCallbackData) {
+        if let Some(history) = self.history.as_mut() {
+            history.iterations.push(*data);
+        }
+    }
+
+    /// Clear the history for a solve that is about to start.
+    pub(crate) fn clear_history(&mut self) {
+        if let Some(history) = self.history.as_mut() {
+            history.iterations.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_test() {
+        let data = IntermediateCallbackData {
+            alg_mod: AlgorithmMode::Regular,
+            iter_count: 0,
+            obj_value: 2.5,
+            inf_pr: 1e-3,
+            inf_du: 0.5,
+            mu: 0.1,
+            d_norm: 0.0,
+            regularization_size: 0.0,
+            alpha_du: 0.0,
+            alpha_pr: 0.0,
+            ls_trials: 0,
+        };
+        let history = History {
+            iterations: vec![
+                data,
+                IntermediateCallbackData {
+                    alg_mod: AlgorithmMode::RestorationPhase,
+                    iter_count: 1,
+                    ls_trials: 2,
+                    ..data
+                },
+            ],
+        };
+
+        let mut csv = Vec::new();
+        history.to_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "iter,mode,objective,inf_pr,inf_du,mu,d_norm,regularization_size,alpha_du,alpha_pr,ls_trials\n\
+             0,regular,2.5e0,1e-3,5e-1,1e-1,0e0,0e0,0e0,0e0,0\n\
+             1,restoration,2.5e0,1e-3,5e-1,1e-1,0e0,0e0,0e0,0e0,2\n"
+        );
+    }
+}
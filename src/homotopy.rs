@@ -0,0 +1,264 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Homotopy (continuation) solves.
+//!
+//! Highly nonlinear models are often impossible to solve from a cold start, while a nearby easy
+//! version of the model is not. A [`Homotopy`](struct.Homotopy.html) solves a
+//! [`ParametricProblem`](trait.ParametricProblem.html) for parameters moving along the straight
+//! line from easy start values to the target values, warm starting each solve from the previous
+//! solution. The step along the line is increased after successful solves and decreased after
+//! failures.
+
+use crate::{CreateError, Number, ParametricProblem, ParametricSolver, SolveStatus};
+
+/// Step control of a [`Homotopy`](struct.Homotopy.html).
+///
+/// Steps are measured along the homotopy path parametrized from `0` (start parameters) to `1`
+/// (target parameters).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HomotopyOptions {
+    /// Size of the first step.
+    pub initial_step: Number,
+    /// The homotopy fails when a step smaller than this fails.
+    pub min_step: Number,
+    /// Largest allowed step.
+    pub max_step: Number,
+    /// Factor by which the step grows after a successful solve.
+    pub growth: Number,
+    /// Factor by which the step shrinks after a failed solve.
+    pub shrink: Number,
+}
+
+impl Default for HomotopyOptions {
+    fn default() -> Self {
+        HomotopyOptions {
+            initial_step: 0.1,
+            min_step: 1e-4,
+            max_step: 1.0,
+            growth: 2.0,
+            shrink: 0.5,
+        }
+    }
+}
+
+/// A single solve along the homotopy path.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HomotopyStep {
+    /// Position along the path.
+    pub t: Number,
+    /// Status of the solve at this position.
+    pub status: SolveStatus,
+    /// Whether the solve was accepted.
+    pub accepted: bool,
+}
+
+/// The outcome of a homotopy solve.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HomotopyResult {
+    /// All solves in the order they were attempted.
+    pub steps: Vec<HomotopyStep>,
+    /// Position of the last accepted solve along the path, or `None` if even the start problem
+    /// could not be solved.
+    pub t: Option<Number>,
+    /// Primal variables of the last accepted solve.
+    pub primal_variables: Vec<Number>,
+}
+
+impl HomotopyResult {
+    /// Returns `true` if the problem was solved for the target parameters.
+    pub fn converged(&self) -> bool {
+        self.t == Some(1.0)
+    }
+}
+
+/// Adaptive step control along the path.
+#[derive(Copy, Clone, Debug)]
+struct StepControl {
+    options: HomotopyOptions,
+    t: Number,
+    step: Number,
+}
+
+impl StepControl {
+    fn new(options: HomotopyOptions) -> Self {
+        StepControl {
+            options,
+            t: 0.0,
+            step: options.initial_step.min(options.max_step),
+        }
+    }
+
+    /// The next position to try, or `None` if the end of the path was reached.
+    fn next(&self) -> Option<Number> {
+        if self.t >= 1.0 {
+            None
+        } else {
+            Some((self.t + self.step).min(1.0))
+        }
+    }
+
+    fn accept(&mut self, t: Number) {
+        self.t = t;
+        self.step = (self.step * self.options.growth).min(self.options.max_step);
+    }
+
+    /// Shrink the step, returning `false` if the step became too small.
+    fn reject(&mut self) -> bool {
+        self.step *= self.options.shrink;
+        self.step >= self.options.min_step
+    }
+}
+
+/// Interpolate between `start` and `target` parameters.
+fn interpolate(start: &[Number], target: &[Number], t: Number, p: &mut [Number]) {
+    for ((p, &s), &e) in p.iter_mut().zip(start.iter()).zip(target.iter()) {
+        *p = s + t * (e - s);
+    }
+}
+
+/// Whether a solve along the path is good enough to continue from.
+fn is_accepted(status: SolveStatus) -> bool {
+    matches!(
+        status,
+        SolveStatus::SolveSucceeded | SolveStatus::SolvedToAcceptableLevel
+    )
+}
+
+/// A continuation solver sweeping the parameters of a problem from start to target values.
+pub struct Homotopy<P: ParametricProblem> {
+    solver: ParametricSolver<P>,
+    start: Vec<Number>,
+    target: Vec<Number>,
+    options: HomotopyOptions,
+}
+
+impl<P: ParametricProblem> Homotopy<P> {
+    /// Create a homotopy from the `start` parameters, for which the problem is easy to solve,
+    /// to the `target` parameters.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the number of start or target parameter values is not equal to
+    /// `problem.num_parameters()`.
+    pub fn new(problem: P, start: &[Number], target: &[Number]) -> Result<Self, CreateError> {
+        assert_eq!(start.len(), target.len());
+        Ok(Homotopy {
+            solver: ParametricSolver::new(problem, start)?,
+            start: start.to_vec(),
+            target: target.to_vec(),
+            options: HomotopyOptions::default(),
+        })
+    }
+
+    /// The underlying parametric solver, for instance to set Ipopt options.
+    pub fn solver(&mut self) -> &mut ParametricSolver<P> {
+        &mut self.solver
+    }
+
+    /// Set the step control options.
+    pub fn set_options(&mut self, options: HomotopyOptions) -> &mut Self {
+        self.options = options;
+        self
+    }
+
+    /// Run the homotopy.
+    ///
+    /// The start problem is solved from the initial point of the problem, and every subsequent
+    /// solve is warm started from the last accepted solution.
+    pub fn solve(&mut self) -> HomotopyResult {
+        let mut steps = Vec::new();
+        let mut params = self.start.clone();
+
+        self.solver.set_params(&params);
+        let status = self.solver.solve().status;
+        let accepted = is_accepted(status);
+        steps.push(HomotopyStep {
+            t: 0.0,
+            status,
+            accepted,
+        });
+        if !accepted {
+            return HomotopyResult {
+                steps,
+                t: None,
+                primal_variables: Vec::new(),
+            };
+        }
+
+        let mut control = StepControl::new(self.options);
+        let mut last_accepted = self.solver.warm_start().cloned();
+        while let Some(t) = control.next() {
+            interpolate(&self.start, &self.target, t, &mut params);
+            self.solver.set_params(&params);
+            let status = self.solver.resolve().status;
+            let accepted = is_accepted(status);
+            steps.push(HomotopyStep {
+                t,
+                status,
+                accepted,
+            });
+            if accepted {
+                control.accept(t);
+                last_accepted = self.solver.warm_start().cloned();
+            } else {
+                // Retry a shorter step from the last accepted solution.
+                self.solver.set_warm_start(last_accepted.clone());
+                if !control.reject() {
+                    break;
+                }
+            }
+        }
+
+        HomotopyResult {
+            steps,
+            t: Some(control.t),
+            primal_variables: last_accepted.map(|w| w.x).unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn step_control_test() {
+        let mut control = StepControl::new(HomotopyOptions {
+            initial_step: 0.25,
+            min_step: 0.05,
+            max_step: 0.5,
+            growth: 2.0,
+            shrink: 0.5,
+        });
+        assert_eq!(control.next(), Some(0.25));
+        control.accept(0.25);
+        assert_eq!(control.next(), Some(0.75));
+        assert!(control.reject());
+        assert_eq!(control.next(), Some(0.5));
+        control.accept(0.5);
+        control.accept(control.next().unwrap());
+        assert_eq!(control.t, 1.0);
+        assert_eq!(control.next(), None);
+
+        assert!(control.reject());
+        assert!(control.reject());
+        assert!(control.reject());
+        assert!(!control.reject());
+
+        let mut p = [0.0; 2];
+        interpolate(&[0.0, 1.0], &[2.0, -1.0], 0.25, &mut p);
+        assert_eq!(p, [0.5, 0.5]);
+    }
+}
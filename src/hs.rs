@@ -0,0 +1,2900 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! A subset of the Hock-Schittkowski test problem collection.
+//!
+//! These problems are taken from W. Hock and K. Schittkowski, "Test Examples for Nonlinear
+//! Programming Codes", Lecture Notes in Economics and Mathematical Systems, vol. 187, Springer,
+//! 1981. Each problem is provided as a ready-made type implementing
+//! [`ConstrainedProblem`](../trait.ConstrainedProblem.html) with exact second derivatives, and
+//! [`TestProblem`](trait.TestProblem.html), which gives access to the known optimum.
+//!
+//! All problems can be iterated over via [`HsProblem::all`](enum.HsProblem.html#method.all):
+//!
+//! ```no_run
+//! use ipopt::{hs::*, Ipopt};
+//!
+//! for problem in HsProblem::all() {
+//!     let optimum = problem.optimal_objective();
+//!     let mut ipopt = Ipopt::new(problem).unwrap();
+//!     ipopt.set_option("print_level", 0);
+//!     let result = ipopt.solve();
+//!     assert!((result.objective_value - optimum).abs() < 1e-6 * optimum.abs().max(1.0));
+//! }
+//! ```
+
+use crate::{BasicProblem, ConstrainedProblem, Index, Number};
+
+use std::f64::consts::FRAC_PI_3;
+
+/// A test problem with a known solution.
+pub trait TestProblem: ConstrainedProblem {
+    /// Name of the problem in the original collection, e.g. "HS071".
+    fn name(&self) -> &'static str;
+    /// Objective value at the known optimum.
+    fn optimal_objective(&self) -> Number;
+    /// The known minimizer.
+    fn optimal_solution(&self) -> Vec<Number>;
+}
+
+/// Fill the sparsity pattern of the lower triangular part of a dense symmetric matrix.
+fn dense_lower_triangular_indices(rows: &mut [Index], cols: &mut [Index]) {
+    let mut idx = 0;
+    let mut row = 0;
+    while idx < rows.len() {
+        for col in 0..=row {
+            rows[idx] = row;
+            cols[idx] = col;
+            idx += 1;
+        }
+        row += 1;
+    }
+}
+
+/// The Rosenbrock function with a bound on the second variable.
+///
+/// ```verbatim
+///     min  100 (x2 - x1^2)^2 + (1 - x1)^2
+///     s.t. -1.5 <= x2
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs001;
+
+impl BasicProblem for Hs001 {
+    fn num_variables(&self) -> usize {
+        2
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[-2e19, -1.5]);
+        x_u.copy_from_slice(&[2e19, 2e19]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[-2.0, 1.0]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = 100.0 * (x[1] - x[0] * x[0]).powi(2) + (1.0 - x[0]).powi(2);
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = -400.0 * x[0] * (x[1] - x[0] * x[0]) - 2.0 * (1.0 - x[0]);
+        grad_f[1] = 200.0 * (x[1] - x[0] * x[0]);
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs001 {
+    fn num_constraints(&self) -> usize {
+        0
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        0
+    }
+    fn constraint(&self, _: &[Number], _: &mut [Number]) -> bool {
+        true
+    }
+    fn constraint_bounds(&self, _: &mut [Number], _: &mut [Number]) -> bool {
+        true
+    }
+    fn constraint_jacobian_indices(&self, _: &mut [Index], _: &mut [Index]) -> bool {
+        true
+    }
+    fn constraint_jacobian_values(&self, _: &[Number], _: &mut [Number]) -> bool {
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        3
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        dense_lower_triangular_indices(rows, cols);
+        true
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        _: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = obj_factor * (1200.0 * x[0] * x[0] - 400.0 * x[1] + 2.0); /* 0,0 */
+        vals[1] = obj_factor * (-400.0 * x[0]); /* 1,0 */
+        vals[2] = obj_factor * 200.0; /* 1,1 */
+        true
+    }
+}
+
+impl TestProblem for Hs001 {
+    fn name(&self) -> &'static str {
+        "HS001"
+    }
+    fn optimal_objective(&self) -> Number {
+        0.0
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![1.0, 1.0]
+    }
+}
+
+/// The Rosenbrock function with a bound on the second variable that is active at the optimum.
+///
+/// ```verbatim
+///     min  100 (x2 - x1^2)^2 + (1 - x1)^2
+///     s.t. 1.5 <= x2
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs002;
+
+impl BasicProblem for Hs002 {
+    fn num_variables(&self) -> usize {
+        2
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[-2e19, 1.5]);
+        x_u.copy_from_slice(&[2e19, 2e19]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[-2.0, 1.0]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        Hs001.objective(x, obj)
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        Hs001.objective_grad(x, grad_f)
+    }
+}
+
+impl ConstrainedProblem for Hs002 {
+    fn num_constraints(&self) -> usize {
+        0
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        0
+    }
+    fn constraint(&self, _: &[Number], _: &mut [Number]) -> bool {
+        true
+    }
+    fn constraint_bounds(&self, _: &mut [Number], _: &mut [Number]) -> bool {
+        true
+    }
+    fn constraint_jacobian_indices(&self, _: &mut [Index], _: &mut [Index]) -> bool {
+        true
+    }
+    fn constraint_jacobian_values(&self, _: &[Number], _: &mut [Number]) -> bool {
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        Hs001.num_hessian_non_zeros()
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        Hs001.hessian_indices(rows, cols)
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        Hs001.hessian_values(x, obj_factor, lambda, vals)
+    }
+}
+
+impl TestProblem for Hs002 {
+    fn name(&self) -> &'static str {
+        "HS002"
+    }
+    fn optimal_objective(&self) -> Number {
+        0.0504261879
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![1.2243707487, 1.5]
+    }
+}
+
+/// A nearly linear objective with a bound on the second variable.
+///
+/// ```verbatim
+///     min  x2 + 1e-5 (x2 - x1)^2
+///     s.t. 0 <= x2
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs003;
+
+impl BasicProblem for Hs003 {
+    fn num_variables(&self) -> usize {
+        2
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[-2e19, 0.0]);
+        x_u.copy_from_slice(&[2e19, 2e19]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[10.0, 1.0]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = x[1] + 1e-5 * (x[1] - x[0]).powi(2);
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = -2e-5 * (x[1] - x[0]);
+        grad_f[1] = 1.0 + 2e-5 * (x[1] - x[0]);
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs003 {
+    fn num_constraints(&self) -> usize {
+        0
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        0
+    }
+    fn constraint(&self, _: &[Number], _: &mut [Number]) -> bool {
+        true
+    }
+    fn constraint_bounds(&self, _: &mut [Number], _: &mut [Number]) -> bool {
+        true
+    }
+    fn constraint_jacobian_indices(&self, _: &mut [Index], _: &mut [Index]) -> bool {
+        true
+    }
+    fn constraint_jacobian_values(&self, _: &[Number], _: &mut [Number]) -> bool {
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        3
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        dense_lower_triangular_indices(rows, cols);
+        true
+    }
+    fn hessian_values(
+        &self,
+        _: &[Number],
+        obj_factor: Number,
+        _: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = obj_factor * 2e-5; /* 0,0 */
+        vals[1] = obj_factor * -2e-5; /* 1,0 */
+        vals[2] = obj_factor * 2e-5; /* 1,1 */
+        true
+    }
+}
+
+impl TestProblem for Hs003 {
+    fn name(&self) -> &'static str {
+        "HS003"
+    }
+    fn optimal_objective(&self) -> Number {
+        0.0
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![0.0, 0.0]
+    }
+}
+
+/// A cubic objective with lower bounds on both variables.
+///
+/// ```verbatim
+///     min  (x1 + 1)^3 / 3 + x2
+///     s.t. 1 <= x1
+///          0 <= x2
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs004;
+
+impl BasicProblem for Hs004 {
+    fn num_variables(&self) -> usize {
+        2
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[1.0, 0.0]);
+        x_u.copy_from_slice(&[2e19, 2e19]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[1.125, 0.125]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = (x[0] + 1.0).powi(3) / 3.0 + x[1];
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = (x[0] + 1.0).powi(2);
+        grad_f[1] = 1.0;
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs004 {
+    fn num_constraints(&self) -> usize {
+        0
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        0
+    }
+    fn constraint(&self, _: &[Number], _: &mut [Number]) -> bool {
+        true
+    }
+    fn constraint_bounds(&self, _: &mut [Number], _: &mut [Number]) -> bool {
+        true
+    }
+    fn constraint_jacobian_indices(&self, _: &mut [Index], _: &mut [Index]) -> bool {
+        true
+    }
+    fn constraint_jacobian_values(&self, _: &[Number], _: &mut [Number]) -> bool {
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        1
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows[0] = 0;
+        cols[0] = 0;
+        true
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        _: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = obj_factor * 2.0 * (x[0] + 1.0); /* 0,0 */
+        true
+    }
+}
+
+impl TestProblem for Hs004 {
+    fn name(&self) -> &'static str {
+        "HS004"
+    }
+    fn optimal_objective(&self) -> Number {
+        8.0 / 3.0
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![1.0, 0.0]
+    }
+}
+
+/// A trigonometric objective on a box.
+///
+/// ```verbatim
+///     min  sin(x1 + x2) + (x1 - x2)^2 - 1.5 x1 + 2.5 x2 + 1
+///     s.t. -1.5 <= x1 <= 4
+///          -3 <= x2 <= 3
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs005;
+
+impl BasicProblem for Hs005 {
+    fn num_variables(&self) -> usize {
+        2
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[-1.5, -3.0]);
+        x_u.copy_from_slice(&[4.0, 3.0]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[0.0, 0.0]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = (x[0] + x[1]).sin() + (x[0] - x[1]).powi(2) - 1.5 * x[0] + 2.5 * x[1] + 1.0;
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        let c = (x[0] + x[1]).cos();
+        grad_f[0] = c + 2.0 * (x[0] - x[1]) - 1.5;
+        grad_f[1] = c - 2.0 * (x[0] - x[1]) + 2.5;
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs005 {
+    fn num_constraints(&self) -> usize {
+        0
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        0
+    }
+    fn constraint(&self, _: &[Number], _: &mut [Number]) -> bool {
+        true
+    }
+    fn constraint_bounds(&self, _: &mut [Number], _: &mut [Number]) -> bool {
+        true
+    }
+    fn constraint_jacobian_indices(&self, _: &mut [Index], _: &mut [Index]) -> bool {
+        true
+    }
+    fn constraint_jacobian_values(&self, _: &[Number], _: &mut [Number]) -> bool {
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        3
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        dense_lower_triangular_indices(rows, cols);
+        true
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        _: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        let s = (x[0] + x[1]).sin();
+        vals[0] = obj_factor * (2.0 - s); /* 0,0 */
+        vals[1] = obj_factor * (-2.0 - s); /* 1,0 */
+        vals[2] = obj_factor * (2.0 - s); /* 1,1 */
+        true
+    }
+}
+
+impl TestProblem for Hs005 {
+    fn name(&self) -> &'static str {
+        "HS005"
+    }
+    fn optimal_objective(&self) -> Number {
+        -(3.0 as Number).sqrt() / 2.0 - FRAC_PI_3
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![0.5 - FRAC_PI_3, -0.5 - FRAC_PI_3]
+    }
+}
+
+/// A least squares objective with a single nonlinear equality constraint.
+///
+/// ```verbatim
+///     min  (1 - x1)^2
+///     s.t. 10 (x2 - x1^2) = 0
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs006;
+
+impl BasicProblem for Hs006 {
+    fn num_variables(&self) -> usize {
+        2
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[-2e19; 2]);
+        x_u.copy_from_slice(&[2e19; 2]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[-1.2, 1.0]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = (1.0 - x[0]).powi(2);
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = -2.0 * (1.0 - x[0]);
+        grad_f[1] = 0.0;
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs006 {
+    fn num_constraints(&self) -> usize {
+        1
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        2
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = 10.0 * (x[1] - x[0] * x[0]);
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l[0] = 0.0;
+        g_u[0] = 0.0;
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0]);
+        cols.copy_from_slice(&[0, 1]);
+        true
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        vals[0] = -20.0 * x[0]; /* 0,0 */
+        vals[1] = 10.0; /* 0,1 */
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        1
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows[0] = 0;
+        cols[0] = 0;
+        true
+    }
+    fn hessian_values(
+        &self,
+        _: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = obj_factor * 2.0 - lambda[0] * 20.0; /* 0,0 */
+        true
+    }
+}
+
+impl TestProblem for Hs006 {
+    fn name(&self) -> &'static str {
+        "HS006"
+    }
+    fn optimal_objective(&self) -> Number {
+        0.0
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![1.0, 1.0]
+    }
+}
+
+/// A logarithmic objective with a single nonlinear equality constraint.
+///
+/// ```verbatim
+///     min  ln(1 + x1^2) - x2
+///     s.t. (1 + x1^2)^2 + x2^2 - 4 = 0
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs007;
+
+impl BasicProblem for Hs007 {
+    fn num_variables(&self) -> usize {
+        2
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[-2e19; 2]);
+        x_u.copy_from_slice(&[2e19; 2]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[2.0, 2.0]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = (1.0 + x[0] * x[0]).ln() - x[1];
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = 2.0 * x[0] / (1.0 + x[0] * x[0]);
+        grad_f[1] = -1.0;
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs007 {
+    fn num_constraints(&self) -> usize {
+        1
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        2
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = (1.0 + x[0] * x[0]).powi(2) + x[1] * x[1] - 4.0;
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l[0] = 0.0;
+        g_u[0] = 0.0;
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0]);
+        cols.copy_from_slice(&[0, 1]);
+        true
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        vals[0] = 4.0 * x[0] * (1.0 + x[0] * x[0]); /* 0,0 */
+        vals[1] = 2.0 * x[1]; /* 0,1 */
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        3
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        dense_lower_triangular_indices(rows, cols);
+        true
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        let s = 1.0 + x[0] * x[0];
+        vals[0] = obj_factor * 2.0 * (1.0 - x[0] * x[0]) / (s * s)
+            + lambda[0] * (4.0 + 12.0 * x[0] * x[0]); /* 0,0 */
+        vals[1] = 0.0; /* 1,0 */
+        vals[2] = lambda[0] * 2.0; /* 1,1 */
+        true
+    }
+}
+
+impl TestProblem for Hs007 {
+    fn name(&self) -> &'static str {
+        "HS007"
+    }
+    fn optimal_objective(&self) -> Number {
+        -(3.0 as Number).sqrt()
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![0.0, (3.0 as Number).sqrt()]
+    }
+}
+
+/// A linear objective with a single nonlinear inequality constraint.
+///
+/// ```verbatim
+///     min  x1 - x2
+///     s.t. -3 x1^2 + 2 x1 x2 - x2^2 + 1 >= 0
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs010;
+
+impl BasicProblem for Hs010 {
+    fn num_variables(&self) -> usize {
+        2
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[-2e19; 2]);
+        x_u.copy_from_slice(&[2e19; 2]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[-10.0, 10.0]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = x[0] - x[1];
+        true
+    }
+    fn objective_grad(&self, _: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f.copy_from_slice(&[1.0, -1.0]);
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs010 {
+    fn num_constraints(&self) -> usize {
+        1
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        2
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = -3.0 * x[0] * x[0] + 2.0 * x[0] * x[1] - x[1] * x[1] + 1.0;
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l[0] = 0.0;
+        g_u[0] = 2e19;
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0]);
+        cols.copy_from_slice(&[0, 1]);
+        true
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        vals[0] = -6.0 * x[0] + 2.0 * x[1]; /* 0,0 */
+        vals[1] = 2.0 * x[0] - 2.0 * x[1]; /* 0,1 */
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        3
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        dense_lower_triangular_indices(rows, cols);
+        true
+    }
+    fn hessian_values(
+        &self,
+        _: &[Number],
+        _: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = lambda[0] * -6.0; /* 0,0 */
+        vals[1] = lambda[0] * 2.0; /* 1,0 */
+        vals[2] = lambda[0] * -2.0; /* 1,1 */
+        true
+    }
+}
+
+impl TestProblem for Hs010 {
+    fn name(&self) -> &'static str {
+        "HS010"
+    }
+    fn optimal_objective(&self) -> Number {
+        -1.0
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![0.0, 1.0]
+    }
+}
+
+/// A quadratic objective with a single nonlinear inequality constraint.
+///
+/// ```verbatim
+///     min  (x1 - 5)^2 + x2^2 - 25
+///     s.t. -x1^2 + x2 >= 0
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs011;
+
+impl BasicProblem for Hs011 {
+    fn num_variables(&self) -> usize {
+        2
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[-2e19; 2]);
+        x_u.copy_from_slice(&[2e19; 2]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[4.9, 0.1]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = (x[0] - 5.0).powi(2) + x[1] * x[1] - 25.0;
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = 2.0 * (x[0] - 5.0);
+        grad_f[1] = 2.0 * x[1];
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs011 {
+    fn num_constraints(&self) -> usize {
+        1
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        2
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = -x[0] * x[0] + x[1];
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l[0] = 0.0;
+        g_u[0] = 2e19;
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0]);
+        cols.copy_from_slice(&[0, 1]);
+        true
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        vals[0] = -2.0 * x[0]; /* 0,0 */
+        vals[1] = 1.0; /* 0,1 */
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        2
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 1]);
+        cols.copy_from_slice(&[0, 1]);
+        true
+    }
+    fn hessian_values(
+        &self,
+        _: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = obj_factor * 2.0 - lambda[0] * 2.0; /* 0,0 */
+        vals[1] = obj_factor * 2.0; /* 1,1 */
+        true
+    }
+}
+
+impl TestProblem for Hs011 {
+    fn name(&self) -> &'static str {
+        "HS011"
+    }
+    fn optimal_objective(&self) -> Number {
+        -8.4984642231
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![1.2347728251, 1.5246639295]
+    }
+}
+
+/// A convex quadratic objective on an ellipse.
+///
+/// ```verbatim
+///     min  0.5 x1^2 + x2^2 - x1 x2 - 7 x1 - 7 x2
+///     s.t. 25 - 4 x1^2 - x2^2 >= 0
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs012;
+
+impl BasicProblem for Hs012 {
+    fn num_variables(&self) -> usize {
+        2
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[-2e19; 2]);
+        x_u.copy_from_slice(&[2e19; 2]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[0.0, 0.0]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = 0.5 * x[0] * x[0] + x[1] * x[1] - x[0] * x[1] - 7.0 * x[0] - 7.0 * x[1];
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = x[0] - x[1] - 7.0;
+        grad_f[1] = 2.0 * x[1] - x[0] - 7.0;
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs012 {
+    fn num_constraints(&self) -> usize {
+        1
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        2
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = 25.0 - 4.0 * x[0] * x[0] - x[1] * x[1];
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l[0] = 0.0;
+        g_u[0] = 2e19;
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0]);
+        cols.copy_from_slice(&[0, 1]);
+        true
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        vals[0] = -8.0 * x[0]; /* 0,0 */
+        vals[1] = -2.0 * x[1]; /* 0,1 */
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        3
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        dense_lower_triangular_indices(rows, cols);
+        true
+    }
+    fn hessian_values(
+        &self,
+        _: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = obj_factor - lambda[0] * 8.0; /* 0,0 */
+        vals[1] = -obj_factor; /* 1,0 */
+        vals[2] = obj_factor * 2.0 - lambda[0] * 2.0; /* 1,1 */
+        true
+    }
+}
+
+impl TestProblem for Hs012 {
+    fn name(&self) -> &'static str {
+        "HS012"
+    }
+    fn optimal_objective(&self) -> Number {
+        -30.0
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![2.0, 3.0]
+    }
+}
+
+/// A problem whose optimum does not satisfy any constraint qualification.
+///
+/// ```verbatim
+///     min  (x1 - 2)^2 + x2^2
+///     s.t. (1 - x1)^3 - x2 >= 0
+///          0 <= x1, x2
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs013;
+
+impl BasicProblem for Hs013 {
+    fn num_variables(&self) -> usize {
+        2
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[0.0; 2]);
+        x_u.copy_from_slice(&[2e19; 2]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[-2.0, -2.0]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = (x[0] - 2.0).powi(2) + x[1] * x[1];
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = 2.0 * (x[0] - 2.0);
+        grad_f[1] = 2.0 * x[1];
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs013 {
+    fn num_constraints(&self) -> usize {
+        1
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        2
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = (1.0 - x[0]).powi(3) - x[1];
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l[0] = 0.0;
+        g_u[0] = 2e19;
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0]);
+        cols.copy_from_slice(&[0, 1]);
+        true
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        vals[0] = -3.0 * (1.0 - x[0]).powi(2); /* 0,0 */
+        vals[1] = -1.0; /* 0,1 */
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        2
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 1]);
+        cols.copy_from_slice(&[0, 1]);
+        true
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = obj_factor * 2.0 + lambda[0] * 6.0 * (1.0 - x[0]); /* 0,0 */
+        vals[1] = obj_factor * 2.0; /* 1,1 */
+        true
+    }
+}
+
+impl TestProblem for Hs013 {
+    fn name(&self) -> &'static str {
+        "HS013"
+    }
+    fn optimal_objective(&self) -> Number {
+        1.0
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![1.0, 0.0]
+    }
+}
+
+/// A quadratic objective with a linear equality and a nonlinear inequality constraint.
+///
+/// ```verbatim
+///     min  (x1 - 2)^2 + (x2 - 1)^2
+///     s.t. x1 - 2 x2 + 1 = 0
+///          -x1^2 / 4 - x2^2 + 1 >= 0
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs014;
+
+impl BasicProblem for Hs014 {
+    fn num_variables(&self) -> usize {
+        2
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[-2e19; 2]);
+        x_u.copy_from_slice(&[2e19; 2]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[2.0, 2.0]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = (x[0] - 2.0).powi(2) + (x[1] - 1.0).powi(2);
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = 2.0 * (x[0] - 2.0);
+        grad_f[1] = 2.0 * (x[1] - 1.0);
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs014 {
+    fn num_constraints(&self) -> usize {
+        2
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        4
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = x[0] - 2.0 * x[1] + 1.0;
+        g[1] = -0.25 * x[0] * x[0] - x[1] * x[1] + 1.0;
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l.copy_from_slice(&[0.0, 0.0]);
+        g_u.copy_from_slice(&[0.0, 2e19]);
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0, 1, 1]);
+        cols.copy_from_slice(&[0, 1, 0, 1]);
+        true
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        vals[0] = 1.0; /* 0,0 */
+        vals[1] = -2.0; /* 0,1 */
+        vals[2] = -0.5 * x[0]; /* 1,0 */
+        vals[3] = -2.0 * x[1]; /* 1,1 */
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        2
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 1]);
+        cols.copy_from_slice(&[0, 1]);
+        true
+    }
+    fn hessian_values(
+        &self,
+        _: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = obj_factor * 2.0 - lambda[1] * 0.5; /* 0,0 */
+        vals[1] = obj_factor * 2.0 - lambda[1] * 2.0; /* 1,1 */
+        true
+    }
+}
+
+impl TestProblem for Hs014 {
+    fn name(&self) -> &'static str {
+        "HS014"
+    }
+    fn optimal_objective(&self) -> Number {
+        9.0 - 23.0 * (7.0 as Number).sqrt() / 8.0
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        let s = (7.0 as Number).sqrt();
+        vec![0.5 * (s - 1.0), 0.25 * (s + 1.0)]
+    }
+}
+
+/// The Rosenbrock function with two nonlinear inequality constraints.
+///
+/// ```verbatim
+///     min  100 (x2 - x1^2)^2 + (1 - x1)^2
+///     s.t. x1 x2 - 1 >= 0
+///          x1 + x2^2 >= 0
+///          x1 <= 0.5
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs015;
+
+impl BasicProblem for Hs015 {
+    fn num_variables(&self) -> usize {
+        2
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[-2e19, -2e19]);
+        x_u.copy_from_slice(&[0.5, 2e19]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[-2.0, 1.0]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        Hs001.objective(x, obj)
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        Hs001.objective_grad(x, grad_f)
+    }
+}
+
+impl ConstrainedProblem for Hs015 {
+    fn num_constraints(&self) -> usize {
+        2
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        4
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = x[0] * x[1] - 1.0;
+        g[1] = x[0] + x[1] * x[1];
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l.copy_from_slice(&[0.0, 0.0]);
+        g_u.copy_from_slice(&[2e19, 2e19]);
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0, 1, 1]);
+        cols.copy_from_slice(&[0, 1, 0, 1]);
+        true
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        vals[0] = x[1]; /* 0,0 */
+        vals[1] = x[0]; /* 0,1 */
+        vals[2] = 1.0; /* 1,0 */
+        vals[3] = 2.0 * x[1]; /* 1,1 */
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        3
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        dense_lower_triangular_indices(rows, cols);
+        true
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = obj_factor * (1200.0 * x[0] * x[0] - 400.0 * x[1] + 2.0); /* 0,0 */
+        vals[1] = obj_factor * (-400.0 * x[0]) + lambda[0]; /* 1,0 */
+        vals[2] = obj_factor * 200.0 + lambda[1] * 2.0; /* 1,1 */
+        true
+    }
+}
+
+impl TestProblem for Hs015 {
+    fn name(&self) -> &'static str {
+        "HS015"
+    }
+    fn optimal_objective(&self) -> Number {
+        306.5
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![0.5, 2.0]
+    }
+}
+
+/// A convex quadratic objective with a linear inequality constraint and bounds.
+///
+/// ```verbatim
+///     min  0.01 x1^2 + x2^2 - 100
+///     s.t. 10 x1 - x2 >= 10
+///          2 <= x1 <= 50
+///          -50 <= x2 <= 50
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs021;
+
+impl BasicProblem for Hs021 {
+    fn num_variables(&self) -> usize {
+        2
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[2.0, -50.0]);
+        x_u.copy_from_slice(&[50.0, 50.0]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[-1.0, -1.0]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = 0.01 * x[0] * x[0] + x[1] * x[1] - 100.0;
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = 0.02 * x[0];
+        grad_f[1] = 2.0 * x[1];
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs021 {
+    fn num_constraints(&self) -> usize {
+        1
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        2
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = 10.0 * x[0] - x[1];
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l[0] = 10.0;
+        g_u[0] = 2e19;
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0]);
+        cols.copy_from_slice(&[0, 1]);
+        true
+    }
+    fn constraint_jacobian_values(&self, _: &[Number], vals: &mut [Number]) -> bool {
+        vals.copy_from_slice(&[10.0, -1.0]);
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        2
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 1]);
+        cols.copy_from_slice(&[0, 1]);
+        true
+    }
+    fn hessian_values(
+        &self,
+        _: &[Number],
+        obj_factor: Number,
+        _: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = obj_factor * 0.02; /* 0,0 */
+        vals[1] = obj_factor * 2.0; /* 1,1 */
+        true
+    }
+}
+
+impl TestProblem for Hs021 {
+    fn name(&self) -> &'static str {
+        "HS021"
+    }
+    fn optimal_objective(&self) -> Number {
+        -99.96
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![2.0, 0.0]
+    }
+}
+
+/// A convex quadratic objective with a single linear equality constraint.
+///
+/// ```verbatim
+///     min  (x1 + x2)^2 + (x2 + x3)^2
+///     s.t. x1 + 2 x2 + 3 x3 = 1
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs028;
+
+impl BasicProblem for Hs028 {
+    fn num_variables(&self) -> usize {
+        3
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[-2e19; 3]);
+        x_u.copy_from_slice(&[2e19; 3]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[-4.0, 1.0, 1.0]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = (x[0] + x[1]).powi(2) + (x[1] + x[2]).powi(2);
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = 2.0 * (x[0] + x[1]);
+        grad_f[1] = 2.0 * (x[0] + x[1]) + 2.0 * (x[1] + x[2]);
+        grad_f[2] = 2.0 * (x[1] + x[2]);
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs028 {
+    fn num_constraints(&self) -> usize {
+        1
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        3
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = x[0] + 2.0 * x[1] + 3.0 * x[2];
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l[0] = 1.0;
+        g_u[0] = 1.0;
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0, 0]);
+        cols.copy_from_slice(&[0, 1, 2]);
+        true
+    }
+    fn constraint_jacobian_values(&self, _: &[Number], vals: &mut [Number]) -> bool {
+        vals.copy_from_slice(&[1.0, 2.0, 3.0]);
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        6
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        dense_lower_triangular_indices(rows, cols);
+        true
+    }
+    fn hessian_values(
+        &self,
+        _: &[Number],
+        obj_factor: Number,
+        _: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = obj_factor * 2.0; /* 0,0 */
+        vals[1] = obj_factor * 2.0; /* 1,0 */
+        vals[2] = obj_factor * 4.0; /* 1,1 */
+        vals[3] = 0.0; /* 2,0 */
+        vals[4] = obj_factor * 2.0; /* 2,1 */
+        vals[5] = obj_factor * 2.0; /* 2,2 */
+        true
+    }
+}
+
+impl TestProblem for Hs028 {
+    fn name(&self) -> &'static str {
+        "HS028"
+    }
+    fn optimal_objective(&self) -> Number {
+        0.0
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![0.5, -0.5, 0.5]
+    }
+}
+
+/// A convex quadratic program with a single linear inequality constraint.
+///
+/// ```verbatim
+///     min  9 - 8 x1 - 6 x2 - 4 x3 + 2 x1^2 + 2 x2^2 + x3^2 + 2 x1 x2 + 2 x1 x3
+///     s.t. x1 + x2 + 2 x3 <= 3
+///          0 <= x1, x2, x3
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs035;
+
+impl BasicProblem for Hs035 {
+    fn num_variables(&self) -> usize {
+        3
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[0.0; 3]);
+        x_u.copy_from_slice(&[2e19; 3]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[0.5; 3]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = 9.0 - 8.0 * x[0] - 6.0 * x[1] - 4.0 * x[2]
+            + 2.0 * x[0] * x[0]
+            + 2.0 * x[1] * x[1]
+            + x[2] * x[2]
+            + 2.0 * x[0] * x[1]
+            + 2.0 * x[0] * x[2];
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = -8.0 + 4.0 * x[0] + 2.0 * x[1] + 2.0 * x[2];
+        grad_f[1] = -6.0 + 4.0 * x[1] + 2.0 * x[0];
+        grad_f[2] = -4.0 + 2.0 * x[2] + 2.0 * x[0];
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs035 {
+    fn num_constraints(&self) -> usize {
+        1
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        3
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = x[0] + x[1] + 2.0 * x[2];
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l[0] = -2e19;
+        g_u[0] = 3.0;
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0, 0]);
+        cols.copy_from_slice(&[0, 1, 2]);
+        true
+    }
+    fn constraint_jacobian_values(&self, _: &[Number], vals: &mut [Number]) -> bool {
+        vals.copy_from_slice(&[1.0, 1.0, 2.0]);
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        6
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        dense_lower_triangular_indices(rows, cols);
+        true
+    }
+    fn hessian_values(
+        &self,
+        _: &[Number],
+        obj_factor: Number,
+        _: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = obj_factor * 4.0; /* 0,0 */
+        vals[1] = obj_factor * 2.0; /* 1,0 */
+        vals[2] = obj_factor * 4.0; /* 1,1 */
+        vals[3] = obj_factor * 2.0; /* 2,0 */
+        vals[4] = 0.0; /* 2,1 */
+        vals[5] = obj_factor * 2.0; /* 2,2 */
+        true
+    }
+}
+
+impl TestProblem for Hs035 {
+    fn name(&self) -> &'static str {
+        "HS035"
+    }
+    fn optimal_objective(&self) -> Number {
+        1.0 / 9.0
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![4.0 / 3.0, 7.0 / 9.0, 4.0 / 9.0]
+    }
+}
+
+/// The Colville function on a box.
+///
+/// ```verbatim
+///     min  100 (x2 - x1^2)^2 + (1 - x1)^2 + 90 (x4 - x3^2)^2 + (1 - x3)^2
+///          + 10.1 ((x2 - 1)^2 + (x4 - 1)^2) + 19.8 (x2 - 1) (x4 - 1)
+///     s.t. -10 <= x1, x2, x3, x4 <= 10
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs038;
+
+impl BasicProblem for Hs038 {
+    fn num_variables(&self) -> usize {
+        4
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[-10.0; 4]);
+        x_u.copy_from_slice(&[10.0; 4]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[-3.0, -1.0, -3.0, -1.0]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = 100.0 * (x[1] - x[0] * x[0]).powi(2)
+            + (1.0 - x[0]).powi(2)
+            + 90.0 * (x[3] - x[2] * x[2]).powi(2)
+            + (1.0 - x[2]).powi(2)
+            + 10.1 * ((x[1] - 1.0).powi(2) + (x[3] - 1.0).powi(2))
+            + 19.8 * (x[1] - 1.0) * (x[3] - 1.0);
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = -400.0 * x[0] * (x[1] - x[0] * x[0]) - 2.0 * (1.0 - x[0]);
+        grad_f[1] = 200.0 * (x[1] - x[0] * x[0]) + 20.2 * (x[1] - 1.0) + 19.8 * (x[3] - 1.0);
+        grad_f[2] = -360.0 * x[2] * (x[3] - x[2] * x[2]) - 2.0 * (1.0 - x[2]);
+        grad_f[3] = 180.0 * (x[3] - x[2] * x[2]) + 20.2 * (x[3] - 1.0) + 19.8 * (x[1] - 1.0);
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs038 {
+    fn num_constraints(&self) -> usize {
+        0
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        0
+    }
+    fn constraint(&self, _: &[Number], _: &mut [Number]) -> bool {
+        true
+    }
+    fn constraint_bounds(&self, _: &mut [Number], _: &mut [Number]) -> bool {
+        true
+    }
+    fn constraint_jacobian_indices(&self, _: &mut [Index], _: &mut [Index]) -> bool {
+        true
+    }
+    fn constraint_jacobian_values(&self, _: &[Number], _: &mut [Number]) -> bool {
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        7
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 1, 1, 2, 3, 3, 3]);
+        cols.copy_from_slice(&[0, 0, 1, 2, 1, 2, 3]);
+        true
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        _: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = obj_factor * (1200.0 * x[0] * x[0] - 400.0 * x[1] + 2.0); /* 0,0 */
+        vals[1] = obj_factor * (-400.0 * x[0]); /* 1,0 */
+        vals[2] = obj_factor * 220.2; /* 1,1 */
+        vals[3] = obj_factor * (1080.0 * x[2] * x[2] - 360.0 * x[3] + 2.0); /* 2,2 */
+        vals[4] = obj_factor * 19.8; /* 3,1 */
+        vals[5] = obj_factor * (-360.0 * x[2]); /* 3,2 */
+        vals[6] = obj_factor * 200.2; /* 3,3 */
+        true
+    }
+}
+
+impl TestProblem for Hs038 {
+    fn name(&self) -> &'static str {
+        "HS038"
+    }
+    fn optimal_objective(&self) -> Number {
+        0.0
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![1.0; 4]
+    }
+}
+
+/// A linear objective with two nonlinear equality constraints.
+///
+/// ```verbatim
+///     min  -x1
+///     s.t. x2 - x1^3 - x3^2 = 0
+///          x1^2 - x2 - x4^2 = 0
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs039;
+
+impl BasicProblem for Hs039 {
+    fn num_variables(&self) -> usize {
+        4
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[-2e19; 4]);
+        x_u.copy_from_slice(&[2e19; 4]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[2.0; 4]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = -x[0];
+        true
+    }
+    fn objective_grad(&self, _: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f.copy_from_slice(&[-1.0, 0.0, 0.0, 0.0]);
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs039 {
+    fn num_constraints(&self) -> usize {
+        2
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        6
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = x[1] - x[0].powi(3) - x[2] * x[2];
+        g[1] = x[0] * x[0] - x[1] - x[3] * x[3];
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l.copy_from_slice(&[0.0; 2]);
+        g_u.copy_from_slice(&[0.0; 2]);
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0, 0, 1, 1, 1]);
+        cols.copy_from_slice(&[0, 1, 2, 0, 1, 3]);
+        true
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        vals[0] = -3.0 * x[0] * x[0]; /* 0,0 */
+        vals[1] = 1.0; /* 0,1 */
+        vals[2] = -2.0 * x[2]; /* 0,2 */
+        vals[3] = 2.0 * x[0]; /* 1,0 */
+        vals[4] = -1.0; /* 1,1 */
+        vals[5] = -2.0 * x[3]; /* 1,3 */
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        3
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 2, 3]);
+        cols.copy_from_slice(&[0, 2, 3]);
+        true
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        _: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = lambda[0] * -6.0 * x[0] + lambda[1] * 2.0; /* 0,0 */
+        vals[1] = lambda[0] * -2.0; /* 2,2 */
+        vals[2] = lambda[1] * -2.0; /* 3,3 */
+        true
+    }
+}
+
+impl TestProblem for Hs039 {
+    fn name(&self) -> &'static str {
+        "HS039"
+    }
+    fn optimal_objective(&self) -> Number {
+        -1.0
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![1.0, 1.0, 0.0, 0.0]
+    }
+}
+
+/// A product objective with three nonlinear equality constraints.
+///
+/// ```verbatim
+///     min  -x1 x2 x3 x4
+///     s.t. x1^3 + x2^2 - 1 = 0
+///          x1^2 x4 - x3 = 0
+///          x4^2 - x2 = 0
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs040;
+
+impl BasicProblem for Hs040 {
+    fn num_variables(&self) -> usize {
+        4
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[-2e19; 4]);
+        x_u.copy_from_slice(&[2e19; 4]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[0.8; 4]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = -x[0] * x[1] * x[2] * x[3];
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = -x[1] * x[2] * x[3];
+        grad_f[1] = -x[0] * x[2] * x[3];
+        grad_f[2] = -x[0] * x[1] * x[3];
+        grad_f[3] = -x[0] * x[1] * x[2];
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs040 {
+    fn num_constraints(&self) -> usize {
+        3
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        7
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = x[0].powi(3) + x[1] * x[1] - 1.0;
+        g[1] = x[0] * x[0] * x[3] - x[2];
+        g[2] = x[3] * x[3] - x[1];
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l.copy_from_slice(&[0.0; 3]);
+        g_u.copy_from_slice(&[0.0; 3]);
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0, 1, 1, 1, 2, 2]);
+        cols.copy_from_slice(&[0, 1, 0, 2, 3, 1, 3]);
+        true
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        vals[0] = 3.0 * x[0] * x[0]; /* 0,0 */
+        vals[1] = 2.0 * x[1]; /* 0,1 */
+        vals[2] = 2.0 * x[0] * x[3]; /* 1,0 */
+        vals[3] = -1.0; /* 1,2 */
+        vals[4] = x[0] * x[0]; /* 1,3 */
+        vals[5] = -1.0; /* 2,1 */
+        vals[6] = 2.0 * x[3]; /* 2,3 */
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        10
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        dense_lower_triangular_indices(rows, cols);
+        true
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = lambda[0] * 6.0 * x[0] + lambda[1] * 2.0 * x[3]; /* 0,0 */
+        vals[1] = obj_factor * -x[2] * x[3]; /* 1,0 */
+        vals[2] = lambda[0] * 2.0; /* 1,1 */
+        vals[3] = obj_factor * -x[1] * x[3]; /* 2,0 */
+        vals[4] = obj_factor * -x[0] * x[3]; /* 2,1 */
+        vals[5] = 0.0; /* 2,2 */
+        vals[6] = obj_factor * -x[1] * x[2] + lambda[1] * 2.0 * x[0]; /* 3,0 */
+        vals[7] = obj_factor * -x[0] * x[2]; /* 3,1 */
+        vals[8] = obj_factor * -x[0] * x[1]; /* 3,2 */
+        vals[9] = lambda[2] * 2.0; /* 3,3 */
+        true
+    }
+}
+
+impl TestProblem for Hs040 {
+    fn name(&self) -> &'static str {
+        "HS040"
+    }
+    fn optimal_objective(&self) -> Number {
+        -0.25
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        [-1.0 / 3.0, -0.5, -11.0 / 12.0, -0.25]
+            .iter()
+            .map(|&e| (2.0 as Number).powf(e))
+            .collect()
+    }
+}
+
+/// An indefinite quadratic objective with six linear inequality constraints.
+///
+/// ```verbatim
+///     min  x1 - x2 - x3 - x1 x3 + x1 x4 + x2 x3 - x2 x4
+///     s.t. x1 + 2 x2 <= 8
+///          4 x1 + x2 <= 12
+///          3 x1 + 4 x2 <= 12
+///          2 x3 + x4 <= 8
+///          x3 + 2 x4 <= 8
+///          x3 + x4 <= 5
+///          0 <= x1, x2, x3, x4
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs044;
+
+impl BasicProblem for Hs044 {
+    fn num_variables(&self) -> usize {
+        4
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[0.0; 4]);
+        x_u.copy_from_slice(&[2e19; 4]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[0.0; 4]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = x[0] - x[1] - x[2] - x[0] * x[2] + x[0] * x[3] + x[1] * x[2] - x[1] * x[3];
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = 1.0 - x[2] + x[3];
+        grad_f[1] = -1.0 + x[2] - x[3];
+        grad_f[2] = -1.0 - x[0] + x[1];
+        grad_f[3] = x[0] - x[1];
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs044 {
+    fn num_constraints(&self) -> usize {
+        6
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        12
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = x[0] + 2.0 * x[1];
+        g[1] = 4.0 * x[0] + x[1];
+        g[2] = 3.0 * x[0] + 4.0 * x[1];
+        g[3] = 2.0 * x[2] + x[3];
+        g[4] = x[2] + 2.0 * x[3];
+        g[5] = x[2] + x[3];
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l.copy_from_slice(&[-2e19; 6]);
+        g_u.copy_from_slice(&[8.0, 12.0, 12.0, 8.0, 8.0, 5.0]);
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5]);
+        cols.copy_from_slice(&[0, 1, 0, 1, 0, 1, 2, 3, 2, 3, 2, 3]);
+        true
+    }
+    fn constraint_jacobian_values(&self, _: &[Number], vals: &mut [Number]) -> bool {
+        vals.copy_from_slice(&[1.0, 2.0, 4.0, 1.0, 3.0, 4.0, 2.0, 1.0, 1.0, 2.0, 1.0, 1.0]);
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        4
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[2, 2, 3, 3]);
+        cols.copy_from_slice(&[0, 1, 0, 1]);
+        true
+    }
+    fn hessian_values(
+        &self,
+        _: &[Number],
+        obj_factor: Number,
+        _: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = -obj_factor; /* 2,0 */
+        vals[1] = obj_factor; /* 2,1 */
+        vals[2] = obj_factor; /* 3,0 */
+        vals[3] = -obj_factor; /* 3,1 */
+        true
+    }
+}
+
+impl TestProblem for Hs044 {
+    fn name(&self) -> &'static str {
+        "HS044"
+    }
+    fn optimal_objective(&self) -> Number {
+        -15.0
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![0.0, 3.0, 0.0, 4.0]
+    }
+}
+
+/// A convex quadratic objective with two linear equality constraints.
+///
+/// ```verbatim
+///     min  (x1 - 1)^2 + (x2 - x3)^2 + (x4 - x5)^2
+///     s.t. x1 + x2 + x3 + x4 + x5 = 5
+///          x3 - 2 (x4 + x5) = -3
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs048;
+
+impl BasicProblem for Hs048 {
+    fn num_variables(&self) -> usize {
+        5
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[-2e19; 5]);
+        x_u.copy_from_slice(&[2e19; 5]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[3.0, 5.0, -3.0, 2.0, -2.0]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = (x[0] - 1.0).powi(2) + (x[1] - x[2]).powi(2) + (x[3] - x[4]).powi(2);
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = 2.0 * (x[0] - 1.0);
+        grad_f[1] = 2.0 * (x[1] - x[2]);
+        grad_f[2] = -2.0 * (x[1] - x[2]);
+        grad_f[3] = 2.0 * (x[3] - x[4]);
+        grad_f[4] = -2.0 * (x[3] - x[4]);
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs048 {
+    fn num_constraints(&self) -> usize {
+        2
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        8
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = x[0] + x[1] + x[2] + x[3] + x[4];
+        g[1] = x[2] - 2.0 * (x[3] + x[4]);
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l.copy_from_slice(&[5.0, -3.0]);
+        g_u.copy_from_slice(&[5.0, -3.0]);
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0, 0, 0, 0, 1, 1, 1]);
+        cols.copy_from_slice(&[0, 1, 2, 3, 4, 2, 3, 4]);
+        true
+    }
+    fn constraint_jacobian_values(&self, _: &[Number], vals: &mut [Number]) -> bool {
+        vals.copy_from_slice(&[1.0, 1.0, 1.0, 1.0, 1.0, 1.0, -2.0, -2.0]);
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        7
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 1, 2, 2, 3, 4, 4]);
+        cols.copy_from_slice(&[0, 1, 1, 2, 3, 3, 4]);
+        true
+    }
+    fn hessian_values(
+        &self,
+        _: &[Number],
+        obj_factor: Number,
+        _: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = obj_factor * 2.0; /* 0,0 */
+        vals[1] = obj_factor * 2.0; /* 1,1 */
+        vals[2] = obj_factor * -2.0; /* 2,1 */
+        vals[3] = obj_factor * 2.0; /* 2,2 */
+        vals[4] = obj_factor * 2.0; /* 3,3 */
+        vals[5] = obj_factor * -2.0; /* 4,3 */
+        vals[6] = obj_factor * 2.0; /* 4,4 */
+        true
+    }
+}
+
+impl TestProblem for Hs048 {
+    fn name(&self) -> &'static str {
+        "HS048"
+    }
+    fn optimal_objective(&self) -> Number {
+        0.0
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![1.0; 5]
+    }
+}
+
+/// A polynomial objective with two linear equality constraints, which is flat to fourth order
+/// around its optimum.
+///
+/// ```verbatim
+///     min  (x1 - x2)^2 + (x3 - 1)^2 + (x4 - 1)^4 + (x5 - 1)^6
+///     s.t. x1 + x2 + x3 + 4 x4 = 7
+///          x3 + 5 x5 = 6
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs049;
+
+impl BasicProblem for Hs049 {
+    fn num_variables(&self) -> usize {
+        5
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[-2e19; 5]);
+        x_u.copy_from_slice(&[2e19; 5]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[10.0, 7.0, 2.0, -3.0, 0.8]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = (x[0] - x[1]).powi(2)
+            + (x[2] - 1.0).powi(2)
+            + (x[3] - 1.0).powi(4)
+            + (x[4] - 1.0).powi(6);
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = 2.0 * (x[0] - x[1]);
+        grad_f[1] = -2.0 * (x[0] - x[1]);
+        grad_f[2] = 2.0 * (x[2] - 1.0);
+        grad_f[3] = 4.0 * (x[3] - 1.0).powi(3);
+        grad_f[4] = 6.0 * (x[4] - 1.0).powi(5);
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs049 {
+    fn num_constraints(&self) -> usize {
+        2
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        6
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = x[0] + x[1] + x[2] + 4.0 * x[3];
+        g[1] = x[2] + 5.0 * x[4];
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l.copy_from_slice(&[7.0, 6.0]);
+        g_u.copy_from_slice(&[7.0, 6.0]);
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0, 0, 0, 1, 1]);
+        cols.copy_from_slice(&[0, 1, 2, 3, 2, 4]);
+        true
+    }
+    fn constraint_jacobian_values(&self, _: &[Number], vals: &mut [Number]) -> bool {
+        vals.copy_from_slice(&[1.0, 1.0, 1.0, 4.0, 1.0, 5.0]);
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        6
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 1, 1, 2, 3, 4]);
+        cols.copy_from_slice(&[0, 0, 1, 2, 3, 4]);
+        true
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        _: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = obj_factor * 2.0; /* 0,0 */
+        vals[1] = obj_factor * -2.0; /* 1,0 */
+        vals[2] = obj_factor * 2.0; /* 1,1 */
+        vals[3] = obj_factor * 2.0; /* 2,2 */
+        vals[4] = obj_factor * 12.0 * (x[3] - 1.0).powi(2); /* 3,3 */
+        vals[5] = obj_factor * 30.0 * (x[4] - 1.0).powi(4); /* 4,4 */
+        true
+    }
+}
+
+impl TestProblem for Hs049 {
+    fn name(&self) -> &'static str {
+        "HS049"
+    }
+    fn optimal_objective(&self) -> Number {
+        0.0
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![1.0; 5]
+    }
+}
+
+/// A polynomial objective with three linear equality constraints.
+///
+/// ```verbatim
+///     min  (x1 - x2)^2 + (x2 - x3)^2 + (x3 - x4)^4 + (x4 - x5)^4
+///     s.t. x1 + 2 x2 + 3 x3 = 6
+///          x2 + 2 x3 + 3 x4 = 6
+///          x3 + 2 x4 + 3 x5 = 6
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs050;
+
+impl BasicProblem for Hs050 {
+    fn num_variables(&self) -> usize {
+        5
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[-2e19; 5]);
+        x_u.copy_from_slice(&[2e19; 5]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[35.0, -31.0, 11.0, 5.0, -5.0]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = (x[0] - x[1]).powi(2)
+            + (x[1] - x[2]).powi(2)
+            + (x[2] - x[3]).powi(4)
+            + (x[3] - x[4]).powi(4);
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        let a = 2.0 * (x[0] - x[1]);
+        let b = 2.0 * (x[1] - x[2]);
+        let c = 4.0 * (x[2] - x[3]).powi(3);
+        let d = 4.0 * (x[3] - x[4]).powi(3);
+        grad_f[0] = a;
+        grad_f[1] = b - a;
+        grad_f[2] = c - b;
+        grad_f[3] = d - c;
+        grad_f[4] = -d;
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs050 {
+    fn num_constraints(&self) -> usize {
+        3
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        9
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = x[0] + 2.0 * x[1] + 3.0 * x[2];
+        g[1] = x[1] + 2.0 * x[2] + 3.0 * x[3];
+        g[2] = x[2] + 2.0 * x[3] + 3.0 * x[4];
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l.copy_from_slice(&[6.0; 3]);
+        g_u.copy_from_slice(&[6.0; 3]);
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0, 0, 1, 1, 1, 2, 2, 2]);
+        cols.copy_from_slice(&[0, 1, 2, 1, 2, 3, 2, 3, 4]);
+        true
+    }
+    fn constraint_jacobian_values(&self, _: &[Number], vals: &mut [Number]) -> bool {
+        vals.copy_from_slice(&[1.0, 2.0, 3.0, 1.0, 2.0, 3.0, 1.0, 2.0, 3.0]);
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        9
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 1, 1, 2, 2, 3, 3, 4, 4]);
+        cols.copy_from_slice(&[0, 0, 1, 1, 2, 2, 3, 3, 4]);
+        true
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        _: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        let c = 12.0 * (x[2] - x[3]).powi(2);
+        let d = 12.0 * (x[3] - x[4]).powi(2);
+        vals[0] = obj_factor * 2.0; /* 0,0 */
+        vals[1] = obj_factor * -2.0; /* 1,0 */
+        vals[2] = obj_factor * 4.0; /* 1,1 */
+        vals[3] = obj_factor * -2.0; /* 2,1 */
+        vals[4] = obj_factor * (2.0 + c); /* 2,2 */
+        vals[5] = obj_factor * -c; /* 3,2 */
+        vals[6] = obj_factor * (c + d); /* 3,3 */
+        vals[7] = obj_factor * -d; /* 4,3 */
+        vals[8] = obj_factor * d; /* 4,4 */
+        true
+    }
+}
+
+impl TestProblem for Hs050 {
+    fn name(&self) -> &'static str {
+        "HS050"
+    }
+    fn optimal_objective(&self) -> Number {
+        0.0
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![1.0; 5]
+    }
+}
+
+/// A convex quadratic objective with three linear equality constraints.
+///
+/// ```verbatim
+///     min  (x1 - x2)^2 + (x2 + x3 - 2)^2 + (x4 - 1)^2 + (x5 - 1)^2
+///     s.t. x1 + 3 x2 = 4
+///          x3 + x4 - 2 x5 = 0
+///          x2 - x5 = 0
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs051;
+
+impl BasicProblem for Hs051 {
+    fn num_variables(&self) -> usize {
+        5
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[-2e19; 5]);
+        x_u.copy_from_slice(&[2e19; 5]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[2.5, 0.5, 2.0, -1.0, 0.5]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = (x[0] - x[1]).powi(2)
+            + (x[1] + x[2] - 2.0).powi(2)
+            + (x[3] - 1.0).powi(2)
+            + (x[4] - 1.0).powi(2);
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        let a = 2.0 * (x[0] - x[1]);
+        let b = 2.0 * (x[1] + x[2] - 2.0);
+        grad_f[0] = a;
+        grad_f[1] = b - a;
+        grad_f[2] = b;
+        grad_f[3] = 2.0 * (x[3] - 1.0);
+        grad_f[4] = 2.0 * (x[4] - 1.0);
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs051 {
+    fn num_constraints(&self) -> usize {
+        3
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        7
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = x[0] + 3.0 * x[1];
+        g[1] = x[2] + x[3] - 2.0 * x[4];
+        g[2] = x[1] - x[4];
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l.copy_from_slice(&[4.0, 0.0, 0.0]);
+        g_u.copy_from_slice(&[4.0, 0.0, 0.0]);
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0, 1, 1, 1, 2, 2]);
+        cols.copy_from_slice(&[0, 1, 2, 3, 4, 1, 4]);
+        true
+    }
+    fn constraint_jacobian_values(&self, _: &[Number], vals: &mut [Number]) -> bool {
+        vals.copy_from_slice(&[1.0, 3.0, 1.0, 1.0, -2.0, 1.0, -1.0]);
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        7
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 1, 1, 2, 2, 3, 4]);
+        cols.copy_from_slice(&[0, 0, 1, 1, 2, 3, 4]);
+        true
+    }
+    fn hessian_values(
+        &self,
+        _: &[Number],
+        obj_factor: Number,
+        _: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = obj_factor * 2.0; /* 0,0 */
+        vals[1] = obj_factor * -2.0; /* 1,0 */
+        vals[2] = obj_factor * 4.0; /* 1,1 */
+        vals[3] = obj_factor * 2.0; /* 2,1 */
+        vals[4] = obj_factor * 2.0; /* 2,2 */
+        vals[5] = obj_factor * 2.0; /* 3,3 */
+        vals[6] = obj_factor * 2.0; /* 4,4 */
+        true
+    }
+}
+
+impl TestProblem for Hs051 {
+    fn name(&self) -> &'static str {
+        "HS051"
+    }
+    fn optimal_objective(&self) -> Number {
+        0.0
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![1.0; 5]
+    }
+}
+
+/// The problem used throughout the Ipopt documentation.
+///
+/// ```verbatim
+///     min  x1 x4 (x1 + x2 + x3) + x3
+///     s.t. x1 x2 x3 x4 >= 25
+///          x1^2 + x2^2 + x3^2 + x4^2 = 40
+///          1 <= x1, x2, x3, x4 <= 5
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs071;
+
+impl BasicProblem for Hs071 {
+    fn num_variables(&self) -> usize {
+        4
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[1.0; 4]);
+        x_u.copy_from_slice(&[5.0; 4]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[1.0, 5.0, 5.0, 1.0]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = x[0] * x[3] * (x[0] + x[1] + x[2]) + x[2];
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = x[0] * x[3] + x[3] * (x[0] + x[1] + x[2]);
+        grad_f[1] = x[0] * x[3];
+        grad_f[2] = x[0] * x[3] + 1.0;
+        grad_f[3] = x[0] * (x[0] + x[1] + x[2]);
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs071 {
+    fn num_constraints(&self) -> usize {
+        2
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        8
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = x[0] * x[1] * x[2] * x[3];
+        g[1] = x[0] * x[0] + x[1] * x[1] + x[2] * x[2] + x[3] * x[3];
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l.copy_from_slice(&[25.0, 40.0]);
+        g_u.copy_from_slice(&[2e19, 40.0]);
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0, 0, 0, 1, 1, 1, 1]);
+        cols.copy_from_slice(&[0, 1, 2, 3, 0, 1, 2, 3]);
+        true
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        vals[0] = x[1] * x[2] * x[3]; /* 0,0 */
+        vals[1] = x[0] * x[2] * x[3]; /* 0,1 */
+        vals[2] = x[0] * x[1] * x[3]; /* 0,2 */
+        vals[3] = x[0] * x[1] * x[2]; /* 0,3 */
+        vals[4] = 2.0 * x[0]; /* 1,0 */
+        vals[5] = 2.0 * x[1]; /* 1,1 */
+        vals[6] = 2.0 * x[2]; /* 1,2 */
+        vals[7] = 2.0 * x[3]; /* 1,3 */
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        10
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        dense_lower_triangular_indices(rows, cols);
+        true
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = obj_factor * 2.0 * x[3] + lambda[1] * 2.0; /* 0,0 */
+        vals[1] = obj_factor * x[3] + lambda[0] * x[2] * x[3]; /* 1,0 */
+        vals[2] = lambda[1] * 2.0; /* 1,1 */
+        vals[3] = obj_factor * x[3] + lambda[0] * x[1] * x[3]; /* 2,0 */
+        vals[4] = lambda[0] * x[0] * x[3]; /* 2,1 */
+        vals[5] = lambda[1] * 2.0; /* 2,2 */
+        vals[6] = obj_factor * (2.0 * x[0] + x[1] + x[2]) + lambda[0] * x[1] * x[2]; /* 3,0 */
+        vals[7] = obj_factor * x[0] + lambda[0] * x[0] * x[2]; /* 3,1 */
+        vals[8] = obj_factor * x[0] + lambda[0] * x[0] * x[1]; /* 3,2 */
+        vals[9] = lambda[1] * 2.0; /* 3,3 */
+        true
+    }
+}
+
+impl TestProblem for Hs071 {
+    fn name(&self) -> &'static str {
+        "HS071"
+    }
+    fn optimal_objective(&self) -> Number {
+        17.0140173
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![1.0, 4.7429994, 3.8211503, 1.3794082]
+    }
+}
+
+/// A convex quadratic program with three linear inequality constraints.
+///
+/// ```verbatim
+///     min  x1^2 + 0.5 x2^2 + x3^2 + 0.5 x4^2 - x1 x3 + x3 x4 - x1 - 3 x2 + x3 - x4
+///     s.t. x1 + 2 x2 + x3 + x4 <= 5
+///          3 x1 + x2 + 2 x3 - x4 <= 4
+///          x2 + 4 x3 >= 1.5
+///          0 <= x1, x2, x3, x4
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Hs076;
+
+impl BasicProblem for Hs076 {
+    fn num_variables(&self) -> usize {
+        4
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&[0.0; 4]);
+        x_u.copy_from_slice(&[2e19; 4]);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[0.5; 4]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = x[0] * x[0] + 0.5 * x[1] * x[1] + x[2] * x[2] + 0.5 * x[3] * x[3] - x[0] * x[2]
+            + x[2] * x[3]
+            - x[0]
+            - 3.0 * x[1]
+            + x[2]
+            - x[3];
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = 2.0 * x[0] - x[2] - 1.0;
+        grad_f[1] = x[1] - 3.0;
+        grad_f[2] = 2.0 * x[2] - x[0] + x[3] + 1.0;
+        grad_f[3] = x[3] + x[2] - 1.0;
+        true
+    }
+}
+
+impl ConstrainedProblem for Hs076 {
+    fn num_constraints(&self) -> usize {
+        3
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        10
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = x[0] + 2.0 * x[1] + x[2] + x[3];
+        g[1] = 3.0 * x[0] + x[1] + 2.0 * x[2] - x[3];
+        g[2] = x[1] + 4.0 * x[2];
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l.copy_from_slice(&[-2e19, -2e19, 1.5]);
+        g_u.copy_from_slice(&[5.0, 4.0, 2e19]);
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0, 0, 0, 1, 1, 1, 1, 2, 2]);
+        cols.copy_from_slice(&[0, 1, 2, 3, 0, 1, 2, 3, 1, 2]);
+        true
+    }
+    fn constraint_jacobian_values(&self, _: &[Number], vals: &mut [Number]) -> bool {
+        vals.copy_from_slice(&[1.0, 2.0, 1.0, 1.0, 3.0, 1.0, 2.0, -1.0, 1.0, 4.0]);
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        6
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 1, 2, 2, 3, 3]);
+        cols.copy_from_slice(&[0, 1, 0, 2, 2, 3]);
+        true
+    }
+    fn hessian_values(
+        &self,
+        _: &[Number],
+        obj_factor: Number,
+        _: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = obj_factor * 2.0; /* 0,0 */
+        vals[1] = obj_factor; /* 1,1 */
+        vals[2] = -obj_factor; /* 2,0 */
+        vals[3] = obj_factor * 2.0; /* 2,2 */
+        vals[4] = obj_factor; /* 3,2 */
+        vals[5] = obj_factor; /* 3,3 */
+        true
+    }
+}
+
+impl TestProblem for Hs076 {
+    fn name(&self) -> &'static str {
+        "HS076"
+    }
+    fn optimal_objective(&self) -> Number {
+        -103.0 / 22.0
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        vec![3.0 / 11.0, 23.0 / 11.0, 0.0, 6.0 / 11.0]
+    }
+}
+
+/// Any one of the problems in this collection.
+///
+/// This is useful for running the same code on all problems, since Ipopt requires a concrete
+/// problem type.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum HsProblem {
+    /// See [`Hs001`](struct.Hs001.html).
+    Hs001(Hs001),
+    /// See [`Hs002`](struct.Hs002.html).
+    Hs002(Hs002),
+    /// See [`Hs003`](struct.Hs003.html).
+    Hs003(Hs003),
+    /// See [`Hs004`](struct.Hs004.html).
+    Hs004(Hs004),
+    /// See [`Hs005`](struct.Hs005.html).
+    Hs005(Hs005),
+    /// See [`Hs006`](struct.Hs006.html).
+    Hs006(Hs006),
+    /// See [`Hs007`](struct.Hs007.html).
+    Hs007(Hs007),
+    /// See [`Hs010`](struct.Hs010.html).
+    Hs010(Hs010),
+    /// See [`Hs011`](struct.Hs011.html).
+    Hs011(Hs011),
+    /// See [`Hs012`](struct.Hs012.html).
+    Hs012(Hs012),
+    /// See [`Hs013`](struct.Hs013.html).
+    Hs013(Hs013),
+    /// See [`Hs014`](struct.Hs014.html).
+    Hs014(Hs014),
+    /// See [`Hs015`](struct.Hs015.html).
+    Hs015(Hs015),
+    /// See [`Hs021`](struct.Hs021.html).
+    Hs021(Hs021),
+    /// See [`Hs028`](struct.Hs028.html).
+    Hs028(Hs028),
+    /// See [`Hs035`](struct.Hs035.html).
+    Hs035(Hs035),
+    /// See [`Hs038`](struct.Hs038.html).
+    Hs038(Hs038),
+    /// See [`Hs039`](struct.Hs039.html).
+    Hs039(Hs039),
+    /// See [`Hs040`](struct.Hs040.html).
+    Hs040(Hs040),
+    /// See [`Hs044`](struct.Hs044.html).
+    Hs044(Hs044),
+    /// See [`Hs048`](struct.Hs048.html).
+    Hs048(Hs048),
+    /// See [`Hs049`](struct.Hs049.html).
+    Hs049(Hs049),
+    /// See [`Hs050`](struct.Hs050.html).
+    Hs050(Hs050),
+    /// See [`Hs051`](struct.Hs051.html).
+    Hs051(Hs051),
+    /// See [`Hs071`](struct.Hs071.html).
+    Hs071(Hs071),
+    /// See [`Hs076`](struct.Hs076.html).
+    Hs076(Hs076),
+}
+
+impl HsProblem {
+    /// All problems in this collection.
+    pub fn all() -> Vec<HsProblem> {
+        vec![
+            HsProblem::Hs001(Hs001),
+            HsProblem::Hs002(Hs002),
+            HsProblem::Hs003(Hs003),
+            HsProblem::Hs004(Hs004),
+            HsProblem::Hs005(Hs005),
+            HsProblem::Hs006(Hs006),
+            HsProblem::Hs007(Hs007),
+            HsProblem::Hs010(Hs010),
+            HsProblem::Hs011(Hs011),
+            HsProblem::Hs012(Hs012),
+            HsProblem::Hs013(Hs013),
+            HsProblem::Hs014(Hs014),
+            HsProblem::Hs015(Hs015),
+            HsProblem::Hs021(Hs021),
+            HsProblem::Hs028(Hs028),
+            HsProblem::Hs035(Hs035),
+            HsProblem::Hs038(Hs038),
+            HsProblem::Hs039(Hs039),
+            HsProblem::Hs040(Hs040),
+            HsProblem::Hs044(Hs044),
+            HsProblem::Hs048(Hs048),
+            HsProblem::Hs049(Hs049),
+            HsProblem::Hs050(Hs050),
+            HsProblem::Hs051(Hs051),
+            HsProblem::Hs071(Hs071),
+            HsProblem::Hs076(Hs076),
+        ]
+    }
+}
+
+/// Forward a method call to the problem stored in an `HsProblem`.
+macro_rules! dispatch {
+    ($self:ident, $p:ident => $e:expr) => {
+        match $self {
+            HsProblem::Hs001($p) => $e,
+            HsProblem::Hs002($p) => $e,
+            HsProblem::Hs003($p) => $e,
+            HsProblem::Hs004($p) => $e,
+            HsProblem::Hs005($p) => $e,
+            HsProblem::Hs006($p) => $e,
+            HsProblem::Hs007($p) => $e,
+            HsProblem::Hs010($p) => $e,
+            HsProblem::Hs011($p) => $e,
+            HsProblem::Hs012($p) => $e,
+            HsProblem::Hs013($p) => $e,
+            HsProblem::Hs014($p) => $e,
+            HsProblem::Hs015($p) => $e,
+            HsProblem::Hs021($p) => $e,
+            HsProblem::Hs028($p) => $e,
+            HsProblem::Hs035($p) => $e,
+            HsProblem::Hs038($p) => $e,
+            HsProblem::Hs039($p) => $e,
+            HsProblem::Hs040($p) => $e,
+            HsProblem::Hs044($p) => $e,
+            HsProblem::Hs048($p) => $e,
+            HsProblem::Hs049($p) => $e,
+            HsProblem::Hs050($p) => $e,
+            HsProblem::Hs051($p) => $e,
+            HsProblem::Hs071($p) => $e,
+            HsProblem::Hs076($p) => $e,
+        }
+    };
+}
+
+impl BasicProblem for HsProblem {
+    fn num_variables(&self) -> usize {
+        dispatch!(self, p => p.num_variables())
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        dispatch!(self, p => p.bounds(x_l, x_u))
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        dispatch!(self, p => p.initial_point(x))
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        dispatch!(self, p => p.objective(x, obj))
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        dispatch!(self, p => p.objective_grad(x, grad_f))
+    }
+}
+
+impl ConstrainedProblem for HsProblem {
+    fn num_constraints(&self) -> usize {
+        dispatch!(self, p => p.num_constraints())
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        dispatch!(self, p => p.num_constraint_jacobian_non_zeros())
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        dispatch!(self, p => p.constraint(x, g))
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        dispatch!(self, p => p.constraint_bounds(g_l, g_u))
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        dispatch!(self, p => p.constraint_jacobian_indices(rows, cols))
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        dispatch!(self, p => p.constraint_jacobian_values(x, vals))
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        dispatch!(self, p => p.num_hessian_non_zeros())
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        dispatch!(self, p => p.hessian_indices(rows, cols))
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        dispatch!(self, p => p.hessian_values(x, obj_factor, lambda, vals))
+    }
+}
+
+impl TestProblem for HsProblem {
+    fn name(&self) -> &'static str {
+        dispatch!(self, p => p.name())
+    }
+    fn optimal_objective(&self) -> Number {
+        dispatch!(self, p => p.optimal_objective())
+    }
+    fn optimal_solution(&self) -> Vec<Number> {
+        dispatch!(self, p => p.optimal_solution())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Check the objective value at the known optimum and the gradient and Jacobian against
+    /// central finite differences at the initial point.
+    #[test]
+    fn derivative_test() {
+        let h = 1e-6;
+        for p in HsProblem::all() {
+            let n = p.num_variables();
+            let m = p.num_constraints();
+
+            let mut obj = 0.0;
+            assert!(p.objective(&p.optimal_solution(), &mut obj));
+            assert!(
+                (obj - p.optimal_objective()).abs() < 1e-6,
+                "{}: wrong optimum",
+                p.name()
+            );
+
+            let mut x = vec![0.0; n];
+            assert!(p.initial_point(&mut x));
+            let mut grad = vec![0.0; n];
+            assert!(p.objective_grad(&x, &mut grad));
+
+            let nnz = p.num_constraint_jacobian_non_zeros();
+            let mut rows = vec![0; nnz];
+            let mut cols = vec![0; nnz];
+            let mut vals = vec![0.0; nnz];
+            assert!(p.constraint_jacobian_indices(&mut rows, &mut cols));
+            assert!(p.constraint_jacobian_values(&x, &mut vals));
+            let mut jac = vec![vec![0.0; n]; m];
+            for k in 0..nnz {
+                jac[rows[k] as usize][cols[k] as usize] += vals[k];
+            }
+
+            for j in 0..n {
+                let mut xp = x.clone();
+                let mut xm = x.clone();
+                xp[j] += h;
+                xm[j] -= h;
+                let (mut fp, mut fm) = (0.0, 0.0);
+                p.objective(&xp, &mut fp);
+                p.objective(&xm, &mut fm);
+                let fd = (fp - fm) / (2.0 * h);
+                assert!(
+                    (fd - grad[j]).abs() < 1e-4 * grad[j].abs().max(1.0),
+                    "{}: wrong gradient",
+                    p.name()
+                );
+
+                let (mut gp, mut gm) = (vec![0.0; m], vec![0.0; m]);
+                p.constraint(&xp, &mut gp);
+                p.constraint(&xm, &mut gm);
+                for i in 0..m {
+                    let fd = (gp[i] - gm[i]) / (2.0 * h);
+                    assert!(
+                        (fd - jac[i][j]).abs() < 1e-4 * jac[i][j].abs().max(1.0),
+                        "{}: wrong Jacobian",
+                        p.name()
+                    );
+                }
+            }
+        }
+    }
+
+    /// Check the Hessian of the Lagrangian against central finite differences of the gradient
+    /// of the Lagrangian at the initial point.
+    #[test]
+    fn hessian_test() {
+        let h = 1e-6;
+        for p in HsProblem::all() {
+            let n = p.num_variables();
+            let m = p.num_constraints();
+            let lambda: Vec<Number> = (0..m).map(|i| 1.0 + i as Number).collect();
+
+            let lagrangian_grad = |x: &[Number]| {
+                let mut grad = vec![0.0; n];
+                assert!(p.objective_grad(x, &mut grad));
+                let nnz = p.num_constraint_jacobian_non_zeros();
+                let mut rows = vec![0; nnz];
+                let mut cols = vec![0; nnz];
+                let mut vals = vec![0.0; nnz];
+                assert!(p.constraint_jacobian_indices(&mut rows, &mut cols));
+                assert!(p.constraint_jacobian_values(x, &mut vals));
+                for k in 0..nnz {
+                    grad[cols[k] as usize] += lambda[rows[k] as usize] * vals[k];
+                }
+                grad
+            };
+
+            let mut x = vec![0.0; n];
+            assert!(p.initial_point(&mut x));
+
+            let nnz = p.num_hessian_non_zeros();
+            let mut rows = vec![0; nnz];
+            let mut cols = vec![0; nnz];
+            let mut vals = vec![0.0; nnz];
+            assert!(p.hessian_indices(&mut rows, &mut cols));
+            assert!(p.hessian_values(&x, 1.0, &lambda, &mut vals));
+            let mut hess = vec![vec![0.0; n]; n];
+            for k in 0..nnz {
+                let (row, col) = (rows[k] as usize, cols[k] as usize);
+                assert!(row >= col, "{}: entry above the diagonal", p.name());
+                hess[row][col] += vals[k];
+                if row != col {
+                    hess[col][row] += vals[k];
+                }
+            }
+
+            for j in 0..n {
+                let mut xp = x.clone();
+                let mut xm = x.clone();
+                xp[j] += h;
+                xm[j] -= h;
+                let (gp, gm) = (lagrangian_grad(&xp), lagrangian_grad(&xm));
+                for i in 0..n {
+                    let fd = (gp[i] - gm[i]) / (2.0 * h);
+                    assert!(
+                        (fd - hess[i][j]).abs() < 1e-4 * hess[i][j].abs().max(1.0),
+                        "{}: wrong Hessian",
+                        p.name()
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Loading the HSL solvers from a shared library at runtime.
+//!
+//! Ipopt 3.14 and newer can load the HSL solvers MA27, MA57, MA77, MA86 and MA97 from a shared
+//! library, such as the `libhsl.so` built by ThirdParty-HSL or the one distributed with an HSL
+//! license, when the solver is created. This makes them usable with prebuilt Ipopt libraries
+//! that were built without HSL. [`Ipopt::set_hsl_library`](struct.Ipopt.html#method.set_hsl_library)
+//! points a solver at such a library and selects one of its solvers.
+
+use crate::{BasicProblem, Ipopt, LinearSolver, OptionRegistry, UnavailableLinearSolverError};
+
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// Error returned when an HSL solver cannot be loaded from a shared library.
+#[derive(Clone, Debug, PartialEq)]
+pub enum HslLibraryError {
+    /// The given library does not exist.
+    NotFound(PathBuf),
+    /// The linked Ipopt library does not have the `hsllib` option, which was added in Ipopt
+    /// 3.14.
+    Unsupported,
+    /// The library was found, but the solver could not be used with it, for example because
+    /// the library does not contain it.
+    Unavailable(UnavailableLinearSolverError),
+}
+
+impl Display for HslLibraryError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            HslLibraryError::NotFound(path) => {
+                write!(f, "The HSL library {} does not exist.", path.display())
+            }
+            HslLibraryError::Unsupported => write!(
+                f,
+                "Loading HSL libraries at runtime requires Ipopt 3.14 or newer."
+            ),
+            HslLibraryError::Unavailable(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for HslLibraryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HslLibraryError::Unavailable(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<UnavailableLinearSolverError> for HslLibraryError {
+    fn from(err: UnavailableLinearSolverError) -> Self {
+        HslLibraryError::Unavailable(err)
+    }
+}
+
+impl<P: BasicProblem> Ipopt<P> {
+    /// Load the HSL solvers from the shared library at `path` and select the given one as the
+    /// linear solver.
+    ///
+    /// The solver is tried on a tiny problem with the library first, so a library that cannot
+    /// be loaded or does not contain the solver is reported here instead of when the first
+    /// solve starts. The options of this solver are only changed once the probe succeeds.
+    /// Requires Ipopt 3.14 or newer.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `solver` is not one of the HSL solvers.
+    pub fn set_hsl_library(
+        &mut self,
+        path: &Path,
+        solver: LinearSolver,
+    ) -> Result<&mut Self, HslLibraryError> {
+        assert!(solver.is_hsl(), "{} is not an HSL solver", solver);
+        if !path.is_file() {
+            return Err(HslLibraryError::NotFound(path.to_path_buf()));
+        }
+        if OptionRegistry::linked_ref().get("hsllib").is_none() {
+            return Err(HslLibraryError::Unsupported);
+        }
+        let path_str = path.to_string_lossy();
+        if !solver.probe(&[("hsllib", path_str.as_ref())])
+            || !Self::set_ipopt_option(self.nlp_internal, "hsllib", path_str.as_ref())
+            || !Self::set_ipopt_option(self.nlp_internal, "linear_solver", solver.option_value())
+        {
+            return Err(HslLibraryError::Unavailable(UnavailableLinearSolverError {
+                solver,
+                available: crate::available_linear_solvers(),
+            }));
+        }
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_message_test() {
+        let err = HslLibraryError::NotFound(PathBuf::from("/opt/hsl/libhsl.so"));
+        assert_eq!(
+            err.to_string(),
+            "The HSL library /opt/hsl/libhsl.so does not exist."
+        );
+        let err = HslLibraryError::from(UnavailableLinearSolverError {
+            solver: LinearSolver::Ma97,
+            available: Vec::new(),
+        });
+        assert!(err.to_string().contains("Ipopt::set_hsl_library"));
+        assert!(std::error::Error::source(&err).is_some());
+    }
+}
@@ -0,0 +1,385 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Heuristics for repairing poor initial points.
+//!
+//! Ipopt moves the initial point into the interior of the variable bounds by itself, but an
+//! initial point far away from the feasible region of the constraints often leads to early
+//! restoration phase failures. [`InitialPointRepair`](struct.InitialPointRepair.html) projects
+//! the initial guess of a problem into its bounds, pushes it off the bounds the way Ipopt does,
+//! and can optionally run a short feasibility pre-phase that moves the point towards the
+//! feasible region while staying close to the original guess.
+//!
+//! The repaired point is returned to the caller, who would typically return it from
+//! [`BasicProblem::initial_point`](trait.BasicProblem.html#tymethod.initial_point) for the
+//! actual solve.
+
+use crate::active_set::BOUND_INF;
+use crate::{BasicProblem, ConstrainedProblem, Index, Ipopt, Number, SolveStatus};
+
+/// Margins by which points are pushed off their bounds.
+///
+/// These have the same meaning as the `bound_push` and `bound_frac` Ipopt options: a value is
+/// kept at least `min(push * max(1, |bound|), frac * (upper - lower))` away from each finite
+/// bound.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoundPush {
+    /// Absolute margin relative to the magnitude of the bound.
+    pub push: Number,
+    /// Margin relative to the distance between the bounds.
+    pub frac: Number,
+}
+
+impl Default for BoundPush {
+    /// The Ipopt defaults for `bound_push` and `bound_frac`.
+    fn default() -> Self {
+        BoundPush {
+            push: 1e-2,
+            frac: 1e-2,
+        }
+    }
+}
+
+/// Project `x` into the box given by `x_l` and `x_u`.
+///
+/// Returns the number of entries that were moved.
+pub fn project_into_bounds(x: &mut [Number], x_l: &[Number], x_u: &[Number]) -> usize {
+    let mut moved = 0;
+    for ((x, &l), &u) in x.iter_mut().zip(x_l.iter()).zip(x_u.iter()) {
+        let projected = if *x < l {
+            l
+        } else if *x > u {
+            u
+        } else {
+            *x
+        };
+        if projected != *x {
+            *x = projected;
+            moved += 1;
+        }
+    }
+    moved
+}
+
+/// Push `x` off the finite bounds given by `x_l` and `x_u` by the given margins.
+///
+/// Values are expected to be within bounds already (see
+/// [`project_into_bounds`](fn.project_into_bounds.html)). Variables with equal bounds are left
+/// untouched.
+///
+/// Returns the number of entries that were moved.
+pub fn push_from_bounds(
+    x: &mut [Number],
+    x_l: &[Number],
+    x_u: &[Number],
+    margins: BoundPush,
+) -> usize {
+    let mut moved = 0;
+    for ((x, &l), &u) in x.iter_mut().zip(x_l.iter()).zip(x_u.iter()) {
+        if l == u {
+            continue;
+        }
+        let has_lower = l > -BOUND_INF;
+        let has_upper = u < BOUND_INF;
+        let width = if has_lower && has_upper {
+            margins.frac * (u - l)
+        } else {
+            Number::INFINITY
+        };
+        let mut pushed = *x;
+        if has_lower {
+            pushed = pushed.max(l + (margins.push * l.abs().max(1.0)).min(width));
+        }
+        if has_upper {
+            pushed = pushed.min(u - (margins.push * u.abs().max(1.0)).min(width));
+        }
+        if pushed != *x {
+            *x = pushed;
+            moved += 1;
+        }
+    }
+    moved
+}
+
+/// Configuration for repairing the initial point of a problem.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InitialPointRepair {
+    /// Margins used to push the initial point off its bounds, or `None` to only project into
+    /// the bounds.
+    pub bound_push: Option<BoundPush>,
+    /// Maximum number of iterations of the feasibility pre-phase, or `None` to skip it.
+    ///
+    /// The pre-phase is only run by
+    /// [`repair_constrained`](struct.InitialPointRepair.html#method.repair_constrained).
+    pub feasibility_max_iter: Option<Index>,
+}
+
+impl Default for InitialPointRepair {
+    fn default() -> Self {
+        InitialPointRepair {
+            bound_push: Some(BoundPush::default()),
+            feasibility_max_iter: None,
+        }
+    }
+}
+
+impl InitialPointRepair {
+    /// Create a repair configuration that pushes points off bounds with the Ipopt defaults and
+    /// skips the feasibility pre-phase.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the margins used to push points off their bounds.
+    pub fn bound_push(&mut self, margins: Option<BoundPush>) -> &mut Self {
+        self.bound_push = margins;
+        self
+    }
+
+    /// Enable the feasibility pre-phase with the given iteration limit.
+    pub fn feasibility_pre_phase(&mut self, max_iter: Index) -> &mut Self {
+        self.feasibility_max_iter = Some(max_iter);
+        self
+    }
+
+    /// Repair the initial point of the given problem with respect to its variable bounds.
+    ///
+    /// Returns `None` if the bounds could not be retrieved from the problem.
+    pub fn repair<P: BasicProblem>(&self, problem: &P) -> Option<Vec<Number>> {
+        let n = problem.num_variables();
+        let mut x = vec![0.0; n];
+        let mut x_l = vec![0.0; n];
+        let mut x_u = vec![0.0; n];
+        if !problem.bounds(&mut x_l, &mut x_u) {
+            return None;
+        }
+        if !problem.initial_point(&mut x) {
+            for v in x.iter_mut() {
+                *v = 0.0;
+            }
+        }
+        self.repair_point(&mut x, &x_l, &x_u);
+        Some(x)
+    }
+
+    /// Repair the initial point of the given constrained problem.
+    ///
+    /// The point is first repaired with respect to the variable bounds. If the feasibility
+    /// pre-phase is enabled, a few Ipopt iterations then minimize the distance to that point
+    /// subject to the constraints, and the result is kept if it reduces the constraint
+    /// violation.
+    ///
+    /// Returns `None` if the bounds could not be retrieved from the problem.
+    pub fn repair_constrained<P: ConstrainedProblem>(&self, problem: &P) -> Option<Vec<Number>> {
+        let mut x = self.repair(problem)?;
+        let max_iter = match self.feasibility_max_iter {
+            Some(max_iter) => max_iter,
+            None => return Some(x),
+        };
+
+        let before = constraint_violation(problem, &x);
+        if before == Some(0.0) {
+            return Some(x);
+        }
+
+        let feasibility = FeasibilityProblem {
+            problem,
+            target: x.clone(),
+        };
+        let mut ipopt = match Ipopt::new(feasibility) {
+            Ok(ipopt) => ipopt,
+            Err(_) => return Some(x),
+        };
+        ipopt.set_option("print_level", 0);
+        ipopt.set_option("sb", "yes");
+        ipopt.set_option("max_iter", max_iter);
+        ipopt.set_option("tol", 1e-4);
+        let result = ipopt.solve();
+        let candidate = match result.status {
+            SolveStatus::SolveSucceeded
+            | SolveStatus::SolvedToAcceptableLevel
+            | SolveStatus::FeasiblePointFound
+            | SolveStatus::SearchDirectionBecomesTooSmall
+            | SolveStatus::MaximumIterationsExceeded
+            | SolveStatus::MaximumCpuTimeExceeded
+            | SolveStatus::RestorationFailed
+            | SolveStatus::InfeasibleProblemDetected => {
+                Some(result.solver_data.solution.primal_variables.to_vec())
+            }
+            _ => None,
+        };
+
+        if let Some(mut candidate) = candidate {
+            let mut x_l = vec![0.0; x.len()];
+            let mut x_u = vec![0.0; x.len()];
+            problem.bounds(&mut x_l, &mut x_u);
+            self.repair_point(&mut candidate, &x_l, &x_u);
+            let after = constraint_violation(problem, &candidate);
+            let improved = match (before, after) {
+                (Some(before), Some(after)) => after < before,
+                (None, Some(_)) => true,
+                _ => false,
+            };
+            if improved {
+                x = candidate;
+            }
+        }
+        Some(x)
+    }
+
+    fn repair_point(&self, x: &mut [Number], x_l: &[Number], x_u: &[Number]) {
+        project_into_bounds(x, x_l, x_u);
+        if let Some(margins) = self.bound_push {
+            push_from_bounds(x, x_l, x_u, margins);
+        }
+    }
+}
+
+/// The largest constraint violation at `x` or `None` if the constraints could not be evaluated.
+fn constraint_violation<P: ConstrainedProblem>(problem: &P, x: &[Number]) -> Option<Number> {
+    let m = problem.num_constraints();
+    let mut g = vec![0.0; m];
+    let mut g_l = vec![0.0; m];
+    let mut g_u = vec![0.0; m];
+    if !problem.constraint(x, &mut g) || !problem.constraint_bounds(&mut g_l, &mut g_u) {
+        return None;
+    }
+    Some(
+        g.iter()
+            .zip(g_l.iter().zip(g_u.iter()))
+            .map(|(&g, (&l, &u))| (l - g).max(g - u).max(0.0))
+            .fold(0.0, Number::max),
+    )
+}
+
+/// Minimize `0.5 * |x - target|^2` subject to the bounds and constraints of `problem`.
+struct FeasibilityProblem<'a, P> {
+    problem: &'a P,
+    target: Vec<Number>,
+}
+
+impl<'a, P: ConstrainedProblem> BasicProblem for FeasibilityProblem<'a, P> {
+    fn indexing_style(&self) -> crate::IndexingStyle {
+        self.problem.indexing_style()
+    }
+    fn num_variables(&self) -> usize {
+        self.target.len()
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        self.problem.bounds(x_l, x_u)
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&self.target);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = 0.5
+            * x.iter()
+                .zip(self.target.iter())
+                .map(|(&x, &t)| (x - t) * (x - t))
+                .sum::<Number>();
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        for ((g, &x), &t) in grad_f.iter_mut().zip(x.iter()).zip(self.target.iter()) {
+            *g = x - t;
+        }
+        true
+    }
+}
+
+impl<'a, P: ConstrainedProblem> ConstrainedProblem for FeasibilityProblem<'a, P> {
+    fn num_constraints(&self) -> usize {
+        self.problem.num_constraints()
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        self.problem.num_constraint_jacobian_non_zeros()
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        self.problem.constraint(x, g)
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        self.problem.constraint_bounds(g_l, g_u)
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        self.problem.constraint_jacobian_indices(rows, cols)
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        self.problem.constraint_jacobian_values(x, vals)
+    }
+    /// The constraint Hessian of the problem followed by the identity.
+    fn num_hessian_non_zeros(&self) -> usize {
+        self.problem.num_hessian_non_zeros() + self.target.len()
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        let nnz = self.problem.num_hessian_non_zeros();
+        if !self
+            .problem
+            .hessian_indices(&mut rows[..nnz], &mut cols[..nnz])
+        {
+            return false;
+        }
+        let base = self.problem.indexing_style().offset();
+        for (i, (r, c)) in rows[nnz..]
+            .iter_mut()
+            .zip(cols[nnz..].iter_mut())
+            .enumerate()
+        {
+            *r = base + i as Index;
+            *c = base + i as Index;
+        }
+        true
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        let nnz = self.problem.num_hessian_non_zeros();
+        if !self
+            .problem
+            .hessian_values(x, 0.0, lambda, &mut vals[..nnz])
+        {
+            return false;
+        }
+        for v in vals[nnz..].iter_mut() {
+            *v = obj_factor;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_repair_test() {
+        let x_l = [0.0, 0.0, -1e20, 1.0, 0.0];
+        let x_u = [1.0, 1e-3, 1e20, 1.0, 1e20];
+        let mut x = [-1.0, 0.0, 5.0, 1.0, 200.0];
+
+        assert_eq!(project_into_bounds(&mut x, &x_l, &x_u), 1);
+        assert_eq!(x, [0.0, 0.0, 5.0, 1.0, 200.0]);
+
+        assert_eq!(
+            push_from_bounds(&mut x, &x_l, &x_u, BoundPush::default()),
+            2
+        );
+        // The margin is limited by the distance between the bounds for narrow boxes.
+        assert_eq!(x, [1e-2, 1e-5, 5.0, 1.0, 200.0]);
+    }
+}
@@ -0,0 +1,137 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Stopping solves on Ctrl-C with [`ctrlc`](https://docs.rs/ctrlc).
+//!
+//! With the `ctrlc` feature,
+//! [`Ipopt::set_interrupt_on_ctrl_c`](struct.Ipopt.html#method.set_interrupt_on_ctrl_c) installs
+//! a process wide handler that turns `SIGINT` (or Ctrl-C on Windows) into a cooperative stop.
+//! Ipopt is not interrupted in the middle of an iteration. Instead, the solve stops at the next
+//! intermediate callback with
+//! [`SolveStatus::UserRequestedStop`](enum.SolveStatus.html#variant.UserRequestedStop) and a
+//! [`user_stop`](struct.SolveResult.html#structfield.user_stop) with reason
+//! [`StopReason::Interrupted`](enum.StopReason.html#variant.Interrupted), so the result holds the
+//! last accepted iterate and the statistics of the solve so far.
+//!
+//! The handler only delays Ctrl-C while an interruptible solve is running. Outside of such a
+//! solve, or when Ctrl-C is pressed a second time before the solve gets to stop, the process
+//! exits with status 130 as it would without the handler.
+
+use crate::{BasicProblem, Ipopt};
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Number of interruptible solves that are currently running.
+static RUNNING: AtomicUsize = AtomicUsize::new(0);
+/// Whether Ctrl-C was pressed during the running solves.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+/// Whether the handler is installed. It can only be installed once per process.
+static INSTALLED: Mutex<bool> = Mutex::new(false);
+
+/// Exit status of a process killed by `SIGINT`.
+const SIGINT_EXIT_STATUS: i32 = 130;
+
+/// The signal handler.
+fn handle_interrupt() {
+    if RUNNING.load(Ordering::SeqCst) == 0 || INTERRUPTED.swap(true, Ordering::SeqCst) {
+        std::process::exit(SIGINT_EXIT_STATUS);
+    }
+}
+
+/// Interrupt state of an [`Ipopt`](struct.Ipopt.html) instance.
+#[derive(Debug, Default)]
+pub(crate) struct Interrupt {
+    /// Whether this instance is counted in `RUNNING`.
+    running: bool,
+}
+
+impl Interrupt {
+    /// Register a solve that is about to start.
+    pub(crate) fn start(&mut self) {
+        if !self.running {
+            RUNNING.fetch_add(1, Ordering::SeqCst);
+            self.running = true;
+        }
+    }
+
+    /// Whether the running solve should stop.
+    pub(crate) fn interrupted(&self) -> bool {
+        self.running && INTERRUPTED.load(Ordering::SeqCst)
+    }
+
+    /// Unregister a finished solve, resetting the interrupt once no solves are left.
+    pub(crate) fn finish(&mut self) {
+        if self.running {
+            self.running = false;
+            if RUNNING.fetch_sub(1, Ordering::SeqCst) == 1 {
+                INTERRUPTED.store(false, Ordering::SeqCst);
+            }
+        }
+    }
+}
+
+impl Drop for Interrupt {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+impl<P: BasicProblem> Ipopt<P> {
+    /// Stop solves cooperatively on Ctrl-C instead of killing the process.
+    ///
+    /// The first call installs the Ctrl-C handler for the whole process, which fails if another
+    /// handler was already installed through `ctrlc`. Disabling the interrupt leaves the handler
+    /// installed, but Ctrl-C then exits the process again.
+    pub fn set_interrupt_on_ctrl_c(&mut self, enable: bool) -> Result<&mut Self, ctrlc::Error> {
+        if enable {
+            let mut installed = INSTALLED.lock().unwrap_or_else(|e| e.into_inner());
+            if !*installed {
+                ctrlc::set_handler(handle_interrupt)?;
+                *installed = true;
+            }
+            self.interrupt = Some(Interrupt::default());
+        } else {
+            self.interrupt = None;
+        }
+        self.update_intermediate_callback();
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interrupt_test() {
+        let mut first = Interrupt::default();
+        let mut second = Interrupt::default();
+        assert!(!first.interrupted());
+
+        first.start();
+        second.start();
+        handle_interrupt();
+        assert!(first.interrupted());
+        assert!(second.interrupted());
+
+        // The interrupt stays until every running solve has finished.
+        first.finish();
+        assert!(!first.interrupted());
+        assert!(second.interrupted());
+        second.finish();
+        assert!(!INTERRUPTED.load(Ordering::SeqCst));
+        assert_eq!(RUNNING.load(Ordering::SeqCst), 0);
+    }
+}
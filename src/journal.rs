@@ -0,0 +1,154 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Control over the console output of Ipopt.
+//!
+//! Ipopt tags every message with a journal category and a print level. The `print_level` option
+//! sets the level for all categories at once, while
+//! [`Ipopt::set_category_print_level`](struct.Ipopt.html#method.set_category_print_level)
+//! overrides it for individual categories. For instance, the main iteration log can be silenced
+//! while keeping warnings from the linear solver:
+//!
+//! ```no_run
+//! use ipopt::*;
+//! # struct NLP;
+//! # impl BasicProblem for NLP {
+//! #     fn num_variables(&self) -> usize { 1 }
+//! #     fn bounds(&self, _: &mut [Number], _: &mut [Number]) -> bool { true }
+//! #     fn initial_point(&self, _: &mut [Number]) -> bool { true }
+//! #     fn objective(&self, _: &[Number], _: &mut Number) -> bool { true }
+//! #     fn objective_grad(&self, _: &[Number], _: &mut [Number]) -> bool { true }
+//! # }
+//! let mut ipopt = Ipopt::new_unconstrained(NLP).unwrap();
+//! ipopt.set_option("print_level", PrintLevel::Warning as i32);
+//! ipopt
+//!     .set_category_print_level(JournalCategory::Main, PrintLevel::None)
+//!     .set_category_print_level(JournalCategory::LinearAlgebra, PrintLevel::Warning);
+//! ```
+
+use crate::ffi;
+use crate::{BasicProblem, Ipopt};
+
+/// Category of a message printed by Ipopt.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum JournalCategory {
+    /// Debugging output. Only printed by Ipopt builds with debugging enabled.
+    Debug,
+    /// Solve statistics printed at the end of a solve.
+    Statistics,
+    /// The main iteration log and final messages.
+    Main,
+    /// Initialization of the algorithm, e.g. the starting point.
+    Initialization,
+    /// Barrier parameter updates.
+    BarrierUpdate,
+    /// Solution of the primal-dual system.
+    SolvePdSystem,
+    /// Fraction-to-the-boundary rule.
+    FracToBound,
+    /// Linear algebra, including messages from the linear solver.
+    LinearAlgebra,
+    /// Line search.
+    LineSearch,
+    /// Quasi-Newton Hessian approximation.
+    HessianApproximation,
+    /// The final solution.
+    Solution,
+    /// Option documentation.
+    Documentation,
+    /// The NLP interface, e.g. derivative checker output.
+    Nlp,
+    /// Timing statistics.
+    TimingStatistics,
+    /// Messages from the application using Ipopt.
+    UserApplication,
+}
+
+impl JournalCategory {
+    fn to_ffi(self) -> ffi::CNLP_JournalCategory {
+        match self {
+            JournalCategory::Debug => ffi::CNLP_JournalCategory_CNLP_J_DBG,
+            JournalCategory::Statistics => ffi::CNLP_JournalCategory_CNLP_J_STATISTICS,
+            JournalCategory::Main => ffi::CNLP_JournalCategory_CNLP_J_MAIN,
+            JournalCategory::Initialization => ffi::CNLP_JournalCategory_CNLP_J_INITIALIZATION,
+            JournalCategory::BarrierUpdate => ffi::CNLP_JournalCategory_CNLP_J_BARRIER_UPDATE,
+            JournalCategory::SolvePdSystem => ffi::CNLP_JournalCategory_CNLP_J_SOLVE_PD_SYSTEM,
+            JournalCategory::FracToBound => ffi::CNLP_JournalCategory_CNLP_J_FRAC_TO_BOUND,
+            JournalCategory::LinearAlgebra => ffi::CNLP_JournalCategory_CNLP_J_LINEAR_ALGEBRA,
+            JournalCategory::LineSearch => ffi::CNLP_JournalCategory_CNLP_J_LINE_SEARCH,
+            JournalCategory::HessianApproximation => {
+                ffi::CNLP_JournalCategory_CNLP_J_HESSIAN_APPROXIMATION
+            }
+            JournalCategory::Solution => ffi::CNLP_JournalCategory_CNLP_J_SOLUTION,
+            JournalCategory::Documentation => ffi::CNLP_JournalCategory_CNLP_J_DOCUMENTATION,
+            JournalCategory::Nlp => ffi::CNLP_JournalCategory_CNLP_J_NLP,
+            JournalCategory::TimingStatistics => ffi::CNLP_JournalCategory_CNLP_J_TIMING_STATISTICS,
+            JournalCategory::UserApplication => ffi::CNLP_JournalCategory_CNLP_J_USER_APPLICATION,
+        }
+    }
+}
+
+/// Verbosity of Ipopt output, as accepted by the `print_level` and `file_print_level` options.
+///
+/// Each level includes the messages of all lower levels.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PrintLevel {
+    /// No output.
+    None = 0,
+    /// Errors only.
+    Error = 1,
+    /// Strong warnings.
+    StrongWarning = 2,
+    /// Summary of the solve.
+    Summary = 3,
+    /// Warnings.
+    Warning = 4,
+    /// One line per iteration. This is the default.
+    IterSummary = 5,
+    /// Detailed output.
+    Detailed = 6,
+    /// More detailed output.
+    MoreDetailed = 7,
+    /// Vector norms and some vectors.
+    Vector = 8,
+    /// All vectors.
+    MoreVector = 9,
+    /// Matrices.
+    Matrix = 10,
+    /// All matrices.
+    MoreMatrix = 11,
+    /// Everything.
+    All = 12,
+}
+
+impl<P: BasicProblem> Ipopt<P> {
+    /// Set the print level of console output for a single journal category.
+    ///
+    /// This overrides the `print_level` option for the given category in all subsequent solves.
+    pub fn set_category_print_level(
+        &mut self,
+        category: JournalCategory,
+        level: PrintLevel,
+    ) -> &mut Self {
+        let ok = unsafe {
+            ffi::cnlp_set_category_print_level(
+                self.nlp_internal,
+                category.to_ffi(),
+                level as ffi::CNLP_Int,
+            )
+        };
+        debug_assert!(ok != 0);
+        self
+    }
+}
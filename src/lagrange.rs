@@ -0,0 +1,197 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Weights of the Lagrangian in Hessian evaluations.
+//!
+//! Ipopt evaluates the Hessian of the Lagrangian `σ ∇²f + Σ λ_i ∇²g_i` with the objective
+//! factor `σ` and the constraint multipliers `λ` passed as separate arguments, and forgetting
+//! one of them is an easy mistake. Problems implementing
+//! [`ConstrainedProblem::lagrangian_hessian_values`](trait.ConstrainedProblem.html#method.lagrangian_hessian_values)
+//! receive both in a [`LagrangeData`](struct.LagrangeData.html) instead, which also weighs
+//! per-function values for them:
+//!
+//! ```no_run
+//! # use ipopt::*;
+//! # struct Model;
+//! # impl BasicProblem for Model {
+//! #     fn num_variables(&self) -> usize { 1 }
+//! #     fn bounds(&self, _: &mut [Number], _: &mut [Number]) -> bool { true }
+//! #     fn initial_point(&self, _: &mut [Number]) -> bool { true }
+//! #     fn objective(&self, _: &[Number], _: &mut Number) -> bool { true }
+//! #     fn objective_grad(&self, _: &[Number], _: &mut [Number]) -> bool { true }
+//! # }
+//! # impl ConstrainedProblem for Model {
+//! #     fn num_constraints(&self) -> usize { 0 }
+//! #     fn num_constraint_jacobian_non_zeros(&self) -> usize { 0 }
+//! #     fn constraint(&self, _: &[Number], _: &mut [Number]) -> bool { true }
+//! #     fn constraint_bounds(&self, _: &mut [Number], _: &mut [Number]) -> bool { true }
+//! #     fn constraint_jacobian_indices(&self, _: &mut [Index], _: &mut [Index]) -> bool { true }
+//! #     fn constraint_jacobian_values(&self, _: &[Number], _: &mut [Number]) -> bool { true }
+//! #     fn num_hessian_non_zeros(&self) -> usize { 0 }
+//! #     fn hessian_indices(&self, _: &mut [Index], _: &mut [Index]) -> bool { true }
+//! fn lagrangian_hessian_values(
+//!     &self,
+//!     x: &[Number],
+//!     lagrange: LagrangeData,
+//!     vals: &mut [Number],
+//! ) -> bool {
+//!     // Diagonal Hessian with objective entries 2 and constraint entries 6 x.
+//!     for (i, v) in vals.iter_mut().enumerate() {
+//!         *v = lagrange.weighted_sum([2.0, 6.0 * x[i]].iter().cloned());
+//!     }
+//!     true
+//! }
+//! # }
+//! ```
+
+use crate::Number;
+
+/// The objective factor and constraint multipliers weighing the Hessian of the Lagrangian.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LagrangeData<'a> {
+    /// Factor of the objective Hessian, also called `obj_factor`.
+    pub sigma: Number,
+    /// Factor of each constraint Hessian.
+    pub lambda: &'a [Number],
+}
+
+impl<'a> LagrangeData<'a> {
+    /// Weights with the given objective factor and multipliers.
+    pub fn new(sigma: Number, lambda: &'a [Number]) -> Self {
+        LagrangeData { sigma, lambda }
+    }
+
+    /// Number of constraints.
+    pub fn num_constraints(&self) -> usize {
+        self.lambda.len()
+    }
+
+    /// Returns `true` if the objective Hessian is not needed in this evaluation.
+    ///
+    /// Ipopt evaluates the Hessian of the constraints alone in some phases of the algorithm.
+    pub fn skips_objective(&self) -> bool {
+        self.sigma == 0.0
+    }
+
+    /// The constraints with a non-zero multiplier, along with their multiplier.
+    ///
+    /// Constraints with a zero multiplier do not contribute to the Hessian, so their Hessians
+    /// need not be evaluated.
+    pub fn active_constraints(&self) -> impl Iterator<Item = (usize, Number)> + 'a {
+        self.lambda
+            .iter()
+            .cloned()
+            .enumerate()
+            .filter(|&(_, l)| l != 0.0)
+    }
+
+    /// Weigh an objective value and one value per constraint.
+    ///
+    /// The first value is multiplied by `sigma` and the following values by the corresponding
+    /// entries of `lambda`. Missing constraint values count as zero.
+    pub fn weighted_sum<I: IntoIterator<Item = Number>>(&self, values: I) -> Number {
+        let mut values = values.into_iter();
+        let objective = match values.next() {
+            Some(value) => self.sigma * value,
+            None => return 0.0,
+        };
+        objective
+            + values
+                .zip(self.lambda.iter())
+                .map(|(value, l)| l * value)
+                .sum::<Number>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BasicProblem, ConstrainedProblem, Index};
+
+    #[test]
+    fn weighted_sum_test() {
+        let lambda = [0.0, 3.0, -1.0];
+        let lagrange = LagrangeData::new(2.0, &lambda);
+        assert_eq!(lagrange.num_constraints(), 3);
+        assert!(!lagrange.skips_objective());
+        assert_eq!(lagrange.weighted_sum(vec![1.0, 5.0, 2.0, 4.0]), 4.0);
+        assert_eq!(lagrange.weighted_sum(vec![1.0, 5.0]), 2.0);
+        let active: Vec<_> = lagrange.active_constraints().collect();
+        assert_eq!(active, vec![(1, 3.0), (2, -1.0)]);
+    }
+
+    /// A problem with the Hessian `sigma + lambda_0 x` in its single entry.
+    struct Weighted;
+
+    impl BasicProblem for Weighted {
+        fn num_variables(&self) -> usize {
+            1
+        }
+        fn bounds(&self, _: &mut [Number], _: &mut [Number]) -> bool {
+            true
+        }
+        fn initial_point(&self, _: &mut [Number]) -> bool {
+            true
+        }
+        fn objective(&self, _: &[Number], _: &mut Number) -> bool {
+            true
+        }
+        fn objective_grad(&self, _: &[Number], _: &mut [Number]) -> bool {
+            true
+        }
+    }
+
+    impl ConstrainedProblem for Weighted {
+        fn num_constraints(&self) -> usize {
+            1
+        }
+        fn num_constraint_jacobian_non_zeros(&self) -> usize {
+            0
+        }
+        fn constraint(&self, _: &[Number], _: &mut [Number]) -> bool {
+            true
+        }
+        fn constraint_bounds(&self, _: &mut [Number], _: &mut [Number]) -> bool {
+            true
+        }
+        fn constraint_jacobian_indices(&self, _: &mut [Index], _: &mut [Index]) -> bool {
+            true
+        }
+        fn constraint_jacobian_values(&self, _: &[Number], _: &mut [Number]) -> bool {
+            true
+        }
+        fn num_hessian_non_zeros(&self) -> usize {
+            1
+        }
+        fn hessian_indices(&self, _: &mut [Index], _: &mut [Index]) -> bool {
+            true
+        }
+        fn lagrangian_hessian_values(
+            &self,
+            x: &[Number],
+            lagrange: LagrangeData,
+            vals: &mut [Number],
+        ) -> bool {
+            vals[0] = lagrange.weighted_sum([1.0, x[0]].iter().cloned());
+            true
+        }
+    }
+
+    #[test]
+    fn hessian_values_forwarding_test() {
+        let mut vals = [0.0];
+        assert!(Weighted.hessian_values(&[3.0], 2.0, &[5.0], &mut vals));
+        assert_eq!(vals, [17.0]);
+    }
+}
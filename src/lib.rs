@@ -124,10 +124,148 @@ pub use crate::ffi::{
     CNLP_Number as Number, // f64
 };
 
+use std::convert::TryFrom;
 use std::ffi::CString;
 use std::fmt::{Debug, Display, Formatter};
 use std::slice;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "ctrlc")]
+use crate::interrupt::Interrupt;
+
+mod active_set;
+mod bench;
+mod block;
+mod bound_constrained;
+mod compressed;
+#[cfg(feature = "cutest")]
+mod cutest;
+mod dense;
+mod diagnostics;
+mod export;
+#[cfg(feature = "faer")]
+mod faer_support;
+mod golden;
+mod groups;
+mod hessian_assembly;
+mod history;
+mod homotopy;
+#[cfg(feature = "hs")]
+pub mod hs;
+mod hsl;
+mod initial_point;
+#[cfg(feature = "ctrlc")]
+mod interrupt;
+mod journal;
+mod lagrange;
+mod linear_expr;
+mod linear_solver;
+mod log_file;
+mod memory;
+#[cfg(feature = "metrics")]
+mod metrics_support;
+mod minimize;
+mod model_file;
+#[cfg(feature = "nalgebra")]
+mod nalgebra_support;
+#[cfg(feature = "ndarray")]
+mod ndarray_support;
+#[cfg(feature = "asl")]
+mod nl;
+mod objective;
+mod option_macro;
+mod option_registry;
+mod option_table;
+mod options;
+mod output_check;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod parametric;
+mod pardiso;
+mod problem_spec;
+#[cfg(feature = "indicatif")]
+mod progress;
+mod random_problem;
+mod restoration;
+mod retry;
+mod scalar;
+mod scaled;
+#[cfg(feature = "sipopt")]
+mod sensitivity;
+mod sparsity;
+mod stats;
+mod structure;
+mod threads;
+mod trace;
+mod transformed;
+mod tune;
+mod user_stop;
+mod violations;
+
+pub use crate::active_set::*;
+pub use crate::bench::*;
+pub use crate::block::*;
+pub use crate::bound_constrained::*;
+pub use crate::compressed::*;
+#[cfg(feature = "cutest")]
+pub use crate::cutest::*;
+pub use crate::dense::*;
+pub use crate::diagnostics::*;
+pub use crate::export::*;
+#[cfg(feature = "faer")]
+pub use crate::faer_support::*;
+pub use crate::golden::*;
+pub use crate::groups::*;
+pub use crate::hessian_assembly::*;
+pub use crate::history::*;
+pub use crate::homotopy::*;
+pub use crate::hsl::*;
+pub use crate::initial_point::*;
+pub use crate::journal::*;
+pub use crate::lagrange::*;
+pub use crate::linear_expr::*;
+pub use crate::linear_solver::*;
+pub use crate::log_file::*;
+pub use crate::memory::*;
+pub use crate::minimize::*;
+pub use crate::model_file::*;
+#[cfg(feature = "nalgebra")]
+pub use crate::nalgebra_support::*;
+#[cfg(feature = "ndarray")]
+pub use crate::ndarray_support::*;
+#[cfg(feature = "asl")]
+pub use crate::nl::*;
+pub use crate::objective::*;
+pub use crate::option_macro::*;
+pub use crate::option_registry::*;
+pub use crate::options::*;
+pub use crate::output_check::*;
+#[cfg(feature = "rayon")]
+pub use crate::parallel::*;
+pub use crate::parametric::*;
+pub use crate::pardiso::*;
+pub use crate::problem_spec::*;
+#[cfg(feature = "indicatif")]
+pub use crate::progress::*;
+pub use crate::random_problem::*;
+pub use crate::restoration::*;
+pub use crate::retry::*;
+pub use crate::scalar::*;
+pub use crate::scaled::*;
+#[cfg(feature = "sipopt")]
+pub use crate::sensitivity::*;
+pub use crate::sparsity::*;
+pub use crate::stats::*;
+pub use crate::structure::*;
+pub use crate::threads::*;
+pub use crate::trace::*;
+pub use crate::transformed::*;
+pub use crate::tune::*;
+pub use crate::user_stop::*;
+pub use crate::violations::*;
+
 /// The callback interface for a non-linear problem to be solved by Ipopt.
 ///
 /// This trait specifies all the information needed to construct the unconstrained optimization
@@ -139,9 +277,19 @@ use std::slice;
 /// of failure to produce values, simply return `false` where applicable.  This feature could be
 /// used to tell Ipopt to try smaller perturbations for `x` for instance. If the caller returns
 /// `true` but the output data was not set, then Ipopt may produce undefined behaviour.
+///
+/// All slices passed to the callbacks borrow Ipopt's own buffers directly: the wrapper neither
+/// allocates nor copies anything when forwarding evaluations, so the per-iteration overhead is
+/// that of the callbacks themselves. Output slices are not zeroed before an evaluation, so every
+/// entry must be written; see [`Ipopt::set_output_check`](struct.Ipopt.html#method.set_output_check)
+/// to verify this while debugging.
 pub trait BasicProblem {
     /// Specify the indexing style used for arrays in this problem.
     /// (Default is zero-based)
+    ///
+    /// This is passed to Ipopt as its `index_style`, so sparsity structures ported from Fortran
+    /// or AMPL with one-based indices can be used as they are. Use
+    /// [`check_structure`](fn.check_structure.html) to validate them.
     fn indexing_style(&self) -> IndexingStyle {
         IndexingStyle::CStyle
     }
@@ -215,6 +363,19 @@ pub trait BasicProblem {
     fn objective_scaling(&self) -> f64 {
         1.0
     }
+
+    /// Called when Ipopt switches between the regular algorithm and the restoration phase.
+    ///
+    /// Ipopt reports the algorithm mode once per iteration, so this is called from the
+    /// intermediate callback of the first iteration in the new mode, and with
+    /// `AlgorithmMode::Regular` before a solve if the previous solve ended in the restoration
+    /// phase. Evaluations until the next call belong to the given mode, so an expensive model
+    /// can store it and, for example, use looser simulation tolerances during restoration. The
+    /// current mode is also available from
+    /// [`Ipopt::algorithm_mode`](struct.Ipopt.html#method.algorithm_mode).
+    ///
+    /// By default, this function does nothing.
+    fn algorithm_mode_changed(&mut self, _mode: AlgorithmMode) {}
 }
 
 /// An extension to the [`BasicProblem`](trait.BasicProblem.html) trait that enables full Newton
@@ -319,13 +480,32 @@ pub trait ConstrainedProblem: BasicProblem {
     /// Hessian values multipled by the corresponding values in `lambda` (the Lagrange
     /// multiplier).
     /// This function is internally called by Ipopt callback `eval_h`.
+    ///
+    /// Implement either this function or `lagrangian_hessian_values`. By default, this function
+    /// forwards to `lagrangian_hessian_values`.
     fn hessian_values(
         &self,
         x: &[Number],
         obj_factor: Number,
         lambda: &[Number],
         vals: &mut [Number],
-    ) -> bool;
+    ) -> bool {
+        self.lagrangian_hessian_values(x, LagrangeData::new(obj_factor, lambda), vals)
+    }
+    /// Hessian values, with the objective factor and the Lagrange multipliers given as
+    /// [`LagrangeData`](struct.LagrangeData.html).
+    ///
+    /// This is an alternative to `hessian_values` and is only called through it. For
+    /// convenience, this function returns `false` by default, which is appropriate when the
+    /// Hessian is approximated with the "limited-memory" `hessian_approximation` option.
+    fn lagrangian_hessian_values(
+        &self,
+        _x: &[Number],
+        _lagrange: LagrangeData,
+        _vals: &mut [Number],
+    ) -> bool {
+        false
+    }
 
     /// Provide custom constraint function scaling.
     ///
@@ -341,6 +521,16 @@ pub trait ConstrainedProblem: BasicProblem {
     fn constraint_scaling(&self, _g_scaling: &mut [Number]) -> bool {
         false
     }
+
+    /// Assign constraints to named groups.
+    ///
+    /// Groups are used only for reporting: violation and active set reports are aggregated per
+    /// group. This function is not called during a solve.
+    ///
+    /// For convenience, this function returns no groups by default.
+    fn constraint_groups(&self) -> ConstraintGroups {
+        ConstraintGroups::new()
+    }
 }
 
 /// Type of option you can specify to Ipopt.
@@ -378,6 +568,7 @@ impl<'a> From<i32> for IpoptOption<'a> {
 /// The solution of the optimization problem including variables, bound multipliers and Lagrange
 /// multipliers. This struct stores immutable slices to the solution data.
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Solution<'a> {
     /// This is the solution after the solve.
     pub primal_variables: &'a [Number],
@@ -409,6 +600,46 @@ impl<'a> Solution<'a> {
     }
 }
 
+/// An owned copy of a [`Solution`](struct.Solution.html).
+///
+/// This is useful for keeping a solution around after the solver is dropped or re-solved, and
+/// can be deserialized with the `serde` feature.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OwnedSolution {
+    /// This is the solution after the solve.
+    pub primal_variables: Vec<Number>,
+    /// Lower bound multipliers.
+    pub lower_bound_multipliers: Vec<Number>,
+    /// Upper bound multipliers.
+    pub upper_bound_multipliers: Vec<Number>,
+    /// Constraint multipliers, which are available only from contrained problems.
+    pub constraint_multipliers: Vec<Number>,
+}
+
+impl OwnedSolution {
+    /// Borrow this solution as a `Solution`.
+    pub fn as_solution(&self) -> Solution {
+        Solution {
+            primal_variables: &self.primal_variables,
+            lower_bound_multipliers: &self.lower_bound_multipliers,
+            upper_bound_multipliers: &self.upper_bound_multipliers,
+            constraint_multipliers: &self.constraint_multipliers,
+        }
+    }
+}
+
+impl<'a> From<Solution<'a>> for OwnedSolution {
+    fn from(solution: Solution<'a>) -> Self {
+        OwnedSolution {
+            primal_variables: solution.primal_variables.to_vec(),
+            lower_bound_multipliers: solution.lower_bound_multipliers.to_vec(),
+            upper_bound_multipliers: solution.upper_bound_multipliers.to_vec(),
+            constraint_multipliers: solution.constraint_multipliers.to_vec(),
+        }
+    }
+}
+
 /// An interface to mutably access the input problem
 /// which Ipopt owns. This method also returns the solver paramters as immutable.
 #[derive(Debug, PartialEq)]
@@ -430,6 +661,7 @@ pub struct SolverData<'a, P: 'a> {
 
 /// Enum that indicates in which mode the algorithm is at some point in time.
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AlgorithmMode {
     /// Ipopt is in regular mode.
     Regular,
@@ -440,6 +672,7 @@ pub enum AlgorithmMode {
 /// Pieces of solver data available from Ipopt after each iteration inside the intermediate
 /// callback.
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct IntermediateCallbackData {
     /// Algorithm mode indicates which mode the algorithm is currently in.
     pub alg_mod: AlgorithmMode,
@@ -509,6 +742,9 @@ pub struct SolveResult<'a, P: 'a> {
     pub objective_value: Number,
     /// Solve status. This enum reports the status of the last solve.
     pub status: SolveStatus,
+    /// Why the solve was stopped, if the status is
+    /// [`UserRequestedStop`](enum.SolveStatus.html#variant.UserRequestedStop).
+    pub user_stop: Option<UserStop>,
 }
 
 /// Type defining the callback function for giving intermediate execution control to
@@ -531,22 +767,58 @@ pub struct Ipopt<P: BasicProblem> {
     nlp_interface: P,
     /// Intermediate callback.
     intermediate_callback: Option<IntermediateCallback<P>>,
+    /// Callback stopping the solve with a reason.
+    stop_callback: Option<StopCallback<P>>,
+    /// Reason and iteration of a user stop during the last solve.
+    stop: Option<(StopReason, Index)>,
     /// Number of primal variables.
     num_primal_variables: usize,
     /// Number of dual variables.
     num_dual_variables: usize,
+    /// Log file configuration, needed to open a new log file for each solve.
+    log_file: Option<LogFile>,
+    /// Linear solver workspace limit relative to the estimated size.
+    workspace_limit: Option<Number>,
+    /// Whether the objective is minimized or maximized.
+    objective_sense: ObjectiveSense,
+    /// Whether callback outputs are verified to be fully written.
+    check_outputs: bool,
+    /// The first unwritten output found by the output check.
+    unwritten_output: Option<UnwrittenOutput>,
+    /// Time spent in callbacks during the last solve, if callback timing is enabled.
+    callback_times: Option<CallbackTimes>,
+    /// Iterations of the last solve, if history recording is enabled.
+    history: Option<History>,
+    /// Algorithm mode reported in the last intermediate callback.
+    algorithm_mode: AlgorithmMode,
+    /// Live progress display, if enabled.
+    #[cfg(feature = "indicatif")]
+    progress: Option<Progress>,
+    /// Ctrl-C interrupt state, if enabled.
+    #[cfg(feature = "ctrlc")]
+    interrupt: Option<Interrupt>,
 }
 
 /// Implement debug for Ipopt.
 impl<P: BasicProblem + Debug> Debug for Ipopt<P> {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         write!(f,
-               "Ipopt {{ nlp_internal: {:?}, nlp_interface: {:?}, intermediate_callback: {:?}, num_primal_variables: {:?}, num_dual_variables: {:?} }}",
+               "Ipopt {{ nlp_internal: {:?}, nlp_interface: {:?}, intermediate_callback: {:?}, stop_callback: {:?}, stop: {:?}, num_primal_variables: {:?}, num_dual_variables: {:?}, log_file: {:?}, workspace_limit: {:?}, objective_sense: {:?}, check_outputs: {:?}, unwritten_output: {:?}, callback_times: {:?}, history: {:?}, algorithm_mode: {:?} }}",
                self.nlp_internal,
                self.nlp_interface,
                if self.intermediate_callback.is_some() { "Some" } else { "None" },
+               if self.stop_callback.is_some() { "Some" } else { "None" },
+               self.stop,
                self.num_primal_variables,
-               self.num_dual_variables)
+               self.num_dual_variables,
+               self.log_file,
+               self.workspace_limit,
+               self.objective_sense,
+               self.check_outputs,
+               self.unwritten_output,
+               self.callback_times,
+               self.history,
+               self.algorithm_mode)
     }
 }
 
@@ -570,9 +842,23 @@ impl<P: BasicProblem> Ipopt<P> {
             nlp_internal,
             nlp_interface: nlp,
             intermediate_callback: None,
+            stop_callback: None,
+            stop: None,
             // These two will be updated every time sizes callback is called.
             num_primal_variables: num_vars,
             num_dual_variables: num_constraints,
+            log_file: None,
+            workspace_limit: None,
+            objective_sense: ObjectiveSense::Minimize,
+            check_outputs: false,
+            unwritten_output: None,
+            callback_times: None,
+            history: None,
+            algorithm_mode: AlgorithmMode::Regular,
+            #[cfg(feature = "indicatif")]
+            progress: None,
+            #[cfg(feature = "ctrlc")]
+            interrupt: None,
         };
 
         // Initialize solution arrays so we can safely call solver_data and solver_data_mut without
@@ -584,6 +870,8 @@ impl<P: BasicProblem> Ipopt<P> {
             );
         }
 
+        ipopt.update_intermediate_callback();
+
         ipopt
     }
 
@@ -596,6 +884,8 @@ impl<P: BasicProblem> Ipopt<P> {
             return Err(CreateError::NoOptimizationVariablesSpecified);
         }
 
+        checked_size(num_vars, ProblemSize::Variables)?;
+
         let mut nlp_internal: ffi::CNLP_ProblemPtr = ::std::ptr::null_mut();
 
         let create_error = CreateProblemStatus::new(unsafe {
@@ -673,9 +963,28 @@ impl<P: BasicProblem> Ipopt<P> {
         P: BasicProblem,
     {
         self.intermediate_callback = mb_cb;
+        self.update_intermediate_callback();
+    }
+
+    /// Install Ipopt's intermediate callback only if something needs to run in every iteration.
+    pub(crate) fn update_intermediate_callback(&mut self) {
+        // Metrics are updated in every iteration, so keep the callback installed for them.
+        #[allow(unused_mut)]
+        let mut needed = self.intermediate_callback.is_some()
+            || self.stop_callback.is_some()
+            || self.history.is_some()
+            || cfg!(feature = "metrics");
+        #[cfg(feature = "indicatif")]
+        {
+            needed |= self.progress.is_some();
+        }
+        #[cfg(feature = "ctrlc")]
+        {
+            needed |= self.interrupt.is_some();
+        }
 
         unsafe {
-            if mb_cb.is_some() {
+            if needed {
                 ffi::cnlp_set_intermediate_callback(self.nlp_internal, Some(Self::intermediate_cb));
             } else {
                 ffi::cnlp_set_intermediate_callback(self.nlp_internal, None);
@@ -683,29 +992,138 @@ impl<P: BasicProblem> Ipopt<P> {
         }
     }
 
+    /// Set whether the objective is minimized or maximized.
+    pub fn set_objective_sense(&mut self, sense: ObjectiveSense) -> &mut Self {
+        self.objective_sense = sense;
+        self
+    }
+
+    /// Whether the objective is minimized or maximized.
+    pub fn objective_sense(&self) -> ObjectiveSense {
+        self.objective_sense
+    }
+
+    /// The algorithm mode reported by Ipopt in the last intermediate callback.
+    ///
+    /// This is `AlgorithmMode::Regular` before the first iteration of a solve.
+    pub fn algorithm_mode(&self) -> AlgorithmMode {
+        self.algorithm_mode
+    }
+
+    /// Record the algorithm mode and notify the problem if it changed.
+    fn update_algorithm_mode(&mut self, mode: AlgorithmMode) {
+        if self.algorithm_mode != mode {
+            self.algorithm_mode = mode;
+            self.nlp_interface.algorithm_mode_changed(mode);
+        }
+    }
+
     /// Solve non-linear problem.
     /// Return the solve status and the final value of the objective function.
     pub fn solve(&mut self) -> SolveResult<P> {
+        self.prepare_solve();
         let res = {
             let udata_ptr = self as *mut Ipopt<P>;
             unsafe { ffi::cnlp_solve(self.nlp_internal, udata_ptr as ffi::CNLP_UserDataPtr) }
         };
 
+        self.solve_result(res)
+    }
+
+    /// Solve a problem with the same structure as in the previous solve.
+    ///
+    /// This is Ipopt's `warm_start_same_structure` path: the internal data structures built by
+    /// the previous solve, including the symbolic factorization of the KKT system computed by
+    /// the linear solver, are reused instead of being set up from scratch. Only numeric data such
+    /// as bounds, function values and derivative values may change between solves. The number of
+    /// variables, the number of constraints and the sparsity structure of the constraint Jacobian
+    /// and Hessian must stay the same, otherwise Ipopt aborts the solve.
+    ///
+    /// The savings depend on how much of a solve is spent in setup. For large sparse problems
+    /// that converge in a few iterations, as is common when a sequence of closely related
+    /// problems is solved, skipping the symbolic analysis can be a significant fraction of the
+    /// total time. Run `cargo run --release --example reoptimize` to measure the speedup on a
+    /// chain problem.
+    ///
+    /// This does not change the starting point. Combine it with the `warm_start_init_point`
+    /// option to also start from the previous solution.
+    ///
+    /// If the problem has not been solved yet, this is equivalent to
+    /// [`solve`](struct.Ipopt.html#method.solve).
+    pub fn reoptimize(&mut self) -> SolveResult<P> {
+        self.prepare_solve();
+        let res = {
+            let udata_ptr = self as *mut Ipopt<P>;
+            unsafe { ffi::cnlp_reoptimize(self.nlp_internal, udata_ptr as ffi::CNLP_UserDataPtr) }
+        };
+
+        self.solve_result(res)
+    }
+
+    /// Reset per-solve state before a solve starts.
+    fn prepare_solve(&mut self) {
+        self.open_solve_log_file();
+        self.unwritten_output = None;
+        self.stop = None;
+        if let Some(times) = self.callback_times.as_mut() {
+            *times = CallbackTimes::default();
+        }
+        self.clear_history();
+        self.update_algorithm_mode(AlgorithmMode::Regular);
+        #[cfg(feature = "indicatif")]
+        {
+            if let Some(progress) = self.progress.as_mut() {
+                progress.start();
+            }
+        }
+        #[cfg(feature = "ctrlc")]
+        {
+            if let Some(interrupt) = self.interrupt.as_mut() {
+                interrupt.start();
+            }
+        }
+    }
+
+    /// Report the end of a solve.
+    #[allow(unused_variables)]
+    fn finish_solve(&mut self, status: SolveStatus) {
+        #[cfg(feature = "metrics")]
+        self.record_solve_metrics(status);
+        #[cfg(feature = "indicatif")]
+        {
+            if let Some(progress) = self.progress.as_mut() {
+                progress.finish(status);
+            }
+        }
+        #[cfg(feature = "ctrlc")]
+        {
+            if let Some(interrupt) = self.interrupt.as_mut() {
+                interrupt.finish();
+            }
+        }
+    }
+
+    /// Wrap the raw result of a solve.
+    fn solve_result(&mut self, res: ffi::CNLP_SolveResult) -> SolveResult<P> {
+        let status = SolveStatus::new(res.status);
+        self.finish_solve(status);
+        let solution =
+            Solution::from_raw(res.data, self.num_primal_variables, self.num_dual_variables);
+        let user_stop = self.user_stop(status, solution.primal_variables);
+
         let Ipopt {
             nlp_interface: ref mut problem,
-            num_primal_variables,
             num_dual_variables,
+            objective_sense,
             ..
         } = *self;
 
         SolveResult {
-            solver_data: SolverDataMut {
-                problem,
-                solution: Solution::from_raw(res.data, num_primal_variables, num_dual_variables),
-            },
+            solver_data: SolverDataMut { problem, solution },
             constraint_values: unsafe { slice::from_raw_parts(res.g, num_dual_variables) },
-            objective_value: res.obj_val,
-            status: SolveStatus::new(res.status),
+            objective_value: res.obj_val * objective_sense.sign(),
+            status,
+            user_stop,
         }
     }
 
@@ -804,8 +1222,12 @@ impl<P: BasicProblem> Ipopt<P> {
         ipopt.num_primal_variables = ipopt.nlp_interface.num_variables();
         ipopt.num_dual_variables = 0; // No constraints
 
-        *n = ipopt.num_primal_variables as Index;
-        *m = ipopt.num_dual_variables as Index;
+        // The problem size may have changed since creation, so check it again.
+        *n = match Index::try_from(ipopt.num_primal_variables) {
+            Ok(n) => n,
+            Err(_) => return false as Bool,
+        };
+        *m = 0;
 
         *nnz_jac_g = 0; // No constraints
         *nnz_h_lag = 0; // No Hessian
@@ -840,8 +1262,14 @@ impl<P: BasicProblem> Ipopt<P> {
         obj_value: *mut Number,
         user_data: ffi::CNLP_UserDataPtr,
     ) -> Bool {
-        let nlp = &mut (*(user_data as *mut Ipopt<P>)).nlp_interface;
-        nlp.objective(slice::from_raw_parts(x, n as usize), &mut *obj_value) as Bool
+        let ip = &mut (*(user_data as *mut Ipopt<P>));
+        ip.timed(Callback::Objective, |ip| {
+            let result = ip
+                .nlp_interface
+                .objective(slice::from_raw_parts(x, n as usize), &mut *obj_value);
+            *obj_value *= ip.objective_sense.sign();
+            result as Bool
+        })
     }
 
     /// Evaluate the objective gradient.
@@ -852,11 +1280,18 @@ impl<P: BasicProblem> Ipopt<P> {
         grad_f: *mut Number,
         user_data: ffi::CNLP_UserDataPtr,
     ) -> Bool {
-        let nlp = &mut (*(user_data as *mut Ipopt<P>)).nlp_interface;
-        nlp.objective_grad(
-            slice::from_raw_parts(x, n as usize),
-            slice::from_raw_parts_mut(grad_f, n as usize),
-        ) as Bool
+        let ip = &mut (*(user_data as *mut Ipopt<P>));
+        ip.timed(Callback::ObjectiveGradient, |ip| {
+            let x = slice::from_raw_parts(x, n as usize);
+            let grad_f = slice::from_raw_parts_mut(grad_f, n as usize);
+            let result = ip.eval_output(Evaluation::ObjectiveGradient, grad_f, |nlp, grad_f| {
+                nlp.objective_grad(x, grad_f)
+            });
+            if ip.objective_sense == ObjectiveSense::Maximize {
+                grad_f.iter_mut().for_each(|g| *g = -*g);
+            }
+            result as Bool
+        })
     }
 
     /// Placeholder constraint function with no constraints.
@@ -947,29 +1382,42 @@ impl<P: BasicProblem> Ipopt<P> {
         user_data: ffi::CNLP_UserDataPtr,
     ) -> Bool {
         let ip = &mut (*(user_data as *mut Ipopt<P>));
-        if let Some(callback) = ip.intermediate_callback {
-            (callback)(
-                &mut ip.nlp_interface,
-                IntermediateCallbackData {
-                    alg_mod: match alg_mod {
-                        0 => AlgorithmMode::Regular,
-                        _ => AlgorithmMode::RestorationPhase,
-                    },
-                    iter_count,
-                    obj_value,
-                    inf_pr,
-                    inf_du,
-                    mu,
-                    d_norm,
-                    regularization_size,
-                    alpha_du,
-                    alpha_pr,
-                    ls_trials,
-                },
-            ) as Bool
-        } else {
-            true as Bool
+        let data = IntermediateCallbackData {
+            alg_mod: match alg_mod {
+                0 => AlgorithmMode::Regular,
+                _ => AlgorithmMode::RestorationPhase,
+            },
+            iter_count,
+            obj_value: obj_value * ip.objective_sense.sign(),
+            inf_pr,
+            inf_du,
+            mu,
+            d_norm,
+            regularization_size,
+            alpha_du,
+            alpha_pr,
+            ls_trials,
+        };
+        ip.update_algorithm_mode(data.alg_mod);
+        ip.record_history(&data);
+        #[cfg(feature = "metrics")]
+        crate::metrics_support::record_iteration(&data);
+        #[cfg(feature = "indicatif")]
+        {
+            if let Some(progress) = ip.progress.as_mut() {
+                progress.update(&data);
+            }
         }
+        #[cfg(feature = "ctrlc")]
+        {
+            if matches!(ip.interrupt, Some(ref interrupt) if interrupt.interrupted()) {
+                ip.stop = Some((StopReason::Interrupted, iter_count));
+                return false as Bool;
+            }
+        }
+        ip.timed(Callback::Intermediate, |ip| {
+            ip.run_intermediate_callbacks(data) as Bool
+        })
     }
 }
 
@@ -983,6 +1431,9 @@ impl<P: NewtonProblem> Ipopt<P> {
             return Err(CreateError::NoOptimizationVariablesSpecified);
         }
 
+        checked_size(num_vars, ProblemSize::Variables)?;
+        checked_size(nlp.num_hessian_non_zeros(), ProblemSize::HessianNonZeros)?;
+
         let mut nlp_internal: ffi::CNLP_ProblemPtr = ::std::ptr::null_mut();
 
         let create_error = CreateProblemStatus::new(unsafe {
@@ -1022,9 +1473,14 @@ impl<P: NewtonProblem> Ipopt<P> {
         nnz_h_lag: *mut Index,
         user_data: ffi::CNLP_UserDataPtr,
     ) -> Bool {
-        Self::basic_sizes(n, m, nnz_jac_g, nnz_h_lag, user_data);
+        if Self::basic_sizes(n, m, nnz_jac_g, nnz_h_lag, user_data) == 0 {
+            return false as Bool;
+        }
         let nlp = &mut (*(user_data as *mut Ipopt<P>)).nlp_interface;
-        *nnz_h_lag = nlp.num_hessian_non_zeros() as Index;
+        *nnz_h_lag = match Index::try_from(nlp.num_hessian_non_zeros()) {
+            Ok(nnz) => nnz,
+            Err(_) => return false as Bool,
+        };
         true as Bool
     }
 
@@ -1043,27 +1499,28 @@ impl<P: NewtonProblem> Ipopt<P> {
         values: *mut Number,
         user_data: ffi::CNLP_UserDataPtr,
     ) -> Bool {
-        let nlp = &mut (*(user_data as *mut Ipopt<P>)).nlp_interface;
-        if values.is_null() {
-            /* return the structure. */
-            nlp.hessian_indices(
-                slice::from_raw_parts_mut(irow, nele_hess as usize),
-                slice::from_raw_parts_mut(jcol, nele_hess as usize),
-            ) as Bool
-        } else {
-            /* return the values. */
-            let result = nlp.hessian_values(
-                slice::from_raw_parts(x, n as usize),
-                slice::from_raw_parts_mut(values, nele_hess as usize),
-            ) as Bool;
-            // This problem has no constraints so we can multiply each entry by the
-            // objective factor.
-            let start_idx = nlp.indexing_style() as isize;
-            for i in start_idx..nele_hess as isize {
-                *values.offset(i) *= obj_factor;
+        let ip = &mut (*(user_data as *mut Ipopt<P>));
+        ip.timed(Callback::Hessian, |ip| {
+            let obj_factor = obj_factor * ip.objective_sense.sign();
+            if values.is_null() {
+                /* return the structure. */
+                ip.nlp_interface.hessian_indices(
+                    slice::from_raw_parts_mut(irow, nele_hess as usize),
+                    slice::from_raw_parts_mut(jcol, nele_hess as usize),
+                ) as Bool
+            } else {
+                /* return the values. */
+                let x = slice::from_raw_parts(x, n as usize);
+                let values = slice::from_raw_parts_mut(values, nele_hess as usize);
+                let result = ip.eval_output(Evaluation::Hessian, values, |nlp, values| {
+                    nlp.hessian_values(x, values)
+                });
+                // This problem has no constraints so we can multiply each entry by the
+                // objective factor.
+                values.iter_mut().for_each(|v| *v *= obj_factor);
+                result as Bool
             }
-            result
-        }
+        })
     }
 }
 
@@ -1092,6 +1549,12 @@ impl<P: ConstrainedProblem> Ipopt<P> {
             });
         }
 
+        // Sizes are passed to Ipopt as `Index`, so make sure they don't get truncated.
+        checked_size(num_vars, ProblemSize::Variables)?;
+        checked_size(num_constraints, ProblemSize::Constraints)?;
+        checked_size(num_constraint_jac_nnz, ProblemSize::JacobianNonZeros)?;
+        checked_size(nlp.num_hessian_non_zeros(), ProblemSize::HessianNonZeros)?;
+
         let mut nlp_internal: ffi::CNLP_ProblemPtr = ::std::ptr::null_mut();
 
         let create_error = CreateProblemStatus::new(unsafe {
@@ -1176,12 +1639,23 @@ impl<P: ConstrainedProblem> Ipopt<P> {
         ipopt.num_primal_variables = ipopt.nlp_interface.num_variables();
         ipopt.num_dual_variables = ipopt.nlp_interface.num_constraints();
 
-        *n = ipopt.num_primal_variables as Index;
-        *m = ipopt.num_dual_variables as Index;
-
-        *nnz_jac_g = ipopt.nlp_interface.num_constraint_jacobian_non_zeros() as Index;
-        *nnz_h_lag = ipopt.nlp_interface.num_hessian_non_zeros() as Index;
-        true as Bool
+        // The problem size may have changed since creation, so check it again.
+        let sizes = (
+            Index::try_from(ipopt.num_primal_variables),
+            Index::try_from(ipopt.num_dual_variables),
+            Index::try_from(ipopt.nlp_interface.num_constraint_jacobian_non_zeros()),
+            Index::try_from(ipopt.nlp_interface.num_hessian_non_zeros()),
+        );
+        match sizes {
+            (Ok(num_vars), Ok(num_constraints), Ok(jac_nnz), Ok(hess_nnz)) => {
+                *n = num_vars;
+                *m = num_constraints;
+                *nnz_jac_g = jac_nnz;
+                *nnz_h_lag = hess_nnz;
+                true as Bool
+            }
+            _ => false as Bool,
+        }
     }
 
     /// Specify lower and upper bounds for variables and the constraint function.
@@ -1213,11 +1687,12 @@ impl<P: ConstrainedProblem> Ipopt<P> {
         g: *mut Number,
         user_data: ffi::CNLP_UserDataPtr,
     ) -> Bool {
-        let nlp = &mut (*(user_data as *mut Ipopt<P>)).nlp_interface;
-        nlp.constraint(
-            slice::from_raw_parts(x, n as usize),
-            slice::from_raw_parts_mut(g, m as usize),
-        ) as Bool
+        let ip = &mut (*(user_data as *mut Ipopt<P>));
+        ip.timed(Callback::Constraint, |ip| {
+            let x = slice::from_raw_parts(x, n as usize);
+            let g = slice::from_raw_parts_mut(g, m as usize);
+            ip.eval_output(Evaluation::Constraint, g, |nlp, g| nlp.constraint(x, g)) as Bool
+        })
     }
 
     /// Evaluate the constraint Jacobian.
@@ -1232,20 +1707,23 @@ impl<P: ConstrainedProblem> Ipopt<P> {
         values: *mut Number,
         user_data: ffi::CNLP_UserDataPtr,
     ) -> Bool {
-        let nlp = &mut (*(user_data as *mut Ipopt<P>)).nlp_interface;
-        if values.is_null() {
-            /* return the structure of the Jacobian */
-            nlp.constraint_jacobian_indices(
-                slice::from_raw_parts_mut(irow, nele_jac as usize),
-                slice::from_raw_parts_mut(jcol, nele_jac as usize),
-            ) as Bool
-        } else {
-            /* return the values of the Jacobian of the constraints */
-            nlp.constraint_jacobian_values(
-                slice::from_raw_parts(x, n as usize),
-                slice::from_raw_parts_mut(values, nele_jac as usize),
-            ) as Bool
-        }
+        let ip = &mut (*(user_data as *mut Ipopt<P>));
+        ip.timed(Callback::ConstraintJacobian, |ip| {
+            if values.is_null() {
+                /* return the structure of the Jacobian */
+                ip.nlp_interface.constraint_jacobian_indices(
+                    slice::from_raw_parts_mut(irow, nele_jac as usize),
+                    slice::from_raw_parts_mut(jcol, nele_jac as usize),
+                ) as Bool
+            } else {
+                /* return the values of the Jacobian of the constraints */
+                let x = slice::from_raw_parts(x, n as usize);
+                let values = slice::from_raw_parts_mut(values, nele_jac as usize);
+                ip.eval_output(Evaluation::ConstraintJacobian, values, |nlp, values| {
+                    nlp.constraint_jacobian_values(x, values)
+                }) as Bool
+            }
+        })
     }
 
     /// Evaluate the Hessian matrix.
@@ -1265,22 +1743,25 @@ impl<P: ConstrainedProblem> Ipopt<P> {
         values: *mut Number,
         user_data: ffi::CNLP_UserDataPtr,
     ) -> Bool {
-        let nlp = &mut (*(user_data as *mut Ipopt<P>)).nlp_interface;
-        if values.is_null() {
-            /* return the structure. */
-            nlp.hessian_indices(
-                slice::from_raw_parts_mut(irow, nele_hess as usize),
-                slice::from_raw_parts_mut(jcol, nele_hess as usize),
-            ) as Bool
-        } else {
-            /* return the values. */
-            nlp.hessian_values(
-                slice::from_raw_parts(x, n as usize),
-                obj_factor,
-                slice::from_raw_parts(lambda, m as usize),
-                slice::from_raw_parts_mut(values, nele_hess as usize),
-            ) as Bool
-        }
+        let ip = &mut (*(user_data as *mut Ipopt<P>));
+        ip.timed(Callback::Hessian, |ip| {
+            let obj_factor = obj_factor * ip.objective_sense.sign();
+            if values.is_null() {
+                /* return the structure. */
+                ip.nlp_interface.hessian_indices(
+                    slice::from_raw_parts_mut(irow, nele_hess as usize),
+                    slice::from_raw_parts_mut(jcol, nele_hess as usize),
+                ) as Bool
+            } else {
+                /* return the values. */
+                let x = slice::from_raw_parts(x, n as usize);
+                let lambda = slice::from_raw_parts(lambda, m as usize);
+                let values = slice::from_raw_parts_mut(values, nele_hess as usize);
+                ip.eval_output(Evaluation::Hessian, values, |nlp, values| {
+                    nlp.hessian_values(x, obj_factor, lambda, values)
+                }) as Bool
+            }
+        })
     }
 
     /// Specify custom scaling parameters.
@@ -1317,6 +1798,7 @@ impl<P: BasicProblem> Drop for Ipopt<P> {
 
 /// Zero-based indexing (C Style) or one-based indexing (Fortran style).
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum IndexingStyle {
     /// C-style array indexing starting from 0.
     CStyle = 0,
@@ -1324,8 +1806,40 @@ pub enum IndexingStyle {
     FortranStyle = 1,
 }
 
+impl IndexingStyle {
+    /// The index of the first entry of an array, which is `0` or `1`.
+    pub fn offset(self) -> Index {
+        self as Index
+    }
+}
+
+/// Whether the objective is minimized or maximized.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ObjectiveSense {
+    /// Minimize the objective. This is the default.
+    Minimize,
+    /// Maximize the objective.
+    ///
+    /// Ipopt still minimizes internally. The objective, its gradient and the objective term
+    /// of the Hessian are negated on the way to Ipopt, and reported objective values are negated
+    /// back. Multipliers are reported for the equivalent minimization of the negated objective.
+    Maximize,
+}
+
+impl ObjectiveSense {
+    /// Factor applied to the objective to turn the problem into a minimization.
+    fn sign(self) -> Number {
+        match self {
+            ObjectiveSense::Minimize => 1.0,
+            ObjectiveSense::Maximize => -1.0,
+        }
+    }
+}
+
 /// Program return status.
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum SolveStatus {
     /// Console Message: `EXIT: Optimal Solution Found.`
     ///
@@ -1622,6 +2136,7 @@ impl SolveStatus {
 /// inconsistencies with the input before even calling `CreateIpoptProblem` internally adding
 /// safety to this wrapper.
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum CreateError {
     /// No optimization variables were provided.
     NoOptimizationVariablesSpecified,
@@ -1633,10 +2148,53 @@ pub enum CreateError {
         /// Number of constraint Jacobian entries specified for the problem.
         num_constraint_jac_nnz: usize,
     },
+    /// A problem size does not fit into the integer type used by the linked Ipopt library.
+    SizeOverflow {
+        /// The quantity that overflowed.
+        size: ProblemSize,
+        /// The size requested by the problem.
+        value: usize,
+        /// The largest size supported by the linked Ipopt library.
+        max: usize,
+    },
     /// Unexpected error occurred: None of the above. This is likely an internal bug.
     Unknown,
 }
 
+/// A size of an optimization problem passed to Ipopt.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ProblemSize {
+    /// Number of optimization variables.
+    Variables,
+    /// Number of constraints.
+    Constraints,
+    /// Number of non-zeros in the constraint Jacobian.
+    JacobianNonZeros,
+    /// Number of non-zeros in the Hessian of the Lagrangian.
+    HessianNonZeros,
+}
+
+impl Display for ProblemSize {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match *self {
+            ProblemSize::Variables => write!(f, "number of variables"),
+            ProblemSize::Constraints => write!(f, "number of constraints"),
+            ProblemSize::JacobianNonZeros => write!(f, "number of constraint Jacobian non-zeros"),
+            ProblemSize::HessianNonZeros => write!(f, "number of Hessian non-zeros"),
+        }
+    }
+}
+
+/// Convert a problem size into an Ipopt `Index`, failing instead of truncating if it doesn't fit.
+fn checked_size(value: usize, size: ProblemSize) -> Result<Index, CreateError> {
+    Index::try_from(value).map_err(|_| CreateError::SizeOverflow {
+        size,
+        value,
+        max: Index::max_value() as usize,
+    })
+}
+
 impl Display for CreateError {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         match *self {
@@ -1652,6 +2210,11 @@ impl Display for CreateError {
                  number of constraints ({}).",
                 num_constraint_jac_nnz, num_constraints
             ),
+            CreateError::SizeOverflow { size, value, max } => write!(
+                f,
+                "The {} ({}) exceeds the largest size supported by Ipopt ({}).",
+                size, value, max
+            ),
             CreateError::Unknown => write!(
                 f,
                 "Unexpected error occurred. This is likely an internal bug."
@@ -1770,6 +2333,19 @@ mod tests {
             Ipopt::new_unconstrained(nlp4).unwrap_err(),
             CreateError::NoOptimizationVariablesSpecified
         );
+        // Too many variables for Ipopt
+        let nlp5 = NlpUnconstrained {
+            num_vars: Index::max_value() as usize + 1,
+            ..nlp.clone()
+        };
+        assert_eq!(
+            Ipopt::new_unconstrained(nlp5).unwrap_err(),
+            CreateError::SizeOverflow {
+                size: ProblemSize::Variables,
+                value: Index::max_value() as usize + 1,
+                max: Index::max_value() as usize,
+            }
+        );
     }
 
     #[derive(Debug, Clone)]
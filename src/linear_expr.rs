@@ -0,0 +1,484 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Declaring linear constraints as expressions.
+//!
+//! Linear constraints have a constant Jacobian, yet writing their triplets by hand alongside the
+//! nonlinear constraints is tedious and easy to get wrong. A
+//! [`LinearExpr`](struct.LinearExpr.html) is a sum of variables with coefficients and a
+//! constant, built with the usual arithmetic operators, and bounding it gives a
+//! [`LinearConstraint`](struct.LinearConstraint.html):
+//!
+//! ```no_run
+//! # use ipopt::{Ipopt, LinearExpr, RandomProblem, WithLinearConstraints};
+//! # let problem = RandomProblem::new(3, 1, 0);
+//! let x = |i| LinearExpr::var(i);
+//! let budget = (x(0) + 2.0 * x(1) - x(2)).at_most(10.0);
+//! let balance = (x(0) - x(1)).equal_to(0.0);
+//! let ipopt = Ipopt::new(WithLinearConstraints::new(problem, vec![budget, balance]));
+//! ```
+//!
+//! [`WithLinearConstraints`](struct.WithLinearConstraints.html) appends the linear constraints
+//! after the constraints of the wrapped problem and generates their Jacobian rows, so the
+//! wrapped problem only describes its nonlinear constraints. Linear constraints do not
+//! contribute to the Hessian of the Lagrangian.
+
+use crate::{
+    AlgorithmMode, BasicProblem, ConstrainedProblem, ConstraintGroups, Index, IndexingStyle, Number,
+};
+
+use std::ops::{Add, AddAssign, Mul, Neg, Sub};
+
+/// A linear expression `a·x + c` in the variables of a problem.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LinearExpr {
+    terms: Vec<(usize, Number)>,
+    constant: Number,
+}
+
+impl LinearExpr {
+    /// The zero expression.
+    pub fn new() -> Self {
+        LinearExpr::default()
+    }
+
+    /// The variable with the given index.
+    pub fn var(index: usize) -> Self {
+        LinearExpr::term(index, 1.0)
+    }
+
+    /// The variable with the given index multiplied by a coefficient.
+    pub fn term(index: usize, coefficient: Number) -> Self {
+        LinearExpr {
+            terms: vec![(index, coefficient)],
+            constant: 0.0,
+        }
+    }
+
+    /// A constant expression.
+    pub fn constant(value: Number) -> Self {
+        LinearExpr {
+            terms: Vec::new(),
+            constant: value,
+        }
+    }
+
+    /// The constant part of the expression.
+    pub fn constant_part(&self) -> Number {
+        self.constant
+    }
+
+    /// The coefficient of each variable in the expression, sorted by variable with repeated
+    /// variables merged.
+    pub fn coefficients(&self) -> Vec<(usize, Number)> {
+        let mut terms = self.terms.clone();
+        terms.sort_by_key(|&(i, _)| i);
+        let mut merged: Vec<(usize, Number)> = Vec::with_capacity(terms.len());
+        for (i, a) in terms {
+            match merged.last_mut() {
+                Some((last, sum)) if *last == i => *sum += a,
+                _ => merged.push((i, a)),
+            }
+        }
+        merged
+    }
+
+    /// Evaluate the expression at `x`.
+    pub fn value(&self, x: &[Number]) -> Number {
+        self.terms
+            .iter()
+            .fold(self.constant, |acc, &(i, a)| acc + a * x[i])
+    }
+
+    /// Constrain the expression to `[lower, upper]`.
+    ///
+    /// Infinite bounds or bounds beyond Ipopt's `nlp_lower_bound_inf` and `nlp_upper_bound_inf`
+    /// leave that side unbounded.
+    pub fn between(self, lower: Number, upper: Number) -> LinearConstraint {
+        LinearConstraint {
+            coefficients: self.coefficients(),
+            lower: lower - self.constant,
+            upper: upper - self.constant,
+        }
+    }
+
+    /// Constrain the expression to be at most `upper`.
+    pub fn at_most(self, upper: Number) -> LinearConstraint {
+        self.between(Number::NEG_INFINITY, upper)
+    }
+
+    /// Constrain the expression to be at least `lower`.
+    pub fn at_least(self, lower: Number) -> LinearConstraint {
+        self.between(lower, Number::INFINITY)
+    }
+
+    /// Constrain the expression to equal `value`.
+    pub fn equal_to(self, value: Number) -> LinearConstraint {
+        self.between(value, value)
+    }
+}
+
+impl Add for LinearExpr {
+    type Output = LinearExpr;
+    fn add(mut self, rhs: LinearExpr) -> LinearExpr {
+        self += rhs;
+        self
+    }
+}
+
+impl Add<Number> for LinearExpr {
+    type Output = LinearExpr;
+    fn add(mut self, rhs: Number) -> LinearExpr {
+        self.constant += rhs;
+        self
+    }
+}
+
+impl AddAssign for LinearExpr {
+    fn add_assign(&mut self, rhs: LinearExpr) {
+        self.terms.extend(rhs.terms);
+        self.constant += rhs.constant;
+    }
+}
+
+impl Sub for LinearExpr {
+    type Output = LinearExpr;
+    fn sub(self, rhs: LinearExpr) -> LinearExpr {
+        self + -rhs
+    }
+}
+
+impl Sub<Number> for LinearExpr {
+    type Output = LinearExpr;
+    fn sub(self, rhs: Number) -> LinearExpr {
+        self + -rhs
+    }
+}
+
+impl Neg for LinearExpr {
+    type Output = LinearExpr;
+    fn neg(self) -> LinearExpr {
+        self * -1.0
+    }
+}
+
+impl Mul<Number> for LinearExpr {
+    type Output = LinearExpr;
+    fn mul(mut self, rhs: Number) -> LinearExpr {
+        for (_, a) in self.terms.iter_mut() {
+            *a *= rhs;
+        }
+        self.constant *= rhs;
+        self
+    }
+}
+
+impl Mul<LinearExpr> for Number {
+    type Output = LinearExpr;
+    fn mul(self, rhs: LinearExpr) -> LinearExpr {
+        rhs * self
+    }
+}
+
+impl std::iter::Sum for LinearExpr {
+    fn sum<I: Iterator<Item = LinearExpr>>(iter: I) -> LinearExpr {
+        iter.fold(LinearExpr::new(), |acc, e| acc + e)
+    }
+}
+
+/// Collect `(variable, coefficient)` pairs into an expression.
+impl std::iter::FromIterator<(usize, Number)> for LinearExpr {
+    fn from_iter<I: IntoIterator<Item = (usize, Number)>>(iter: I) -> LinearExpr {
+        LinearExpr {
+            terms: iter.into_iter().collect(),
+            constant: 0.0,
+        }
+    }
+}
+
+/// A bounded linear expression `lower <= a·x <= upper`.
+///
+/// The constant of the expression is folded into the bounds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinearConstraint {
+    coefficients: Vec<(usize, Number)>,
+    lower: Number,
+    upper: Number,
+}
+
+impl LinearConstraint {
+    /// The coefficient of each variable, sorted by variable.
+    pub fn coefficients(&self) -> &[(usize, Number)] {
+        &self.coefficients
+    }
+
+    /// The lower and upper bounds on `a·x`.
+    pub fn bounds(&self) -> (Number, Number) {
+        (self.lower, self.upper)
+    }
+
+    /// The value of `a·x`.
+    pub fn value(&self, x: &[Number]) -> Number {
+        self.coefficients.iter().map(|&(i, a)| a * x[i]).sum()
+    }
+}
+
+/// A problem with linear constraints appended after its own constraints.
+pub struct WithLinearConstraints<P> {
+    problem: P,
+    linear: Vec<LinearConstraint>,
+    num_linear_non_zeros: usize,
+}
+
+impl<P: ConstrainedProblem> WithLinearConstraints<P> {
+    /// Append the given linear constraints to the constraints of `problem`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if a constraint refers to a variable that is not in the problem.
+    pub fn new(problem: P, linear: Vec<LinearConstraint>) -> Self {
+        let n = problem.num_variables();
+        for (k, constraint) in linear.iter().enumerate() {
+            if let Some(&(i, _)) = constraint.coefficients.last() {
+                assert!(
+                    i < n,
+                    "linear constraint {} refers to variable {}, but the problem has {}",
+                    k,
+                    i,
+                    n
+                );
+            }
+        }
+        let num_linear_non_zeros = linear.iter().map(|c| c.coefficients.len()).sum();
+        WithLinearConstraints {
+            problem,
+            linear,
+            num_linear_non_zeros,
+        }
+    }
+
+    /// The wrapped problem.
+    pub fn problem(&self) -> &P {
+        &self.problem
+    }
+
+    /// The appended linear constraints.
+    pub fn linear_constraints(&self) -> &[LinearConstraint] {
+        &self.linear
+    }
+
+    /// Index of the first linear constraint in the constraints seen by Ipopt.
+    pub fn first_linear_constraint(&self) -> usize {
+        self.problem.num_constraints()
+    }
+
+    /// Unwrap the problem and the linear constraints.
+    pub fn into_parts(self) -> (P, Vec<LinearConstraint>) {
+        (self.problem, self.linear)
+    }
+
+    /// The number of Jacobian non-zeros of the wrapped problem.
+    fn num_problem_non_zeros(&self) -> usize {
+        self.problem.num_constraint_jacobian_non_zeros()
+    }
+}
+
+impl<P: ConstrainedProblem> BasicProblem for WithLinearConstraints<P> {
+    fn indexing_style(&self) -> IndexingStyle {
+        self.problem.indexing_style()
+    }
+    fn num_variables(&self) -> usize {
+        self.problem.num_variables()
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        self.problem.bounds(x_l, x_u)
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        self.problem.initial_point(x)
+    }
+    fn initial_bounds_multipliers(&self, z_l: &mut [Number], z_u: &mut [Number]) -> bool {
+        self.problem.initial_bounds_multipliers(z_l, z_u)
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        self.problem.objective(x, obj)
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        self.problem.objective_grad(x, grad_f)
+    }
+    fn variable_scaling(&self, x_scaling: &mut [Number]) -> bool {
+        self.problem.variable_scaling(x_scaling)
+    }
+    fn objective_scaling(&self) -> f64 {
+        self.problem.objective_scaling()
+    }
+    fn algorithm_mode_changed(&mut self, mode: AlgorithmMode) {
+        self.problem.algorithm_mode_changed(mode)
+    }
+}
+
+impl<P: ConstrainedProblem> ConstrainedProblem for WithLinearConstraints<P> {
+    fn num_constraints(&self) -> usize {
+        self.problem.num_constraints() + self.linear.len()
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        self.num_problem_non_zeros() + self.num_linear_non_zeros
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        let (g, g_linear) = g.split_at_mut(self.first_linear_constraint());
+        if !self.problem.constraint(x, g) {
+            return false;
+        }
+        for (g, constraint) in g_linear.iter_mut().zip(self.linear.iter()) {
+            *g = constraint.value(x);
+        }
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        let m = self.first_linear_constraint();
+        let (g_l, g_l_linear) = g_l.split_at_mut(m);
+        let (g_u, g_u_linear) = g_u.split_at_mut(m);
+        if !self.problem.constraint_bounds(g_l, g_u) {
+            return false;
+        }
+        for ((l, u), constraint) in g_l_linear
+            .iter_mut()
+            .zip(g_u_linear.iter_mut())
+            .zip(self.linear.iter())
+        {
+            *l = constraint.lower;
+            *u = constraint.upper;
+        }
+        true
+    }
+    fn initial_constraint_multipliers(&self, lambda: &mut [Number]) -> bool {
+        let (lambda, lambda_linear) = lambda.split_at_mut(self.first_linear_constraint());
+        if !self.problem.initial_constraint_multipliers(lambda) {
+            return false;
+        }
+        for l in lambda_linear.iter_mut() {
+            *l = 0.0;
+        }
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        let nnz = self.num_problem_non_zeros();
+        let (rows, rows_linear) = rows.split_at_mut(nnz);
+        let (cols, cols_linear) = cols.split_at_mut(nnz);
+        if !self.problem.constraint_jacobian_indices(rows, cols) {
+            return false;
+        }
+        let offset = self.indexing_style().offset();
+        let first_row = self.first_linear_constraint() as Index + offset;
+        let entries = self.linear.iter().enumerate().flat_map(|(k, constraint)| {
+            constraint
+                .coefficients
+                .iter()
+                .map(move |&(i, _)| (first_row + k as Index, i as Index + offset))
+        });
+        for ((r, c), (row, col)) in rows_linear
+            .iter_mut()
+            .zip(cols_linear.iter_mut())
+            .zip(entries)
+        {
+            *r = row;
+            *c = col;
+        }
+        true
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        let (vals, vals_linear) = vals.split_at_mut(self.num_problem_non_zeros());
+        if !self.problem.constraint_jacobian_values(x, vals) {
+            return false;
+        }
+        let values = self
+            .linear
+            .iter()
+            .flat_map(|constraint| constraint.coefficients.iter().map(|&(_, a)| a));
+        for (v, a) in vals_linear.iter_mut().zip(values) {
+            *v = a;
+        }
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        self.problem.num_hessian_non_zeros()
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        self.problem.hessian_indices(rows, cols)
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        let lambda = &lambda[..self.first_linear_constraint()];
+        self.problem.hessian_values(x, obj_factor, lambda, vals)
+    }
+    fn constraint_scaling(&self, g_scaling: &mut [Number]) -> bool {
+        let (g_scaling, g_scaling_linear) = g_scaling.split_at_mut(self.first_linear_constraint());
+        if !self.problem.constraint_scaling(g_scaling) {
+            return false;
+        }
+        for s in g_scaling_linear.iter_mut() {
+            *s = 1.0;
+        }
+        true
+    }
+    fn constraint_groups(&self) -> ConstraintGroups {
+        self.problem.constraint_groups()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RandomProblem;
+
+    #[test]
+    fn linear_constraints_test() {
+        let x = LinearExpr::var;
+        let expr = x(0) + 2.0 * x(2) - x(0) * 3.0 + 1.0;
+        assert_eq!(expr.coefficients(), vec![(0, -2.0), (2, 2.0)]);
+        assert_eq!(expr.value(&[1.0, 5.0, 2.0]), 3.0);
+        let sum: LinearExpr = (0..3).map(x).sum();
+        assert_eq!(sum.coefficients(), vec![(0, 1.0), (1, 1.0), (2, 1.0)]);
+
+        let problem = RandomProblem::new(3, 2, 1);
+        let linear = vec![expr.at_most(4.0), sum.equal_to(1.0)];
+        assert_eq!(linear[0].bounds(), (Number::NEG_INFINITY, 3.0));
+
+        for &style in [IndexingStyle::CStyle, IndexingStyle::FortranStyle].iter() {
+            let mut problem = problem.clone();
+            problem.with_indexing_style(style);
+            let wrapped = WithLinearConstraints::new(problem, linear.clone());
+            assert!(crate::diagnose(&wrapped).is_empty());
+            assert_eq!(wrapped.num_constraints(), 4);
+
+            let nnz = wrapped.num_constraint_jacobian_non_zeros();
+            let (mut rows, mut cols) = (vec![0; nnz], vec![0; nnz]);
+            let mut vals = vec![0.0; nnz];
+            assert!(wrapped.constraint_jacobian_indices(&mut rows, &mut cols));
+            assert!(wrapped.constraint_jacobian_values(&[0.0; 3], &mut vals));
+            let offset = style.offset();
+            let linear_entries: Vec<_> = rows[nnz - 5..]
+                .iter()
+                .zip(&cols[nnz - 5..])
+                .map(|(&r, &c)| (r - offset, c - offset))
+                .collect();
+            assert_eq!(linear_entries, vec![(2, 0), (2, 2), (3, 0), (3, 1), (3, 2)]);
+            assert_eq!(&vals[nnz - 5..], &[-2.0, 2.0, 1.0, 1.0, 1.0]);
+        }
+    }
+}
@@ -0,0 +1,285 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Selection of the linear solver used for the KKT system.
+//!
+//! Which linear solvers can be used depends on how the linked Ipopt library was built and on
+//! which solver libraries can be loaded at runtime. Requesting an unavailable solver through the
+//! `linear_solver` option is only reported once the first solve starts, so
+//! [`Ipopt::set_linear_solver`](struct.Ipopt.html#method.set_linear_solver) checks availability
+//! up front instead.
+
+use crate::ffi;
+use crate::{BasicProblem, Ipopt, Number, SolveStatus};
+
+use std::ffi::CString;
+use std::fmt::{Display, Formatter};
+
+/// Sparse symmetric indefinite linear solvers supported by Ipopt.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LinearSolver {
+    /// HSL MA27.
+    Ma27,
+    /// HSL MA57.
+    Ma57,
+    /// HSL MA77.
+    Ma77,
+    /// HSL MA86.
+    Ma86,
+    /// HSL MA97.
+    Ma97,
+    /// Panua Pardiso, formerly from pardiso-project.org. See
+    /// [`Ipopt::use_pardiso`](struct.Ipopt.html#method.use_pardiso).
+    Pardiso,
+    /// Pardiso from Intel MKL. Requires Ipopt 3.14 or newer.
+    PardisoMkl,
+    /// SPRAL. Requires Ipopt 3.14 or newer.
+    Spral,
+    /// WSMP.
+    Wsmp,
+    /// MUMPS.
+    Mumps,
+}
+
+impl LinearSolver {
+    /// All linear solvers known to this crate.
+    pub fn all() -> Vec<LinearSolver> {
+        use LinearSolver::*;
+        vec![
+            Ma27, Ma57, Ma77, Ma86, Ma97, Pardiso, PardisoMkl, Spral, Wsmp, Mumps,
+        ]
+    }
+
+    /// The value of the `linear_solver` Ipopt option selecting this solver.
+    pub fn option_value(self) -> &'static str {
+        match self {
+            LinearSolver::Ma27 => "ma27",
+            LinearSolver::Ma57 => "ma57",
+            LinearSolver::Ma77 => "ma77",
+            LinearSolver::Ma86 => "ma86",
+            LinearSolver::Ma97 => "ma97",
+            LinearSolver::Pardiso => "pardiso",
+            LinearSolver::PardisoMkl => "pardisomkl",
+            LinearSolver::Spral => "spral",
+            LinearSolver::Wsmp => "wsmp",
+            LinearSolver::Mumps => "mumps",
+        }
+    }
+
+    /// Whether this is one of the HSL solvers.
+    pub fn is_hsl(self) -> bool {
+        matches!(
+            self,
+            LinearSolver::Ma27
+                | LinearSolver::Ma57
+                | LinearSolver::Ma77
+                | LinearSolver::Ma86
+                | LinearSolver::Ma97
+        )
+    }
+
+    /// Check whether this solver can be used with the linked Ipopt library.
+    ///
+    /// This solves a tiny problem with the solver, which also catches solvers that Ipopt loads
+    /// from a shared library at runtime.
+    pub fn is_available(self) -> bool {
+        self.probe(&[])
+    }
+
+    /// Check whether this solver can be used with the given string options set, such as the
+    /// path of a library to load it from.
+    pub(crate) fn probe(self, options: &[(&str, &str)]) -> bool {
+        let mut ipopt = match Ipopt::new_unconstrained(Probe) {
+            Ok(ipopt) => ipopt,
+            Err(_) => return false,
+        };
+        for &(name, value) in options {
+            if ipopt.set_option(name, value).is_none() {
+                return false;
+            }
+        }
+        let configured = ipopt
+            .set_option("linear_solver", self.option_value())
+            .and_then(|ipopt| ipopt.set_option("print_level", 0))
+            .and_then(|ipopt| ipopt.set_option("sb", "yes"))
+            .and_then(|ipopt| ipopt.set_option("max_iter", 1))
+            .is_some();
+        configured
+            && matches!(
+                ipopt.solve().status,
+                SolveStatus::SolveSucceeded
+                    | SolveStatus::SolvedToAcceptableLevel
+                    | SolveStatus::MaximumIterationsExceeded
+            )
+    }
+}
+
+impl Display for LinearSolver {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.option_value())
+    }
+}
+
+/// Linear solvers that can be used with the linked Ipopt library.
+pub fn available_linear_solvers() -> Vec<LinearSolver> {
+    LinearSolver::all()
+        .into_iter()
+        .filter(|s| s.is_available())
+        .collect()
+}
+
+/// Error returned when the requested linear solver is not available.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnavailableLinearSolverError {
+    /// The requested solver.
+    pub solver: LinearSolver,
+    /// Solvers that are available instead.
+    pub available: Vec<LinearSolver>,
+}
+
+impl Display for UnavailableLinearSolverError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Linear solver {} is not available in the linked Ipopt library.",
+            self.solver
+        )?;
+        if self.solver.is_hsl() {
+            write!(
+                f,
+                " HSL solvers must be compiled into Ipopt or provided as a shared library \
+                 (see the \"linear_solver_load_library\" option, or Ipopt::set_hsl_library \
+                 with Ipopt 3.14 or newer)."
+            )?;
+        }
+        if self.solver == LinearSolver::Pardiso {
+            write!(
+                f,
+                " Panua Pardiso must be linked into Ipopt with PARDISO_LIB or loaded with \
+                 Ipopt::use_pardiso, and requires a license file."
+            )?;
+        }
+        if self.available.is_empty() {
+            write!(f, " No other linear solvers are available.")
+        } else {
+            let names: Vec<_> = self.available.iter().map(|s| s.option_value()).collect();
+            write!(f, " Available linear solvers: {}.", names.join(", "))
+        }
+    }
+}
+
+impl std::error::Error for UnavailableLinearSolverError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+impl<P: BasicProblem> Ipopt<P> {
+    /// Select the linear solver used for the KKT system.
+    ///
+    /// Unlike setting the `linear_solver` option directly, this fails immediately if the solver
+    /// is not available in the linked Ipopt library. The solver is tried with the library set
+    /// for it on this solver through the `hsllib` or `pardisolib` option, if any.
+    pub fn set_linear_solver(
+        &mut self,
+        solver: LinearSolver,
+    ) -> Result<&mut Self, UnavailableLinearSolverError> {
+        let library_option = if solver.is_hsl() {
+            Some("hsllib")
+        } else if solver == LinearSolver::Pardiso {
+            Some("pardisolib")
+        } else {
+            None
+        };
+        let library = library_option.and_then(|name| Some((name, self.str_option(name)?)));
+        let options: Vec<_> = library
+            .iter()
+            .map(|(name, path)| (*name, path.as_str()))
+            .collect();
+        if solver.probe(&options)
+            && Self::set_ipopt_option(self.nlp_internal, "linear_solver", solver.option_value())
+        {
+            Ok(self)
+        } else {
+            Err(UnavailableLinearSolverError {
+                solver,
+                available: available_linear_solvers(),
+            })
+        }
+    }
+
+    /// The value of a string option set on this solver, if it is set.
+    fn str_option(&self, name: &str) -> Option<String> {
+        let name = CString::new(name).ok()?;
+        // Long enough for any library path.
+        let mut value = vec![0u8; 4096];
+        let found = unsafe {
+            ffi::cnlp_get_str_option(
+                self.nlp_internal,
+                name.as_ptr(),
+                value.as_mut_ptr() as *mut ::std::os::raw::c_char,
+                value.len() as ffi::CNLP_Int,
+            ) != 0
+        };
+        if !found {
+            return None;
+        }
+        let len = value.iter().position(|&c| c == 0).unwrap_or(value.len());
+        value.truncate(len);
+        String::from_utf8(value).ok()
+    }
+}
+
+/// Tiny problem used to probe linear solvers: min (x - 1)^2.
+struct Probe;
+
+impl BasicProblem for Probe {
+    fn num_variables(&self) -> usize {
+        1
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l[0] = -1e20;
+        x_u[0] = 1e20;
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x[0] = 0.0;
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = (x[0] - 1.0) * (x[0] - 1.0);
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = 2.0 * (x[0] - 1.0);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_message_test() {
+        let err = UnavailableLinearSolverError {
+            solver: LinearSolver::Ma57,
+            available: vec![LinearSolver::Mumps],
+        };
+        let msg = err.to_string();
+        assert!(msg.starts_with("Linear solver ma57 is not available"));
+        assert!(msg.contains("linear_solver_load_library"));
+        assert!(msg.ends_with("Available linear solvers: mumps."));
+    }
+}
@@ -0,0 +1,222 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Writing Ipopt output to a file.
+//!
+//! This is a typed replacement for the `output_file` and `file_print_level` options. Unlike
+//! those options, the log file can be changed between solves, appended to instead of truncated,
+//! and named after the time of each solve.
+
+use crate::ffi;
+use crate::{BasicProblem, Ipopt, PrintLevel};
+
+use std::ffi::CString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How an existing log file is treated when it is opened.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LogFileMode {
+    /// Discard the previous contents of the file.
+    Truncate,
+    /// Add output to the end of the file.
+    Append,
+}
+
+/// Configuration of a log file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogFile {
+    path: PathBuf,
+    level: PrintLevel,
+    mode: LogFileMode,
+    per_solve: bool,
+}
+
+impl LogFile {
+    /// Log to the given path at the default print level, truncating the file.
+    pub fn new<Q: AsRef<Path>>(path: Q) -> Self {
+        LogFile {
+            path: path.as_ref().to_path_buf(),
+            level: PrintLevel::IterSummary,
+            mode: LogFileMode::Truncate,
+            per_solve: false,
+        }
+    }
+
+    /// Set the print level of the output written to the file.
+    pub fn level(&mut self, level: PrintLevel) -> &mut Self {
+        self.level = level;
+        self
+    }
+
+    /// Set whether the file is truncated or appended to.
+    pub fn mode(&mut self, mode: LogFileMode) -> &mut Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Open a new file for every solve, named by inserting the UTC time of the solve into the
+    /// file name. For instance `logs/ipopt.txt` becomes `logs/ipopt-20201014T010203.004Z.txt`.
+    pub fn per_solve(&mut self, per_solve: bool) -> &mut Self {
+        self.per_solve = per_solve;
+        self
+    }
+
+    /// The path of the file written for a solve started at the given time.
+    pub fn path_at(&self, time: SystemTime) -> PathBuf {
+        if !self.per_solve {
+            return self.path.clone();
+        }
+        let stem = self
+            .path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut name = format!("{}-{}", stem, timestamp(time));
+        if let Some(ext) = self.path.extension() {
+            name.push('.');
+            name.push_str(&ext.to_string_lossy());
+        }
+        self.path.with_file_name(name)
+    }
+}
+
+/// Format a time as a compact ISO 8601 UTC timestamp with millisecond precision.
+fn timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (days, secs_of_day) = (secs / 86400, secs % 86400);
+
+    // Convert days since the epoch to a civil date in the proleptic Gregorian calendar.
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+impl<P: BasicProblem> Ipopt<P> {
+    /// Write output to the given file at the given print level, truncating the file.
+    ///
+    /// This is a shorthand for [`set_log_file`](struct.Ipopt.html#method.set_log_file).
+    pub fn log_to_file<Q: AsRef<Path>>(
+        &mut self,
+        path: Q,
+        level: PrintLevel,
+    ) -> io::Result<&mut Self> {
+        self.set_log_file(LogFile::new(path).level(level))
+    }
+
+    /// Write output to a file in addition to the console, replacing any previous log file.
+    ///
+    /// Missing parent directories are created. Unless the file is opened per solve, it is opened
+    /// immediately so that errors are reported here. Files opened per solve that cannot be
+    /// created when a solve starts are skipped.
+    pub fn set_log_file(&mut self, log_file: &LogFile) -> io::Result<&mut Self> {
+        if let Some(parent) = log_file.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+        if log_file.per_solve {
+            unsafe { ffi::cnlp_close_log_file(self.nlp_internal) };
+        } else {
+            self.open_log_file(&log_file.path, log_file)?;
+        }
+        self.log_file = Some(log_file.clone());
+        Ok(self)
+    }
+
+    /// Stop writing output to the log file.
+    pub fn close_log_file(&mut self) -> &mut Self {
+        unsafe { ffi::cnlp_close_log_file(self.nlp_internal) };
+        self.log_file = None;
+        self
+    }
+
+    /// Open the log file for a solve that is about to start, if one is configured per solve.
+    pub(crate) fn open_solve_log_file(&mut self) {
+        let log_file = match self.log_file {
+            Some(ref log_file) if log_file.per_solve => log_file.clone(),
+            _ => return,
+        };
+        let path = log_file.path_at(SystemTime::now());
+        if self.open_log_file(&path, &log_file).is_err() {
+            unsafe { ffi::cnlp_close_log_file(self.nlp_internal) };
+        }
+    }
+
+    fn open_log_file(&mut self, path: &Path, log_file: &LogFile) -> io::Result<()> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidInput, "invalid log file path");
+        let path_cstr = CString::new(path.to_str().ok_or_else(invalid)?).map_err(|_| invalid())?;
+        let ok = unsafe {
+            ffi::cnlp_open_log_file(
+                self.nlp_internal,
+                path_cstr.as_ptr(),
+                log_file.level as ffi::CNLP_Int,
+                (log_file.mode == LogFileMode::Append) as ffi::CNLP_Bool,
+            )
+        };
+        if ok != 0 {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("Ipopt could not open the log file {}", path.display()),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn per_solve_path_test() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_602_637_323_004);
+        assert_eq!(timestamp(time), "20201014T010203.004Z");
+        assert_eq!(timestamp(UNIX_EPOCH), "19700101T000000.000Z");
+
+        let mut log_file = LogFile::new("logs/ipopt.txt");
+        assert_eq!(log_file.path_at(time), Path::new("logs/ipopt.txt"));
+        log_file.per_solve(true);
+        assert_eq!(
+            log_file.path_at(time),
+            Path::new("logs/ipopt-20201014T010203.004Z.txt")
+        );
+        assert_eq!(
+            LogFile::new("ipopt").per_solve(true).path_at(time),
+            Path::new("ipopt-20201014T010203.004Z")
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Bounding the memory used by the linear solver.
+//!
+//! Most of the memory of a solve is used by the factorization of the KKT system. Before
+//! factoring, the linear solvers estimate the workspace they need from the sparsity structure,
+//! and then allocate a multiple of that estimate to leave room for fill-in from pivoting. A
+//! workspace limit set with
+//! [`Ipopt::set_workspace_limit`](struct.Ipopt.html#method.set_workspace_limit) caps that multiple
+//! through the workspace options of every linear solver that has them. When the factorization does
+//! not fit, Ipopt stops with
+//! [`SolveStatus::InsufficientMemory`](enum.SolveStatus.html#variant.InsufficientMemory), and
+//! [`Ipopt::try_solve`](struct.Ipopt.html#method.try_solve) turns that status into an error
+//! describing the problem size and what can be done about it.
+
+use crate::{
+    BasicProblem, InvalidOptionError, Ipopt, Number, OptionRegistry, OptionSet, OptionValue,
+    SolveResult, SolveStatus,
+};
+
+use std::fmt::{Display, Formatter};
+
+/// The value of a workspace option for a given limit on the workspace relative to the estimated
+/// size.
+type WorkspaceValue = fn(Number) -> OptionValue;
+
+/// Workspace options of the linear solvers.
+const WORKSPACE_OPTIONS: [(&str, WorkspaceValue); 4] = [
+    // Percentage by which MUMPS increases the estimated workspace.
+    ("mumps_mem_percent", |factor| {
+        OptionValue::Int(((factor - 1.0) * 100.0).ceil().min(i32::MAX as Number) as i32)
+    }),
+    ("ma27_liw_init_factor", OptionValue::Num),
+    ("ma27_la_init_factor", OptionValue::Num),
+    ("ma57_pre_alloc", OptionValue::Num),
+];
+
+/// The workspace options implementing the given workspace limit, or restoring their defaults
+/// for `None`, restricted to the options registered in `registry`.
+fn workspace_options(limit: Option<Number>, registry: &OptionRegistry) -> OptionSet {
+    let mut options = OptionSet::new();
+    for (name, value) in WORKSPACE_OPTIONS.iter() {
+        if let Some(info) = registry.get(name) {
+            let value = match limit {
+                Some(factor) => value(factor),
+                None => info.default.clone(),
+            };
+            options.set(name, value);
+        }
+    }
+    options
+}
+
+/// Error returned when a workspace limit can't be set.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WorkspaceLimitError {
+    /// The limit is not a finite number of at least 1.
+    InvalidLimit(Number),
+    /// Ipopt rejected one of the workspace options.
+    InvalidOption(InvalidOptionError),
+}
+
+impl Display for WorkspaceLimitError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            WorkspaceLimitError::InvalidLimit(limit) => write!(
+                f,
+                "Invalid workspace limit {}: the limit must be a finite number of at least 1.",
+                limit
+            ),
+            WorkspaceLimitError::InvalidOption(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for WorkspaceLimitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WorkspaceLimitError::InvalidOption(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<InvalidOptionError> for WorkspaceLimitError {
+    fn from(err: InvalidOptionError) -> Self {
+        WorkspaceLimitError::InvalidOption(err)
+    }
+}
+
+/// Error returned when a solve runs out of memory.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InsufficientMemoryError {
+    /// The workspace limit in effect during the solve, if any.
+    pub workspace_limit: Option<Number>,
+    /// Number of variables of the problem.
+    pub num_variables: usize,
+    /// Number of constraints of the problem.
+    pub num_constraints: usize,
+}
+
+impl Display for InsufficientMemoryError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Ipopt ran out of memory solving a problem with {} variables and {} constraints",
+            self.num_variables, self.num_constraints
+        )?;
+        if let Some(limit) = self.workspace_limit {
+            write!(
+                f,
+                " within a linear solver workspace of {} times the estimated size",
+                limit
+            )?;
+        }
+        write!(
+            f,
+            ". Most memory is used by the factorization of the KKT system; consider raising \
+             the workspace limit, choosing a linear solver with lower fill-in, or using a \
+             \"limited-memory\" Hessian approximation."
+        )
+    }
+}
+
+impl std::error::Error for InsufficientMemoryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+impl<P: BasicProblem> Ipopt<P> {
+    /// Limit the workspace of the linear solver to the given multiple of the size estimated
+    /// from the sparsity structure, or restore the default allowances with `None`.
+    ///
+    /// This sets `mumps_mem_percent`, `ma27_liw_init_factor`, `ma27_la_init_factor` and
+    /// `ma57_pre_alloc`, skipping the options of linear solvers that are not available. The
+    /// solvers without workspace options, like MA86, MA97 and Pardiso, are not limited. A limit
+    /// close to 1 saves memory, but makes it more likely that a factorization needs more room
+    /// for fill-in than the estimate, in which case Ipopt enlarges the workspace and factors
+    /// again, or fails with `SolveStatus::InsufficientMemory`.
+    pub fn set_workspace_limit(
+        &mut self,
+        limit: Option<Number>,
+    ) -> Result<&mut Self, WorkspaceLimitError> {
+        if let Some(factor) = limit {
+            if !factor.is_finite() || factor < 1.0 {
+                return Err(WorkspaceLimitError::InvalidLimit(factor));
+            }
+        }
+        self.set_options(&workspace_options(limit, OptionRegistry::linked_ref()))?;
+        self.workspace_limit = limit;
+        Ok(self)
+    }
+
+    /// Solve the problem, reporting an exhausted memory limit as an error.
+    ///
+    /// This is equivalent to [`solve`](struct.Ipopt.html#method.solve) except that a solve
+    /// ending with `SolveStatus::InsufficientMemory` is returned as an `Err`.
+    pub fn try_solve(&mut self) -> Result<SolveResult<P>, InsufficientMemoryError> {
+        let workspace_limit = self.workspace_limit;
+        let result = self.solve();
+        if result.status == SolveStatus::InsufficientMemory {
+            Err(InsufficientMemoryError {
+                workspace_limit,
+                num_variables: result.solver_data.solution.primal_variables.len(),
+                num_constraints: result.constraint_values.len(),
+            })
+        } else {
+            Ok(result)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OptionInfo, OptionType};
+
+    #[test]
+    fn workspace_options_test() {
+        let option = |name: &str, default: OptionValue| OptionInfo {
+            name: name.to_string(),
+            option_type: OptionType::of(&default),
+            category: String::new(),
+            description: String::new(),
+            default,
+            lower: None,
+            upper: None,
+            valid_values: Vec::new(),
+        };
+        // A build with MUMPS and MA57 only.
+        let registry: OptionRegistry = vec![
+            option("mumps_mem_percent", OptionValue::Int(1000)),
+            option("ma57_pre_alloc", OptionValue::Num(1.05)),
+            option("tol", OptionValue::Num(1e-8)),
+        ]
+        .into_iter()
+        .collect();
+
+        let options = workspace_options(Some(1.5), &registry);
+        let values: Vec<_> = options.iter().collect();
+        assert_eq!(
+            values,
+            vec![
+                ("ma57_pre_alloc", &OptionValue::Num(1.5)),
+                ("mumps_mem_percent", &OptionValue::Int(50)),
+            ]
+        );
+
+        let options = workspace_options(None, &registry);
+        let values: Vec<_> = options.iter().collect();
+        assert_eq!(
+            values,
+            vec![
+                ("ma57_pre_alloc", &OptionValue::Num(1.05)),
+                ("mumps_mem_percent", &OptionValue::Int(1000)),
+            ]
+        );
+
+        let err = InsufficientMemoryError {
+            workspace_limit: Some(2.0),
+            num_variables: 10,
+            num_constraints: 2,
+        };
+        assert!(err.to_string().starts_with(
+            "Ipopt ran out of memory solving a problem with 10 variables and 2 constraints \
+             within a linear solver workspace of 2 times the estimated size."
+        ));
+    }
+}
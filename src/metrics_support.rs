@@ -0,0 +1,87 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Solver metrics emitted through the [`metrics`](https://docs.rs/metrics) facade.
+//!
+//! With the `metrics` feature enabled, every solve emits the following metrics, which an
+//! installed recorder such as `metrics-exporter-prometheus` can export. Without a recorder,
+//! emitting them is practically free.
+//!
+//! | Name | Type | Description |
+//! |------|------|-------------|
+//! | `ipopt_solves_total` | counter | Finished solves, labeled by `status`. |
+//! | `ipopt_iterations_total` | counter | Iterations over all solves. |
+//! | `ipopt_objective_evaluations_total` | counter | Objective evaluations. |
+//! | `ipopt_constraint_evaluations_total` | counter | Constraint evaluations. |
+//! | `ipopt_objective_gradient_evaluations_total` | counter | Objective gradient evaluations. |
+//! | `ipopt_constraint_jacobian_evaluations_total` | counter | Constraint Jacobian evaluations. |
+//! | `ipopt_hessian_evaluations_total` | counter | Hessian evaluations. |
+//! | `ipopt_solve_duration_seconds` | histogram | Wallclock time of each solve. |
+//! | `ipopt_iteration` | gauge | Current iteration of the running solve. |
+//! | `ipopt_primal_infeasibility` | gauge | Current constraint violation. |
+//! | `ipopt_dual_infeasibility` | gauge | Current scaled dual infeasibility. |
+//! | `ipopt_barrier_parameter` | gauge | Current barrier parameter. |
+//!
+//! The gauges are updated in every iteration, which requires Ipopt's intermediate callback to be
+//! installed for all solves.
+
+use crate::{BasicProblem, IntermediateCallbackData, Ipopt, SolveStatus};
+
+use metrics::{counter, gauge, histogram};
+
+/// Update the per-iteration gauges.
+pub(crate) fn record_iteration(data: &IntermediateCallbackData) {
+    gauge!("ipopt_iteration").set(data.iter_count as f64);
+    gauge!("ipopt_primal_infeasibility").set(data.inf_pr);
+    gauge!("ipopt_dual_infeasibility").set(data.inf_du);
+    gauge!("ipopt_barrier_parameter").set(data.mu);
+}
+
+impl<P: BasicProblem> Ipopt<P> {
+    /// Emit the metrics of a finished solve.
+    pub(crate) fn record_solve_metrics(&self, status: SolveStatus) {
+        counter!("ipopt_solves_total", "status" => format!("{:?}", status)).increment(1);
+        let stats = match self.solve_stats() {
+            Some(stats) => stats,
+            None => return,
+        };
+        let counts = [
+            ("ipopt_iterations_total", stats.iteration_count),
+            (
+                "ipopt_objective_evaluations_total",
+                stats.num_objective_evaluations,
+            ),
+            (
+                "ipopt_constraint_evaluations_total",
+                stats.num_constraint_evaluations,
+            ),
+            (
+                "ipopt_objective_gradient_evaluations_total",
+                stats.num_objective_gradient_evaluations,
+            ),
+            (
+                "ipopt_constraint_jacobian_evaluations_total",
+                stats.num_constraint_jacobian_evaluations,
+            ),
+            (
+                "ipopt_hessian_evaluations_total",
+                stats.num_hessian_evaluations,
+            ),
+        ];
+        for &(name, count) in counts.iter() {
+            counter!(name).increment(count.max(0) as u64);
+        }
+        histogram!("ipopt_solve_duration_seconds").record(stats.total_wallclock_time);
+    }
+}
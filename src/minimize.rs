@@ -0,0 +1,149 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Unconstrained minimization of closures.
+//!
+//! [`minimize`](fn.minimize.html) takes the objective and its gradient as closures and solves
+//! through [`solve_bound_constrained`](fn.solve_bound_constrained.html), so smooth
+//! unconstrained problems need no trait implementations:
+//!
+//! ```no_run
+//! # use ipopt::OptionSet;
+//! # fn main() -> Result<(), ipopt::BoundConstrainedError> {
+//! let rosenbrock = |x: &[f64]| (1.0 - x[0]).powi(2) + 100.0 * (x[1] - x[0] * x[0]).powi(2);
+//! let gradient = |x: &[f64], g: &mut [f64]| {
+//!     g[0] = -2.0 * (1.0 - x[0]) - 400.0 * x[0] * (x[1] - x[0] * x[0]);
+//!     g[1] = 200.0 * (x[1] - x[0] * x[0]);
+//! };
+//! let solution = ipopt::minimize(rosenbrock, gradient, &[-1.2, 1.0], &OptionSet::new())?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    solve_bound_constrained, BoundConstrainedError, BoundConstrainedProblem,
+    BoundConstrainedSolution, Number, OptionSet,
+};
+
+/// A bound-constrained problem given by closures for the objective and its gradient.
+#[derive(Clone, Debug)]
+pub struct ClosureProblem<F, G> {
+    objective: F,
+    gradient: G,
+    initial_point: Vec<Number>,
+    lower: Vec<Number>,
+    upper: Vec<Number>,
+}
+
+impl<F, G> ClosureProblem<F, G>
+where
+    F: Fn(&[Number]) -> Number,
+    G: Fn(&[Number], &mut [Number]),
+{
+    /// Create an unbounded problem starting at `x0`.
+    pub fn new(objective: F, gradient: G, x0: &[Number]) -> Self {
+        ClosureProblem {
+            objective,
+            gradient,
+            initial_point: x0.to_vec(),
+            lower: vec![Number::NEG_INFINITY; x0.len()],
+            upper: vec![Number::INFINITY; x0.len()],
+        }
+    }
+
+    /// Set lower and upper variable bounds.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the bounds have a different length than the starting point.
+    pub fn with_bounds(&mut self, lower: &[Number], upper: &[Number]) -> &mut Self {
+        assert_eq!(lower.len(), self.initial_point.len());
+        assert_eq!(upper.len(), self.initial_point.len());
+        self.lower.copy_from_slice(lower);
+        self.upper.copy_from_slice(upper);
+        self
+    }
+}
+
+impl<F, G> BoundConstrainedProblem for ClosureProblem<F, G>
+where
+    F: Fn(&[Number]) -> Number,
+    G: Fn(&[Number], &mut [Number]),
+{
+    fn num_variables(&self) -> usize {
+        self.initial_point.len()
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) {
+        x_l.copy_from_slice(&self.lower);
+        x_u.copy_from_slice(&self.upper);
+    }
+    fn initial_point(&self, x: &mut [Number]) {
+        x.copy_from_slice(&self.initial_point);
+    }
+    // Non-finite values are reported as failed evaluations, so Ipopt cuts the step back.
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = (self.objective)(x);
+        obj.is_finite()
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        (self.gradient)(x, grad_f);
+        grad_f.iter().all(|g| g.is_finite())
+    }
+}
+
+/// Minimize the smooth function `f` with gradient `grad`, starting at `x0`.
+///
+/// The gradient closure writes the gradient at its first argument into its second. The
+/// minimizer and the statistics of the solve are returned in the solution.
+pub fn minimize<F, G>(
+    f: F,
+    grad: G,
+    x0: &[Number],
+    options: &OptionSet,
+) -> Result<BoundConstrainedSolution, BoundConstrainedError>
+where
+    F: Fn(&[Number]) -> Number,
+    G: Fn(&[Number], &mut [Number]),
+{
+    solve_bound_constrained(ClosureProblem::new(f, grad, x0), options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closure_problem_test() {
+        let mut problem = ClosureProblem::new(
+            |x: &[Number]| x[0].ln() + x[1] * x[1],
+            |x: &[Number], g: &mut [Number]| {
+                g[0] = 1.0 / x[0];
+                g[1] = 2.0 * x[1];
+            },
+            &[1.0, 3.0],
+        );
+        problem.with_bounds(&[0.0, -1.0], &[2.0, 1.0]);
+
+        let (mut x_l, mut x_u) = ([0.0; 2], [0.0; 2]);
+        problem.bounds(&mut x_l, &mut x_u);
+        assert_eq!((x_l, x_u), ([0.0, -1.0], [2.0, 1.0]));
+
+        let mut obj = 0.0;
+        assert!(problem.objective(&[1.0, 3.0], &mut obj));
+        assert_eq!(obj, 9.0);
+        assert!(!problem.objective(&[-1.0, 3.0], &mut obj));
+        let mut grad = [0.0; 2];
+        assert!(!problem.objective_grad(&[0.0, 3.0], &mut grad));
+    }
+}
@@ -0,0 +1,1423 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Reading linear and quadratic programs from MPS and LP files.
+//!
+//! Ipopt is not a dedicated LP or QP solver, but many benchmark models are only available in
+//! these formats. A [`QuadraticProgram`](struct.QuadraticProgram.html) read from a file
+//! implements [`ConstrainedProblem`](trait.ConstrainedProblem.html) and can be passed directly
+//! to [`Ipopt::new`](struct.Ipopt.html#method.new). The objective sense is not part of the
+//! problem callbacks, so it has to be set on the solver:
+//!
+//! ```no_run
+//! use ipopt::*;
+//!
+//! let qp = QuadraticProgram::from_mps_file("afiro.mps").unwrap();
+//! let sense = qp.objective_sense();
+//! let mut ipopt = Ipopt::new(qp).unwrap();
+//! ipopt.set_objective_sense(sense);
+//! let result = ipopt.solve();
+//! ```
+//!
+//! MPS files are read in free format, which also covers fixed format files without spaces in
+//! names. The `OBJSENSE`, `RANGES`, `QUADOBJ` and `QMATRIX` sections are supported, as is the
+//! output of [`write_mps`](fn.write_mps.html). LP files are read in the CPLEX LP format, with
+//! quadratic objective terms in brackets such as `[ x^2 + 2 x * y ] / 2`. Integer variables are
+//! relaxed to continuous ones, and quadratic constraints, semi-continuous variables and SOS
+//! constraints are not supported.
+
+use crate::sparsity::Triplets;
+use crate::{BasicProblem, ConstrainedProblem, Index, Number, ObjectiveSense, SparsityBuilder};
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::io::{self, BufRead};
+use std::path::Path;
+
+/// Error reading an MPS or LP file.
+#[derive(Debug)]
+pub enum ModelFileError {
+    /// A line could not be parsed.
+    Syntax {
+        /// Line number, starting at one.
+        line: usize,
+        /// What was wrong with the line.
+        message: String,
+    },
+    /// The model uses a feature that is not supported, such as quadratic constraints.
+    Unsupported {
+        /// Line number, starting at one.
+        line: usize,
+        /// The unsupported feature.
+        feature: String,
+    },
+    /// Reading the file failed.
+    Io(io::Error),
+}
+
+impl Display for ModelFileError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ModelFileError::Syntax { line, message } => write!(f, "Line {}: {}.", line, message),
+            ModelFileError::Unsupported { line, feature } => {
+                write!(f, "Line {}: {} are not supported.", line, feature)
+            }
+            ModelFileError::Io(err) => write!(f, "Failed to read the model: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ModelFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ModelFileError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ModelFileError {
+    fn from(err: io::Error) -> Self {
+        ModelFileError::Io(err)
+    }
+}
+
+fn syntax(line: usize, message: impl Into<String>) -> ModelFileError {
+    ModelFileError::Syntax {
+        line,
+        message: message.into(),
+    }
+}
+
+fn unsupported(line: usize, feature: &str) -> ModelFileError {
+    ModelFileError::Unsupported {
+        line,
+        feature: feature.to_string(),
+    }
+}
+
+/// A linear or quadratic program
+///
+/// ```verbatim
+/// min or max  c·x + x'Qx/2 + k
+/// subject to  g_l <= A x <= g_u
+///             x_l <= x <= x_u
+/// ```
+///
+/// read from an MPS or LP file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuadraticProgram {
+    name: String,
+    sense: ObjectiveSense,
+    variable_names: Vec<String>,
+    constraint_names: Vec<String>,
+    integer: Vec<bool>,
+    x_l: Vec<Number>,
+    x_u: Vec<Number>,
+    g_l: Vec<Number>,
+    g_u: Vec<Number>,
+    linear: Vec<Number>,
+    constant: Number,
+    /// Lower triangular part of `Q`.
+    hessian: Triplets,
+    hessian_values: Vec<Number>,
+    jacobian: Triplets,
+    jacobian_values: Vec<Number>,
+}
+
+impl QuadraticProgram {
+    /// Read a program from an MPS file.
+    ///
+    /// The name of the program is the file stem if the file has no `NAME` section.
+    pub fn from_mps_file<P: AsRef<Path>>(path: P) -> Result<Self, ModelFileError> {
+        let file = std::fs::File::open(path.as_ref())?;
+        let mut qp = Self::read_mps(io::BufReader::new(file))?;
+        qp.name_from_path(path.as_ref());
+        Ok(qp)
+    }
+
+    /// Read a program from an LP file.
+    ///
+    /// The name of the program is the file stem.
+    pub fn from_lp_file<P: AsRef<Path>>(path: P) -> Result<Self, ModelFileError> {
+        let file = std::fs::File::open(path.as_ref())?;
+        let mut qp = Self::read_lp(io::BufReader::new(file))?;
+        qp.name_from_path(path.as_ref());
+        Ok(qp)
+    }
+
+    /// Read a program in MPS format.
+    pub fn read_mps<R: BufRead>(input: R) -> Result<Self, ModelFileError> {
+        MpsReader::default().read(input)
+    }
+
+    /// Read a program in CPLEX LP format.
+    pub fn read_lp<R: BufRead>(input: R) -> Result<Self, ModelFileError> {
+        read_lp(input)
+    }
+
+    fn name_from_path(&mut self, path: &Path) {
+        if self.name.is_empty() {
+            if let Some(stem) = path.file_stem() {
+                self.name = stem.to_string_lossy().into_owned();
+            }
+        }
+    }
+
+    /// The name of the program.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the objective is minimized or maximized.
+    ///
+    /// Pass this to [`Ipopt::set_objective_sense`](struct.Ipopt.html#method.set_objective_sense).
+    pub fn objective_sense(&self) -> ObjectiveSense {
+        self.sense
+    }
+
+    /// Names of the variables, in the order of the variables in the problem.
+    pub fn variable_names(&self) -> &[String] {
+        &self.variable_names
+    }
+
+    /// Names of the constraints, in the order of the constraints in the problem.
+    pub fn constraint_names(&self) -> &[String] {
+        &self.constraint_names
+    }
+
+    /// For each variable, whether the file declares it as integer.
+    ///
+    /// Integer variables are treated as continuous.
+    pub fn integer_variables(&self) -> &[bool] {
+        &self.integer
+    }
+
+    /// Returns `true` if the objective has no quadratic terms.
+    pub fn is_linear(&self) -> bool {
+        self.hessian_values.iter().all(|&v| v == 0.0)
+    }
+}
+
+impl BasicProblem for QuadraticProgram {
+    fn num_variables(&self) -> usize {
+        self.variable_names.len()
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&self.x_l);
+        x_u.copy_from_slice(&self.x_u);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        // The origin, moved into the bounds.
+        for ((x, &l), &u) in x.iter_mut().zip(self.x_l.iter()).zip(self.x_u.iter()) {
+            *x = if l > 0.0 {
+                l
+            } else if u < 0.0 {
+                u
+            } else {
+                0.0
+            };
+        }
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        let mut f = self.constant;
+        for (&c, &x) in self.linear.iter().zip(x.iter()) {
+            f += c * x;
+        }
+        for (&(row, col), &v) in self
+            .hessian
+            .entries()
+            .iter()
+            .zip(self.hessian_values.iter())
+        {
+            if row == col {
+                f += 0.5 * v * x[row] * x[row];
+            } else {
+                f += v * x[row] * x[col];
+            }
+        }
+        *obj = f;
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f.copy_from_slice(&self.linear);
+        for (&(row, col), &v) in self
+            .hessian
+            .entries()
+            .iter()
+            .zip(self.hessian_values.iter())
+        {
+            grad_f[row] += v * x[col];
+            if row != col {
+                grad_f[col] += v * x[row];
+            }
+        }
+        true
+    }
+}
+
+impl ConstrainedProblem for QuadraticProgram {
+    fn num_constraints(&self) -> usize {
+        self.constraint_names.len()
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        self.jacobian.len()
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        for g in g.iter_mut() {
+            *g = 0.0;
+        }
+        for (&(row, col), &v) in self
+            .jacobian
+            .entries()
+            .iter()
+            .zip(self.jacobian_values.iter())
+        {
+            g[row] += v * x[col];
+        }
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l.copy_from_slice(&self.g_l);
+        g_u.copy_from_slice(&self.g_u);
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        self.jacobian
+            .write_indices(self.indexing_style(), rows, cols)
+    }
+    fn constraint_jacobian_values(&self, _x: &[Number], vals: &mut [Number]) -> bool {
+        vals.copy_from_slice(&self.jacobian_values);
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        self.hessian.len()
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        self.hessian
+            .write_indices(self.indexing_style(), rows, cols)
+    }
+    fn hessian_values(
+        &self,
+        _x: &[Number],
+        obj_factor: Number,
+        _lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        for (v, &h) in vals.iter_mut().zip(self.hessian_values.iter()) {
+            *v = obj_factor * h;
+        }
+        true
+    }
+}
+
+/// Accumulates a program while it is read.
+struct ModelBuilder {
+    name: String,
+    sense: ObjectiveSense,
+    variable_names: Vec<String>,
+    variables: HashMap<String, usize>,
+    integer: Vec<bool>,
+    x_l: Vec<Number>,
+    x_u: Vec<Number>,
+    constraint_names: Vec<String>,
+    g_l: Vec<Number>,
+    g_u: Vec<Number>,
+    linear: Vec<Number>,
+    constant: Number,
+    /// Entries of `Q`, in either triangle.
+    quadratic: Vec<(usize, usize, Number)>,
+    jacobian: Vec<(usize, usize, Number)>,
+}
+
+impl Default for ModelBuilder {
+    fn default() -> Self {
+        ModelBuilder {
+            name: String::new(),
+            sense: ObjectiveSense::Minimize,
+            variable_names: Vec::new(),
+            variables: HashMap::new(),
+            integer: Vec::new(),
+            x_l: Vec::new(),
+            x_u: Vec::new(),
+            constraint_names: Vec::new(),
+            g_l: Vec::new(),
+            g_u: Vec::new(),
+            linear: Vec::new(),
+            constant: 0.0,
+            quadratic: Vec::new(),
+            jacobian: Vec::new(),
+        }
+    }
+}
+
+impl ModelBuilder {
+    /// The index of the named variable, which is added with bounds `[0, ∞)` if it is new.
+    fn variable(&mut self, name: &str) -> usize {
+        if let Some(&i) = self.variables.get(name) {
+            return i;
+        }
+        let i = self.variable_names.len();
+        self.variables.insert(name.to_string(), i);
+        self.variable_names.push(name.to_string());
+        self.integer.push(false);
+        self.x_l.push(0.0);
+        self.x_u.push(Number::INFINITY);
+        self.linear.push(0.0);
+        i
+    }
+
+    fn add_constraint(&mut self, name: String, lower: Number, upper: Number) -> usize {
+        self.constraint_names.push(name);
+        self.g_l.push(lower);
+        self.g_u.push(upper);
+        self.constraint_names.len() - 1
+    }
+
+    fn build(self) -> QuadraticProgram {
+        let n = self.variable_names.len();
+        let m = self.constraint_names.len();
+
+        let mut hessian = SparsityBuilder::symmetric(n);
+        for &(i, j, _) in self.quadratic.iter() {
+            hessian.add(i.max(j), i.min(j));
+        }
+        let hessian = hessian.build().unwrap();
+        let mut hessian_values = vec![0.0; hessian.len()];
+        for (&slot, &(_, _, v)) in hessian.entry_slots().iter().zip(self.quadratic.iter()) {
+            hessian_values[slot] += v;
+        }
+
+        let mut jacobian = SparsityBuilder::new(m, n);
+        for &(row, col, _) in self.jacobian.iter() {
+            jacobian.add(row, col);
+        }
+        let jacobian = jacobian.build().unwrap();
+        let mut jacobian_values = vec![0.0; jacobian.len()];
+        for (&slot, &(_, _, v)) in jacobian.entry_slots().iter().zip(self.jacobian.iter()) {
+            jacobian_values[slot] += v;
+        }
+
+        QuadraticProgram {
+            name: self.name,
+            sense: self.sense,
+            variable_names: self.variable_names,
+            constraint_names: self.constraint_names,
+            integer: self.integer,
+            x_l: self.x_l,
+            x_u: self.x_u,
+            g_l: self.g_l,
+            g_u: self.g_u,
+            linear: self.linear,
+            constant: self.constant,
+            hessian,
+            hessian_values,
+            jacobian,
+            jacobian_values,
+        }
+    }
+}
+
+/*
+ * MPS format
+ */
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum MpsSection {
+    Start,
+    ObjSense,
+    Rows,
+    Columns,
+    Rhs,
+    Ranges,
+    Bounds,
+    QuadObj,
+    QMatrix,
+    End,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum MpsRow {
+    Objective,
+    /// Free rows after the first are dropped.
+    Free,
+    Constraint(usize),
+}
+
+#[derive(Default)]
+struct MpsReader {
+    model: ModelBuilder,
+    rows: HashMap<String, MpsRow>,
+    has_objective: bool,
+    /// Row type (`E`, `L` or `G`), right-hand side and range of each constraint.
+    row_types: Vec<char>,
+    rhs: Vec<Number>,
+    ranges: Vec<Option<Number>>,
+    in_integer_block: bool,
+}
+
+fn parse_number(token: &str, line: usize) -> Result<Number, ModelFileError> {
+    token
+        .parse()
+        .map_err(|_| syntax(line, format!("invalid number \"{}\"", token)))
+}
+
+impl MpsReader {
+    fn read<R: BufRead>(mut self, input: R) -> Result<QuadraticProgram, ModelFileError> {
+        let mut section = MpsSection::Start;
+        for (i, line) in input.lines().enumerate() {
+            let line = line?;
+            let line_no = i + 1;
+            if line.trim().is_empty() || line.starts_with('*') {
+                continue;
+            }
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            if !line.starts_with(char::is_whitespace) {
+                section = self.header(&tokens, line_no)?;
+                if section == MpsSection::End {
+                    break;
+                }
+                continue;
+            }
+            match section {
+                MpsSection::Start => return Err(syntax(line_no, "data before the first section")),
+                MpsSection::ObjSense => self.objective_sense(tokens[0], line_no)?,
+                MpsSection::Rows => self.row(&tokens, line_no)?,
+                MpsSection::Columns => self.column(&tokens, line_no)?,
+                MpsSection::Rhs => self.rhs(&tokens, line_no)?,
+                MpsSection::Ranges => self.range(&tokens, line_no)?,
+                MpsSection::Bounds => self.bound(&tokens, line_no)?,
+                MpsSection::QuadObj => self.quadratic(&tokens, line_no, false)?,
+                MpsSection::QMatrix => self.quadratic(&tokens, line_no, true)?,
+                MpsSection::End => unreachable!(),
+            }
+        }
+
+        for k in 0..self.row_types.len() {
+            let rhs = self.rhs[k];
+            let (lower, upper) = match (self.row_types[k], self.ranges[k]) {
+                ('E', None) => (rhs, rhs),
+                ('E', Some(r)) if r < 0.0 => (rhs + r, rhs),
+                ('E', Some(r)) => (rhs, rhs + r),
+                ('L', None) => (Number::NEG_INFINITY, rhs),
+                ('L', Some(r)) => (rhs - r.abs(), rhs),
+                ('G', None) => (rhs, Number::INFINITY),
+                (_, Some(r)) => (rhs, rhs + r.abs()),
+                _ => unreachable!(),
+            };
+            self.model.g_l[k] = lower;
+            self.model.g_u[k] = upper;
+        }
+        Ok(self.model.build())
+    }
+
+    fn header(&mut self, tokens: &[&str], line: usize) -> Result<MpsSection, ModelFileError> {
+        let section = match tokens[0].to_ascii_uppercase().as_str() {
+            "NAME" => {
+                self.model.name = tokens[1..].join(" ");
+                MpsSection::Start
+            }
+            "OBJSENSE" => {
+                if let Some(sense) = tokens.get(1) {
+                    self.objective_sense(sense, line)?;
+                }
+                MpsSection::ObjSense
+            }
+            "ROWS" => MpsSection::Rows,
+            "COLUMNS" => MpsSection::Columns,
+            "RHS" => MpsSection::Rhs,
+            "RANGES" => MpsSection::Ranges,
+            "BOUNDS" => MpsSection::Bounds,
+            "QUADOBJ" => MpsSection::QuadObj,
+            "QMATRIX" => MpsSection::QMatrix,
+            "ENDATA" => MpsSection::End,
+            "QSECTION" | "QCMATRIX" => return Err(unsupported(line, "quadratic constraints")),
+            "SOS" => return Err(unsupported(line, "SOS constraints")),
+            _ => return Err(syntax(line, format!("unknown section {}", tokens[0]))),
+        };
+        Ok(section)
+    }
+
+    fn objective_sense(&mut self, sense: &str, line: usize) -> Result<(), ModelFileError> {
+        self.model.sense = match sense.to_ascii_uppercase().as_str() {
+            "MIN" | "MINIMIZE" => ObjectiveSense::Minimize,
+            "MAX" | "MAXIMIZE" => ObjectiveSense::Maximize,
+            _ => return Err(syntax(line, format!("unknown objective sense {}", sense))),
+        };
+        Ok(())
+    }
+
+    fn row(&mut self, tokens: &[&str], line: usize) -> Result<(), ModelFileError> {
+        if tokens.len() != 2 {
+            return Err(syntax(line, "expected a row type and name"));
+        }
+        let row_type = tokens[0].to_ascii_uppercase();
+        let row = match row_type.as_str() {
+            "N" if !self.has_objective => {
+                self.has_objective = true;
+                MpsRow::Objective
+            }
+            "N" => MpsRow::Free,
+            "E" | "L" | "G" => {
+                let k = self.model.add_constraint(
+                    tokens[1].to_string(),
+                    Number::NEG_INFINITY,
+                    Number::INFINITY,
+                );
+                self.row_types.push(row_type.chars().next().unwrap());
+                self.rhs.push(0.0);
+                self.ranges.push(None);
+                MpsRow::Constraint(k)
+            }
+            _ => return Err(syntax(line, format!("unknown row type {}", tokens[0]))),
+        };
+        self.rows.insert(tokens[1].to_string(), row);
+        Ok(())
+    }
+
+    fn find_row(&self, name: &str, line: usize) -> Result<MpsRow, ModelFileError> {
+        match self.rows.get(name) {
+            Some(&row) => Ok(row),
+            None => Err(syntax(line, format!("unknown row {}", name))),
+        }
+    }
+
+    fn find_column(&self, name: &str, line: usize) -> Result<usize, ModelFileError> {
+        match self.model.variables.get(name) {
+            Some(&i) => Ok(i),
+            None => Err(syntax(line, format!("unknown column {}", name))),
+        }
+    }
+
+    fn column(&mut self, tokens: &[&str], line: usize) -> Result<(), ModelFileError> {
+        if tokens.len() >= 3 && tokens[1].trim_matches('\'').eq_ignore_ascii_case("MARKER") {
+            match tokens[2].trim_matches('\'').to_ascii_uppercase().as_str() {
+                "INTORG" => self.in_integer_block = true,
+                "INTEND" => self.in_integer_block = false,
+                _ => return Err(syntax(line, format!("unknown marker {}", tokens[2]))),
+            }
+            return Ok(());
+        }
+        if tokens.len() != 3 && tokens.len() != 5 {
+            return Err(syntax(
+                line,
+                "expected a column name and one or two row entries",
+            ));
+        }
+        let col = self.model.variable(tokens[0]);
+        if self.in_integer_block {
+            self.model.integer[col] = true;
+        }
+        for entry in tokens[1..].chunks(2) {
+            let value = parse_number(entry[1], line)?;
+            match self.find_row(entry[0], line)? {
+                MpsRow::Objective => self.model.linear[col] += value,
+                MpsRow::Free => {}
+                MpsRow::Constraint(k) => self.model.jacobian.push((k, col, value)),
+            }
+        }
+        Ok(())
+    }
+
+    /// The row entries of an `RHS` or `RANGES` line, whose set name is optional.
+    fn row_entries<'a>(
+        tokens: &'a [&'a str],
+        line: usize,
+    ) -> Result<&'a [&'a str], ModelFileError> {
+        match tokens.len() {
+            2 | 4 => Ok(tokens),
+            3 | 5 => Ok(&tokens[1..]),
+            _ => Err(syntax(line, "expected one or two row entries")),
+        }
+    }
+
+    fn rhs(&mut self, tokens: &[&str], line: usize) -> Result<(), ModelFileError> {
+        for entry in Self::row_entries(tokens, line)?.chunks(2) {
+            let value = parse_number(entry[1], line)?;
+            match self.find_row(entry[0], line)? {
+                // The right-hand side of the objective is its negated constant.
+                MpsRow::Objective => self.model.constant = -value,
+                MpsRow::Free => {}
+                MpsRow::Constraint(k) => self.rhs[k] = value,
+            }
+        }
+        Ok(())
+    }
+
+    fn range(&mut self, tokens: &[&str], line: usize) -> Result<(), ModelFileError> {
+        for entry in Self::row_entries(tokens, line)?.chunks(2) {
+            let value = parse_number(entry[1], line)?;
+            match self.find_row(entry[0], line)? {
+                MpsRow::Constraint(k) => self.ranges[k] = Some(value),
+                _ => return Err(syntax(line, "ranges are only allowed on constraint rows")),
+            }
+        }
+        Ok(())
+    }
+
+    fn bound(&mut self, tokens: &[&str], line: usize) -> Result<(), ModelFileError> {
+        let bound_type = tokens[0].to_ascii_uppercase();
+        let has_value = match bound_type.as_str() {
+            "UP" | "LO" | "FX" | "LI" | "UI" => true,
+            "FR" | "MI" | "PL" | "BV" => false,
+            "SC" => return Err(unsupported(line, "semi-continuous variables")),
+            _ => return Err(syntax(line, format!("unknown bound type {}", tokens[0]))),
+        };
+        // The bound set name is optional.
+        let (name, value) = match (has_value, tokens.len()) {
+            (true, 4) => (tokens[2], Some(tokens[3])),
+            (true, 3) => (tokens[1], Some(tokens[2])),
+            (false, 3) => (tokens[2], None),
+            (false, 2) => (tokens[1], None),
+            _ => return Err(syntax(line, "expected a bound type, column name and value")),
+        };
+        let col = self.find_column(name, line)?;
+        let value = match value {
+            Some(value) => parse_number(value, line)?,
+            None => 0.0,
+        };
+        let m = &mut self.model;
+        match bound_type.as_str() {
+            "UP" | "UI" => {
+                // A negative upper bound on a variable with the default lower bound makes the
+                // variable unbounded below, as in most MPS readers.
+                if value < 0.0 && m.x_l[col] == 0.0 {
+                    m.x_l[col] = Number::NEG_INFINITY;
+                }
+                m.x_u[col] = value;
+            }
+            "LO" | "LI" => m.x_l[col] = value,
+            "FX" => {
+                m.x_l[col] = value;
+                m.x_u[col] = value;
+            }
+            "FR" => {
+                m.x_l[col] = Number::NEG_INFINITY;
+                m.x_u[col] = Number::INFINITY;
+            }
+            "MI" => m.x_l[col] = Number::NEG_INFINITY,
+            "PL" => m.x_u[col] = Number::INFINITY,
+            "BV" => {
+                m.x_l[col] = 0.0;
+                m.x_u[col] = 1.0;
+            }
+            _ => unreachable!(),
+        }
+        if bound_type == "LI" || bound_type == "UI" || bound_type == "BV" {
+            m.integer[col] = true;
+        }
+        Ok(())
+    }
+
+    /// An entry of `Q`. `QUADOBJ` lists one triangle and `QMATRIX` both.
+    fn quadratic(
+        &mut self,
+        tokens: &[&str],
+        line: usize,
+        full: bool,
+    ) -> Result<(), ModelFileError> {
+        if tokens.len() != 3 {
+            return Err(syntax(line, "expected two column names and a value"));
+        }
+        let i = self.find_column(tokens[0], line)?;
+        let j = self.find_column(tokens[1], line)?;
+        let value = parse_number(tokens[2], line)?;
+        if !full || i >= j {
+            self.model.quadratic.push((i, j, value));
+        }
+        Ok(())
+    }
+}
+
+/*
+ * LP format
+ */
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(Number),
+    Name(String),
+    Op(&'static str),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum LpSection {
+    Objective,
+    Constraints,
+    Bounds,
+    Integer,
+    Binary,
+}
+
+/// Characters allowed in names besides letters and digits.
+const NAME_CHARS: &str = "!\"#$%&()_,.;?@`'{}|~";
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || NAME_CHARS.contains(c)
+}
+
+/// Recognize a section keyword at the start of a line, returning the section and the rest of
+/// the line. The section is `None` at the `End` keyword.
+fn lp_section(line: &str) -> Option<(Option<LpSection>, &str)> {
+    let lower = line.to_ascii_lowercase();
+    for &(keyword, section) in [
+        ("subject to", Some(LpSection::Constraints)),
+        ("such that", Some(LpSection::Constraints)),
+    ]
+    .iter()
+    {
+        if lower.starts_with(keyword) {
+            return Some((section, &line[keyword.len()..]));
+        }
+    }
+    let word_len = line.find(char::is_whitespace).unwrap_or(line.len());
+    let section = match &lower[..word_len] {
+        "minimize" | "minimise" | "minimum" | "min" | "maximize" | "maximise" | "maximum"
+        | "max" => Some(LpSection::Objective),
+        "st" | "s.t." | "st." => Some(LpSection::Constraints),
+        "bounds" | "bound" => Some(LpSection::Bounds),
+        "general" | "generals" | "gen" | "integer" | "integers" => Some(LpSection::Integer),
+        "binary" | "binaries" | "bin" => Some(LpSection::Binary),
+        "end" => None,
+        _ => return None,
+    };
+    Some((section, &line[word_len..]))
+}
+
+fn tokenize(
+    line: &str,
+    line_no: usize,
+    tokens: &mut Vec<(usize, Token)>,
+) -> Result<(), ModelFileError> {
+    let mut chars = line.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let token = match c {
+            _ if c.is_whitespace() => continue,
+            '0'..='9' | '.' => {
+                let mut end = start + 1;
+                while let Some(&(i, c)) = chars.peek() {
+                    let exponent = (c == 'e' || c == 'E') && {
+                        let rest = &line[i + 1..];
+                        rest.starts_with(|c: char| c.is_ascii_digit())
+                            || ((rest.starts_with('+') || rest.starts_with('-'))
+                                && rest[1..].starts_with(|c: char| c.is_ascii_digit()))
+                    };
+                    if c.is_ascii_digit() || c == '.' {
+                        end = i + 1;
+                        chars.next();
+                    } else if exponent {
+                        // Consume the exponent marker and its sign.
+                        chars.next();
+                        end = i + 1;
+                        match chars.peek() {
+                            Some(&(j, '+')) | Some(&(j, '-')) => {
+                                end = j + 1;
+                                chars.next();
+                            }
+                            _ => {}
+                        }
+                    } else {
+                        break;
+                    }
+                }
+                Token::Number(parse_number(&line[start..end], line_no)?)
+            }
+            '<' | '>' | '=' => {
+                let op = match (c, chars.peek().map(|&(_, c)| c)) {
+                    ('<', Some('=')) | ('=', Some('<')) => {
+                        chars.next();
+                        "<="
+                    }
+                    ('>', Some('=')) | ('=', Some('>')) => {
+                        chars.next();
+                        ">="
+                    }
+                    ('<', _) => "<=",
+                    ('>', _) => ">=",
+                    _ => "=",
+                };
+                Token::Op(op)
+            }
+            '+' => Token::Op("+"),
+            '-' => Token::Op("-"),
+            '*' => Token::Op("*"),
+            '^' => Token::Op("^"),
+            '[' => Token::Op("["),
+            ']' => Token::Op("]"),
+            '/' => Token::Op("/"),
+            ':' => Token::Op(":"),
+            _ if is_name_char(c) => {
+                let mut end = start + c.len_utf8();
+                while let Some(&(i, c)) = chars.peek() {
+                    if !is_name_char(c) {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                Token::Name(line[start..end].to_string())
+            }
+            _ => return Err(syntax(line_no, format!("unexpected character '{}'", c))),
+        };
+        tokens.push((line_no, token));
+    }
+    Ok(())
+}
+
+fn read_lp<R: BufRead>(input: R) -> Result<QuadraticProgram, ModelFileError> {
+    let mut model = ModelBuilder::default();
+    let mut sections: Vec<(LpSection, Vec<(usize, Token)>)> = Vec::new();
+    let mut last_line = 0;
+    for (i, line) in input.lines().enumerate() {
+        let line = line?;
+        last_line = i + 1;
+        let line = match line.find('\\') {
+            Some(comment) => &line[..comment],
+            None => &line[..],
+        };
+        let mut rest = line.trim();
+        if rest.is_empty() {
+            continue;
+        }
+        if let Some((section, after)) = lp_section(rest) {
+            match section {
+                Some(section) => {
+                    if section == LpSection::Objective
+                        && rest.to_ascii_lowercase().starts_with("max")
+                    {
+                        model.sense = ObjectiveSense::Maximize;
+                    }
+                    sections.push((section, Vec::new()));
+                    rest = after;
+                }
+                None => break,
+            }
+        }
+        match sections.last_mut() {
+            Some((_, tokens)) => tokenize(rest, last_line, tokens)?,
+            None => return Err(syntax(last_line, "expected Minimize or Maximize")),
+        }
+    }
+
+    for (section, tokens) in sections.iter() {
+        let mut parser = LpParser {
+            tokens,
+            pos: 0,
+            last_line,
+            model: &mut model,
+        };
+        match section {
+            LpSection::Objective => parser.objective()?,
+            LpSection::Constraints => {
+                while !parser.at_end() {
+                    parser.constraint()?;
+                }
+            }
+            LpSection::Bounds => {
+                while !parser.at_end() {
+                    parser.bound()?;
+                }
+            }
+            LpSection::Integer | LpSection::Binary => {
+                while let Some(name) = parser.name() {
+                    let i = parser.model.variable(&name);
+                    parser.model.integer[i] = true;
+                    if *section == LpSection::Binary {
+                        parser.model.x_l[i] = 0.0;
+                        parser.model.x_u[i] = 1.0;
+                    }
+                }
+                if !parser.at_end() {
+                    return Err(syntax(parser.line(), "expected a variable name"));
+                }
+            }
+        }
+    }
+    Ok(model.build())
+}
+
+/// A linear expression with quadratic monomials, as written in an LP file.
+#[derive(Default)]
+struct Expression {
+    linear: Vec<(usize, Number)>,
+    /// Coefficients of the monomials `x_i x_j`.
+    quadratic: Vec<(usize, usize, Number)>,
+    constant: Number,
+}
+
+/// Either side of a bound.
+enum Operand {
+    Value(Number),
+    Variable(String),
+}
+
+struct LpParser<'a> {
+    tokens: &'a [(usize, Token)],
+    pos: usize,
+    last_line: usize,
+    model: &'a mut ModelBuilder,
+}
+
+impl<'a> LpParser<'a> {
+    fn at_end(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&'a Token> {
+        self.tokens.get(self.pos + offset).map(|(_, t)| t)
+    }
+
+    fn peek(&self) -> Option<&'a Token> {
+        self.peek_at(0)
+    }
+
+    fn line(&self) -> usize {
+        match self.tokens.get(self.pos) {
+            Some(&(line, _)) => line,
+            None => self.last_line,
+        }
+    }
+
+    fn is_op(&self, op: &str) -> bool {
+        match self.peek() {
+            Some(Token::Op(next)) => *next == op,
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, op: &str) -> Result<(), ModelFileError> {
+        if self.is_op(op) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(syntax(self.line(), format!("expected '{}'", op)))
+        }
+    }
+
+    /// Consume a name that is not followed by a colon.
+    fn name(&mut self) -> Option<String> {
+        match self.peek() {
+            Some(Token::Name(name)) if self.peek_at(1) != Some(&Token::Op(":")) => {
+                self.pos += 1;
+                Some(name.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// Consume a `name:` label.
+    fn label(&mut self) -> Option<String> {
+        match (self.peek(), self.peek_at(1)) {
+            (Some(Token::Name(name)), Some(Token::Op(":"))) => {
+                self.pos += 2;
+                Some(name.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// Consume a relation, which is one of `<=`, `>=` and `=`.
+    fn relation(&mut self) -> Option<&'static str> {
+        match self.peek() {
+            Some(&Token::Op(op)) if op == "<=" || op == ">=" || op == "=" => {
+                self.pos += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    /// Consume any number of signs, returning the combined sign.
+    fn signs(&mut self) -> Option<Number> {
+        let mut sign = None;
+        loop {
+            if self.is_op("+") {
+                sign = Some(sign.unwrap_or(1.0));
+            } else if self.is_op("-") {
+                sign = Some(-sign.unwrap_or(1.0));
+            } else {
+                return sign;
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Consume a signed number, where `inf` and `infinity` are accepted.
+    fn value(&mut self) -> Option<Number> {
+        let start = self.pos;
+        let sign = self.signs().unwrap_or(1.0);
+        let value = match self.peek() {
+            Some(&Token::Number(v)) => v,
+            Some(Token::Name(name))
+                if name.eq_ignore_ascii_case("inf") || name.eq_ignore_ascii_case("infinity") =>
+            {
+                Number::INFINITY
+            }
+            _ => {
+                self.pos = start;
+                return None;
+            }
+        };
+        self.pos += 1;
+        Some(sign * value)
+    }
+
+    fn expression(&mut self) -> Result<Expression, ModelFileError> {
+        let mut expr = Expression::default();
+        let mut first = true;
+        loop {
+            let signs = self.signs();
+            let sign = match signs {
+                Some(sign) => sign,
+                None if first => 1.0,
+                None => break,
+            };
+            let mut coefficient = sign;
+            let mut explicit = signs.is_some();
+            if let Some(&Token::Number(v)) = self.peek() {
+                self.pos += 1;
+                coefficient *= v;
+                explicit = true;
+                if self.is_op("*") {
+                    self.pos += 1;
+                } else if !self.name_follows() && !self.is_op("[") {
+                    expr.constant += coefficient;
+                    first = false;
+                    continue;
+                }
+            }
+            if self.is_op("[") {
+                self.quadratic(coefficient, &mut expr)?;
+            } else if let Some(name) = self.name() {
+                if self.is_op("^") || self.is_op("*") {
+                    return Err(syntax(
+                        self.line(),
+                        "quadratic terms must be enclosed in brackets",
+                    ));
+                }
+                let i = self.model.variable(&name);
+                expr.linear.push((i, coefficient));
+            } else if first && !explicit {
+                // An empty expression.
+                break;
+            } else {
+                return Err(syntax(self.line(), "expected a term"));
+            }
+            first = false;
+        }
+        Ok(expr)
+    }
+
+    fn name_follows(&self) -> bool {
+        match (self.peek(), self.peek_at(1)) {
+            (Some(Token::Name(_)), Some(Token::Op(":"))) => false,
+            (Some(Token::Name(_)), _) => true,
+            _ => false,
+        }
+    }
+
+    /// Parse `[ ... ]` or `[ ... ] / d` scaled by `factor`.
+    fn quadratic(&mut self, factor: Number, expr: &mut Expression) -> Result<(), ModelFileError> {
+        self.expect("[")?;
+        let mut terms = Vec::new();
+        let mut first = true;
+        while !self.is_op("]") {
+            let mut coefficient = match self.signs() {
+                Some(sign) => sign,
+                None if first => 1.0,
+                None => return Err(syntax(self.line(), "expected '+', '-' or ']'")),
+            };
+            if let Some(&Token::Number(v)) = self.peek() {
+                self.pos += 1;
+                coefficient *= v;
+            }
+            let x = self
+                .name()
+                .ok_or_else(|| syntax(self.line(), "expected a variable name"))?;
+            let i = self.model.variable(&x);
+            let j = if self.is_op("^") {
+                self.pos += 1;
+                match self.peek() {
+                    Some(&Token::Number(2.0)) => self.pos += 1,
+                    _ => return Err(syntax(self.line(), "only squares are supported")),
+                }
+                i
+            } else {
+                self.expect("*")?;
+                let y = self
+                    .name()
+                    .ok_or_else(|| syntax(self.line(), "expected a variable name"))?;
+                self.model.variable(&y)
+            };
+            terms.push((i, j, coefficient));
+            first = false;
+        }
+        self.pos += 1;
+        let mut divisor = 1.0;
+        if self.is_op("/") {
+            self.pos += 1;
+            divisor = match self.peek() {
+                Some(&Token::Number(d)) if d != 0.0 => d,
+                _ => return Err(syntax(self.line(), "expected a non-zero divisor")),
+            };
+            self.pos += 1;
+        }
+        for (i, j, a) in terms {
+            expr.quadratic.push((i, j, factor * a / divisor));
+        }
+        Ok(())
+    }
+
+    fn objective(&mut self) -> Result<(), ModelFileError> {
+        self.label();
+        let expr = self.expression()?;
+        if !self.at_end() {
+            return Err(syntax(self.line(), "unexpected token in the objective"));
+        }
+        for (i, a) in expr.linear {
+            self.model.linear[i] += a;
+        }
+        // x'Qx/2 has Q_ii/2 for each square and Q_ij for each product of distinct variables.
+        for (i, j, a) in expr.quadratic {
+            let q = if i == j { 2.0 * a } else { a };
+            self.model.quadratic.push((i, j, q));
+        }
+        self.model.constant += expr.constant;
+        Ok(())
+    }
+
+    fn constraint(&mut self) -> Result<(), ModelFileError> {
+        let line = self.line();
+        let label = self.label();
+        let name = label.unwrap_or_else(|| format!("c{}", self.model.constraint_names.len()));
+
+        // A ranged constraint `lower <= a·x <= upper`.
+        let start = self.pos;
+        let range = match self.value() {
+            Some(value) => match self.relation() {
+                Some(op) => Some((value, op)),
+                None => {
+                    self.pos = start;
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let expr = self.expression()?;
+        if !expr.quadratic.is_empty() {
+            return Err(unsupported(line, "quadratic constraints"));
+        }
+        let op = self
+            .relation()
+            .ok_or_else(|| syntax(self.line(), "expected a relation"))?;
+        let rhs = self
+            .value()
+            .ok_or_else(|| syntax(self.line(), "expected a number"))?;
+
+        let (lower, upper) = match (range, op) {
+            (None, "<=") => (Number::NEG_INFINITY, rhs),
+            (None, ">=") => (rhs, Number::INFINITY),
+            (None, _) => (rhs, rhs),
+            (Some((lhs, "<=")), "<=") => (lhs, rhs),
+            (Some((lhs, ">=")), ">=") => (rhs, lhs),
+            _ => return Err(syntax(line, "invalid ranged constraint")),
+        };
+        let k = self
+            .model
+            .add_constraint(name, lower - expr.constant, upper - expr.constant);
+        for (i, a) in expr.linear {
+            self.model.jacobian.push((k, i, a));
+        }
+        Ok(())
+    }
+
+    fn operand(&mut self) -> Result<Operand, ModelFileError> {
+        if let Some(value) = self.value() {
+            return Ok(Operand::Value(value));
+        }
+        match self.name() {
+            Some(name) => Ok(Operand::Variable(name)),
+            None => Err(syntax(self.line(), "expected a number or a variable name")),
+        }
+    }
+
+    fn bound(&mut self) -> Result<(), ModelFileError> {
+        let line = self.line();
+        if let (Some(Token::Name(name)), Some(Token::Name(free))) = (self.peek(), self.peek_at(1)) {
+            if free.eq_ignore_ascii_case("free") {
+                self.pos += 2;
+                let i = self.model.variable(name);
+                self.model.x_l[i] = Number::NEG_INFINITY;
+                self.model.x_u[i] = Number::INFINITY;
+                return Ok(());
+            }
+        }
+        let invalid = || syntax(line, "invalid bound");
+        let first = self.operand()?;
+        let op = self.relation().ok_or_else(invalid)?;
+        let second = self.operand()?;
+        let (name, lower, upper) = match (first, second) {
+            (Operand::Variable(name), Operand::Value(v)) => match op {
+                "<=" => (name, None, Some(v)),
+                ">=" => (name, Some(v), None),
+                _ => (name, Some(v), Some(v)),
+            },
+            (Operand::Value(v), Operand::Variable(name)) => {
+                let (lower, upper) = match op {
+                    "<=" => (Some(v), None),
+                    ">=" => (None, Some(v)),
+                    _ => (Some(v), Some(v)),
+                };
+                // `l <= x <= u` or `u >= x >= l`.
+                match self.relation() {
+                    Some(second_op) => {
+                        let w = self.value().ok_or_else(invalid)?;
+                        match (op, second_op) {
+                            ("<=", "<=") => (name, lower, Some(w)),
+                            (">=", ">=") => (name, Some(w), upper),
+                            _ => return Err(invalid()),
+                        }
+                    }
+                    None => (name, lower, upper),
+                }
+            }
+            _ => return Err(invalid()),
+        };
+        let i = self.model.variable(&name);
+        if let Some(lower) = lower {
+            self.model.x_l[i] = lower;
+        }
+        if let Some(upper) = upper {
+            self.model.x_u[i] = upper;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MPS: &str = "\
+* A small QP with every kind of row and bound.
+NAME          example
+OBJSENSE
+    MAX
+ROWS
+ N  obj
+ L  lim
+ G  low
+ E  eq
+ E  rng
+COLUMNS
+    x         obj       1.0        lim       1.0
+    x         low       1.0        rng       1.0
+    y         obj       -2.0       lim       1.0
+    y         eq        1.0
+    z         eq        -1.0       rng       2.0
+RHS
+    rhs       obj       -3.0       lim       4.0
+    rhs       low       -1.0       eq        0.5
+    rhs       rng       1.0
+RANGES
+    rng       rng       -2.0
+BOUNDS
+ UP bnd       x         10.0
+ FR bnd       y
+ MI bnd       z
+ UP bnd       z         5.0
+QUADOBJ
+    x         x         2.0
+    x         y         -1.0
+    z         z         4.0
+ENDATA
+";
+
+    const LP: &str = "\
+\\ The same QP in LP format.
+Maximize
+ obj: x - 2 y + 3 + [ 2 x^2 - 2 x * y + 4 z ^ 2 ] / 2
+Subject To
+ lim: x + y <= 4
+ low: x >= -1
+ eq: y - z = 0.5
+ rng: -1 <= x + 2 z <= 1
+Bounds
+ 0 <= x <= 10
+ y free
+ -inf <= z <= 5
+End
+";
+
+    /// Evaluate every callback at a point, with the Hessian expanded to a dense matrix.
+    fn evaluate(qp: &QuadraticProgram, x: &[Number]) -> Vec<Vec<Number>> {
+        let n = qp.num_variables();
+        let m = qp.num_constraints();
+        let (mut x_l, mut x_u) = (vec![0.0; n], vec![0.0; n]);
+        let (mut g_l, mut g_u) = (vec![0.0; m], vec![0.0; m]);
+        let (mut f, mut grad, mut g) = (0.0, vec![0.0; n], vec![0.0; m]);
+        assert!(qp.bounds(&mut x_l, &mut x_u));
+        assert!(qp.constraint_bounds(&mut g_l, &mut g_u));
+        assert!(qp.objective(x, &mut f));
+        assert!(qp.objective_grad(x, &mut grad));
+        assert!(qp.constraint(x, &mut g));
+        let nnz = qp.num_hessian_non_zeros();
+        let (mut rows, mut cols, mut vals) = (vec![0; nnz], vec![0; nnz], vec![0.0; nnz]);
+        assert!(qp.hessian_indices(&mut rows, &mut cols));
+        assert!(qp.hessian_values(x, 1.0, &g, &mut vals));
+        let mut hess = vec![0.0; n * n];
+        for k in 0..nnz {
+            hess[rows[k] as usize * n + cols[k] as usize] += vals[k];
+        }
+        vec![x_l, x_u, g_l, g_u, vec![f], grad, g, hess]
+    }
+
+    #[test]
+    fn read_test() {
+        let mps = QuadraticProgram::read_mps(MPS.as_bytes()).unwrap();
+        let lp = QuadraticProgram::read_lp(LP.as_bytes()).unwrap();
+        assert_eq!(mps.name(), "example");
+        assert_eq!(mps.objective_sense(), ObjectiveSense::Maximize);
+        assert_eq!(lp.objective_sense(), ObjectiveSense::Maximize);
+        assert_eq!(mps.variable_names(), lp.variable_names());
+        assert_eq!(mps.constraint_names(), lp.constraint_names());
+        assert!(!mps.is_linear());
+
+        let x = [1.0, -2.0, 0.5];
+        let expected = evaluate(&mps, &x);
+        assert_eq!(expected[2], vec![Number::NEG_INFINITY, -1.0, 0.5, -1.0]);
+        assert_eq!(expected[3], vec![4.0, Number::INFINITY, 0.5, 1.0]);
+        // x - 2y + 3 + x^2 - xy + 2z^2
+        assert_eq!(expected[4], vec![1.0 + 4.0 + 3.0 + 1.0 + 2.0 + 0.5]);
+        assert_eq!(evaluate(&lp, &x), expected);
+
+        // Reading back an exported program gives the same program up to names.
+        let mut out = Vec::new();
+        crate::write_mps(&mps, &x, "example", &mut out).unwrap();
+        let exported = QuadraticProgram::read_mps(out.as_slice()).unwrap();
+        let actual = evaluate(&exported, &x);
+        for (a, b) in actual.iter().flatten().zip(expected.iter().flatten()) {
+            assert!(a == b || (a - b).abs() < 1e-12, "{} != {}", a, b);
+        }
+
+        match QuadraticProgram::read_lp("Minimize\n obj: x\nSubject To\n [ x^2 ] <= 1\n".as_bytes())
+        {
+            Err(ModelFileError::Unsupported { line: 4, .. }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
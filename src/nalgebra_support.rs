@@ -0,0 +1,275 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Interoperability with [`nalgebra`](https://nalgebra.org).
+//!
+//! Problems written in terms of `nalgebra` types implement
+//! [`NalgebraBasicProblem`](trait.NalgebraBasicProblem.html) and optionally
+//! [`NalgebraConstrainedProblem`](trait.NalgebraConstrainedProblem.html). Wrapping such a problem
+//! in [`Nalgebra`](struct.Nalgebra.html) makes it usable with [`Ipopt`](struct.Ipopt.html).
+//!
+//! All vector arguments are views into the buffers owned by Ipopt, so no copies are made. The
+//! constraint Jacobian is treated as a dense matrix stored in column-major order, which matches
+//! the storage of `nalgebra` matrices, so it is also written directly into the Ipopt buffer.
+
+use crate::{BasicProblem, ConstrainedProblem, Index, Number, Solution};
+
+use nalgebra::{DMatrix, DMatrixViewMut, DVectorView, DVectorViewMut};
+
+/// View a slice as an `nalgebra` column vector.
+pub fn dvector_view<'a>(x: &'a [Number]) -> DVectorView<'a, Number> {
+    DVectorView::from_slice(x, x.len())
+}
+
+/// View a mutable slice as an `nalgebra` column vector.
+pub fn dvector_view_mut<'a>(x: &'a mut [Number]) -> DVectorViewMut<'a, Number> {
+    let n = x.len();
+    DVectorViewMut::from_slice(x, n)
+}
+
+/// Fill the sparsity pattern of a dense `nrows` by `ncols` matrix in column-major order using
+/// zero-based indices.
+///
+/// Values of a dense `nalgebra` matrix can then be copied directly from `DMatrix::as_slice`.
+/// The output slices must have size `nrows * ncols`.
+pub fn dense_indices(nrows: usize, ncols: usize, rows: &mut [Index], cols: &mut [Index]) {
+    let mut k = 0;
+    for j in 0..ncols {
+        for i in 0..nrows {
+            rows[k] = i as Index;
+            cols[k] = j as Index;
+            k += 1;
+        }
+    }
+}
+
+/// Fill the sparsity pattern of the lower triangular part of a dense symmetric `n` by `n` matrix
+/// in column-major order using zero-based indices.
+///
+/// The output slices must have size `n * (n + 1) / 2`.
+pub fn dense_lower_triangular_indices(n: usize, rows: &mut [Index], cols: &mut [Index]) {
+    let mut k = 0;
+    for j in 0..n {
+        for i in j..n {
+            rows[k] = i as Index;
+            cols[k] = j as Index;
+            k += 1;
+        }
+    }
+}
+
+/// Copy the lower triangular part of a square matrix into `vals` in column-major order.
+///
+/// This matches the sparsity pattern produced by
+/// [`dense_lower_triangular_indices`](fn.dense_lower_triangular_indices.html).
+pub fn copy_lower_triangular(mtx: &DMatrix<Number>, vals: &mut [Number]) {
+    let n = mtx.nrows();
+    let mut k = 0;
+    for j in 0..n {
+        for i in j..n {
+            vals[k] = mtx[(i, j)];
+            k += 1;
+        }
+    }
+}
+
+/// A version of [`BasicProblem`](trait.BasicProblem.html) with callbacks taking `nalgebra`
+/// vectors.
+pub trait NalgebraBasicProblem {
+    /// Total number of variables of the non-linear problem.
+    fn num_variables(&self) -> usize;
+
+    /// Specify lower and upper variable bounds given by `x_l` and `x_u` respectively.
+    fn bounds(&self, x_l: DVectorViewMut<Number>, x_u: DVectorViewMut<Number>) -> bool;
+
+    /// Construct the initial guess of the primal variables for Ipopt to start with.
+    ///
+    /// If this function returns `false`, then a zero initial guess will be used.
+    fn initial_point(&self, x: DVectorViewMut<Number>) -> bool;
+
+    /// Objective function. This is the function being minimized.
+    fn objective(&self, x: DVectorView<Number>, obj: &mut Number) -> bool;
+
+    /// The gradient of the objective function.
+    fn objective_grad(&self, x: DVectorView<Number>, grad_f: DVectorViewMut<Number>) -> bool;
+}
+
+/// A version of [`ConstrainedProblem`](trait.ConstrainedProblem.html) with callbacks taking
+/// `nalgebra` vectors and dense matrices.
+pub trait NalgebraConstrainedProblem: NalgebraBasicProblem {
+    /// Number of equality and inequality constraints.
+    fn num_constraints(&self) -> usize;
+
+    /// Constraint function.
+    fn constraint(&self, x: DVectorView<Number>, g: DVectorViewMut<Number>) -> bool;
+
+    /// Specify lower and upper bounds, `g_l` and `g_u` respectively, on the constraint function.
+    fn constraint_bounds(&self, g_l: DVectorViewMut<Number>, g_u: DVectorViewMut<Number>) -> bool;
+
+    /// Construct the initial guess of the constraint multipliers for Ipopt to start with.
+    ///
+    /// For convenience, the default implementation initializes constraint multipliers to zero.
+    fn initial_constraint_multipliers(&self, mut lambda: DVectorViewMut<Number>) -> bool {
+        lambda.fill(0.0);
+        true
+    }
+
+    /// The dense constraint Jacobian with `num_constraints` rows and `num_variables` columns.
+    fn constraint_jacobian(&self, x: DVectorView<Number>, jac: DMatrixViewMut<Number>) -> bool;
+
+    /// The dense Hessian of the Lagrangian.
+    ///
+    /// The output is a zeroed `num_variables` by `num_variables` matrix. Only the lower
+    /// triangular part is passed to Ipopt, so the upper triangular part need not be filled.
+    /// Write the objective Hessian multiplied by `obj_factor` and constraint Hessians multiplied
+    /// by the corresponding values in `lambda`.
+    fn hessian(
+        &self,
+        x: DVectorView<Number>,
+        obj_factor: Number,
+        lambda: DVectorView<Number>,
+        hess: &mut DMatrix<Number>,
+    ) -> bool;
+}
+
+/// An adapter implementing the Ipopt problem traits for problems defined with `nalgebra` types.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Nalgebra<P>(pub P);
+
+impl<P: NalgebraBasicProblem> BasicProblem for Nalgebra<P> {
+    fn num_variables(&self) -> usize {
+        self.0.num_variables()
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        self.0.bounds(dvector_view_mut(x_l), dvector_view_mut(x_u))
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        self.0.initial_point(dvector_view_mut(x))
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        self.0.objective(dvector_view(x), obj)
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        self.0
+            .objective_grad(dvector_view(x), dvector_view_mut(grad_f))
+    }
+}
+
+impl<P: NalgebraConstrainedProblem> ConstrainedProblem for Nalgebra<P> {
+    fn num_constraints(&self) -> usize {
+        self.0.num_constraints()
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        self.0.num_constraints() * self.0.num_variables()
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        self.0.constraint(dvector_view(x), dvector_view_mut(g))
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        self.0
+            .constraint_bounds(dvector_view_mut(g_l), dvector_view_mut(g_u))
+    }
+    fn initial_constraint_multipliers(&self, lambda: &mut [Number]) -> bool {
+        self.0
+            .initial_constraint_multipliers(dvector_view_mut(lambda))
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        dense_indices(self.0.num_constraints(), self.0.num_variables(), rows, cols);
+        true
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        let jac = DMatrixViewMut::from_slice(vals, self.0.num_constraints(), x.len());
+        self.0.constraint_jacobian(dvector_view(x), jac)
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        let n = self.0.num_variables();
+        n * (n + 1) / 2
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        dense_lower_triangular_indices(self.0.num_variables(), rows, cols);
+        true
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        let mut hess = DMatrix::zeros(x.len(), x.len());
+        if !self
+            .0
+            .hessian(dvector_view(x), obj_factor, dvector_view(lambda), &mut hess)
+        {
+            return false;
+        }
+        copy_lower_triangular(&hess, vals);
+        true
+    }
+}
+
+impl<'a> Solution<'a> {
+    /// The primal variables as an `nalgebra` vector view.
+    ///
+    /// Use `into_owned` to get a `DVector`.
+    pub fn primal_variables_dvector(&self) -> DVectorView<'a, Number> {
+        dvector_view(self.primal_variables)
+    }
+
+    /// The lower bound multipliers as an `nalgebra` vector view.
+    pub fn lower_bound_multipliers_dvector(&self) -> DVectorView<'a, Number> {
+        dvector_view(self.lower_bound_multipliers)
+    }
+
+    /// The upper bound multipliers as an `nalgebra` vector view.
+    pub fn upper_bound_multipliers_dvector(&self) -> DVectorView<'a, Number> {
+        dvector_view(self.upper_bound_multipliers)
+    }
+
+    /// The constraint multipliers as an `nalgebra` vector view.
+    pub fn constraint_multipliers_dvector(&self) -> DVectorView<'a, Number> {
+        dvector_view(self.constraint_multipliers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dense_structure_test() {
+        let mut rows = [0; 6];
+        let mut cols = [0; 6];
+        dense_indices(2, 3, &mut rows, &mut cols);
+        assert_eq!(rows, [0, 1, 0, 1, 0, 1]);
+        assert_eq!(cols, [0, 0, 1, 1, 2, 2]);
+
+        // The dense layout matches nalgebra storage.
+        let jac = DMatrix::from_row_slice(2, 3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        for (k, &v) in jac.as_slice().iter().enumerate() {
+            assert_eq!(jac[(rows[k] as usize, cols[k] as usize)], v);
+        }
+
+        let mut rows = [0; 6];
+        let mut cols = [0; 6];
+        dense_lower_triangular_indices(3, &mut rows, &mut cols);
+        assert_eq!(rows, [0, 1, 2, 1, 2, 2]);
+        assert_eq!(cols, [0, 0, 0, 1, 1, 2]);
+
+        let hess = DMatrix::from_row_slice(3, 3, &[1.0, 0.0, 0.0, 2.0, 4.0, 0.0, 3.0, 5.0, 6.0]);
+        let mut vals = [0.0; 6];
+        copy_lower_triangular(&hess, &mut vals);
+        assert_eq!(vals, [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    }
+}
@@ -0,0 +1,300 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Interoperability with [`ndarray`](https://docs.rs/ndarray).
+//!
+//! Problems written in terms of `ndarray` types implement
+//! [`NdarrayBasicProblem`](trait.NdarrayBasicProblem.html) and optionally
+//! [`NdarrayConstrainedProblem`](trait.NdarrayConstrainedProblem.html). Wrapping such a problem
+//! in [`Ndarray`](struct.Ndarray.html) makes it usable with [`Ipopt`](struct.Ipopt.html).
+//!
+//! All array arguments are views into the buffers owned by Ipopt, so no copies are made. The
+//! constraint Jacobian is treated as a dense matrix in standard (row-major) layout, so it is also
+//! written directly into the Ipopt buffer.
+
+use crate::{BasicProblem, ConstrainedProblem, Index, Number, Solution};
+
+use ndarray::{Array2, ArrayView1, ArrayViewMut1, ArrayViewMut2};
+
+/// A version of [`BasicProblem`](trait.BasicProblem.html) with callbacks taking `ndarray`
+/// views.
+pub trait NdarrayBasicProblem {
+    /// Total number of variables of the non-linear problem.
+    fn num_variables(&self) -> usize;
+
+    /// Specify lower and upper variable bounds given by `x_l` and `x_u` respectively.
+    fn bounds(&self, x_l: ArrayViewMut1<Number>, x_u: ArrayViewMut1<Number>) -> bool;
+
+    /// Construct the initial guess of the primal variables for Ipopt to start with.
+    ///
+    /// If this function returns `false`, then a zero initial guess will be used.
+    fn initial_point(&self, x: ArrayViewMut1<Number>) -> bool;
+
+    /// Objective function. This is the function being minimized.
+    fn objective(&self, x: ArrayView1<Number>, obj: &mut Number) -> bool;
+
+    /// The gradient of the objective function.
+    fn objective_grad(&self, x: ArrayView1<Number>, grad_f: ArrayViewMut1<Number>) -> bool;
+}
+
+/// A version of [`ConstrainedProblem`](trait.ConstrainedProblem.html) with callbacks taking
+/// `ndarray` views and dense matrices.
+pub trait NdarrayConstrainedProblem: NdarrayBasicProblem {
+    /// Number of equality and inequality constraints.
+    fn num_constraints(&self) -> usize;
+
+    /// Constraint function.
+    fn constraint(&self, x: ArrayView1<Number>, g: ArrayViewMut1<Number>) -> bool;
+
+    /// Specify lower and upper bounds, `g_l` and `g_u` respectively, on the constraint function.
+    fn constraint_bounds(&self, g_l: ArrayViewMut1<Number>, g_u: ArrayViewMut1<Number>) -> bool;
+
+    /// Construct the initial guess of the constraint multipliers for Ipopt to start with.
+    ///
+    /// For convenience, the default implementation initializes constraint multipliers to zero.
+    fn initial_constraint_multipliers(&self, mut lambda: ArrayViewMut1<Number>) -> bool {
+        lambda.fill(0.0);
+        true
+    }
+
+    /// The dense constraint Jacobian with `num_constraints` rows and `num_variables` columns.
+    fn constraint_jacobian(&self, x: ArrayView1<Number>, jac: ArrayViewMut2<Number>) -> bool;
+
+    /// The dense Hessian of the Lagrangian.
+    ///
+    /// The output is a zeroed `num_variables` by `num_variables` matrix. Only the lower
+    /// triangular part is passed to Ipopt, so the upper triangular part need not be filled.
+    /// Write the objective Hessian multiplied by `obj_factor` and constraint Hessians multiplied
+    /// by the corresponding values in `lambda`.
+    fn hessian(
+        &self,
+        x: ArrayView1<Number>,
+        obj_factor: Number,
+        lambda: ArrayView1<Number>,
+        hess: &mut Array2<Number>,
+    ) -> bool;
+}
+
+/// An adapter implementing the Ipopt problem traits for problems defined with `ndarray` types.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ndarray<P>(pub P);
+
+impl<P: NdarrayBasicProblem> BasicProblem for Ndarray<P> {
+    fn num_variables(&self) -> usize {
+        self.0.num_variables()
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        self.0.bounds(x_l.into(), x_u.into())
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        self.0.initial_point(x.into())
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        self.0.objective(x.into(), obj)
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        self.0.objective_grad(x.into(), grad_f.into())
+    }
+}
+
+impl<P: NdarrayConstrainedProblem> ConstrainedProblem for Ndarray<P> {
+    fn num_constraints(&self) -> usize {
+        self.0.num_constraints()
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        self.0.num_constraints() * self.0.num_variables()
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        self.0.constraint(x.into(), g.into())
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        self.0.constraint_bounds(g_l.into(), g_u.into())
+    }
+    fn initial_constraint_multipliers(&self, lambda: &mut [Number]) -> bool {
+        self.0.initial_constraint_multipliers(lambda.into())
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        let n = self.0.num_variables();
+        for (k, (r, c)) in rows.iter_mut().zip(cols.iter_mut()).enumerate() {
+            *r = (k / n) as Index;
+            *c = (k % n) as Index;
+        }
+        true
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        match ArrayViewMut2::from_shape((self.0.num_constraints(), x.len()), vals) {
+            Ok(jac) => self.0.constraint_jacobian(x.into(), jac),
+            Err(_) => false,
+        }
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        let n = self.0.num_variables();
+        n * (n + 1) / 2
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        // Lower triangular part in row-major order.
+        let mut k = 0;
+        for i in 0..self.0.num_variables() {
+            for j in 0..=i {
+                rows[k] = i as Index;
+                cols[k] = j as Index;
+                k += 1;
+            }
+        }
+        true
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        let n = x.len();
+        let mut hess = Array2::zeros((n, n));
+        if !self
+            .0
+            .hessian(x.into(), obj_factor, lambda.into(), &mut hess)
+        {
+            return false;
+        }
+        let mut k = 0;
+        for i in 0..n {
+            for j in 0..=i {
+                vals[k] = hess[[i, j]];
+                k += 1;
+            }
+        }
+        true
+    }
+}
+
+impl<'a> Solution<'a> {
+    /// The primal variables as an `ndarray` view.
+    ///
+    /// Use `to_owned` to get an `Array1`.
+    pub fn primal_variables_array(&self) -> ArrayView1<'a, Number> {
+        self.primal_variables.into()
+    }
+
+    /// The lower bound multipliers as an `ndarray` view.
+    pub fn lower_bound_multipliers_array(&self) -> ArrayView1<'a, Number> {
+        self.lower_bound_multipliers.into()
+    }
+
+    /// The upper bound multipliers as an `ndarray` view.
+    pub fn upper_bound_multipliers_array(&self) -> ArrayView1<'a, Number> {
+        self.upper_bound_multipliers.into()
+    }
+
+    /// The constraint multipliers as an `ndarray` view.
+    pub fn constraint_multipliers_array(&self) -> ArrayView1<'a, Number> {
+        self.constraint_multipliers.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// min x0^2 + x0 x1 s.t. x0 + 2 x1 = 1
+    struct Dense;
+
+    impl NdarrayBasicProblem for Dense {
+        fn num_variables(&self) -> usize {
+            2
+        }
+        fn bounds(&self, mut x_l: ArrayViewMut1<Number>, mut x_u: ArrayViewMut1<Number>) -> bool {
+            x_l.fill(-1e20);
+            x_u.fill(1e20);
+            true
+        }
+        fn initial_point(&self, _: ArrayViewMut1<Number>) -> bool {
+            false
+        }
+        fn objective(&self, x: ArrayView1<Number>, obj: &mut Number) -> bool {
+            *obj = x[0] * x[0] + x[0] * x[1];
+            true
+        }
+        fn objective_grad(&self, x: ArrayView1<Number>, mut grad_f: ArrayViewMut1<Number>) -> bool {
+            grad_f[0] = 2.0 * x[0] + x[1];
+            grad_f[1] = x[0];
+            true
+        }
+    }
+
+    impl NdarrayConstrainedProblem for Dense {
+        fn num_constraints(&self) -> usize {
+            1
+        }
+        fn constraint(&self, x: ArrayView1<Number>, mut g: ArrayViewMut1<Number>) -> bool {
+            g[0] = x[0] + 2.0 * x[1];
+            true
+        }
+        fn constraint_bounds(
+            &self,
+            mut g_l: ArrayViewMut1<Number>,
+            mut g_u: ArrayViewMut1<Number>,
+        ) -> bool {
+            g_l.fill(1.0);
+            g_u.fill(1.0);
+            true
+        }
+        fn constraint_jacobian(
+            &self,
+            _: ArrayView1<Number>,
+            mut jac: ArrayViewMut2<Number>,
+        ) -> bool {
+            jac[[0, 0]] = 1.0;
+            jac[[0, 1]] = 2.0;
+            true
+        }
+        fn hessian(
+            &self,
+            _: ArrayView1<Number>,
+            obj_factor: Number,
+            _: ArrayView1<Number>,
+            hess: &mut Array2<Number>,
+        ) -> bool {
+            hess[[0, 0]] = 2.0 * obj_factor;
+            hess[[1, 0]] = obj_factor;
+            true
+        }
+    }
+
+    #[test]
+    fn dense_structure_test() {
+        let nlp = Ndarray(Dense);
+        let x = [1.0, 1.0];
+
+        let mut rows = [0; 2];
+        let mut cols = [0; 2];
+        let mut vals = [0.0; 2];
+        assert!(nlp.constraint_jacobian_indices(&mut rows, &mut cols));
+        assert!(nlp.constraint_jacobian_values(&x, &mut vals));
+        assert_eq!(rows, [0, 0]);
+        assert_eq!(cols, [0, 1]);
+        assert_eq!(vals, [1.0, 2.0]);
+
+        let mut rows = [0; 3];
+        let mut cols = [0; 3];
+        let mut vals = [0.0; 3];
+        assert_eq!(nlp.num_hessian_non_zeros(), 3);
+        assert!(nlp.hessian_indices(&mut rows, &mut cols));
+        assert!(nlp.hessian_values(&x, 0.5, &[0.0], &mut vals));
+        assert_eq!(rows, [0, 1, 1]);
+        assert_eq!(cols, [0, 0, 1]);
+        assert_eq!(vals, [1.0, 0.5, 0.0]);
+    }
+}
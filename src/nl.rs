@@ -0,0 +1,232 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Reading AMPL models from `.nl` files.
+//!
+//! Models are read and evaluated through the AMPL Solver Library (ASL), which must be available
+//! when building with the `asl` feature. The resulting [`NlProblem`](struct.NlProblem.html)
+//! implements [`ConstrainedProblem`](trait.ConstrainedProblem.html) and can be passed directly to
+//! [`Ipopt::new`](struct.Ipopt.html#method.new):
+//!
+//! ```no_run
+//! use ipopt::*;
+//!
+//! let nlp = NlProblem::from_nl_file("model.nl").unwrap();
+//! let mut ipopt = Ipopt::new(nlp).unwrap();
+//! let result = ipopt.solve();
+//! let message = format!("Ipopt: {:?}", result.status);
+//! let SolverDataMut { problem, solution } = result.solver_data;
+//! problem.write_solution(&message, &solution);
+//! ```
+
+use crate::ffi;
+use crate::{BasicProblem, ConstrainedProblem, Index, Number, Solution};
+
+use std::ffi::CString;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+/// An optimization problem read from an AMPL `.nl` file.
+///
+/// Maximization problems are converted to minimization problems by negating the objective, so
+/// the objective value reported by Ipopt has the opposite sign of the AMPL objective in that case.
+///
+/// The ASL keeps evaluation state inside the model, so this type is intentionally neither `Send`
+/// nor `Sync`.
+#[derive(Debug)]
+pub struct NlProblem {
+    asl: ffi::CNLP_AslProblemPtr,
+    num_variables: usize,
+    num_constraints: usize,
+    num_constraint_jac_nnz: usize,
+    num_hessian_nnz: usize,
+}
+
+impl NlProblem {
+    /// Read the model stored in the `.nl` file at the given path.
+    ///
+    /// The `.nl` extension may be omitted, as is customary for AMPL solvers.
+    pub fn from_nl_file<P: AsRef<Path>>(path: P) -> Result<Self, NlReadError> {
+        let path = path
+            .as_ref()
+            .to_str()
+            .and_then(|p| CString::new(p).ok())
+            .ok_or(NlReadError::InvalidPath)?;
+
+        let mut asl: ffi::CNLP_AslProblemPtr = ::std::ptr::null_mut();
+        let status = unsafe { ffi::cnlp_asl_read(&mut asl as *mut _, path.as_ptr()) };
+
+        match status {
+            ffi::CNLP_AslReadStatus_CNLP_ASL_READ_SUCCESS => {}
+            ffi::CNLP_AslReadStatus_CNLP_ASL_FILE_NOT_FOUND => {
+                return Err(NlReadError::FileNotFound)
+            }
+            _ => return Err(NlReadError::ReadError),
+        }
+
+        let mut n: Index = 0;
+        let mut m: Index = 0;
+        let mut nnz_jac_g: Index = 0;
+        let mut nnz_h_lag: Index = 0;
+        unsafe { ffi::cnlp_asl_sizes(asl, &mut n, &mut m, &mut nnz_jac_g, &mut nnz_h_lag) };
+
+        Ok(NlProblem {
+            asl,
+            num_variables: n as usize,
+            num_constraints: m as usize,
+            num_constraint_jac_nnz: nnz_jac_g as usize,
+            num_hessian_nnz: nnz_h_lag as usize,
+        })
+    }
+
+    /// Write an AMPL `.sol` file for the given solution next to the `.nl` file that this problem
+    /// was read from.
+    ///
+    /// The message is reported back to AMPL as the solver message.
+    pub fn write_solution(&self, message: &str, solution: &Solution) {
+        let message = CString::new(message.replace('\0', "")).unwrap();
+        unsafe {
+            ffi::cnlp_asl_write_solution(
+                self.asl,
+                message.as_ptr(),
+                solution.primal_variables.as_ptr(),
+                solution.constraint_multipliers.as_ptr(),
+            )
+        };
+    }
+}
+
+impl Drop for NlProblem {
+    fn drop(&mut self) {
+        unsafe { ffi::cnlp_asl_free(self.asl) };
+    }
+}
+
+impl BasicProblem for NlProblem {
+    fn num_variables(&self) -> usize {
+        self.num_variables
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        let mut g_l = vec![0.0; self.num_constraints];
+        let mut g_u = vec![0.0; self.num_constraints];
+        unsafe {
+            ffi::cnlp_asl_bounds(
+                self.asl,
+                x_l.as_mut_ptr(),
+                x_u.as_mut_ptr(),
+                g_l.as_mut_ptr(),
+                g_u.as_mut_ptr(),
+            )
+        };
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        unsafe { ffi::cnlp_asl_initial_point(self.asl, x.as_mut_ptr()) };
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        unsafe { ffi::cnlp_asl_eval_f(self.asl, x.as_ptr(), obj) != 0 }
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        unsafe { ffi::cnlp_asl_eval_grad_f(self.asl, x.as_ptr(), grad_f.as_mut_ptr()) != 0 }
+    }
+}
+
+impl ConstrainedProblem for NlProblem {
+    fn num_constraints(&self) -> usize {
+        self.num_constraints
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        self.num_constraint_jac_nnz
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        unsafe { ffi::cnlp_asl_eval_g(self.asl, x.as_ptr(), g.as_mut_ptr()) != 0 }
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        let mut x_l = vec![0.0; self.num_variables];
+        let mut x_u = vec![0.0; self.num_variables];
+        unsafe {
+            ffi::cnlp_asl_bounds(
+                self.asl,
+                x_l.as_mut_ptr(),
+                x_u.as_mut_ptr(),
+                g_l.as_mut_ptr(),
+                g_u.as_mut_ptr(),
+            )
+        };
+        true
+    }
+    fn initial_constraint_multipliers(&self, lambda: &mut [Number]) -> bool {
+        unsafe { ffi::cnlp_asl_initial_multipliers(self.asl, lambda.as_mut_ptr()) };
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        unsafe { ffi::cnlp_asl_jac_g_structure(self.asl, rows.as_mut_ptr(), cols.as_mut_ptr()) };
+        true
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        unsafe { ffi::cnlp_asl_eval_jac_g(self.asl, x.as_ptr(), vals.as_mut_ptr()) != 0 }
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        self.num_hessian_nnz
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        unsafe { ffi::cnlp_asl_h_structure(self.asl, rows.as_mut_ptr(), cols.as_mut_ptr()) };
+        true
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        unsafe {
+            ffi::cnlp_asl_eval_h(
+                self.asl,
+                x.as_ptr(),
+                obj_factor,
+                lambda.as_ptr(),
+                vals.as_mut_ptr(),
+            ) != 0
+        }
+    }
+}
+
+/// Error reading an `.nl` file.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum NlReadError {
+    /// The path is not valid UTF-8 or contains a nul byte.
+    InvalidPath,
+    /// The `.nl` file could not be found.
+    FileNotFound,
+    /// The `.nl` file could not be parsed by the AMPL Solver Library.
+    ReadError,
+}
+
+impl Display for NlReadError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match *self {
+            NlReadError::InvalidPath => write!(f, "The path to the .nl file is invalid."),
+            NlReadError::FileNotFound => write!(f, "The .nl file could not be found."),
+            NlReadError::ReadError => write!(f, "Failed to read the .nl file."),
+        }
+    }
+}
+
+impl std::error::Error for NlReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
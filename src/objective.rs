@@ -0,0 +1,294 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Objectives composed of a sum of terms.
+//!
+//! Regularized or multi-physics objectives are naturally written as sums of terms that each
+//! depend on a few of the variables. An [`ObjectiveTerm`](trait.ObjectiveTerm.html) is defined
+//! in local coordinates over its own subset of variables, and a
+//! [`CompositeObjective`](struct.CompositeObjective.html) merges the Hessian sparsity patterns of
+//! all terms and accumulates their values, gradients and Hessians into the global arrays
+//! expected by [`BasicProblem`](trait.BasicProblem.html) and
+//! [`ConstrainedProblem`](trait.ConstrainedProblem.html).
+//!
+//! The composite objective produces zero-based indices, so problems using it should keep the
+//! default `IndexingStyle::CStyle`.
+
+use crate::{Index, Number};
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// A single term of an objective, defined over a subset of the variables.
+///
+/// All vectors and indices are local: entry `i` corresponds to the global variable
+/// `variables()[i]`.
+pub trait ObjectiveTerm {
+    /// Global indices of the variables this term depends on.
+    fn variables(&self) -> &[usize];
+    /// Value of the term.
+    fn value(&self, x: &[Number], value: &mut Number) -> bool;
+    /// Gradient of the term with respect to its variables.
+    fn gradient(&self, x: &[Number], grad: &mut [Number]) -> bool;
+    /// Number of non-zeros in the lower triangular part of the term Hessian.
+    fn num_hessian_non_zeros(&self) -> usize {
+        0
+    }
+    /// Local row and column indices of the term Hessian entries.
+    fn hessian_indices(&self, _rows: &mut [Index], _cols: &mut [Index]) -> bool {
+        true
+    }
+    /// Values of the term Hessian entries.
+    fn hessian_values(&self, _x: &[Number], _vals: &mut [Number]) -> bool {
+        true
+    }
+}
+
+/// A term with its variables and the position of each of its Hessian entries in the merged
+/// Hessian pattern.
+struct Term {
+    term: Box<dyn ObjectiveTerm>,
+    hessian_positions: Vec<usize>,
+}
+
+/// An objective given by the sum of a number of terms.
+pub struct CompositeObjective {
+    num_variables: usize,
+    terms: Vec<Term>,
+    hessian_rows: Vec<Index>,
+    hessian_cols: Vec<Index>,
+    hessian_position: HashMap<(Index, Index), usize>,
+    /// Scratch buffers for local variables and derivatives.
+    scratch: RefCell<(Vec<Number>, Vec<Number>)>,
+}
+
+impl CompositeObjective {
+    /// Create an objective over the given number of variables without any terms.
+    pub fn new(num_variables: usize) -> Self {
+        CompositeObjective {
+            num_variables,
+            terms: Vec::new(),
+            hessian_rows: Vec::new(),
+            hessian_cols: Vec::new(),
+            hessian_position: HashMap::new(),
+            scratch: RefCell::new((Vec::new(), Vec::new())),
+        }
+    }
+
+    /// Add a term to the objective.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the term refers to variables out of bounds or if its Hessian
+    /// indices cannot be determined.
+    pub fn add_term<T: ObjectiveTerm + 'static>(&mut self, term: T) -> &mut Self {
+        let vars = term.variables();
+        assert!(
+            vars.iter().all(|&v| v < self.num_variables),
+            "objective term refers to a variable out of bounds"
+        );
+
+        let nnz = term.num_hessian_non_zeros();
+        let mut rows = vec![0; nnz];
+        let mut cols = vec![0; nnz];
+        assert!(
+            term.hessian_indices(&mut rows, &mut cols),
+            "failed to get objective term Hessian indices"
+        );
+
+        let mut hessian_positions = Vec::with_capacity(nnz);
+        for (&r, &c) in rows.iter().zip(cols.iter()) {
+            let (r, c) = (vars[r as usize] as Index, vars[c as usize] as Index);
+            // Terms may order their variables arbitrarily, so reorient into the lower triangle.
+            let entry = if r >= c { (r, c) } else { (c, r) };
+            let (hessian_rows, hessian_cols) = (&mut self.hessian_rows, &mut self.hessian_cols);
+            let pos = *self.hessian_position.entry(entry).or_insert_with(|| {
+                hessian_rows.push(entry.0);
+                hessian_cols.push(entry.1);
+                hessian_rows.len() - 1
+            });
+            hessian_positions.push(pos);
+        }
+
+        self.terms.push(Term {
+            term: Box::new(term),
+            hessian_positions,
+        });
+        self
+    }
+
+    /// Number of variables of the objective.
+    pub fn num_variables(&self) -> usize {
+        self.num_variables
+    }
+
+    /// Number of terms in the objective.
+    pub fn num_terms(&self) -> usize {
+        self.terms.len()
+    }
+
+    /// Evaluate the objective as the sum of all terms.
+    pub fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        let (ref mut local_x, _) = *self.scratch.borrow_mut();
+        *obj = 0.0;
+        for t in self.terms.iter() {
+            gather(x, t.term.variables(), local_x);
+            let mut value = 0.0;
+            if !t.term.value(local_x, &mut value) {
+                return false;
+            }
+            *obj += value;
+        }
+        true
+    }
+
+    /// Evaluate the gradient of the objective by accumulating the term gradients.
+    pub fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        let (ref mut local_x, ref mut local_grad) = *self.scratch.borrow_mut();
+        for g in grad_f.iter_mut() {
+            *g = 0.0;
+        }
+        for t in self.terms.iter() {
+            let vars = t.term.variables();
+            gather(x, vars, local_x);
+            local_grad.clear();
+            local_grad.resize(vars.len(), 0.0);
+            if !t.term.gradient(local_x, local_grad) {
+                return false;
+            }
+            for (&v, &g) in vars.iter().zip(local_grad.iter()) {
+                grad_f[v] += g;
+            }
+        }
+        true
+    }
+
+    /// Number of non-zeros in the merged Hessian pattern of all terms.
+    pub fn num_hessian_non_zeros(&self) -> usize {
+        self.hessian_rows.len()
+    }
+
+    /// Row and column indices of the merged Hessian pattern, in the lower triangle.
+    ///
+    /// Only the first `num_hessian_non_zeros()` entries are written, so problems with
+    /// constraints can append the entries of the constraint Hessians.
+    pub fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        let nnz = self.num_hessian_non_zeros();
+        rows[..nnz].copy_from_slice(&self.hessian_rows);
+        cols[..nnz].copy_from_slice(&self.hessian_cols);
+        true
+    }
+
+    /// Hessian values of the objective multiplied by `obj_factor`.
+    ///
+    /// Only the first `num_hessian_non_zeros()` entries are written.
+    pub fn hessian_values(&self, x: &[Number], obj_factor: Number, vals: &mut [Number]) -> bool {
+        let (ref mut local_x, ref mut local_vals) = *self.scratch.borrow_mut();
+        let nnz = self.num_hessian_non_zeros();
+        for v in vals[..nnz].iter_mut() {
+            *v = 0.0;
+        }
+        for t in self.terms.iter() {
+            gather(x, t.term.variables(), local_x);
+            local_vals.clear();
+            local_vals.resize(t.hessian_positions.len(), 0.0);
+            if !t.term.hessian_values(local_x, local_vals) {
+                return false;
+            }
+            for (&pos, &val) in t.hessian_positions.iter().zip(local_vals.iter()) {
+                vals[pos] += obj_factor * val;
+            }
+        }
+        true
+    }
+}
+
+/// Collect the values of the given variables.
+fn gather(x: &[Number], vars: &[usize], local_x: &mut Vec<Number>) {
+    local_x.clear();
+    local_x.extend(vars.iter().map(|&v| x[v]));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// w * (x[a] - x[b])^2
+    struct Spring {
+        vars: [usize; 2],
+        w: Number,
+    }
+
+    impl ObjectiveTerm for Spring {
+        fn variables(&self) -> &[usize] {
+            &self.vars
+        }
+        fn value(&self, x: &[Number], value: &mut Number) -> bool {
+            *value = self.w * (x[0] - x[1]) * (x[0] - x[1]);
+            true
+        }
+        fn gradient(&self, x: &[Number], grad: &mut [Number]) -> bool {
+            grad[0] = 2.0 * self.w * (x[0] - x[1]);
+            grad[1] = -grad[0];
+            true
+        }
+        fn num_hessian_non_zeros(&self) -> usize {
+            3
+        }
+        fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+            rows.copy_from_slice(&[0, 1, 0]);
+            cols.copy_from_slice(&[0, 1, 1]);
+            true
+        }
+        fn hessian_values(&self, _: &[Number], vals: &mut [Number]) -> bool {
+            vals.copy_from_slice(&[2.0 * self.w, 2.0 * self.w, -2.0 * self.w]);
+            true
+        }
+    }
+
+    #[test]
+    fn composite_test() {
+        let mut obj = CompositeObjective::new(3);
+        obj.add_term(Spring {
+            vars: [0, 2],
+            w: 1.0,
+        })
+        .add_term(Spring {
+            vars: [2, 1],
+            w: 2.0,
+        });
+        assert_eq!(obj.num_terms(), 2);
+
+        let x = [1.0, 3.0, 2.0];
+        let mut f = 0.0;
+        assert!(obj.objective(&x, &mut f));
+        assert_eq!(f, 1.0 + 2.0);
+
+        let mut grad = [0.0; 3];
+        assert!(obj.objective_grad(&x, &mut grad));
+        assert_eq!(grad, [-2.0, 4.0, 2.0 - 4.0]);
+
+        // The entry (2, 2) is shared and the off-diagonal entries are in the lower triangle.
+        assert_eq!(obj.num_hessian_non_zeros(), 5);
+        let mut rows = [0; 5];
+        let mut cols = [0; 5];
+        assert!(obj.hessian_indices(&mut rows, &mut cols));
+        assert_eq!(rows, [0, 2, 2, 1, 2]);
+        assert_eq!(cols, [0, 2, 0, 1, 1]);
+
+        let mut vals = [0.0; 5];
+        assert!(obj.hessian_values(&x, 0.5, &mut vals));
+        assert_eq!(vals, [1.0, 1.0 + 2.0, -1.0, 2.0, -2.0]);
+    }
+}
@@ -0,0 +1,98 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Options with names and types checked at compile time.
+//!
+//! The [`option!`](macro.option.html) macro is backed by a table of the options registered by
+//! Ipopt, generated from the [`OptionRegistry`](struct.OptionRegistry.html) of the Ipopt version
+//! this crate builds by default. Options that only exist in other versions, or with linear
+//! solvers missing from the table, can still be set by name with
+//! [`OptionSet::set`](struct.OptionSet.html#method.set).
+
+use crate::{Number, OptionValue};
+
+/// Build a named option, failing to compile if Ipopt has no option with the given name or if
+/// the value has the wrong type.
+///
+/// The result is a `(&str, OptionValue)` pair, so options can be collected into an
+/// [`OptionSet`](struct.OptionSet.html):
+///
+/// ```
+/// use ipopt::{option, OptionSet};
+///
+/// let options: OptionSet = vec![
+///     option!("tol" => 1e-8),
+///     option!("max_iter" => 100),
+///     option!("mu_strategy" => "adaptive"),
+/// ]
+/// .into_iter()
+/// .collect();
+/// ```
+///
+/// Misspelled names and values of the wrong type are compile errors:
+///
+/// ```compile_fail
+/// let tol = ipopt::option!("tool" => 1e-8);
+/// ```
+///
+/// ```compile_fail
+/// let max_iter = ipopt::option!("max_iter" => "x");
+/// ```
+///
+/// The range and valid values of an option are only known to Ipopt at run time; check them with
+/// [`OptionRegistry::validate`](struct.OptionRegistry.html#method.validate).
+#[macro_export]
+macro_rules! option {
+    ($name:tt => $value:expr) => {
+        $crate::__ipopt_option!($name, $value)
+    };
+}
+
+#[doc(hidden)]
+pub fn __num_option(name: &'static str, value: Number) -> (&'static str, OptionValue) {
+    (name, OptionValue::Num(value))
+}
+
+#[doc(hidden)]
+pub fn __int_option(name: &'static str, value: i32) -> (&'static str, OptionValue) {
+    (name, OptionValue::Int(value))
+}
+
+#[doc(hidden)]
+pub fn __str_option(name: &'static str, value: &str) -> (&'static str, OptionValue) {
+    (name, OptionValue::Str(value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{OptionSet, OptionValue};
+
+    #[test]
+    fn option_macro_test() {
+        let options: OptionSet = vec![
+            option!("tol" => 1e-8),
+            option!("max_iter" => 100),
+            option!("mu_strategy" => "adaptive"),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(options.get("tol"), Some(&OptionValue::Num(1e-8)));
+        assert_eq!(options.get("max_iter"), Some(&OptionValue::Int(100)));
+        assert_eq!(
+            options.get("mu_strategy"),
+            Some(&OptionValue::Str("adaptive".to_string()))
+        );
+    }
+}
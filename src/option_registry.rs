@@ -0,0 +1,441 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Metadata of the options known to the linked version of Ipopt.
+//!
+//! [`OptionRegistry::linked`](struct.OptionRegistry.html#method.linked) lists every option with
+//! its type, default, valid range or values, category and description, as registered by the
+//! Ipopt library the crate is linked against. This is the information needed to build
+//! configuration GUIs, or to validate an [`OptionSet`](struct.OptionSet.html) before handing it
+//! to a solver:
+//!
+//! ```no_run
+//! # use ipopt::{OptionRegistry, OptionSet};
+//! # fn main() -> Result<(), ipopt::OptionValueError> {
+//! # let options = OptionSet::new();
+//! let registry = OptionRegistry::linked();
+//! registry.validate_set(&options)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::ffi;
+use crate::{Number, OptionSet, OptionValue};
+
+use std::collections::BTreeMap;
+use std::ffi::CStr;
+use std::fmt::{Display, Formatter};
+use std::iter::FromIterator;
+use std::os::raw::c_char;
+use std::slice;
+use std::sync::OnceLock;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Type of an Ipopt option.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum OptionType {
+    /// Floating point option, set with [`OptionValue::Num`](enum.OptionValue.html).
+    Number,
+    /// Integer option, set with [`OptionValue::Int`](enum.OptionValue.html).
+    Integer,
+    /// String option, set with [`OptionValue::Str`](enum.OptionValue.html).
+    String,
+}
+
+impl OptionType {
+    /// The type of the given value.
+    pub fn of(value: &OptionValue) -> Self {
+        match value {
+            OptionValue::Num(_) => OptionType::Number,
+            OptionValue::Int(_) => OptionType::Integer,
+            OptionValue::Str(_) => OptionType::String,
+        }
+    }
+}
+
+impl Display for OptionType {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            OptionType::Number => write!(f, "number"),
+            OptionType::Integer => write!(f, "integer"),
+            OptionType::String => write!(f, "string"),
+        }
+    }
+}
+
+/// A lower or upper bound on a numeric or integer option.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OptionBound {
+    /// The bound.
+    pub value: Number,
+    /// Whether the bound itself is excluded from the valid range.
+    pub strict: bool,
+}
+
+/// A valid value of a string option.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ValidValue {
+    /// The value.
+    pub value: String,
+    /// What the value means.
+    pub description: String,
+}
+
+/// Metadata of an option registered with Ipopt.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OptionInfo {
+    /// Name of the option.
+    pub name: String,
+    /// Type of the option.
+    pub option_type: OptionType,
+    /// Category the option is listed under in the Ipopt documentation.
+    pub category: String,
+    /// Short description of the option.
+    pub description: String,
+    /// Value used when the option is not set.
+    pub default: OptionValue,
+    /// Lower bound of a numeric or integer option.
+    pub lower: Option<OptionBound>,
+    /// Upper bound of a numeric or integer option.
+    pub upper: Option<OptionBound>,
+    /// Valid values of a string option. If empty, any string is accepted.
+    pub valid_values: Vec<ValidValue>,
+}
+
+impl OptionInfo {
+    /// Check that the given value has the type of this option and is within its valid range or
+    /// among its valid values.
+    pub fn validate(&self, value: &OptionValue) -> Result<(), OptionValueError> {
+        let found = OptionType::of(value);
+        if found != self.option_type {
+            return Err(OptionValueError::TypeMismatch {
+                name: self.name.clone(),
+                expected: self.option_type,
+                found,
+            });
+        }
+        let in_range = |x: Number| {
+            let above = match self.lower {
+                Some(b) if b.strict => x > b.value,
+                Some(b) => x >= b.value,
+                None => true,
+            };
+            let below = match self.upper {
+                Some(b) if b.strict => x < b.value,
+                Some(b) => x <= b.value,
+                None => true,
+            };
+            above && below
+        };
+        let valid = match value {
+            OptionValue::Num(x) => in_range(*x),
+            OptionValue::Int(x) => in_range(Number::from(*x)),
+            // Ipopt compares string values case insensitively.
+            OptionValue::Str(s) => {
+                self.valid_values.is_empty()
+                    || self
+                        .valid_values
+                        .iter()
+                        .any(|v| v.value.eq_ignore_ascii_case(s))
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(OptionValueError::InvalidValue {
+                name: self.name.clone(),
+                value: value.clone(),
+            })
+        }
+    }
+}
+
+/// The options registered with Ipopt, sorted by name.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct OptionRegistry {
+    options: BTreeMap<String, OptionInfo>,
+}
+
+/// Collect an option reported by `cnlp_visit_registered_options`.
+unsafe extern "C" fn visit_registered_option(
+    info: *const ffi::CNLP_OptionInfo,
+    user_data: ffi::CNLP_UserDataPtr,
+) {
+    let registry = &mut *(user_data as *mut OptionRegistry);
+    let info = &*info;
+    let string = |s: *const c_char| CStr::from_ptr(s).to_string_lossy().into_owned();
+    let bound = |has: ffi::CNLP_Bool, strict: ffi::CNLP_Bool, value: Number| {
+        if has != 0 {
+            Some(OptionBound {
+                value,
+                strict: strict != 0,
+            })
+        } else {
+            None
+        }
+    };
+
+    let (option_type, default) = match info.type_ {
+        ffi::CNLP_OptionType_CNLP_NUMBER_OPTION => {
+            (OptionType::Number, OptionValue::Num(info.default_num))
+        }
+        ffi::CNLP_OptionType_CNLP_INTEGER_OPTION => {
+            (OptionType::Integer, OptionValue::Int(info.default_int))
+        }
+        _ => (
+            OptionType::String,
+            OptionValue::Str(string(info.default_str)),
+        ),
+    };
+    let num_valid_values = info.num_valid_values.max(0) as usize;
+    let valid_values = if num_valid_values > 0 {
+        let values = slice::from_raw_parts(info.valid_values, num_valid_values);
+        let descriptions = slice::from_raw_parts(info.valid_value_descriptions, num_valid_values);
+        values
+            .iter()
+            .zip(descriptions.iter())
+            .map(|(&value, &description)| ValidValue {
+                value: string(value),
+                description: string(description),
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let name = string(info.name);
+    registry.options.insert(
+        name.clone(),
+        OptionInfo {
+            name,
+            option_type,
+            category: string(info.category),
+            description: string(info.short_description),
+            default,
+            lower: bound(info.has_lower, info.lower_strict, info.lower),
+            upper: bound(info.has_upper, info.upper_strict, info.upper),
+            valid_values,
+        },
+    );
+}
+
+impl OptionRegistry {
+    /// The options registered by the linked Ipopt library.
+    ///
+    /// The available options depend on the version of Ipopt and the linear solvers it was built
+    /// with. They are collected from Ipopt on the first call, later calls return a copy.
+    pub fn linked() -> Self {
+        Self::linked_ref().clone()
+    }
+
+    /// The options registered by the linked Ipopt library, collected on the first call only.
+    pub(crate) fn linked_ref() -> &'static OptionRegistry {
+        static LINKED: OnceLock<OptionRegistry> = OnceLock::new();
+        LINKED.get_or_init(|| {
+            let mut registry = OptionRegistry::default();
+            unsafe {
+                ffi::cnlp_visit_registered_options(
+                    Some(visit_registered_option),
+                    &mut registry as *mut OptionRegistry as ffi::CNLP_UserDataPtr,
+                );
+            }
+            registry
+        })
+    }
+
+    /// Metadata of the option with the given name.
+    pub fn get(&self, name: &str) -> Option<&OptionInfo> {
+        self.options.get(name)
+    }
+
+    /// Iterate over all options sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = &OptionInfo> {
+        self.options.values()
+    }
+
+    /// Number of registered options.
+    pub fn len(&self) -> usize {
+        self.options.len()
+    }
+
+    /// Returns `true` if no options are registered.
+    pub fn is_empty(&self) -> bool {
+        self.options.is_empty()
+    }
+
+    /// Check that an option with the given name exists and accepts the given value.
+    pub fn validate(&self, name: &str, value: &OptionValue) -> Result<(), OptionValueError> {
+        match self.get(name) {
+            Some(info) => info.validate(value),
+            None => Err(OptionValueError::UnknownOption {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// Check all options in the given set, returning the error of the first invalid option in
+    /// order of option names.
+    pub fn validate_set(&self, options: &OptionSet) -> Result<(), OptionValueError> {
+        options
+            .iter()
+            .try_for_each(|(name, value)| self.validate(name, value))
+    }
+}
+
+impl FromIterator<OptionInfo> for OptionRegistry {
+    fn from_iter<I: IntoIterator<Item = OptionInfo>>(iter: I) -> Self {
+        OptionRegistry {
+            options: iter
+                .into_iter()
+                .map(|info| (info.name.clone(), info))
+                .collect(),
+        }
+    }
+}
+
+/// Error returned when an option value is not accepted by an
+/// [`OptionRegistry`](struct.OptionRegistry.html).
+#[derive(Clone, Debug, PartialEq)]
+pub enum OptionValueError {
+    /// No option with this name is registered.
+    UnknownOption {
+        /// Name of the option.
+        name: String,
+    },
+    /// The value has a different type than the option.
+    TypeMismatch {
+        /// Name of the option.
+        name: String,
+        /// Type of the option.
+        expected: OptionType,
+        /// Type of the value.
+        found: OptionType,
+    },
+    /// The value is outside the valid range, or not among the valid values of the option.
+    InvalidValue {
+        /// Name of the option.
+        name: String,
+        /// The rejected value.
+        value: OptionValue,
+    },
+}
+
+impl Display for OptionValueError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            OptionValueError::UnknownOption { name } => {
+                write!(f, "Unknown option \"{}\".", name)
+            }
+            OptionValueError::TypeMismatch {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Option \"{}\" is of type {}, but a value of type {} was given.",
+                name, expected, found
+            ),
+            OptionValueError::InvalidValue { name, value } => {
+                write!(f, "Invalid value {:?} for option \"{}\".", value, name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OptionValueError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_test() {
+        let registry: OptionRegistry = vec![
+            OptionInfo {
+                name: "tol".to_string(),
+                option_type: OptionType::Number,
+                category: "Termination".to_string(),
+                description: "Desired convergence tolerance (relative).".to_string(),
+                default: OptionValue::Num(1e-8),
+                lower: Some(OptionBound {
+                    value: 0.0,
+                    strict: true,
+                }),
+                upper: None,
+                valid_values: Vec::new(),
+            },
+            OptionInfo {
+                name: "mu_strategy".to_string(),
+                option_type: OptionType::String,
+                category: "Barrier Parameter Update".to_string(),
+                description: "Update strategy for barrier parameter.".to_string(),
+                default: OptionValue::from("monotone"),
+                lower: None,
+                upper: None,
+                valid_values: vec![
+                    ValidValue {
+                        value: "monotone".to_string(),
+                        description: "use the monotone (Fiacco-McCormick) strategy".to_string(),
+                    },
+                    ValidValue {
+                        value: "adaptive".to_string(),
+                        description: "use the adaptive update strategy".to_string(),
+                    },
+                ],
+            },
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(registry.len(), 2);
+        assert!(registry.validate("tol", &1e-6.into()).is_ok());
+        assert!(registry.validate("mu_strategy", &"Adaptive".into()).is_ok());
+        assert_eq!(
+            registry.validate("tol", &0.0.into()),
+            Err(OptionValueError::InvalidValue {
+                name: "tol".to_string(),
+                value: OptionValue::Num(0.0),
+            })
+        );
+        assert_eq!(
+            registry.validate("tol", &1.into()),
+            Err(OptionValueError::TypeMismatch {
+                name: "tol".to_string(),
+                expected: OptionType::Number,
+                found: OptionType::Integer,
+            })
+        );
+        assert!(registry.validate("mu_strategy", &"fast".into()).is_err());
+
+        let mut options = OptionSet::new();
+        options.set("tol", 1e-6).set("toll", 1e-6);
+        assert_eq!(
+            registry.validate_set(&options),
+            Err(OptionValueError::UnknownOption {
+                name: "toll".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,638 @@
+// This file is generated by `cargo run --example generate_option_table`.
+// Do not edit it by hand.
+
+/// Build a typed option from the name of an option registered with Ipopt.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ipopt_option {
+    ("accept_after_max_steps", $value:expr) => {
+        $crate::__int_option("accept_after_max_steps", $value)
+    };
+    ("accept_every_trial_step", $value:expr) => {
+        $crate::__str_option("accept_every_trial_step", $value)
+    };
+    ("acceptable_compl_inf_tol", $value:expr) => {
+        $crate::__num_option("acceptable_compl_inf_tol", $value)
+    };
+    ("acceptable_constr_viol_tol", $value:expr) => {
+        $crate::__num_option("acceptable_constr_viol_tol", $value)
+    };
+    ("acceptable_dual_inf_tol", $value:expr) => {
+        $crate::__num_option("acceptable_dual_inf_tol", $value)
+    };
+    ("acceptable_iter", $value:expr) => {
+        $crate::__int_option("acceptable_iter", $value)
+    };
+    ("acceptable_obj_change_tol", $value:expr) => {
+        $crate::__num_option("acceptable_obj_change_tol", $value)
+    };
+    ("acceptable_tol", $value:expr) => {
+        $crate::__num_option("acceptable_tol", $value)
+    };
+    ("adaptive_mu_globalization", $value:expr) => {
+        $crate::__str_option("adaptive_mu_globalization", $value)
+    };
+    ("adaptive_mu_kkt_norm_type", $value:expr) => {
+        $crate::__str_option("adaptive_mu_kkt_norm_type", $value)
+    };
+    ("adaptive_mu_kkterror_red_fact", $value:expr) => {
+        $crate::__num_option("adaptive_mu_kkterror_red_fact", $value)
+    };
+    ("adaptive_mu_kkterror_red_iters", $value:expr) => {
+        $crate::__int_option("adaptive_mu_kkterror_red_iters", $value)
+    };
+    ("adaptive_mu_monotone_init_factor", $value:expr) => {
+        $crate::__num_option("adaptive_mu_monotone_init_factor", $value)
+    };
+    ("adaptive_mu_restore_previous_iterate", $value:expr) => {
+        $crate::__str_option("adaptive_mu_restore_previous_iterate", $value)
+    };
+    ("alpha_for_y", $value:expr) => {
+        $crate::__str_option("alpha_for_y", $value)
+    };
+    ("alpha_for_y_tol", $value:expr) => {
+        $crate::__num_option("alpha_for_y_tol", $value)
+    };
+    ("alpha_min_frac", $value:expr) => {
+        $crate::__num_option("alpha_min_frac", $value)
+    };
+    ("alpha_red_factor", $value:expr) => {
+        $crate::__num_option("alpha_red_factor", $value)
+    };
+    ("barrier_tol_factor", $value:expr) => {
+        $crate::__num_option("barrier_tol_factor", $value)
+    };
+    ("bound_frac", $value:expr) => {
+        $crate::__num_option("bound_frac", $value)
+    };
+    ("bound_mult_init_method", $value:expr) => {
+        $crate::__str_option("bound_mult_init_method", $value)
+    };
+    ("bound_mult_init_val", $value:expr) => {
+        $crate::__num_option("bound_mult_init_val", $value)
+    };
+    ("bound_mult_reset_threshold", $value:expr) => {
+        $crate::__num_option("bound_mult_reset_threshold", $value)
+    };
+    ("bound_push", $value:expr) => {
+        $crate::__num_option("bound_push", $value)
+    };
+    ("bound_relax_factor", $value:expr) => {
+        $crate::__num_option("bound_relax_factor", $value)
+    };
+    ("check_derivatives_for_naninf", $value:expr) => {
+        $crate::__str_option("check_derivatives_for_naninf", $value)
+    };
+    ("compl_inf_tol", $value:expr) => {
+        $crate::__num_option("compl_inf_tol", $value)
+    };
+    ("constr_mult_init_max", $value:expr) => {
+        $crate::__num_option("constr_mult_init_max", $value)
+    };
+    ("constr_mult_reset_threshold", $value:expr) => {
+        $crate::__num_option("constr_mult_reset_threshold", $value)
+    };
+    ("constr_viol_tol", $value:expr) => {
+        $crate::__num_option("constr_viol_tol", $value)
+    };
+    ("corrector_compl_avrg_red_fact", $value:expr) => {
+        $crate::__num_option("corrector_compl_avrg_red_fact", $value)
+    };
+    ("corrector_type", $value:expr) => {
+        $crate::__str_option("corrector_type", $value)
+    };
+    ("delta", $value:expr) => {
+        $crate::__num_option("delta", $value)
+    };
+    ("dependency_detection_with_rhs", $value:expr) => {
+        $crate::__str_option("dependency_detection_with_rhs", $value)
+    };
+    ("dependency_detector", $value:expr) => {
+        $crate::__str_option("dependency_detector", $value)
+    };
+    ("derivative_test", $value:expr) => {
+        $crate::__str_option("derivative_test", $value)
+    };
+    ("derivative_test_first_index", $value:expr) => {
+        $crate::__int_option("derivative_test_first_index", $value)
+    };
+    ("derivative_test_perturbation", $value:expr) => {
+        $crate::__num_option("derivative_test_perturbation", $value)
+    };
+    ("derivative_test_print_all", $value:expr) => {
+        $crate::__str_option("derivative_test_print_all", $value)
+    };
+    ("derivative_test_tol", $value:expr) => {
+        $crate::__num_option("derivative_test_tol", $value)
+    };
+    ("diverging_iterates_tol", $value:expr) => {
+        $crate::__num_option("diverging_iterates_tol", $value)
+    };
+    ("dual_inf_tol", $value:expr) => {
+        $crate::__num_option("dual_inf_tol", $value)
+    };
+    ("eta_phi", $value:expr) => {
+        $crate::__num_option("eta_phi", $value)
+    };
+    ("evaluate_orig_obj_at_resto_trial", $value:expr) => {
+        $crate::__str_option("evaluate_orig_obj_at_resto_trial", $value)
+    };
+    ("expect_infeasible_problem", $value:expr) => {
+        $crate::__str_option("expect_infeasible_problem", $value)
+    };
+    ("expect_infeasible_problem_ctol", $value:expr) => {
+        $crate::__num_option("expect_infeasible_problem_ctol", $value)
+    };
+    ("expect_infeasible_problem_ytol", $value:expr) => {
+        $crate::__num_option("expect_infeasible_problem_ytol", $value)
+    };
+    ("file_append", $value:expr) => {
+        $crate::__str_option("file_append", $value)
+    };
+    ("file_print_level", $value:expr) => {
+        $crate::__int_option("file_print_level", $value)
+    };
+    ("filter_margin_fact", $value:expr) => {
+        $crate::__num_option("filter_margin_fact", $value)
+    };
+    ("filter_max_margin", $value:expr) => {
+        $crate::__num_option("filter_max_margin", $value)
+    };
+    ("filter_reset_trigger", $value:expr) => {
+        $crate::__int_option("filter_reset_trigger", $value)
+    };
+    ("findiff_perturbation", $value:expr) => {
+        $crate::__num_option("findiff_perturbation", $value)
+    };
+    ("first_hessian_perturbation", $value:expr) => {
+        $crate::__num_option("first_hessian_perturbation", $value)
+    };
+    ("fixed_mu_oracle", $value:expr) => {
+        $crate::__str_option("fixed_mu_oracle", $value)
+    };
+    ("fixed_variable_treatment", $value:expr) => {
+        $crate::__str_option("fixed_variable_treatment", $value)
+    };
+    ("gamma_phi", $value:expr) => {
+        $crate::__num_option("gamma_phi", $value)
+    };
+    ("gamma_theta", $value:expr) => {
+        $crate::__num_option("gamma_theta", $value)
+    };
+    ("hessian_approximation", $value:expr) => {
+        $crate::__str_option("hessian_approximation", $value)
+    };
+    ("hessian_approximation_space", $value:expr) => {
+        $crate::__str_option("hessian_approximation_space", $value)
+    };
+    ("hessian_constant", $value:expr) => {
+        $crate::__str_option("hessian_constant", $value)
+    };
+    ("honor_original_bounds", $value:expr) => {
+        $crate::__str_option("honor_original_bounds", $value)
+    };
+    ("hsllib", $value:expr) => {
+        $crate::__str_option("hsllib", $value)
+    };
+    ("inf_pr_output", $value:expr) => {
+        $crate::__str_option("inf_pr_output", $value)
+    };
+    ("jac_c_constant", $value:expr) => {
+        $crate::__str_option("jac_c_constant", $value)
+    };
+    ("jac_d_constant", $value:expr) => {
+        $crate::__str_option("jac_d_constant", $value)
+    };
+    ("jacobian_approximation", $value:expr) => {
+        $crate::__str_option("jacobian_approximation", $value)
+    };
+    ("jacobian_regularization_exponent", $value:expr) => {
+        $crate::__num_option("jacobian_regularization_exponent", $value)
+    };
+    ("jacobian_regularization_value", $value:expr) => {
+        $crate::__num_option("jacobian_regularization_value", $value)
+    };
+    ("kappa_d", $value:expr) => {
+        $crate::__num_option("kappa_d", $value)
+    };
+    ("kappa_sigma", $value:expr) => {
+        $crate::__num_option("kappa_sigma", $value)
+    };
+    ("kappa_soc", $value:expr) => {
+        $crate::__num_option("kappa_soc", $value)
+    };
+    ("least_square_init_duals", $value:expr) => {
+        $crate::__str_option("least_square_init_duals", $value)
+    };
+    ("least_square_init_primal", $value:expr) => {
+        $crate::__str_option("least_square_init_primal", $value)
+    };
+    ("limited_memory_aug_solver", $value:expr) => {
+        $crate::__str_option("limited_memory_aug_solver", $value)
+    };
+    ("limited_memory_init_val", $value:expr) => {
+        $crate::__num_option("limited_memory_init_val", $value)
+    };
+    ("limited_memory_init_val_max", $value:expr) => {
+        $crate::__num_option("limited_memory_init_val_max", $value)
+    };
+    ("limited_memory_init_val_min", $value:expr) => {
+        $crate::__num_option("limited_memory_init_val_min", $value)
+    };
+    ("limited_memory_initialization", $value:expr) => {
+        $crate::__str_option("limited_memory_initialization", $value)
+    };
+    ("limited_memory_max_history", $value:expr) => {
+        $crate::__int_option("limited_memory_max_history", $value)
+    };
+    ("limited_memory_max_skipping", $value:expr) => {
+        $crate::__int_option("limited_memory_max_skipping", $value)
+    };
+    ("limited_memory_special_for_resto", $value:expr) => {
+        $crate::__str_option("limited_memory_special_for_resto", $value)
+    };
+    ("limited_memory_update_type", $value:expr) => {
+        $crate::__str_option("limited_memory_update_type", $value)
+    };
+    ("line_search_method", $value:expr) => {
+        $crate::__str_option("line_search_method", $value)
+    };
+    ("linear_scaling_on_demand", $value:expr) => {
+        $crate::__str_option("linear_scaling_on_demand", $value)
+    };
+    ("linear_solver", $value:expr) => {
+        $crate::__str_option("linear_solver", $value)
+    };
+    ("linear_system_scaling", $value:expr) => {
+        $crate::__str_option("linear_system_scaling", $value)
+    };
+    ("ma27_ignore_singularity", $value:expr) => {
+        $crate::__str_option("ma27_ignore_singularity", $value)
+    };
+    ("ma27_la_init_factor", $value:expr) => {
+        $crate::__num_option("ma27_la_init_factor", $value)
+    };
+    ("ma27_liw_init_factor", $value:expr) => {
+        $crate::__num_option("ma27_liw_init_factor", $value)
+    };
+    ("ma27_meminc_factor", $value:expr) => {
+        $crate::__num_option("ma27_meminc_factor", $value)
+    };
+    ("ma27_pivtol", $value:expr) => {
+        $crate::__num_option("ma27_pivtol", $value)
+    };
+    ("ma27_pivtolmax", $value:expr) => {
+        $crate::__num_option("ma27_pivtolmax", $value)
+    };
+    ("ma27_skip_inertia_check", $value:expr) => {
+        $crate::__str_option("ma27_skip_inertia_check", $value)
+    };
+    ("ma57_automatic_scaling", $value:expr) => {
+        $crate::__str_option("ma57_automatic_scaling", $value)
+    };
+    ("ma57_block_size", $value:expr) => {
+        $crate::__int_option("ma57_block_size", $value)
+    };
+    ("ma57_node_amalgamation", $value:expr) => {
+        $crate::__int_option("ma57_node_amalgamation", $value)
+    };
+    ("ma57_pivot_order", $value:expr) => {
+        $crate::__int_option("ma57_pivot_order", $value)
+    };
+    ("ma57_pivtol", $value:expr) => {
+        $crate::__num_option("ma57_pivtol", $value)
+    };
+    ("ma57_pivtolmax", $value:expr) => {
+        $crate::__num_option("ma57_pivtolmax", $value)
+    };
+    ("ma57_pre_alloc", $value:expr) => {
+        $crate::__num_option("ma57_pre_alloc", $value)
+    };
+    ("ma57_small_pivot_flag", $value:expr) => {
+        $crate::__int_option("ma57_small_pivot_flag", $value)
+    };
+    ("max_cpu_time", $value:expr) => {
+        $crate::__num_option("max_cpu_time", $value)
+    };
+    ("max_filter_resets", $value:expr) => {
+        $crate::__int_option("max_filter_resets", $value)
+    };
+    ("max_hessian_perturbation", $value:expr) => {
+        $crate::__num_option("max_hessian_perturbation", $value)
+    };
+    ("max_iter", $value:expr) => {
+        $crate::__int_option("max_iter", $value)
+    };
+    ("max_refinement_steps", $value:expr) => {
+        $crate::__int_option("max_refinement_steps", $value)
+    };
+    ("max_resto_iter", $value:expr) => {
+        $crate::__int_option("max_resto_iter", $value)
+    };
+    ("max_soc", $value:expr) => {
+        $crate::__int_option("max_soc", $value)
+    };
+    ("max_soft_resto_iters", $value:expr) => {
+        $crate::__int_option("max_soft_resto_iters", $value)
+    };
+    ("max_wall_time", $value:expr) => {
+        $crate::__num_option("max_wall_time", $value)
+    };
+    ("mehrotra_algorithm", $value:expr) => {
+        $crate::__str_option("mehrotra_algorithm", $value)
+    };
+    ("min_hessian_perturbation", $value:expr) => {
+        $crate::__num_option("min_hessian_perturbation", $value)
+    };
+    ("min_refinement_steps", $value:expr) => {
+        $crate::__int_option("min_refinement_steps", $value)
+    };
+    ("mu_allow_fast_monotone_decrease", $value:expr) => {
+        $crate::__str_option("mu_allow_fast_monotone_decrease", $value)
+    };
+    ("mu_init", $value:expr) => {
+        $crate::__num_option("mu_init", $value)
+    };
+    ("mu_linear_decrease_factor", $value:expr) => {
+        $crate::__num_option("mu_linear_decrease_factor", $value)
+    };
+    ("mu_max", $value:expr) => {
+        $crate::__num_option("mu_max", $value)
+    };
+    ("mu_max_fact", $value:expr) => {
+        $crate::__num_option("mu_max_fact", $value)
+    };
+    ("mu_min", $value:expr) => {
+        $crate::__num_option("mu_min", $value)
+    };
+    ("mu_oracle", $value:expr) => {
+        $crate::__str_option("mu_oracle", $value)
+    };
+    ("mu_strategy", $value:expr) => {
+        $crate::__str_option("mu_strategy", $value)
+    };
+    ("mu_superlinear_decrease_power", $value:expr) => {
+        $crate::__num_option("mu_superlinear_decrease_power", $value)
+    };
+    ("mu_target", $value:expr) => {
+        $crate::__num_option("mu_target", $value)
+    };
+    ("mumps_dep_tol", $value:expr) => {
+        $crate::__num_option("mumps_dep_tol", $value)
+    };
+    ("mumps_mem_percent", $value:expr) => {
+        $crate::__int_option("mumps_mem_percent", $value)
+    };
+    ("mumps_permuting_scaling", $value:expr) => {
+        $crate::__int_option("mumps_permuting_scaling", $value)
+    };
+    ("mumps_pivot_order", $value:expr) => {
+        $crate::__int_option("mumps_pivot_order", $value)
+    };
+    ("mumps_pivtol", $value:expr) => {
+        $crate::__num_option("mumps_pivtol", $value)
+    };
+    ("mumps_pivtolmax", $value:expr) => {
+        $crate::__num_option("mumps_pivtolmax", $value)
+    };
+    ("mumps_scaling", $value:expr) => {
+        $crate::__int_option("mumps_scaling", $value)
+    };
+    ("neg_curv_test_tol", $value:expr) => {
+        $crate::__num_option("neg_curv_test_tol", $value)
+    };
+    ("nlp_lower_bound_inf", $value:expr) => {
+        $crate::__num_option("nlp_lower_bound_inf", $value)
+    };
+    ("nlp_scaling_constr_target_gradient", $value:expr) => {
+        $crate::__num_option("nlp_scaling_constr_target_gradient", $value)
+    };
+    ("nlp_scaling_max_gradient", $value:expr) => {
+        $crate::__num_option("nlp_scaling_max_gradient", $value)
+    };
+    ("nlp_scaling_method", $value:expr) => {
+        $crate::__str_option("nlp_scaling_method", $value)
+    };
+    ("nlp_scaling_min_value", $value:expr) => {
+        $crate::__num_option("nlp_scaling_min_value", $value)
+    };
+    ("nlp_scaling_obj_target_gradient", $value:expr) => {
+        $crate::__num_option("nlp_scaling_obj_target_gradient", $value)
+    };
+    ("nlp_upper_bound_inf", $value:expr) => {
+        $crate::__num_option("nlp_upper_bound_inf", $value)
+    };
+    ("nu_inc", $value:expr) => {
+        $crate::__num_option("nu_inc", $value)
+    };
+    ("nu_init", $value:expr) => {
+        $crate::__num_option("nu_init", $value)
+    };
+    ("num_linear_variables", $value:expr) => {
+        $crate::__int_option("num_linear_variables", $value)
+    };
+    ("obj_max_inc", $value:expr) => {
+        $crate::__num_option("obj_max_inc", $value)
+    };
+    ("obj_scaling_factor", $value:expr) => {
+        $crate::__num_option("obj_scaling_factor", $value)
+    };
+    ("option_file_name", $value:expr) => {
+        $crate::__str_option("option_file_name", $value)
+    };
+    ("output_file", $value:expr) => {
+        $crate::__str_option("output_file", $value)
+    };
+    ("pardisolib", $value:expr) => {
+        $crate::__str_option("pardisolib", $value)
+    };
+    ("perturb_always_cd", $value:expr) => {
+        $crate::__str_option("perturb_always_cd", $value)
+    };
+    ("perturb_dec_fact", $value:expr) => {
+        $crate::__num_option("perturb_dec_fact", $value)
+    };
+    ("perturb_inc_fact", $value:expr) => {
+        $crate::__num_option("perturb_inc_fact", $value)
+    };
+    ("perturb_inc_fact_first", $value:expr) => {
+        $crate::__num_option("perturb_inc_fact_first", $value)
+    };
+    ("point_perturbation_radius", $value:expr) => {
+        $crate::__num_option("point_perturbation_radius", $value)
+    };
+    ("print_advanced_options", $value:expr) => {
+        $crate::__str_option("print_advanced_options", $value)
+    };
+    ("print_frequency_iter", $value:expr) => {
+        $crate::__int_option("print_frequency_iter", $value)
+    };
+    ("print_frequency_time", $value:expr) => {
+        $crate::__num_option("print_frequency_time", $value)
+    };
+    ("print_info_string", $value:expr) => {
+        $crate::__str_option("print_info_string", $value)
+    };
+    ("print_level", $value:expr) => {
+        $crate::__int_option("print_level", $value)
+    };
+    ("print_options_documentation", $value:expr) => {
+        $crate::__str_option("print_options_documentation", $value)
+    };
+    ("print_options_mode", $value:expr) => {
+        $crate::__str_option("print_options_mode", $value)
+    };
+    ("print_timing_statistics", $value:expr) => {
+        $crate::__str_option("print_timing_statistics", $value)
+    };
+    ("print_user_options", $value:expr) => {
+        $crate::__str_option("print_user_options", $value)
+    };
+    ("quality_function_balancing_term", $value:expr) => {
+        $crate::__str_option("quality_function_balancing_term", $value)
+    };
+    ("quality_function_centrality", $value:expr) => {
+        $crate::__str_option("quality_function_centrality", $value)
+    };
+    ("quality_function_max_section_steps", $value:expr) => {
+        $crate::__int_option("quality_function_max_section_steps", $value)
+    };
+    ("quality_function_norm_type", $value:expr) => {
+        $crate::__str_option("quality_function_norm_type", $value)
+    };
+    ("quality_function_section_qf_tol", $value:expr) => {
+        $crate::__num_option("quality_function_section_qf_tol", $value)
+    };
+    ("quality_function_section_sigma_tol", $value:expr) => {
+        $crate::__num_option("quality_function_section_sigma_tol", $value)
+    };
+    ("recalc_y", $value:expr) => {
+        $crate::__str_option("recalc_y", $value)
+    };
+    ("recalc_y_feas_tol", $value:expr) => {
+        $crate::__num_option("recalc_y_feas_tol", $value)
+    };
+    ("required_infeasibility_reduction", $value:expr) => {
+        $crate::__num_option("required_infeasibility_reduction", $value)
+    };
+    ("residual_improvement_factor", $value:expr) => {
+        $crate::__num_option("residual_improvement_factor", $value)
+    };
+    ("residual_ratio_max", $value:expr) => {
+        $crate::__num_option("residual_ratio_max", $value)
+    };
+    ("residual_ratio_singular", $value:expr) => {
+        $crate::__num_option("residual_ratio_singular", $value)
+    };
+    ("resto_failure_feasibility_threshold", $value:expr) => {
+        $crate::__num_option("resto_failure_feasibility_threshold", $value)
+    };
+    ("resto_penalty_parameter", $value:expr) => {
+        $crate::__num_option("resto_penalty_parameter", $value)
+    };
+    ("resto_proximity_weight", $value:expr) => {
+        $crate::__num_option("resto_proximity_weight", $value)
+    };
+    ("rho", $value:expr) => {
+        $crate::__num_option("rho", $value)
+    };
+    ("s_max", $value:expr) => {
+        $crate::__num_option("s_max", $value)
+    };
+    ("s_phi", $value:expr) => {
+        $crate::__num_option("s_phi", $value)
+    };
+    ("s_theta", $value:expr) => {
+        $crate::__num_option("s_theta", $value)
+    };
+    ("sb", $value:expr) => {
+        $crate::__str_option("sb", $value)
+    };
+    ("sigma_max", $value:expr) => {
+        $crate::__num_option("sigma_max", $value)
+    };
+    ("sigma_min", $value:expr) => {
+        $crate::__num_option("sigma_min", $value)
+    };
+    ("skip_corr_if_neg_curv", $value:expr) => {
+        $crate::__str_option("skip_corr_if_neg_curv", $value)
+    };
+    ("skip_corr_in_monotone_mode", $value:expr) => {
+        $crate::__str_option("skip_corr_in_monotone_mode", $value)
+    };
+    ("skip_finalize_solution_call", $value:expr) => {
+        $crate::__str_option("skip_finalize_solution_call", $value)
+    };
+    ("slack_bound_frac", $value:expr) => {
+        $crate::__num_option("slack_bound_frac", $value)
+    };
+    ("slack_bound_push", $value:expr) => {
+        $crate::__num_option("slack_bound_push", $value)
+    };
+    ("soft_resto_pderror_reduction_factor", $value:expr) => {
+        $crate::__num_option("soft_resto_pderror_reduction_factor", $value)
+    };
+    ("start_with_resto", $value:expr) => {
+        $crate::__str_option("start_with_resto", $value)
+    };
+    ("tau_min", $value:expr) => {
+        $crate::__num_option("tau_min", $value)
+    };
+    ("theta_max_fact", $value:expr) => {
+        $crate::__num_option("theta_max_fact", $value)
+    };
+    ("theta_min_fact", $value:expr) => {
+        $crate::__num_option("theta_min_fact", $value)
+    };
+    ("timing_statistics", $value:expr) => {
+        $crate::__str_option("timing_statistics", $value)
+    };
+    ("tiny_step_tol", $value:expr) => {
+        $crate::__num_option("tiny_step_tol", $value)
+    };
+    ("tiny_step_y_tol", $value:expr) => {
+        $crate::__num_option("tiny_step_y_tol", $value)
+    };
+    ("tol", $value:expr) => {
+        $crate::__num_option("tol", $value)
+    };
+    ("warm_start_bound_frac", $value:expr) => {
+        $crate::__num_option("warm_start_bound_frac", $value)
+    };
+    ("warm_start_bound_push", $value:expr) => {
+        $crate::__num_option("warm_start_bound_push", $value)
+    };
+    ("warm_start_entire_iterate", $value:expr) => {
+        $crate::__str_option("warm_start_entire_iterate", $value)
+    };
+    ("warm_start_init_point", $value:expr) => {
+        $crate::__str_option("warm_start_init_point", $value)
+    };
+    ("warm_start_mult_bound_push", $value:expr) => {
+        $crate::__num_option("warm_start_mult_bound_push", $value)
+    };
+    ("warm_start_mult_init_max", $value:expr) => {
+        $crate::__num_option("warm_start_mult_init_max", $value)
+    };
+    ("warm_start_same_structure", $value:expr) => {
+        $crate::__str_option("warm_start_same_structure", $value)
+    };
+    ("warm_start_slack_bound_frac", $value:expr) => {
+        $crate::__num_option("warm_start_slack_bound_frac", $value)
+    };
+    ("warm_start_slack_bound_push", $value:expr) => {
+        $crate::__num_option("warm_start_slack_bound_push", $value)
+    };
+    ("warm_start_target_mu", $value:expr) => {
+        $crate::__num_option("warm_start_target_mu", $value)
+    };
+    ("watchdog_shortened_iter_trigger", $value:expr) => {
+        $crate::__int_option("watchdog_shortened_iter_trigger", $value)
+    };
+    ("watchdog_trial_iter_max", $value:expr) => {
+        $crate::__int_option("watchdog_trial_iter_max", $value)
+    };
+    ($name:tt, $value:expr) => {
+        compile_error!(concat!("unknown Ipopt option ", stringify!($name)))
+    };
+}
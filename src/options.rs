@@ -0,0 +1,355 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! A reusable set of Ipopt options.
+//!
+//! Unlike [`Ipopt::set_option`](struct.Ipopt.html#method.set_option), which forwards each option
+//! to Ipopt immediately, an [`OptionSet`](struct.OptionSet.html) owns its values. This makes it
+//! possible to build a configuration once, apply it to many solver instances, and (with the
+//! `serde` feature) store it alongside experiment results.
+//!
+//! After a solve, [`Ipopt::effective_options`](struct.Ipopt.html#method.effective_options)
+//! reports the value every option actually had, and whether it was a default, read from the
+//! option file, or set programmatically, so the exact configuration that produced a result can
+//! be archived.
+
+use crate::ffi;
+use crate::{BasicProblem, Ipopt, IpoptOption};
+
+use std::collections::BTreeMap;
+use std::ffi::CStr;
+use std::fmt::{Display, Formatter};
+use std::iter::FromIterator;
+use std::os::raw::c_char;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// An owned value of an Ipopt option.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(untagged))]
+pub enum OptionValue {
+    /// Integer option.
+    ///
+    /// This variant comes first so that untagged deserialization prefers integers over floats
+    /// for whole numbers.
+    Int(i32),
+    /// Numeric option.
+    Num(f64),
+    /// String option.
+    Str(String),
+}
+
+impl From<f64> for OptionValue {
+    fn from(opt: f64) -> Self {
+        OptionValue::Num(opt)
+    }
+}
+
+impl From<i32> for OptionValue {
+    fn from(opt: i32) -> Self {
+        OptionValue::Int(opt)
+    }
+}
+
+impl<'a> From<&'a str> for OptionValue {
+    fn from(opt: &'a str) -> Self {
+        OptionValue::Str(opt.to_string())
+    }
+}
+
+impl From<String> for OptionValue {
+    fn from(opt: String) -> Self {
+        OptionValue::Str(opt)
+    }
+}
+
+impl<'a> From<IpoptOption<'a>> for OptionValue {
+    fn from(opt: IpoptOption<'a>) -> Self {
+        match opt {
+            IpoptOption::Num(opt) => OptionValue::Num(opt),
+            IpoptOption::Str(opt) => OptionValue::Str(opt.to_string()),
+            IpoptOption::Int(opt) => OptionValue::Int(opt),
+        }
+    }
+}
+
+/// Borrow an `OptionValue` as an `IpoptOption` to pass it to Ipopt.
+impl<'a> From<&'a OptionValue> for IpoptOption<'a> {
+    fn from(opt: &'a OptionValue) -> Self {
+        match opt {
+            OptionValue::Num(opt) => IpoptOption::Num(*opt),
+            OptionValue::Str(opt) => IpoptOption::Str(opt.as_str()),
+            OptionValue::Int(opt) => IpoptOption::Int(*opt),
+        }
+    }
+}
+
+/// A set of named Ipopt options.
+///
+/// Options are stored sorted by name. Setting an option that is already in the set replaces its
+/// value.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct OptionSet {
+    options: BTreeMap<String, OptionValue>,
+}
+
+impl OptionSet {
+    /// Construct an empty option set.
+    pub fn new() -> Self {
+        OptionSet::default()
+    }
+
+    /// Set the option with the given name, replacing any previous value.
+    pub fn set<O: Into<OptionValue>>(&mut self, name: &str, option: O) -> &mut Self {
+        self.options.insert(name.to_string(), option.into());
+        self
+    }
+
+    /// Get the value of the option with the given name if it has been set.
+    pub fn get(&self, name: &str) -> Option<&OptionValue> {
+        self.options.get(name)
+    }
+
+    /// Remove the option with the given name from the set, returning its value.
+    pub fn remove(&mut self, name: &str) -> Option<OptionValue> {
+        self.options.remove(name)
+    }
+
+    /// Iterate over all options in the set sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &OptionValue)> {
+        self.options.iter().map(|(name, opt)| (name.as_str(), opt))
+    }
+
+    /// Number of options in the set.
+    pub fn len(&self) -> usize {
+        self.options.len()
+    }
+
+    /// Returns `true` if no options have been set.
+    pub fn is_empty(&self) -> bool {
+        self.options.is_empty()
+    }
+}
+
+impl<S: Into<String>, O: Into<OptionValue>> FromIterator<(S, O)> for OptionSet {
+    fn from_iter<I: IntoIterator<Item = (S, O)>>(iter: I) -> Self {
+        let mut set = OptionSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<S: Into<String>, O: Into<OptionValue>> Extend<(S, O)> for OptionSet {
+    fn extend<I: IntoIterator<Item = (S, O)>>(&mut self, iter: I) {
+        self.options.extend(
+            iter.into_iter()
+                .map(|(name, opt)| (name.into(), opt.into())),
+        );
+    }
+}
+
+impl<P: BasicProblem> Ipopt<P> {
+    /// Set all options in the given option set.
+    ///
+    /// Options are applied in order of their names. If Ipopt rejects an option, the remaining
+    /// options are not applied and the name of the rejected option is returned in the error.
+    pub fn set_options(&mut self, options: &OptionSet) -> Result<&mut Self, InvalidOptionError> {
+        for (name, opt) in options.iter() {
+            if !Self::set_ipopt_option(self.nlp_internal, name, opt) {
+                return Err(InvalidOptionError {
+                    name: name.to_string(),
+                });
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// Where the effective value of an option comes from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum OptionSource {
+    /// The option was not set, so Ipopt used its default value.
+    Default,
+    /// The option was set in the option file, `ipopt.opt` unless changed with the
+    /// `option_file_name` option. This includes options that were set programmatically but
+    /// overridden by the option file.
+    OptionFile,
+    /// The option was set through this crate, for instance with
+    /// [`Ipopt::set_option`](struct.Ipopt.html#method.set_option).
+    Programmatic,
+}
+
+impl Display for OptionSource {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            OptionSource::Default => write!(f, "default"),
+            OptionSource::OptionFile => write!(f, "option file"),
+            OptionSource::Programmatic => write!(f, "programmatic"),
+        }
+    }
+}
+
+/// The value an option had in a solve, along with where it was set.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EffectiveOption {
+    /// Name of the option.
+    pub name: String,
+    /// Value of the option.
+    pub value: OptionValue,
+    /// Where the value comes from.
+    pub source: OptionSource,
+}
+
+/// Collect an option reported by `cnlp_visit_options`.
+unsafe extern "C" fn visit_option(
+    name: *const c_char,
+    type_: ffi::CNLP_OptionType,
+    source: ffi::CNLP_OptionSource,
+    num_value: ffi::CNLP_Number,
+    int_value: ffi::CNLP_Int,
+    str_value: *const c_char,
+    user_data: ffi::CNLP_UserDataPtr,
+) {
+    let options = &mut *(user_data as *mut Vec<EffectiveOption>);
+    let value = match type_ {
+        ffi::CNLP_OptionType_CNLP_NUMBER_OPTION => OptionValue::Num(num_value),
+        ffi::CNLP_OptionType_CNLP_INTEGER_OPTION => OptionValue::Int(int_value),
+        _ => OptionValue::Str(CStr::from_ptr(str_value).to_string_lossy().into_owned()),
+    };
+    let source = match source {
+        ffi::CNLP_OptionSource_CNLP_USER_VALUE => OptionSource::Programmatic,
+        ffi::CNLP_OptionSource_CNLP_OPTION_FILE => OptionSource::OptionFile,
+        _ => OptionSource::Default,
+    };
+    options.push(EffectiveOption {
+        name: CStr::from_ptr(name).to_string_lossy().into_owned(),
+        value,
+        source,
+    });
+}
+
+impl<P: BasicProblem> Ipopt<P> {
+    /// The effective value of every option registered with Ipopt, sorted by name.
+    ///
+    /// The option file is read when the solver is initialized at the start of the first solve,
+    /// so this returns `None` if the problem has not been solved yet. Options changed after the
+    /// first solve are reported with the values they will have in the next solve.
+    pub fn effective_options(&self) -> Option<Vec<EffectiveOption>> {
+        let mut options = Vec::<EffectiveOption>::new();
+        let visited = unsafe {
+            ffi::cnlp_visit_options(
+                self.nlp_internal,
+                Some(visit_option),
+                &mut options as *mut Vec<EffectiveOption> as ffi::CNLP_UserDataPtr,
+            )
+        };
+        if visited != 0 {
+            Some(options)
+        } else {
+            None
+        }
+    }
+}
+
+/// Error returned when Ipopt rejects an option from an [`OptionSet`](struct.OptionSet.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct InvalidOptionError {
+    /// Name of the rejected option.
+    pub name: String,
+}
+
+impl Display for InvalidOptionError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "Ipopt rejected the option \"{}\".", self.name)
+    }
+}
+
+impl std::error::Error for InvalidOptionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn option_set_test() {
+        let mut options: OptionSet =
+            vec![("tol", OptionValue::from(1e-8)), ("max_iter", 100.into())]
+                .into_iter()
+                .collect();
+        options.set("mu_strategy", "adaptive").set("max_iter", 200);
+
+        assert_eq!(options.len(), 3);
+        assert_eq!(options.get("max_iter"), Some(&OptionValue::Int(200)));
+        let names: Vec<_> = options.iter().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["max_iter", "mu_strategy", "tol"]);
+
+        match IpoptOption::from(options.get("mu_strategy").unwrap()) {
+            IpoptOption::Str(s) => assert_eq!(s, "adaptive"),
+            _ => panic!("expected a string option"),
+        }
+
+        assert_eq!(options.remove("tol"), Some(OptionValue::Num(1e-8)));
+        assert_eq!(options.len(), 2);
+    }
+
+    #[test]
+    fn visit_option_test() {
+        let mut options = Vec::new();
+        let user_data = &mut options as *mut Vec<EffectiveOption> as ffi::CNLP_UserDataPtr;
+        unsafe {
+            visit_option(
+                b"tol\0".as_ptr() as *const c_char,
+                ffi::CNLP_OptionType_CNLP_NUMBER_OPTION,
+                ffi::CNLP_OptionSource_CNLP_OPTION_FILE,
+                1e-6,
+                0,
+                b"\0".as_ptr() as *const c_char,
+                user_data,
+            );
+            visit_option(
+                b"mu_strategy\0".as_ptr() as *const c_char,
+                ffi::CNLP_OptionType_CNLP_STRING_OPTION,
+                ffi::CNLP_OptionSource_CNLP_USER_VALUE,
+                0.0,
+                0,
+                b"adaptive\0".as_ptr() as *const c_char,
+                user_data,
+            );
+        }
+
+        assert_eq!(
+            options,
+            vec![
+                EffectiveOption {
+                    name: "tol".to_string(),
+                    value: OptionValue::Num(1e-6),
+                    source: OptionSource::OptionFile,
+                },
+                EffectiveOption {
+                    name: "mu_strategy".to_string(),
+                    value: OptionValue::Str("adaptive".to_string()),
+                    source: OptionSource::Programmatic,
+                },
+            ]
+        );
+    }
+}
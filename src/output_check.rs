@@ -0,0 +1,160 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Verification of callback outputs.
+//!
+//! Output slices given to the callbacks are write-only views over Ipopt's memory. They are not
+//! zeroed before an evaluation, and contain whatever was left there before, so every entry must
+//! be written by the callback. For large Jacobians and Hessians this saves a pass over the
+//! values in every iteration, but an entry that is accidentally skipped silently reuses a stale
+//! value.
+//!
+//! When the output check is enabled with
+//! [`Ipopt::set_output_check`](struct.Ipopt.html#method.set_output_check), outputs are filled
+//! with a sentinel before each evaluation and verified afterwards. An evaluation that leaves an
+//! entry unwritten fails, and the offending entry is reported by
+//! [`Ipopt::unwritten_output`](struct.Ipopt.html#method.unwritten_output).
+
+use crate::{BasicProblem, Ipopt, Number};
+
+use std::fmt::{Display, Formatter};
+
+/// Bit pattern of a signaling NaN that no arithmetic produces, used to mark unwritten entries.
+const SENTINEL: u64 = 0x7ff4_2b1d_0f0c_a5e5;
+
+/// An evaluation writing into an output slice.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Evaluation {
+    /// Gradient of the objective.
+    ObjectiveGradient,
+    /// Constraint values.
+    Constraint,
+    /// Values of the constraint Jacobian.
+    ConstraintJacobian,
+    /// Values of the Hessian of the Lagrangian.
+    Hessian,
+}
+
+impl Display for Evaluation {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Evaluation::ObjectiveGradient => write!(f, "objective gradient"),
+            Evaluation::Constraint => write!(f, "constraint"),
+            Evaluation::ConstraintJacobian => write!(f, "constraint Jacobian"),
+            Evaluation::Hessian => write!(f, "Hessian"),
+        }
+    }
+}
+
+/// An output entry left unwritten by a callback.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct UnwrittenOutput {
+    /// The evaluation that left the entry unwritten.
+    pub evaluation: Evaluation,
+    /// Position of the first unwritten entry in the output slice.
+    pub index: usize,
+}
+
+impl Display for UnwrittenOutput {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} entry {} was not written by the callback",
+            self.evaluation, self.index
+        )
+    }
+}
+
+/// Fill the output with the sentinel.
+pub(crate) fn poison(out: &mut [Number]) {
+    out.iter_mut()
+        .for_each(|v| *v = Number::from_bits(SENTINEL));
+}
+
+/// Position of the first entry still holding the sentinel.
+pub(crate) fn first_unwritten(out: &[Number]) -> Option<usize> {
+    out.iter().position(|v| v.to_bits() == SENTINEL)
+}
+
+impl<P: BasicProblem> Ipopt<P> {
+    /// Verify that callbacks write every entry of their outputs.
+    ///
+    /// This costs two extra passes over each output and is meant for debugging. It is disabled
+    /// by default.
+    pub fn set_output_check(&mut self, enable: bool) -> &mut Self {
+        self.check_outputs = enable;
+        self
+    }
+
+    /// The first output entry left unwritten during the last solve, if the output check is
+    /// enabled.
+    pub fn unwritten_output(&self) -> Option<UnwrittenOutput> {
+        self.unwritten_output
+    }
+
+    /// Run an evaluation writing into `out`, checking the output if enabled.
+    pub(crate) fn eval_output<F>(
+        &mut self,
+        evaluation: Evaluation,
+        out: &mut [Number],
+        eval: F,
+    ) -> bool
+    where
+        F: FnOnce(&P, &mut [Number]) -> bool,
+    {
+        if !self.check_outputs {
+            return eval(&self.nlp_interface, out);
+        }
+
+        poison(out);
+        if !eval(&self.nlp_interface, out) {
+            return false;
+        }
+        match first_unwritten(out) {
+            Some(index) => {
+                if self.unwritten_output.is_none() {
+                    self.unwritten_output = Some(UnwrittenOutput { evaluation, index });
+                }
+                false
+            }
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sentinel_test() {
+        let mut out = [0.0; 3];
+        poison(&mut out);
+        assert_eq!(first_unwritten(&out), Some(0));
+        out[0] = 1.0;
+        out[1] = Number::NAN;
+        assert_eq!(first_unwritten(&out), Some(2));
+        out[2] = out[1] * 2.0;
+        assert_eq!(first_unwritten(&out), None);
+
+        let unwritten = UnwrittenOutput {
+            evaluation: Evaluation::ConstraintJacobian,
+            index: 4,
+        };
+        assert_eq!(
+            unwritten.to_string(),
+            "constraint Jacobian entry 4 was not written by the callback"
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Parallel evaluation of independent constraint blocks with rayon.
+//!
+//! Many large problems consist of constraints, or least-squares style residual blocks, that can
+//! be evaluated independently of each other: one block per time step, per finite element or per
+//! scenario. [`ParallelConstraints`](struct.ParallelConstraints.html) collects such
+//! [`ConstraintBlock`](trait.ConstraintBlock.html)s, stacks their rows in the order they were
+//! added, and evaluates the constraint values and Jacobian values of all blocks in parallel,
+//! writing directly into the arrays given by Ipopt.
+//!
+//! A problem forwards its constraint callbacks to `ParallelConstraints`:
+//!
+//! ```no_run
+//! # use ipopt::*;
+//! # struct Model {
+//! #     constraints: ParallelConstraints,
+//! # }
+//! # impl BasicProblem for Model {
+//! #     fn num_variables(&self) -> usize { 1 }
+//! #     fn bounds(&self, _: &mut [Number], _: &mut [Number]) -> bool { true }
+//! #     fn initial_point(&self, _: &mut [Number]) -> bool { true }
+//! #     fn objective(&self, _: &[Number], _: &mut Number) -> bool { true }
+//! #     fn objective_grad(&self, _: &[Number], _: &mut [Number]) -> bool { true }
+//! # }
+//! impl ConstrainedProblem for Model {
+//!     fn num_constraints(&self) -> usize {
+//!         self.constraints.num_constraints()
+//!     }
+//!     fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+//!         self.constraints.constraint(x, g)
+//!     }
+//!     // ...
+//! #     fn num_constraint_jacobian_non_zeros(&self) -> usize {
+//! #         self.constraints.num_constraint_jacobian_non_zeros()
+//! #     }
+//! #     fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+//! #         self.constraints.constraint_bounds(g_l, g_u)
+//! #     }
+//! #     fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+//! #         self.constraints.constraint_jacobian_indices(rows, cols)
+//! #     }
+//! #     fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+//! #         self.constraints.constraint_jacobian_values(x, vals)
+//! #     }
+//! #     fn num_hessian_non_zeros(&self) -> usize { 0 }
+//! #     fn hessian_indices(&self, _: &mut [Index], _: &mut [Index]) -> bool { true }
+//! #     fn hessian_values(&self, _: &[Number], _: Number, _: &[Number], _: &mut [Number]) -> bool {
+//! #         true
+//! #     }
+//! }
+//! ```
+
+use crate::{Index, Number};
+
+/// A block of constraints that can be evaluated independently of all other blocks.
+///
+/// Variable indices are global and zero-based, while row indices of the Jacobian are local to
+/// the block.
+pub trait ConstraintBlock: Send + Sync {
+    /// Number of constraints in this block.
+    fn num_constraints(&self) -> usize;
+    /// Number of non-zeros in the Jacobian of this block.
+    fn num_constraint_jacobian_non_zeros(&self) -> usize;
+    /// Constraint function of this block.
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool;
+    /// Lower and upper bounds on the constraints of this block.
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool;
+    /// Jacobian indices of this block.
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool;
+    /// Jacobian values of this block.
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool;
+}
+
+/// A collection of independent constraint blocks evaluated in parallel.
+///
+/// Outputs are zero-based, as for the default
+/// [`IndexingStyle`](enum.IndexingStyle.html).
+pub struct ParallelConstraints {
+    blocks: Vec<Box<dyn ConstraintBlock>>,
+    /// Offsets of the rows of each block, followed by the total number of rows.
+    row_offsets: Vec<usize>,
+    /// Offsets of the Jacobian entries of each block, followed by the total number of entries.
+    jacobian_offsets: Vec<usize>,
+}
+
+impl Default for ParallelConstraints {
+    fn default() -> Self {
+        ParallelConstraints {
+            blocks: Vec::new(),
+            row_offsets: vec![0],
+            jacobian_offsets: vec![0],
+        }
+    }
+}
+
+impl ParallelConstraints {
+    /// Create an empty collection of constraint blocks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a block of constraints after all previously added blocks, and return its index.
+    ///
+    /// The sizes of the block are queried once, here.
+    pub fn add_block<B: ConstraintBlock + 'static>(&mut self, block: B) -> usize {
+        let rows = *self.row_offsets.last().unwrap() + block.num_constraints();
+        let nnz =
+            *self.jacobian_offsets.last().unwrap() + block.num_constraint_jacobian_non_zeros();
+        self.row_offsets.push(rows);
+        self.jacobian_offsets.push(nnz);
+        self.blocks.push(Box::new(block));
+        self.blocks.len() - 1
+    }
+
+    /// Number of blocks.
+    pub fn num_blocks(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Offset of the first constraint of the given block.
+    pub fn row_offset(&self, block: usize) -> usize {
+        self.row_offsets[block]
+    }
+
+    /// Total number of constraints.
+    pub fn num_constraints(&self) -> usize {
+        *self.row_offsets.last().unwrap()
+    }
+
+    /// Total number of non-zeros in the constraint Jacobian.
+    pub fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        *self.jacobian_offsets.last().unwrap()
+    }
+
+    /// Evaluate the constraints of all blocks in parallel.
+    pub fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        eval_blocks(&self.blocks, &self.row_offsets, g, &|b, g| {
+            b.constraint(x, g)
+        })
+    }
+
+    /// Lower and upper bounds on the constraints of all blocks.
+    pub fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        self.blocks.iter().enumerate().all(|(i, b)| {
+            let range = self.row_offsets[i]..self.row_offsets[i + 1];
+            b.constraint_bounds(&mut g_l[range.clone()], &mut g_u[range])
+        })
+    }
+
+    /// Jacobian indices of all blocks, with rows offset into the stacked constraints.
+    pub fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        self.blocks.iter().enumerate().all(|(i, b)| {
+            let range = self.jacobian_offsets[i]..self.jacobian_offsets[i + 1];
+            let rows = &mut rows[range.clone()];
+            if !b.constraint_jacobian_indices(rows, &mut cols[range]) {
+                return false;
+            }
+            let offset = self.row_offsets[i] as Index;
+            rows.iter_mut().for_each(|r| *r += offset);
+            true
+        })
+    }
+
+    /// Evaluate the Jacobian values of all blocks in parallel.
+    pub fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        eval_blocks(&self.blocks, &self.jacobian_offsets, vals, &|b, vals| {
+            b.constraint_jacobian_values(x, vals)
+        })
+    }
+}
+
+/// Evaluate `f` on each block with its part of `out`, recursively splitting the blocks in half
+/// and evaluating the halves in parallel.
+///
+/// `offsets` has one more entry than `blocks`, giving the end of the part of the last block.
+fn eval_blocks<F>(
+    blocks: &[Box<dyn ConstraintBlock>],
+    offsets: &[usize],
+    out: &mut [Number],
+    f: &F,
+) -> bool
+where
+    F: Fn(&dyn ConstraintBlock, &mut [Number]) -> bool + Sync,
+{
+    match blocks.len() {
+        0 => true,
+        1 => f(&*blocks[0], &mut out[..offsets[1] - offsets[0]]),
+        n => {
+            let mid = n / 2;
+            let (left, right) = out.split_at_mut(offsets[mid] - offsets[0]);
+            let (l, r) = rayon::join(
+                || eval_blocks(&blocks[..mid], &offsets[..=mid], left, f),
+                || eval_blocks(&blocks[mid..], &offsets[mid..], right, f),
+            );
+            l && r
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Constraints `x_i * x_{i+1}` for `i` in `start..end`.
+    struct Products {
+        start: usize,
+        end: usize,
+    }
+
+    impl ConstraintBlock for Products {
+        fn num_constraints(&self) -> usize {
+            self.end - self.start
+        }
+        fn num_constraint_jacobian_non_zeros(&self) -> usize {
+            2 * (self.end - self.start)
+        }
+        fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+            for (g, i) in g.iter_mut().zip(self.start..self.end) {
+                *g = x[i] * x[i + 1];
+            }
+            true
+        }
+        fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+            g_l.iter_mut().for_each(|g| *g = 0.0);
+            g_u.iter_mut().for_each(|g| *g = 1.0);
+            true
+        }
+        fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+            for (k, i) in (self.start..self.end).enumerate() {
+                rows[2 * k] = k as Index;
+                cols[2 * k] = i as Index;
+                rows[2 * k + 1] = k as Index;
+                cols[2 * k + 1] = (i + 1) as Index;
+            }
+            true
+        }
+        fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+            for (k, i) in (self.start..self.end).enumerate() {
+                vals[2 * k] = x[i + 1];
+                vals[2 * k + 1] = x[i];
+            }
+            true
+        }
+    }
+
+    #[test]
+    fn parallel_constraints_test() {
+        let mut constraints = ParallelConstraints::new();
+        constraints.add_block(Products { start: 0, end: 1 });
+        constraints.add_block(Products { start: 1, end: 3 });
+        assert_eq!(constraints.add_block(Products { start: 3, end: 4 }), 2);
+        assert_eq!(constraints.num_constraints(), 4);
+        assert_eq!(constraints.num_constraint_jacobian_non_zeros(), 8);
+        assert_eq!(constraints.row_offset(2), 3);
+
+        let x = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let mut g = [0.0; 4];
+        assert!(constraints.constraint(&x, &mut g));
+        assert_eq!(g, [2.0, 6.0, 12.0, 20.0]);
+
+        let mut rows = [0; 8];
+        let mut cols = [0; 8];
+        assert!(constraints.constraint_jacobian_indices(&mut rows, &mut cols));
+        assert_eq!(rows, [0, 0, 1, 1, 2, 2, 3, 3]);
+        assert_eq!(cols, [0, 1, 1, 2, 2, 3, 3, 4]);
+
+        let mut vals = [0.0; 8];
+        assert!(constraints.constraint_jacobian_values(&x, &mut vals));
+        assert_eq!(vals, [2.0, 1.0, 3.0, 2.0, 4.0, 3.0, 5.0, 4.0]);
+    }
+}
@@ -0,0 +1,437 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Problems depending on parameters.
+//!
+//! Many applications solve the same problem repeatedly for different values of some parameters,
+//! like load cases in structural design or the current state in model predictive control. A
+//! [`ParametricProblem`](trait.ParametricProblem.html) receives the parameter values in every
+//! callback, and a [`ParametricSolver`](struct.ParametricSolver.html) keeps them separate from
+//! the optimization variables. Changing the parameters with
+//! [`set_params`](struct.ParametricSolver.html#method.set_params) and calling
+//! [`resolve`](struct.ParametricSolver.html#method.resolve) starts Ipopt from the previous
+//! solution, which is typically much cheaper than solving from scratch when parameters change
+//! gradually.
+
+use crate::{
+    BasicProblem, ConstrainedProblem, CreateError, Index, IndexingStyle, Ipopt, Number, Solution,
+    SolveResult,
+};
+
+/// A constrained problem whose callbacks depend on a vector of parameters `p`.
+///
+/// The callbacks correspond to the ones in [`BasicProblem`](trait.BasicProblem.html) and
+/// [`ConstrainedProblem`](trait.ConstrainedProblem.html). Sizes and sparsity structure must not
+/// depend on the parameters.
+pub trait ParametricProblem {
+    /// Specify the indexing style used for arrays in this problem.
+    fn indexing_style(&self) -> IndexingStyle {
+        IndexingStyle::CStyle
+    }
+    /// Number of parameters.
+    fn num_parameters(&self) -> usize;
+    /// Total number of variables of the non-linear problem.
+    fn num_variables(&self) -> usize;
+    /// Specify lower and upper variable bounds given by `x_l` and `x_u` respectively.
+    fn bounds(&self, p: &[Number], x_l: &mut [Number], x_u: &mut [Number]) -> bool;
+    /// Construct the initial guess of the primal variables used for the first solve.
+    fn initial_point(&self, p: &[Number], x: &mut [Number]) -> bool;
+    /// Objective function. This is the function being minimized.
+    fn objective(&self, p: &[Number], x: &[Number], obj: &mut Number) -> bool;
+    /// The gradient of the objective function.
+    fn objective_grad(&self, p: &[Number], x: &[Number], grad_f: &mut [Number]) -> bool;
+    /// Number of equality and inequality constraints.
+    fn num_constraints(&self) -> usize;
+    /// Number of non-zeros in the constraint Jacobian.
+    fn num_constraint_jacobian_non_zeros(&self) -> usize;
+    /// Constraint function.
+    fn constraint(&self, p: &[Number], x: &[Number], g: &mut [Number]) -> bool;
+    /// Specify lower and upper bounds, `g_l` and `g_u` respectively, on the constraint function.
+    fn constraint_bounds(&self, p: &[Number], g_l: &mut [Number], g_u: &mut [Number]) -> bool;
+    /// Constraint Jacobian indices.
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool;
+    /// Constraint Jacobian values.
+    fn constraint_jacobian_values(&self, p: &[Number], x: &[Number], vals: &mut [Number]) -> bool;
+    /// Number of non-zeros in the Hessian matrix.
+    fn num_hessian_non_zeros(&self) -> usize;
+    /// Hessian indices.
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool;
+    /// Hessian of the Lagrangian values.
+    fn hessian_values(
+        &self,
+        p: &[Number],
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool;
+}
+
+/// The solution of the previous solve used to warm start the next one.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct WarmStart {
+    pub(crate) x: Vec<Number>,
+    z_l: Vec<Number>,
+    z_u: Vec<Number>,
+    lambda: Vec<Number>,
+}
+
+impl WarmStart {
+    /// Store the given solution, reusing the existing buffers.
+    fn store(&mut self, sol: &Solution) {
+        fn assign(dst: &mut Vec<Number>, src: &[Number]) {
+            dst.clear();
+            dst.extend_from_slice(src);
+        }
+        assign(&mut self.x, sol.primal_variables);
+        assign(&mut self.z_l, sol.lower_bound_multipliers);
+        assign(&mut self.z_u, sol.upper_bound_multipliers);
+        assign(&mut self.lambda, sol.constraint_multipliers);
+    }
+}
+
+/// A parametric problem together with the current parameter values.
+///
+/// This implements [`ConstrainedProblem`](trait.ConstrainedProblem.html) and is the problem type
+/// behind a [`ParametricSolver`](struct.ParametricSolver.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Parametric<P> {
+    problem: P,
+    params: Vec<Number>,
+    warm_start: Option<WarmStart>,
+}
+
+impl<P> Parametric<P> {
+    /// The wrapped problem.
+    pub fn problem(&self) -> &P {
+        &self.problem
+    }
+
+    /// The wrapped problem.
+    pub fn problem_mut(&mut self) -> &mut P {
+        &mut self.problem
+    }
+
+    /// Current parameter values.
+    pub fn params(&self) -> &[Number] {
+        &self.params
+    }
+}
+
+impl<P: ParametricProblem> BasicProblem for Parametric<P> {
+    fn indexing_style(&self) -> IndexingStyle {
+        self.problem.indexing_style()
+    }
+    fn num_variables(&self) -> usize {
+        self.problem.num_variables()
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        self.problem.bounds(&self.params, x_l, x_u)
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        match self.warm_start {
+            Some(ref ws) => {
+                x.copy_from_slice(&ws.x);
+                true
+            }
+            None => self.problem.initial_point(&self.params, x),
+        }
+    }
+    fn initial_bounds_multipliers(&self, z_l: &mut [Number], z_u: &mut [Number]) -> bool {
+        match self.warm_start {
+            Some(ref ws) => {
+                z_l.copy_from_slice(&ws.z_l);
+                z_u.copy_from_slice(&ws.z_u);
+            }
+            None => {
+                for (l, u) in z_l.iter_mut().zip(z_u.iter_mut()) {
+                    *l = 0.0;
+                    *u = 0.0;
+                }
+            }
+        }
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        self.problem.objective(&self.params, x, obj)
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        self.problem.objective_grad(&self.params, x, grad_f)
+    }
+}
+
+impl<P: ParametricProblem> ConstrainedProblem for Parametric<P> {
+    fn num_constraints(&self) -> usize {
+        self.problem.num_constraints()
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        self.problem.num_constraint_jacobian_non_zeros()
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        self.problem.constraint(&self.params, x, g)
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        self.problem.constraint_bounds(&self.params, g_l, g_u)
+    }
+    fn initial_constraint_multipliers(&self, lambda: &mut [Number]) -> bool {
+        match self.warm_start {
+            Some(ref ws) => lambda.copy_from_slice(&ws.lambda),
+            None => {
+                for l in lambda.iter_mut() {
+                    *l = 0.0;
+                }
+            }
+        }
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        self.problem.constraint_jacobian_indices(rows, cols)
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        self.problem
+            .constraint_jacobian_values(&self.params, x, vals)
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        self.problem.num_hessian_non_zeros()
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        self.problem.hessian_indices(rows, cols)
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        self.problem
+            .hessian_values(&self.params, x, obj_factor, lambda, vals)
+    }
+}
+
+/// A solver for a parametric problem supporting cheap warm-started re-solves.
+///
+/// Repeated solves reuse the Ipopt instance along with all buffers, so once the first solve is
+/// done, solves don't allocate on the Rust side. Run `cargo run --release --example
+/// solve_allocations` to verify this.
+pub struct ParametricSolver<P: ParametricProblem> {
+    ipopt: Ipopt<Parametric<P>>,
+    /// Buffers of a discarded warm start, reused by the next solve.
+    spare: Option<WarmStart>,
+    /// The last value of the `warm_start_init_point` option, to avoid setting it on every solve.
+    warm_start_init_point: Option<bool>,
+}
+
+impl<P: ParametricProblem> ParametricSolver<P> {
+    /// Create a solver for the given problem with initial parameter values.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the number of parameter values is not equal to
+    /// `problem.num_parameters()`.
+    pub fn new(problem: P, params: &[Number]) -> Result<Self, CreateError> {
+        assert_eq!(params.len(), problem.num_parameters());
+        let ipopt = Ipopt::new(Parametric {
+            problem,
+            params: params.to_vec(),
+            warm_start: None,
+        })?;
+        Ok(ParametricSolver {
+            ipopt,
+            spare: None,
+            warm_start_init_point: None,
+        })
+    }
+
+    /// The underlying Ipopt instance, for instance to set options.
+    pub fn ipopt(&mut self) -> &mut Ipopt<Parametric<P>> {
+        &mut self.ipopt
+    }
+
+    /// Current parameter values.
+    pub fn params(&self) -> &[Number] {
+        &self.ipopt.solver_data().problem.params
+    }
+
+    /// Set new parameter values for the next solve.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the number of parameter values changes.
+    pub fn set_params(&mut self, params: &[Number]) -> &mut Self {
+        let problem = self.ipopt.solver_data_mut().problem;
+        assert_eq!(params.len(), problem.params.len());
+        problem.params.copy_from_slice(params);
+        self
+    }
+
+    /// Solve the problem from the initial point given by the problem.
+    pub fn solve(&mut self) -> SolveResult<Parametric<P>> {
+        if let Some(warm_start) = self.ipopt.solver_data_mut().problem.warm_start.take() {
+            self.spare = Some(warm_start);
+        }
+        self.set_warm_start_init_point(false);
+        self.solve_and_store()
+    }
+
+    /// Solve the problem starting from the solution of the previous solve.
+    ///
+    /// Primal variables as well as bound and constraint multipliers are used as the initial
+    /// iterate. If there was no previous solve, this is equivalent to
+    /// [`solve`](struct.ParametricSolver.html#method.solve).
+    pub fn resolve(&mut self) -> SolveResult<Parametric<P>> {
+        if self.ipopt.solver_data().problem.warm_start.is_none() {
+            return self.solve();
+        }
+        self.set_warm_start_init_point(true);
+        self.solve_and_store()
+    }
+
+    /// The iterate used to warm start the next solve, if any.
+    pub(crate) fn warm_start(&self) -> Option<&WarmStart> {
+        self.ipopt.solver_data().problem.warm_start.as_ref()
+    }
+
+    /// Replace the iterate used to warm start the next solve.
+    pub(crate) fn set_warm_start(&mut self, warm_start: Option<WarmStart>) {
+        self.ipopt.solver_data_mut().problem.warm_start = warm_start;
+    }
+
+    fn set_warm_start_init_point(&mut self, enable: bool) {
+        if self.warm_start_init_point != Some(enable) {
+            self.ipopt
+                .set_option("warm_start_init_point", if enable { "yes" } else { "no" });
+            self.warm_start_init_point = Some(enable);
+        }
+    }
+
+    fn solve_and_store(&mut self) -> SolveResult<Parametric<P>> {
+        let spare = &mut self.spare;
+        let result = self.ipopt.solve();
+        // Reuse the buffers of the previous warm start instead of allocating new ones.
+        let mut warm_start = result
+            .solver_data
+            .problem
+            .warm_start
+            .take()
+            .or_else(|| spare.take())
+            .unwrap_or_default();
+        warm_start.store(&result.solver_data.solution);
+        result.solver_data.problem.warm_start = Some(warm_start);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// min (x - p0)^2 s.t. x >= p1
+    struct Shifted;
+
+    impl ParametricProblem for Shifted {
+        fn num_parameters(&self) -> usize {
+            2
+        }
+        fn num_variables(&self) -> usize {
+            1
+        }
+        fn bounds(&self, p: &[Number], x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+            x_l[0] = p[1];
+            x_u[0] = 2e19;
+            true
+        }
+        fn initial_point(&self, _: &[Number], x: &mut [Number]) -> bool {
+            x[0] = 0.0;
+            true
+        }
+        fn objective(&self, p: &[Number], x: &[Number], obj: &mut Number) -> bool {
+            *obj = (x[0] - p[0]) * (x[0] - p[0]);
+            true
+        }
+        fn objective_grad(&self, p: &[Number], x: &[Number], grad_f: &mut [Number]) -> bool {
+            grad_f[0] = 2.0 * (x[0] - p[0]);
+            true
+        }
+        fn num_constraints(&self) -> usize {
+            0
+        }
+        fn num_constraint_jacobian_non_zeros(&self) -> usize {
+            0
+        }
+        fn constraint(&self, _: &[Number], _: &[Number], _: &mut [Number]) -> bool {
+            true
+        }
+        fn constraint_bounds(&self, _: &[Number], _: &mut [Number], _: &mut [Number]) -> bool {
+            true
+        }
+        fn constraint_jacobian_indices(&self, _: &mut [Index], _: &mut [Index]) -> bool {
+            true
+        }
+        fn constraint_jacobian_values(&self, _: &[Number], _: &[Number], _: &mut [Number]) -> bool {
+            true
+        }
+        fn num_hessian_non_zeros(&self) -> usize {
+            1
+        }
+        fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+            rows[0] = 0;
+            cols[0] = 0;
+            true
+        }
+        fn hessian_values(
+            &self,
+            _: &[Number],
+            _: &[Number],
+            obj_factor: Number,
+            _: &[Number],
+            vals: &mut [Number],
+        ) -> bool {
+            vals[0] = 2.0 * obj_factor;
+            true
+        }
+    }
+
+    #[test]
+    fn warm_start_callbacks_test() {
+        let mut nlp = Parametric {
+            problem: Shifted,
+            params: vec![3.0, 1.0],
+            warm_start: None,
+        };
+
+        let (mut x_l, mut x_u) = ([0.0], [0.0]);
+        assert!(nlp.bounds(&mut x_l, &mut x_u));
+        assert_eq!(x_l, [1.0]);
+        let mut obj = 0.0;
+        assert!(nlp.objective(&[1.0], &mut obj));
+        assert_eq!(obj, 4.0);
+
+        let mut x = [1.0];
+        assert!(nlp.initial_point(&mut x));
+        assert_eq!(x, [0.0]);
+
+        nlp.warm_start = Some(WarmStart {
+            x: vec![3.0],
+            z_l: vec![0.5],
+            z_u: vec![0.0],
+            lambda: vec![],
+        });
+        assert!(nlp.initial_point(&mut x));
+        assert_eq!(x, [3.0]);
+        let (mut z_l, mut z_u) = ([0.0], [1.0]);
+        assert!(nlp.initial_bounds_multipliers(&mut z_l, &mut z_u));
+        assert_eq!((z_l, z_u), ([0.5], [0.0]));
+    }
+}
@@ -0,0 +1,283 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Support for the commercial Panua Pardiso solver.
+//!
+//! Panua Pardiso (formerly from pardiso-project.org) is distributed as a shared library that
+//! needs a license file at runtime. It can be linked into Ipopt at build time by pointing the
+//! `PARDISO_LIB` environment variable at the library when building `ipopt-sys`, or loaded when
+//! the solver is created with [`Ipopt::use_pardiso`](struct.Ipopt.html#method.use_pardiso).
+//!
+//! The library is looked up in `PARDISO_LIB`, then in the directories of `LD_LIBRARY_PATH`,
+//! `DYLD_LIBRARY_PATH` and `PATH`. The license file is looked up in `PARDISO_LIC_PATH`, then as
+//! `pardiso.lic` in the current and home directories, which is where Pardiso itself looks.
+
+use crate::ffi;
+use crate::{BasicProblem, Ipopt, LinearSolver, OptionRegistry, UnavailableLinearSolverError};
+
+use std::ffi::CString;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// Environment variables listing directories searched for the Pardiso library.
+const LIBRARY_PATH_VARS: [&str; 3] = ["LD_LIBRARY_PATH", "DYLD_LIBRARY_PATH", "PATH"];
+
+/// Name of the Pardiso license file.
+const LICENSE_FILE: &str = "pardiso.lic";
+
+/// Returns `true` if the given file name looks like a Pardiso shared library.
+fn is_pardiso_library(name: &str) -> bool {
+    let name = name.strip_prefix("lib").unwrap_or(name);
+    name.starts_with("pardiso")
+        && (name.ends_with(".so")
+            || name.contains(".so.")
+            || name.ends_with(".dylib")
+            || name.ends_with(".dll"))
+}
+
+/// Find the first Pardiso library in the given directories.
+fn find_in_dirs<I: IntoIterator<Item = PathBuf>>(dirs: I) -> Option<PathBuf> {
+    for dir in dirs {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        let mut found: Vec<_> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(
+                |path| match path.file_name().and_then(|name| name.to_str()) {
+                    Some(name) => is_pardiso_library(name),
+                    None => false,
+                },
+            )
+            .collect();
+        // Pick the same library regardless of the directory listing order.
+        found.sort();
+        if let Some(path) = found.into_iter().next() {
+            return Some(path);
+        }
+    }
+    None
+}
+
+/// Locate the Panua Pardiso shared library.
+///
+/// Returns the path in `PARDISO_LIB` if it is set and the file exists, or the first Pardiso
+/// library found in the directories of `LD_LIBRARY_PATH`, `DYLD_LIBRARY_PATH` and `PATH`.
+pub fn find_pardiso_library() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("PARDISO_LIB") {
+        let path = PathBuf::from(path);
+        return if path.is_file() { Some(path) } else { None };
+    }
+    let dirs = LIBRARY_PATH_VARS
+        .iter()
+        .filter_map(std::env::var_os)
+        .flat_map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>());
+    find_in_dirs(dirs)
+}
+
+/// Locate the Panua Pardiso license file.
+///
+/// Returns the path in `PARDISO_LIC_PATH` if the file exists, or the `pardiso.lic` file in the
+/// directory given by `PARDISO_LIC_PATH`, the current directory or the home directory.
+pub fn find_pardiso_license() -> Option<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Some(path) = std::env::var_os("PARDISO_LIC_PATH") {
+        let path = PathBuf::from(path);
+        candidates.push(path.join(LICENSE_FILE));
+        candidates.push(path);
+    }
+    if let Ok(dir) = std::env::current_dir() {
+        candidates.push(dir.join(LICENSE_FILE));
+    }
+    if let Some(home) = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")) {
+        candidates.push(PathBuf::from(home).join(LICENSE_FILE));
+    }
+    candidates.into_iter().find(|path| path.is_file())
+}
+
+/// Error returned when Panua Pardiso cannot be used.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PardisoError {
+    /// No Pardiso library was given or found.
+    LibraryNotFound,
+    /// No Pardiso license file was found.
+    LicenseNotFound,
+    /// The Pardiso library could not be loaded.
+    LoadFailed {
+        /// Path of the library.
+        path: PathBuf,
+        /// Message from the loader, which may be empty.
+        message: String,
+    },
+    /// The library was loaded but Ipopt cannot use Pardiso, for example because the license
+    /// is invalid or expired.
+    Unavailable(UnavailableLinearSolverError),
+}
+
+impl Display for PardisoError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            PardisoError::LibraryNotFound => write!(
+                f,
+                "The Pardiso library was not found. Set PARDISO_LIB to the path of \
+                 libpardiso or add its directory to the library search path."
+            ),
+            PardisoError::LicenseNotFound => write!(
+                f,
+                "The Pardiso license file was not found. Set PARDISO_LIC_PATH or place \
+                 {} in the current or home directory.",
+                LICENSE_FILE
+            ),
+            PardisoError::LoadFailed { path, message } => {
+                write!(f, "Failed to load the Pardiso library {}", path.display())?;
+                if message.is_empty() {
+                    write!(f, ".")
+                } else {
+                    write!(f, ": {}", message)
+                }
+            }
+            PardisoError::Unavailable(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for PardisoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PardisoError::Unavailable(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<UnavailableLinearSolverError> for PardisoError {
+    fn from(err: UnavailableLinearSolverError) -> Self {
+        PardisoError::Unavailable(err)
+    }
+}
+
+/// Load the Pardiso library through the linear solver loader of older Ipopt versions.
+fn load_library(path: &Path) -> Result<(), PardisoError> {
+    let load_failed = |message: String| PardisoError::LoadFailed {
+        path: path.to_path_buf(),
+        message,
+    };
+    let path_str = match path.to_str().and_then(|p| CString::new(p).ok()) {
+        Some(p) => p,
+        None => return Err(load_failed("invalid path".to_string())),
+    };
+    let mut msg = vec![0u8; 512];
+    let loaded = unsafe {
+        ffi::cnlp_load_pardiso_library(
+            path_str.as_ptr(),
+            msg.as_mut_ptr() as *mut ::std::os::raw::c_char,
+            msg.len() as ffi::CNLP_Int,
+        ) != 0
+    };
+    if loaded {
+        Ok(())
+    } else {
+        let len = msg.iter().position(|&c| c == 0).unwrap_or(msg.len());
+        Err(load_failed(
+            String::from_utf8_lossy(&msg[..len]).into_owned(),
+        ))
+    }
+}
+
+impl<P: BasicProblem> Ipopt<P> {
+    /// Select Panua Pardiso as the linear solver.
+    ///
+    /// The library is loaded from the given path, or located as described in the
+    /// [module documentation](index.html) if no path is given. If Ipopt was built with Pardiso
+    /// linked in through `PARDISO_LIB`, it is used as is. The license file is checked up front,
+    /// since Pardiso otherwise only reports a missing license once the first solve starts. On
+    /// Ipopt 3.14 and newer, the library is also tried on a tiny problem first, and the options
+    /// of this solver are only changed once that succeeds.
+    pub fn use_pardiso(&mut self, library: Option<&Path>) -> Result<&mut Self, PardisoError> {
+        if find_pardiso_license().is_none() {
+            return Err(PardisoError::LicenseNotFound);
+        }
+        if LinearSolver::Pardiso.is_available() {
+            self.set_linear_solver(LinearSolver::Pardiso)?;
+            return Ok(self);
+        }
+        let path = match library {
+            Some(path) => path.to_path_buf(),
+            None => find_pardiso_library().ok_or(PardisoError::LibraryNotFound)?,
+        };
+        if !path.is_file() {
+            return Err(PardisoError::LibraryNotFound);
+        }
+
+        // Ipopt 3.14 and newer load Pardiso through an option, so try the library on a tiny
+        // problem before changing the options of this solver.
+        if OptionRegistry::linked_ref().get("pardisolib").is_some() {
+            let path_str = path.to_string_lossy();
+            if !LinearSolver::Pardiso.probe(&[("pardisolib", path_str.as_ref())]) {
+                return Err(PardisoError::LoadFailed {
+                    path,
+                    message: String::new(),
+                });
+            }
+            if !Self::set_ipopt_option(self.nlp_internal, "pardisolib", path_str.as_ref())
+                || !Self::set_ipopt_option(self.nlp_internal, "linear_solver", "pardiso")
+            {
+                return Err(PardisoError::Unavailable(UnavailableLinearSolverError {
+                    solver: LinearSolver::Pardiso,
+                    available: crate::available_linear_solvers(),
+                }));
+            }
+            return Ok(self);
+        }
+
+        // Older versions load it into the process-wide linear solver loader.
+        load_library(&path)?;
+        self.set_linear_solver(LinearSolver::Pardiso)?;
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn library_name_test() {
+        assert!(is_pardiso_library("libpardiso.so"));
+        assert!(is_pardiso_library("libpardiso600-GNU800-X86-64.so"));
+        assert!(is_pardiso_library("libpardiso.so.7"));
+        assert!(is_pardiso_library("libpardiso.dylib"));
+        assert!(is_pardiso_library("pardiso.dll"));
+        assert!(!is_pardiso_library("libpardiso.a"));
+        assert!(!is_pardiso_library("libmkl_pardiso.so"));
+        assert!(!is_pardiso_library("pardiso.lic"));
+
+        let err = PardisoError::LoadFailed {
+            path: PathBuf::from("/opt/libpardiso.so"),
+            message: String::new(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Failed to load the Pardiso library /opt/libpardiso.so."
+        );
+        assert!(std::error::Error::source(&err).is_none());
+        let err = PardisoError::from(UnavailableLinearSolverError {
+            solver: LinearSolver::Pardiso,
+            available: Vec::new(),
+        });
+        assert!(std::error::Error::source(&err).is_some());
+    }
+}
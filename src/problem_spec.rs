@@ -0,0 +1,887 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! A JSON interchange format for the structure of a problem.
+//!
+//! A [`ProblemSpec`](struct.ProblemSpec.html) holds everything about a problem except its
+//! functions: the dimensions, bounds, initial point, sparsity structures of the constraint
+//! Jacobian and the Hessian of the Lagrangian, and solver options. Other tools and languages can
+//! generate it as JSON, and a small binary built on this crate reads it, evaluates the
+//! functions itself and solves the problem. It can also be taken from an existing problem with
+//! [`from_problem`](struct.ProblemSpec.html#method.from_problem) to check that a model
+//! generated elsewhere has the expected structure.
+//!
+//! The JSON document is an object:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "num_variables": 2,
+//!   "num_constraints": 1,
+//!   "indexing": "c",
+//!   "variable_bounds": { "lower": [0.0, null], "upper": [null, 4.0] },
+//!   "constraint_bounds": { "lower": [1.0], "upper": [1.0] },
+//!   "initial_point": [1.0, 1.0],
+//!   "jacobian": { "rows": [0, 0], "cols": [0, 1] },
+//!   "hessian": { "rows": [0, 1, 1], "cols": [0, 0, 1] },
+//!   "options": { "tol": 1e-8, "max_iter": 100, "mu_strategy": "adaptive" }
+//! }
+//! ```
+//!
+//! `null` bounds are infinite, and bounds at or beyond `1e19` in magnitude are written as
+//! `null`. Indices are zero-based when `indexing` is `"c"` and one-based when it is
+//! `"fortran"`. Values in `options` take the type of the option as registered with the linked
+//! Ipopt, so `"tol": 1` sets a numeric option. Options unknown to Ipopt are integer options when
+//! written as integers and numeric options otherwise. `indexing` and `options` may be omitted,
+//! and other keys are ignored.
+
+use crate::{
+    ConstrainedProblem, Index, IndexingStyle, Number, OptionRegistry, OptionSet, OptionType,
+    OptionValue, BOUND_INF,
+};
+
+use std::fmt::{Display, Formatter, Write as _};
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+/// Version of the format written by [`ProblemSpec::write_json`](struct.ProblemSpec.html#method.write_json).
+const VERSION: i64 = 1;
+
+/// Maximum nesting depth of arrays and objects in a JSON document.
+const MAX_DEPTH: usize = 128;
+
+/// Error reading, writing or building a [`ProblemSpec`](struct.ProblemSpec.html).
+#[derive(Debug)]
+pub enum ProblemSpecError {
+    /// The input is not valid JSON.
+    Syntax {
+        /// Line number, starting at one.
+        line: usize,
+        /// What was wrong with the input.
+        message: String,
+    },
+    /// The JSON document does not follow the schema, or the specification is inconsistent.
+    Invalid(String),
+    /// One of the problem callbacks returned `false`.
+    EvaluationFailed,
+    /// Reading or writing the file failed.
+    Io(io::Error),
+}
+
+impl Display for ProblemSpecError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ProblemSpecError::Syntax { line, message } => {
+                write!(f, "Invalid JSON at line {}: {}.", line, message)
+            }
+            ProblemSpecError::Invalid(message) => {
+                write!(f, "Invalid problem specification: {}.", message)
+            }
+            ProblemSpecError::EvaluationFailed => write!(f, "Failed to evaluate the problem."),
+            ProblemSpecError::Io(err) => write!(f, "Failed to read or write the problem: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ProblemSpecError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ProblemSpecError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ProblemSpecError {
+    fn from(err: io::Error) -> Self {
+        ProblemSpecError::Io(err)
+    }
+}
+
+fn invalid(message: impl Into<String>) -> ProblemSpecError {
+    ProblemSpecError::Invalid(message.into())
+}
+
+/// The structure of a problem and the options to solve it with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProblemSpec {
+    /// Indexing style of the sparsity structures.
+    pub indexing_style: IndexingStyle,
+    /// Lower bounds of the variables.
+    pub variable_lower: Vec<Number>,
+    /// Upper bounds of the variables.
+    pub variable_upper: Vec<Number>,
+    /// Lower bounds of the constraints.
+    pub constraint_lower: Vec<Number>,
+    /// Upper bounds of the constraints.
+    pub constraint_upper: Vec<Number>,
+    /// Initial point.
+    pub initial_point: Vec<Number>,
+    /// Row indices of the constraint Jacobian non-zeros.
+    pub jacobian_rows: Vec<Index>,
+    /// Column indices of the constraint Jacobian non-zeros.
+    pub jacobian_cols: Vec<Index>,
+    /// Row indices of the non-zeros in the lower triangle of the Hessian of the Lagrangian.
+    pub hessian_rows: Vec<Index>,
+    /// Column indices of the non-zeros in the lower triangle of the Hessian of the Lagrangian.
+    pub hessian_cols: Vec<Index>,
+    /// Options to solve the problem with.
+    pub options: OptionSet,
+}
+
+impl ProblemSpec {
+    /// Take the structure of a problem, without options.
+    ///
+    /// Bounds at or beyond `1e19` in magnitude are stored as infinite.
+    pub fn from_problem<P: ConstrainedProblem>(problem: &P) -> Result<Self, ProblemSpecError> {
+        let n = problem.num_variables();
+        let m = problem.num_constraints();
+        let jac_nnz = problem.num_constraint_jacobian_non_zeros();
+        let hess_nnz = problem.num_hessian_non_zeros();
+        let mut spec = ProblemSpec {
+            indexing_style: problem.indexing_style(),
+            variable_lower: vec![0.0; n],
+            variable_upper: vec![0.0; n],
+            constraint_lower: vec![0.0; m],
+            constraint_upper: vec![0.0; m],
+            initial_point: vec![0.0; n],
+            jacobian_rows: vec![0; jac_nnz],
+            jacobian_cols: vec![0; jac_nnz],
+            hessian_rows: vec![0; hess_nnz],
+            hessian_cols: vec![0; hess_nnz],
+            options: OptionSet::new(),
+        };
+        if !problem.bounds(&mut spec.variable_lower, &mut spec.variable_upper)
+            || !problem.constraint_bounds(&mut spec.constraint_lower, &mut spec.constraint_upper)
+            || !problem.initial_point(&mut spec.initial_point)
+            || !problem
+                .constraint_jacobian_indices(&mut spec.jacobian_rows, &mut spec.jacobian_cols)
+            || !problem.hessian_indices(&mut spec.hessian_rows, &mut spec.hessian_cols)
+        {
+            return Err(ProblemSpecError::EvaluationFailed);
+        }
+        for v in spec
+            .variable_lower
+            .iter_mut()
+            .chain(spec.constraint_lower.iter_mut())
+        {
+            if *v <= -BOUND_INF {
+                *v = Number::NEG_INFINITY;
+            }
+        }
+        for v in spec
+            .variable_upper
+            .iter_mut()
+            .chain(spec.constraint_upper.iter_mut())
+        {
+            if *v >= BOUND_INF {
+                *v = Number::INFINITY;
+            }
+        }
+        Ok(spec)
+    }
+
+    /// Number of variables.
+    pub fn num_variables(&self) -> usize {
+        self.variable_lower.len()
+    }
+
+    /// Number of constraints.
+    pub fn num_constraints(&self) -> usize {
+        self.constraint_lower.len()
+    }
+
+    /// Check that all vectors have consistent sizes and all indices are in bounds.
+    pub fn validate(&self) -> Result<(), ProblemSpecError> {
+        let n = self.num_variables();
+        let m = self.num_constraints();
+        let check_len = |name: &str, len: usize, expected: usize| {
+            if len == expected {
+                Ok(())
+            } else {
+                Err(invalid(format!(
+                    "{} has {} entries instead of {}",
+                    name, len, expected
+                )))
+            }
+        };
+        check_len("the variable upper bounds", self.variable_upper.len(), n)?;
+        check_len(
+            "the constraint upper bounds",
+            self.constraint_upper.len(),
+            m,
+        )?;
+        check_len("the initial point", self.initial_point.len(), n)?;
+        let jac_nnz = self.jacobian_rows.len();
+        check_len(
+            "the Jacobian column indices",
+            self.jacobian_cols.len(),
+            jac_nnz,
+        )?;
+        let hess_nnz = self.hessian_rows.len();
+        check_len(
+            "the Hessian column indices",
+            self.hessian_cols.len(),
+            hess_nnz,
+        )?;
+
+        let offset = self.indexing_style.offset();
+        let in_range = |i: Index, len: usize| i >= offset && ((i - offset) as usize) < len;
+        for (k, (&r, &c)) in self
+            .jacobian_rows
+            .iter()
+            .zip(self.jacobian_cols.iter())
+            .enumerate()
+        {
+            if !in_range(r, m) || !in_range(c, n) {
+                return Err(invalid(format!(
+                    "Jacobian entry {} at ({}, {}) is out of bounds",
+                    k, r, c
+                )));
+            }
+        }
+        for (k, (&r, &c)) in self
+            .hessian_rows
+            .iter()
+            .zip(self.hessian_cols.iter())
+            .enumerate()
+        {
+            if !in_range(r, n) || !in_range(c, n) {
+                return Err(invalid(format!(
+                    "Hessian entry {} at ({}, {}) is out of bounds",
+                    k, r, c
+                )));
+            }
+            if r < c {
+                return Err(invalid(format!(
+                    "Hessian entry {} at ({}, {}) is in the upper triangle",
+                    k, r, c
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a specification from a JSON file.
+    pub fn from_json_file<P: AsRef<Path>>(path: P) -> Result<Self, ProblemSpecError> {
+        Self::read_json(std::fs::File::open(path)?)
+    }
+
+    /// Write the specification to a JSON file.
+    pub fn write_json_file<P: AsRef<Path>>(&self, path: P) -> Result<(), ProblemSpecError> {
+        self.write_json(std::fs::File::create(path)?)
+    }
+
+    /// Read a specification in JSON format and validate it.
+    pub fn read_json<R: Read>(mut input: R) -> Result<Self, ProblemSpecError> {
+        let mut text = String::new();
+        input.read_to_string(&mut text)?;
+        let json = JsonParser::new(&text).parse_document()?;
+        let fields = match json {
+            Json::Object(fields) => fields,
+            _ => return Err(invalid("the document is not an object")),
+        };
+        let get = |key: &str| fields.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+        let require = |key: &str| get(key).ok_or_else(|| invalid(format!("missing \"{}\"", key)));
+
+        match require("version")? {
+            Json::Number(v, true) if *v == VERSION as f64 => {}
+            _ => return Err(invalid(format!("only version {} is supported", VERSION))),
+        }
+        let indexing_style = match get("indexing") {
+            None => IndexingStyle::CStyle,
+            Some(Json::String(s)) if s == "c" => IndexingStyle::CStyle,
+            Some(Json::String(s)) if s == "fortran" => IndexingStyle::FortranStyle,
+            Some(_) => return Err(invalid("\"indexing\" must be \"c\" or \"fortran\"")),
+        };
+        let num_variables = require("num_variables")?.as_count("num_variables")?;
+        let num_constraints = require("num_constraints")?.as_count("num_constraints")?;
+
+        let (variable_lower, variable_upper) =
+            bounds(require("variable_bounds")?, "variable_bounds")?;
+        let (constraint_lower, constraint_upper) =
+            bounds(require("constraint_bounds")?, "constraint_bounds")?;
+        let initial_point = require("initial_point")?.as_numbers("initial_point", None)?;
+        let (jacobian_rows, jacobian_cols) = indices(require("jacobian")?, "jacobian")?;
+        let (hessian_rows, hessian_cols) = indices(require("hessian")?, "hessian")?;
+
+        let options = match get("options") {
+            None => OptionSet::new(),
+            Some(options) => option_set(options, OptionRegistry::linked_ref())?,
+        };
+
+        let spec = ProblemSpec {
+            indexing_style,
+            variable_lower,
+            variable_upper,
+            constraint_lower,
+            constraint_upper,
+            initial_point,
+            jacobian_rows,
+            jacobian_cols,
+            hessian_rows,
+            hessian_cols,
+            options,
+        };
+        if spec.num_variables() != num_variables {
+            return Err(invalid(format!(
+                "the variable bounds have {} entries instead of {}",
+                spec.num_variables(),
+                num_variables
+            )));
+        }
+        if spec.num_constraints() != num_constraints {
+            return Err(invalid(format!(
+                "the constraint bounds have {} entries instead of {}",
+                spec.num_constraints(),
+                num_constraints
+            )));
+        }
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// Write the specification in JSON format.
+    pub fn write_json<W: Write>(&self, out: W) -> Result<(), ProblemSpecError> {
+        let mut out = io::BufWriter::new(out);
+        let indexing = match self.indexing_style {
+            IndexingStyle::CStyle => "c",
+            IndexingStyle::FortranStyle => "fortran",
+        };
+        writeln!(out, "{{")?;
+        writeln!(out, "  \"version\": {},", VERSION)?;
+        writeln!(out, "  \"num_variables\": {},", self.num_variables())?;
+        writeln!(out, "  \"num_constraints\": {},", self.num_constraints())?;
+        writeln!(out, "  \"indexing\": \"{}\",", indexing)?;
+        writeln!(
+            out,
+            "  \"variable_bounds\": {{ \"lower\": {}, \"upper\": {} }},",
+            number_array(&self.variable_lower),
+            number_array(&self.variable_upper)
+        )?;
+        writeln!(
+            out,
+            "  \"constraint_bounds\": {{ \"lower\": {}, \"upper\": {} }},",
+            number_array(&self.constraint_lower),
+            number_array(&self.constraint_upper)
+        )?;
+        writeln!(
+            out,
+            "  \"initial_point\": {},",
+            number_array(&self.initial_point)
+        )?;
+        writeln!(
+            out,
+            "  \"jacobian\": {{ \"rows\": {}, \"cols\": {} }},",
+            index_array(&self.jacobian_rows),
+            index_array(&self.jacobian_cols)
+        )?;
+        writeln!(
+            out,
+            "  \"hessian\": {{ \"rows\": {}, \"cols\": {} }},",
+            index_array(&self.hessian_rows),
+            index_array(&self.hessian_cols)
+        )?;
+        let mut options = String::new();
+        for (k, (name, value)) in self.options.iter().enumerate() {
+            if k > 0 {
+                options.push_str(", ");
+            }
+            let value = match value {
+                OptionValue::Int(i) => i.to_string(),
+                OptionValue::Num(v) if v.is_finite() => format!("{:?}", v),
+                OptionValue::Num(_) => "null".to_string(),
+                OptionValue::Str(s) => json_string(s),
+            };
+            let _ = write!(options, "{}: {}", json_string(name), value);
+        }
+        writeln!(out, "  \"options\": {{ {} }}", options)?;
+        writeln!(out, "}}")?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// Write numbers with infinite values as `null`.
+fn number_array(values: &[Number]) -> String {
+    let values: Vec<_> = values
+        .iter()
+        .map(|&v| {
+            if v.is_nan() || v.abs() >= BOUND_INF {
+                "null".to_string()
+            } else {
+                format!("{:?}", v)
+            }
+        })
+        .collect();
+    format!("[{}]", values.join(", "))
+}
+
+fn index_array(values: &[Index]) -> String {
+    let values: Vec<_> = values.iter().map(|i| i.to_string()).collect();
+    format!("[{}]", values.join(", "))
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Read `{ "lower": [...], "upper": [...] }`, where `null` is an infinite bound.
+fn bounds(json: &Json, name: &str) -> Result<(Vec<Number>, Vec<Number>), ProblemSpecError> {
+    let lower = json.field(name, "lower")?;
+    let upper = json.field(name, "upper")?;
+    Ok((
+        lower.as_numbers(name, Some(Number::NEG_INFINITY))?,
+        upper.as_numbers(name, Some(Number::INFINITY))?,
+    ))
+}
+
+/// Read `{ "rows": [...], "cols": [...] }`.
+fn indices(json: &Json, name: &str) -> Result<(Vec<Index>, Vec<Index>), ProblemSpecError> {
+    let rows = json.field(name, "rows")?.as_indices(name)?;
+    let cols = json.field(name, "cols")?.as_indices(name)?;
+    Ok((rows, cols))
+}
+
+/// Read the `options` object, giving each value the type of the option in `registry`.
+fn option_set(json: &Json, registry: &OptionRegistry) -> Result<OptionSet, ProblemSpecError> {
+    let entries = match json {
+        Json::Object(entries) => entries,
+        _ => return Err(invalid("\"options\" must be an object")),
+    };
+    let int_range = |v: Number| v.fract() == 0.0 && v.abs() <= i32::MAX as Number;
+    let mut options = OptionSet::new();
+    for (name, value) in entries.iter() {
+        let option_type = registry.get(name).map(|info| info.option_type);
+        let value = match (value, option_type) {
+            (Json::Number(v, _), Some(OptionType::Integer)) if int_range(*v) => {
+                OptionValue::Int(*v as i32)
+            }
+            (Json::Number(v, true), None) if int_range(*v) => OptionValue::Int(*v as i32),
+            (Json::Number(v, _), Some(OptionType::Number)) | (Json::Number(v, _), None) => {
+                OptionValue::Num(*v)
+            }
+            (Json::String(s), Some(OptionType::String)) | (Json::String(s), None) => {
+                OptionValue::Str(s.clone())
+            }
+            (_, Some(option_type)) => {
+                return Err(invalid(format!(
+                    "option \"{}\" must be a {}",
+                    name, option_type
+                )))
+            }
+            (_, None) => {
+                return Err(invalid(format!(
+                    "option \"{}\" must be a number or a string",
+                    name
+                )))
+            }
+        };
+        options.set(name, value);
+    }
+    Ok(options)
+}
+
+/// A parsed JSON value.
+#[derive(Clone, Debug, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    /// A number, and whether it was written as an integer.
+    Number(f64, bool),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn field(&self, name: &str, key: &str) -> Result<&Json, ProblemSpecError> {
+        match self {
+            Json::Object(fields) => match fields.iter().find(|(k, _)| k == key) {
+                Some((_, v)) => Ok(v),
+                None => Err(invalid(format!("\"{}\" is missing \"{}\"", name, key))),
+            },
+            _ => Err(invalid(format!("\"{}\" must be an object", name))),
+        }
+    }
+
+    fn as_count(&self, name: &str) -> Result<usize, ProblemSpecError> {
+        // Counts are passed to Ipopt as indices.
+        let max = (Index::MAX as f64).min(usize::MAX as f64);
+        match *self {
+            Json::Number(v, true) if v >= 0.0 && v <= max => Ok(v as usize),
+            _ => Err(invalid(format!(
+                "\"{}\" must be a non-negative integer no larger than {}",
+                name,
+                Index::MAX
+            ))),
+        }
+    }
+
+    /// Read an array of numbers, with `null` entries replaced by `null_value` if given.
+    fn as_numbers(
+        &self,
+        name: &str,
+        null_value: Option<Number>,
+    ) -> Result<Vec<Number>, ProblemSpecError> {
+        let entries = match self {
+            Json::Array(entries) => entries,
+            _ => return Err(invalid(format!("\"{}\" must be an array", name))),
+        };
+        entries
+            .iter()
+            .map(|entry| match (entry, null_value) {
+                (Json::Number(v, _), _) => Ok(*v),
+                (Json::Null, Some(v)) => Ok(v),
+                _ => Err(invalid(format!("\"{}\" must only contain numbers", name))),
+            })
+            .collect()
+    }
+
+    fn as_indices(&self, name: &str) -> Result<Vec<Index>, ProblemSpecError> {
+        let entries = match self {
+            Json::Array(entries) => entries,
+            _ => return Err(invalid(format!("\"{}\" must contain arrays", name))),
+        };
+        entries
+            .iter()
+            .map(|entry| match *entry {
+                Json::Number(v, true) if v.abs() <= Index::MAX as f64 => Ok(v as Index),
+                _ => Err(invalid(format!(
+                    "\"{}\" must only contain integer indices",
+                    name
+                ))),
+            })
+            .collect()
+    }
+}
+
+/// A recursive descent JSON parser.
+struct JsonParser<'a> {
+    text: &'a str,
+    pos: usize,
+    /// Number of arrays and objects enclosing the current position.
+    depth: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn new(text: &'a str) -> Self {
+        JsonParser {
+            text,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> ProblemSpecError {
+        ProblemSpecError::Syntax {
+            line: 1 + self.text[..self.pos].matches('\n').count(),
+            message: message.into(),
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.rest().chars().next()
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ProblemSpecError> {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{}'", c)))
+        }
+    }
+
+    fn parse_document(&mut self) -> Result<Json, ProblemSpecError> {
+        let value = self.parse_value()?;
+        if self.peek().is_some() {
+            return Err(self.error("unexpected data after the document"));
+        }
+        Ok(value)
+    }
+
+    fn parse_value(&mut self) -> Result<Json, ProblemSpecError> {
+        match self.peek() {
+            Some(c @ '{') | Some(c @ '[') => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error("the document is nested too deeply"));
+                }
+                self.depth += 1;
+                let value = if c == '{' {
+                    self.parse_object()
+                } else {
+                    self.parse_array()
+                };
+                self.depth -= 1;
+                value
+            }
+            Some('"') => Ok(Json::String(self.parse_string()?)),
+            Some('-') | Some('0'..='9') => self.parse_number(),
+            Some(_) => {
+                for &(literal, ref value) in [
+                    ("null", Json::Null),
+                    ("true", Json::Bool(true)),
+                    ("false", Json::Bool(false)),
+                ]
+                .iter()
+                {
+                    if self.rest().starts_with(literal) {
+                        self.pos += literal.len();
+                        return Ok(value.clone());
+                    }
+                }
+                Err(self.error("expected a value"))
+            }
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Json, ProblemSpecError> {
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Json::Object(fields));
+        }
+        loop {
+            if self.peek() != Some('"') {
+                return Err(self.error("expected a key"));
+            }
+            let key = self.parse_string()?;
+            self.expect(':')?;
+            fields.push((key, self.parse_value()?));
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(Json::Object(fields));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Json, ProblemSpecError> {
+        self.expect('[')?;
+        let mut entries = Vec::new();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(Json::Array(entries));
+        }
+        loop {
+            entries.push(self.parse_value()?);
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(Json::Array(entries));
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, ProblemSpecError> {
+        self.expect('"')?;
+        let mut s = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok(s);
+                }
+                '\\' => {
+                    let escaped = match chars.next() {
+                        Some((_, '"')) => '"',
+                        Some((_, '\\')) => '\\',
+                        Some((_, '/')) => '/',
+                        Some((_, 'b')) => '\u{8}',
+                        Some((_, 'f')) => '\u{c}',
+                        Some((_, 'n')) => '\n',
+                        Some((_, 'r')) => '\r',
+                        Some((_, 't')) => '\t',
+                        Some((j, 'u')) => {
+                            let rest = self.rest();
+                            let hex = |k: usize| {
+                                rest.get(k..k + 4)
+                                    .filter(|h| h.bytes().all(|b| b.is_ascii_hexdigit()))
+                                    .and_then(|h| u32::from_str_radix(h, 16).ok())
+                            };
+                            let mut code = hex(j + 1);
+                            let mut len = 4;
+                            // Characters outside the basic multilingual plane are escaped as a
+                            // pair of UTF-16 surrogates.
+                            if let Some(high @ 0xD800..=0xDBFF) = code {
+                                code = match (rest.get(j + 5..j + 7), hex(j + 7)) {
+                                    (Some("\\u"), Some(low @ 0xDC00..=0xDFFF)) => {
+                                        len += 6;
+                                        Some(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+                                    }
+                                    _ => None,
+                                };
+                            }
+                            for _ in 0..len {
+                                chars.next();
+                            }
+                            match code.and_then(std::char::from_u32) {
+                                Some(c) => c,
+                                None => return Err(self.error("invalid unicode escape")),
+                            }
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    s.push(escaped);
+                }
+                c if (c as u32) < 0x20 => return Err(self.error("control character in string")),
+                c => s.push(c),
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    fn parse_number(&mut self) -> Result<Json, ProblemSpecError> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+            .unwrap_or(rest.len());
+        let literal = &rest[..len];
+        let integer = !literal.contains(['.', 'e', 'E']);
+        match literal.parse() {
+            Ok(v) => {
+                self.pos += len;
+                Ok(Json::Number(v, integer))
+            }
+            Err(_) => Err(self.error(format!("invalid number {}", literal))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OptionInfo, RandomProblem};
+
+    #[test]
+    fn json_round_trip_test() {
+        let mut problem = RandomProblem::new(6, 3, 2);
+        problem.with_indexing_style(IndexingStyle::FortranStyle);
+        let mut spec = ProblemSpec::from_problem(&problem).unwrap();
+        spec.options
+            .set("tol", 1e-8)
+            .set("max_iter", 100)
+            .set("linear_solver", "ma\"27\"");
+        spec.validate().unwrap();
+
+        let mut out = Vec::new();
+        spec.write_json(&mut out).unwrap();
+        let read = ProblemSpec::read_json(out.as_slice()).unwrap();
+        assert_eq!(read, spec);
+        assert!(read.variable_lower.iter().any(|v| v.is_infinite()));
+
+        let err =
+            ProblemSpec::read_json("{\n  \"version\": 1,\n  \"num_variables\" 2\n}".as_bytes())
+                .unwrap_err();
+        match err {
+            ProblemSpecError::Syntax { line: 3, .. } => {}
+            err => panic!("unexpected error: {}", err),
+        }
+        spec.hessian_rows[0] = 0;
+        assert!(spec.validate().is_err());
+    }
+
+    #[test]
+    fn json_parser_test() {
+        let parse = |text: &str| JsonParser::new(text).parse_document();
+        assert_eq!(
+            parse("\"\\u00e9\\ud83d\\ude00\"").unwrap(),
+            Json::String("\u{e9}\u{1f600}".to_string())
+        );
+        assert!(parse("\"\\ud83d\"").is_err());
+        assert!(parse(&"[".repeat(MAX_DEPTH)).is_err());
+        assert!(parse(&format!(
+            "{}{}",
+            "[".repeat(MAX_DEPTH),
+            "]".repeat(MAX_DEPTH)
+        ))
+        .is_ok());
+        match parse(&"[".repeat(MAX_DEPTH + 1)) {
+            Err(ProblemSpecError::Syntax { message, .. }) => assert!(message.contains("deeply")),
+            _ => panic!("expected a syntax error"),
+        }
+        assert_eq!(parse("25").unwrap().as_count("n").unwrap(), 25);
+        assert!(parse("-1").unwrap().as_count("n").is_err());
+        assert!(parse("100000000000000000000")
+            .unwrap()
+            .as_count("n")
+            .is_err());
+
+        let info = |name: &str, option_type, default| OptionInfo {
+            name: name.to_string(),
+            option_type,
+            category: String::new(),
+            description: String::new(),
+            default,
+            lower: None,
+            upper: None,
+            valid_values: Vec::new(),
+        };
+        let registry: OptionRegistry = vec![
+            info("tol", OptionType::Number, OptionValue::Num(1e-8)),
+            info("max_iter", OptionType::Integer, OptionValue::Int(3000)),
+        ]
+        .into_iter()
+        .collect();
+        let json = parse("{ \"tol\": 1, \"max_iter\": 1e2, \"custom\": 2 }").unwrap();
+        let options = option_set(&json, &registry).unwrap();
+        let options: Vec<_> = options.iter().collect();
+        assert_eq!(
+            options,
+            vec![
+                ("custom", &OptionValue::Int(2)),
+                ("max_iter", &OptionValue::Int(100)),
+                ("tol", &OptionValue::Num(1.0)),
+            ]
+        );
+        let json = parse("{ \"max_iter\": \"x\" }").unwrap();
+        assert!(option_set(&json, &registry).is_err());
+    }
+}
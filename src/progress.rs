@@ -0,0 +1,113 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! A live progress line rendered with [`indicatif`](https://docs.rs/indicatif).
+//!
+//! With the `indicatif` feature,
+//! [`Ipopt::set_progress_bar`](struct.Ipopt.html#method.set_progress_bar) shows the iteration
+//! count, the current objective and the infeasibilities on a single line on standard error,
+//! updated in every iteration. This is meant for CLI tools, so the line is hidden when standard
+//! error is not a terminal. Combine it with `print_level` 0 to keep Ipopt's own output from
+//! interleaving with the progress line.
+
+use crate::{AlgorithmMode, BasicProblem, IntermediateCallbackData, Ipopt, SolveStatus};
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// Progress line state of an [`Ipopt`](struct.Ipopt.html) instance.
+#[derive(Debug, Default)]
+pub(crate) struct Progress {
+    /// The line of the running solve.
+    bar: Option<ProgressBar>,
+}
+
+impl Progress {
+    /// Show a new line for a solve that is about to start.
+    pub(crate) fn start(&mut self) {
+        let bar = ProgressBar::new_spinner();
+        bar.set_style(
+            ProgressStyle::with_template("{spinner} iter {pos:>4}  {msg}  [{elapsed}]").unwrap(),
+        );
+        self.bar = Some(bar);
+    }
+
+    pub(crate) fn update(&mut self, data: &IntermediateCallbackData) {
+        if let Some(bar) = self.bar.as_ref() {
+            bar.set_position(data.iter_count.max(0) as u64);
+            bar.set_message(message(data));
+        }
+    }
+
+    /// Replace the line by the final status.
+    pub(crate) fn finish(&mut self, status: SolveStatus) {
+        if let Some(bar) = self.bar.take() {
+            bar.finish_with_message(format!("{:?}", status));
+        }
+    }
+}
+
+/// The progress message of an iteration.
+fn message(data: &IntermediateCallbackData) -> String {
+    let restoration = match data.alg_mod {
+        AlgorithmMode::Regular => "",
+        AlgorithmMode::RestorationPhase => "  (restoration)",
+    };
+    format!(
+        "obj {:>13.6e}  inf_pr {:>8.2e}  inf_du {:>8.2e}  mu {:>8.2e}{}",
+        data.obj_value, data.inf_pr, data.inf_du, data.mu, restoration
+    )
+}
+
+impl<P: BasicProblem> Ipopt<P> {
+    /// Show a live progress line on standard error during solves.
+    ///
+    /// The line shows the iteration count, the current objective, the constraint violation
+    /// (`inf_pr`), the dual infeasibility (`inf_du`) and the barrier parameter, and is replaced
+    /// by the solve status when the solve finishes.
+    pub fn set_progress_bar(&mut self, enable: bool) -> &mut Self {
+        self.progress = if enable {
+            Some(Progress::default())
+        } else {
+            None
+        };
+        self.update_intermediate_callback();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_test() {
+        let data = IntermediateCallbackData {
+            alg_mod: AlgorithmMode::RestorationPhase,
+            iter_count: 12,
+            obj_value: 1.5,
+            inf_pr: 1e-3,
+            inf_du: 2e-4,
+            mu: 1e-5,
+            d_norm: 0.0,
+            regularization_size: 0.0,
+            alpha_du: 1.0,
+            alpha_pr: 1.0,
+            ls_trials: 1,
+        };
+        assert_eq!(
+            message(&data),
+            "obj    1.500000e0  inf_pr  1.00e-3  inf_du  2.00e-4  mu  1.00e-5  (restoration)"
+        );
+    }
+}
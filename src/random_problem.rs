@@ -0,0 +1,365 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Randomly generated well-posed problems.
+//!
+//! [`RandomProblem`](struct.RandomProblem.html) generates a constrained problem of any size
+//! from a seed, with a random sparsity structure for the constraint Jacobian and the Hessian of
+//! the Lagrangian. It is meant for exercising the bindings on many shapes of problems, such as
+//! in the `fuzz` integration test, rather than for benchmarking Ipopt itself.
+//!
+//! The objective is a strictly convex quadratic with a diagonally dominant Hessian plus small
+//! `w·ln(1 + x²)` terms, which bend it without breaking convexity. Constraints are either linear
+//! with a lower and an upper bound, or linear plus a convex `q/2·|x|²` term over their variables
+//! with an upper bound only. All bounds are placed around a random reference point with some
+//! slack, so the feasible set has a non-empty interior and the solution is unique.
+
+use crate::sparsity::Triplets;
+use crate::tune::Rng;
+use crate::{
+    BasicProblem, ConstrainedProblem, Index, IndexingStyle, LagrangeData, Number, SparsityBuilder,
+};
+
+/// Largest number of variables in a constraint or off-diagonal objective Hessian row.
+const MAX_ROW_ENTRIES: usize = 4;
+
+/// Bound used for infinite constraint and variable bounds.
+const INFINITY: Number = 2e19;
+
+/// A constraint `g(x) = a·x + q/2·|x|²` over a few variables.
+#[derive(Clone, Debug, PartialEq)]
+struct RandomConstraint {
+    /// Variables and linear coefficients, with distinct variables.
+    terms: Vec<(usize, Number)>,
+    /// Curvature `q`, which is zero for linear constraints.
+    curvature: Number,
+}
+
+impl RandomConstraint {
+    fn value(&self, x: &[Number]) -> Number {
+        self.terms
+            .iter()
+            .map(|&(i, a)| a * x[i] + 0.5 * self.curvature * x[i] * x[i])
+            .sum()
+    }
+}
+
+/// A randomly generated, well-posed constrained problem.
+///
+/// The same sizes and seed always produce the same problem.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RandomProblem {
+    indexing_style: IndexingStyle,
+    reference: Vec<Number>,
+    initial: Vec<Number>,
+    x_l: Vec<Number>,
+    x_u: Vec<Number>,
+    /// Lower triangular entries of the objective Hessian, with the diagonal last.
+    quadratic: Vec<(usize, usize, Number)>,
+    linear: Vec<Number>,
+    /// Weights of the `ln(1 + x²)` terms.
+    log_weights: Vec<Number>,
+    constraints: Vec<RandomConstraint>,
+    g_l: Vec<Number>,
+    g_u: Vec<Number>,
+    jacobian: Triplets,
+    hessian: Triplets,
+}
+
+/// Pick `count` distinct indices below `n`.
+fn distinct(rng: &mut Rng, n: usize, count: usize) -> Vec<usize> {
+    let mut picked = Vec::with_capacity(count);
+    while picked.len() < count.min(n) {
+        let i = rng.below(n as u64) as usize;
+        if !picked.contains(&i) {
+            picked.push(i);
+        }
+    }
+    picked
+}
+
+/// A uniform number in `[lo, hi)`.
+fn uniform_in(rng: &mut Rng, lo: Number, hi: Number) -> Number {
+    lo + rng.uniform() * (hi - lo)
+}
+
+impl RandomProblem {
+    /// Generate a problem with the given number of variables and constraints.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `num_variables` is zero.
+    pub fn new(num_variables: usize, num_constraints: usize, seed: u64) -> Self {
+        assert!(num_variables > 0, "a problem needs at least one variable");
+        let n = num_variables;
+        let mut rng = Rng(seed);
+
+        let reference: Vec<Number> = (0..n).map(|_| uniform_in(&mut rng, -2.0, 2.0)).collect();
+
+        // About a third of the variables are free, the rest are bounded on one or both sides.
+        let mut x_l = vec![-INFINITY; n];
+        let mut x_u = vec![INFINITY; n];
+        for i in 0..n {
+            match rng.below(3) {
+                0 => {}
+                1 => x_l[i] = reference[i] - uniform_in(&mut rng, 0.1, 1.0),
+                _ => {
+                    x_l[i] = reference[i] - uniform_in(&mut rng, 0.1, 1.0);
+                    x_u[i] = reference[i] + uniform_in(&mut rng, 0.1, 1.0);
+                }
+            }
+        }
+        // Start between the reference point and the bounds.
+        let initial = (0..n)
+            .map(|i| {
+                let x = reference[i] + uniform_in(&mut rng, -0.5, 0.5);
+                x.max(0.5 * (x_l[i] + reference[i]))
+                    .min(0.5 * (x_u[i] + reference[i]))
+            })
+            .collect();
+
+        let mut quadratic = Vec::new();
+        let mut diagonal = vec![1.0; n];
+        for row in 1..n {
+            let count = rng.below(MAX_ROW_ENTRIES as u64) as usize;
+            for col in distinct(&mut rng, row, count) {
+                let value = uniform_in(&mut rng, -1.0, 1.0);
+                diagonal[row] += value.abs();
+                diagonal[col] += value.abs();
+                quadratic.push((row, col, value));
+            }
+        }
+        quadratic.extend(diagonal.iter().enumerate().map(|(i, &d)| (i, i, d)));
+        let linear = (0..n).map(|_| uniform_in(&mut rng, -1.0, 1.0)).collect();
+        // The second derivative of ln(1 + x²) is at least -1/4, so these weights keep the
+        // diagonal, and with it the whole Hessian, positive definite.
+        let log_weights = (0..n).map(|_| uniform_in(&mut rng, 0.0, 0.5)).collect();
+
+        let mut constraints = Vec::with_capacity(num_constraints);
+        let mut g_l = Vec::with_capacity(num_constraints);
+        let mut g_u = Vec::with_capacity(num_constraints);
+        for _ in 0..num_constraints {
+            let count = 1 + rng.below(MAX_ROW_ENTRIES as u64) as usize;
+            let terms = distinct(&mut rng, n, count)
+                .into_iter()
+                .map(|i| (i, uniform_in(&mut rng, -1.0, 1.0)))
+                .collect();
+            let nonlinear = rng.below(2) == 0;
+            let curvature = if nonlinear {
+                uniform_in(&mut rng, 0.1, 1.0)
+            } else {
+                0.0
+            };
+            let constraint = RandomConstraint { terms, curvature };
+            let value = constraint.value(&reference);
+            g_u.push(value + uniform_in(&mut rng, 0.1, 1.0));
+            g_l.push(if nonlinear {
+                -INFINITY
+            } else {
+                value - uniform_in(&mut rng, 0.1, 1.0)
+            });
+            constraints.push(constraint);
+        }
+
+        let mut jacobian = SparsityBuilder::new(num_constraints, n);
+        for (row, constraint) in constraints.iter().enumerate() {
+            for &(col, _) in constraint.terms.iter() {
+                jacobian.add(row, col);
+            }
+        }
+        let mut hessian = SparsityBuilder::symmetric(n);
+        for &(row, col, _) in quadratic.iter() {
+            hessian.add(row, col);
+        }
+
+        RandomProblem {
+            indexing_style: IndexingStyle::CStyle,
+            reference,
+            initial,
+            x_l,
+            x_u,
+            quadratic,
+            linear,
+            log_weights,
+            constraints,
+            g_l,
+            g_u,
+            jacobian: jacobian.build().unwrap(),
+            hessian: hessian.build().unwrap(),
+        }
+    }
+
+    /// Report indices to Ipopt in the given style. Problems use C-style indexing by default.
+    pub fn with_indexing_style(&mut self, style: IndexingStyle) -> &mut Self {
+        self.indexing_style = style;
+        self
+    }
+
+    /// A point strictly inside the variable and constraint bounds.
+    pub fn reference_point(&self) -> &[Number] {
+        &self.reference
+    }
+}
+
+impl BasicProblem for RandomProblem {
+    fn indexing_style(&self) -> IndexingStyle {
+        self.indexing_style
+    }
+    fn num_variables(&self) -> usize {
+        self.reference.len()
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.copy_from_slice(&self.x_l);
+        x_u.copy_from_slice(&self.x_u);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&self.initial);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        let mut f = 0.0;
+        for &(row, col, value) in self.quadratic.iter() {
+            if row == col {
+                f += 0.5 * value * x[row] * x[row];
+            } else {
+                f += value * x[row] * x[col];
+            }
+        }
+        for i in 0..x.len() {
+            f += self.linear[i] * x[i] + self.log_weights[i] * (1.0 + x[i] * x[i]).ln();
+        }
+        *obj = f;
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        for i in 0..x.len() {
+            grad_f[i] = self.linear[i] + self.log_weights[i] * 2.0 * x[i] / (1.0 + x[i] * x[i]);
+        }
+        for &(row, col, value) in self.quadratic.iter() {
+            if row == col {
+                grad_f[row] += value * x[row];
+            } else {
+                grad_f[row] += value * x[col];
+                grad_f[col] += value * x[row];
+            }
+        }
+        true
+    }
+}
+
+impl ConstrainedProblem for RandomProblem {
+    fn num_constraints(&self) -> usize {
+        self.constraints.len()
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        self.jacobian.len()
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        for (g, constraint) in g.iter_mut().zip(self.constraints.iter()) {
+            *g = constraint.value(x);
+        }
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l.copy_from_slice(&self.g_l);
+        g_u.copy_from_slice(&self.g_u);
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        self.jacobian.write_indices(self.indexing_style, rows, cols)
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        let terms = self.constraints.iter().flat_map(|constraint| {
+            constraint
+                .terms
+                .iter()
+                .map(move |&(i, a)| a + constraint.curvature * x[i])
+        });
+        for (&slot, value) in self.jacobian.entry_slots().iter().zip(terms) {
+            vals[slot] = value;
+        }
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        self.hessian.len()
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        self.hessian.write_indices(self.indexing_style, rows, cols)
+    }
+    fn lagrangian_hessian_values(
+        &self,
+        x: &[Number],
+        lagrange: LagrangeData,
+        vals: &mut [Number],
+    ) -> bool {
+        let slots = self.hessian.entry_slots();
+        for (&slot, &(row, col, value)) in slots.iter().zip(self.quadratic.iter()) {
+            let mut h = value;
+            if row == col {
+                let xx = x[row] * x[row];
+                h += self.log_weights[row] * 2.0 * (1.0 - xx) / ((1.0 + xx) * (1.0 + xx));
+            }
+            vals[slot] = lagrange.sigma * h;
+        }
+        // The diagonal entries are the last ones added, one per variable.
+        let diagonal = &slots[slots.len() - x.len()..];
+        for (constraint, &lambda) in self.constraints.iter().zip(lagrange.lambda.iter()) {
+            for &(i, _) in constraint.terms.iter() {
+                vals[diagonal[i]] += lambda * constraint.curvature;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_problem_test() {
+        for &(n, m) in &[(1, 0), (1, 3), (5, 2), (40, 25)] {
+            let problem = RandomProblem::new(n, m, 7);
+            assert_eq!(problem, RandomProblem::new(n, m, 7));
+            assert!(crate::diagnose(&problem).is_empty());
+
+            // The reference point is strictly feasible.
+            let x = problem.reference_point();
+            let mut g = vec![0.0; m];
+            assert!(problem.constraint(x, &mut g));
+            for i in 0..m {
+                assert!(problem.g_l[i] < g[i] && g[i] < problem.g_u[i]);
+            }
+            for i in 0..n {
+                assert!(problem.x_l[i] < x[i] && x[i] < problem.x_u[i]);
+            }
+
+            // The gradient matches central differences of the objective.
+            let mut grad = vec![0.0; n];
+            assert!(problem.objective_grad(x, &mut grad));
+            let h = 1e-6;
+            let mut xh = x.to_vec();
+            for i in 0..n {
+                let (mut f_plus, mut f_minus) = (0.0, 0.0);
+                xh[i] = x[i] + h;
+                problem.objective(&xh, &mut f_plus);
+                xh[i] = x[i] - h;
+                problem.objective(&xh, &mut f_minus);
+                xh[i] = x[i];
+                assert!((grad[i] - (f_plus - f_minus) / (2.0 * h)).abs() < 1e-5);
+            }
+        }
+    }
+}
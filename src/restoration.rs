@@ -0,0 +1,292 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Diagnostics for failures of the restoration phase.
+//!
+//! Ipopt switches to its feasibility restoration phase when the regular line search cannot make
+//! progress. If restoration fails too, the solve ends with
+//! [`SolveStatus::RestorationFailed`](enum.SolveStatus.html#variant.RestorationFailed) and
+//! little explanation. The iterate at which restoration started is recorded during every solve,
+//! and [`Ipopt::restoration_diagnostics`](struct.Ipopt.html#method.restoration_diagnostics)
+//! reports the constraints violated there along with suggested remedies.
+
+use crate::ffi;
+use crate::{ConstrainedProblem, ConstraintGroups, GroupViolation, Index, Ipopt, Number};
+
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+/// Violation of a single constraint.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConstraintViolation {
+    /// Zero-based index of the constraint.
+    pub index: usize,
+    /// Name of the constraint group containing this constraint, if any.
+    pub group: Option<String>,
+    /// Value of the constraint function.
+    pub value: Number,
+    /// Lower bound of the constraint.
+    pub lower: Number,
+    /// Upper bound of the constraint.
+    pub upper: Number,
+    /// Distance of the constraint value to the feasible interval.
+    pub violation: Number,
+}
+
+impl ConstraintViolation {
+    /// Whether this is an equality constraint.
+    pub fn is_equality(&self) -> bool {
+        self.lower == self.upper
+    }
+}
+
+/// A suggestion for avoiding a restoration failure.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RestorationRemedy {
+    /// Verify the derivatives with the `derivative_test` option. Inconsistent derivatives are
+    /// the most common cause of restoration failures.
+    CheckDerivatives,
+    /// Start from a point closer to the feasible region.
+    ImproveInitialPoint,
+    /// Check the violated equality constraints for consistency and redundancy.
+    CheckEqualityConstraints,
+    /// Scale the constraints so that violations have comparable magnitudes.
+    ScaleConstraints,
+}
+
+impl Display for RestorationRemedy {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match *self {
+            RestorationRemedy::CheckDerivatives => write!(
+                f,
+                "Verify the derivatives by setting the \"derivative_test\" option to \
+                 \"second-order\"; wrong derivatives are the most common cause of restoration \
+                 failures."
+            ),
+            RestorationRemedy::ImproveInitialPoint => write!(
+                f,
+                "Provide an initial point closer to the feasible region, for instance from the \
+                 solution of a simpler problem."
+            ),
+            RestorationRemedy::CheckEqualityConstraints => write!(
+                f,
+                "Check whether the violated equality constraints are consistent and not \
+                 redundant; a rank deficient constraint Jacobian makes restoration fail."
+            ),
+            RestorationRemedy::ScaleConstraints => write!(
+                f,
+                "Scale the constraints, e.g. through the \"nlp_scaling_method\" option or \
+                 ConstrainedProblem::constraint_scaling, since violations are very large."
+            ),
+        }
+    }
+}
+
+/// Violations larger than this suggest that the constraints are badly scaled.
+const LARGE_VIOLATION: Number = 1e4;
+
+/// State of the problem at the point where the last solve entered the restoration phase.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RestorationDiagnostics {
+    /// Iteration at which restoration started.
+    pub iteration: Index,
+    /// Primal variables at which restoration started.
+    pub start_point: Vec<Number>,
+    /// Violated constraints at the start point, sorted from most to least violated.
+    pub violations: Vec<ConstraintViolation>,
+    /// Violations aggregated by constraint group, sorted from most to least violated.
+    ///
+    /// This is empty if the problem doesn't define
+    /// [`constraint_groups`](trait.ConstrainedProblem.html#method.constraint_groups).
+    pub groups: Vec<GroupViolation>,
+    /// Suggested remedies, most likely to help first.
+    pub remedies: Vec<RestorationRemedy>,
+}
+
+impl RestorationDiagnostics {
+    fn new(
+        iteration: Index,
+        start_point: Vec<Number>,
+        g: &[Number],
+        g_l: &[Number],
+        g_u: &[Number],
+        groups: &ConstraintGroups,
+    ) -> Self {
+        let mut violations: Vec<_> = g
+            .iter()
+            .zip(g_l.iter().zip(g_u.iter()))
+            .enumerate()
+            .filter_map(|(index, (&value, (&lower, &upper)))| {
+                let violation = (lower - value).max(value - upper);
+                if violation > 0.0 {
+                    Some(ConstraintViolation {
+                        index,
+                        group: groups.group_of(index).map(String::from),
+                        value,
+                        lower,
+                        upper,
+                        violation,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect();
+        violations.sort_by(|a, b| {
+            b.violation
+                .partial_cmp(&a.violation)
+                .unwrap_or(Ordering::Equal)
+        });
+
+        let mut remedies = vec![RestorationRemedy::CheckDerivatives];
+        if !violations.is_empty() {
+            remedies.push(RestorationRemedy::ImproveInitialPoint);
+        }
+        if violations.iter().any(ConstraintViolation::is_equality) {
+            remedies.push(RestorationRemedy::CheckEqualityConstraints);
+        }
+        if violations
+            .first()
+            .map_or(false, |v| v.violation > LARGE_VIOLATION)
+        {
+            remedies.push(RestorationRemedy::ScaleConstraints);
+        }
+
+        RestorationDiagnostics {
+            iteration,
+            start_point,
+            groups: groups.summarize_violations(&violations),
+            violations,
+            remedies,
+        }
+    }
+}
+
+impl Display for RestorationDiagnostics {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Restoration phase started at iteration {}.",
+            self.iteration
+        )?;
+        if self.violations.is_empty() {
+            writeln!(f, "No constraints were violated at that point.")?;
+        } else {
+            if !self.groups.is_empty() {
+                writeln!(f, "Violations by constraint group:")?;
+                for group in self.groups.iter() {
+                    writeln!(f, "  {}", group)?;
+                }
+            }
+            writeln!(f, "Most violated constraints at that point:")?;
+            for v in self.violations.iter().take(10) {
+                write!(
+                    f,
+                    "  g[{}] = {:e} with bounds [{:e}, {:e}], violated by {:e}",
+                    v.index, v.value, v.lower, v.upper, v.violation
+                )?;
+                match v.group.as_ref() {
+                    Some(group) => writeln!(f, " ({})", group)?,
+                    None => writeln!(f)?,
+                }
+            }
+            if self.violations.len() > 10 {
+                writeln!(f, "  and {} more", self.violations.len() - 10)?;
+            }
+        }
+        writeln!(f, "Suggested remedies:")?;
+        for remedy in self.remedies.iter() {
+            writeln!(f, "  - {}", remedy)?;
+        }
+        Ok(())
+    }
+}
+
+impl<P: ConstrainedProblem> Ipopt<P> {
+    /// Diagnose where the last solve entered the restoration phase.
+    ///
+    /// This is most useful when the solve ended with `SolveStatus::RestorationFailed`. Returns
+    /// `None` if the last solve never entered restoration, or if the constraints could not be
+    /// evaluated at the recorded point.
+    pub fn restoration_diagnostics(&self) -> Option<RestorationDiagnostics> {
+        let n = self.num_primal_variables;
+        let m = self.num_dual_variables;
+        let mut x = vec![0.0; n];
+        let mut iteration = 0;
+        let available = unsafe {
+            ffi::cnlp_get_restoration_start(self.nlp_internal, x.as_mut_ptr(), &mut iteration)
+        };
+        if available == 0 {
+            return None;
+        }
+
+        let problem = &self.nlp_interface;
+        let mut g = vec![0.0; m];
+        let mut g_l = vec![0.0; m];
+        let mut g_u = vec![0.0; m];
+        if !problem.constraint(&x, &mut g) || !problem.constraint_bounds(&mut g_l, &mut g_u) {
+            return None;
+        }
+
+        let groups = problem.constraint_groups();
+        Some(RestorationDiagnostics::new(
+            iteration, x, &g, &g_l, &g_u, &groups,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diagnostics_test() {
+        let g = [1.0, 5.0, -3e4, 0.5];
+        let g_l = [0.0, 0.0, 0.0, 1.0];
+        let g_u = [2.0, 4.0, 1e20, 1.0];
+        let mut groups = ConstraintGroups::new();
+        groups.add_group("box", 0..3);
+        let diag = RestorationDiagnostics::new(3, vec![0.0], &g, &g_l, &g_u, &groups);
+
+        let indices: Vec<_> = diag.violations.iter().map(|v| v.index).collect();
+        assert_eq!(indices, vec![2, 1, 3]);
+        assert_eq!(diag.violations[1].violation, 1.0);
+        assert!(diag.violations[2].is_equality());
+        let group_names: Vec<_> = diag.groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(group_names, vec!["box", crate::UNGROUPED]);
+        assert_eq!(diag.violations[0].group.as_deref(), Some("box"));
+        assert_eq!(diag.violations[2].group, None);
+        assert!(diag.to_string().contains("violated by 3e4 (box)"));
+        assert_eq!(
+            diag.remedies,
+            vec![
+                RestorationRemedy::CheckDerivatives,
+                RestorationRemedy::ImproveInitialPoint,
+                RestorationRemedy::CheckEqualityConstraints,
+                RestorationRemedy::ScaleConstraints,
+            ]
+        );
+
+        let feasible = RestorationDiagnostics::new(
+            0,
+            vec![0.0],
+            &[1.0],
+            &[0.0],
+            &[2.0],
+            &ConstraintGroups::new(),
+        );
+        assert!(feasible.violations.is_empty());
+        assert_eq!(feasible.remedies, vec![RestorationRemedy::CheckDerivatives]);
+    }
+}
@@ -0,0 +1,273 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Retrying failed solves with fallback settings.
+//!
+//! In unattended batch runs a single failed solve often just needs different settings: another
+//! linear solver, a larger initial barrier parameter, a quasi-Newton Hessian or a looser
+//! tolerance. A [`RetryPolicy`](struct.RetryPolicy.html) lists such fallbacks, and
+//! [`Ipopt::solve_with_retry`](struct.Ipopt.html#method.solve_with_retry) applies them one at a
+//! time until a solve succeeds, reporting which attempt did.
+//!
+//! Fallbacks are cumulative: each attempt keeps the settings of all previous fallbacks, since
+//! Ipopt offers no way to restore an option to its previous value.
+
+use crate::{BasicProblem, Ipopt, LinearSolver, Number, OptionSet, SolveStatus};
+
+use std::fmt::{Display, Formatter};
+
+/// A change of settings applied before retrying a failed solve.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Fallback {
+    /// Use a different linear solver. This attempt is skipped if the solver is unavailable.
+    LinearSolver(LinearSolver),
+    /// Set the initial barrier parameter (`mu_init`), typically larger than the default `0.1`
+    /// for problems with a poor initial point.
+    MuInit(Number),
+    /// Approximate the Hessian with limited-memory quasi-Newton updates.
+    LimitedMemoryHessian,
+    /// Loosen the convergence tolerance (`tol`) and the acceptable tolerance
+    /// (`acceptable_tol`).
+    Tolerance(Number),
+    /// Set arbitrary options.
+    Options(OptionSet),
+}
+
+impl Display for Fallback {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Fallback::LinearSolver(solver) => write!(f, "linear_solver = {}", solver),
+            Fallback::MuInit(mu) => write!(f, "mu_init = {:e}", mu),
+            Fallback::LimitedMemoryHessian => write!(f, "hessian_approximation = limited-memory"),
+            Fallback::Tolerance(tol) => write!(f, "tol = {:e}", tol),
+            Fallback::Options(options) => {
+                let names: Vec<_> = options.iter().map(|(name, _)| name).collect();
+                write!(f, "options {}", names.join(", "))
+            }
+        }
+    }
+}
+
+impl Fallback {
+    /// Apply the fallback, returning `false` if it could not be applied.
+    fn apply<P: BasicProblem>(&self, ipopt: &mut Ipopt<P>) -> bool {
+        match self {
+            Fallback::LinearSolver(solver) => ipopt.set_linear_solver(*solver).is_ok(),
+            Fallback::MuInit(mu) => ipopt.set_option("mu_init", *mu).is_some(),
+            Fallback::LimitedMemoryHessian => ipopt
+                .set_option("hessian_approximation", "limited-memory")
+                .is_some(),
+            Fallback::Tolerance(tol) => {
+                ipopt.set_option("tol", *tol).is_some()
+                    && ipopt.set_option("acceptable_tol", *tol).is_some()
+            }
+            Fallback::Options(options) => ipopt.set_options(options).is_ok(),
+        }
+    }
+}
+
+/// A sequence of fallbacks tried after a failed solve.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    fallbacks: Vec<Fallback>,
+    accept_acceptable: bool,
+}
+
+impl Default for RetryPolicy {
+    /// A policy without fallbacks that accepts `SolvedToAcceptableLevel`.
+    fn default() -> Self {
+        RetryPolicy {
+            fallbacks: Vec::new(),
+            accept_acceptable: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create a policy without any fallbacks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A policy with commonly useful fallbacks, in order: MUMPS as the linear solver, a larger
+    /// initial barrier parameter, a limited-memory Hessian approximation, and a looser
+    /// tolerance.
+    pub fn standard() -> Self {
+        let mut policy = Self::new();
+        policy
+            .fallback(Fallback::LinearSolver(LinearSolver::Mumps))
+            .fallback(Fallback::MuInit(1.0))
+            .fallback(Fallback::LimitedMemoryHessian)
+            .fallback(Fallback::Tolerance(1e-5));
+        policy
+    }
+
+    /// Add a fallback tried after all previously added ones.
+    pub fn fallback(&mut self, fallback: Fallback) -> &mut Self {
+        self.fallbacks.push(fallback);
+        self
+    }
+
+    /// Whether `SolvedToAcceptableLevel` counts as success. This is `true` by default.
+    pub fn accept_acceptable(&mut self, accept: bool) -> &mut Self {
+        self.accept_acceptable = accept;
+        self
+    }
+
+    /// The fallbacks of this policy.
+    pub fn fallbacks(&self) -> &[Fallback] {
+        &self.fallbacks
+    }
+
+    /// Whether a solve ending with the given status needs no retry.
+    ///
+    /// A stop requested by the user is never retried.
+    pub fn is_success(&self, status: SolveStatus) -> bool {
+        match status {
+            SolveStatus::SolveSucceeded | SolveStatus::UserRequestedStop => true,
+            SolveStatus::SolvedToAcceptableLevel => self.accept_acceptable,
+            _ => false,
+        }
+    }
+}
+
+/// A single attempt of a solve with retries.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryAttempt {
+    /// The fallback applied before this attempt, or `None` for the first attempt.
+    pub fallback: Option<Fallback>,
+    /// Status of the attempt, or `None` if the fallback could not be applied and the attempt
+    /// was skipped.
+    pub status: Option<SolveStatus>,
+}
+
+/// Report of all attempts made by
+/// [`Ipopt::solve_with_retry`](struct.Ipopt.html#method.solve_with_retry).
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryReport {
+    /// All attempts in order.
+    pub attempts: Vec<RetryAttempt>,
+    /// Index into `attempts` of the successful attempt, if any.
+    pub succeeded: Option<usize>,
+}
+
+impl RetryReport {
+    /// Status of the last attempt that was run.
+    pub fn status(&self) -> Option<SolveStatus> {
+        self.attempts.iter().rev().find_map(|a| a.status)
+    }
+
+    /// The fallback that made the solve succeed, or `None` if the first attempt succeeded or no
+    /// attempt did.
+    pub fn successful_fallback(&self) -> Option<&Fallback> {
+        self.succeeded
+            .and_then(|i| self.attempts[i].fallback.as_ref())
+    }
+}
+
+impl Display for RetryReport {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        for (i, attempt) in self.attempts.iter().enumerate() {
+            write!(f, "attempt {}", i)?;
+            if let Some(fallback) = attempt.fallback.as_ref() {
+                write!(f, " ({})", fallback)?;
+            }
+            match attempt.status {
+                Some(status) => writeln!(f, ": {:?}", status)?,
+                None => writeln!(f, ": skipped")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<P: BasicProblem> Ipopt<P> {
+    /// Solve the problem, retrying with the fallbacks of the given policy on failure.
+    ///
+    /// Each retry starts from the initial point given by the problem. The solution of the last
+    /// attempt is available through [`solver_data`](struct.Ipopt.html#method.solver_data)
+    /// afterwards.
+    pub fn solve_with_retry(&mut self, policy: &RetryPolicy) -> RetryReport {
+        let status = self.solve().status;
+        let mut report = RetryReport {
+            attempts: vec![RetryAttempt {
+                fallback: None,
+                status: Some(status),
+            }],
+            succeeded: None,
+        };
+        if policy.is_success(status) {
+            report.succeeded = Some(0);
+            return report;
+        }
+
+        for fallback in policy.fallbacks.iter() {
+            let status = if fallback.apply(self) {
+                Some(self.solve().status)
+            } else {
+                None
+            };
+            report.attempts.push(RetryAttempt {
+                fallback: Some(fallback.clone()),
+                status,
+            });
+            if matches!(status, Some(s) if policy.is_success(s)) {
+                report.succeeded = Some(report.attempts.len() - 1);
+                break;
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policy_test() {
+        let mut policy = RetryPolicy::standard();
+        assert_eq!(policy.fallbacks().len(), 4);
+        assert!(policy.is_success(SolveStatus::SolvedToAcceptableLevel));
+        policy.accept_acceptable(false);
+        assert!(!policy.is_success(SolveStatus::SolvedToAcceptableLevel));
+        assert!(!policy.is_success(SolveStatus::RestorationFailed));
+
+        let report = RetryReport {
+            attempts: vec![
+                RetryAttempt {
+                    fallback: None,
+                    status: Some(SolveStatus::RestorationFailed),
+                },
+                RetryAttempt {
+                    fallback: Some(Fallback::LinearSolver(LinearSolver::Ma57)),
+                    status: None,
+                },
+                RetryAttempt {
+                    fallback: Some(Fallback::MuInit(1.0)),
+                    status: Some(SolveStatus::SolveSucceeded),
+                },
+            ],
+            succeeded: Some(2),
+        };
+        assert_eq!(report.status(), Some(SolveStatus::SolveSucceeded));
+        assert_eq!(report.successful_fallback(), Some(&Fallback::MuInit(1.0)));
+        assert_eq!(
+            report.to_string(),
+            "attempt 0: RestorationFailed\n\
+             attempt 1 (linear_solver = ma57): skipped\n\
+             attempt 2 (mu_init = 1e0): SolveSucceeded\n"
+        );
+    }
+}
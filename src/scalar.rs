@@ -0,0 +1,451 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Problems with scalars other than `Number`.
+//!
+//! Ipopt works in double precision, but the natural data of some models, for instance data
+//! produced on a GPU or by machine learning frameworks, is `f32`. Such problems can implement
+//! [`ScalarBasicProblem`](trait.ScalarBasicProblem.html) and
+//! [`ScalarConstrainedProblem`](trait.ScalarConstrainedProblem.html), which mirror
+//! [`BasicProblem`](trait.BasicProblem.html) and
+//! [`ConstrainedProblem`](trait.ConstrainedProblem.html) with an associated scalar type, and be
+//! wrapped in a [`ScalarAdapter`](struct.ScalarAdapter.html) to be solved by Ipopt. The adapter
+//! converts inputs to the problem scalar and promotes outputs to `Number` through buffers
+//! that are allocated once and reused in every callback.
+
+use crate::{AlgorithmMode, BasicProblem, ConstrainedProblem, Index, IndexingStyle, Number};
+
+use std::cell::RefCell;
+
+/// A scalar type that can be converted to and from `Number`.
+pub trait Scalar: Copy + Default + std::fmt::Debug {
+    /// Convert from `Number`, possibly losing precision.
+    fn from_number(x: Number) -> Self;
+    /// Convert to `Number`.
+    fn to_number(self) -> Number;
+}
+
+impl Scalar for f32 {
+    fn from_number(x: Number) -> Self {
+        x as f32
+    }
+    fn to_number(self) -> Number {
+        Number::from(self)
+    }
+}
+
+impl Scalar for f64 {
+    fn from_number(x: Number) -> Self {
+        x
+    }
+    fn to_number(self) -> Number {
+        self
+    }
+}
+
+/// The counterpart of [`BasicProblem`](trait.BasicProblem.html) for problems with a custom
+/// scalar type.
+///
+/// See [`BasicProblem`](trait.BasicProblem.html) for the documentation of each callback.
+pub trait ScalarBasicProblem {
+    /// The scalar type of this problem.
+    type Scalar: Scalar;
+    /// Specify the indexing style used for arrays in this problem.
+    fn indexing_style(&self) -> IndexingStyle {
+        IndexingStyle::CStyle
+    }
+    /// Total number of variables of the non-linear problem.
+    fn num_variables(&self) -> usize;
+    /// Specify lower and upper variable bounds.
+    fn bounds(&self, x_l: &mut [Self::Scalar], x_u: &mut [Self::Scalar]) -> bool;
+    /// Construct the initial guess of the primal variables.
+    fn initial_point(&self, x: &mut [Self::Scalar]) -> bool;
+    /// Construct the initial guess of the lower and upper bounds multipliers.
+    fn initial_bounds_multipliers(
+        &self,
+        z_l: &mut [Self::Scalar],
+        z_u: &mut [Self::Scalar],
+    ) -> bool {
+        for (l, u) in z_l.iter_mut().zip(z_u.iter_mut()) {
+            *l = Self::Scalar::default();
+            *u = Self::Scalar::default();
+        }
+        true
+    }
+    /// Objective function.
+    fn objective(&self, x: &[Self::Scalar], obj: &mut Self::Scalar) -> bool;
+    /// Gradient of the objective function.
+    fn objective_grad(&self, x: &[Self::Scalar], grad_f: &mut [Self::Scalar]) -> bool;
+    /// Provide custom variable scaling.
+    fn variable_scaling(&self, _x_scaling: &mut [Self::Scalar]) -> bool {
+        false
+    }
+    /// Provide custom scaling for the objective function.
+    fn objective_scaling(&self) -> f64 {
+        1.0
+    }
+    /// Called when Ipopt switches between the regular and restoration phases.
+    fn algorithm_mode_changed(&mut self, _mode: AlgorithmMode) {}
+}
+
+/// The counterpart of [`ConstrainedProblem`](trait.ConstrainedProblem.html) for problems with a
+/// custom scalar type.
+///
+/// See [`ConstrainedProblem`](trait.ConstrainedProblem.html) for the documentation of each
+/// callback.
+pub trait ScalarConstrainedProblem: ScalarBasicProblem {
+    /// Number of equality and inequality constraints.
+    fn num_constraints(&self) -> usize;
+    /// Number of non-zeros in the constraint Jacobian.
+    fn num_constraint_jacobian_non_zeros(&self) -> usize;
+    /// Constraint function.
+    fn constraint(&self, x: &[Self::Scalar], g: &mut [Self::Scalar]) -> bool;
+    /// Specify lower and upper bounds on the constraint function.
+    fn constraint_bounds(&self, g_l: &mut [Self::Scalar], g_u: &mut [Self::Scalar]) -> bool;
+    /// Construct the initial guess of the constraint multipliers.
+    fn initial_constraint_multipliers(&self, lambda: &mut [Self::Scalar]) -> bool {
+        for l in lambda.iter_mut() {
+            *l = Self::Scalar::default();
+        }
+        true
+    }
+    /// Constraint Jacobian indices.
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool;
+    /// Constraint Jacobian values.
+    fn constraint_jacobian_values(&self, x: &[Self::Scalar], vals: &mut [Self::Scalar]) -> bool;
+    /// Number of non-zeros in the Hessian matrix.
+    fn num_hessian_non_zeros(&self) -> usize;
+    /// Hessian indices.
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool;
+    /// Hessian values.
+    fn hessian_values(
+        &self,
+        x: &[Self::Scalar],
+        obj_factor: Self::Scalar,
+        lambda: &[Self::Scalar],
+        vals: &mut [Self::Scalar],
+    ) -> bool;
+    /// Provide custom constraint function scaling.
+    fn constraint_scaling(&self, _g_scaling: &mut [Self::Scalar]) -> bool {
+        false
+    }
+}
+
+/// Reusable conversion buffers.
+#[derive(Clone, Debug, Default)]
+struct Buffers<T> {
+    input: Vec<T>,
+    multipliers: Vec<T>,
+    output: Vec<T>,
+    second_output: Vec<T>,
+}
+
+/// Convert `src` into `dst`, reusing the allocation of `dst`.
+fn demote<T: Scalar>(src: &[Number], dst: &mut Vec<T>) {
+    dst.clear();
+    dst.extend(src.iter().map(|&x| T::from_number(x)));
+}
+
+/// Resize `buf` to hold `n` values.
+fn reserve<T: Scalar>(buf: &mut Vec<T>, n: usize) {
+    buf.clear();
+    buf.resize(n, T::default());
+}
+
+/// Promote `src` into `dst`.
+fn promote<T: Scalar>(src: &[T], dst: &mut [Number]) {
+    for (d, &s) in dst.iter_mut().zip(src.iter()) {
+        *d = s.to_number();
+    }
+}
+
+/// Adapter solving a problem with a custom scalar type with Ipopt.
+///
+/// This implements [`BasicProblem`](trait.BasicProblem.html), and
+/// [`ConstrainedProblem`](trait.ConstrainedProblem.html) if the wrapped problem implements
+/// [`ScalarConstrainedProblem`](trait.ScalarConstrainedProblem.html).
+#[derive(Debug)]
+pub struct ScalarAdapter<P: ScalarBasicProblem> {
+    problem: P,
+    buffers: RefCell<Buffers<P::Scalar>>,
+}
+
+impl<P: ScalarBasicProblem> ScalarAdapter<P> {
+    /// Wrap the given problem.
+    pub fn new(problem: P) -> Self {
+        ScalarAdapter {
+            problem,
+            buffers: RefCell::new(Buffers::default()),
+        }
+    }
+
+    /// The wrapped problem.
+    pub fn problem(&self) -> &P {
+        &self.problem
+    }
+
+    /// The wrapped problem.
+    pub fn problem_mut(&mut self) -> &mut P {
+        &mut self.problem
+    }
+
+    /// Unwrap the problem.
+    pub fn into_inner(self) -> P {
+        self.problem
+    }
+
+    /// Call `f` with a buffer for output of the given size and promote the result to `out`.
+    fn output(&self, out: &mut [Number], f: impl FnOnce(&P, &mut [P::Scalar]) -> bool) -> bool {
+        let mut buffers = self.buffers.borrow_mut();
+        reserve(&mut buffers.output, out.len());
+        let ok = f(&self.problem, &mut buffers.output);
+        if ok {
+            promote(&buffers.output, out);
+        }
+        ok
+    }
+
+    /// Like `output` but for two outputs of the same size.
+    fn output_pair(
+        &self,
+        a: &mut [Number],
+        b: &mut [Number],
+        f: impl FnOnce(&P, &mut [P::Scalar], &mut [P::Scalar]) -> bool,
+    ) -> bool {
+        let buffers = &mut *self.buffers.borrow_mut();
+        reserve(&mut buffers.output, a.len());
+        reserve(&mut buffers.second_output, b.len());
+        let ok = f(
+            &self.problem,
+            &mut buffers.output,
+            &mut buffers.second_output,
+        );
+        if ok {
+            promote(&buffers.output, a);
+            promote(&buffers.second_output, b);
+        }
+        ok
+    }
+
+    /// Call `f` with `x` converted to the problem scalar and an output buffer.
+    fn map(
+        &self,
+        x: &[Number],
+        out: &mut [Number],
+        f: impl FnOnce(&P, &[P::Scalar], &mut [P::Scalar]) -> bool,
+    ) -> bool {
+        let buffers = &mut *self.buffers.borrow_mut();
+        demote(x, &mut buffers.input);
+        reserve(&mut buffers.output, out.len());
+        let ok = f(&self.problem, &buffers.input, &mut buffers.output);
+        if ok {
+            promote(&buffers.output, out);
+        }
+        ok
+    }
+}
+
+impl<P: ScalarBasicProblem> BasicProblem for ScalarAdapter<P> {
+    fn indexing_style(&self) -> IndexingStyle {
+        self.problem.indexing_style()
+    }
+    fn num_variables(&self) -> usize {
+        self.problem.num_variables()
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        self.output_pair(x_l, x_u, |p, l, u| p.bounds(l, u))
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        self.output(x, |p, x| p.initial_point(x))
+    }
+    fn initial_bounds_multipliers(&self, z_l: &mut [Number], z_u: &mut [Number]) -> bool {
+        self.output_pair(z_l, z_u, |p, l, u| p.initial_bounds_multipliers(l, u))
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        let mut out = [0.0];
+        let ok = self.map(x, &mut out, |p, x, obj| p.objective(x, &mut obj[0]));
+        *obj = out[0];
+        ok
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        self.map(x, grad_f, |p, x, grad_f| p.objective_grad(x, grad_f))
+    }
+    fn variable_scaling(&self, x_scaling: &mut [Number]) -> bool {
+        self.output(x_scaling, |p, s| p.variable_scaling(s))
+    }
+    fn objective_scaling(&self) -> f64 {
+        self.problem.objective_scaling()
+    }
+    fn algorithm_mode_changed(&mut self, mode: AlgorithmMode) {
+        self.problem.algorithm_mode_changed(mode)
+    }
+}
+
+impl<P: ScalarConstrainedProblem> ConstrainedProblem for ScalarAdapter<P> {
+    fn num_constraints(&self) -> usize {
+        self.problem.num_constraints()
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        self.problem.num_constraint_jacobian_non_zeros()
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        self.map(x, g, |p, x, g| p.constraint(x, g))
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        self.output_pair(g_l, g_u, |p, l, u| p.constraint_bounds(l, u))
+    }
+    fn initial_constraint_multipliers(&self, lambda: &mut [Number]) -> bool {
+        self.output(lambda, |p, l| p.initial_constraint_multipliers(l))
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        self.problem.constraint_jacobian_indices(rows, cols)
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        self.map(x, vals, |p, x, vals| p.constraint_jacobian_values(x, vals))
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        self.problem.num_hessian_non_zeros()
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        self.problem.hessian_indices(rows, cols)
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        let buffers = &mut *self.buffers.borrow_mut();
+        demote(x, &mut buffers.input);
+        demote(lambda, &mut buffers.multipliers);
+        reserve(&mut buffers.output, vals.len());
+        let ok = self.problem.hessian_values(
+            &buffers.input,
+            P::Scalar::from_number(obj_factor),
+            &buffers.multipliers,
+            &mut buffers.output,
+        );
+        if ok {
+            promote(&buffers.output, vals);
+        }
+        ok
+    }
+    fn constraint_scaling(&self, g_scaling: &mut [Number]) -> bool {
+        self.output(g_scaling, |p, s| p.constraint_scaling(s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// min sum (x_i - 0.5)^2 s.t. x_0 + x_1 = 1, in single precision.
+    struct Single;
+
+    impl ScalarBasicProblem for Single {
+        type Scalar = f32;
+        fn num_variables(&self) -> usize {
+            2
+        }
+        fn bounds(&self, x_l: &mut [f32], x_u: &mut [f32]) -> bool {
+            x_l.copy_from_slice(&[-1.0e20; 2]);
+            x_u.copy_from_slice(&[1.0e20; 2]);
+            true
+        }
+        fn initial_point(&self, x: &mut [f32]) -> bool {
+            x.copy_from_slice(&[0.25, 0.75]);
+            true
+        }
+        fn objective(&self, x: &[f32], obj: &mut f32) -> bool {
+            *obj = x.iter().map(|&x| (x - 0.5) * (x - 0.5)).sum();
+            true
+        }
+        fn objective_grad(&self, x: &[f32], grad_f: &mut [f32]) -> bool {
+            for (g, &x) in grad_f.iter_mut().zip(x.iter()) {
+                *g = 2.0 * (x - 0.5);
+            }
+            true
+        }
+    }
+
+    impl ScalarConstrainedProblem for Single {
+        fn num_constraints(&self) -> usize {
+            1
+        }
+        fn num_constraint_jacobian_non_zeros(&self) -> usize {
+            2
+        }
+        fn constraint(&self, x: &[f32], g: &mut [f32]) -> bool {
+            g[0] = x[0] + x[1];
+            true
+        }
+        fn constraint_bounds(&self, g_l: &mut [f32], g_u: &mut [f32]) -> bool {
+            g_l[0] = 1.0;
+            g_u[0] = 1.0;
+            true
+        }
+        fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+            rows.copy_from_slice(&[0, 0]);
+            cols.copy_from_slice(&[0, 1]);
+            true
+        }
+        fn constraint_jacobian_values(&self, _: &[f32], vals: &mut [f32]) -> bool {
+            vals.copy_from_slice(&[1.0, 1.0]);
+            true
+        }
+        fn num_hessian_non_zeros(&self) -> usize {
+            2
+        }
+        fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+            rows.copy_from_slice(&[0, 1]);
+            cols.copy_from_slice(&[0, 1]);
+            true
+        }
+        fn hessian_values(&self, _: &[f32], obj_factor: f32, _: &[f32], vals: &mut [f32]) -> bool {
+            vals.copy_from_slice(&[2.0 * obj_factor; 2]);
+            true
+        }
+    }
+
+    #[test]
+    fn conversion_test() {
+        let adapter = ScalarAdapter::new(Single);
+
+        let mut x = [0.0; 2];
+        assert!(adapter.initial_point(&mut x));
+        assert_eq!(x, [0.25, 0.75]);
+
+        let mut x_l = [0.0; 2];
+        let mut x_u = [0.0; 2];
+        assert!(adapter.bounds(&mut x_l, &mut x_u));
+        assert_eq!(x_u[0], Number::from(1.0e20_f32));
+
+        let mut obj = 0.0;
+        assert!(adapter.objective(&x, &mut obj));
+        assert_eq!(obj, 0.125);
+
+        let mut grad = [0.0; 2];
+        assert!(adapter.objective_grad(&x, &mut grad));
+        assert_eq!(grad, [-0.5, 0.5]);
+
+        let mut g = [0.0];
+        assert!(adapter.constraint(&[0.1, 0.2], &mut g));
+        assert_eq!(g[0], Number::from(0.1_f32 + 0.2_f32));
+
+        let mut vals = [0.0; 2];
+        assert!(adapter.hessian_values(&x, 0.5, &[3.0], &mut vals));
+        assert_eq!(vals, [1.0, 1.0]);
+    }
+}
@@ -0,0 +1,506 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Affine scaling and shifting of the variables of a problem.
+//!
+//! [`Scaled`](struct.Scaled.html) wraps a problem defined in variables `x` and presents it to
+//! Ipopt in variables `y` with `x = D·y + c`, where `D` is a diagonal matrix with non-zero
+//! entries and `c` a shift. Bounds, initial point, bound multipliers, gradients, Jacobians and
+//! Hessians are transformed accordingly, so a badly scaled model can be fixed by choosing `D`
+//! close to the expected magnitude of each variable, without touching the model itself.
+//!
+//! The solution reported by Ipopt is in the scaled variables; use
+//! [`to_original`](struct.Scaled.html#method.to_original) to recover `x`.
+
+use crate::{
+    AlgorithmMode, BasicProblem, ConstrainedProblem, ConstraintGroups, Index, IndexingStyle,
+    NewtonProblem, Number,
+};
+
+use std::cell::RefCell;
+
+/// Bounds at or beyond this magnitude are infinite, as with Ipopt's default
+/// `nlp_lower_bound_inf` and `nlp_upper_bound_inf` options.
+const INFINITE_BOUND: Number = 1e19;
+
+/// A problem with affinely transformed variables `x = D·y + c`.
+pub struct Scaled<P> {
+    problem: P,
+    /// Diagonal of `D`.
+    scale: Vec<Number>,
+    /// The shift `c`.
+    shift: Vec<Number>,
+    /// Scratch buffer for the original variables.
+    x: RefCell<Vec<Number>>,
+    /// Zero-based columns of the constraint Jacobian entries.
+    jacobian_cols: RefCell<Vec<usize>>,
+    /// Zero-based rows and columns of the Hessian entries.
+    hessian_indices: RefCell<Vec<(usize, usize)>>,
+}
+
+impl<P: BasicProblem> Scaled<P> {
+    /// Wrap a problem with the diagonal `scale` of `D` and the `shift` `c`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `scale` or `shift` do not have one entry per variable, or if any
+    /// scale is zero or not finite.
+    pub fn new(problem: P, scale: Vec<Number>, shift: Vec<Number>) -> Self {
+        let n = problem.num_variables();
+        assert_eq!(scale.len(), n, "one scale per variable is required");
+        assert_eq!(shift.len(), n, "one shift per variable is required");
+        assert!(
+            scale.iter().all(|&d| d != 0.0 && d.is_finite()),
+            "scales must be finite and non-zero"
+        );
+        Scaled {
+            problem,
+            scale,
+            shift,
+            x: RefCell::new(Vec::with_capacity(n)),
+            jacobian_cols: RefCell::new(Vec::new()),
+            hessian_indices: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Wrap a problem with the given scales and no shift.
+    pub fn with_scale(problem: P, scale: Vec<Number>) -> Self {
+        let shift = vec![0.0; scale.len()];
+        Self::new(problem, scale, shift)
+    }
+
+    /// The wrapped problem.
+    pub fn problem(&self) -> &P {
+        &self.problem
+    }
+
+    /// The wrapped problem.
+    pub fn problem_mut(&mut self) -> &mut P {
+        &mut self.problem
+    }
+
+    /// Unwrap the original problem.
+    pub fn into_inner(self) -> P {
+        self.problem
+    }
+
+    /// Map scaled variables `y` to the original variables `x = D·y + c`.
+    pub fn to_original(&self, y: &[Number], x: &mut [Number]) {
+        for (((x, &y), &d), &c) in x.iter_mut().zip(y).zip(&self.scale).zip(&self.shift) {
+            *x = d * y + c;
+        }
+    }
+
+    /// Map original variables `x` to the scaled variables `y = D⁻¹·(x - c)`.
+    pub fn to_scaled(&self, x: &[Number], y: &mut [Number]) {
+        for (((y, &x), &d), &c) in y.iter_mut().zip(x).zip(&self.scale).zip(&self.shift) {
+            *y = (x - c) / d;
+        }
+    }
+
+    /// Evaluate `f` at the original variables corresponding to `y`.
+    fn at_original<R>(&self, y: &[Number], f: impl FnOnce(&[Number]) -> R) -> R {
+        let mut x = self.x.borrow_mut();
+        x.resize(y.len(), 0.0);
+        self.to_original(y, &mut x);
+        f(&x)
+    }
+
+    /// Zero-based index from an index in the indexing style of the problem.
+    fn zero_based(&self, i: Index) -> usize {
+        (i - self.problem.indexing_style().offset()) as usize
+    }
+
+    /// Remember the columns of the constraint Jacobian for scaling its values.
+    fn store_jacobian_cols(&self, cols: &[Index]) {
+        let mut stored = self.jacobian_cols.borrow_mut();
+        stored.clear();
+        stored.extend(cols.iter().map(|&c| self.zero_based(c)));
+    }
+
+    /// Remember the Hessian indices for scaling its values.
+    fn store_hessian_indices(&self, rows: &[Index], cols: &[Index]) {
+        let mut stored = self.hessian_indices.borrow_mut();
+        stored.clear();
+        stored.extend(
+            rows.iter()
+                .zip(cols)
+                .map(|(&r, &c)| (self.zero_based(r), self.zero_based(c))),
+        );
+    }
+
+    /// Scale constraint Jacobian values with respect to `x` to values with respect to `y`.
+    ///
+    /// The columns are queried from the problem if they are not stored yet, so values requested
+    /// before the structure are scaled too.
+    fn scale_jacobian(&self, vals: &mut [Number]) -> bool
+    where
+        P: ConstrainedProblem,
+    {
+        if self.jacobian_cols.borrow().len() != vals.len() {
+            let (mut rows, mut cols) = (vec![0; vals.len()], vec![0; vals.len()]);
+            if !self
+                .problem
+                .constraint_jacobian_indices(&mut rows, &mut cols)
+            {
+                return false;
+            }
+            self.store_jacobian_cols(&cols);
+        }
+        for (v, &c) in vals.iter_mut().zip(self.jacobian_cols.borrow().iter()) {
+            *v *= self.scale[c];
+        }
+        true
+    }
+
+    /// Scale Hessian values with respect to `x` to Hessian values with respect to `y`.
+    ///
+    /// The indices are queried with `indices` if they are not stored yet, so values requested
+    /// before the structure are scaled too.
+    fn scale_hessian(
+        &self,
+        vals: &mut [Number],
+        indices: impl FnOnce(&mut [Index], &mut [Index]) -> bool,
+    ) -> bool {
+        if self.hessian_indices.borrow().len() != vals.len() {
+            let (mut rows, mut cols) = (vec![0; vals.len()], vec![0; vals.len()]);
+            if !indices(&mut rows, &mut cols) {
+                return false;
+            }
+            self.store_hessian_indices(&rows, &cols);
+        }
+        for (v, &(r, c)) in vals.iter_mut().zip(self.hessian_indices.borrow().iter()) {
+            *v *= self.scale[r] * self.scale[c];
+        }
+        true
+    }
+}
+
+/// Map a bound on `x` to a bound on `y`, keeping infinite bounds infinite.
+fn scale_bound(bound: Number, d: Number, c: Number) -> Number {
+    if bound <= -INFINITE_BOUND {
+        Number::NEG_INFINITY * d.signum()
+    } else if bound >= INFINITE_BOUND {
+        Number::INFINITY * d.signum()
+    } else {
+        (bound - c) / d
+    }
+}
+
+impl<P: BasicProblem> BasicProblem for Scaled<P> {
+    fn indexing_style(&self) -> IndexingStyle {
+        self.problem.indexing_style()
+    }
+    fn num_variables(&self) -> usize {
+        self.problem.num_variables()
+    }
+    fn bounds(&self, y_l: &mut [Number], y_u: &mut [Number]) -> bool {
+        if !self.problem.bounds(y_l, y_u) {
+            return false;
+        }
+        for (((l, u), &d), &c) in y_l
+            .iter_mut()
+            .zip(y_u.iter_mut())
+            .zip(&self.scale)
+            .zip(&self.shift)
+        {
+            let (a, b) = (scale_bound(*l, d, c), scale_bound(*u, d, c));
+            // A negative scale flips the bounds.
+            if d > 0.0 {
+                *l = a;
+                *u = b;
+            } else {
+                *l = b;
+                *u = a;
+            }
+        }
+        true
+    }
+    fn initial_point(&self, y: &mut [Number]) -> bool {
+        let mut x = self.x.borrow_mut();
+        x.resize(y.len(), 0.0);
+        let init = self.problem.initial_point(&mut x);
+        if init {
+            self.to_scaled(&x, y);
+        }
+        init
+    }
+    fn initial_bounds_multipliers(&self, z_l: &mut [Number], z_u: &mut [Number]) -> bool {
+        if !self.problem.initial_bounds_multipliers(z_l, z_u) {
+            return false;
+        }
+        // Bound multipliers scale like the gradient.
+        for ((l, u), &d) in z_l.iter_mut().zip(z_u.iter_mut()).zip(&self.scale) {
+            if d < 0.0 {
+                std::mem::swap(l, u);
+            }
+            *l *= d.abs();
+            *u *= d.abs();
+        }
+        true
+    }
+    fn objective(&self, y: &[Number], obj: &mut Number) -> bool {
+        self.at_original(y, |x| self.problem.objective(x, obj))
+    }
+    fn objective_grad(&self, y: &[Number], grad_f: &mut [Number]) -> bool {
+        if !self.at_original(y, |x| self.problem.objective_grad(x, grad_f)) {
+            return false;
+        }
+        for (g, &d) in grad_f.iter_mut().zip(&self.scale) {
+            *g *= d;
+        }
+        true
+    }
+    fn variable_scaling(&self, y_scaling: &mut [Number]) -> bool {
+        if !self.problem.variable_scaling(y_scaling) {
+            return false;
+        }
+        for (s, &d) in y_scaling.iter_mut().zip(&self.scale) {
+            *s *= d.abs();
+        }
+        true
+    }
+    fn objective_scaling(&self) -> f64 {
+        self.problem.objective_scaling()
+    }
+    fn algorithm_mode_changed(&mut self, mode: AlgorithmMode) {
+        self.problem.algorithm_mode_changed(mode)
+    }
+}
+
+impl<P: NewtonProblem> NewtonProblem for Scaled<P> {
+    fn num_hessian_non_zeros(&self) -> usize {
+        self.problem.num_hessian_non_zeros()
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        let ok = self.problem.hessian_indices(rows, cols);
+        if ok {
+            self.store_hessian_indices(rows, cols);
+        }
+        ok
+    }
+    fn hessian_values(&self, y: &[Number], vals: &mut [Number]) -> bool {
+        if !self.at_original(y, |x| self.problem.hessian_values(x, vals)) {
+            return false;
+        }
+        self.scale_hessian(vals, |rows, cols| self.problem.hessian_indices(rows, cols))
+    }
+}
+
+impl<P: ConstrainedProblem> ConstrainedProblem for Scaled<P> {
+    fn num_constraints(&self) -> usize {
+        self.problem.num_constraints()
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        self.problem.num_constraint_jacobian_non_zeros()
+    }
+    fn constraint(&self, y: &[Number], g: &mut [Number]) -> bool {
+        self.at_original(y, |x| self.problem.constraint(x, g))
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        self.problem.constraint_bounds(g_l, g_u)
+    }
+    fn initial_constraint_multipliers(&self, lambda: &mut [Number]) -> bool {
+        self.problem.initial_constraint_multipliers(lambda)
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        let ok = self.problem.constraint_jacobian_indices(rows, cols);
+        if ok {
+            self.store_jacobian_cols(cols);
+        }
+        ok
+    }
+    fn constraint_jacobian_values(&self, y: &[Number], vals: &mut [Number]) -> bool {
+        if !self.at_original(y, |x| self.problem.constraint_jacobian_values(x, vals)) {
+            return false;
+        }
+        self.scale_jacobian(vals)
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        ConstrainedProblem::num_hessian_non_zeros(&self.problem)
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        let ok = ConstrainedProblem::hessian_indices(&self.problem, rows, cols);
+        if ok {
+            self.store_hessian_indices(rows, cols);
+        }
+        ok
+    }
+    fn hessian_values(
+        &self,
+        y: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        let ok = self.at_original(y, |x| {
+            ConstrainedProblem::hessian_values(&self.problem, x, obj_factor, lambda, vals)
+        });
+        if !ok {
+            return false;
+        }
+        self.scale_hessian(vals, |rows, cols| {
+            ConstrainedProblem::hessian_indices(&self.problem, rows, cols)
+        })
+    }
+    fn constraint_scaling(&self, g_scaling: &mut [Number]) -> bool {
+        self.problem.constraint_scaling(g_scaling)
+    }
+    fn constraint_groups(&self) -> ConstraintGroups {
+        self.problem.constraint_groups()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimize `x0^2 x1` subject to `x0 x1 >= 1` with `x0` in `[1, 10]`.
+    struct Model;
+
+    impl BasicProblem for Model {
+        fn num_variables(&self) -> usize {
+            2
+        }
+        fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+            x_l.copy_from_slice(&[1.0, -1e20]);
+            x_u.copy_from_slice(&[10.0, 1e20]);
+            true
+        }
+        fn initial_point(&self, x: &mut [Number]) -> bool {
+            x.copy_from_slice(&[2.0, 3.0]);
+            true
+        }
+        fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+            *obj = x[0] * x[0] * x[1];
+            true
+        }
+        fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+            grad_f.copy_from_slice(&[2.0 * x[0] * x[1], x[0] * x[0]]);
+            true
+        }
+    }
+
+    impl ConstrainedProblem for Model {
+        fn num_constraints(&self) -> usize {
+            1
+        }
+        fn num_constraint_jacobian_non_zeros(&self) -> usize {
+            2
+        }
+        fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+            g[0] = x[0] * x[1];
+            true
+        }
+        fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+            g_l[0] = 1.0;
+            g_u[0] = 1e20;
+            true
+        }
+        fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+            rows.copy_from_slice(&[0, 0]);
+            cols.copy_from_slice(&[0, 1]);
+            true
+        }
+        fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+            vals.copy_from_slice(&[x[1], x[0]]);
+            true
+        }
+        fn num_hessian_non_zeros(&self) -> usize {
+            2
+        }
+        fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+            rows.copy_from_slice(&[0, 1]);
+            cols.copy_from_slice(&[0, 0]);
+            true
+        }
+        fn hessian_values(
+            &self,
+            x: &[Number],
+            obj_factor: Number,
+            lambda: &[Number],
+            vals: &mut [Number],
+        ) -> bool {
+            vals[0] = obj_factor * 2.0 * x[1];
+            vals[1] = obj_factor * 2.0 * x[0] + lambda[0];
+            true
+        }
+    }
+
+    #[test]
+    fn scaled_derivatives_test() {
+        // x0 = 10 y0 + 1, x1 = -2 y1.
+        let scaled = Scaled::new(Model, vec![10.0, -2.0], vec![1.0, 0.0]);
+
+        let (mut y_l, mut y_u) = ([0.0; 2], [0.0; 2]);
+        assert!(scaled.bounds(&mut y_l, &mut y_u));
+        assert_eq!(y_l, [0.0, Number::NEG_INFINITY]);
+        assert_eq!(y_u, [0.9, Number::INFINITY]);
+
+        let mut y = [0.0; 2];
+        assert!(scaled.initial_point(&mut y));
+        assert_eq!(y, [0.1, -1.5]);
+
+        let mut obj = 0.0;
+        assert!(scaled.objective(&y, &mut obj));
+        assert_eq!(obj, 12.0);
+
+        let mut grad = [0.0; 2];
+        assert!(scaled.objective_grad(&y, &mut grad));
+        assert_eq!(grad, [120.0, -8.0]);
+
+        let (mut rows, mut cols) = ([0; 2], [0; 2]);
+        assert!(scaled.constraint_jacobian_indices(&mut rows, &mut cols));
+        let mut jac = [0.0; 2];
+        assert!(scaled.constraint_jacobian_values(&y, &mut jac));
+        assert_eq!(jac, [30.0, -4.0]);
+
+        assert!(ConstrainedProblem::hessian_indices(
+            &scaled, &mut rows, &mut cols
+        ));
+        let mut hess = [0.0; 2];
+        assert!(ConstrainedProblem::hessian_values(
+            &scaled,
+            &y,
+            1.0,
+            &[0.5],
+            &mut hess
+        ));
+        assert_eq!(hess, [600.0, -90.0]);
+
+        let mut x = [0.0; 2];
+        scaled.to_original(&y, &mut x);
+        assert_eq!(x, [2.0, 3.0]);
+    }
+
+    #[test]
+    fn values_before_structure_test() {
+        let scaled = Scaled::new(Model, vec![10.0, -2.0], vec![1.0, 0.0]);
+        let y = [0.1, -1.5];
+
+        let mut jac = [0.0; 2];
+        assert!(scaled.constraint_jacobian_values(&y, &mut jac));
+        assert_eq!(jac, [30.0, -4.0]);
+
+        let mut hess = [0.0; 2];
+        assert!(ConstrainedProblem::hessian_values(
+            &scaled,
+            &y,
+            1.0,
+            &[0.5],
+            &mut hess
+        ));
+        assert_eq!(hess, [600.0, -90.0]);
+    }
+}
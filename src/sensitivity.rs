@@ -0,0 +1,279 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Parametric sensitivity analysis through sIpopt.
+//!
+//! sIpopt computes how the optimal solution changes with respect to problem parameters from the
+//! KKT system at the solution, without solving the problem again. Following the sIpopt
+//! convention, a parameter is a variable of the problem fixed by an equality constraint, for
+//! instance `x[2] = p`. Parameters are declared with
+//! [`Ipopt::set_sensitivity_parameters`](struct.Ipopt.html#method.set_sensitivity_parameters)
+//! before the first solve, and
+//! [`Ipopt::solve_with_sensitivities`](struct.Ipopt.html#method.solve_with_sensitivities)
+//! returns the derivatives `dx*/dp` and `dlambda*/dp` along with the usual solve result.
+//!
+//! ```no_run
+//! use ipopt::*;
+//! # struct NLP;
+//! # impl BasicProblem for NLP {
+//! #     fn num_variables(&self) -> usize { 2 }
+//! #     fn bounds(&self, _: &mut [Number], _: &mut [Number]) -> bool { true }
+//! #     fn initial_point(&self, _: &mut [Number]) -> bool { true }
+//! #     fn objective(&self, _: &[Number], _: &mut Number) -> bool { true }
+//! #     fn objective_grad(&self, _: &[Number], _: &mut [Number]) -> bool { true }
+//! # }
+//! # impl ConstrainedProblem for NLP {
+//! #     fn num_constraints(&self) -> usize { 1 }
+//! #     fn num_constraint_jacobian_non_zeros(&self) -> usize { 1 }
+//! #     fn constraint(&self, _: &[Number], _: &mut [Number]) -> bool { true }
+//! #     fn constraint_bounds(&self, _: &mut [Number], _: &mut [Number]) -> bool { true }
+//! #     fn constraint_jacobian_indices(&self, _: &mut [Index], _: &mut [Index]) -> bool { true }
+//! #     fn constraint_jacobian_values(&self, _: &[Number], _: &mut [Number]) -> bool { true }
+//! #     fn num_hessian_non_zeros(&self) -> usize { 0 }
+//! #     fn hessian_indices(&self, _: &mut [Index], _: &mut [Index]) -> bool { true }
+//! #     fn hessian_values(&self, _: &[Number], _: Number, _: &[Number], _: &mut [Number]) -> bool { true }
+//! # }
+//! let mut ipopt = Ipopt::new(NLP).unwrap();
+//! // Variable 1 is a parameter fixed by constraint 0.
+//! ipopt
+//!     .set_sensitivity_parameters(&[SensitivityParameter { variable: 1, constraint: 0 }])
+//!     .unwrap();
+//! let SensitivityResult { sensitivities, .. } = ipopt.solve_with_sensitivities();
+//! let dx_dp = sensitivities.unwrap().primal_variables(0).to_vec();
+//! ```
+
+use crate::ffi;
+use crate::{ConstrainedProblem, Ipopt, Number, Solution, SolveResult, SolveStatus, SolverDataMut};
+
+use std::fmt::{Display, Formatter};
+use std::slice;
+
+/// A problem parameter in the sIpopt sense: a variable fixed by an equality constraint.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SensitivityParameter {
+    /// Zero-based index of the variable representing the parameter.
+    pub variable: usize,
+    /// Zero-based index of the equality constraint fixing the variable to the parameter value.
+    pub constraint: usize,
+}
+
+/// Error produced when declaring sensitivity parameters.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SensitivityError {
+    /// The parameter at the given position refers to a variable or constraint that is out of
+    /// bounds.
+    InvalidParameter(usize),
+    /// Parameters were declared after the problem was solved, or sIpopt rejected its options.
+    Rejected,
+}
+
+impl Display for SensitivityError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match *self {
+            SensitivityError::InvalidParameter(i) => write!(
+                f,
+                "Sensitivity parameter {} refers to a nonexistent variable or constraint.",
+                i
+            ),
+            SensitivityError::Rejected => write!(
+                f,
+                "Sensitivity parameters must be declared before the first solve."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SensitivityError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+/// Derivatives of the optimal solution with respect to the declared parameters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sensitivities {
+    num_variables: usize,
+    num_constraints: usize,
+    dx_dp: Vec<Number>,
+    dlambda_dp: Vec<Number>,
+}
+
+impl Sensitivities {
+    /// Number of parameters.
+    pub fn num_parameters(&self) -> usize {
+        self.dx_dp
+            .len()
+            .checked_div(self.num_variables)
+            .unwrap_or(0)
+    }
+
+    /// Derivatives of the optimal primal variables with respect to the given parameter.
+    pub fn primal_variables(&self, param: usize) -> &[Number] {
+        let n = self.num_variables;
+        &self.dx_dp[param * n..(param + 1) * n]
+    }
+
+    /// Derivatives of the optimal constraint multipliers with respect to the given parameter.
+    pub fn constraint_multipliers(&self, param: usize) -> &[Number] {
+        let m = self.num_constraints;
+        &self.dlambda_dp[param * m..(param + 1) * m]
+    }
+
+    /// First order estimate of the primal solution after changing the parameters by `dp`.
+    pub fn predict_primal_variables(&self, solution: &Solution, dp: &[Number]) -> Vec<Number> {
+        let mut x = solution.primal_variables.to_vec();
+        for (j, &dp) in dp.iter().enumerate().take(self.num_parameters()) {
+            for (x, &dx) in x.iter_mut().zip(self.primal_variables(j)) {
+                *x += dx * dp;
+            }
+        }
+        x
+    }
+}
+
+/// The result of a solve followed by sensitivity analysis.
+#[derive(Debug, PartialEq)]
+pub struct SensitivityResult<'a, P: 'a> {
+    /// The result of the solve.
+    pub result: SolveResult<'a, P>,
+    /// Derivatives of the solution with respect to the declared parameters, or `None` if sIpopt
+    /// did not produce any, for example because the solve failed.
+    pub sensitivities: Option<Sensitivities>,
+}
+
+impl<P: ConstrainedProblem> Ipopt<P> {
+    /// Declare the parameters for sensitivity analysis.
+    ///
+    /// This must be called before the problem is solved for the first time.
+    pub fn set_sensitivity_parameters(
+        &mut self,
+        params: &[SensitivityParameter],
+    ) -> Result<&mut Self, SensitivityError> {
+        for (i, p) in params.iter().enumerate() {
+            if p.variable >= self.num_primal_variables || p.constraint >= self.num_dual_variables {
+                return Err(SensitivityError::InvalidParameter(i));
+            }
+        }
+        let vars: Vec<_> = params
+            .iter()
+            .map(|p| p.variable as ffi::CNLP_Index)
+            .collect();
+        let cons: Vec<_> = params
+            .iter()
+            .map(|p| p.constraint as ffi::CNLP_Index)
+            .collect();
+        let ok = unsafe {
+            ffi::cnlp_set_sens_parameters(
+                self.nlp_internal,
+                params.len() as ffi::CNLP_Index,
+                vars.as_ptr(),
+                cons.as_ptr(),
+            )
+        };
+        if ok != 0 {
+            Ok(self)
+        } else {
+            Err(SensitivityError::Rejected)
+        }
+    }
+
+    /// Solve the problem and compute the sensitivities of the solution with respect to the
+    /// parameters declared with
+    /// [`set_sensitivity_parameters`](struct.Ipopt.html#method.set_sensitivity_parameters).
+    pub fn solve_with_sensitivities(&mut self) -> SensitivityResult<P> {
+        self.prepare_solve();
+        let res = {
+            let udata_ptr = self as *mut Ipopt<P>;
+            unsafe { ffi::cnlp_sens_solve(self.nlp_internal, udata_ptr as ffi::CNLP_UserDataPtr) }
+        };
+        let status = SolveStatus::new(res.status);
+        self.finish_solve(status);
+        let solution =
+            Solution::from_raw(res.data, self.num_primal_variables, self.num_dual_variables);
+        let user_stop = self.user_stop(status, solution.primal_variables);
+
+        let Ipopt {
+            nlp_interface: ref mut problem,
+            nlp_internal,
+            num_primal_variables,
+            num_dual_variables,
+            objective_sense,
+            ..
+        } = *self;
+
+        let num_params = unsafe { ffi::cnlp_get_num_sens_parameters(nlp_internal) } as usize;
+        let mut dx_dp = vec![0.0; num_params * num_primal_variables];
+        let mut dlambda_dp = vec![0.0; num_params * num_dual_variables];
+        let available = num_params > 0
+            && unsafe {
+                ffi::cnlp_get_sens_derivatives(
+                    nlp_internal,
+                    dx_dp.as_mut_ptr(),
+                    dlambda_dp.as_mut_ptr(),
+                )
+            } != 0;
+
+        SensitivityResult {
+            result: SolveResult {
+                solver_data: SolverDataMut { problem, solution },
+                constraint_values: unsafe { slice::from_raw_parts(res.g, num_dual_variables) },
+                objective_value: res.obj_val * objective_sense.sign(),
+                status,
+                user_stop,
+            },
+            sensitivities: if available {
+                Some(Sensitivities {
+                    num_variables: num_primal_variables,
+                    num_constraints: num_dual_variables,
+                    dx_dp,
+                    dlambda_dp,
+                })
+            } else {
+                None
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prediction_test() {
+        let sens = Sensitivities {
+            num_variables: 2,
+            num_constraints: 1,
+            dx_dp: vec![1.0, 0.5, -1.0, 2.0],
+            dlambda_dp: vec![3.0, 4.0],
+        };
+        assert_eq!(sens.num_parameters(), 2);
+        assert_eq!(sens.primal_variables(1), &[-1.0, 2.0]);
+        assert_eq!(sens.constraint_multipliers(1), &[4.0]);
+
+        let x = [1.0, 1.0];
+        let zero = [0.0; 2];
+        let lambda = [0.0];
+        let solution = Solution {
+            primal_variables: &x,
+            lower_bound_multipliers: &zero,
+            upper_bound_multipliers: &zero,
+            constraint_multipliers: &lambda,
+        };
+        assert_eq!(
+            sens.predict_primal_variables(&solution, &[0.5, 0.25]),
+            vec![1.25, 1.75]
+        );
+    }
+}
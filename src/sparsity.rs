@@ -0,0 +1,347 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Building triplet sparsity structures.
+//!
+//! Ipopt takes the constraint Jacobian and the Hessian of the Lagrangian as triplets: a row, a
+//! column and a value per non-zero. A [`SparsityBuilder`](struct.SparsityBuilder.html) collects
+//! `(row, col)` entries in any order and with repetitions, and
+//! [`build`](struct.SparsityBuilder.html#method.build) validates them and produces
+//! [`Triplets`](struct.Triplets.html) with duplicates merged and entries sorted in row-major
+//! order.
+//!
+//! The position of an entry in the value array is its slot. Slots never change once the
+//! structure is built, so the value callbacks can look them up once with
+//! [`slot`](struct.Triplets.html#method.slot), or use
+//! [`entry_slots`](struct.Triplets.html#method.entry_slots) to accumulate values in the order
+//! the entries were added:
+//!
+//! ```no_run
+//! # use ipopt::*;
+//! # struct Model {
+//! #     hessian: Triplets,
+//! # }
+//! # impl Model {
+//! #     fn entry_values(&self, _: &[Number]) -> Vec<Number> { Vec::new() }
+//! # }
+//! # impl BasicProblem for Model {
+//! #     fn num_variables(&self) -> usize { 1 }
+//! #     fn bounds(&self, _: &mut [Number], _: &mut [Number]) -> bool { true }
+//! #     fn initial_point(&self, _: &mut [Number]) -> bool { true }
+//! #     fn objective(&self, _: &[Number], _: &mut Number) -> bool { true }
+//! #     fn objective_grad(&self, _: &[Number], _: &mut [Number]) -> bool { true }
+//! # }
+//! # impl NewtonProblem for Model {
+//! #     fn num_hessian_non_zeros(&self) -> usize { 0 }
+//! #     fn hessian_indices(&self, _: &mut [Index], _: &mut [Index]) -> bool { true }
+//! fn hessian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+//!     for v in vals.iter_mut() {
+//!         *v = 0.0;
+//!     }
+//!     for (&slot, value) in self.hessian.entry_slots().iter().zip(self.entry_values(x)) {
+//!         vals[slot] += value;
+//!     }
+//!     true
+//! }
+//! # }
+//! ```
+
+use crate::{Evaluation, Index, IndexingStyle};
+
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+
+/// Collects the non-zero entries of a sparse matrix.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SparsityBuilder {
+    num_rows: usize,
+    num_cols: usize,
+    symmetric: bool,
+    entries: Vec<(usize, usize)>,
+}
+
+impl SparsityBuilder {
+    /// Start the structure of a general matrix, such as a constraint Jacobian.
+    pub fn new(num_rows: usize, num_cols: usize) -> Self {
+        SparsityBuilder {
+            num_rows,
+            num_cols,
+            symmetric: false,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Start the structure of a symmetric matrix, such as the Hessian of the Lagrangian.
+    ///
+    /// Only entries in the lower triangular part are accepted.
+    pub fn symmetric(n: usize) -> Self {
+        SparsityBuilder {
+            symmetric: true,
+            ..SparsityBuilder::new(n, n)
+        }
+    }
+
+    /// Add an entry. Entries may be added more than once.
+    pub fn add(&mut self, row: usize, col: usize) -> &mut Self {
+        self.entries.push((row, col));
+        self
+    }
+
+    /// Add all entries of a dense block with the given rows and columns.
+    pub fn add_block(&mut self, rows: &[usize], cols: &[usize]) -> &mut Self {
+        for &r in rows {
+            for &c in cols {
+                self.add(r, c);
+            }
+        }
+        self
+    }
+
+    /// Number of entries added so far, including duplicates.
+    pub fn num_entries(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Validate the entries and build the triplet structure.
+    pub fn build(&self) -> Result<Triplets, SparsityError> {
+        let invalid = |entry: usize, row: usize, col: usize| {
+            let index = |i: usize| Index::try_from(i).unwrap_or(Index::MAX);
+            InvalidEntry {
+                matrix: None,
+                entry,
+                row: index(row),
+                col: index(col),
+                style: IndexingStyle::CStyle,
+            }
+        };
+        for (entry, &(row, col)) in self.entries.iter().enumerate() {
+            if row >= self.num_rows || col >= self.num_cols {
+                return Err(SparsityError::OutOfBounds(invalid(entry, row, col)));
+            }
+            if self.symmetric && row < col {
+                return Err(SparsityError::UpperTriangle(invalid(entry, row, col)));
+            }
+        }
+
+        let mut entries = self.entries.clone();
+        entries.sort_unstable();
+        entries.dedup();
+        let entry_slots = self
+            .entries
+            .iter()
+            .map(|e| entries.binary_search(e).unwrap())
+            .collect();
+
+        Ok(Triplets {
+            num_rows: self.num_rows,
+            num_cols: self.num_cols,
+            symmetric: self.symmetric,
+            entries,
+            entry_slots,
+        })
+    }
+}
+
+/// A validated triplet sparsity structure with distinct entries in row-major order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Triplets {
+    num_rows: usize,
+    num_cols: usize,
+    symmetric: bool,
+    entries: Vec<(usize, usize)>,
+    entry_slots: Vec<usize>,
+}
+
+impl Triplets {
+    /// Number of rows of the matrix.
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    /// Number of columns of the matrix.
+    pub fn num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    /// Whether only the lower triangular part of a symmetric matrix is stored.
+    pub fn is_symmetric(&self) -> bool {
+        self.symmetric
+    }
+
+    /// Number of distinct non-zeros.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the structure has no non-zeros.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Zero-based rows and columns of all non-zeros in slot order.
+    pub fn entries(&self) -> &[(usize, usize)] {
+        &self.entries
+    }
+
+    /// The slot of each entry given to the builder, in the order the entries were added.
+    ///
+    /// Duplicate entries share a slot, so values should be accumulated.
+    pub fn entry_slots(&self) -> &[usize] {
+        &self.entry_slots
+    }
+
+    /// The slot of the given entry, if it is a non-zero.
+    ///
+    /// For symmetric structures, entries in the upper triangular part refer to their transpose.
+    pub fn slot(&self, row: usize, col: usize) -> Option<usize> {
+        let entry = if self.symmetric && row < col {
+            (col, row)
+        } else {
+            (row, col)
+        };
+        self.entries.binary_search(&entry).ok()
+    }
+
+    /// Write the row and column indices in the given indexing style.
+    ///
+    /// This is meant to be called from the index callbacks of problems, such as
+    /// [`ConstrainedProblem::hessian_indices`](trait.ConstrainedProblem.html#tymethod.hessian_indices).
+    /// Returns `false` if the output slices do not have one entry per non-zero.
+    pub fn write_indices(
+        &self,
+        style: IndexingStyle,
+        rows: &mut [Index],
+        cols: &mut [Index],
+    ) -> bool {
+        if rows.len() != self.len() || cols.len() != self.len() {
+            return false;
+        }
+        let offset = style.offset();
+        for ((r, c), &(row, col)) in rows.iter_mut().zip(cols.iter_mut()).zip(&self.entries) {
+            *r = row as Index + offset;
+            *c = col as Index + offset;
+        }
+        true
+    }
+}
+
+/// An invalid entry of a sparsity structure.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InvalidEntry {
+    /// The matrix, if the structure was returned by a problem.
+    pub matrix: Option<Evaluation>,
+    /// Position of the entry in the structure.
+    pub entry: usize,
+    /// Row index in the indexing style `style`.
+    pub row: Index,
+    /// Column index in the indexing style `style`.
+    pub col: Index,
+    /// Indexing style of the indices.
+    pub style: IndexingStyle,
+}
+
+impl Display for InvalidEntry {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self.matrix {
+            Some(matrix) => write!(f, "The {} entry", matrix)?,
+            None => write!(f, "The entry")?,
+        }
+        write!(f, " {} at ({}, {})", self.entry, self.row, self.col)
+    }
+}
+
+/// An invalid sparsity structure, built with a
+/// [`SparsityBuilder`](struct.SparsityBuilder.html) or returned by the structure callbacks of a
+/// problem.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SparsityError {
+    /// The structure callback of a problem returned `false`.
+    IndicesFailed(Evaluation),
+    /// An entry is outside the matrix.
+    OutOfBounds(InvalidEntry),
+    /// An entry of a symmetric matrix is in the upper triangular part.
+    UpperTriangle(InvalidEntry),
+    /// The offsets of a compressed structure are not increasing from zero to the number of
+    /// entries, or there is not one offset per row or column plus one.
+    InvalidOffsets,
+}
+
+impl Display for SparsityError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            SparsityError::IndicesFailed(matrix) => {
+                write!(f, "Failed to get the {} indices.", matrix)
+            }
+            SparsityError::OutOfBounds(entry) => write!(
+                f,
+                "{} is outside the matrix ({} indices).",
+                entry, entry.style
+            ),
+            SparsityError::UpperTriangle(entry) => write!(
+                f,
+                "{} is in the upper triangular part of a symmetric matrix ({} indices).",
+                entry, entry.style
+            ),
+            SparsityError::InvalidOffsets => {
+                write!(f, "The offsets of the compressed structure are invalid.")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SparsityError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_test() {
+        let mut builder = SparsityBuilder::symmetric(3);
+        builder.add(2, 1).add(0, 0).add(2, 1).add(1, 0);
+        let triplets = builder.build().unwrap();
+
+        assert_eq!(triplets.len(), 3);
+        assert_eq!(triplets.entries(), &[(0, 0), (1, 0), (2, 1)]);
+        assert_eq!(triplets.entry_slots(), &[2, 0, 2, 1]);
+        assert_eq!(triplets.slot(1, 2), Some(2));
+        assert_eq!(triplets.slot(2, 2), None);
+
+        let (mut rows, mut cols) = ([0; 3], [0; 3]);
+        assert!(triplets.write_indices(IndexingStyle::FortranStyle, &mut rows, &mut cols));
+        assert_eq!((rows, cols), ([1, 2, 3], [1, 1, 2]));
+        assert!(!triplets.write_indices(IndexingStyle::CStyle, &mut rows[..2], &mut cols[..2]));
+
+        builder.add(0, 2);
+        assert_eq!(
+            builder.build(),
+            Err(SparsityError::UpperTriangle(InvalidEntry {
+                matrix: None,
+                entry: 4,
+                row: 0,
+                col: 2,
+                style: IndexingStyle::CStyle,
+            }))
+        );
+        let err = SparsityBuilder::new(2, 3).add(2, 0).build().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The entry 0 at (2, 0) is outside the matrix (zero-based indices)."
+        );
+    }
+}
@@ -0,0 +1,405 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Statistics collected by Ipopt during a solve.
+//!
+//! With [`Ipopt::set_callback_timing`](struct.Ipopt.html#method.set_callback_timing), the
+//! wrapper also measures the time spent in each kind of user callback. Comparing it to the total
+//! time tells whether a solve is dominated by the evaluation of the model or by Ipopt itself,
+//! where most of the time is typically spent in the linear solver.
+//!
+//! With [`Ipopt::set_timing_statistics`](struct.Ipopt.html#method.set_timing_statistics),
+//! Ipopt's own breakdown of the time spent in each part of the algorithm, which
+//! `print_timing_statistics` prints at the end of a solve, is reported as
+//! [`TimingStats`](struct.TimingStats.html).
+
+use crate::ffi;
+use crate::{BasicProblem, Index, Ipopt, Number, ObjectiveSense, OptionRegistry};
+
+use std::time::Instant;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Statistics of the last solve as reported by Ipopt.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SolveStats {
+    /// Number of iterations taken by the algorithm.
+    pub iteration_count: Index,
+    /// Total CPU time spent in Ipopt in seconds.
+    pub total_cpu_time: Number,
+    /// Total system time spent in Ipopt in seconds.
+    pub total_sys_time: Number,
+    /// Total wallclock time spent in Ipopt in seconds.
+    pub total_wallclock_time: Number,
+    /// Number of objective function evaluations.
+    pub num_objective_evaluations: Index,
+    /// Number of constraint function evaluations.
+    pub num_constraint_evaluations: Index,
+    /// Number of objective gradient evaluations.
+    pub num_objective_gradient_evaluations: Index,
+    /// Number of constraint Jacobian evaluations.
+    pub num_constraint_jacobian_evaluations: Index,
+    /// Number of Hessian evaluations.
+    pub num_hessian_evaluations: Index,
+    /// Final value of the unscaled objective.
+    pub objective_value: Number,
+    /// Final value of the scaled objective.
+    pub scaled_objective_value: Number,
+    /// Final unscaled dual infeasibility.
+    pub dual_infeasibility: Number,
+    /// Final unscaled constraint violation.
+    pub constraint_violation: Number,
+    /// Final unscaled complementarity.
+    pub complementarity: Number,
+    /// Final unscaled overall NLP error.
+    pub nlp_error: Number,
+    /// Number of iterations in which the Hessian was regularized to correct the inertia of the
+    /// KKT matrix, including iterations of the restoration phase. Frequent regularization
+    /// indicates a poor or indefinite Hessian.
+    ///
+    /// This counts iterations, not the trial factorizations within them. The regularization used
+    /// in each iteration is reported in
+    /// [`IntermediateCallbackData::regularization_size`](struct.IntermediateCallbackData.html#structfield.regularization_size).
+    pub num_regularized_iterations: Index,
+    /// Size of the Hessian regularization in the last iteration.
+    pub final_regularization: Number,
+    /// Time spent in user callbacks, if callback timing is enabled.
+    pub callback_times: Option<CallbackTimes>,
+    /// Time spent in each part of the algorithm, if timing statistics are enabled.
+    pub timing: Option<TimingStats>,
+}
+
+impl SolveStats {
+    /// Wallclock time in seconds spent inside Ipopt itself, excluding user callbacks, if
+    /// callback timing is enabled.
+    pub fn ipopt_wallclock_time(&self) -> Option<Number> {
+        self.callback_times
+            .map(|times| (self.total_wallclock_time - times.total()).max(0.0))
+    }
+}
+
+/// Cumulative wallclock time in seconds spent in each kind of user callback during a solve.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CallbackTimes {
+    /// Time spent evaluating the objective.
+    pub objective: Number,
+    /// Time spent evaluating the objective gradient.
+    pub objective_gradient: Number,
+    /// Time spent evaluating the constraints.
+    pub constraint: Number,
+    /// Time spent evaluating the constraint Jacobian, including its sparsity structure.
+    pub constraint_jacobian: Number,
+    /// Time spent evaluating the Hessian, including its sparsity structure.
+    pub hessian: Number,
+    /// Time spent in the intermediate callback.
+    pub intermediate: Number,
+}
+
+impl CallbackTimes {
+    /// Total time spent in user callbacks.
+    pub fn total(&self) -> Number {
+        self.objective
+            + self.objective_gradient
+            + self.constraint
+            + self.constraint_jacobian
+            + self.hessian
+            + self.intermediate
+    }
+
+    fn entry(&mut self, callback: Callback) -> &mut Number {
+        match callback {
+            Callback::Objective => &mut self.objective,
+            Callback::ObjectiveGradient => &mut self.objective_gradient,
+            Callback::Constraint => &mut self.constraint,
+            Callback::ConstraintJacobian => &mut self.constraint_jacobian,
+            Callback::Hessian => &mut self.hessian,
+            Callback::Intermediate => &mut self.intermediate,
+        }
+    }
+}
+
+/// A kind of user callback measured by callback timing.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum Callback {
+    Objective,
+    ObjectiveGradient,
+    Constraint,
+    ConstraintJacobian,
+    Hessian,
+    Intermediate,
+}
+
+/// CPU and wallclock time in seconds spent in a part of the algorithm.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TaskTime {
+    /// CPU time.
+    pub cpu_time: Number,
+    /// Wallclock time.
+    pub wallclock_time: Number,
+}
+
+impl std::ops::Add for TaskTime {
+    type Output = TaskTime;
+    fn add(self, other: TaskTime) -> TaskTime {
+        TaskTime {
+            cpu_time: self.cpu_time + other.cpu_time,
+            wallclock_time: self.wallclock_time + other.wallclock_time,
+        }
+    }
+}
+
+/// Ipopt's breakdown of the time spent in the last solve.
+///
+/// The times of nested parts overlap. For instance, the linear system times are part of
+/// `pd_system_solve`, which is part of `compute_search_direction`.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimingStats {
+    /// The whole algorithm.
+    pub overall_algorithm: TaskTime,
+    /// All function evaluations, including those of the restoration phase.
+    pub function_evaluations: TaskTime,
+    /// Objective evaluations.
+    pub objective: TaskTime,
+    /// Objective gradient evaluations.
+    pub objective_gradient: TaskTime,
+    /// Evaluations of the equality constraints.
+    pub equality_constraints: TaskTime,
+    /// Evaluations of the inequality constraints.
+    pub inequality_constraints: TaskTime,
+    /// Evaluations of the equality constraint Jacobian.
+    pub equality_jacobian: TaskTime,
+    /// Evaluations of the inequality constraint Jacobian.
+    pub inequality_jacobian: TaskTime,
+    /// Hessian evaluations.
+    pub hessian: TaskTime,
+    /// Computing the initial point.
+    pub initialize_iterates: TaskTime,
+    /// Updating the Hessian or its quasi-Newton approximation.
+    pub update_hessian: TaskTime,
+    /// Printing the iteration output.
+    pub output_iteration: TaskTime,
+    /// Updating the barrier parameter.
+    pub update_barrier_parameter: TaskTime,
+    /// Computing the search direction.
+    pub compute_search_direction: TaskTime,
+    /// The line search.
+    pub compute_acceptable_trial_point: TaskTime,
+    /// Accepting the trial point.
+    pub accept_trial_point: TaskTime,
+    /// Checking convergence.
+    pub check_convergence: TaskTime,
+    /// Solving the primal-dual system, including iterative refinement.
+    pub pd_system_solve: TaskTime,
+    /// Computing the residuals of the primal-dual system.
+    pub compute_residuals: TaskTime,
+    /// Scaling the linear system.
+    pub linear_system_scaling: TaskTime,
+    /// Symbolic factorization of the linear system.
+    pub linear_system_symbolic_factorization: TaskTime,
+    /// Numeric factorization of the linear system.
+    pub linear_system_factorization: TaskTime,
+    /// Back solves with the factorized linear system.
+    pub linear_system_back_solve: TaskTime,
+}
+
+impl TimingStats {
+    /// Total time spent in the linear solver, which is the scaling, factorizations and back
+    /// solves of the linear system.
+    pub fn linear_solver(&self) -> TaskTime {
+        self.linear_system_scaling
+            + self.linear_system_symbolic_factorization
+            + self.linear_system_factorization
+            + self.linear_system_back_solve
+    }
+}
+
+impl From<ffi::CNLP_TaskTime> for TaskTime {
+    fn from(time: ffi::CNLP_TaskTime) -> Self {
+        TaskTime {
+            cpu_time: time.cpu_time,
+            wallclock_time: time.wallclock_time,
+        }
+    }
+}
+
+impl From<ffi::CNLP_TimingStats> for TimingStats {
+    fn from(stats: ffi::CNLP_TimingStats) -> Self {
+        TimingStats {
+            overall_algorithm: stats.overall_algorithm.into(),
+            function_evaluations: stats.function_evaluations.into(),
+            objective: stats.objective.into(),
+            objective_gradient: stats.objective_gradient.into(),
+            equality_constraints: stats.equality_constraints.into(),
+            inequality_constraints: stats.inequality_constraints.into(),
+            equality_jacobian: stats.equality_jacobian.into(),
+            inequality_jacobian: stats.inequality_jacobian.into(),
+            hessian: stats.hessian.into(),
+            initialize_iterates: stats.initialize_iterates.into(),
+            update_hessian: stats.update_hessian.into(),
+            output_iteration: stats.output_iteration.into(),
+            update_barrier_parameter: stats.update_barrier_parameter.into(),
+            compute_search_direction: stats.compute_search_direction.into(),
+            compute_acceptable_trial_point: stats.compute_acceptable_trial_point.into(),
+            accept_trial_point: stats.accept_trial_point.into(),
+            check_convergence: stats.check_convergence.into(),
+            pd_system_solve: stats.pd_system_solve.into(),
+            compute_residuals: stats.compute_residuals.into(),
+            linear_system_scaling: stats.linear_system_scaling.into(),
+            linear_system_symbolic_factorization: stats.linear_system_symbolic_factorization.into(),
+            linear_system_factorization: stats.linear_system_factorization.into(),
+            linear_system_back_solve: stats.linear_system_back_solve.into(),
+        }
+    }
+}
+
+impl From<ffi::CNLP_SolveStats> for SolveStats {
+    fn from(stats: ffi::CNLP_SolveStats) -> Self {
+        SolveStats {
+            iteration_count: stats.iteration_count,
+            total_cpu_time: stats.total_cpu_time,
+            total_sys_time: stats.total_sys_time,
+            total_wallclock_time: stats.total_wallclock_time,
+            num_objective_evaluations: stats.num_obj_evals,
+            num_constraint_evaluations: stats.num_constr_evals,
+            num_objective_gradient_evaluations: stats.num_obj_grad_evals,
+            num_constraint_jacobian_evaluations: stats.num_constr_jac_evals,
+            num_hessian_evaluations: stats.num_hess_evals,
+            objective_value: stats.obj_val,
+            scaled_objective_value: stats.scaled_obj_val,
+            dual_infeasibility: stats.dual_inf,
+            constraint_violation: stats.constr_viol,
+            complementarity: stats.complementarity,
+            nlp_error: stats.kkt_error,
+            num_regularized_iterations: stats.num_regularized_iterations,
+            final_regularization: stats.final_regularization,
+            callback_times: None,
+            timing: None,
+        }
+    }
+}
+
+impl<P: BasicProblem> Ipopt<P> {
+    /// Measure the time spent in user callbacks during solves.
+    ///
+    /// The measurements are reported in
+    /// [`SolveStats::callback_times`](struct.SolveStats.html#structfield.callback_times). This
+    /// adds two clock reads per callback, and is disabled by default.
+    pub fn set_callback_timing(&mut self, enable: bool) -> &mut Self {
+        self.callback_times = if enable {
+            Some(CallbackTimes::default())
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Collect Ipopt's timing statistics during solves.
+    ///
+    /// The statistics are reported in
+    /// [`SolveStats::timing`](struct.SolveStats.html#structfield.timing). This sets the
+    /// `timing_statistics` option of Ipopt 3.14 and newer, which is disabled by default since
+    /// measuring adds overhead to every part of the algorithm. Older versions don't have the
+    /// option and always collect timing statistics, so there this does nothing.
+    pub fn set_timing_statistics(&mut self, enable: bool) -> &mut Self {
+        if OptionRegistry::linked_ref()
+            .get("timing_statistics")
+            .is_some()
+        {
+            let value = if enable { "yes" } else { "no" };
+            Self::set_ipopt_option(self.nlp_internal, "timing_statistics", value);
+        }
+        self
+    }
+
+    /// Run a callback, adding the time it takes to the callback times if timing is enabled.
+    pub(crate) fn timed<R>(&mut self, callback: Callback, f: impl FnOnce(&mut Self) -> R) -> R {
+        if self.callback_times.is_none() {
+            return f(self);
+        }
+        let start = Instant::now();
+        let result = f(self);
+        let elapsed = start.elapsed().as_secs_f64();
+        if let Some(times) = self.callback_times.as_mut() {
+            *times.entry(callback) += elapsed;
+        }
+        result
+    }
+
+    /// Get statistics of the last solve.
+    ///
+    /// Returns `None` if the problem has not been solved yet, or if the last solve failed before
+    /// the algorithm started (e.g. due to an invalid option).
+    pub fn solve_stats(&self) -> Option<SolveStats> {
+        let mut stats: ffi::CNLP_SolveStats = unsafe { std::mem::zeroed() };
+        let available = unsafe { ffi::cnlp_get_solve_stats(self.nlp_internal, &mut stats) };
+        if available != 0 {
+            let mut stats = SolveStats::from(stats);
+            if self.objective_sense == ObjectiveSense::Maximize {
+                stats.objective_value = -stats.objective_value;
+                stats.scaled_objective_value = -stats.scaled_objective_value;
+            }
+            stats.callback_times = self.callback_times;
+            stats.timing = self.timing_stats();
+            Some(stats)
+        } else {
+            None
+        }
+    }
+
+    /// Ipopt's timing statistics of the last solve, if they were collected.
+    fn timing_stats(&self) -> Option<TimingStats> {
+        let mut stats: ffi::CNLP_TimingStats = unsafe { std::mem::zeroed() };
+        let available = unsafe { ffi::cnlp_get_timing_stats(self.nlp_internal, &mut stats) };
+        if available != 0 {
+            Some(TimingStats::from(stats))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn callback_times_test() {
+        let mut times = CallbackTimes::default();
+        *times.entry(Callback::Objective) += 0.5;
+        *times.entry(Callback::Hessian) += 0.25;
+        *times.entry(Callback::Objective) += 0.5;
+        assert_eq!(times.objective, 1.0);
+        assert_eq!(times.total(), 1.25);
+    }
+
+    #[test]
+    fn timing_stats_test() {
+        let time = |t| TaskTime {
+            cpu_time: t,
+            wallclock_time: 2.0 * t,
+        };
+        let stats = TimingStats {
+            linear_system_factorization: time(1.0),
+            linear_system_back_solve: time(0.5),
+            pd_system_solve: time(2.0),
+            ..TimingStats::default()
+        };
+        assert_eq!(stats.linear_solver(), time(1.5));
+    }
+}
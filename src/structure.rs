@@ -0,0 +1,147 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Validation of sparsity structures.
+//!
+//! Ipopt does not check the indices returned by the structure callbacks, and an index that is
+//! off by one crashes the linear solver or silently produces a wrong KKT matrix.
+//! [`check_structure`](fn.check_structure.html) and
+//! [`check_newton_structure`](fn.check_newton_structure.html) verify that the Jacobian and
+//! Hessian indices of a problem are in bounds for its
+//! [`IndexingStyle`](enum.IndexingStyle.html), and that Hessian entries are in the lower
+//! triangular part. Offending entries are reported with the indices as the problem returned
+//! them, so they can be found in one-based code as well.
+
+use crate::{
+    ConstrainedProblem, Evaluation, Index, IndexingStyle, InvalidEntry, NewtonProblem,
+    SparsityError,
+};
+
+use std::fmt::{Display, Formatter};
+
+impl Display for IndexingStyle {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            IndexingStyle::CStyle => write!(f, "zero-based"),
+            IndexingStyle::FortranStyle => write!(f, "one-based"),
+        }
+    }
+}
+
+/// Check the indices of a matrix with the given size.
+pub(crate) fn check_entries(
+    matrix: Evaluation,
+    style: IndexingStyle,
+    size: (usize, usize),
+    rows: &[Index],
+    cols: &[Index],
+) -> Result<(), SparsityError> {
+    let offset = style.offset();
+    let in_bounds = |i: Index, n: usize| i >= offset && ((i - offset) as usize) < n;
+    for (entry, (&row, &col)) in rows.iter().zip(cols).enumerate() {
+        if !in_bounds(row, size.0) || !in_bounds(col, size.1) {
+            return Err(SparsityError::OutOfBounds(InvalidEntry {
+                matrix: Some(matrix),
+                entry,
+                row,
+                col,
+                style,
+            }));
+        }
+        if matrix == Evaluation::Hessian && row < col {
+            return Err(SparsityError::UpperTriangle(InvalidEntry {
+                matrix: Some(matrix),
+                entry,
+                row,
+                col,
+                style,
+            }));
+        }
+    }
+    Ok(())
+}
+
+/// Check the Hessian structure of an unconstrained problem.
+pub fn check_newton_structure<P: NewtonProblem>(problem: &P) -> Result<(), SparsityError> {
+    let n = problem.num_variables();
+    let nnz = problem.num_hessian_non_zeros();
+    let (mut rows, mut cols) = (vec![0; nnz], vec![0; nnz]);
+    if !problem.hessian_indices(&mut rows, &mut cols) {
+        return Err(SparsityError::IndicesFailed(Evaluation::Hessian));
+    }
+    let style = problem.indexing_style();
+    check_entries(Evaluation::Hessian, style, (n, n), &rows, &cols)
+}
+
+/// Check the constraint Jacobian and Hessian structures of a constrained problem.
+pub fn check_structure<P: ConstrainedProblem>(problem: &P) -> Result<(), SparsityError> {
+    let n = problem.num_variables();
+    let m = problem.num_constraints();
+    let style = problem.indexing_style();
+
+    let nnz = problem.num_constraint_jacobian_non_zeros();
+    let (mut rows, mut cols) = (vec![0; nnz], vec![0; nnz]);
+    if !problem.constraint_jacobian_indices(&mut rows, &mut cols) {
+        return Err(SparsityError::IndicesFailed(Evaluation::ConstraintJacobian));
+    }
+    check_entries(Evaluation::ConstraintJacobian, style, (m, n), &rows, &cols)?;
+
+    let nnz = problem.num_hessian_non_zeros();
+    rows.resize(nnz, 0);
+    cols.resize(nnz, 0);
+    if !problem.hessian_indices(&mut rows, &mut cols) {
+        return Err(SparsityError::IndicesFailed(Evaluation::Hessian));
+    }
+    check_entries(Evaluation::Hessian, style, (n, n), &rows, &cols)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_entries_test() {
+        let style = IndexingStyle::FortranStyle;
+        let jac = Evaluation::ConstraintJacobian;
+        assert!(check_entries(jac, style, (1, 2), &[1, 1], &[1, 2]).is_ok());
+        let err = check_entries(jac, style, (1, 2), &[1, 1], &[0, 1]).unwrap_err();
+        assert_eq!(
+            err,
+            SparsityError::OutOfBounds(InvalidEntry {
+                matrix: Some(jac),
+                entry: 0,
+                row: 1,
+                col: 0,
+                style
+            })
+        );
+        assert_eq!(
+            err.to_string(),
+            "The constraint Jacobian entry 0 at (1, 0) is outside the matrix (one-based indices)."
+        );
+
+        let hess = Evaluation::Hessian;
+        assert!(check_entries(hess, style, (2, 2), &[1, 2, 2], &[1, 1, 2]).is_ok());
+        assert_eq!(
+            check_entries(hess, IndexingStyle::CStyle, (2, 2), &[0, 0], &[0, 1]),
+            Err(SparsityError::UpperTriangle(InvalidEntry {
+                matrix: Some(hess),
+                entry: 1,
+                row: 0,
+                col: 1,
+                style: IndexingStyle::CStyle
+            }))
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Threading of the underlying linear algebra and deterministic solves.
+//!
+//! Ipopt itself is single threaded, but BLAS, LAPACK and linear solvers like MA86, MA97 and
+//! Pardiso may use OpenMP, OpenBLAS or MKL threads. Parallel reductions in these libraries sum
+//! in a nondeterministic order, so repeated solves of the same problem can take different
+//! iteration paths. [`set_num_threads`](fn.set_num_threads.html) sets the number of threads of
+//! all of these libraries, and
+//! [`Ipopt::enable_deterministic_mode`](struct.Ipopt.html#method.enable_deterministic_mode)
+//! pins them to a single thread, which makes repeated solves bit-identical.
+//!
+//! Both change the threading runtimes of the whole process through their runtime APIs, which
+//! is only possible for runtimes that can be detected. To cover the others, set the
+//! `OMP_NUM_THREADS`, `OPENBLAS_NUM_THREADS` or `MKL_NUM_THREADS` environment variables before
+//! the program starts.
+
+use crate::ffi;
+use crate::{BasicProblem, Ipopt};
+
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+
+/// Error returned when the number of threads can't be set.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NumThreadsError {
+    /// At least one thread is required.
+    Zero,
+    /// The number of threads is too large for the threading runtimes.
+    TooMany(usize),
+    /// None of the threading runtimes could be found in the process. This is expected if Ipopt
+    /// was built without threaded libraries, or on platforms other than Linux and the BSDs,
+    /// where runtimes cannot be detected.
+    NoRuntime,
+}
+
+impl Display for NumThreadsError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            NumThreadsError::Zero => write!(f, "At least one thread is required."),
+            NumThreadsError::TooMany(n) => write!(f, "Too many threads: {}.", n),
+            NumThreadsError::NoRuntime => write!(
+                f,
+                "No OpenMP, OpenBLAS or MKL runtime was found in the process."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NumThreadsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+/// Set the number of threads used by OpenMP, OpenBLAS and MKL.
+///
+/// This affects the whole process, including solvers that are already running, and other
+/// libraries using the same runtimes.
+pub fn set_num_threads(num_threads: usize) -> Result<(), NumThreadsError> {
+    if num_threads == 0 {
+        return Err(NumThreadsError::Zero);
+    }
+    let n =
+        ffi::CNLP_Int::try_from(num_threads).map_err(|_| NumThreadsError::TooMany(num_threads))?;
+    if unsafe { ffi::cnlp_set_num_threads(n) } != 0 {
+        Ok(())
+    } else {
+        Err(NumThreadsError::NoRuntime)
+    }
+}
+
+impl<P: BasicProblem> Ipopt<P> {
+    /// Make repeated solves produce bit-identical iteration paths.
+    ///
+    /// This pins OpenMP, OpenBLAS and MKL to a single thread with
+    /// [`set_num_threads`](fn.set_num_threads.html), which affects the whole process, and
+    /// removes the CPU and wall time limits (`max_cpu_time` and, on Ipopt 3.14 and newer,
+    /// `max_wall_time`) of this solver, since whether they are hit depends on timing.
+    ///
+    /// The iteration path is only reproduced if the rest of the setup is fixed as well: the
+    /// thread count must not be changed again afterwards, and the linear solver should be chosen
+    /// explicitly with the `linear_solver` option, since the default depends on how Ipopt was
+    /// built and which libraries are found at runtime.
+    ///
+    /// Returns `false` if no threading runtime could be found, in which case the threads of
+    /// runtimes that can't be detected must be pinned with environment variables instead.
+    pub fn enable_deterministic_mode(&mut self) -> bool {
+        let configured = set_num_threads(1).is_ok();
+        self.set_option("max_cpu_time", 1e20);
+        // Older versions of Ipopt don't have a wall time limit.
+        self.set_option("max_wall_time", 1e20);
+        configured
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn num_threads_test() {
+        assert_eq!(set_num_threads(0), Err(NumThreadsError::Zero));
+        let too_many = ffi::CNLP_Int::MAX as usize + 1;
+        assert_eq!(
+            set_num_threads(too_many),
+            Err(NumThreadsError::TooMany(too_many))
+        );
+    }
+}
@@ -0,0 +1,891 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Recording and replaying the callbacks of a solve.
+//!
+//! Solver bugs are hard to report when the model they show up with cannot be shared. A
+//! [`Recorder`](struct.Recorder.html) wraps a problem and records every callback invocation
+//! along with its inputs and outputs into a [`Trace`](struct.Trace.html), which can be written
+//! to a compact binary file. A [`Replay`](struct.Replay.html) is a problem that answers the
+//! callbacks from a trace alone, so the solve can be reproduced without the model code:
+//!
+//! ```no_run
+//! # use ipopt::{Ipopt, RandomProblem, Recorder, Replay, Trace};
+//! # use std::fs::File;
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # let problem = RandomProblem::new(10, 5, 0);
+//! // On the user side.
+//! let mut ipopt = Ipopt::new(Recorder::new(problem))?;
+//! ipopt.solve();
+//! ipopt.solver_data().problem.trace().write_to(File::create("solve.trace")?)?;
+//!
+//! // On the maintainer side.
+//! let trace = Trace::read_from(File::open("solve.trace")?)?;
+//! let mut ipopt = Ipopt::new(Replay::new(trace))?;
+//! ipopt.solve();
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Replayed callbacks are matched by their inputs, bit for bit, so the replay follows the
+//! recorded solve as long as Ipopt runs with the same options on the same platform. A callback
+//! with inputs that were never recorded fails, and is reported by
+//! [`Replay::first_unmatched`](struct.Replay.html#method.first_unmatched).
+
+use crate::{
+    AlgorithmMode, BasicProblem, ConstrainedProblem, ConstraintGroups, Index, IndexingStyle,
+    NewtonProblem, Number,
+};
+
+use std::cell::{Cell, Ref, RefCell};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::io::{self, Read, Write};
+
+/// Identifies trace files.
+const MAGIC: &[u8; 8] = b"IPOPTTRC";
+
+/// Version of the trace format.
+const VERSION: u32 = 1;
+
+/// A recorded callback.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TraceCallback {
+    /// [`BasicProblem::bounds`](trait.BasicProblem.html#tymethod.bounds).
+    Bounds,
+    /// [`BasicProblem::initial_point`](trait.BasicProblem.html#tymethod.initial_point).
+    InitialPoint,
+    /// [`BasicProblem::initial_bounds_multipliers`](trait.BasicProblem.html#method.initial_bounds_multipliers).
+    InitialBoundsMultipliers,
+    /// [`BasicProblem::objective`](trait.BasicProblem.html#tymethod.objective).
+    Objective,
+    /// [`BasicProblem::objective_grad`](trait.BasicProblem.html#tymethod.objective_grad).
+    ObjectiveGrad,
+    /// [`BasicProblem::variable_scaling`](trait.BasicProblem.html#method.variable_scaling).
+    VariableScaling,
+    /// [`ConstrainedProblem::constraint`](trait.ConstrainedProblem.html#tymethod.constraint).
+    Constraint,
+    /// [`ConstrainedProblem::constraint_bounds`](trait.ConstrainedProblem.html#tymethod.constraint_bounds).
+    ConstraintBounds,
+    /// [`ConstrainedProblem::initial_constraint_multipliers`](trait.ConstrainedProblem.html#method.initial_constraint_multipliers).
+    InitialConstraintMultipliers,
+    /// [`ConstrainedProblem::constraint_jacobian_indices`](trait.ConstrainedProblem.html#tymethod.constraint_jacobian_indices).
+    ConstraintJacobianIndices,
+    /// [`ConstrainedProblem::constraint_jacobian_values`](trait.ConstrainedProblem.html#tymethod.constraint_jacobian_values).
+    ConstraintJacobianValues,
+    /// The Hessian indices of a constrained or unconstrained problem.
+    HessianIndices,
+    /// [`NewtonProblem::hessian_values`](trait.NewtonProblem.html#tymethod.hessian_values).
+    NewtonHessianValues,
+    /// [`ConstrainedProblem::hessian_values`](trait.ConstrainedProblem.html#method.hessian_values).
+    HessianValues,
+    /// [`ConstrainedProblem::constraint_scaling`](trait.ConstrainedProblem.html#method.constraint_scaling).
+    ConstraintScaling,
+}
+
+impl TraceCallback {
+    const ALL: [TraceCallback; 15] = [
+        TraceCallback::Bounds,
+        TraceCallback::InitialPoint,
+        TraceCallback::InitialBoundsMultipliers,
+        TraceCallback::Objective,
+        TraceCallback::ObjectiveGrad,
+        TraceCallback::VariableScaling,
+        TraceCallback::Constraint,
+        TraceCallback::ConstraintBounds,
+        TraceCallback::InitialConstraintMultipliers,
+        TraceCallback::ConstraintJacobianIndices,
+        TraceCallback::ConstraintJacobianValues,
+        TraceCallback::HessianIndices,
+        TraceCallback::NewtonHessianValues,
+        TraceCallback::HessianValues,
+        TraceCallback::ConstraintScaling,
+    ];
+
+    fn from_tag(tag: u8) -> Option<TraceCallback> {
+        TraceCallback::ALL.get(tag as usize).cloned()
+    }
+
+    fn tag(self) -> u8 {
+        TraceCallback::ALL.iter().position(|&c| c == self).unwrap() as u8
+    }
+}
+
+impl Display for TraceCallback {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// A single callback invocation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceEvent {
+    /// The callback.
+    pub callback: TraceCallback,
+    /// The inputs in the order of the callback arguments, such as `x`, or `x`, `obj_factor`
+    /// and `lambda` for the Hessian.
+    pub input: Vec<Number>,
+    /// The outputs in the order of the callback arguments, concatenated. Indices are stored
+    /// as numbers.
+    pub output: Vec<Number>,
+    /// The value returned by the callback.
+    pub success: bool,
+}
+
+/// The recorded callbacks of a problem.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trace {
+    /// Indexing style of the problem.
+    pub indexing_style: IndexingStyle,
+    /// Number of variables.
+    pub num_variables: usize,
+    /// Number of constraints.
+    pub num_constraints: usize,
+    /// Number of non-zeros of the constraint Jacobian.
+    pub num_constraint_jacobian_non_zeros: usize,
+    /// Number of non-zeros of the Hessian.
+    pub num_hessian_non_zeros: usize,
+    /// Objective scaling of the problem.
+    pub objective_scaling: Number,
+    /// The invocations in the order they happened.
+    pub events: Vec<TraceEvent>,
+}
+
+/// Error produced when reading a trace.
+#[derive(Debug)]
+pub enum TraceError {
+    /// The data does not start like a trace file.
+    NotATrace,
+    /// The trace was written with an unsupported version of the format.
+    UnsupportedVersion(u32),
+    /// The trace contains an unknown callback.
+    UnknownCallback(u8),
+    /// The trace ends early, for example because the recording was cut off.
+    Corrupt,
+    /// Reading or writing the trace failed.
+    Io(io::Error),
+}
+
+impl Display for TraceError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            TraceError::NotATrace => write!(f, "The data is not an Ipopt trace."),
+            TraceError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported trace format version {}.", version)
+            }
+            TraceError::UnknownCallback(tag) => {
+                write!(f, "The trace contains an unknown callback ({}).", tag)
+            }
+            TraceError::Corrupt => write!(f, "The trace is truncated or corrupt."),
+            TraceError::Io(err) => write!(f, "Failed to read or write the trace: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for TraceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TraceError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TraceError {
+    fn from(err: io::Error) -> Self {
+        if err.kind() == io::ErrorKind::UnexpectedEof {
+            TraceError::Corrupt
+        } else {
+            TraceError::Io(err)
+        }
+    }
+}
+
+fn write_u64<W: Write>(out: &mut W, value: u64) -> io::Result<()> {
+    out.write_all(&value.to_le_bytes())
+}
+
+fn write_numbers<W: Write>(out: &mut W, values: &[Number]) -> io::Result<()> {
+    write_u64(out, values.len() as u64)?;
+    for v in values {
+        out.write_all(&v.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+fn read_u64<R: Read>(input: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_numbers<R: Read>(input: &mut R) -> Result<Vec<Number>, TraceError> {
+    let len = read_u64(input)?;
+    // Don't preallocate from the stored length, a corrupt trace runs out of values first.
+    let mut values = Vec::new();
+    for _ in 0..len {
+        values.push(Number::from_bits(read_u64(input)?));
+    }
+    Ok(values)
+}
+
+impl Trace {
+    /// Write the trace in its binary format.
+    ///
+    /// All numbers are stored in little endian byte order, so traces can be moved between
+    /// platforms.
+    pub fn write_to<W: Write>(&self, mut out: W) -> io::Result<()> {
+        out.write_all(MAGIC)?;
+        out.write_all(&VERSION.to_le_bytes())?;
+        out.write_all(&[self.indexing_style as u8])?;
+        write_u64(&mut out, self.num_variables as u64)?;
+        write_u64(&mut out, self.num_constraints as u64)?;
+        write_u64(&mut out, self.num_constraint_jacobian_non_zeros as u64)?;
+        write_u64(&mut out, self.num_hessian_non_zeros as u64)?;
+        out.write_all(&self.objective_scaling.to_le_bytes())?;
+        for event in self.events.iter() {
+            out.write_all(&[event.callback.tag(), event.success as u8])?;
+            write_numbers(&mut out, &event.input)?;
+            write_numbers(&mut out, &event.output)?;
+        }
+        out.flush()
+    }
+
+    /// Read a trace written by [`write_to`](#method.write_to).
+    pub fn read_from<R: Read>(mut input: R) -> Result<Trace, TraceError> {
+        let mut magic = [0; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(TraceError::NotATrace);
+        }
+        let mut version = [0; 4];
+        input.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != VERSION {
+            return Err(TraceError::UnsupportedVersion(version));
+        }
+        let mut style = [0; 1];
+        input.read_exact(&mut style)?;
+        let indexing_style = if style[0] == 0 {
+            IndexingStyle::CStyle
+        } else {
+            IndexingStyle::FortranStyle
+        };
+        let mut trace = Trace {
+            indexing_style,
+            num_variables: read_u64(&mut input)? as usize,
+            num_constraints: read_u64(&mut input)? as usize,
+            num_constraint_jacobian_non_zeros: read_u64(&mut input)? as usize,
+            num_hessian_non_zeros: read_u64(&mut input)? as usize,
+            objective_scaling: Number::from_bits(read_u64(&mut input)?),
+            events: Vec::new(),
+        };
+
+        loop {
+            let mut header = [0; 2];
+            // The events end with the data.
+            let read = loop {
+                match input.read(&mut header[..1]) {
+                    Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    read => break read?,
+                }
+            };
+            if read == 0 {
+                break;
+            }
+            input.read_exact(&mut header[1..])?;
+            let callback =
+                TraceCallback::from_tag(header[0]).ok_or(TraceError::UnknownCallback(header[0]))?;
+            trace.events.push(TraceEvent {
+                callback,
+                success: header[1] != 0,
+                input: read_numbers(&mut input)?,
+                output: read_numbers(&mut input)?,
+            });
+        }
+        Ok(trace)
+    }
+}
+
+/// A problem that records its callbacks into a trace.
+///
+/// All callbacks are forwarded to the wrapped problem. The trace is kept in memory until it is
+/// taken with [`into_parts`](#method.into_parts) or written out.
+#[derive(Debug)]
+pub struct Recorder<P> {
+    problem: P,
+    trace: RefCell<Trace>,
+}
+
+impl<P: BasicProblem> Recorder<P> {
+    /// Start recording the callbacks of the given problem.
+    pub fn new(problem: P) -> Self {
+        let trace = Trace {
+            indexing_style: problem.indexing_style(),
+            num_variables: problem.num_variables(),
+            num_constraints: 0,
+            num_constraint_jacobian_non_zeros: 0,
+            num_hessian_non_zeros: 0,
+            objective_scaling: problem.objective_scaling(),
+            events: Vec::new(),
+        };
+        Recorder {
+            problem,
+            trace: RefCell::new(trace),
+        }
+    }
+}
+
+impl<P> Recorder<P> {
+    /// The recorded problem.
+    pub fn problem(&self) -> &P {
+        &self.problem
+    }
+
+    /// The trace recorded so far.
+    pub fn trace(&self) -> Ref<Trace> {
+        self.trace.borrow()
+    }
+
+    /// Stop recording, returning the problem and its trace.
+    pub fn into_parts(self) -> (P, Trace) {
+        (self.problem, self.trace.into_inner())
+    }
+
+    fn record(
+        &self,
+        callback: TraceCallback,
+        input: &[&[Number]],
+        output: &[&[Number]],
+        success: bool,
+    ) {
+        self.trace.borrow_mut().events.push(TraceEvent {
+            callback,
+            input: input.concat(),
+            output: output.concat(),
+            success,
+        });
+    }
+
+    fn record_indices(
+        &self,
+        callback: TraceCallback,
+        rows: &[Index],
+        cols: &[Index],
+        success: bool,
+    ) {
+        let output: Vec<_> = rows.iter().chain(cols).map(|&i| Number::from(i)).collect();
+        self.record(callback, &[], &[&output], success);
+    }
+}
+
+impl<P: BasicProblem> BasicProblem for Recorder<P> {
+    fn indexing_style(&self) -> IndexingStyle {
+        self.problem.indexing_style()
+    }
+    fn num_variables(&self) -> usize {
+        self.problem.num_variables()
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        let success = self.problem.bounds(x_l, x_u);
+        self.record(TraceCallback::Bounds, &[], &[x_l, x_u], success);
+        success
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        let success = self.problem.initial_point(x);
+        self.record(TraceCallback::InitialPoint, &[], &[x], success);
+        success
+    }
+    fn initial_bounds_multipliers(&self, z_l: &mut [Number], z_u: &mut [Number]) -> bool {
+        let success = self.problem.initial_bounds_multipliers(z_l, z_u);
+        self.record(
+            TraceCallback::InitialBoundsMultipliers,
+            &[],
+            &[z_l, z_u],
+            success,
+        );
+        success
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        let success = self.problem.objective(x, obj);
+        self.record(TraceCallback::Objective, &[x], &[&[*obj]], success);
+        success
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        let success = self.problem.objective_grad(x, grad_f);
+        self.record(TraceCallback::ObjectiveGrad, &[x], &[grad_f], success);
+        success
+    }
+    fn variable_scaling(&self, x_scaling: &mut [Number]) -> bool {
+        let success = self.problem.variable_scaling(x_scaling);
+        self.record(TraceCallback::VariableScaling, &[], &[x_scaling], success);
+        success
+    }
+    fn objective_scaling(&self) -> f64 {
+        self.problem.objective_scaling()
+    }
+    fn algorithm_mode_changed(&mut self, mode: AlgorithmMode) {
+        self.problem.algorithm_mode_changed(mode)
+    }
+}
+
+impl<P: NewtonProblem> NewtonProblem for Recorder<P> {
+    fn num_hessian_non_zeros(&self) -> usize {
+        let nnz = self.problem.num_hessian_non_zeros();
+        self.trace.borrow_mut().num_hessian_non_zeros = nnz;
+        nnz
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        let success = self.problem.hessian_indices(rows, cols);
+        self.record_indices(TraceCallback::HessianIndices, rows, cols, success);
+        success
+    }
+    fn hessian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        let success = self.problem.hessian_values(x, vals);
+        self.record(TraceCallback::NewtonHessianValues, &[x], &[vals], success);
+        success
+    }
+}
+
+impl<P: ConstrainedProblem> ConstrainedProblem for Recorder<P> {
+    fn num_constraints(&self) -> usize {
+        let m = self.problem.num_constraints();
+        self.trace.borrow_mut().num_constraints = m;
+        m
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        let nnz = self.problem.num_constraint_jacobian_non_zeros();
+        self.trace.borrow_mut().num_constraint_jacobian_non_zeros = nnz;
+        nnz
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        let success = self.problem.constraint(x, g);
+        self.record(TraceCallback::Constraint, &[x], &[g], success);
+        success
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        let success = self.problem.constraint_bounds(g_l, g_u);
+        self.record(TraceCallback::ConstraintBounds, &[], &[g_l, g_u], success);
+        success
+    }
+    fn initial_constraint_multipliers(&self, lambda: &mut [Number]) -> bool {
+        let success = self.problem.initial_constraint_multipliers(lambda);
+        self.record(
+            TraceCallback::InitialConstraintMultipliers,
+            &[],
+            &[lambda],
+            success,
+        );
+        success
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        let success = self.problem.constraint_jacobian_indices(rows, cols);
+        self.record_indices(
+            TraceCallback::ConstraintJacobianIndices,
+            rows,
+            cols,
+            success,
+        );
+        success
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        let success = self.problem.constraint_jacobian_values(x, vals);
+        self.record(
+            TraceCallback::ConstraintJacobianValues,
+            &[x],
+            &[vals],
+            success,
+        );
+        success
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        let nnz = self.problem.num_hessian_non_zeros();
+        self.trace.borrow_mut().num_hessian_non_zeros = nnz;
+        nnz
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        let success = self.problem.hessian_indices(rows, cols);
+        self.record_indices(TraceCallback::HessianIndices, rows, cols, success);
+        success
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        let success = self.problem.hessian_values(x, obj_factor, lambda, vals);
+        self.record(
+            TraceCallback::HessianValues,
+            &[x, &[obj_factor], lambda],
+            &[vals],
+            success,
+        );
+        success
+    }
+    fn constraint_scaling(&self, g_scaling: &mut [Number]) -> bool {
+        let success = self.problem.constraint_scaling(g_scaling);
+        self.record(TraceCallback::ConstraintScaling, &[], &[g_scaling], success);
+        success
+    }
+    fn constraint_groups(&self) -> ConstraintGroups {
+        self.problem.constraint_groups()
+    }
+}
+
+/// Key of a recorded invocation: the callback and the bits of its inputs.
+type ReplayKey = (TraceCallback, Vec<u64>);
+
+/// A problem answering its callbacks from a trace.
+#[derive(Debug)]
+pub struct Replay {
+    trace: Trace,
+    /// Position of the recorded event for each callback and input.
+    events: HashMap<ReplayKey, usize>,
+    first_unmatched: Cell<Option<TraceCallback>>,
+}
+
+impl Replay {
+    /// Replay the given trace.
+    ///
+    /// If a callback was recorded more than once with the same inputs, the last recording is
+    /// replayed.
+    pub fn new(trace: Trace) -> Self {
+        let events = trace
+            .events
+            .iter()
+            .enumerate()
+            .map(|(i, event)| (replay_key(event.callback, &[&event.input]), i))
+            .collect();
+        Replay {
+            trace,
+            events,
+            first_unmatched: Cell::new(None),
+        }
+    }
+
+    /// The replayed trace.
+    pub fn trace(&self) -> &Trace {
+        &self.trace
+    }
+
+    /// The first callback invoked with inputs that are not in the trace, which means the
+    /// replayed solve diverged from the recorded one.
+    pub fn first_unmatched(&self) -> Option<TraceCallback> {
+        self.first_unmatched.get()
+    }
+
+    /// Write the recorded outputs of a callback with the given inputs.
+    fn replay(
+        &self,
+        callback: TraceCallback,
+        input: &[&[Number]],
+        output: &mut [&mut [Number]],
+    ) -> bool {
+        let event = match self.events.get(&replay_key(callback, input)) {
+            Some(&i) => &self.trace.events[i],
+            None => {
+                if self.first_unmatched.get().is_none() {
+                    self.first_unmatched.set(Some(callback));
+                }
+                return false;
+            }
+        };
+        let mut values = event.output.iter();
+        for out in output.iter_mut() {
+            for (o, &v) in out.iter_mut().zip(values.by_ref()) {
+                *o = v;
+            }
+        }
+        event.success
+    }
+
+    fn replay_indices(
+        &self,
+        callback: TraceCallback,
+        rows: &mut [Index],
+        cols: &mut [Index],
+    ) -> bool {
+        let mut values = vec![0.0; rows.len() + cols.len()];
+        let success = self.replay(callback, &[], &mut [&mut values]);
+        let (r, c) = values.split_at(rows.len());
+        for (i, &v) in rows.iter_mut().zip(r).chain(cols.iter_mut().zip(c)) {
+            *i = v as Index;
+        }
+        success
+    }
+}
+
+fn replay_key(callback: TraceCallback, input: &[&[Number]]) -> ReplayKey {
+    let bits = input
+        .iter()
+        .flat_map(|v| v.iter())
+        .map(|v| v.to_bits())
+        .collect();
+    (callback, bits)
+}
+
+impl BasicProblem for Replay {
+    fn indexing_style(&self) -> IndexingStyle {
+        self.trace.indexing_style
+    }
+    fn num_variables(&self) -> usize {
+        self.trace.num_variables
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        self.replay(TraceCallback::Bounds, &[], &mut [x_l, x_u])
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        self.replay(TraceCallback::InitialPoint, &[], &mut [x])
+    }
+    fn initial_bounds_multipliers(&self, z_l: &mut [Number], z_u: &mut [Number]) -> bool {
+        self.replay(
+            TraceCallback::InitialBoundsMultipliers,
+            &[],
+            &mut [z_l, z_u],
+        )
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        self.replay(
+            TraceCallback::Objective,
+            &[x],
+            &mut [std::slice::from_mut(obj)],
+        )
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        self.replay(TraceCallback::ObjectiveGrad, &[x], &mut [grad_f])
+    }
+    fn variable_scaling(&self, x_scaling: &mut [Number]) -> bool {
+        self.replay(TraceCallback::VariableScaling, &[], &mut [x_scaling])
+    }
+    fn objective_scaling(&self) -> f64 {
+        self.trace.objective_scaling
+    }
+}
+
+impl NewtonProblem for Replay {
+    fn num_hessian_non_zeros(&self) -> usize {
+        self.trace.num_hessian_non_zeros
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        self.replay_indices(TraceCallback::HessianIndices, rows, cols)
+    }
+    fn hessian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        self.replay(TraceCallback::NewtonHessianValues, &[x], &mut [vals])
+    }
+}
+
+impl ConstrainedProblem for Replay {
+    fn num_constraints(&self) -> usize {
+        self.trace.num_constraints
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        self.trace.num_constraint_jacobian_non_zeros
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        self.replay(TraceCallback::Constraint, &[x], &mut [g])
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        self.replay(TraceCallback::ConstraintBounds, &[], &mut [g_l, g_u])
+    }
+    fn initial_constraint_multipliers(&self, lambda: &mut [Number]) -> bool {
+        self.replay(
+            TraceCallback::InitialConstraintMultipliers,
+            &[],
+            &mut [lambda],
+        )
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        self.replay_indices(TraceCallback::ConstraintJacobianIndices, rows, cols)
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        self.replay(TraceCallback::ConstraintJacobianValues, &[x], &mut [vals])
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        self.trace.num_hessian_non_zeros
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        self.replay_indices(TraceCallback::HessianIndices, rows, cols)
+    }
+    fn hessian_values(
+        &self,
+        x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        self.replay(
+            TraceCallback::HessianValues,
+            &[x, &[obj_factor], lambda],
+            &mut [vals],
+        )
+    }
+    fn constraint_scaling(&self, g_scaling: &mut [Number]) -> bool {
+        self.replay(TraceCallback::ConstraintScaling, &[], &mut [g_scaling])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// min (x0 - 1)^2 + x1^2 subject to x0 + x1 = 1.
+    struct Model;
+
+    impl BasicProblem for Model {
+        fn num_variables(&self) -> usize {
+            2
+        }
+        fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+            x_l.copy_from_slice(&[-1e20, 0.0]);
+            x_u.copy_from_slice(&[1e20, 1e20]);
+            true
+        }
+        fn initial_point(&self, x: &mut [Number]) -> bool {
+            x.copy_from_slice(&[0.5, 0.5]);
+            true
+        }
+        fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+            *obj = (x[0] - 1.0).powi(2) + x[1] * x[1];
+            true
+        }
+        fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+            grad_f.copy_from_slice(&[2.0 * (x[0] - 1.0), 2.0 * x[1]]);
+            true
+        }
+    }
+
+    impl ConstrainedProblem for Model {
+        fn num_constraints(&self) -> usize {
+            1
+        }
+        fn num_constraint_jacobian_non_zeros(&self) -> usize {
+            2
+        }
+        fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+            g[0] = x[0] + x[1];
+            true
+        }
+        fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+            g_l[0] = 1.0;
+            g_u[0] = 1.0;
+            true
+        }
+        fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+            rows.copy_from_slice(&[0, 0]);
+            cols.copy_from_slice(&[0, 1]);
+            true
+        }
+        fn constraint_jacobian_values(&self, _x: &[Number], vals: &mut [Number]) -> bool {
+            vals.copy_from_slice(&[1.0, 1.0]);
+            true
+        }
+        fn num_hessian_non_zeros(&self) -> usize {
+            2
+        }
+        fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+            rows.copy_from_slice(&[0, 1]);
+            cols.copy_from_slice(&[0, 1]);
+            true
+        }
+        fn hessian_values(
+            &self,
+            _x: &[Number],
+            obj_factor: Number,
+            _lambda: &[Number],
+            vals: &mut [Number],
+        ) -> bool {
+            vals.copy_from_slice(&[2.0 * obj_factor, 2.0 * obj_factor]);
+            true
+        }
+    }
+
+    #[test]
+    fn record_replay_test() {
+        let recorder = Recorder::new(Model);
+        let x = [0.25, 0.75];
+        let (mut rows, mut cols) = ([0; 2], [0; 2]);
+        let (mut obj, mut g, mut vals) = (0.0, [0.0], [0.0; 2]);
+        assert_eq!(ConstrainedProblem::num_hessian_non_zeros(&recorder), 2);
+        assert!(ConstrainedProblem::hessian_indices(
+            &recorder, &mut rows, &mut cols
+        ));
+        assert!(recorder.objective(&x, &mut obj));
+        assert!(recorder.constraint(&x, &mut g));
+        assert!(ConstrainedProblem::hessian_values(
+            &recorder,
+            &x,
+            0.5,
+            &[2.0],
+            &mut vals
+        ));
+
+        let (_, trace) = recorder.into_parts();
+        assert_eq!(trace.events.len(), 4);
+        assert_eq!(trace.num_hessian_non_zeros, 2);
+        let mut bytes = Vec::new();
+        trace.write_to(&mut bytes).unwrap();
+        let read = Trace::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(read, trace);
+
+        let replay = Replay::new(read);
+        let (mut rows, mut cols) = ([0; 2], [0; 2]);
+        let (mut obj, mut vals) = (0.0, [0.0; 2]);
+        assert!(ConstrainedProblem::hessian_indices(
+            &replay, &mut rows, &mut cols
+        ));
+        assert_eq!((rows, cols), ([0, 1], [0, 1]));
+        assert!(replay.objective(&x, &mut obj));
+        assert_eq!(obj, 1.125);
+        assert!(ConstrainedProblem::hessian_values(
+            &replay,
+            &x,
+            0.5,
+            &[2.0],
+            &mut vals
+        ));
+        assert_eq!(vals, [1.0, 1.0]);
+        assert_eq!(replay.first_unmatched(), None);
+
+        assert!(!replay.objective(&[0.0, 0.0], &mut obj));
+        assert_eq!(replay.first_unmatched(), Some(TraceCallback::Objective));
+        assert!(matches!(
+            Trace::read_from(&b"not a trace"[..]),
+            Err(TraceError::NotATrace)
+        ));
+
+        // Cut the trace off in the middle of the last event.
+        bytes.truncate(bytes.len() - 4);
+        assert!(matches!(
+            Trace::read_from(bytes.as_slice()),
+            Err(TraceError::Corrupt)
+        ));
+
+        // A huge length must not be allocated up front.
+        let mut bytes = Vec::new();
+        Trace {
+            events: Vec::new(),
+            ..trace
+        }
+        .write_to(&mut bytes)
+        .unwrap();
+        bytes.extend_from_slice(&[TraceCallback::Objective.tag(), 1]);
+        bytes.extend_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            Trace::read_from(bytes.as_slice()),
+            Err(TraceError::Corrupt)
+        ));
+    }
+}
@@ -0,0 +1,859 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Elementwise changes of variables.
+//!
+//! [`Transformed`](struct.Transformed.html) wraps a problem defined in variables `x` and
+//! presents it to Ipopt in variables `y` with `x_i = φ_i(y_i)`, where each `φ_i` is a
+//! [`Transform`](enum.Transform.html). This is useful for variables that must stay strictly
+//! positive, such as rates or concentrations, which become unbounded with
+//! [`Transform::Exp`](enum.Transform.html#variant.Exp), or variables confined to an open
+//! interval with [`Transform::Logit`](enum.Transform.html#variant.Logit).
+//!
+//! Derivatives are transformed with the chain rule. The Hessian of the Lagrangian with respect
+//! to `y` is
+//!
+//! ```text
+//! ∇²_y L = Φ' ∇²_x L Φ' + diag(φ'' ∘ ∇_x L),
+//! ```
+//!
+//! where `Φ' = diag(φ')`. The second term needs the gradient of the Lagrangian with respect to
+//! `x`, so the objective gradient and the constraint Jacobian are evaluated along with every
+//! Hessian. Diagonal entries missing from the Hessian structure of the wrapped problem are
+//! appended for all nonlinearly transformed variables.
+//!
+//! Initial points on or beyond the ends of the range of a transform, such as `x_i = 0` with
+//! `Transform::Exp`, have no finite preimage. They are moved into the interior of the range
+//! first, in the same way Ipopt pushes initial points away from bounds.
+//!
+//! The solution reported by Ipopt is in the transformed variables; use
+//! [`to_original`](struct.Transformed.html#method.to_original) to recover `x`.
+
+use crate::{
+    AlgorithmMode, BasicProblem, ConstrainedProblem, ConstraintGroups, Index, IndexingStyle,
+    NewtonProblem, Number,
+};
+
+use std::cell::RefCell;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Bounds at or beyond this magnitude are infinite, as with Ipopt's default
+/// `nlp_lower_bound_inf` and `nlp_upper_bound_inf` options.
+const INFINITE_BOUND: Number = 1e19;
+
+/// Relative and interval fraction margins used to move initial points off the ends of the range
+/// of a transform, as with Ipopt's default `bound_push` and `bound_frac` options.
+const BOUND_PUSH: Number = 1e-2;
+const BOUND_FRAC: Number = 1e-2;
+
+/// An elementwise change of variables `x = φ(y)`.
+///
+/// All transforms are strictly increasing.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Transform {
+    /// `x = y`.
+    Identity,
+    /// `x = exp(y)`, for strictly positive variables.
+    Exp,
+    /// `x = lower + (upper - lower) / (1 + exp(-y))`, for variables in the open interval
+    /// `(lower, upper)`.
+    Logit {
+        /// Lower end of the interval.
+        lower: Number,
+        /// Upper end of the interval.
+        upper: Number,
+    },
+}
+
+impl Transform {
+    /// The original variable `φ(y)`.
+    pub fn forward(self, y: Number) -> Number {
+        match self {
+            Transform::Identity => y,
+            Transform::Exp => y.exp(),
+            Transform::Logit { lower, upper } => lower + (upper - lower) * sigmoid(y),
+        }
+    }
+
+    /// The transformed variable `φ⁻¹(x)`. Values at or beyond the ends of the range of `φ`
+    /// map to infinity.
+    pub fn inverse(self, x: Number) -> Number {
+        match self {
+            Transform::Identity => x,
+            Transform::Exp if x <= 0.0 => Number::NEG_INFINITY,
+            Transform::Exp => x.ln(),
+            Transform::Logit { lower, upper } => {
+                let t = (x - lower) / (upper - lower);
+                if t <= 0.0 {
+                    Number::NEG_INFINITY
+                } else if t >= 1.0 {
+                    Number::INFINITY
+                } else {
+                    (t / (1.0 - t)).ln()
+                }
+            }
+        }
+    }
+
+    /// The first derivative `φ'(y)`.
+    pub fn derivative(self, y: Number) -> Number {
+        match self {
+            Transform::Identity => 1.0,
+            Transform::Exp => y.exp(),
+            Transform::Logit { lower, upper } => {
+                let s = sigmoid(y);
+                (upper - lower) * s * (1.0 - s)
+            }
+        }
+    }
+
+    /// The second derivative `φ''(y)`.
+    pub fn second_derivative(self, y: Number) -> Number {
+        match self {
+            Transform::Identity => 0.0,
+            Transform::Exp => y.exp(),
+            Transform::Logit { lower, upper } => {
+                let s = sigmoid(y);
+                (upper - lower) * s * (1.0 - s) * (1.0 - 2.0 * s)
+            }
+        }
+    }
+
+    /// Map a bound on `x` to a bound on `y`, keeping infinite bounds infinite.
+    fn inverse_bound(self, bound: Number) -> Number {
+        if bound <= -INFINITE_BOUND {
+            Number::NEG_INFINITY
+        } else if bound >= INFINITE_BOUND {
+            Number::INFINITY
+        } else {
+            self.inverse(bound)
+        }
+    }
+
+    /// Move `x` into the open range of `φ` if it is at or beyond one of its ends, so that its
+    /// inverse is finite.
+    ///
+    /// Like Ipopt's `bound_push`, the margin is relative to the magnitude of the end it is
+    /// pushed away from and at most a fraction of the length of a bounded interval.
+    fn interior(self, x: Number) -> Number {
+        let push = |end: Number, length: Number| {
+            (BOUND_PUSH * end.abs().max(1.0)).min(BOUND_FRAC * length)
+        };
+        match self {
+            Transform::Identity => x,
+            Transform::Exp if x <= 0.0 => BOUND_PUSH,
+            Transform::Exp => x,
+            Transform::Logit { lower, upper } => {
+                if x <= lower {
+                    lower + push(lower, upper - lower)
+                } else if x >= upper {
+                    upper - push(upper, upper - lower)
+                } else {
+                    x
+                }
+            }
+        }
+    }
+}
+
+fn sigmoid(y: Number) -> Number {
+    1.0 / (1.0 + (-y).exp())
+}
+
+/// Hessian structure of a transformed problem.
+struct HessianLayout {
+    /// Number of entries of the wrapped problem.
+    num_original: usize,
+    /// Zero-based rows and columns of all entries, including appended diagonal entries.
+    indices: Vec<(usize, usize)>,
+    /// Entry receiving the second derivative term of each variable, if it has one.
+    diagonal: Vec<Option<usize>>,
+}
+
+impl HessianLayout {
+    fn new(transforms: &[Transform], rows: &[Index], cols: &[Index], style: IndexingStyle) -> Self {
+        let offset = style.offset();
+        let mut indices: Vec<_> = rows
+            .iter()
+            .zip(cols)
+            .map(|(&r, &c)| ((r - offset) as usize, (c - offset) as usize))
+            .collect();
+        let num_original = indices.len();
+        let mut diagonal = vec![None; transforms.len()];
+        for (k, &(r, c)) in indices.iter().enumerate() {
+            if r == c && diagonal[r].is_none() {
+                diagonal[r] = Some(k);
+            }
+        }
+        for (i, t) in transforms.iter().enumerate() {
+            if *t == Transform::Identity {
+                diagonal[i] = None;
+            } else if diagonal[i].is_none() {
+                diagonal[i] = Some(indices.len());
+                indices.push((i, i));
+            }
+        }
+        HessianLayout {
+            num_original,
+            indices,
+            diagonal,
+        }
+    }
+}
+
+/// A problem with elementwise transformed variables `x_i = φ_i(y_i)`.
+pub struct Transformed<P> {
+    problem: P,
+    transforms: Vec<Transform>,
+    /// Scratch buffer for the original variables.
+    x: RefCell<Vec<Number>>,
+    /// Scratch buffer for the gradient of the Lagrangian with respect to `x`.
+    lagrangian_grad: RefCell<Vec<Number>>,
+    /// Scratch buffer for constraint Jacobian values.
+    jacobian_vals: RefCell<Vec<Number>>,
+    /// Zero-based rows and columns of the constraint Jacobian entries.
+    jacobian_indices: RefCell<Vec<(usize, usize)>>,
+    /// Hessian structure, determined on first use.
+    hessian: RefCell<Option<HessianLayout>>,
+}
+
+impl<P: BasicProblem> Transformed<P> {
+    /// Wrap a problem with one transform per variable.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the number of transforms differs from the number of variables,
+    /// or if a [`Logit`](enum.Transform.html#variant.Logit) interval is empty.
+    pub fn new(problem: P, transforms: Vec<Transform>) -> Self {
+        assert_eq!(
+            transforms.len(),
+            problem.num_variables(),
+            "one transform per variable is required"
+        );
+        assert!(
+            transforms.iter().all(|t| match *t {
+                Transform::Logit { lower, upper } => lower < upper,
+                _ => true,
+            }),
+            "logit intervals must not be empty"
+        );
+        Transformed {
+            problem,
+            transforms,
+            x: RefCell::new(Vec::new()),
+            lagrangian_grad: RefCell::new(Vec::new()),
+            jacobian_vals: RefCell::new(Vec::new()),
+            jacobian_indices: RefCell::new(Vec::new()),
+            hessian: RefCell::new(None),
+        }
+    }
+
+    /// The wrapped problem.
+    pub fn problem(&self) -> &P {
+        &self.problem
+    }
+
+    /// The wrapped problem.
+    pub fn problem_mut(&mut self) -> &mut P {
+        &mut self.problem
+    }
+
+    /// Unwrap the original problem.
+    pub fn into_inner(self) -> P {
+        self.problem
+    }
+
+    /// Map transformed variables `y` to the original variables `x`.
+    pub fn to_original(&self, y: &[Number], x: &mut [Number]) {
+        for ((x, &y), t) in x.iter_mut().zip(y).zip(&self.transforms) {
+            *x = t.forward(y);
+        }
+    }
+
+    /// Map original variables `x` to the transformed variables `y`.
+    pub fn to_transformed(&self, x: &[Number], y: &mut [Number]) {
+        for ((y, &x), t) in y.iter_mut().zip(x).zip(&self.transforms) {
+            *y = t.inverse(x);
+        }
+    }
+
+    /// Evaluate `f` at the original variables corresponding to `y`.
+    fn at_original<R>(&self, y: &[Number], f: impl FnOnce(&[Number]) -> R) -> R {
+        let mut x = self.x.borrow_mut();
+        x.resize(y.len(), 0.0);
+        self.to_original(y, &mut x);
+        f(&x)
+    }
+
+    /// Multiply each entry of a vector by the derivative of the transform of its variable.
+    fn scale_by_derivative(&self, y: &[Number], v: &mut [Number]) {
+        for ((v, &y), t) in v.iter_mut().zip(y).zip(&self.transforms) {
+            *v *= t.derivative(y);
+        }
+    }
+
+    /// Remember the zero-based constraint Jacobian indices of the wrapped problem.
+    fn store_jacobian_indices(&self, rows: &[Index], cols: &[Index]) {
+        let offset = self.problem.indexing_style().offset();
+        let mut indices = self.jacobian_indices.borrow_mut();
+        indices.clear();
+        indices.extend(
+            rows.iter()
+                .zip(cols.iter())
+                .map(|(&r, &c)| ((r - offset) as usize, (c - offset) as usize)),
+        );
+    }
+
+    /// Query the constraint Jacobian indices of the wrapped problem if they are not stored yet,
+    /// so values requested before the structure are transformed too.
+    fn jacobian_layout(&self) -> bool
+    where
+        P: ConstrainedProblem,
+    {
+        let nnz = self.problem.num_constraint_jacobian_non_zeros();
+        if self.jacobian_indices.borrow().len() == nnz {
+            return true;
+        }
+        let (mut rows, mut cols) = (vec![0; nnz], vec![0; nnz]);
+        if !self
+            .problem
+            .constraint_jacobian_indices(&mut rows, &mut cols)
+        {
+            return false;
+        }
+        self.store_jacobian_indices(&rows, &cols);
+        true
+    }
+
+    /// Determine the Hessian structure from the indices of the wrapped problem.
+    fn hessian_layout(
+        &self,
+        nnz: usize,
+        indices: impl FnOnce(&mut [Index], &mut [Index]) -> bool,
+    ) -> bool {
+        if self.hessian.borrow().is_some() {
+            return true;
+        }
+        let mut rows = vec![0; nnz];
+        let mut cols = vec![0; nnz];
+        if !indices(&mut rows, &mut cols) {
+            return false;
+        }
+        let layout = HessianLayout::new(
+            &self.transforms,
+            &rows,
+            &cols,
+            self.problem.indexing_style(),
+        );
+        *self.hessian.borrow_mut() = Some(layout);
+        true
+    }
+
+    /// Number of Hessian entries including appended diagonal entries.
+    fn hessian_non_zeros(
+        &self,
+        nnz: usize,
+        indices: impl FnOnce(&mut [Index], &mut [Index]) -> bool,
+    ) -> usize {
+        if self.hessian_layout(nnz, indices) {
+            self.hessian.borrow().as_ref().unwrap().indices.len()
+        } else {
+            nnz
+        }
+    }
+
+    /// Write the Hessian indices including appended diagonal entries.
+    fn write_hessian_indices(
+        &self,
+        nnz: usize,
+        indices: impl FnOnce(&mut [Index], &mut [Index]) -> bool,
+        rows: &mut [Index],
+        cols: &mut [Index],
+    ) -> bool {
+        if !self.hessian_layout(nnz, indices) {
+            return false;
+        }
+        let offset = self.problem.indexing_style().offset();
+        let layout = self.hessian.borrow();
+        for ((r, c), &(i, j)) in rows
+            .iter_mut()
+            .zip(cols.iter_mut())
+            .zip(&layout.as_ref().unwrap().indices)
+        {
+            *r = i as Index + offset;
+            *c = j as Index + offset;
+        }
+        true
+    }
+
+    /// Transform the Hessian values of the wrapped problem, stored in the leading entries of
+    /// `vals`, given the gradient of the Lagrangian with respect to `x`.
+    fn transform_hessian(&self, y: &[Number], vals: &mut [Number], lagrangian_grad: &[Number]) {
+        let layout = self.hessian.borrow();
+        let layout = layout.as_ref().unwrap();
+        for v in vals[layout.num_original..].iter_mut() {
+            *v = 0.0;
+        }
+        for (v, &(r, c)) in vals.iter_mut().zip(&layout.indices) {
+            *v *= self.transforms[r].derivative(y[r]) * self.transforms[c].derivative(y[c]);
+        }
+        for (i, k) in layout.diagonal.iter().enumerate() {
+            if let Some(k) = *k {
+                vals[k] += self.transforms[i].second_derivative(y[i]) * lagrangian_grad[i];
+            }
+        }
+    }
+
+    /// Number of Hessian entries of the wrapped problem.
+    fn num_original_hessian_non_zeros(&self) -> usize {
+        match *self.hessian.borrow() {
+            Some(ref layout) => layout.num_original,
+            None => 0,
+        }
+    }
+}
+
+impl<P: BasicProblem> BasicProblem for Transformed<P> {
+    fn indexing_style(&self) -> IndexingStyle {
+        self.problem.indexing_style()
+    }
+    fn num_variables(&self) -> usize {
+        self.problem.num_variables()
+    }
+    fn bounds(&self, y_l: &mut [Number], y_u: &mut [Number]) -> bool {
+        if !self.problem.bounds(y_l, y_u) {
+            return false;
+        }
+        for ((l, u), t) in y_l.iter_mut().zip(y_u.iter_mut()).zip(&self.transforms) {
+            if *t != Transform::Identity {
+                *l = t.inverse_bound(*l);
+                *u = t.inverse_bound(*u);
+            }
+        }
+        true
+    }
+    fn initial_point(&self, y: &mut [Number]) -> bool {
+        let mut x = self.x.borrow_mut();
+        x.resize(y.len(), 0.0);
+        let init = self.problem.initial_point(&mut x);
+        if init {
+            for ((y, &x), t) in y.iter_mut().zip(x.iter()).zip(&self.transforms) {
+                *y = t.inverse(t.interior(x));
+            }
+        }
+        init
+    }
+    fn initial_bounds_multipliers(&self, z_l: &mut [Number], z_u: &mut [Number]) -> bool {
+        if !self.problem.initial_bounds_multipliers(z_l, z_u) {
+            return false;
+        }
+        // Bound multipliers scale like the gradient at the initial point.
+        let mut y = vec![0.0; z_l.len()];
+        if self.initial_point(&mut y) {
+            self.scale_by_derivative(&y, z_l);
+            self.scale_by_derivative(&y, z_u);
+        }
+        true
+    }
+    fn objective(&self, y: &[Number], obj: &mut Number) -> bool {
+        self.at_original(y, |x| self.problem.objective(x, obj))
+    }
+    fn objective_grad(&self, y: &[Number], grad_f: &mut [Number]) -> bool {
+        if !self.at_original(y, |x| self.problem.objective_grad(x, grad_f)) {
+            return false;
+        }
+        self.scale_by_derivative(y, grad_f);
+        true
+    }
+    fn objective_scaling(&self) -> f64 {
+        self.problem.objective_scaling()
+    }
+    fn algorithm_mode_changed(&mut self, mode: AlgorithmMode) {
+        self.problem.algorithm_mode_changed(mode)
+    }
+}
+
+impl<P: NewtonProblem> NewtonProblem for Transformed<P> {
+    fn num_hessian_non_zeros(&self) -> usize {
+        self.hessian_non_zeros(self.problem.num_hessian_non_zeros(), |rows, cols| {
+            self.problem.hessian_indices(rows, cols)
+        })
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        self.write_hessian_indices(
+            self.problem.num_hessian_non_zeros(),
+            |rows, cols| self.problem.hessian_indices(rows, cols),
+            rows,
+            cols,
+        )
+    }
+    fn hessian_values(&self, y: &[Number], vals: &mut [Number]) -> bool {
+        let layout = self.hessian_layout(self.problem.num_hessian_non_zeros(), |rows, cols| {
+            self.problem.hessian_indices(rows, cols)
+        });
+        if !layout {
+            return false;
+        }
+        let nnz = self.num_original_hessian_non_zeros();
+        let mut grad = self.lagrangian_grad.borrow_mut();
+        grad.resize(y.len(), 0.0);
+        let ok = self.at_original(y, |x| {
+            self.problem.hessian_values(x, &mut vals[..nnz])
+                && self.problem.objective_grad(x, &mut grad)
+        });
+        if !ok {
+            return false;
+        }
+        self.transform_hessian(y, vals, &grad);
+        true
+    }
+}
+
+impl<P: ConstrainedProblem> ConstrainedProblem for Transformed<P> {
+    fn num_constraints(&self) -> usize {
+        self.problem.num_constraints()
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        self.problem.num_constraint_jacobian_non_zeros()
+    }
+    fn constraint(&self, y: &[Number], g: &mut [Number]) -> bool {
+        self.at_original(y, |x| self.problem.constraint(x, g))
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        self.problem.constraint_bounds(g_l, g_u)
+    }
+    fn initial_constraint_multipliers(&self, lambda: &mut [Number]) -> bool {
+        self.problem.initial_constraint_multipliers(lambda)
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        if !self.problem.constraint_jacobian_indices(rows, cols) {
+            return false;
+        }
+        self.store_jacobian_indices(rows, cols);
+        true
+    }
+    fn constraint_jacobian_values(&self, y: &[Number], vals: &mut [Number]) -> bool {
+        if !self.jacobian_layout()
+            || !self.at_original(y, |x| self.problem.constraint_jacobian_values(x, vals))
+        {
+            return false;
+        }
+        for (v, &(_, c)) in vals.iter_mut().zip(self.jacobian_indices.borrow().iter()) {
+            *v *= self.transforms[c].derivative(y[c]);
+        }
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        self.hessian_non_zeros(
+            ConstrainedProblem::num_hessian_non_zeros(&self.problem),
+            |rows, cols| ConstrainedProblem::hessian_indices(&self.problem, rows, cols),
+        )
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        self.write_hessian_indices(
+            ConstrainedProblem::num_hessian_non_zeros(&self.problem),
+            |rows, cols| ConstrainedProblem::hessian_indices(&self.problem, rows, cols),
+            rows,
+            cols,
+        )
+    }
+    fn hessian_values(
+        &self,
+        y: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        let layout = self.jacobian_layout()
+            && self.hessian_layout(
+                ConstrainedProblem::num_hessian_non_zeros(&self.problem),
+                |rows, cols| ConstrainedProblem::hessian_indices(&self.problem, rows, cols),
+            );
+        if !layout {
+            return false;
+        }
+        let nnz = self.num_original_hessian_non_zeros();
+        let mut grad = self.lagrangian_grad.borrow_mut();
+        let mut jac = self.jacobian_vals.borrow_mut();
+        grad.resize(y.len(), 0.0);
+        jac.resize(self.jacobian_indices.borrow().len(), 0.0);
+        let ok = self.at_original(y, |x| {
+            ConstrainedProblem::hessian_values(
+                &self.problem,
+                x,
+                obj_factor,
+                lambda,
+                &mut vals[..nnz],
+            ) && self.problem.objective_grad(x, &mut grad)
+                && self.problem.constraint_jacobian_values(x, &mut jac)
+        });
+        if !ok {
+            return false;
+        }
+
+        // Gradient of the Lagrangian with respect to x.
+        for g in grad.iter_mut() {
+            *g *= obj_factor;
+        }
+        for (&v, &(r, c)) in jac.iter().zip(self.jacobian_indices.borrow().iter()) {
+            grad[c] += lambda[r] * v;
+        }
+        self.transform_hessian(y, vals, &grad);
+        true
+    }
+    fn constraint_scaling(&self, g_scaling: &mut [Number]) -> bool {
+        self.problem.constraint_scaling(g_scaling)
+    }
+    fn constraint_groups(&self) -> ConstraintGroups {
+        self.problem.constraint_groups()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimize `x0^2 x1 + x1` subject to `x0 x1 >= 1`, with `x0 > 0` and `x1` in `(0, 4)`.
+    struct Model;
+
+    impl BasicProblem for Model {
+        fn num_variables(&self) -> usize {
+            2
+        }
+        fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+            x_l.copy_from_slice(&[0.0, 0.0]);
+            x_u.copy_from_slice(&[1e20, 4.0]);
+            true
+        }
+        fn initial_point(&self, x: &mut [Number]) -> bool {
+            x.copy_from_slice(&[1.0, 2.0]);
+            true
+        }
+        fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+            *obj = x[0] * x[0] * x[1] + x[1];
+            true
+        }
+        fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+            grad_f.copy_from_slice(&[2.0 * x[0] * x[1], x[0] * x[0] + 1.0]);
+            true
+        }
+    }
+
+    impl ConstrainedProblem for Model {
+        fn num_constraints(&self) -> usize {
+            1
+        }
+        fn num_constraint_jacobian_non_zeros(&self) -> usize {
+            2
+        }
+        fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+            g[0] = x[0] * x[1];
+            true
+        }
+        fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+            g_l[0] = 1.0;
+            g_u[0] = 1e20;
+            true
+        }
+        fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+            rows.copy_from_slice(&[0, 0]);
+            cols.copy_from_slice(&[0, 1]);
+            true
+        }
+        fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+            vals.copy_from_slice(&[x[1], x[0]]);
+            true
+        }
+        // No diagonal entry for x1, so one is appended.
+        fn num_hessian_non_zeros(&self) -> usize {
+            2
+        }
+        fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+            rows.copy_from_slice(&[0, 1]);
+            cols.copy_from_slice(&[0, 0]);
+            true
+        }
+        fn hessian_values(
+            &self,
+            x: &[Number],
+            obj_factor: Number,
+            lambda: &[Number],
+            vals: &mut [Number],
+        ) -> bool {
+            vals[0] = obj_factor * 2.0 * x[1];
+            vals[1] = obj_factor * 2.0 * x[0] + lambda[0];
+            true
+        }
+    }
+
+    /// A problem starting on the ends of the ranges of its transforms.
+    struct OnBounds;
+
+    impl BasicProblem for OnBounds {
+        fn num_variables(&self) -> usize {
+            3
+        }
+        fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+            x_l.copy_from_slice(&[0.0, -2.0, -2.0]);
+            x_u.copy_from_slice(&[1e20, 2.0, 2.0]);
+            true
+        }
+        fn initial_point(&self, x: &mut [Number]) -> bool {
+            x.copy_from_slice(&[0.0, -2.0, 3.0]);
+            true
+        }
+        fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+            *obj = x.iter().sum();
+            true
+        }
+        fn objective_grad(&self, _x: &[Number], grad_f: &mut [Number]) -> bool {
+            grad_f.copy_from_slice(&[1.0; 3]);
+            true
+        }
+    }
+
+    /// Central finite difference of `f` at `y` in direction `i`.
+    fn fd(f: impl Fn(&[Number]) -> Number, y: &[Number], i: usize) -> Number {
+        let h = 1e-6;
+        let (mut yp, mut ym) = (y.to_vec(), y.to_vec());
+        yp[i] += h;
+        ym[i] -= h;
+        (f(&yp) - f(&ym)) / (2.0 * h)
+    }
+
+    #[test]
+    fn transformed_derivatives_test() {
+        let p = Transformed::new(
+            Model,
+            vec![
+                Transform::Exp,
+                Transform::Logit {
+                    lower: 0.0,
+                    upper: 4.0,
+                },
+            ],
+        );
+
+        let (mut y_l, mut y_u) = ([0.0; 2], [0.0; 2]);
+        assert!(p.bounds(&mut y_l, &mut y_u));
+        assert_eq!(y_l, [Number::NEG_INFINITY; 2]);
+        assert_eq!(y_u, [Number::INFINITY; 2]);
+
+        let mut y = [0.0; 2];
+        assert!(p.initial_point(&mut y));
+        assert_eq!(y, [0.0, 0.0]);
+        let y = [0.3, -0.2];
+
+        // Derivatives of the Lagrangian with objective factor 2 and multiplier 0.5.
+        let lagrangian = |y: &[Number]| {
+            let (mut f, mut g) = (0.0, [0.0]);
+            p.objective(y, &mut f);
+            p.constraint(y, &mut g);
+            2.0 * f + 0.5 * g[0]
+        };
+        let gradient = |y: &[Number], i: usize| {
+            let (mut grad, mut jac) = ([0.0; 2], [0.0; 2]);
+            p.objective_grad(y, &mut grad);
+            p.constraint_jacobian_values(y, &mut jac);
+            2.0 * grad[i] + 0.5 * jac[i]
+        };
+
+        let (mut rows, mut cols) = ([0; 2], [0; 2]);
+        assert!(p.constraint_jacobian_indices(&mut rows, &mut cols));
+        for i in 0..2 {
+            assert!((gradient(&y, i) - fd(lagrangian, &y, i)).abs() < 1e-6);
+        }
+
+        assert_eq!(ConstrainedProblem::num_hessian_non_zeros(&p), 3);
+        let (mut rows, mut cols) = ([0; 3], [0; 3]);
+        assert!(ConstrainedProblem::hessian_indices(
+            &p, &mut rows, &mut cols
+        ));
+        assert_eq!((rows, cols), ([0, 1, 1], [0, 0, 1]));
+        let mut hess = [0.0; 3];
+        assert!(ConstrainedProblem::hessian_values(
+            &p,
+            &y,
+            2.0,
+            &[0.5],
+            &mut hess
+        ));
+        for (k, (&r, &c)) in rows.iter().zip(cols.iter()).enumerate() {
+            let expected = fd(|y| gradient(y, r as usize), &y, c as usize);
+            assert!(
+                (hess[k] - expected).abs() < 1e-5,
+                "{} {}",
+                hess[k],
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn initial_point_on_bounds_test() {
+        let logit = Transform::Logit {
+            lower: -2.0,
+            upper: 2.0,
+        };
+        let p = Transformed::new(OnBounds, vec![Transform::Exp, logit, logit]);
+
+        let mut y = [0.0; 3];
+        assert!(p.initial_point(&mut y));
+        assert!(y.iter().all(|y| y.is_finite()), "{:?}", y);
+
+        let mut x = [0.0; 3];
+        p.to_original(&y, &mut x);
+        let expected = [0.01, -1.98, 1.98];
+        for (&x, &expected) in x.iter().zip(expected.iter()) {
+            assert!((x - expected).abs() < 1e-12, "{} {}", x, expected);
+        }
+    }
+
+    #[test]
+    fn values_before_structure_test() {
+        let transforms = vec![
+            Transform::Exp,
+            Transform::Logit {
+                lower: 0.0,
+                upper: 4.0,
+            },
+        ];
+        let y = [0.3, -0.2];
+        let values = |p: &Transformed<Model>| {
+            let (mut jac, mut hess) = ([0.0; 2], [0.0; 3]);
+            assert!(p.constraint_jacobian_values(&y, &mut jac));
+            assert!(ConstrainedProblem::hessian_values(
+                p,
+                &y,
+                2.0,
+                &[0.5],
+                &mut hess
+            ));
+            (jac, hess)
+        };
+
+        let p = Transformed::new(Model, transforms.clone());
+        let (mut rows, mut cols) = ([0; 3], [0; 3]);
+        assert!(p.constraint_jacobian_indices(&mut rows[..2], &mut cols[..2]));
+        assert!(ConstrainedProblem::hessian_indices(
+            &p, &mut rows, &mut cols
+        ));
+        let expected = values(&p);
+
+        assert_eq!(values(&Transformed::new(Model, transforms)), expected);
+    }
+}
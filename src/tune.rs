@@ -0,0 +1,417 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Automatic search for good Ipopt options.
+//!
+//! A [`Tuner`](struct.Tuner.html) evaluates configurations drawn from an
+//! [`OptionSpace`](struct.OptionSpace.html) on a set of representative problems using the
+//! [benchmark harness](struct.Benchmark.html). Configurations are either enumerated on a grid or
+//! sampled at random. The result contains the Pareto front of configurations trading off speed
+//! (total time over all solves) against robustness (number of problems solved): no configuration
+//! on the front is both faster and more robust than another.
+
+use crate::bench::config_label;
+use crate::{
+    BenchError, BenchReport, Benchmark, ConstrainedProblem, Number, OptionSet, OptionValue,
+};
+
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+/// The set of values an option can take during tuning.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OptionRange {
+    /// An explicit list of values.
+    Values(Vec<OptionValue>),
+    /// Numbers between `min` and `max` (inclusive) on a logarithmic scale.
+    ///
+    /// Grid search uses `steps` geometrically spaced points. Random search samples uniformly in
+    /// log space. Both `min` and `max` must be positive.
+    LogRange {
+        /// Smallest value.
+        min: Number,
+        /// Largest value.
+        max: Number,
+        /// Number of grid points.
+        steps: usize,
+    },
+    /// All integers between `min` and `max` (inclusive).
+    IntRange {
+        /// Smallest value.
+        min: i32,
+        /// Largest value.
+        max: i32,
+    },
+}
+
+impl OptionRange {
+    /// All values used by grid search.
+    fn grid(&self) -> Vec<OptionValue> {
+        match *self {
+            OptionRange::Values(ref values) => values.clone(),
+            OptionRange::LogRange { min, max, steps } => {
+                if steps == 1 {
+                    return vec![OptionValue::Num(min)];
+                }
+                let (lo, hi) = (min.ln(), max.ln());
+                (0..steps)
+                    .map(|i| {
+                        let t = i as Number / (steps - 1) as Number;
+                        OptionValue::Num((lo + t * (hi - lo)).exp())
+                    })
+                    .collect()
+            }
+            OptionRange::IntRange { min, max } => (min..=max).map(OptionValue::Int).collect(),
+        }
+    }
+
+    /// Draw a random value, or `None` if the range is empty.
+    fn sample(&self, rng: &mut Rng) -> Option<OptionValue> {
+        match *self {
+            OptionRange::Values(ref values) => {
+                if values.is_empty() {
+                    None
+                } else {
+                    Some(values[rng.below(values.len() as u64) as usize].clone())
+                }
+            }
+            OptionRange::LogRange { min, max, .. } => {
+                let (lo, hi) = (min.ln(), max.ln());
+                Some(OptionValue::Num((lo + rng.uniform() * (hi - lo)).exp()))
+            }
+            OptionRange::IntRange { min, max } => {
+                if max < min {
+                    None
+                } else {
+                    let n = (max as i64 - min as i64 + 1) as u64;
+                    Some(OptionValue::Int((min as i64 + rng.below(n) as i64) as i32))
+                }
+            }
+        }
+    }
+}
+
+/// A space of Ipopt options to search over.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OptionSpace {
+    axes: Vec<(String, OptionRange)>,
+}
+
+impl OptionSpace {
+    /// Construct an empty option space.
+    pub fn new() -> Self {
+        OptionSpace::default()
+    }
+
+    /// Add an option with the given range of values.
+    pub fn axis(&mut self, name: &str, range: OptionRange) -> &mut Self {
+        self.axes.push((name.to_string(), range));
+        self
+    }
+
+    /// Enumerate all configurations on the grid given by this space.
+    ///
+    /// The last axis varies fastest.
+    pub fn grid(&self) -> Vec<OptionSet> {
+        let mut configs = vec![OptionSet::new()];
+        for (name, range) in self.axes.iter() {
+            let values = range.grid();
+            configs = configs
+                .iter()
+                .flat_map(|config| {
+                    values.iter().map(move |value| {
+                        let mut config = config.clone();
+                        config.set(name, value.clone());
+                        config
+                    })
+                })
+                .collect();
+        }
+        configs
+    }
+
+    /// Draw `samples` random configurations from this space.
+    ///
+    /// The same `seed` always produces the same configurations. Options with an empty range are
+    /// left unset.
+    pub fn sample(&self, samples: usize, seed: u64) -> Vec<OptionSet> {
+        let mut rng = Rng(seed);
+        (0..samples)
+            .map(|_| {
+                let mut config = OptionSet::new();
+                for (name, range) in self.axes.iter() {
+                    if let Some(value) = range.sample(&mut rng) {
+                        config.set(name, value);
+                    }
+                }
+                config
+            })
+            .collect()
+    }
+}
+
+/// A small deterministic pseudo-random number generator (SplitMix64).
+pub(crate) struct Rng(pub(crate) u64);
+
+impl Rng {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A uniform number in `[0, 1)`.
+    pub(crate) fn uniform(&mut self) -> Number {
+        (self.next() >> 11) as Number / (1u64 << 53) as Number
+    }
+
+    /// A uniform integer in `[0, n)`.
+    pub(crate) fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+/// How configurations are drawn from the option space.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SearchStrategy {
+    /// Evaluate every configuration on the grid.
+    Grid,
+    /// Evaluate a number of randomly sampled configurations.
+    Random {
+        /// Number of configurations to evaluate.
+        samples: usize,
+        /// Seed of the random number generator.
+        seed: u64,
+    },
+}
+
+/// Searches an option space for configurations that solve a set of problems quickly and reliably.
+#[derive(Clone, Debug)]
+pub struct Tuner<P> {
+    bench: Benchmark<P>,
+    space: OptionSpace,
+    strategy: SearchStrategy,
+}
+
+impl<P: ConstrainedProblem + Clone> Tuner<P> {
+    /// Construct a tuner searching the given space.
+    pub fn new(space: OptionSpace, strategy: SearchStrategy) -> Self {
+        Tuner {
+            bench: Benchmark::new(),
+            space,
+            strategy,
+        }
+    }
+
+    /// Set options applied before each configuration, for instance to suppress output.
+    pub fn base_options(&mut self, options: OptionSet) -> &mut Self {
+        self.bench.base_options(options);
+        self
+    }
+
+    /// Add a named representative problem.
+    pub fn problem(&mut self, name: &str, problem: P) -> &mut Self {
+        self.bench.problem(name, problem);
+        self
+    }
+
+    /// Evaluate all configurations and compute the Pareto front.
+    pub fn run(&self) -> Result<TuneResult, BenchError> {
+        let configs = match self.strategy {
+            SearchStrategy::Grid => self.space.grid(),
+            SearchStrategy::Random { samples, seed } => self.space.sample(samples, seed),
+        };
+        let report = self.bench.clone().configurations(configs).run()?;
+        Ok(TuneResult::new(report))
+    }
+}
+
+/// Speed and robustness of a single configuration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TunePoint {
+    /// Index of the configuration in the benchmark report.
+    pub config: usize,
+    /// Number of problems solved to the desired or acceptable tolerance.
+    pub solved: usize,
+    /// Number of problems attempted.
+    pub attempted: usize,
+    /// Total time over all solves, including failed ones.
+    pub time: Duration,
+}
+
+impl TunePoint {
+    /// Returns `true` if this configuration is at least as good as `other` in both speed and
+    /// robustness, and strictly better in one.
+    pub fn dominates(&self, other: &TunePoint) -> bool {
+        self.solved >= other.solved
+            && self.time <= other.time
+            && (self.solved > other.solved || self.time < other.time)
+    }
+}
+
+/// The outcome of a tuning run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TuneResult {
+    /// Results of all evaluated configurations.
+    pub report: BenchReport,
+    /// Speed and robustness of every evaluated configuration.
+    pub points: Vec<TunePoint>,
+    /// Non-dominated configurations, sorted from most to least robust.
+    pub pareto_front: Vec<TunePoint>,
+}
+
+impl TuneResult {
+    /// Compute the Pareto front of the configurations in the given report.
+    pub fn new(report: BenchReport) -> Self {
+        let mut points: Vec<_> = (0..report.configs.len())
+            .map(|config| TunePoint {
+                config,
+                solved: 0,
+                attempted: 0,
+                time: Duration::default(),
+            })
+            .collect();
+        for run in report.runs.iter() {
+            let p = &mut points[run.config];
+            p.attempted += 1;
+            p.time += run.time;
+            if run.succeeded() {
+                p.solved += 1;
+            }
+        }
+
+        let mut pareto_front: Vec<_> = points
+            .iter()
+            .filter(|p| p.solved > 0 && !points.iter().any(|q| q.dominates(p)))
+            .cloned()
+            .collect();
+        pareto_front.sort_by(|a, b| b.solved.cmp(&a.solved).then(a.time.cmp(&b.time)));
+
+        TuneResult {
+            report,
+            points,
+            pareto_front,
+        }
+    }
+
+    /// Options of the configuration at the given point.
+    pub fn options(&self, point: &TunePoint) -> &OptionSet {
+        &self.report.configs[point.config]
+    }
+}
+
+/// Print the Pareto front as a table, most robust configuration first.
+impl Display for TuneResult {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(f, "{:>8} {:>12}  configuration", "solved", "time (s)")?;
+        for p in self.pareto_front.iter() {
+            writeln!(
+                f,
+                "{:>8} {:>12.4}  {}",
+                format!("{}/{}", p.solved, p.attempted),
+                p.time.as_secs_f64(),
+                config_label(self.options(p))
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BenchRun, SolveStatus};
+
+    fn space() -> OptionSpace {
+        let mut space = OptionSpace::new();
+        space
+            .axis(
+                "mu_strategy",
+                OptionRange::Values(vec!["monotone".into(), "adaptive".into()]),
+            )
+            .axis(
+                "tol",
+                OptionRange::LogRange {
+                    min: 1e-10,
+                    max: 1e-6,
+                    steps: 3,
+                },
+            )
+            .axis("max_soc", OptionRange::IntRange { min: 2, max: 4 });
+        space
+    }
+
+    #[test]
+    fn grid_test() {
+        let configs = space().grid();
+        assert_eq!(configs.len(), 18);
+        match configs[3].get("tol") {
+            Some(&OptionValue::Num(tol)) => assert!((tol / 1e-8 - 1.0).abs() < 1e-12),
+            _ => panic!("expected a numeric tolerance"),
+        }
+        assert_eq!(configs[17].get("max_soc"), Some(&OptionValue::Int(4)));
+    }
+
+    #[test]
+    fn sample_test() {
+        let configs = space().sample(50, 7);
+        assert_eq!(configs, space().sample(50, 7));
+        for config in configs.iter() {
+            match config.get("tol") {
+                Some(&OptionValue::Num(tol)) => assert!((1e-10..=1e-6).contains(&tol)),
+                _ => panic!("expected a numeric tolerance"),
+            }
+            match config.get("max_soc") {
+                Some(&OptionValue::Int(n)) => assert!((2..=4).contains(&n)),
+                _ => panic!("expected an integer option"),
+            }
+        }
+    }
+
+    #[test]
+    fn pareto_front_test() {
+        let run = |config, status, millis| BenchRun {
+            problem: String::from("p"),
+            config,
+            status,
+            objective_value: 0.0,
+            iterations: None,
+            time: Duration::from_millis(millis),
+        };
+        let ok = SolveStatus::SolveSucceeded;
+        let fail = SolveStatus::MaximumIterationsExceeded;
+        let report = BenchReport {
+            configs: vec![OptionSet::new(); 4],
+            runs: vec![
+                // Robust but slow.
+                run(0, ok, 50),
+                run(0, ok, 50),
+                // Fast but fragile.
+                run(1, ok, 10),
+                run(1, fail, 10),
+                // Dominated by 0.
+                run(2, ok, 60),
+                run(2, ok, 60),
+                // Solves nothing.
+                run(3, fail, 1),
+                run(3, fail, 1),
+            ],
+        };
+        let result = TuneResult::new(report);
+        let front: Vec<_> = result.pareto_front.iter().map(|p| p.config).collect();
+        assert_eq!(front, vec![0, 1]);
+    }
+}
@@ -0,0 +1,113 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Stopping a solve early with a reason.
+//!
+//! An [`IntermediateCallback`](type.IntermediateCallback.html) can only tell Ipopt to stop, which
+//! ends the solve with
+//! [`SolveStatus::UserRequestedStop`](enum.SolveStatus.html#variant.UserRequestedStop) no matter
+//! why the solve was stopped. A [`StopCallback`](type.StopCallback.html) installed with
+//! [`Ipopt::set_stop_callback`](struct.Ipopt.html#method.set_stop_callback) returns a
+//! [`StopReason`](enum.StopReason.html) instead, which is reported in the
+//! [`user_stop`](struct.SolveResult.html#structfield.user_stop) field of the result along with the
+//! iterate at which the solve was stopped.
+
+use crate::{BasicProblem, Index, IntermediateCallbackData, Ipopt, Number, SolveStatus};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Why a solve was stopped by the user.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StopReason {
+    /// A custom convergence criterion was met, so the iterate is an accepted solution.
+    Converged,
+    /// The solve ran out of its time budget.
+    Timeout,
+    /// The solve was aborted from outside, for instance by a cancelled request.
+    Aborted,
+    /// The solve was interrupted by Ctrl-C, see
+    /// [`Ipopt::set_interrupt_on_ctrl_c`](struct.Ipopt.html#method.set_interrupt_on_ctrl_c).
+    Interrupted,
+    /// Any other reason.
+    Other(String),
+    /// The [`IntermediateCallback`](type.IntermediateCallback.html) returned `false`, which does
+    /// not give a reason.
+    Unspecified,
+}
+
+/// A solve stopped through an intermediate or stop callback.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UserStop {
+    /// Why the solve was stopped.
+    pub reason: StopReason,
+    /// The iteration in which the solve was stopped.
+    pub iteration: Index,
+    /// The primal iterate at which the solve was stopped. This is the iterate Ipopt accepted in
+    /// that iteration, and the same as the primal variables of the solution.
+    pub best_iterate: Vec<Number>,
+}
+
+/// Type defining a callback function that can stop the solve with a reason.
+///
+/// If set, it is called once per iteration after the
+/// [`IntermediateCallback`](type.IntermediateCallback.html). Returning `Some` stops the solve.
+pub type StopCallback<P> = fn(&mut P, IntermediateCallbackData) -> Option<StopReason>;
+
+impl<P: BasicProblem> Ipopt<P> {
+    /// Set a callback that can stop the solve with a reason.
+    pub fn set_stop_callback(&mut self, stop_callback: Option<StopCallback<P>>) -> &mut Self {
+        self.stop_callback = stop_callback;
+        self.update_intermediate_callback();
+        self
+    }
+
+    /// Run the intermediate and stop callbacks, returning whether the solve continues.
+    pub(crate) fn run_intermediate_callbacks(&mut self, data: IntermediateCallbackData) -> bool {
+        let proceed = match self.intermediate_callback {
+            Some(callback) => callback(&mut self.nlp_interface, data),
+            None => true,
+        };
+        let reason = if proceed {
+            match self.stop_callback {
+                Some(callback) => callback(&mut self.nlp_interface, data),
+                None => None,
+            }
+        } else {
+            Some(StopReason::Unspecified)
+        };
+        match reason {
+            Some(reason) => {
+                self.stop = Some((reason, data.iter_count));
+                false
+            }
+            None => true,
+        }
+    }
+
+    /// The user stop of a finished solve given its status and final primal variables.
+    pub(crate) fn user_stop(&mut self, status: SolveStatus, x: &[Number]) -> Option<UserStop> {
+        let stop = self.stop.take();
+        if status != SolveStatus::UserRequestedStop {
+            return None;
+        }
+        stop.map(|(reason, iteration)| UserStop {
+            reason,
+            iteration,
+            best_iterate: x.to_vec(),
+        })
+    }
+}
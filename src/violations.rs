@@ -0,0 +1,314 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Reports of bound and constraint violations at a solution.
+//!
+//! When Ipopt stops with `SolveStatus::SolvedToAcceptableLevel` or at an iteration limit, the
+//! returned point may still violate some bounds or constraints. A
+//! [`ViolationReport`](struct.ViolationReport.html) lists these violations from the largest to the
+//! smallest, which helps to decide whether the point is good enough for the application.
+//!
+//! Note that Ipopt relaxes bounds slightly (see the `bound_relax_factor` option), so tiny
+//! violations are expected even for successful solves.
+
+use crate::{
+    BasicProblem, ConstrainedProblem, ConstraintGroups, ConstraintViolation, GroupViolation,
+    Number, Solution,
+};
+
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+/// What is violated.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// A variable bound.
+    Bound,
+    /// A constraint.
+    Constraint,
+}
+
+/// A single violated bound or constraint.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+    /// Whether a variable bound or a constraint is violated.
+    pub kind: ViolationKind,
+    /// Zero-based index of the variable or constraint.
+    pub index: usize,
+    /// Name of the constraint group containing this constraint, if any.
+    pub group: Option<String>,
+    /// Value of the variable or constraint function.
+    pub value: Number,
+    /// Lower bound.
+    pub lower: Number,
+    /// Upper bound.
+    pub upper: Number,
+    /// Distance of the value to the feasible interval.
+    pub violation: Number,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let name = match self.kind {
+            ViolationKind::Bound => "x",
+            ViolationKind::Constraint => "g",
+        };
+        write!(
+            f,
+            "{}[{}] = {:e} with bounds [{:e}, {:e}], violated by {:e}",
+            name, self.index, self.value, self.lower, self.upper, self.violation
+        )?;
+        if let Some(group) = self.group.as_ref() {
+            write!(f, " ({})", group)?;
+        }
+        Ok(())
+    }
+}
+
+/// Bound and constraint violations at a point.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ViolationReport {
+    /// All violations, sorted from the largest to the smallest.
+    pub violations: Vec<Violation>,
+    /// Constraint violations aggregated by constraint group.
+    ///
+    /// This is empty if the problem doesn't define
+    /// [`constraint_groups`](trait.ConstrainedProblem.html#method.constraint_groups).
+    pub groups: Vec<GroupViolation>,
+}
+
+impl ViolationReport {
+    /// The largest violation, or zero if nothing is violated.
+    pub fn max_violation(&self) -> Number {
+        self.violations.first().map_or(0.0, |v| v.violation)
+    }
+
+    /// Returns `true` if no bound or constraint is violated by more than `tol`.
+    pub fn is_feasible(&self, tol: Number) -> bool {
+        self.max_violation() <= tol
+    }
+
+    /// Violations larger than `tol`.
+    pub fn above(&self, tol: Number) -> impl Iterator<Item = &Violation> {
+        // Violations are sorted, so stop at the first small one.
+        self.violations
+            .iter()
+            .take_while(move |v| v.violation > tol)
+    }
+
+    /// Violated variable bounds.
+    pub fn bound_violations(&self) -> impl Iterator<Item = &Violation> {
+        self.violations
+            .iter()
+            .filter(|v| v.kind == ViolationKind::Bound)
+    }
+
+    /// Violated constraints.
+    pub fn constraint_violations(&self) -> impl Iterator<Item = &Violation> {
+        self.violations
+            .iter()
+            .filter(|v| v.kind == ViolationKind::Constraint)
+    }
+
+    fn new(mut violations: Vec<Violation>, groups: &ConstraintGroups) -> Self {
+        violations.sort_by(|a, b| {
+            b.violation
+                .partial_cmp(&a.violation)
+                .unwrap_or(Ordering::Equal)
+        });
+        let constraint_violations: Vec<_> = violations
+            .iter()
+            .filter(|v| v.kind == ViolationKind::Constraint)
+            .map(|v| ConstraintViolation {
+                index: v.index,
+                group: v.group.clone(),
+                value: v.value,
+                lower: v.lower,
+                upper: v.upper,
+                violation: v.violation,
+            })
+            .collect();
+        ViolationReport {
+            groups: groups.summarize_violations(&constraint_violations),
+            violations,
+        }
+    }
+}
+
+impl Display for ViolationReport {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        if self.violations.is_empty() {
+            return writeln!(f, "No bounds or constraints are violated.");
+        }
+        writeln!(
+            f,
+            "{} violated bounds and {} violated constraints, max violation {:e}.",
+            self.bound_violations().count(),
+            self.constraint_violations().count(),
+            self.max_violation()
+        )?;
+        if !self.groups.is_empty() {
+            writeln!(f, "Violations by constraint group:")?;
+            for group in self.groups.iter() {
+                writeln!(f, "  {}", group)?;
+            }
+        }
+        writeln!(f, "Largest violations:")?;
+        for v in self.violations.iter().take(10) {
+            writeln!(f, "  {}", v)?;
+        }
+        if self.violations.len() > 10 {
+            writeln!(f, "  and {} more", self.violations.len() - 10)?;
+        }
+        Ok(())
+    }
+}
+
+/// Collect the violations of `values` with respect to the given bounds.
+fn collect_violations(
+    kind: ViolationKind,
+    values: &[Number],
+    lower: &[Number],
+    upper: &[Number],
+    groups: &ConstraintGroups,
+) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for (index, (&value, (&lower, &upper))) in values
+        .iter()
+        .zip(lower.iter().zip(upper.iter()))
+        .enumerate()
+    {
+        let violation = (lower - value).max(value - upper);
+        if violation > 0.0 {
+            violations.push(Violation {
+                kind,
+                index,
+                group: groups.group_of(index).map(String::from),
+                value,
+                lower,
+                upper,
+                violation,
+            });
+        }
+    }
+    violations
+}
+
+impl<'a> Solution<'a> {
+    /// Report the violated variable bounds at this solution.
+    ///
+    /// Constraints are ignored, which makes this function suitable for problems that don't
+    /// implement [`ConstrainedProblem`](trait.ConstrainedProblem.html).
+    ///
+    /// Returns `None` if the bounds could not be retrieved from the problem.
+    pub fn bound_violations<P: BasicProblem>(&self, problem: &P) -> Option<ViolationReport> {
+        let n = self.primal_variables.len();
+        let mut x_l = vec![0.0; n];
+        let mut x_u = vec![0.0; n];
+        if !problem.bounds(&mut x_l, &mut x_u) {
+            return None;
+        }
+        let no_groups = ConstraintGroups::new();
+        let violations = collect_violations(
+            ViolationKind::Bound,
+            self.primal_variables,
+            &x_l,
+            &x_u,
+            &no_groups,
+        );
+        Some(ViolationReport::new(violations, &no_groups))
+    }
+
+    /// Report the violated variable bounds and constraints at this solution.
+    ///
+    /// The constraint function is evaluated at the primal variables of this solution.
+    ///
+    /// Returns `None` if the bounds or the constraint values could not be retrieved from the
+    /// problem.
+    pub fn violations<P: ConstrainedProblem>(&self, problem: &P) -> Option<ViolationReport> {
+        let report = self.bound_violations(problem)?;
+
+        let m = self.constraint_multipliers.len();
+        let mut g = vec![0.0; m];
+        let mut g_l = vec![0.0; m];
+        let mut g_u = vec![0.0; m];
+        if !problem.constraint_bounds(&mut g_l, &mut g_u)
+            || !problem.constraint(self.primal_variables, &mut g)
+        {
+            return None;
+        }
+
+        let groups = problem.constraint_groups();
+        let mut violations = report.violations;
+        violations.extend(collect_violations(
+            ViolationKind::Constraint,
+            &g,
+            &g_l,
+            &g_u,
+            &groups,
+        ));
+        Some(ViolationReport::new(violations, &groups))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_test() {
+        let mut groups = ConstraintGroups::new();
+        groups.add_group("balance", vec![1]);
+        let mut violations: Vec<_> = collect_violations(
+            ViolationKind::Bound,
+            &[0.0, -1e-9, 3.0],
+            &[0.0, 0.0, 0.0],
+            &[1.0, 1.0, 2.0],
+            &ConstraintGroups::new(),
+        );
+        violations.extend(collect_violations(
+            ViolationKind::Constraint,
+            &[0.5, 2.0],
+            &[1.0, 0.0],
+            &[1e20, 0.0],
+            &groups,
+        ));
+        let report = ViolationReport::new(violations, &groups);
+
+        let order: Vec<_> = report
+            .violations
+            .iter()
+            .map(|v| (v.kind, v.index))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                (ViolationKind::Constraint, 1),
+                (ViolationKind::Bound, 2),
+                (ViolationKind::Constraint, 0),
+                (ViolationKind::Bound, 1),
+            ]
+        );
+        assert_eq!(report.max_violation(), 2.0);
+        assert_eq!(report.above(1e-6).count(), 3);
+        assert!(!report.is_feasible(1e-6));
+        assert_eq!(report.bound_violations().count(), 2);
+        assert_eq!(
+            report.violations[0].to_string(),
+            "g[1] = 2e0 with bounds [0e0, 0e0], violated by 2e0 (balance)"
+        );
+        let group_names: Vec<_> = report.groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(group_names, vec!["balance", crate::UNGROUPED]);
+    }
+}
@@ -0,0 +1,80 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+/**
+ * Minimize `(x - 2)^2 + (y + 1)^2` over the box `[0, 1] x [0, 1]`, whose solution is the corner
+ * `(1, 0)`, and minimize the Rosenbrock function given as closures.
+ */
+use approx::assert_relative_eq;
+
+use ipopt::*;
+
+struct Quadratic;
+
+impl BoundConstrainedProblem for Quadratic {
+    fn num_variables(&self) -> usize {
+        2
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) {
+        x_l.copy_from_slice(&[0.0, 0.0]);
+        x_u.copy_from_slice(&[1.0, 1.0]);
+    }
+    fn initial_point(&self, x: &mut [Number]) {
+        x.copy_from_slice(&[0.5, 0.5]);
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = (x[0] - 2.0) * (x[0] - 2.0) + (x[1] + 1.0) * (x[1] + 1.0);
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = 2.0 * (x[0] - 2.0);
+        grad_f[1] = 2.0 * (x[1] + 1.0);
+        true
+    }
+}
+
+#[test]
+fn bound_constrained_test() {
+    let mut options = OptionSet::new();
+    options.set("tol", 1e-9).set("print_level", 0);
+    let solution = solve_bound_constrained(Quadratic, &options).unwrap();
+
+    assert!(solution.succeeded());
+    assert_relative_eq!(solution.primal_variables[0], 1.0, epsilon = 1e-6);
+    assert_relative_eq!(solution.primal_variables[1], 0.0, epsilon = 1e-6);
+    assert_relative_eq!(solution.objective_value, 2.0, epsilon = 1e-6);
+    // Both variables are pushed against their bounds.
+    assert_relative_eq!(solution.upper_bound_multipliers[0], 2.0, epsilon = 1e-4);
+    assert_relative_eq!(solution.lower_bound_multipliers[1], 2.0, epsilon = 1e-4);
+    assert!(solution.stats.is_some());
+}
+
+#[test]
+fn minimize_test() {
+    let rosenbrock = |x: &[Number]| {
+        (1.0 - x[0]) * (1.0 - x[0]) + 100.0 * (x[1] - x[0] * x[0]) * (x[1] - x[0] * x[0])
+    };
+    let gradient = |x: &[Number], g: &mut [Number]| {
+        g[0] = -2.0 * (1.0 - x[0]) - 400.0 * x[0] * (x[1] - x[0] * x[0]);
+        g[1] = 200.0 * (x[1] - x[0] * x[0]);
+    };
+    let mut options = OptionSet::new();
+    options.set("tol", 1e-10).set("print_level", 0);
+    let solution = minimize(rosenbrock, gradient, &[-1.2, 1.0], &options).unwrap();
+
+    assert!(solution.succeeded());
+    assert_relative_eq!(solution.primal_variables[0], 1.0, epsilon = 1e-5);
+    assert_relative_eq!(solution.primal_variables[1], 1.0, epsilon = 1e-5);
+    assert!(solution.stats.unwrap().iteration_count > 0);
+}
@@ -0,0 +1,157 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+/**
+ * Verify that evaluations are forwarded to the problem without any Rust-side allocations.
+ *
+ * A counting global allocator records every allocation, and each callback checks that the count
+ * did not change since the first evaluation. Ipopt itself allocates through the C++ runtime, which
+ * is not counted. The problem minimizes `(x - 1)^2 + (y - 2)^2` subject to `x^2 + y^2 <= 1`.
+ */
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ipopt::*;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[derive(Default)]
+struct NLP {
+    /// Allocation count at the first evaluation.
+    first: Cell<Option<usize>>,
+    /// Allocations observed since the first evaluation.
+    allocations: Cell<usize>,
+    /// Number of evaluations.
+    evaluations: Cell<usize>,
+}
+
+impl NLP {
+    fn record(&self) {
+        let count = ALLOCATIONS.load(Ordering::SeqCst);
+        let first = self.first.get().unwrap_or(count);
+        self.first.set(Some(first));
+        self.allocations.set(count - first);
+        self.evaluations.set(self.evaluations.get() + 1);
+    }
+}
+
+impl BasicProblem for NLP {
+    fn num_variables(&self) -> usize {
+        2
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.iter_mut().for_each(|x| *x = -1e20);
+        x_u.iter_mut().for_each(|x| *x = 1e20);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.iter_mut().for_each(|x| *x = 0.0);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        self.record();
+        *obj = (x[0] - 1.0) * (x[0] - 1.0) + (x[1] - 2.0) * (x[1] - 2.0);
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        self.record();
+        grad_f[0] = 2.0 * (x[0] - 1.0);
+        grad_f[1] = 2.0 * (x[1] - 2.0);
+        true
+    }
+}
+
+impl ConstrainedProblem for NLP {
+    fn num_constraints(&self) -> usize {
+        1
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        2
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        self.record();
+        g[0] = x[0] * x[0] + x[1] * x[1];
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l[0] = -1e20;
+        g_u[0] = 1.0;
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0]);
+        cols.copy_from_slice(&[0, 1]);
+        true
+    }
+    fn constraint_jacobian_values(&self, x: &[Number], vals: &mut [Number]) -> bool {
+        self.record();
+        vals[0] = 2.0 * x[0];
+        vals[1] = 2.0 * x[1];
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        2
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 1]);
+        cols.copy_from_slice(&[0, 1]);
+        true
+    }
+    fn hessian_values(
+        &self,
+        _x: &[Number],
+        obj_factor: Number,
+        lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        self.record();
+        vals[0] = 2.0 * obj_factor + 2.0 * lambda[0];
+        vals[1] = 2.0 * obj_factor + 2.0 * lambda[0];
+        true
+    }
+}
+
+#[test]
+fn callback_allocations_test() {
+    let mut ipopt = Ipopt::new(NLP::default()).unwrap();
+    ipopt.set_option("sb", "yes"); // suppress license message
+    ipopt.set_option("print_level", 0); // suppress debug output
+
+    let status = ipopt.solve().status;
+    assert_eq!(status, SolveStatus::SolveSucceeded);
+
+    let nlp = ipopt.solver_data().problem;
+    assert!(nlp.evaluations.get() > 10);
+    assert_eq!(nlp.allocations.get(), 0);
+}
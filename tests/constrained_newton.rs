@@ -262,6 +262,7 @@ fn hs071() -> Ipopt<NLP> {
 fn all() {
     hs071_user_interrupt_test();
     hs071_warm_start_test();
+    hs071_reoptimize_test();
     hs071_custom_scaling_test();
 }
 
@@ -310,6 +311,44 @@ fn hs071_user_interrupt_test() {
     assert_relative_eq!(obj, 1.701402e+01, max_relative = 1e-6);
 }
 
+fn hs071_reoptimize_test() {
+    let mut ipopt = hs071();
+    {
+        let SolveResult {
+            solver_data: SolverDataMut { problem, .. },
+            status,
+            ..
+        } = ipopt.solve();
+        assert_eq!(status, SolveStatus::SolveSucceeded);
+        problem.g_offset[0] = 0.2;
+    }
+
+    // Only the constraint function changed, so the structure of the previous solve can be reused.
+    let SolveResult {
+        solver_data: SolverDataMut { solution, .. },
+        status,
+        objective_value: obj,
+        ..
+    } = ipopt.reoptimize();
+
+    let x = solution.primal_variables;
+    assert_eq!(status, SolveStatus::SolveSucceeded);
+    assert_relative_eq!(x[0], 1.000000e+00, max_relative = 1e-6);
+    assert_relative_eq!(x[1], 4.749269e+00, max_relative = 1e-6);
+    assert_relative_eq!(x[2], 3.817510e+00, max_relative = 1e-6);
+    assert_relative_eq!(x[3], 1.367870e+00, max_relative = 1e-6);
+    assert_relative_eq!(obj, 1.690362e+01, max_relative = 1e-6);
+
+    // Reoptimizing enables same structure warm starts only for that solve.
+    let options = ipopt.effective_options().unwrap();
+    let warm_start = options
+        .iter()
+        .find(|opt| opt.name == "warm_start_same_structure")
+        .unwrap();
+    assert_eq!(warm_start.value, OptionValue::from("no"));
+    assert_eq!(warm_start.source, OptionSource::Default);
+}
+
 fn hs071_warm_start_test() {
     let mut ipopt = hs071();
     ipopt.set_intermediate_callback(Some(NLP::count_iterations_cb));
@@ -0,0 +1,92 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+/**
+ * Solve the same problem twice in deterministic mode and check that both solves evaluate the
+ * problem at the same points and end with the same solution, bit for bit.
+ */
+use ipopt::*;
+
+/// The bits of a slice of numbers.
+fn bits(values: &[Number]) -> Vec<u64> {
+    values.iter().map(|v| v.to_bits()).collect()
+}
+
+struct Run {
+    /// The recorded callbacks, which include every point the problem was evaluated at.
+    trace: Vec<u8>,
+    iteration_count: Index,
+    iterations: Vec<(Index, u64, u64, u64)>,
+    solution: Vec<Vec<u64>>,
+}
+
+fn run() -> Run {
+    let problem = Recorder::new(RandomProblem::new(40, 25, 7));
+    let mut ipopt = Ipopt::new(problem).unwrap();
+    ipopt.set_option("sb", "yes"); // suppress license message
+    ipopt.set_option("print_level", 0); // suppress debug output
+    ipopt.enable_deterministic_mode();
+    ipopt.set_history_recording(true);
+
+    let status = ipopt.solve().status;
+    assert_eq!(status, SolveStatus::SolveSucceeded);
+
+    let iterations = ipopt
+        .history()
+        .unwrap()
+        .iterations()
+        .iter()
+        .map(|data| {
+            (
+                data.iter_count,
+                data.obj_value.to_bits(),
+                data.inf_pr.to_bits(),
+                data.inf_du.to_bits(),
+            )
+        })
+        .collect();
+    let iteration_count = ipopt.solve_stats().unwrap().iteration_count;
+
+    let SolverData {
+        problem, solution, ..
+    } = ipopt.solver_data();
+    let mut trace = Vec::new();
+    problem.trace().write_to(&mut trace).unwrap();
+    let solution = vec![
+        bits(solution.primal_variables),
+        bits(solution.lower_bound_multipliers),
+        bits(solution.upper_bound_multipliers),
+        bits(solution.constraint_multipliers),
+    ];
+
+    Run {
+        trace,
+        iteration_count,
+        iterations,
+        solution,
+    }
+}
+
+#[test]
+fn deterministic_test() {
+    let first = run();
+    let second = run();
+    assert_eq!(first.iteration_count, second.iteration_count);
+    assert_eq!(first.iterations, second.iterations);
+    assert_eq!(first.solution, second.solution);
+    assert!(
+        first.trace == second.trace,
+        "the solves took different paths"
+    );
+}
@@ -0,0 +1,131 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+/**
+ * Solve randomly generated problems of many sizes with different indexing styles and option
+ * combinations, checking that everything Ipopt reports back has the right size and is
+ * consistent with the problem.
+ *
+ * The number of seeds per size defaults to 2 and can be raised with the `IPOPT_FUZZ_SEEDS`
+ * environment variable for longer runs.
+ */
+use ipopt::*;
+
+const SIZES: [(usize, usize); 6] = [(1, 0), (1, 1), (3, 2), (10, 10), (50, 20), (200, 120)];
+
+const OPTIONS: [&[(&str, &str)]; 4] = [
+    &[],
+    &[("hessian_approximation", "limited-memory")],
+    &[("mu_strategy", "adaptive"), ("nlp_scaling_method", "none")],
+    &[
+        ("fixed_variable_treatment", "relax_bounds"),
+        ("bound_push", "1e-8"),
+    ],
+];
+
+fn num_seeds() -> u64 {
+    match std::env::var("IPOPT_FUZZ_SEEDS") {
+        Ok(seeds) => seeds.parse().expect("IPOPT_FUZZ_SEEDS must be a number"),
+        Err(_) => 2,
+    }
+}
+
+fn solve_and_check(problem: &RandomProblem, options: &[(&str, &str)], context: &str) {
+    let mut ipopt = Ipopt::new(problem.clone()).unwrap();
+    ipopt.set_option("tol", 1e-8);
+    ipopt.set_option("max_iter", 500);
+    ipopt.set_option("sb", "yes"); // suppress license message
+    ipopt.set_option("print_level", 0); // suppress debug output
+    for &(name, value) in options {
+        let set = match value.parse::<f64>() {
+            Ok(value) => ipopt.set_option(name, value),
+            Err(_) => ipopt.set_option(name, value),
+        };
+        assert!(set.is_some(), "{}: invalid option {}", context, name);
+    }
+
+    let n = problem.num_variables();
+    let m = problem.num_constraints();
+    let SolveResult {
+        solver_data: SolverDataMut { solution, .. },
+        constraint_values,
+        objective_value,
+        status,
+        ..
+    } = ipopt.solve();
+
+    assert!(
+        status == SolveStatus::SolveSucceeded || status == SolveStatus::SolvedToAcceptableLevel,
+        "{}: {:?}",
+        context,
+        status
+    );
+    assert_eq!(solution.primal_variables.len(), n, "{}", context);
+    assert_eq!(solution.lower_bound_multipliers.len(), n, "{}", context);
+    assert_eq!(solution.upper_bound_multipliers.len(), n, "{}", context);
+    assert_eq!(solution.constraint_multipliers.len(), m, "{}", context);
+    assert_eq!(constraint_values.len(), m, "{}", context);
+
+    // The reported values are those of the reported solution.
+    let x = solution.primal_variables;
+    let mut obj = 0.0;
+    assert!(problem.objective(x, &mut obj));
+    assert!(
+        (obj - objective_value).abs() <= 1e-9 * (1.0 + obj.abs()),
+        "{}",
+        context
+    );
+    let mut g = vec![0.0; m];
+    assert!(problem.constraint(x, &mut g));
+    let (mut g_l, mut g_u) = (vec![0.0; m], vec![0.0; m]);
+    assert!(problem.constraint_bounds(&mut g_l, &mut g_u));
+    for i in 0..m {
+        assert!((g[i] - constraint_values[i]).abs() <= 1e-9 * (1.0 + g[i].abs()));
+        assert!(
+            g_l[i] - 1e-6 <= g[i] && g[i] <= g_u[i] + 1e-6,
+            "{}",
+            context
+        );
+    }
+    let (mut x_l, mut x_u) = (vec![0.0; n], vec![0.0; n]);
+    assert!(problem.bounds(&mut x_l, &mut x_u));
+    for i in 0..n {
+        assert!(
+            x_l[i] - 1e-6 <= x[i] && x[i] <= x_u[i] + 1e-6,
+            "{}",
+            context
+        );
+        assert!(solution.lower_bound_multipliers[i] >= 0.0, "{}", context);
+        assert!(solution.upper_bound_multipliers[i] >= 0.0, "{}", context);
+    }
+}
+
+#[test]
+fn fuzz_test() {
+    for seed in 0..num_seeds() {
+        for &(n, m) in SIZES.iter() {
+            for style in [IndexingStyle::CStyle, IndexingStyle::FortranStyle].iter() {
+                for (k, options) in OPTIONS.iter().enumerate() {
+                    let mut problem = RandomProblem::new(n, m, seed);
+                    problem.with_indexing_style(*style);
+                    let context = format!(
+                        "seed {}, {} variables, {} constraints, {:?}, options {}",
+                        seed, n, m, style, k
+                    );
+                    solve_and_check(&problem, options, &context);
+                }
+            }
+        }
+    }
+}
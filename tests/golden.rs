@@ -0,0 +1,29 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+#![cfg(feature = "hs")]
+
+use ipopt::hs::*;
+use ipopt::*;
+
+#[test]
+fn hs071_golden_test() {
+    let mut ipopt = Ipopt::new(Hs071).unwrap();
+    ipopt.set_option("tol", 1e-9);
+    ipopt.set_option("sb", "yes"); // suppress license message
+    ipopt.set_option("print_level", 0); // suppress debug output
+
+    let golden = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/hs071.golden");
+    assert_golden(&mut ipopt, golden, GoldenTolerance::default());
+}
@@ -0,0 +1,66 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+#![cfg(feature = "hs")]
+
+use approx::assert_relative_eq;
+
+use ipopt::hs::*;
+use ipopt::*;
+
+/// Problems with degenerate optima.
+const DEGENERATE: [&str; 2] = ["HS013", "HS049"];
+
+// NOTE: All problems are solved from a single test since MUMPS is not safe to run in parallel.
+// See constrained_newton.rs for details.
+#[test]
+fn all() {
+    for problem in HsProblem::all() {
+        let name = problem.name();
+        let f_opt = problem.optimal_objective();
+        let x_opt = problem.optimal_solution();
+
+        let mut ipopt = Ipopt::new(problem).unwrap();
+        ipopt.set_option("tol", 1e-9);
+        ipopt.set_option("sb", "yes"); // suppress license message
+        ipopt.set_option("print_level", 0); // suppress debug output
+
+        let SolveResult {
+            solver_data: SolverDataMut { solution, .. },
+            status,
+            objective_value: obj,
+            ..
+        } = ipopt.solve();
+
+        // Ipopt converges slowly to the optima of HS013, where no constraint qualification
+        // holds, and HS049, whose objective is flat to fourth order, so it stops further away.
+        let scale = if DEGENERATE.contains(&name) { 1e4 } else { 1.0 };
+
+        assert_eq!(status, SolveStatus::SolveSucceeded, "{}", name);
+        assert_relative_eq!(
+            obj,
+            f_opt,
+            epsilon = 1e-7 * scale,
+            max_relative = 1e-6 * scale
+        );
+        for (&x, &x_opt) in solution.primal_variables.iter().zip(x_opt.iter()) {
+            assert_relative_eq!(
+                x,
+                x_opt,
+                epsilon = 1e-5 * scale,
+                max_relative = 1e-5 * scale
+            );
+        }
+    }
+}
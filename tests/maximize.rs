@@ -0,0 +1,115 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+/**
+ * Maximize the concave quadratic `-(x - 1)^2 - (y - 2)^2` subject to `x + y <= 2`, whose
+ * solution is the projection of `(1, 2)` onto the constraint.
+ */
+use approx::assert_relative_eq;
+
+use ipopt::*;
+
+struct NLP;
+
+impl BasicProblem for NLP {
+    fn num_variables(&self) -> usize {
+        2
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.swap_with_slice(vec![-1e20; 2].as_mut_slice());
+        x_u.swap_with_slice(vec![1e20; 2].as_mut_slice());
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.swap_with_slice(vec![0.0, 0.0].as_mut_slice());
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = -(x[0] - 1.0) * (x[0] - 1.0) - (x[1] - 2.0) * (x[1] - 2.0);
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = -2.0 * (x[0] - 1.0);
+        grad_f[1] = -2.0 * (x[1] - 2.0);
+        true
+    }
+}
+
+impl ConstrainedProblem for NLP {
+    fn num_constraints(&self) -> usize {
+        1
+    }
+    fn num_constraint_jacobian_non_zeros(&self) -> usize {
+        2
+    }
+    fn constraint(&self, x: &[Number], g: &mut [Number]) -> bool {
+        g[0] = x[0] + x[1];
+        true
+    }
+    fn constraint_bounds(&self, g_l: &mut [Number], g_u: &mut [Number]) -> bool {
+        g_l[0] = -1e20;
+        g_u[0] = 2.0;
+        true
+    }
+    fn constraint_jacobian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 0]);
+        cols.copy_from_slice(&[0, 1]);
+        true
+    }
+    fn constraint_jacobian_values(&self, _x: &[Number], vals: &mut [Number]) -> bool {
+        vals.copy_from_slice(&[1.0, 1.0]);
+        true
+    }
+    fn num_hessian_non_zeros(&self) -> usize {
+        2
+    }
+    fn hessian_indices(&self, rows: &mut [Index], cols: &mut [Index]) -> bool {
+        rows.copy_from_slice(&[0, 1]);
+        cols.copy_from_slice(&[0, 1]);
+        true
+    }
+    fn hessian_values(
+        &self,
+        _x: &[Number],
+        obj_factor: Number,
+        _lambda: &[Number],
+        vals: &mut [Number],
+    ) -> bool {
+        vals[0] = -2.0 * obj_factor;
+        vals[1] = -2.0 * obj_factor;
+        true
+    }
+}
+
+#[test]
+fn maximize_test() {
+    let mut ipopt = Ipopt::new(NLP).unwrap();
+    ipopt.set_option("tol", 1e-9);
+    ipopt.set_option("sb", "yes"); // suppress license message
+    ipopt.set_option("print_level", 0); // suppress debug output
+    ipopt.set_objective_sense(ObjectiveSense::Maximize);
+
+    let SolveResult {
+        solver_data: SolverDataMut { solution, .. },
+        objective_value: obj,
+        status,
+        ..
+    } = ipopt.solve();
+
+    let x = solution.primal_variables;
+    assert_eq!(status, SolveStatus::SolveSucceeded);
+    assert_relative_eq!(x[0], 0.5, epsilon = 1e-7);
+    assert_relative_eq!(x[1], 1.5, epsilon = 1e-7);
+    assert_relative_eq!(obj, -0.5, epsilon = 1e-7);
+}
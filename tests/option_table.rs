@@ -0,0 +1,52 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+/**
+ * Check that the table behind the `option!` macro covers every option registered by the linked
+ * Ipopt library with the right type. Regenerate it with
+ * `cargo run --example generate_option_table > src/option_table.rs` if this fails.
+ */
+use ipopt::{OptionRegistry, OptionType};
+use std::collections::HashMap;
+
+/// Map each option in the table to the name of its constructor.
+fn table() -> HashMap<&'static str, &'static str> {
+    include_str!("../src/option_table.rs")
+        .lines()
+        .filter_map(|line| {
+            let call = line.trim().strip_prefix("$crate::")?;
+            let (constructor, args) = call.split_at(call.find('(')?);
+            Some((args.split('"').nth(1)?, constructor))
+        })
+        .collect()
+}
+
+#[test]
+fn option_table_test() {
+    let table = table();
+    assert_eq!(table.get("tol"), Some(&"__num_option"));
+    for info in OptionRegistry::linked().iter() {
+        let constructor = match info.option_type {
+            OptionType::Number => "__num_option",
+            OptionType::Integer => "__int_option",
+            OptionType::String => "__str_option",
+        };
+        assert_eq!(
+            table.get(info.name.as_str()),
+            Some(&constructor),
+            "option {}",
+            info.name
+        );
+    }
+}
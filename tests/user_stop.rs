@@ -0,0 +1,93 @@
+//   Copyright 2020 Egor Larionov
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+/**
+ * Stop the minimization of the Rosenbrock function after a few iterations, once with a reason
+ * from a stop callback and once through the intermediate callback.
+ */
+use ipopt::*;
+
+struct Rosenbrock;
+
+impl BasicProblem for Rosenbrock {
+    fn num_variables(&self) -> usize {
+        2
+    }
+    fn bounds(&self, x_l: &mut [Number], x_u: &mut [Number]) -> bool {
+        x_l.iter_mut().for_each(|x| *x = -1e20);
+        x_u.iter_mut().for_each(|x| *x = 1e20);
+        true
+    }
+    fn initial_point(&self, x: &mut [Number]) -> bool {
+        x.copy_from_slice(&[-1.2, 1.0]);
+        true
+    }
+    fn objective(&self, x: &[Number], obj: &mut Number) -> bool {
+        *obj = (1.0 - x[0]).powi(2) + 100.0 * (x[1] - x[0] * x[0]).powi(2);
+        true
+    }
+    fn objective_grad(&self, x: &[Number], grad_f: &mut [Number]) -> bool {
+        grad_f[0] = -2.0 * (1.0 - x[0]) - 400.0 * x[0] * (x[1] - x[0] * x[0]);
+        grad_f[1] = 200.0 * (x[1] - x[0] * x[0]);
+        true
+    }
+}
+
+fn solver() -> Ipopt<Rosenbrock> {
+    let mut ipopt = Ipopt::new_unconstrained(Rosenbrock).unwrap();
+    ipopt.set_option("sb", "yes"); // suppress license message
+    ipopt.set_option("print_level", 0); // suppress debug output
+    ipopt
+}
+
+#[test]
+fn stop_callback_test() {
+    let mut ipopt = solver();
+    ipopt.set_stop_callback(Some(|_, data| {
+        if data.iter_count == 3 {
+            Some(StopReason::Timeout)
+        } else {
+            None
+        }
+    }));
+
+    let SolveResult {
+        solver_data: SolverDataMut { solution, .. },
+        status,
+        user_stop,
+        ..
+    } = ipopt.solve();
+
+    assert_eq!(status, SolveStatus::UserRequestedStop);
+    let user_stop = user_stop.unwrap();
+    assert_eq!(user_stop.reason, StopReason::Timeout);
+    assert_eq!(user_stop.iteration, 3);
+    assert_eq!(user_stop.best_iterate.as_slice(), solution.primal_variables);
+}
+
+#[test]
+fn intermediate_callback_stop_test() {
+    let mut ipopt = solver();
+    ipopt.set_intermediate_callback(Some(|_, data| data.iter_count < 2));
+
+    let result = ipopt.solve();
+    assert_eq!(result.status, SolveStatus::UserRequestedStop);
+    let user_stop = result.user_stop.unwrap();
+    assert_eq!(user_stop.reason, StopReason::Unspecified);
+    assert_eq!(user_stop.iteration, 2);
+
+    // The reason does not carry over to a solve that is not stopped.
+    ipopt.set_intermediate_callback(None);
+    assert_eq!(ipopt.solve().user_stop, None);
+}